@@ -0,0 +1,13 @@
+//! Fuzz target for running arbitrary code under arbitrary resource limits.
+//!
+//! Limit checks sit on many code paths, so hitting each limit at every possible point
+//! must raise an exception rather than panic or leave the heap inconsistent. The generated
+//! limits are always bounded in time and memory.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use monty::{ResourceLimits, fuzz::fuzz_run_with_limits};
+
+fuzz_target!(|input: (String, ResourceLimits)| {
+    fuzz_run_with_limits(&input.0, &input.1);
+});
@@ -0,0 +1,13 @@
+//! Fuzz target for loading tampered snapshots and compiled code.
+//!
+//! Hosts that store snapshots where users can modify them must not panic when loading or
+//! resuming them. The payload is framed with a valid header so the fuzzer exercises the
+//! decoder and the snapshot validation rather than the checksum.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use monty::fuzz::{FramedPayload, fuzz_snapshot_load};
+
+fuzz_target!(|input: FramedPayload| {
+    fuzz_snapshot_load(&input.to_bytes());
+});
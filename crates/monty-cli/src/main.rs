@@ -7,7 +7,7 @@ use std::{
 
 use clap::Parser;
 use monty::{
-    MontyObject, MontyRepl, MontyRun, NoLimitTracker, PrintWriter, ReplContinuationMode, RunProgress,
+    Exit, MontyObject, MontyRepl, MontyRun, NoLimitTracker, PrintWriter, ReplContinuationMode, RunProgress,
     detect_repl_continuation_mode,
 };
 // disabled due to format failing on https://github.com/pydantic/monty/pull/75 where CI and local wanted imports ordered differently
@@ -75,8 +75,8 @@ fn main() -> ExitCode {
 /// execution or through the suspendable progress loop when external functions
 /// are enabled.
 ///
-/// Returns `ExitCode::SUCCESS` for successful execution and
-/// `ExitCode::FAILURE` for parse/type/runtime failures.
+/// Returns `ExitCode::SUCCESS` for successful execution, the script's status if it
+/// called `sys.exit()`, and `ExitCode::FAILURE` for parse/type/runtime failures.
 fn run_script(file_path: &str, code: String) -> ExitCode {
     let start = Instant::now();
     if let Some(failure) = type_check(&SourceFile::new(&code, file_path), None).unwrap() {
@@ -124,8 +124,8 @@ fn run_script(file_path: &str, code: String) -> ExitCode {
         }
     } else {
         let start = Instant::now();
-        let value = match runner.run_no_limits(inputs) {
-            Ok(p) => p,
+        let exit = match Exit::from_result(runner.run_no_limits(inputs)) {
+            Ok(exit) => exit,
             Err(err) => {
                 let elapsed = start.elapsed();
                 eprintln!("error after: {elapsed:?}\n{err}");
@@ -133,8 +133,13 @@ fn run_script(file_path: &str, code: String) -> ExitCode {
             }
         };
         let elapsed = start.elapsed();
-        eprintln!("success after: {elapsed:?}\n{value}");
-        ExitCode::SUCCESS
+        match (&exit, exit.message()) {
+            (Exit::Complete(value), _) => eprintln!("success after: {elapsed:?}\n{value}"),
+            (Exit::SystemExit(_), Some(message)) => eprintln!("{message}\nexited after: {elapsed:?}"),
+            (Exit::SystemExit(_), None) => eprintln!("exited after: {elapsed:?}"),
+        }
+        // process exit statuses are a single byte on unix, like `status & 0xff` in CPython
+        ExitCode::from(exit.status().to_le_bytes()[0])
     }
 }
 
@@ -258,7 +263,7 @@ fn execute_repl_snippet(repl: &mut MontyRepl<NoLimitTracker>, snippet: &str) {
 ///
 /// This repeatedly resumes `RunProgress` values by resolving supported
 /// external calls and returns the final value when execution reaches
-/// `RunProgress::Complete`.
+/// `RunProgress::Complete`. Values streamed with `emit()` are printed as they arrive.
 ///
 /// Returns an error string for unsupported suspend points (OS calls or async
 /// futures) or invalid external-function dispatch.
@@ -286,6 +291,24 @@ fn run_until_complete(mut progress: RunProgress<NoLimitTracker>) -> Result<Monty
             RunProgress::OsCall { function, args, .. } => {
                 return Err(format!("OS calls not supported in CLI: {function:?}({args:?})"));
             }
+            RunProgress::Emit { value, state } => {
+                println!("emit: {value}");
+                progress = state
+                    .run(MontyObject::None, &mut PrintWriter::Stdout)
+                    .map_err(|err| format!("{err}"))?;
+            }
+            RunProgress::Sleep { duration, state } => {
+                std::thread::sleep(duration);
+                progress = state
+                    .run(MontyObject::None, &mut PrintWriter::Stdout)
+                    .map_err(|err| format!("{err}"))?;
+            }
+            RunProgress::Paused(state) => {
+                progress = state.run(&mut PrintWriter::Stdout).map_err(|err| format!("{err}"))?;
+            }
+            RunProgress::Breakpoint(state) => {
+                progress = state.resume(&mut PrintWriter::Stdout).map_err(|err| format!("{err}"))?;
+            }
         }
     }
 }
@@ -47,7 +47,7 @@ use std::borrow::Cow;
 
 use monty::{
     ExcType, ExternalResult, LimitedTracker, MontyException, MontyObject, MontyRepl as CoreMontyRepl, MontyRun,
-    NoLimitTracker, PrintWriter, PrintWriterCallback, ResourceTracker, RunProgress, Snapshot,
+    NoLimitTracker, PrintWriter, PrintWriterCallback, ResourceTracker, RunProgress, Snapshot, SnapshotError,
 };
 use monty_type_checking::{type_check, SourceFile};
 use napi::bindgen_prelude::*;
@@ -282,6 +282,20 @@ impl Monty {
                                 "OS calls are not supported: {function:?}",
                             )));
                         }
+                        RunProgress::Paused(_) => {
+                            return Err(Error::from_reason(
+                                "Fuel-limited execution is not supported in synchronous run().",
+                            ));
+                        }
+                        RunProgress::Breakpoint(_) => {
+                            return Err(Error::from_reason("Breakpoints are not supported in synchronous run()."));
+                        }
+                        RunProgress::Emit { .. } => {
+                            return Err(Error::from_reason("emit() is not supported in synchronous run()."));
+                        }
+                        RunProgress::Sleep { .. } => {
+                            return Err(Error::from_reason("time.sleep() is not supported in synchronous run()."));
+                        }
                     }
                 }
             }};
@@ -352,27 +366,28 @@ impl Monty {
     #[napi]
     pub fn dump(&self) -> Result<Buffer> {
         let serialized = SerializedMonty {
-            runner: self.runner.clone(),
+            code: self.runner.dump_code().map_err(dump_error)?,
             script_name: self.script_name.clone(),
             input_names: self.input_names.clone(),
             external_function_names: self.external_function_names.clone(),
         };
-        let bytes =
-            postcard::to_allocvec(&serialized).map_err(|e| Error::from_reason(format!("Serialization failed: {e}")))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(Buffer::from(bytes))
     }
 
     /// Deserializes a Monty instance from binary format.
     ///
+    /// Fails if the data is not `dump()` output, is corrupted, or was written by another
+    /// Monty version.
+    ///
     /// @param data - The serialized Monty data from `dump()`
     /// @returns A new Monty instance
     #[napi(factory)]
     pub fn load(data: Buffer) -> Result<Self> {
-        let serialized: SerializedMonty =
-            postcard::from_bytes(&data).map_err(|e| Error::from_reason(format!("Deserialization failed: {e}")))?;
+        let serialized: SerializedMonty = postcard::from_bytes(&data).map_err(envelope_error)?;
 
         Ok(Self {
-            runner: serialized.runner,
+            runner: MontyRun::load_code(&serialized.code).map_err(load_error)?,
             script_name: serialized.script_name,
             input_names: serialized.input_names,
             external_function_names: serialized.external_function_names,
@@ -449,12 +464,28 @@ fn run_type_check_result(code: &str, script_name: &str, prefix_code: Option<&str
 ///
 /// `napi` classes cannot be generic, so this enum stores REPL sessions for both
 /// resource tracker variants.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug)]
 enum EitherRepl {
     NoLimit(CoreMontyRepl<NoLimitTracker>),
     Limited(CoreMontyRepl<LimitedTracker>),
 }
 
+impl EitherRepl {
+    fn dump(&self) -> Result<DumpedState> {
+        match self {
+            Self::NoLimit(repl) => Ok(DumpedState::NoLimit(repl.dump().map_err(dump_error)?)),
+            Self::Limited(repl) => Ok(DumpedState::Limited(repl.dump().map_err(dump_error)?)),
+        }
+    }
+
+    fn load(state: &DumpedState) -> Result<Self> {
+        match state {
+            DumpedState::NoLimit(bytes) => Ok(Self::NoLimit(CoreMontyRepl::load(bytes).map_err(load_error)?)),
+            DumpedState::Limited(bytes) => Ok(Self::Limited(CoreMontyRepl::load(bytes).map_err(load_error)?)),
+        }
+    }
+}
+
 /// Stateful no-replay REPL session.
 ///
 /// Each call to `feed()` compiles and executes only the provided snippet against
@@ -574,21 +605,22 @@ impl MontyRepl {
     #[napi]
     pub fn dump(&self) -> Result<Buffer> {
         let serialized = SerializedRepl {
-            repl: &self.repl,
+            repl: self.repl.dump()?,
             script_name: &self.script_name,
         };
-        let bytes =
-            postcard::to_allocvec(&serialized).map_err(|e| Error::from_reason(format!("Serialization failed: {e}")))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(Buffer::from(bytes))
     }
 
     /// Restores a REPL session from bytes produced by `dump()`.
+    ///
+    /// Fails if the data is not `dump()` output, is corrupted, or was written by another
+    /// Monty version.
     #[napi(factory)]
     pub fn load(data: Buffer) -> Result<Self> {
-        let serialized: SerializedReplOwned =
-            postcard::from_bytes(&data).map_err(|e| Error::from_reason(format!("Deserialization failed: {e}")))?;
+        let serialized: SerializedReplOwned = postcard::from_bytes(&data).map_err(envelope_error)?;
         Ok(Self {
-            repl: serialized.repl,
+            repl: EitherRepl::load(&serialized.repl)?,
             script_name: serialized.script_name,
         })
     }
@@ -674,7 +706,7 @@ fn extract_input_values_in_order(
 ///
 /// Used internally by `MontySnapshot` to store execution state.
 /// The `Done` variant indicates the snapshot has been consumed.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug)]
 enum EitherSnapshot {
     NoLimit(Snapshot<NoLimitTracker>),
     Limited(Snapshot<LimitedTracker>),
@@ -683,6 +715,23 @@ enum EitherSnapshot {
     Done,
 }
 
+impl EitherSnapshot {
+    fn dump(&self) -> Result<DumpedState> {
+        match self {
+            Self::NoLimit(snapshot) => Ok(DumpedState::NoLimit(snapshot.dump().map_err(dump_error)?)),
+            Self::Limited(snapshot) => Ok(DumpedState::Limited(snapshot.dump().map_err(dump_error)?)),
+            Self::Done => Err(Error::from_reason("Cannot dump snapshot that has already been resumed")),
+        }
+    }
+
+    fn load(state: &DumpedState) -> Result<Self> {
+        match state {
+            DumpedState::NoLimit(bytes) => Ok(Self::NoLimit(Snapshot::load(bytes).map_err(load_error)?)),
+            DumpedState::Limited(bytes) => Ok(Self::Limited(Snapshot::load(bytes).map_err(load_error)?)),
+        }
+    }
+}
+
 // =============================================================================
 // MontySnapshot - Paused execution at an external function call
 // =============================================================================
@@ -848,35 +897,32 @@ impl MontySnapshot {
     /// @returns Buffer containing the serialized snapshot
     #[napi]
     pub fn dump(&self) -> Result<Buffer> {
-        if matches!(self.snapshot, EitherSnapshot::Done) {
-            return Err(Error::from_reason("Cannot dump snapshot that has already been resumed"));
-        }
-
         let serialized = SerializedSnapshot {
-            snapshot: &self.snapshot,
+            snapshot: self.snapshot.dump()?,
             script_name: &self.script_name,
             function_name: &self.function_name,
             args: &self.args,
             kwargs: &self.kwargs,
         };
 
-        let bytes =
-            postcard::to_allocvec(&serialized).map_err(|e| Error::from_reason(format!("Serialization failed: {e}")))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(Buffer::from(bytes))
     }
 
     /// Deserializes a MontySnapshot from binary format.
     ///
+    /// Fails if the data is not `dump()` output, is corrupted, or was written by another
+    /// Monty version.
+    ///
     /// @param data - The serialized snapshot data from `dump()`
     /// @param options - Optional load options (reserved for future use)
     /// @returns A new MontySnapshot instance
     #[napi(factory)]
     pub fn load(data: Buffer, options: Option<SnapshotLoadOptions>) -> Result<Self> {
-        let serialized: SerializedSnapshotOwned =
-            postcard::from_bytes(&data).map_err(|e| Error::from_reason(format!("Deserialization failed: {e}")))?;
+        let serialized: SerializedSnapshotOwned = postcard::from_bytes(&data).map_err(envelope_error)?;
 
         Ok(Self {
-            snapshot: serialized.snapshot,
+            snapshot: EitherSnapshot::load(&serialized.snapshot)?,
             script_name: serialized.script_name,
             function_name: serialized.function_name,
             args: serialized.args,
@@ -1009,6 +1055,18 @@ where
         RunProgress::OsCall { function, .. } => {
             panic!("OS calls are not yet supported in the JS bindings: {function:?}")
         }
+        RunProgress::Paused(_) => {
+            panic!("Fuel-limited execution (Paused) is not yet supported in the JS bindings")
+        }
+        RunProgress::Breakpoint(_) => {
+            panic!("Breakpoints are not yet supported in the JS bindings")
+        }
+        RunProgress::Emit { .. } => {
+            panic!("Streaming with emit() is not yet supported in the JS bindings")
+        }
+        RunProgress::Sleep { .. } => {
+            panic!("time.sleep() is not yet supported in the JS bindings")
+        }
     }
 }
 
@@ -1043,7 +1101,8 @@ fn string_to_exc_type(type_name: &str) -> Result<ExcType> {
 /// Serialization wrapper for `Monty` that includes all fields needed for reconstruction.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct SerializedMonty {
-    runner: MontyRun,
+    /// The runner as written by `MontyRun::dump_code()`.
+    code: Vec<u8>,
     script_name: String,
     input_names: Vec<String>,
     external_function_names: Vec<String>,
@@ -1052,21 +1111,21 @@ struct SerializedMonty {
 /// Serialization wrapper for `MontyRepl` using borrowed references.
 #[derive(serde::Serialize)]
 struct SerializedRepl<'a> {
-    repl: &'a EitherRepl,
+    repl: DumpedState,
     script_name: &'a str,
 }
 
 /// Owned version of `SerializedRepl` for deserialization.
 #[derive(serde::Deserialize)]
 struct SerializedReplOwned {
-    repl: EitherRepl,
+    repl: DumpedState,
     script_name: String,
 }
 
 /// Serialization wrapper for `MontySnapshot` using borrowed references.
 #[derive(serde::Serialize)]
 struct SerializedSnapshot<'a> {
-    snapshot: &'a EitherSnapshot,
+    snapshot: DumpedState,
     script_name: &'a str,
     function_name: &'a str,
     args: &'a [MontyObject],
@@ -1076,13 +1135,37 @@ struct SerializedSnapshot<'a> {
 /// Owned version of `SerializedSnapshot` for deserialization.
 #[derive(serde::Deserialize)]
 struct SerializedSnapshotOwned {
-    snapshot: EitherSnapshot,
+    snapshot: DumpedState,
     script_name: String,
     function_name: String,
     args: Vec<MontyObject>,
     kwargs: Vec<(MontyObject, MontyObject)>,
 }
 
+/// Core state written by its own `dump()`, with the versioned header and checks on load that
+/// brings, tagged with the kind of tracker it runs under.
+#[derive(serde::Serialize, serde::Deserialize)]
+enum DumpedState {
+    NoLimit(Vec<u8>),
+    Limited(Vec<u8>),
+}
+
+/// Converts a failure to serialize state into a JS error.
+fn dump_error(err: postcard::Error) -> Error {
+    Error::from_reason(format!("Serialization failed: {err}"))
+}
+
+/// Converts a failure to decode the wrapper around dumped state into a JS error.
+fn envelope_error(err: postcard::Error) -> Error {
+    load_error(SnapshotError::Postcard(err))
+}
+
+/// Converts a failure to load dumped state into a JS error saying what was wrong with the
+/// bytes: written by another Monty version, corrupted, or not a dump at all.
+fn load_error(err: SnapshotError) -> Error {
+    Error::from_reason(format!("Deserialization failed: {err}"))
+}
+
 // =============================================================================
 // External function support
 // =============================================================================
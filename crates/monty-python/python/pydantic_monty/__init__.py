@@ -188,6 +188,9 @@ class ResourceLimits(TypedDict, total=False):
     max_recursion_depth: int
     """Maximum function call stack depth (default: 1000)."""
 
+    hide_resources: bool
+    """Hide the remaining budget from code, making `resources()` raise `NameError`."""
+
 
 class ExternalReturnValue(TypedDict):
     return_value: Any
@@ -230,6 +230,15 @@ class MontyRepl:
         Execute one incremental snippet and return its output.
         """
 
+    def freeze_globals(self) -> None:
+        """
+        Prevent later snippets from rebinding the globals defined so far.
+
+        Rebinding or deleting a frozen global raises `TypeError`. Names first bound by later
+        snippets stay writable. Only the bindings are protected: the objects frozen globals
+        refer to are not frozen, so mutating one is visible to every later snippet.
+        """
+
     def dump(self) -> bytes:
         """Serialize the REPL session to bytes."""
 
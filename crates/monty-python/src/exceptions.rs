@@ -401,6 +401,7 @@ pub fn exc_monty_to_py(py: Python<'_>, exc: MontyException) -> PyErr {
         ExcType::TypeError => exceptions::PyTypeError::new_err(msg),
         ExcType::ValueError => exceptions::PyValueError::new_err(msg),
         ExcType::UnicodeDecodeError => exceptions::PyUnicodeDecodeError::new_err(msg),
+        ExcType::UnicodeEncodeError => exceptions::PyUnicodeEncodeError::new_err(msg),
         ExcType::ImportError => exceptions::PyImportError::new_err(msg),
         ExcType::ModuleNotFoundError => exceptions::PyModuleNotFoundError::new_err(msg),
         ExcType::OSError => exceptions::PyOSError::new_err(msg),
@@ -440,10 +441,12 @@ fn py_err_to_exc_type(exc: &Bound<'_, exceptions::PyBaseException>) -> ExcType {
         // put the most commonly used exceptions first
         if exceptions::PyTypeError::type_check(exc) {
             ExcType::TypeError
-        // ValueError hierarchy (check UnicodeDecodeError first as it's a subclass)
+        // ValueError hierarchy (check the Unicode errors first as they're subclasses)
         } else if exceptions::PyValueError::type_check(exc) {
             if exceptions::PyUnicodeDecodeError::type_check(exc) {
                 ExcType::UnicodeDecodeError
+            } else if exceptions::PyUnicodeEncodeError::type_check(exc) {
+                ExcType::UnicodeEncodeError
             } else {
                 ExcType::ValueError
             }
@@ -8,7 +8,7 @@ use std::{
     time::Duration,
 };
 
-use monty::{DEFAULT_MAX_RECURSION_DEPTH, ResourceError, ResourceTracker};
+use monty::{CollectionKind, DEFAULT_MAX_RECURSION_DEPTH, ResourceBudget, ResourceError, ResourceTracker, SpanKind};
 use pyo3::{prelude::*, types::PyDict};
 
 use crate::exceptions::exc_py_to_monty;
@@ -21,6 +21,7 @@ use crate::exceptions::exc_py_to_monty;
 /// - `max_memory`: Maximum heap memory in bytes (int)
 /// - `gc_interval`: Run garbage collection every N allocations (int)
 /// - `max_recursion_depth`: Maximum function call stack depth (int, default: 1000)
+/// - `hide_resources`: Hide the remaining budget from the `resources()` builtin (bool)
 ///
 /// If a key is missing or set to `None`, that limit is not applied
 /// (except `max_recursion_depth` which defaults to 1000).
@@ -34,7 +35,14 @@ pub fn extract_limits(dict: &Bound<'_, PyDict>) -> PyResult<monty::ResourceLimit
     let max_recursion_depth =
         extract_optional_usize(dict, "max_recursion_depth")?.or(Some(DEFAULT_MAX_RECURSION_DEPTH));
 
-    let mut limits = monty::ResourceLimits::new().max_recursion_depth(max_recursion_depth);
+    let hide_resources = match dict.get_item("hide_resources")? {
+        Some(value) if !value.is_none() => value.extract()?,
+        _ => false,
+    };
+
+    let mut limits = monty::ResourceLimits::new()
+        .max_recursion_depth(max_recursion_depth)
+        .hide_resources(hide_resources);
 
     if let Some(max) = max_allocations {
         limits = limits.max_allocations(max);
@@ -124,6 +132,10 @@ impl<T: ResourceTracker> ResourceTracker for PySignalTracker<T> {
         self.inner.on_free(get_size);
     }
 
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
     fn check_time(&self) -> Result<(), ResourceError> {
         // First check inner tracker's time limit
         self.inner.check_time()?;
@@ -139,4 +151,24 @@ impl<T: ResourceTracker> ResourceTracker for PySignalTracker<T> {
     fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
         self.inner.check_large_result(estimated_bytes)
     }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
 }
@@ -3,7 +3,7 @@ use std::{borrow::Cow, fmt::Write};
 // Use `::monty` to refer to the external crate (not the pymodule)
 use ::monty::{
     ExternalResult, LimitedTracker, MontyException, MontyObject, MontyRepl as CoreMontyRepl, MontyRun, NoLimitTracker,
-    PrintWriter, PrintWriterCallback, ResourceTracker, RunProgress, Snapshot,
+    PrintWriter, PrintWriterCallback, ResourceTracker, RunProgress, Snapshot, SnapshotError,
 };
 use monty::{ExcType, FutureSnapshot, OsFunction};
 use monty_type_checking::{SourceFile, type_check};
@@ -231,12 +231,12 @@ impl PyMonty {
     /// `ValueError` if serialization fails.
     fn dump<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
         let serialized = SerializedMonty {
-            runner: self.runner.clone(),
+            code: self.runner.dump_code().map_err(dump_error)?,
             script_name: self.script_name.clone(),
             input_names: self.input_names.clone(),
             external_function_names: self.external_function_names.clone(),
         };
-        let bytes = postcard::to_allocvec(&serialized).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(PyBytes::new(py, &bytes))
     }
 
@@ -250,7 +250,8 @@ impl PyMonty {
     /// A new Monty instance.
     ///
     /// # Raises
-    /// `ValueError` if deserialization fails.
+    /// `ValueError` if the data is not `dump()` output, is corrupted, or was written by
+    /// another Monty version.
     #[staticmethod]
     #[pyo3(signature = (data, *, dataclass_registry=None))]
     fn load(
@@ -258,12 +259,10 @@ impl PyMonty {
         data: &Bound<'_, PyBytes>,
         dataclass_registry: Option<&Bound<'_, PyList>>,
     ) -> PyResult<Self> {
-        let bytes = data.as_bytes();
-        let serialized: SerializedMonty =
-            postcard::from_bytes(bytes).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let serialized: SerializedMonty = postcard::from_bytes(data.as_bytes()).map_err(envelope_error)?;
 
         Ok(Self {
-            runner: serialized.runner,
+            runner: MontyRun::load_code(&serialized.code).map_err(load_error)?,
             script_name: serialized.script_name,
             input_names: serialized.input_names,
             external_function_names: serialized.external_function_names,
@@ -405,6 +404,20 @@ impl PyMonty {
                 RunProgress::ResolveFutures { .. } => {
                     return Err(PyRuntimeError::new_err("async futures not supported with `Monty.run`"));
                 }
+                RunProgress::Paused(_) => {
+                    return Err(PyRuntimeError::new_err(
+                        "fuel-limited execution not supported with `Monty.run`",
+                    ));
+                }
+                RunProgress::Breakpoint(_) => {
+                    return Err(PyRuntimeError::new_err("breakpoints not supported with `Monty.run`"));
+                }
+                RunProgress::Emit { .. } => {
+                    return Err(PyRuntimeError::new_err("emit() not supported with `Monty.run`"));
+                }
+                RunProgress::Sleep { .. } => {
+                    return Err(PyRuntimeError::new_err("time.sleep() not supported with `Monty.run`"));
+                }
                 RunProgress::OsCall {
                     function,
                     args,
@@ -511,6 +524,18 @@ impl EitherProgress {
                     print_callback,
                     dc_registry,
                 ),
+                RunProgress::Paused(_) => Err(PyRuntimeError::new_err(
+                    "fuel-limited execution is not supported by the Python bindings",
+                )),
+                RunProgress::Breakpoint(_) => Err(PyRuntimeError::new_err(
+                    "breakpoints are not supported by the Python bindings",
+                )),
+                RunProgress::Emit { .. } => Err(PyRuntimeError::new_err(
+                    "emit() is not supported by the Python bindings",
+                )),
+                RunProgress::Sleep { .. } => Err(PyRuntimeError::new_err(
+                    "time.sleep() is not supported by the Python bindings",
+                )),
             },
             Self::Limited(p) => match p {
                 RunProgress::Complete(result) => PyMontyComplete::create(py, &result, &dc_registry),
@@ -556,6 +581,18 @@ impl EitherProgress {
                     print_callback,
                     dc_registry,
                 ),
+                RunProgress::Paused(_) => Err(PyRuntimeError::new_err(
+                    "fuel-limited execution is not supported by the Python bindings",
+                )),
+                RunProgress::Breakpoint(_) => Err(PyRuntimeError::new_err(
+                    "breakpoints are not supported by the Python bindings",
+                )),
+                RunProgress::Emit { .. } => Err(PyRuntimeError::new_err(
+                    "emit() is not supported by the Python bindings",
+                )),
+                RunProgress::Sleep { .. } => Err(PyRuntimeError::new_err(
+                    "time.sleep() is not supported by the Python bindings",
+                )),
             },
         }
     }
@@ -647,12 +684,28 @@ impl EitherProgress {
 ///
 /// PyO3 classes cannot be generic, so this enum stores REPL sessions for both
 /// resource tracker variants.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug)]
 enum EitherRepl {
     NoLimit(CoreMontyRepl<PySignalTracker<NoLimitTracker>>),
     Limited(CoreMontyRepl<PySignalTracker<LimitedTracker>>),
 }
 
+impl EitherRepl {
+    fn dump(&self) -> PyResult<DumpedState> {
+        match self {
+            Self::NoLimit(repl) => Ok(DumpedState::NoLimit(repl.dump().map_err(dump_error)?)),
+            Self::Limited(repl) => Ok(DumpedState::Limited(repl.dump().map_err(dump_error)?)),
+        }
+    }
+
+    fn load(state: &DumpedState) -> PyResult<Self> {
+        match state {
+            DumpedState::NoLimit(bytes) => Ok(Self::NoLimit(CoreMontyRepl::load(bytes).map_err(load_error)?)),
+            DumpedState::Limited(bytes) => Ok(Self::Limited(CoreMontyRepl::load(bytes).map_err(load_error)?)),
+        }
+    }
+}
+
 #[pyclass(name = "MontyRepl", module = "pydantic_monty")]
 #[derive(Debug)]
 pub struct PyMontyRepl {
@@ -749,23 +802,36 @@ impl PyMontyRepl {
         Ok(monty_to_py(py, &output, &self.dc_registry)?.into_bound(py))
     }
 
+    /// Prevents later snippets from rebinding the globals defined so far.
+    ///
+    /// See `MontyRepl::freeze_globals` in the core crate for the exact semantics.
+    fn freeze_globals(&mut self) {
+        match &mut self.repl {
+            EitherRepl::NoLimit(repl) => repl.freeze_globals(),
+            EitherRepl::Limited(repl) => repl.freeze_globals(),
+        }
+    }
+
     /// Serializes this REPL session to bytes.
     fn dump<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
         #[derive(serde::Serialize)]
         struct SerializedRepl<'a> {
-            repl: &'a EitherRepl,
+            repl: DumpedState,
             script_name: &'a str,
         }
 
         let serialized = SerializedRepl {
-            repl: &self.repl,
+            repl: self.repl.dump()?,
             script_name: &self.script_name,
         };
-        let bytes = postcard::to_allocvec(&serialized).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(PyBytes::new(py, &bytes))
     }
 
     /// Restores a REPL session from `dump()` bytes.
+    ///
+    /// Raises `ValueError` if the data is not `dump()` output, is corrupted, or was written
+    /// by another Monty version.
     #[staticmethod]
     #[pyo3(signature = (data, *, print_callback=None, dataclass_registry=None))]
     fn load(
@@ -776,15 +842,14 @@ impl PyMontyRepl {
     ) -> PyResult<Self> {
         #[derive(serde::Deserialize)]
         struct SerializedReplOwned {
-            repl: EitherRepl,
+            repl: DumpedState,
             script_name: String,
         }
 
-        let serialized: SerializedReplOwned =
-            postcard::from_bytes(data.as_bytes()).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let serialized: SerializedReplOwned = postcard::from_bytes(data.as_bytes()).map_err(envelope_error)?;
 
         Ok(Self {
-            repl: serialized.repl,
+            repl: EitherRepl::load(&serialized.repl)?,
             print_callback,
             dc_registry: DcRegistry::from_list(py, dataclass_registry)?,
             script_name: serialized.script_name,
@@ -898,7 +963,7 @@ impl PyMontyRepl {
 ///
 /// Used internally by `PyMontySnapshot` to store execution state.
 /// The `Done` variant indicates the snapshot has been consumed.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug)]
 enum EitherSnapshot {
     NoLimit(Snapshot<PySignalTracker<NoLimitTracker>>),
     Limited(Snapshot<PySignalTracker<LimitedTracker>>),
@@ -907,6 +972,25 @@ enum EitherSnapshot {
     Done,
 }
 
+impl EitherSnapshot {
+    fn dump(&self) -> PyResult<DumpedState> {
+        match self {
+            Self::NoLimit(snapshot) => Ok(DumpedState::NoLimit(snapshot.dump().map_err(dump_error)?)),
+            Self::Limited(snapshot) => Ok(DumpedState::Limited(snapshot.dump().map_err(dump_error)?)),
+            Self::Done => Err(PyRuntimeError::new_err(
+                "Cannot dump progress that has already been resumed",
+            )),
+        }
+    }
+
+    fn load(state: &DumpedState) -> PyResult<Self> {
+        match state {
+            DumpedState::NoLimit(bytes) => Ok(Self::NoLimit(Snapshot::load(bytes).map_err(load_error)?)),
+            DumpedState::Limited(bytes) => Ok(Self::Limited(Snapshot::load(bytes).map_err(load_error)?)),
+        }
+    }
+}
+
 #[pyclass(name = "MontySnapshot", module = "pydantic_monty")]
 #[derive(Debug)]
 pub struct PyMontySnapshot {
@@ -1034,7 +1118,7 @@ impl PyMontySnapshot {
     fn dump<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
         #[derive(serde::Serialize)]
         struct SerializedSnapshot<'a> {
-            snapshot: &'a EitherSnapshot,
+            snapshot: DumpedState,
             script_name: &'a str,
             is_os_function: bool,
             function_name: &'a str,
@@ -1066,7 +1150,7 @@ impl PyMontySnapshot {
             .collect::<PyResult<_>>()?;
 
         let serialized = SerializedSnapshot {
-            snapshot: &self.snapshot,
+            snapshot: self.snapshot.dump()?,
             script_name: &self.script_name,
             is_os_function: self.is_os_function,
             function_name: &self.function_name,
@@ -1074,7 +1158,7 @@ impl PyMontySnapshot {
             kwargs,
             call_id: self.call_id,
         };
-        let bytes = postcard::to_allocvec(&serialized).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(PyBytes::new(py, &bytes))
     }
 
@@ -1092,7 +1176,8 @@ impl PyMontySnapshot {
     /// A new MontySnapshot instance.
     ///
     /// # Raises
-    /// `ValueError` if deserialization fails.
+    /// `ValueError` if the data is not `dump()` output, is corrupted, or was written by
+    /// another Monty version.
     #[staticmethod]
     #[pyo3(signature = (data, *, print_callback=None, dataclass_registry=None))]
     fn load(
@@ -1103,7 +1188,7 @@ impl PyMontySnapshot {
     ) -> PyResult<Self> {
         #[derive(serde::Deserialize)]
         struct SerializedSnapshotOwned {
-            snapshot: EitherSnapshot,
+            snapshot: DumpedState,
             script_name: String,
             is_os_function: bool,
             function_name: String,
@@ -1112,10 +1197,8 @@ impl PyMontySnapshot {
             call_id: u32,
         }
 
-        let bytes = data.as_bytes();
-
-        let serialized: SerializedSnapshotOwned =
-            postcard::from_bytes(bytes).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let serialized: SerializedSnapshotOwned = postcard::from_bytes(data.as_bytes()).map_err(envelope_error)?;
+        let snapshot = EitherSnapshot::load(&serialized.snapshot)?;
 
         let dc_registry = DcRegistry::from_list(py, dataclass_registry)?;
 
@@ -1133,7 +1216,7 @@ impl PyMontySnapshot {
         }
 
         Ok(Self {
-            snapshot: serialized.snapshot,
+            snapshot,
             print_callback,
             dc_registry,
             script_name: serialized.script_name,
@@ -1156,7 +1239,7 @@ impl PyMontySnapshot {
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug)]
 enum EitherFutureSnapshot {
     NoLimit(FutureSnapshot<PySignalTracker<NoLimitTracker>>),
     Limited(FutureSnapshot<PySignalTracker<LimitedTracker>>),
@@ -1165,6 +1248,25 @@ enum EitherFutureSnapshot {
     Done,
 }
 
+impl EitherFutureSnapshot {
+    fn dump(&self) -> PyResult<DumpedState> {
+        match self {
+            Self::NoLimit(snapshot) => Ok(DumpedState::NoLimit(snapshot.dump().map_err(dump_error)?)),
+            Self::Limited(snapshot) => Ok(DumpedState::Limited(snapshot.dump().map_err(dump_error)?)),
+            Self::Done => Err(PyRuntimeError::new_err(
+                "Cannot dump progress that has already been resumed",
+            )),
+        }
+    }
+
+    fn load(state: &DumpedState) -> PyResult<Self> {
+        match state {
+            DumpedState::NoLimit(bytes) => Ok(Self::NoLimit(FutureSnapshot::load(bytes).map_err(load_error)?)),
+            DumpedState::Limited(bytes) => Ok(Self::Limited(FutureSnapshot::load(bytes).map_err(load_error)?)),
+        }
+    }
+}
+
 #[pyclass(name = "MontyFutureSnapshot", module = "pydantic_monty")]
 #[derive(Debug)]
 pub struct PyMontyFutureSnapshot {
@@ -1252,7 +1354,7 @@ impl PyMontyFutureSnapshot {
     fn dump<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
         #[derive(serde::Serialize)]
         struct SerializedSnapshot<'a> {
-            snapshot: &'a EitherFutureSnapshot,
+            snapshot: DumpedState,
             script_name: &'a str,
         }
 
@@ -1263,10 +1365,10 @@ impl PyMontyFutureSnapshot {
         }
 
         let serialized = SerializedSnapshot {
-            snapshot: &self.snapshot,
+            snapshot: self.snapshot.dump()?,
             script_name: &self.script_name,
         };
-        let bytes = postcard::to_allocvec(&serialized).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let bytes = postcard::to_allocvec(&serialized).map_err(dump_error)?;
         Ok(PyBytes::new(py, &bytes))
     }
 
@@ -1284,7 +1386,8 @@ impl PyMontyFutureSnapshot {
     /// A new MontyFutureSnapshot instance.
     ///
     /// # Raises
-    /// `ValueError` if deserialization fails.
+    /// `ValueError` if the data is not `dump()` output, is corrupted, or was written by
+    /// another Monty version.
     #[staticmethod]
     #[pyo3(signature = (data, *, print_callback=None, dataclass_registry=None))]
     fn load(
@@ -1295,17 +1398,14 @@ impl PyMontyFutureSnapshot {
     ) -> PyResult<Self> {
         #[derive(serde::Deserialize)]
         struct SerializedSnapshotOwned {
-            snapshot: EitherFutureSnapshot,
+            snapshot: DumpedState,
             script_name: String,
         }
 
-        let bytes = data.as_bytes();
-
-        let serialized: SerializedSnapshotOwned =
-            postcard::from_bytes(bytes).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let serialized: SerializedSnapshotOwned = postcard::from_bytes(data.as_bytes()).map_err(envelope_error)?;
 
         Ok(Self {
-            snapshot: serialized.snapshot,
+            snapshot: EitherFutureSnapshot::load(&serialized.snapshot)?,
             print_callback,
             dc_registry: DcRegistry::from_list(py, dataclass_registry)?,
             script_name: serialized.script_name,
@@ -1418,8 +1518,33 @@ fn contains_dataclass(obj: &MontyObject) -> bool {
 /// Serialization wrapper for `PyMonty` that includes all fields needed for reconstruction.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct SerializedMonty {
-    runner: MontyRun,
+    /// The runner as written by `MontyRun::dump_code()`.
+    code: Vec<u8>,
     script_name: String,
     input_names: Vec<String>,
     external_function_names: Vec<String>,
 }
+
+/// Core state written by its own `dump()`, with the versioned header and checks on load that
+/// brings, tagged with the kind of tracker it runs under.
+#[derive(serde::Serialize, serde::Deserialize)]
+enum DumpedState {
+    NoLimit(Vec<u8>),
+    Limited(Vec<u8>),
+}
+
+/// Converts a failure to serialize state into a `ValueError`.
+fn dump_error(err: postcard::Error) -> PyErr {
+    PyValueError::new_err(format!("Serialization failed: {err}"))
+}
+
+/// Converts a failure to decode the wrapper around dumped state into a `ValueError`.
+fn envelope_error(err: postcard::Error) -> PyErr {
+    load_error(SnapshotError::Postcard(err))
+}
+
+/// Converts a failure to load dumped state into a `ValueError` saying what was wrong with
+/// the bytes: written by another Monty version, corrupted, or not a dump at all.
+fn load_error(err: SnapshotError) -> PyErr {
+    PyValueError::new_err(format!("Cannot load dumped state: {err}"))
+}
@@ -1,3 +1,4 @@
+import pytest
 from inline_snapshot import snapshot
 
 import pydantic_monty
@@ -32,3 +33,17 @@ def test_repl_create_with_start_inputs_feed_stateful():
     assert output == snapshot(None)
     assert repl.feed('counter = counter + 1') == snapshot(None)
     assert repl.feed('counter') == snapshot(1)
+
+
+def test_repl_freeze_globals():
+    repl, _ = pydantic_monty.MontyRepl.create('LIMIT = 10')
+    repl.freeze_globals()
+
+    with pytest.raises(pydantic_monty.MontyRuntimeError) as exc_info:
+        repl.feed('LIMIT = 0')
+    inner = exc_info.value.exception()
+    assert isinstance(inner, TypeError)
+    assert inner.args[0] == snapshot("cannot assign to frozen global 'LIMIT'")
+
+    assert repl.feed('extra = LIMIT + 1') == snapshot(None)
+    assert repl.feed('extra') == snapshot(11)
@@ -46,7 +46,9 @@ def test_monty_dump_load_preserves_external_functions():
 def test_monty_load_invalid_data():
     with pytest.raises(ValueError) as exc_info:
         pydantic_monty.Monty.load(b'invalid data')
-    assert str(exc_info.value) == snapshot('Hit the end of buffer, expected more data')
+    assert str(exc_info.value) == snapshot(
+        'Cannot load dumped state: invalid snapshot data: Hit the end of buffer, expected more data'
+    )
 
 
 def test_progress_dump_load_roundtrip():
@@ -428,3 +428,11 @@ assert_type(y, str | int)
 
 x2 = os.environ.get('foobar')
 assert_type(x2, str | None)
+
+budget = resources()
+assert_type(budget, dict[str, int | float | None])
+if (memory := budget['memory']) is not None:
+    check_float(memory)
+
+reply = emit({'row': 1})
+check_str(reply)
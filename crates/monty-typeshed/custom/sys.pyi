@@ -1,4 +1,4 @@
-from typing import Any, Final, Literal, TextIO, final, type_check_only
+from typing import Any, Final, Literal, NoReturn, TextIO, final, type_check_only
 
 from _typeshed import MaybeNone, structseq
 from typing_extensions import TypeAlias
@@ -31,3 +31,5 @@ class _version_info(_UninstantiableStructseq, tuple[int, int, int, _ReleaseLevel
     def serial(self) -> int: ...
 
 version_info: _version_info
+
+def exit(status: object = None, /) -> NoReturn: ...
@@ -0,0 +1,4 @@
+def time() -> float: ...
+def monotonic() -> float: ...
+def perf_counter() -> float: ...
+def sleep(secs: float, /) -> None: ...
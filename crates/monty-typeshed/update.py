@@ -41,6 +41,14 @@ ALLOWED_FUNCTIONS = {
     'sum',
 }
 
+# Monty-specific builtins with no CPython counterpart, appended to the filtered builtins.pyi
+MONTY_BUILTINS = """
+# === Monty-specific builtins (from crates/monty/src/builtins/) ===
+
+def emit(value: object, /) -> Any: ...
+def resources() -> dict[str, int | float | None]: ...
+"""
+
 # Whitelisted builtin classes (from crates/monty/src/types/ and exception_private.rs)
 ALLOWED_CLASSES = {
     # Core types
@@ -134,6 +142,7 @@ os: 3.0-
 pathlib: 3.4-
 pathlib.types: 3.14-
 sys: 3.0-
+time: 3.0-
 typing: 3.5-
 typing_extensions: 3.7-
 types: 3.0-
@@ -244,7 +253,8 @@ def filter_builtins(source: str) -> str:
 
     This function parses the source with Python's ast module and filters
     top-level definitions to only include those in the allow lists.
-    All imports and type definitions are preserved.
+    All imports and type definitions are preserved, and the stubs for
+    Monty's own builtins are appended.
 
     Args:
         source: The source code of builtins.pyi.
@@ -255,7 +265,7 @@ def filter_builtins(source: str) -> str:
     tree = ast.parse(source)
     tree.body = filter_statements(tree.body)
     ast.fix_missing_locations(tree)
-    return ast.unparse(tree)
+    return ast.unparse(tree) + '\n\n' + MONTY_BUILTINS
 
 
 def main() -> int:
@@ -146,6 +146,7 @@ const PAIR_TUPLES: &str = "len([(i, i + 1) for i in range(100_000)])";
 /// Benchmarks end-to-end execution (parsing + running) using Monty.
 /// This is different from other benchmarks as it includes parsing in the loop.
 fn end_to_end_monty(bench: &mut Bencher) {
+    MontyRun::warm_up();
     bench.iter(|| {
         let ex = MontyRun::new(black_box("1 + 2").to_owned(), "test.py", vec![], vec![]).unwrap();
         let r = ex.run_no_limits(vec![]).unwrap();
@@ -154,6 +155,21 @@ fn end_to_end_monty(bench: &mut Bencher) {
     });
 }
 
+/// Benchmarks end-to-end execution from bytecode cached with `dump_code()`, skipping parsing.
+fn end_to_end_cached_monty(bench: &mut Bencher) {
+    MontyRun::warm_up();
+    let bytes = MontyRun::new("1 + 2".to_owned(), "test.py", vec![], vec![])
+        .unwrap()
+        .dump_code()
+        .unwrap();
+    bench.iter(|| {
+        let ex = MontyRun::load_code(black_box(&bytes)).unwrap();
+        let r = ex.run_no_limits(vec![]).unwrap();
+        let int_value: i64 = r.as_ref().try_into().unwrap();
+        black_box(int_value);
+    });
+}
+
 /// Benchmarks end-to-end execution (parsing + running) using CPython.
 /// This is different from other benchmarks as it includes parsing in the loop.
 #[cfg(not(codspeed))]
@@ -188,6 +204,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("loop_mod_13__cpython", |b| run_cpython(b, LOOP_MOD_13, 77));
 
     c.bench_function("end_to_end__monty", end_to_end_monty);
+    c.bench_function("end_to_end_cached__monty", end_to_end_cached_monty);
     #[cfg(not(codspeed))]
     c.bench_function("end_to_end__cpython", end_to_end_cpython);
 
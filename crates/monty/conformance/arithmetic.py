@@ -0,0 +1,7 @@
+print(7 // 2, -7 // 2, 7 % 3, -7 % 3)
+print(2**10, 10 / 4, 1 + 2 * 3)
+print(divmod(17, 5), abs(-4), round(2.5), round(3.5))
+x = 10
+x += 5
+x *= 2
+[x, x > 20, x == 30, -x]
@@ -0,0 +1,10 @@
+items = [5, 3, 8, 1]
+items.append(4)
+items.sort()
+print(items, items[-1], items[1:3])
+counts = {}
+for word in ['a', 'b', 'a', 'c', 'a']:
+    counts[word] = counts.get(word, 0) + 1
+print(counts, list(counts.keys()), sorted(counts.values()))
+print(sorted({3, 1, 2}), (1, 'two', 3.0))
+[n * n for n in items if n % 2 == 1]
@@ -0,0 +1,13 @@
+def fib(n):
+    if n < 2:
+        return n
+    return fib(n - 1) + fib(n - 2)
+
+
+def greet(name, greeting='hello'):
+    return greeting + ' ' + name
+
+
+print([fib(i) for i in range(10)])
+print(greet('monty'), greet('monty', greeting='hi'))
+{k: fib(k) for k in range(5)}
@@ -0,0 +1,6 @@
+s = 'Hello, World'
+print(s.upper(), s.lower(), len(s))
+print(s.split(', '), '-'.join(['a', 'b', 'c']))
+print(s[1:5], s[::-1], s.replace('l', 'L'))
+print(f'{s!r} has {s.count("o")} o')
+s.startswith('Hello') and s.endswith('World')
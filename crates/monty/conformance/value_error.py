@@ -0,0 +1,5 @@
+try:
+    int('abc')
+except ValueError as exc:
+    print('caught', exc)
+int('xyz')
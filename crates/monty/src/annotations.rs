@@ -0,0 +1,220 @@
+//! Type annotations on function signatures, and checking external calls against them.
+//!
+//! The parser keeps every parameter and return annotation as written, together with the
+//! [`TypeCheck`] it implies. Only the outer type of an annotation is checked: `list[int]`
+//! accepts any list, and names Monty can't resolve statically (user types, string forward
+//! references, `Literal[...]`, ...) accept anything.
+//!
+//! Annotations never change how script functions run. They're enforced at the boundary
+//! with the host when `CompileOptions::check_external_calls` provides stubs declaring the
+//! external functions: arguments are checked before the host sees a call, and return values
+//! when the host resumes with them.
+
+use ruff_python_ast::{self as ast, Expr as AstExpr, Operator as AstOperator};
+use ruff_text_size::Ranged;
+
+use crate::{
+    exception_private::{ExcType, RunResult},
+    exception_public::MontyException,
+    expressions::Node,
+    intern::InternerBuilder,
+    parse::{ParsedParam, RawFunctionDef, parse},
+    types::Type,
+};
+
+/// Script name used for errors in the stubs passed to `CompileOptions::check_external_calls`.
+pub(crate) const STUBS_FILENAME: &str = "stubs.pyi";
+
+/// A parameter or return annotation.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Annotation {
+    /// The annotation's source text, e.g. `list[str] | None`.
+    pub text: String,
+    /// The check values must pass to match the annotation.
+    pub check: TypeCheck,
+}
+
+impl Annotation {
+    pub fn new(expr: &AstExpr, code: &str) -> Self {
+        Self {
+            text: code[expr.range()].to_owned(),
+            check: TypeCheck::from_expr(expr),
+        }
+    }
+}
+
+/// The runtime check implied by an annotation.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TypeCheck {
+    /// Anything is accepted: `Any`, `object`, or an annotation Monty can't resolve.
+    Any,
+    /// Only `None`.
+    NoneType,
+    /// Instances of the type, including subtypes; `float` also accepts `int`.
+    Type(Type),
+    /// Values accepted by any member: `int | None`, `Optional[int]`, `Union[int, str]`.
+    Union(Vec<Self>),
+}
+
+impl TypeCheck {
+    /// Builds the check for an annotation expression.
+    fn from_expr(expr: &AstExpr) -> Self {
+        match expr {
+            AstExpr::NoneLiteral(_) => Self::NoneType,
+            AstExpr::Name(ast::ExprName { id, .. }) => Self::from_name(id),
+            AstExpr::Attribute(ast::ExprAttribute { attr, .. }) => Self::from_name(&attr.id),
+            AstExpr::Subscript(ast::ExprSubscript { value, slice, .. }) => match type_name(value) {
+                Some("Optional") => Self::union([Self::from_expr(slice), Self::NoneType]),
+                Some("Union") => match slice.as_ref() {
+                    AstExpr::Tuple(tuple) => Self::union(tuple.elts.iter().map(Self::from_expr)),
+                    other => Self::from_expr(other),
+                },
+                // only the container is checked, not its type parameters
+                _ => Self::from_expr(value),
+            },
+            AstExpr::BinOp(ast::ExprBinOp {
+                left,
+                op: AstOperator::BitOr,
+                right,
+                ..
+            }) => Self::union([Self::from_expr(left), Self::from_expr(right)]),
+            _ => Self::Any,
+        }
+    }
+
+    fn from_name(name: &str) -> Self {
+        let builtin_name = match name {
+            "List" => "list",
+            "Dict" => "dict",
+            "Tuple" => "tuple",
+            "Set" => "set",
+            "FrozenSet" => "frozenset",
+            other => other,
+        };
+        Type::from_builtin_name(builtin_name).map_or(Self::Any, Self::Type)
+    }
+
+    /// Combines checks into a union; a union containing `Any` accepts anything.
+    fn union(members: impl IntoIterator<Item = Self>) -> Self {
+        let mut flat = Vec::new();
+        for member in members {
+            match member {
+                Self::Any => return Self::Any,
+                Self::Union(inner) => flat.extend(inner),
+                other => flat.push(other),
+            }
+        }
+        Self::Union(flat)
+    }
+
+    /// Returns whether a value of type `value_type` passes the check.
+    pub fn accepts(&self, value_type: Type) -> bool {
+        match self {
+            Self::Any => true,
+            Self::NoneType => value_type == Type::NoneType,
+            Self::Type(Type::Float) => value_type == Type::Float || value_type.is_instance_of(Type::Int),
+            Self::Type(Type::Tuple) => matches!(value_type, Type::Tuple | Type::NamedTuple),
+            Self::Type(expected) => value_type.is_instance_of(*expected),
+            Self::Union(members) => members.iter().any(|member| member.accepts(value_type)),
+        }
+    }
+}
+
+/// Returns the name an annotation refers to, e.g. `Optional` for `typing.Optional`.
+fn type_name(expr: &AstExpr) -> Option<&str> {
+    match expr {
+        AstExpr::Name(ast::ExprName { id, .. }) => Some(id.as_str()),
+        AstExpr::Attribute(ast::ExprAttribute { attr, .. }) => Some(attr.id.as_str()),
+        _ => None,
+    }
+}
+
+/// The annotations of an external function, declared in the stubs.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ExternalSignature {
+    /// Parameters that can be passed by position, in order.
+    positional: Vec<(String, Option<Annotation>)>,
+    /// Parameters that can be passed by keyword.
+    keyword: Vec<(String, Option<Annotation>)>,
+    /// The return annotation.
+    returns: Option<Annotation>,
+}
+
+impl ExternalSignature {
+    fn new(function: RawFunctionDef, interner: &InternerBuilder) -> Self {
+        let signature = function.signature;
+        let named = |params: Vec<ParsedParam>| -> Vec<(String, Option<Annotation>)> {
+            params
+                .into_iter()
+                .map(|param| (interner.get_str(param.name).to_owned(), param.annotation))
+                .collect()
+        };
+        let pos_only = named(signature.pos_args);
+        let args = named(signature.args);
+        let kw_only = named(signature.kwargs);
+        Self {
+            positional: pos_only.into_iter().chain(args.iter().cloned()).collect(),
+            keyword: args.into_iter().chain(kw_only).collect(),
+            returns: function.returns,
+        }
+    }
+
+    /// Checks the type of the positional argument at `index`, if it has an annotation.
+    pub fn check_positional(&self, function_name: &str, index: usize, value_type: Type) -> RunResult<()> {
+        match self.positional.get(index) {
+            Some((name, Some(annotation))) => check_argument(function_name, name, annotation, value_type),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the type of the keyword argument `name`, if it has an annotation.
+    pub fn check_keyword(&self, function_name: &str, name: &str, value_type: Type) -> RunResult<()> {
+        match self.keyword.iter().find(|(param, _)| param == name) {
+            Some((name, Some(annotation))) => check_argument(function_name, name, annotation, value_type),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the type of the value the host returned, if the stub declares a return type.
+    pub fn check_return(&self, function_name: &str, value_type: Type) -> RunResult<()> {
+        match &self.returns {
+            Some(annotation) if !annotation.check.accepts(value_type) => Err(ExcType::type_error(format!(
+                "{function_name}() return value must be {}, not {value_type}",
+                annotation.text
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check_argument(function_name: &str, name: &str, annotation: &Annotation, value_type: Type) -> RunResult<()> {
+    if annotation.check.accepts(value_type) {
+        Ok(())
+    } else {
+        Err(ExcType::type_error(format!(
+            "{function_name}() argument '{name}' must be {}, not {value_type}",
+            annotation.text
+        )))
+    }
+}
+
+/// Finds the declaration of each external function in `stubs`, indexed like `external_functions`.
+///
+/// Stubs are Python source declaring external functions with annotated `def` statements;
+/// function bodies are ignored. External functions the stubs don't declare aren't checked.
+pub(crate) fn external_signatures(
+    stubs: &str,
+    external_functions: &[String],
+) -> Result<Vec<Option<ExternalSignature>>, MontyException> {
+    let parsed = parse(stubs, STUBS_FILENAME).map_err(|e| e.into_python_exc(STUBS_FILENAME, stubs))?;
+    let mut signatures = vec![None; external_functions.len()];
+    for node in parsed.nodes {
+        if let Node::FunctionDef(function) = node {
+            let name = parsed.interner.get_str(function.name.name_id);
+            if let Some(index) = external_functions.iter().position(|external| external == name) {
+                signatures[index] = Some(ExternalSignature::new(function, &parsed.interner));
+            }
+        }
+    }
+    Ok(signatures)
+}
@@ -4,7 +4,7 @@ use crate::{
     MontyObject, ResourceTracker, defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunError, RunResult, SimpleException},
     expressions::{ExprLoc, Identifier},
-    heap::{DropWithHeap, Heap, HeapGuard},
+    heap::{DropWithHeap, Heap, HeapGuard, HeapIdMap},
     intern::{Interns, StringId},
     parse::ParseError,
     types::{Dict, dict::DictIntoIter},
@@ -297,6 +297,23 @@ impl ArgValues {
             Self::ArgsKargs { args, .. } => args.len(),
         }
     }
+
+    /// Rewrites heap ids in the arguments after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        match self {
+            Self::Empty => {}
+            Self::One(v) => map.remap_value(v),
+            Self::Two(v1, v2) => {
+                map.remap_value(v1);
+                map.remap_value(v2);
+            }
+            Self::Kwargs(kwargs) => kwargs.remap_heap_ids(map),
+            Self::ArgsKargs { args, kwargs } => {
+                map.remap_values(args);
+                kwargs.remap_heap_ids(map);
+            }
+        }
+    }
 }
 
 impl DropWithHeap for ArgValues {
@@ -460,6 +477,15 @@ impl KwargsValues {
             .into())
         }
     }
+
+    /// Rewrites heap ids in the keyword argument values after heap compaction.
+    fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        match self {
+            Self::Empty => {}
+            Self::Inline(kvs) => map.remap_values(kvs.iter_mut().map(|(_, v)| v)),
+            Self::Dict(dict) => dict.remap_heap_ids(map),
+        }
+    }
 }
 
 impl DropWithHeap for KwargsValues {
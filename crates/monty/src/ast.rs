@@ -0,0 +1,1132 @@
+//! Public syntax tree of a script, for tooling that lints or analyzes code before running it.
+//!
+//! [`parse`] runs Monty's parser and projects its output into the owned types below, with
+//! names and literals resolved to plain strings and values. The projection is deliberately
+//! simpler than Python's `ast` module: every assignment form is a [`Stmt::Assign`] with a
+//! [`Target`], method calls are a [`ExprKind::Call`] of an [`ExprKind::Attribute`], and
+//! `and`/`or` are binary operators. Only syntax Monty supports can be parsed.
+//!
+//! Walk the tree with a [`Visitor`], overriding the methods for the nodes of interest and
+//! calling the matching `walk_*` function to continue into children.
+//!
+//! The node enums are `#[non_exhaustive]`: supporting more syntax adds variants, so matches
+//! on them outside this crate need a wildcard arm.
+
+use num_bigint::BigInt;
+
+use crate::{
+    MontyException,
+    args::{ArgExprs, Kwarg},
+    exception_public::CodeLoc,
+    expressions::{
+        Callable, CmpOperator, Comprehension as RawComprehension, Expr as RawExpr, ExprLoc, Identifier, Literal, Node,
+        Operator, UnpackTarget,
+    },
+    fstring::{ConversionFlag, FStringPart as RawFStringPart, FormatSpec, ParsedFormatSpec},
+    intern::{Interns, StringId},
+    parse::{CodeRange, ParseNode, ParsedParam, ParsedSignature, RawFunctionDef, Try, parse as parse_raw},
+    value::EitherStr,
+};
+
+/// Parses `code` into a [`Module`].
+///
+/// # Errors
+/// Returns `MontyException` with a `SyntaxError` (or `NotImplementedError` for syntax Monty
+/// doesn't support) if the code can't be parsed.
+pub fn parse(code: &str, script_name: &str) -> Result<Module, MontyException> {
+    let parsed = parse_raw(code, script_name).map_err(|e| e.into_python_exc(script_name, code))?;
+    let interns = Interns::new(parsed.interner, Vec::new(), Vec::new());
+    let converter = Converter { interns: &interns };
+    Ok(Module {
+        body: converter.body(parsed.nodes),
+    })
+}
+
+/// Source range of a node, from its first character to just past its last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Span {
+    /// Start of the node.
+    pub start: CodeLoc,
+    /// End of the node.
+    pub end: CodeLoc,
+}
+
+impl From<CodeRange> for Span {
+    fn from(range: CodeRange) -> Self {
+        Self {
+            start: range.start(),
+            end: range.end(),
+        }
+    }
+}
+
+/// A parsed script.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Module {
+    /// Top-level statements, in source order.
+    pub body: Vec<Stmt>,
+}
+
+impl Module {
+    /// Walks every statement of the module with `visitor`.
+    pub fn visit(&self, visitor: &mut impl Visitor) {
+        walk_body(visitor, &self.body);
+    }
+}
+
+/// A name bound or referenced at a known position.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Name {
+    /// The identifier.
+    pub id: String,
+    /// Where it appears.
+    pub span: Span,
+}
+
+/// A statement.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Stmt {
+    /// An expression evaluated for its side effects (or as the script's result).
+    Expr(Expr),
+    /// `pass`
+    Pass,
+    /// `return` with an optional value.
+    Return(Option<Expr>),
+    /// `raise exc from cause`; `exc` is `None` for a bare `raise`.
+    Raise { exc: Option<Expr>, cause: Option<Expr> },
+    /// `assert test, msg`
+    Assert { test: Expr, msg: Option<Expr> },
+    /// Any assignment: `a = ...`, `a, *b = ...`, `a[i] = ...` or `a.b = ...`.
+    Assign { target: Target, value: Expr },
+    /// Augmented assignment, e.g. `a += 1`.
+    AugAssign { target: Name, op: BinOp, value: Expr },
+    /// `for target in iter: body else: or_else`
+    For {
+        target: Target,
+        iter: Expr,
+        body: Vec<Stmt>,
+        or_else: Vec<Stmt>,
+    },
+    /// `while test: body else: or_else`
+    While {
+        test: Expr,
+        body: Vec<Stmt>,
+        or_else: Vec<Stmt>,
+    },
+    /// `if test: body else: or_else`; `elif` is an `If` nested in `or_else`.
+    If {
+        test: Expr,
+        body: Vec<Stmt>,
+        or_else: Vec<Stmt>,
+    },
+    /// `break`
+    Break(Span),
+    /// `continue`
+    Continue(Span),
+    /// `def` or `async def`.
+    FunctionDef(FunctionDef),
+    /// `global a, b`
+    Global { names: Vec<String>, span: Span },
+    /// `nonlocal a, b`
+    Nonlocal { names: Vec<String>, span: Span },
+    /// `try` with its handlers, `else` and `finally` blocks.
+    Try {
+        body: Vec<Stmt>,
+        handlers: Vec<ExceptHandler>,
+        or_else: Vec<Stmt>,
+        finally: Vec<Stmt>,
+    },
+    /// `import module` or `import module as alias`; `alias` is the bound name.
+    Import { module: String, alias: Name },
+    /// `from module import name as alias, ...`, as `(name, alias)` pairs.
+    ImportFrom {
+        module: String,
+        names: Vec<(String, Name)>,
+        span: Span,
+    },
+}
+
+/// A function definition.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FunctionDef {
+    /// The function's name.
+    pub name: Name,
+    /// The parameters.
+    pub params: Parameters,
+    /// The return annotation as written, e.g. `list[str]`.
+    pub returns: Option<String>,
+    /// The body.
+    pub body: Vec<Stmt>,
+    /// Whether this is an `async def`.
+    pub is_async: bool,
+}
+
+/// Parameters of a function or lambda.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Parameters {
+    /// Parameters before `/`.
+    pub positional_only: Vec<Param>,
+    /// Positional-or-keyword parameters.
+    pub args: Vec<Param>,
+    /// The `*args` parameter.
+    pub var_args: Option<String>,
+    /// Parameters after `*` or `*args`.
+    pub keyword_only: Vec<Param>,
+    /// The `**kwargs` parameter.
+    pub var_kwargs: Option<String>,
+}
+
+/// A single parameter with its optional annotation and default.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Param {
+    /// The parameter's name.
+    pub name: String,
+    /// The type annotation as written, e.g. `int | None`.
+    pub annotation: Option<String>,
+    /// The default value.
+    pub default: Option<Expr>,
+}
+
+/// An `except` clause.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExceptHandler {
+    /// The exception type(s) caught, `None` for a bare `except:`.
+    pub exc_type: Option<Expr>,
+    /// The name bound with `as`.
+    pub name: Option<Name>,
+    /// The handler body.
+    pub body: Vec<Stmt>,
+}
+
+/// The left-hand side of an assignment, `for` loop or comprehension.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Target {
+    /// A plain name.
+    Name(Name),
+    /// A tuple of targets to unpack into, e.g. `a, (b, c)`.
+    Tuple { targets: Vec<Target>, span: Span },
+    /// A starred target, e.g. `*rest`.
+    Starred(Name),
+    /// An item assignment, e.g. `a[i]`.
+    Subscript { object: Expr, index: Expr, span: Span },
+    /// An attribute assignment, e.g. `a.b`.
+    Attribute { object: Expr, attr: String, span: Span },
+}
+
+/// An expression with its position.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Expr {
+    /// Where the expression appears.
+    ///
+    /// The callee of a call that Monty parses as a unit (a name or method call) shares the
+    /// span of the whole call.
+    pub span: Span,
+    /// What the expression is.
+    pub kind: ExprKind,
+}
+
+/// The kinds of expression.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum ExprKind {
+    /// A literal value.
+    Constant(Constant),
+    /// A variable reference.
+    Name(String),
+    /// A call, e.g. `f(a, *b, c=d)`; method calls have an `Attribute` as `func`.
+    Call { func: Box<Expr>, args: Vec<Arg> },
+    /// Attribute access, e.g. `a.b`.
+    Attribute { value: Box<Expr>, attr: String },
+    /// A binary operation, including `and` and `or`.
+    BinOp {
+        left: Box<Expr>,
+        op: BinOp,
+        right: Box<Expr>,
+    },
+    /// A unary operation.
+    UnaryOp { op: UnaryOp, operand: Box<Expr> },
+    /// One comparison or a chain of them, e.g. `a < b <= c`.
+    Compare {
+        left: Box<Expr>,
+        comparisons: Vec<(CmpOp, Expr)>,
+    },
+    /// `body if test else orelse`
+    IfElse {
+        test: Box<Expr>,
+        body: Box<Expr>,
+        orelse: Box<Expr>,
+    },
+    /// A list display.
+    List(Vec<Expr>),
+    /// A tuple display.
+    Tuple(Vec<Expr>),
+    /// A set display.
+    Set(Vec<Expr>),
+    /// A dict display, as key/value pairs.
+    Dict(Vec<(Expr, Expr)>),
+    /// Subscript, e.g. `a[i]` or `a[1:2]` (with a `Slice` index).
+    Subscript { value: Box<Expr>, index: Box<Expr> },
+    /// A slice inside a subscript.
+    Slice {
+        lower: Option<Box<Expr>>,
+        upper: Option<Box<Expr>>,
+        step: Option<Box<Expr>>,
+    },
+    /// An f-string.
+    FString(Vec<FStringPart>),
+    /// `[elt for ...]`
+    ListComp {
+        elt: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+    /// `{elt for ...}`
+    SetComp {
+        elt: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+    /// `{key: value for ...}`
+    DictComp {
+        key: Box<Expr>,
+        value: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+    /// `(elt for ...)`
+    Generator {
+        elt: Box<Expr>,
+        generators: Vec<Comprehension>,
+    },
+    /// `lambda params: body`
+    Lambda { params: Parameters, body: Box<Expr> },
+    /// `(target := value)`
+    Named { target: Name, value: Box<Expr> },
+    /// `await value`
+    Await(Box<Expr>),
+}
+
+/// A literal value.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Constant {
+    /// `None`
+    None,
+    /// `...`
+    Ellipsis,
+    /// `True` or `False`
+    Bool(bool),
+    /// An integer that fits in `i64`.
+    Int(i64),
+    /// A larger integer.
+    BigInt(BigInt),
+    /// A float.
+    Float(f64),
+    /// A string, with escapes resolved and implicit concatenation applied.
+    Str(String),
+    /// A bytes literal.
+    Bytes(Vec<u8>),
+}
+
+/// A call argument.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Arg {
+    /// A positional argument.
+    Positional(Expr),
+    /// `*iterable`
+    Starred(Expr),
+    /// `name=value`
+    Keyword { name: Name, value: Expr },
+    /// `**mapping`
+    DoubleStarred(Expr),
+}
+
+/// One `for target in iter if ...` clause of a comprehension.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Comprehension {
+    /// The loop target.
+    pub target: Target,
+    /// The iterable.
+    pub iter: Expr,
+    /// The `if` filters.
+    pub ifs: Vec<Expr>,
+}
+
+/// A piece of an f-string.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum FStringPart {
+    /// Literal text.
+    Literal(String),
+    /// A `{...}` replacement field.
+    Interpolation {
+        /// The interpolated expression.
+        value: Expr,
+        /// The conversion: `'s'`, `'r'` or `'a'`.
+        conversion: Option<char>,
+        /// The format spec after `:`, which may itself contain replacement fields.
+        format_spec: Option<Vec<FStringPart>>,
+        /// Whether the field uses the `=` debug specifier, e.g. `{x=}`.
+        debug: bool,
+    },
+}
+
+/// Binary operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mult,
+    MatMult,
+    Div,
+    FloorDiv,
+    Mod,
+    Pow,
+    LShift,
+    RShift,
+    BitOr,
+    BitXor,
+    BitAnd,
+    And,
+    Or,
+}
+
+/// Unary operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum UnaryOp {
+    /// `not`
+    Not,
+    /// `-`
+    Minus,
+    /// `+`
+    Plus,
+    /// `~`
+    Invert,
+}
+
+/// Comparison operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum CmpOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtE,
+    Gt,
+    GtE,
+    Is,
+    IsNot,
+    In,
+    NotIn,
+}
+
+/// Walks a syntax tree, visiting statements, expressions and assignment targets.
+///
+/// Each method defaults to walking the node's children, so an override that still wants
+/// the children visited calls the matching `walk_*` function.
+pub trait Visitor {
+    /// Visits a statement.
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    /// Visits an expression.
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    /// Visits an assignment, loop or comprehension target.
+    fn visit_target(&mut self, target: &Target) {
+        walk_target(self, target);
+    }
+}
+
+/// Visits each statement of `body`.
+pub fn walk_body<V: Visitor + ?Sized>(visitor: &mut V, body: &[Stmt]) {
+    for stmt in body {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+/// Visits the children of `stmt`.
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Expr(expr) | Stmt::Return(Some(expr)) => visitor.visit_expr(expr),
+        Stmt::Raise { exc, cause } => {
+            for expr in exc.iter().chain(cause) {
+                visitor.visit_expr(expr);
+            }
+        }
+        Stmt::Assert { test, msg } => {
+            visitor.visit_expr(test);
+            if let Some(msg) = msg {
+                visitor.visit_expr(msg);
+            }
+        }
+        Stmt::Assign { target, value } => {
+            visitor.visit_expr(value);
+            visitor.visit_target(target);
+        }
+        Stmt::AugAssign { value, .. } => visitor.visit_expr(value),
+        Stmt::For {
+            target,
+            iter,
+            body,
+            or_else,
+        } => {
+            visitor.visit_expr(iter);
+            visitor.visit_target(target);
+            walk_body(visitor, body);
+            walk_body(visitor, or_else);
+        }
+        Stmt::While { test, body, or_else } | Stmt::If { test, body, or_else } => {
+            visitor.visit_expr(test);
+            walk_body(visitor, body);
+            walk_body(visitor, or_else);
+        }
+        Stmt::FunctionDef(function) => {
+            walk_params(visitor, &function.params);
+            walk_body(visitor, &function.body);
+        }
+        Stmt::Try {
+            body,
+            handlers,
+            or_else,
+            finally,
+        } => {
+            walk_body(visitor, body);
+            for handler in handlers {
+                if let Some(exc_type) = &handler.exc_type {
+                    visitor.visit_expr(exc_type);
+                }
+                walk_body(visitor, &handler.body);
+            }
+            walk_body(visitor, or_else);
+            walk_body(visitor, finally);
+        }
+        Stmt::Pass
+        | Stmt::Return(None)
+        | Stmt::Break(_)
+        | Stmt::Continue(_)
+        | Stmt::Global { .. }
+        | Stmt::Nonlocal { .. }
+        | Stmt::Import { .. }
+        | Stmt::ImportFrom { .. } => {}
+    }
+}
+
+/// Visits the children of `expr`.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Constant(_) | ExprKind::Name(_) => {}
+        ExprKind::Call { func, args } => {
+            visitor.visit_expr(func);
+            for arg in args {
+                match arg {
+                    Arg::Positional(value)
+                    | Arg::Starred(value)
+                    | Arg::Keyword { value, .. }
+                    | Arg::DoubleStarred(value) => visitor.visit_expr(value),
+                }
+            }
+        }
+        ExprKind::Attribute { value, .. } | ExprKind::UnaryOp { operand: value, .. } | ExprKind::Await(value) => {
+            visitor.visit_expr(value);
+        }
+        ExprKind::Named { value, .. } => visitor.visit_expr(value),
+        ExprKind::BinOp { left, right, .. }
+        | ExprKind::Subscript {
+            value: left,
+            index: right,
+        } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Compare { left, comparisons } => {
+            visitor.visit_expr(left);
+            for (_, operand) in comparisons {
+                visitor.visit_expr(operand);
+            }
+        }
+        ExprKind::IfElse { test, body, orelse } => {
+            visitor.visit_expr(test);
+            visitor.visit_expr(body);
+            visitor.visit_expr(orelse);
+        }
+        ExprKind::List(elements) | ExprKind::Tuple(elements) | ExprKind::Set(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        }
+        ExprKind::Dict(pairs) => {
+            for (key, value) in pairs {
+                visitor.visit_expr(key);
+                visitor.visit_expr(value);
+            }
+        }
+        ExprKind::Slice { lower, upper, step } => {
+            for part in [lower, upper, step].into_iter().flatten() {
+                visitor.visit_expr(part);
+            }
+        }
+        ExprKind::FString(parts) => walk_fstring(visitor, parts),
+        ExprKind::ListComp { elt, generators }
+        | ExprKind::SetComp { elt, generators }
+        | ExprKind::Generator { elt, generators } => {
+            walk_generators(visitor, generators);
+            visitor.visit_expr(elt);
+        }
+        ExprKind::DictComp { key, value, generators } => {
+            walk_generators(visitor, generators);
+            visitor.visit_expr(key);
+            visitor.visit_expr(value);
+        }
+        ExprKind::Lambda { params, body } => {
+            walk_params(visitor, params);
+            visitor.visit_expr(body);
+        }
+    }
+}
+
+/// Visits the expressions inside `target`: the object and index of subscripts and the
+/// object of attributes.
+pub fn walk_target<V: Visitor + ?Sized>(visitor: &mut V, target: &Target) {
+    match target {
+        Target::Name(_) | Target::Starred(_) => {}
+        Target::Tuple { targets, .. } => {
+            for target in targets {
+                visitor.visit_target(target);
+            }
+        }
+        Target::Subscript { object, index, .. } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        }
+        Target::Attribute { object, .. } => visitor.visit_expr(object),
+    }
+}
+
+fn walk_params<V: Visitor + ?Sized>(visitor: &mut V, params: &Parameters) {
+    let all = params
+        .positional_only
+        .iter()
+        .chain(&params.args)
+        .chain(&params.keyword_only);
+    for default in all.filter_map(|param| param.default.as_ref()) {
+        visitor.visit_expr(default);
+    }
+}
+
+fn walk_generators<V: Visitor + ?Sized>(visitor: &mut V, generators: &[Comprehension]) {
+    for generator in generators {
+        visitor.visit_expr(&generator.iter);
+        visitor.visit_target(&generator.target);
+        for condition in &generator.ifs {
+            visitor.visit_expr(condition);
+        }
+    }
+}
+
+fn walk_fstring<V: Visitor + ?Sized>(visitor: &mut V, parts: &[FStringPart]) {
+    for part in parts {
+        if let FStringPart::Interpolation { value, format_spec, .. } = part {
+            visitor.visit_expr(value);
+            if let Some(spec) = format_spec {
+                walk_fstring(visitor, spec);
+            }
+        }
+    }
+}
+
+/// Converts parser output into the public tree, resolving interned names and literals.
+struct Converter<'a> {
+    interns: &'a Interns,
+}
+
+impl Converter<'_> {
+    fn str(&self, id: StringId) -> String {
+        self.interns.get_str(id).to_owned()
+    }
+
+    fn name(&self, ident: Identifier) -> Name {
+        Name {
+            id: self.str(ident.name_id),
+            span: ident.position.into(),
+        }
+    }
+
+    fn either_str(&self, s: EitherStr) -> String {
+        s.as_str(self.interns).to_owned()
+    }
+
+    fn body(&self, nodes: Vec<ParseNode>) -> Vec<Stmt> {
+        nodes.into_iter().map(|node| self.stmt(node)).collect()
+    }
+
+    fn stmt(&self, node: ParseNode) -> Stmt {
+        match node {
+            Node::Pass => Stmt::Pass,
+            Node::Expr(expr) => Stmt::Expr(self.expr(expr)),
+            Node::Return(expr) => Stmt::Return(Some(self.expr(expr))),
+            Node::ReturnNone => Stmt::Return(None),
+            Node::Raise(exc) => Stmt::Raise {
+                exc: exc.map(|exc| self.expr(exc)),
+                cause: None,
+            },
+            Node::RaiseFrom { exc, cause } => Stmt::Raise {
+                exc: Some(self.expr(exc)),
+                cause: Some(self.expr(cause)),
+            },
+            Node::Assert { test, msg } => Stmt::Assert {
+                test: self.expr(test),
+                msg: msg.map(|msg| self.expr(msg)),
+            },
+            Node::Assign { target, object } => Stmt::Assign {
+                target: Target::Name(self.name(target)),
+                value: self.expr(object),
+            },
+            Node::UnpackAssign {
+                targets,
+                targets_position,
+                object,
+            } => Stmt::Assign {
+                target: Target::Tuple {
+                    targets: targets.into_iter().map(|target| self.target(target)).collect(),
+                    span: targets_position.into(),
+                },
+                value: self.expr(object),
+            },
+            Node::OpAssign { target, op, object } => Stmt::AugAssign {
+                target: self.name(target),
+                op: op.into(),
+                value: self.expr(object),
+            },
+            Node::SubscriptAssign {
+                target,
+                index,
+                value,
+                target_position,
+            } => Stmt::Assign {
+                target: Target::Subscript {
+                    object: Expr {
+                        span: target.position.into(),
+                        kind: ExprKind::Name(self.str(target.name_id)),
+                    },
+                    index: self.expr(index),
+                    span: target_position.into(),
+                },
+                value: self.expr(value),
+            },
+            Node::AttrAssign {
+                object,
+                attr,
+                target_position,
+                value,
+            } => Stmt::Assign {
+                target: Target::Attribute {
+                    object: self.expr(object),
+                    attr: self.either_str(attr),
+                    span: target_position.into(),
+                },
+                value: self.expr(value),
+            },
+            Node::For {
+                target,
+                iter,
+                body,
+                or_else,
+            } => Stmt::For {
+                target: self.target(target),
+                iter: self.expr(iter),
+                body: self.body(body),
+                or_else: self.body(or_else),
+            },
+            Node::While { test, body, or_else } => Stmt::While {
+                test: self.expr(test),
+                body: self.body(body),
+                or_else: self.body(or_else),
+            },
+            Node::If { test, body, or_else } => Stmt::If {
+                test: self.expr(test),
+                body: self.body(body),
+                or_else: self.body(or_else),
+            },
+            Node::Break { position } => Stmt::Break(position.into()),
+            Node::Continue { position } => Stmt::Continue(position.into()),
+            Node::FunctionDef(RawFunctionDef {
+                name,
+                signature,
+                body,
+                returns,
+                is_async,
+            }) => Stmt::FunctionDef(FunctionDef {
+                name: self.name(name),
+                params: self.params(signature),
+                returns: returns.map(|returns| returns.text),
+                body: self.body(body),
+                is_async,
+            }),
+            Node::Global { position, names } => Stmt::Global {
+                names: names.into_iter().map(|name| self.str(name)).collect(),
+                span: position.into(),
+            },
+            Node::Nonlocal { position, names } => Stmt::Nonlocal {
+                names: names.into_iter().map(|name| self.str(name)).collect(),
+                span: position.into(),
+            },
+            Node::Try(Try {
+                body,
+                handlers,
+                or_else,
+                finally,
+            }) => Stmt::Try {
+                body: self.body(body),
+                handlers: handlers
+                    .into_iter()
+                    .map(|handler| ExceptHandler {
+                        exc_type: handler.exc_type.map(|exc_type| self.expr(exc_type)),
+                        name: handler.name.map(|name| self.name(name)),
+                        body: self.body(handler.body),
+                    })
+                    .collect(),
+                or_else: self.body(or_else),
+                finally: self.body(finally),
+            },
+            Node::Import { module_name, binding } => Stmt::Import {
+                module: self.str(module_name),
+                alias: self.name(binding),
+            },
+            Node::ImportFrom {
+                module_name,
+                names,
+                position,
+            } => Stmt::ImportFrom {
+                module: self.str(module_name),
+                names: names
+                    .into_iter()
+                    .map(|(name, binding)| (self.str(name), self.name(binding)))
+                    .collect(),
+                span: position.into(),
+            },
+        }
+    }
+
+    fn target(&self, target: UnpackTarget) -> Target {
+        match target {
+            UnpackTarget::Name(ident) => Target::Name(self.name(ident)),
+            UnpackTarget::Starred(ident) => Target::Starred(self.name(ident)),
+            UnpackTarget::Tuple { targets, position } => Target::Tuple {
+                targets: targets.into_iter().map(|target| self.target(target)).collect(),
+                span: position.into(),
+            },
+            UnpackTarget::Subscript {
+                object,
+                index,
+                position,
+            } => Target::Subscript {
+                object: self.expr(object),
+                index: self.expr(index),
+                span: position.into(),
+            },
+            UnpackTarget::Attr { object, attr, position } => Target::Attribute {
+                object: self.expr(object),
+                attr: self.either_str(attr),
+                span: position.into(),
+            },
+        }
+    }
+
+    fn params(&self, signature: ParsedSignature) -> Parameters {
+        let params = |params: Vec<ParsedParam>| {
+            params
+                .into_iter()
+                .map(|param| Param {
+                    name: self.str(param.name),
+                    annotation: param.annotation.map(|annotation| annotation.text),
+                    default: param.default.map(|default| self.expr(default)),
+                })
+                .collect()
+        };
+        Parameters {
+            positional_only: params(signature.pos_args),
+            args: params(signature.args),
+            var_args: signature.var_args.map(|name| self.str(name)),
+            keyword_only: params(signature.kwargs),
+            var_kwargs: signature.var_kwargs.map(|name| self.str(name)),
+        }
+    }
+
+    fn boxed(&self, expr: Box<ExprLoc>) -> Box<Expr> {
+        Box::new(self.expr(*expr))
+    }
+
+    fn exprs(&self, exprs: Vec<ExprLoc>) -> Vec<Expr> {
+        exprs.into_iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn expr(&self, expr_loc: ExprLoc) -> Expr {
+        let span = Span::from(expr_loc.position);
+        let kind = match expr_loc.expr {
+            RawExpr::Literal(literal) => ExprKind::Constant(self.constant(literal)),
+            RawExpr::Name(ident) => ExprKind::Name(self.str(ident.name_id)),
+            RawExpr::Call { callable, args } => {
+                let func = match callable {
+                    Callable::Name(ident) => ExprKind::Name(self.str(ident.name_id)),
+                    Callable::Builtin(_) => unreachable!("builtins are resolved by prepare, not the parser"),
+                };
+                ExprKind::Call {
+                    func: Box::new(Expr { span, kind: func }),
+                    args: self.args(*args),
+                }
+            }
+            RawExpr::AttrCall { object, attr, args } => {
+                let func = ExprKind::Attribute {
+                    value: self.boxed(object),
+                    attr: self.either_str(attr),
+                };
+                ExprKind::Call {
+                    func: Box::new(Expr { span, kind: func }),
+                    args: self.args(*args),
+                }
+            }
+            RawExpr::IndirectCall { callable, args } => ExprKind::Call {
+                func: self.boxed(callable),
+                args: self.args(*args),
+            },
+            RawExpr::AttrGet { object, attr } => ExprKind::Attribute {
+                value: self.boxed(object),
+                attr: self.either_str(attr),
+            },
+            RawExpr::Op { left, op, right } => ExprKind::BinOp {
+                left: self.boxed(left),
+                op: op.into(),
+                right: self.boxed(right),
+            },
+            RawExpr::CmpOp { left, op, right } => ExprKind::Compare {
+                left: self.boxed(left),
+                comparisons: vec![(op.into(), self.expr(*right))],
+            },
+            RawExpr::ChainCmp { left, comparisons } => ExprKind::Compare {
+                left: self.boxed(left),
+                comparisons: comparisons
+                    .into_iter()
+                    .map(|(op, operand)| (op.into(), self.expr(operand)))
+                    .collect(),
+            },
+            RawExpr::List(elements) => ExprKind::List(self.exprs(elements)),
+            RawExpr::Tuple(elements) => ExprKind::Tuple(self.exprs(elements)),
+            RawExpr::Set(elements) => ExprKind::Set(self.exprs(elements)),
+            RawExpr::Dict(pairs) => ExprKind::Dict(
+                pairs
+                    .into_iter()
+                    .map(|(key, value)| (self.expr(key), self.expr(value)))
+                    .collect(),
+            ),
+            RawExpr::Subscript { object, index } => ExprKind::Subscript {
+                value: self.boxed(object),
+                index: self.boxed(index),
+            },
+            RawExpr::Slice { lower, upper, step } => ExprKind::Slice {
+                lower: lower.map(|part| self.boxed(part)),
+                upper: upper.map(|part| self.boxed(part)),
+                step: step.map(|part| self.boxed(part)),
+            },
+            RawExpr::Not(operand) => self.unary(UnaryOp::Not, operand),
+            RawExpr::UnaryMinus(operand) => self.unary(UnaryOp::Minus, operand),
+            RawExpr::UnaryPlus(operand) => self.unary(UnaryOp::Plus, operand),
+            RawExpr::UnaryInvert(operand) => self.unary(UnaryOp::Invert, operand),
+            RawExpr::Await(value) => ExprKind::Await(self.boxed(value)),
+            RawExpr::FString(parts) => ExprKind::FString(self.fstring(parts)),
+            RawExpr::IfElse { test, body, orelse } => ExprKind::IfElse {
+                test: self.boxed(test),
+                body: self.boxed(body),
+                orelse: self.boxed(orelse),
+            },
+            RawExpr::ListComp { elt, generators } => ExprKind::ListComp {
+                elt: self.boxed(elt),
+                generators: self.generators(generators),
+            },
+            RawExpr::SetComp { elt, generators } => ExprKind::SetComp {
+                elt: self.boxed(elt),
+                generators: self.generators(generators),
+            },
+            RawExpr::Generator { elt, generators } => ExprKind::Generator {
+                elt: self.boxed(elt),
+                generators: self.generators(generators),
+            },
+            RawExpr::DictComp { key, value, generators } => ExprKind::DictComp {
+                key: self.boxed(key),
+                value: self.boxed(value),
+                generators: self.generators(generators),
+            },
+            RawExpr::LambdaRaw { signature, body, .. } => ExprKind::Lambda {
+                params: self.params(signature),
+                body: self.boxed(body),
+            },
+            RawExpr::Named { target, value } => ExprKind::Named {
+                target: self.name(target),
+                value: self.boxed(value),
+            },
+            RawExpr::Builtin(_) | RawExpr::Lambda { .. } => {
+                unreachable!("builtins and prepared lambdas are created by prepare, not the parser")
+            }
+        };
+        Expr { span, kind }
+    }
+
+    fn unary(&self, op: UnaryOp, operand: Box<ExprLoc>) -> ExprKind {
+        ExprKind::UnaryOp {
+            op,
+            operand: self.boxed(operand),
+        }
+    }
+
+    fn constant(&self, literal: Literal) -> Constant {
+        match literal {
+            Literal::Ellipsis => Constant::Ellipsis,
+            Literal::None => Constant::None,
+            Literal::Bool(b) => Constant::Bool(b),
+            Literal::Int(i) => Constant::Int(i),
+            Literal::Float(f) => Constant::Float(f),
+            Literal::Str(id) => Constant::Str(self.str(id)),
+            Literal::Bytes(id) => Constant::Bytes(self.interns.get_bytes(id).to_vec()),
+            Literal::LongInt(id) => Constant::BigInt(self.interns.get_long_int(id).clone()),
+            Literal::Marker(_) => unreachable!("markers are created by prepare, not the parser"),
+        }
+    }
+
+    fn args(&self, args: ArgExprs) -> Vec<Arg> {
+        let keyword = |kwarg: Kwarg| Arg::Keyword {
+            name: self.name(kwarg.key),
+            value: self.expr(kwarg.value),
+        };
+        match args {
+            ArgExprs::Empty => Vec::new(),
+            ArgExprs::One(arg) => vec![Arg::Positional(self.expr(arg))],
+            ArgExprs::Two(first, second) => vec![Arg::Positional(self.expr(first)), Arg::Positional(self.expr(second))],
+            ArgExprs::Args(args) => args.into_iter().map(|arg| Arg::Positional(self.expr(arg))).collect(),
+            ArgExprs::Kwargs(kwargs) => kwargs.into_iter().map(keyword).collect(),
+            ArgExprs::ArgsKargs {
+                args,
+                var_args,
+                kwargs,
+                var_kwargs,
+            } => {
+                let mut result: Vec<Arg> = args
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|arg| Arg::Positional(self.expr(arg)))
+                    .collect();
+                result.extend(var_args.map(|arg| Arg::Starred(self.expr(arg))));
+                result.extend(kwargs.unwrap_or_default().into_iter().map(keyword));
+                result.extend(var_kwargs.map(|arg| Arg::DoubleStarred(self.expr(arg))));
+                result
+            }
+        }
+    }
+
+    fn generators(&self, generators: Vec<RawComprehension>) -> Vec<Comprehension> {
+        generators
+            .into_iter()
+            .map(|generator| Comprehension {
+                target: self.target(generator.target),
+                iter: self.expr(generator.iter),
+                ifs: self.exprs(generator.ifs),
+            })
+            .collect()
+    }
+
+    fn fstring(&self, parts: Vec<RawFStringPart>) -> Vec<FStringPart> {
+        parts
+            .into_iter()
+            .map(|part| match part {
+                RawFStringPart::Literal(id) => FStringPart::Literal(self.str(id)),
+                RawFStringPart::Interpolation {
+                    expr,
+                    conversion,
+                    format_spec,
+                    debug_prefix,
+                } => FStringPart::Interpolation {
+                    value: self.expr(*expr),
+                    conversion: match conversion {
+                        ConversionFlag::None => None,
+                        ConversionFlag::Str => Some('s'),
+                        ConversionFlag::Repr => Some('r'),
+                        ConversionFlag::Ascii => Some('a'),
+                    },
+                    format_spec: format_spec.map(|spec| match spec {
+                        FormatSpec::Static(spec) => vec![FStringPart::Literal(render_format_spec(&spec))],
+                        FormatSpec::Dynamic(parts) => self.fstring(parts),
+                    }),
+                    debug: debug_prefix.is_some(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Renders a pre-parsed format spec back to format mini-language text.
+fn render_format_spec(spec: &ParsedFormatSpec) -> String {
+    let mut text = String::new();
+    if let Some(align) = spec.align {
+        if spec.fill != ' ' {
+            text.push(spec.fill);
+        }
+        text.push(align);
+    }
+    text.extend(spec.sign);
+    if spec.zero_pad {
+        text.push('0');
+    }
+    if spec.width > 0 {
+        text.push_str(&spec.width.to_string());
+    }
+    if let Some(precision) = spec.precision {
+        text.push('.');
+        text.push_str(&precision.to_string());
+    }
+    text.extend(spec.type_char);
+    text
+}
+
+impl From<Operator> for BinOp {
+    fn from(op: Operator) -> Self {
+        match op {
+            Operator::Add => Self::Add,
+            Operator::Sub => Self::Sub,
+            Operator::Mult => Self::Mult,
+            Operator::MatMult => Self::MatMult,
+            Operator::Div => Self::Div,
+            Operator::FloorDiv => Self::FloorDiv,
+            Operator::Mod => Self::Mod,
+            Operator::Pow => Self::Pow,
+            Operator::LShift => Self::LShift,
+            Operator::RShift => Self::RShift,
+            Operator::BitOr => Self::BitOr,
+            Operator::BitXor => Self::BitXor,
+            Operator::BitAnd => Self::BitAnd,
+            Operator::And => Self::And,
+            Operator::Or => Self::Or,
+        }
+    }
+}
+
+impl From<CmpOperator> for CmpOp {
+    fn from(op: CmpOperator) -> Self {
+        match op {
+            CmpOperator::Eq => Self::Eq,
+            CmpOperator::NotEq => Self::NotEq,
+            CmpOperator::Lt => Self::Lt,
+            CmpOperator::LtE => Self::LtE,
+            CmpOperator::Gt => Self::Gt,
+            CmpOperator::GtE => Self::GtE,
+            CmpOperator::Is => Self::Is,
+            CmpOperator::IsNot => Self::IsNot,
+            CmpOperator::In => Self::In,
+            CmpOperator::NotIn => Self::NotIn,
+            CmpOperator::ModEq(_) => unreachable!("ModEq is created by prepare, not the parser"),
+        }
+    }
+}
@@ -11,7 +11,7 @@ use crate::{heap::HeapId, intern::FunctionId, value::Value};
 /// Sequential integers allocated by the scheduler. Used to correlate
 /// external function calls with their results when the host resolves them.
 /// The counter always increments, even for sync resolution, to keep IDs unique.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub(crate) struct CallId(u32);
 
 impl CallId {
@@ -0,0 +1,86 @@
+//! Checking the heap for leaked references after a run.
+//!
+//! Every heap entry is freed when its reference count drops to zero, so a code path that
+//! clones a value without dropping it keeps the entry alive for the rest of the run. Such
+//! leaks don't change results, which makes them easy to miss. `MontyRun::run_audited()`
+//! compares the reference count of every live entry with the references the globals and
+//! other live entries actually hold, and [`HeapAudit::assert_no_leaks`] fails a test when
+//! they differ.
+//!
+//! Only available when the `heap-audit` feature is enabled.
+
+use std::{collections::BTreeMap, fmt};
+
+/// The live entries of a heap after a run, made by `MontyRun::run_audited()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapAudit {
+    /// Number of live heap entries, not counting the empty tuple singleton.
+    pub live_objects: usize,
+    /// Number of live heap entries of each type, keyed by type name.
+    pub objects_by_type: BTreeMap<String, usize>,
+    /// Sum of the reference counts of the live entries.
+    pub refcount_total: usize,
+    /// Number of references to live entries held by globals and by other live entries.
+    ///
+    /// Equal to `refcount_total` when no reference has leaked.
+    pub reference_total: usize,
+    /// Entries that no global refers to, directly or through other entries.
+    ///
+    /// Reference cycles the garbage collector hasn't freed yet show up here with matching
+    /// counts; entries kept alive by a leaked reference also appear in `miscounted`.
+    pub unreachable: Vec<AuditedEntry>,
+    /// Entries whose reference count differs from the references actually held.
+    pub miscounted: Vec<AuditedEntry>,
+}
+
+/// A heap entry reported by a [`HeapAudit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditedEntry {
+    /// Slot index of the entry in the heap.
+    pub index: usize,
+    /// Python type name of the value, e.g. `"list"`.
+    pub type_name: String,
+    /// The entry's reference count.
+    pub refcount: usize,
+    /// Number of references to the entry held by globals and by other live entries.
+    pub references: usize,
+}
+
+impl HeapAudit {
+    /// Returns true if every live entry's reference count matches its references.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.miscounted.is_empty()
+    }
+
+    /// Panics, listing the affected entries, if any reference count doesn't match the
+    /// references actually held.
+    ///
+    /// Unreachable cycles with matching counts are not leaks, since the garbage collector
+    /// frees them, so they don't fail the check.
+    #[track_caller]
+    pub fn assert_no_leaks(&self) {
+        assert!(self.is_clean(), "heap has miscounted references:\n{self}");
+    }
+}
+
+impl fmt::Display for HeapAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} live objects, refcount total {}, {} references held, {} unreachable",
+            self.live_objects,
+            self.refcount_total,
+            self.reference_total,
+            self.unreachable.len()
+        )?;
+        for entry in &self.miscounted {
+            writeln!(
+                f,
+                "  entry {} ({}): refcount {}, {} references held",
+                entry.index, entry.type_name, entry.refcount, entry.references
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Depth- and length-bounded `repr()` and `str()` of values.
+//!
+//! A plain `repr()` of a huge or deeply nested container builds the whole string before
+//! anything can look at its size, so quoting such a value in an error message or printing
+//! it can use up the time and memory budget of a run on text nobody will read. The bounded
+//! engine stops writing as soon as the output reaches its length limit and writes nested
+//! containers below its depth limit as `...`, like Python's `reprlib`.
+
+use std::fmt::{self, Write};
+
+use ahash::AHashSet;
+
+use crate::{
+    heap::{Heap, HeapData},
+    intern::Interns,
+    resource::{DepthGuard, ResourceTracker},
+    types::PyTrait,
+    value::Value,
+};
+
+/// Marker ending output cut short at its length limit.
+const ELLIPSIS: &str = "...";
+
+/// How much of a value a bounded `repr()` or `str()` writes.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReprLimits {
+    /// Maximum length of the output in characters, including the `...` ending it when cut short.
+    pub max_len: usize,
+    /// Maximum nesting of containers, deeper ones being written as `...`; `None` leaves
+    /// nesting to the usual limits.
+    pub max_depth: Option<u16>,
+}
+
+impl ReprLimits {
+    /// Limits for values quoted in exception messages, such as the key of a `KeyError`.
+    pub const ERROR_MESSAGE: Self = Self {
+        max_len: 1_000,
+        max_depth: Some(16),
+    };
+
+    /// Limits for each container written by `print()`; strings and other values are
+    /// printed in full, as they are already held in memory.
+    pub const PRINT: Self = Self {
+        max_len: 1 << 20,
+        max_depth: None,
+    };
+
+    /// Returns the guard enforcing `max_depth`.
+    fn depth_guard(self) -> DepthGuard {
+        self.max_depth.map_or_else(DepthGuard::default, DepthGuard::with_limit)
+    }
+}
+
+/// Returns the `repr()` of `value`, cut short at `limits`.
+#[must_use]
+pub(crate) fn bounded_repr(
+    value: &Value,
+    heap: &Heap<impl ResourceTracker>,
+    interns: &Interns,
+    limits: ReprLimits,
+) -> String {
+    let mut guard = limits.depth_guard();
+    let mut heap_ids = AHashSet::new();
+    write_bounded(limits.max_len, |f| {
+        value.py_repr_fmt(f, heap, &mut heap_ids, &mut guard, interns)
+    })
+}
+
+/// Returns the `str()` of `value`, cut short at `limits`.
+///
+/// Containers are written with the bounded `repr()` of their items; any other value's
+/// `str()` is already held by the value, so it is only cut to length.
+#[must_use]
+pub(crate) fn bounded_str(
+    value: &Value,
+    heap: &Heap<impl ResourceTracker>,
+    interns: &Interns,
+    limits: ReprLimits,
+) -> String {
+    if let Some(repr) = bounded_container_repr(value, heap, interns, limits) {
+        return repr;
+    }
+    let mut guard = limits.depth_guard();
+    let s = value.py_str(heap, &mut guard, interns);
+    write_bounded(limits.max_len, |f| f.write_str(&s))
+}
+
+/// Returns the `repr()` of `value`, cut short at `limits`, if it is a container whose
+/// `str()` is built from the `repr()` of its items, or `None` for any other value.
+#[must_use]
+pub(crate) fn bounded_container_repr(
+    value: &Value,
+    heap: &Heap<impl ResourceTracker>,
+    interns: &Interns,
+    limits: ReprLimits,
+) -> Option<String> {
+    is_container(value, heap).then(|| bounded_repr(value, heap, interns, limits))
+}
+
+/// Runs `write` into a string holding at most `max_len` characters, ending the string with
+/// `...` if `write` had more to say.
+///
+/// `write` is stopped with `fmt::Error` as soon as it goes over the limit, so it must pass
+/// errors from the writer on rather than retrying or ignoring them.
+#[must_use]
+pub(crate) fn write_bounded(max_len: usize, write: impl FnOnce(&mut BoundedWriter) -> fmt::Result) -> String {
+    let mut writer = BoundedWriter::new(max_len);
+    // an error either comes from going over the limit, which `truncated` records, or from
+    // a repr implementation giving up, after which what was written is all there is
+    let _ = write(&mut writer);
+    writer.finish()
+}
+
+/// `fmt::Write` sink that accepts a limited number of characters, then fails every write.
+pub(crate) struct BoundedWriter {
+    out: String,
+    remaining: usize,
+    max_len: usize,
+    truncated: bool,
+}
+
+impl BoundedWriter {
+    fn new(max_len: usize) -> Self {
+        Self {
+            out: String::new(),
+            remaining: max_len,
+            max_len,
+            truncated: false,
+        }
+    }
+
+    /// Returns the output, making room for `...` at its end if it was cut short.
+    fn finish(mut self) -> String {
+        if self.truncated {
+            let keep = self.max_len.saturating_sub(ELLIPSIS.len());
+            if let Some((cut, _)) = self.out.char_indices().nth(keep) {
+                self.out.truncate(cut);
+            }
+            self.out.push_str(ELLIPSIS);
+        }
+        self.out
+    }
+}
+
+impl Write for BoundedWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Err(fmt::Error);
+        }
+        // a string of no more bytes than the remaining characters always fits
+        if s.len() <= self.remaining {
+            self.out.push_str(s);
+            self.remaining -= s.chars().count();
+            return Ok(());
+        }
+        match s.char_indices().nth(self.remaining) {
+            None => {
+                self.out.push_str(s);
+                self.remaining -= s.chars().count();
+                Ok(())
+            }
+            Some((cut, _)) => {
+                self.out.push_str(&s[..cut]);
+                self.remaining = 0;
+                self.truncated = true;
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Whether `value` is a container whose `str()` is built from the `repr()` of its items.
+fn is_container(value: &Value, heap: &Heap<impl ResourceTracker>) -> bool {
+    let Value::Ref(id) = value else {
+        return false;
+    };
+    matches!(
+        heap.get(*id),
+        HeapData::List(_)
+            | HeapData::Tuple(_)
+            | HeapData::NamedTuple(_)
+            | HeapData::Dict(_)
+            | HeapData::Set(_)
+            | HeapData::FrozenSet(_)
+            | HeapData::Dataclass(_)
+    )
+}
@@ -0,0 +1,394 @@
+//! Configuring a run in one place instead of through `MontyRun::new` and per-call arguments.
+//!
+//! `MontyRun::new` takes the code, script name, input names and external function names
+//! positionally, while compiler options, limits, the print sink and trace hooks are each
+//! passed somewhere else. [`MontyRunBuilder`] collects all of them; `build()` compiles the
+//! code, composes the tracker each run gets, and returns a [`ConfiguredRun`] that applies
+//! the same limits, output, tracing and determinism to every run.
+
+use std::fmt;
+
+use crate::{
+    deterministic::DeterministicTracker,
+    exception_public::MontyException,
+    io::PrintWriter,
+    object::MontyObject,
+    resource::{LimitedTracker, NoLimitTracker, ResourceLimits, ResourceTracker},
+    run::{CompileOptions, MontyRun, RunProgress},
+    shared_interns::SharedInterns,
+    trace::{TraceHook, TracingTracker},
+};
+
+/// Builds a [`ConfiguredRun`], created with [`MontyRun::builder`].
+///
+/// Each setting is stored on its own and the tracker for a run is only put together in
+/// `build()`, so settings can be given in any order. The type parameters record them:
+/// `T` is the base tracker each run gets, `NoLimitTracker` until `limits()` or `tracker()`
+/// is called; `H` is the [`TraceLayer`] set by `trace()`; and `D` is the
+/// [`DeterministicLayer`] set by `deterministic()`. Layers that weren't set are [`NoLayer`].
+///
+/// # Example
+/// ```
+/// use monty::{MontyObject, MontyRun, PrintWriter, ResourceLimits};
+///
+/// let mut run = MontyRun::builder("print(greeting, name)\nlen(name)")
+///     .script_name("greet.py")
+///     .inputs(["name"])
+///     .globals([("greeting", MontyObject::from("hello"))])
+///     .allow_builtins(["print", "len"])
+///     .limits(ResourceLimits::new().max_allocations(100))
+///     .print(PrintWriter::Collect(String::new()))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(run.run(vec![MontyObject::from("monty")]), Ok(MontyObject::Int(5)));
+/// assert_eq!(run.collected_output(), Some("hello monty\n"));
+/// ```
+pub struct MontyRunBuilder<'a, T: ResourceTracker = NoLimitTracker, H = NoLayer, D = NoLayer> {
+    code: String,
+    script_name: String,
+    input_names: Vec<String>,
+    external_functions: Vec<String>,
+    options: CompileOptions,
+    globals: Vec<(String, MontyObject)>,
+    new_tracker: Box<dyn FnMut() -> T + 'a>,
+    trace: H,
+    deterministic: D,
+    print: PrintWriter<'a>,
+}
+
+impl MontyRunBuilder<'static> {
+    /// Starts configuring a run of `code`, named `main.py`, with no inputs, no external
+    /// functions, default compiler options, no limits and print output discarded.
+    pub(crate) fn new(code: String) -> Self {
+        Self {
+            code,
+            script_name: "main.py".to_owned(),
+            input_names: Vec::new(),
+            external_functions: Vec::new(),
+            options: CompileOptions::default(),
+            globals: Vec::new(),
+            new_tracker: Box::new(|| NoLimitTracker),
+            trace: NoLayer,
+            deterministic: NoLayer,
+            print: PrintWriter::Disabled,
+        }
+    }
+}
+
+impl<'a, T: ResourceTracker, H, D> MontyRunBuilder<'a, T, H, D> {
+    /// Sets the script name used in tracebacks and error messages.
+    #[must_use]
+    pub fn script_name(mut self, script_name: impl Into<String>) -> Self {
+        self.script_name = script_name.into();
+        self
+    }
+
+    /// Declares the names of the inputs, in the order their values are passed to `run()`.
+    #[must_use]
+    pub fn inputs(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.input_names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Declares the names of the functions the host implements.
+    #[must_use]
+    pub fn external_functions(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.external_functions = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Replaces all compiler options at once.
+    #[must_use]
+    pub fn options(mut self, options: CompileOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets whether the code is optimized, see [`CompileOptions::optimize()`].
+    #[must_use]
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.options = self.options.optimize(optimize);
+        self
+    }
+
+    /// Restricts the code to the given builtins, see [`CompileOptions::allow_builtins`].
+    #[must_use]
+    pub fn allow_builtins(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options = self.options.allow_builtins(names);
+        self
+    }
+
+    /// Forbids the given builtins, see [`CompileOptions::deny_builtins`].
+    #[must_use]
+    pub fn deny_builtins(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options = self.options.deny_builtins(names);
+        self
+    }
+
+    /// Checks external calls against annotated stubs, see [`CompileOptions::check_external_calls`].
+    #[must_use]
+    pub fn check_external_calls(mut self, stubs: impl Into<String>) -> Self {
+        self.options = self.options.check_external_calls(stubs);
+        self
+    }
+
+    /// Batches calls to the given external functions, see [`CompileOptions::batch_external_calls`].
+    #[must_use]
+    pub fn batch_external_calls(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options = self.options.batch_external_calls(names);
+        self
+    }
+
+    /// Makes external functions host iterables, see [`CompileOptions::host_iterables`].
+    #[must_use]
+    pub fn host_iterables(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options = self.options.host_iterables(names);
+        self
+    }
+
+    /// Shares constants with other programs, see [`CompileOptions::share_interns`].
+    #[must_use]
+    pub fn share_interns(mut self, shared: SharedInterns) -> Self {
+        self.options = self.options.share_interns(shared);
+        self
+    }
+
+    /// Sets module-level variables before every run, see [`MontyRun::with_globals`].
+    ///
+    /// Calling this again adds to the previous globals.
+    #[must_use]
+    pub fn globals(mut self, globals: impl IntoIterator<Item = (impl Into<String>, MontyObject)>) -> Self {
+        self.globals
+            .extend(globals.into_iter().map(|(name, value)| (name.into(), value)));
+        self
+    }
+
+    /// Applies `limits` to each run, replacing any tracker set earlier.
+    ///
+    /// A trace hook or `deterministic()` set before or after is kept.
+    #[must_use]
+    pub fn limits(self, limits: ResourceLimits) -> MontyRunBuilder<'a, LimitedTracker, H, D> {
+        self.tracker(move || LimitedTracker::new(limits.clone()))
+    }
+
+    /// Gives each run a tracker made by `new_tracker`, replacing any limits set earlier.
+    ///
+    /// The tracker is wrapped in the trace hook and determinism layers in `build()`, so
+    /// those are kept whether they were set before or after.
+    #[must_use]
+    pub fn tracker<U: ResourceTracker>(self, new_tracker: impl FnMut() -> U + 'a) -> MontyRunBuilder<'a, U, H, D> {
+        MontyRunBuilder {
+            code: self.code,
+            script_name: self.script_name,
+            input_names: self.input_names,
+            external_functions: self.external_functions,
+            options: self.options,
+            globals: self.globals,
+            new_tracker: Box::new(new_tracker),
+            trace: self.trace,
+            deterministic: self.deterministic,
+            print: self.print,
+        }
+    }
+
+    /// Reports trace events from each run to a clone of `hook`, replacing any hook set
+    /// earlier.
+    ///
+    /// Hooks that collect events for the host should share their storage between clones,
+    /// e.g. through an `Arc<Mutex<_>>`.
+    #[must_use]
+    pub fn trace<G: TraceHook + Clone>(self, hook: G) -> MontyRunBuilder<'a, T, TraceLayer<G>, D> {
+        MontyRunBuilder {
+            code: self.code,
+            script_name: self.script_name,
+            input_names: self.input_names,
+            external_functions: self.external_functions,
+            options: self.options,
+            globals: self.globals,
+            new_tracker: self.new_tracker,
+            trace: TraceLayer(hook),
+            deterministic: self.deterministic,
+            print: self.print,
+        }
+    }
+
+    /// Makes each run deterministic: the `time` module reads a frozen clock and `resources()`
+    /// reports no time budget, so runs with the same inputs and external call results give
+    /// the same results and byte-identical snapshots. See [`DeterministicTracker`].
+    #[must_use]
+    pub fn deterministic(self) -> MontyRunBuilder<'a, T, H, DeterministicLayer> {
+        MontyRunBuilder {
+            code: self.code,
+            script_name: self.script_name,
+            input_names: self.input_names,
+            external_functions: self.external_functions,
+            options: self.options,
+            globals: self.globals,
+            new_tracker: self.new_tracker,
+            trace: self.trace,
+            deterministic: DeterministicLayer,
+            print: self.print,
+        }
+    }
+
+    /// Sets where `print()` output goes. Output is discarded by default.
+    #[must_use]
+    pub fn print<'b>(self, print: PrintWriter<'b>) -> MontyRunBuilder<'b, T, H, D>
+    where
+        'a: 'b,
+    {
+        MontyRunBuilder {
+            code: self.code,
+            script_name: self.script_name,
+            input_names: self.input_names,
+            external_functions: self.external_functions,
+            options: self.options,
+            globals: self.globals,
+            new_tracker: self.new_tracker,
+            trace: self.trace,
+            deterministic: self.deterministic,
+            print,
+        }
+    }
+
+    /// Compiles the code with the configured options.
+    ///
+    /// Each run's tracker is the base tracker wrapped in the trace hook layer, then in the
+    /// determinism layer.
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the code can't be compiled, e.g. because of a syntax
+    /// error or a builtin the options don't allow.
+    pub fn build(self) -> Result<ConfiguredRun<'a, D::Tracker>, MontyException>
+    where
+        H: TrackerLayer<T> + 'a,
+        D: TrackerLayer<H::Tracker> + 'a,
+    {
+        let runner = MontyRun::new_with_options(
+            self.code,
+            &self.script_name,
+            self.input_names,
+            self.external_functions,
+            self.options,
+        )?
+        .with_globals(self.globals);
+        let (mut new_base, mut trace, mut deterministic) = (self.new_tracker, self.trace, self.deterministic);
+        Ok(ConfiguredRun {
+            runner,
+            new_tracker: Box::new(move || deterministic.wrap(trace.wrap(new_base()))),
+            print: self.print,
+        })
+    }
+}
+
+impl<T: ResourceTracker, H, D> fmt::Debug for MontyRunBuilder<'_, T, H, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MontyRunBuilder")
+            .field("script_name", &self.script_name)
+            .field("input_names", &self.input_names)
+            .field("external_functions", &self.external_functions)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A wrapper [`MontyRunBuilder::build`] puts around each run's base tracker.
+pub trait TrackerLayer<T: ResourceTracker> {
+    /// The tracker with this layer applied.
+    type Tracker: ResourceTracker;
+
+    /// Wraps `inner` in this layer.
+    fn wrap(&mut self, inner: T) -> Self::Tracker;
+}
+
+/// A layer that wasn't configured, leaving the tracker unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoLayer;
+
+impl<T: ResourceTracker> TrackerLayer<T> for NoLayer {
+    type Tracker = T;
+
+    fn wrap(&mut self, inner: T) -> T {
+        inner
+    }
+}
+
+/// The layer set by [`MontyRunBuilder::trace`], reporting trace events to a clone of the hook.
+#[derive(Debug, Clone)]
+pub struct TraceLayer<G>(G);
+
+impl<T: ResourceTracker, G: TraceHook + Clone> TrackerLayer<T> for TraceLayer<G> {
+    type Tracker = TracingTracker<T, G>;
+
+    fn wrap(&mut self, inner: T) -> Self::Tracker {
+        TracingTracker::new(inner, self.0.clone())
+    }
+}
+
+/// The layer set by [`MontyRunBuilder::deterministic`], see [`DeterministicTracker`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeterministicLayer;
+
+impl<T: ResourceTracker> TrackerLayer<T> for DeterministicLayer {
+    type Tracker = DeterministicTracker<T>;
+
+    fn wrap(&mut self, inner: T) -> Self::Tracker {
+        DeterministicTracker::new(inner)
+    }
+}
+
+/// A compiled program together with the limits, trace hook and print sink to run it with.
+///
+/// Every run gets a new tracker, so limits apply to each run separately, and all runs
+/// print to the same sink.
+pub struct ConfiguredRun<'a, T: ResourceTracker> {
+    runner: MontyRun,
+    new_tracker: Box<dyn FnMut() -> T + 'a>,
+    print: PrintWriter<'a>,
+}
+
+impl<'a, T: ResourceTracker> ConfiguredRun<'a, T> {
+    /// Executes the code to completion, like [`MontyRun::run`].
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the inputs are invalid or the code raises.
+    pub fn run(&mut self, inputs: Vec<MontyObject>) -> Result<MontyObject, MontyException> {
+        self.runner.run(inputs, (self.new_tracker)(), &mut self.print)
+    }
+
+    /// Starts executing the code, pausing at external calls, like [`MontyRun::start`].
+    ///
+    /// Pass `print_mut()` when resuming so the rest of the run prints to the same sink.
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the inputs are invalid or the code raises.
+    pub fn start(&mut self, inputs: Vec<MontyObject>) -> Result<RunProgress<T>, MontyException> {
+        self.runner.clone().start(inputs, (self.new_tracker)(), &mut self.print)
+    }
+
+    /// Returns the compiled runner, e.g. to serialize it or create a `Pool`.
+    #[must_use]
+    pub fn runner(&self) -> &MontyRun {
+        &self.runner
+    }
+
+    /// Returns the print sink.
+    pub fn print_mut(&mut self) -> &mut PrintWriter<'a> {
+        &mut self.print
+    }
+
+    /// Returns the output collected so far if the print sink is `PrintWriter::Collect`.
+    #[must_use]
+    pub fn collected_output(&self) -> Option<&str> {
+        self.print.collected_output()
+    }
+}
+
+impl<T: ResourceTracker> fmt::Debug for ConfiguredRun<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfiguredRun")
+            .field("runner", &self.runner)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,27 @@
+//! Implementation of the emit() builtin function.
+
+use crate::{
+    args::ArgValues,
+    exception_private::{ExcType, RunResult},
+    heap::{DropWithHeap, Heap},
+    resource::ResourceTracker,
+    value::Value,
+};
+
+/// Implementation of the emit() builtin function.
+///
+/// Monty-specific: streams one value to the host. The VM handles direct calls itself and
+/// suspends with the value, so the host can consume results one at a time (e.g. row by row)
+/// while the script keeps only the current one in memory. This function only unpacks the
+/// argument; the host's resume value becomes the call's return value.
+pub fn builtin_emit(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<Value> {
+    args.get_one_arg("emit", heap)
+}
+
+/// Handles `emit` called from inside another builtin (e.g. `map(emit, rows)`).
+///
+/// There is no VM to suspend there, so this raises `NotImplementedError`.
+pub fn builtin_emit_indirect(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<Value> {
+    args.drop_with_heap(heap);
+    Err(ExcType::not_implemented("emit() can only be called directly from Python code").into())
+}
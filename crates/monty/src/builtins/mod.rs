@@ -9,6 +9,7 @@ mod any;
 mod bin;
 mod chr;
 mod divmod;
+mod emit;
 mod enumerate;
 mod hash;
 mod hex;
@@ -23,6 +24,7 @@ mod ord;
 mod pow;
 mod print;
 mod repr;
+mod resources;
 mod reversed;
 mod round;
 mod sorted;
@@ -30,9 +32,14 @@ mod sum;
 mod type_;
 mod zip;
 
-use std::{fmt::Write, str::FromStr};
+use std::{fmt::Write, str::FromStr, sync::LazyLock};
 
-use strum::{Display, EnumString, FromRepr, IntoStaticStr};
+use ahash::AHashMap;
+
+use strum::{Display, EnumString, FromRepr, IntoEnumIterator, IntoStaticStr};
+
+pub(crate) use emit::builtin_emit;
+pub(crate) use resources::builtin_resources;
 
 use crate::{
     args::ArgValues,
@@ -100,16 +107,35 @@ impl Builtins {
     }
 }
 
+/// Process-wide lookup table from builtin function and exception names to their `Builtins` value.
+///
+/// Built once on first use and shared by every `MontyRun::new`, so resolving a global name
+/// during preparation is a single hash lookup rather than a scan of every strum variant.
+static BUILTINS_MAP: LazyLock<AHashMap<&'static str, Builtins>> = LazyLock::new(|| {
+    let mut map = AHashMap::new();
+    // Priority: BuiltinsFunctions > ExcType, so functions are inserted first
+    for function in (0..=u8::MAX).filter_map(BuiltinsFunctions::from_repr) {
+        map.insert(function.into(), Builtins::Function(function));
+    }
+    for exc_type in ExcType::iter() {
+        map.entry(exc_type.into()).or_insert(Builtins::ExcType(exc_type));
+    }
+    map
+});
+
+/// Forces initialization of the process-wide builtins table.
+pub(crate) fn warm_up() {
+    LazyLock::force(&BUILTINS_MAP);
+}
+
 impl FromStr for Builtins {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Priority: BuiltinsFunctions > ExcType > Type
         // Only matches names that are true Python builtins (accessible without imports).
-        if let Ok(b) = BuiltinsFunctions::from_str(s) {
-            Ok(Self::Function(b))
-        } else if let Ok(exc) = ExcType::from_str(s) {
-            Ok(Self::ExcType(exc))
+        if let Some(builtin) = BUILTINS_MAP.get(s) {
+            Ok(*builtin)
         } else if let Some(t) = Type::from_builtin_name(s) {
             Ok(Self::Type(t))
         } else {
@@ -164,6 +190,8 @@ pub enum BuiltinsFunctions {
     // dict - handled by Type enum
     // Dir,
     Divmod,
+    /// Monty-specific: streams a value to the host.
+    Emit,
     Enumerate,
     // Eval,
     // Exec,
@@ -200,6 +228,8 @@ pub enum BuiltinsFunctions {
     // Property,
     // range - handled by Type enum
     Repr,
+    /// Monty-specific: reports the remaining resource budget.
+    Resources,
     Reversed,
     Round,
     // set - handled by Type enum
@@ -236,6 +266,7 @@ impl BuiltinsFunctions {
             Self::Bin => bin::builtin_bin(heap, args),
             Self::Chr => chr::builtin_chr(heap, args),
             Self::Divmod => divmod::builtin_divmod(heap, args),
+            Self::Emit => emit::builtin_emit_indirect(heap, args),
             Self::Enumerate => enumerate::builtin_enumerate(heap, args, interns),
             Self::Hash => hash::builtin_hash(heap, args, interns),
             Self::Hex => hex::builtin_hex(heap, args),
@@ -251,6 +282,7 @@ impl BuiltinsFunctions {
             Self::Pow => pow::builtin_pow(heap, args),
             Self::Print => print::builtin_print(heap, args, interns, print_writer),
             Self::Repr => repr::builtin_repr(heap, args, interns),
+            Self::Resources => resources::builtin_resources(heap, args, interns, None),
             Self::Reversed => reversed::builtin_reversed(heap, args, interns),
             Self::Round => round::builtin_round(heap, args),
             Self::Sorted => sorted::builtin_sorted(heap, args, interns),
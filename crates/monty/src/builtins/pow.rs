@@ -10,13 +10,14 @@ use crate::{
     heap::{Heap, HeapData},
     resource::{ResourceTracker, check_pow_size},
     types::{LongInt, PyTrait},
-    value::Value,
+    value::{Value, extract_bigint},
 };
 
 /// Implementation of the pow() builtin function.
 ///
 /// Returns base to the power exp. With three arguments, returns (base ** exp) % mod.
-/// Handles negative exponents by returning a float.
+/// Handles negative exponents by returning a float, or with three arguments by
+/// using the modular inverse of the base.
 pub fn builtin_pow(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<Value> {
     // pow() accepts 2 or 3 arguments
     let positional = args.into_pos_only("pow", heap)?;
@@ -32,33 +33,7 @@ pub fn builtin_pow(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> Ru
             let base = normalize_bool(base);
             let exp = normalize_bool(exp);
             let m = normalize_bool(m);
-            // Three-argument pow: modular exponentiation
-            match (base, exp, m) {
-                (Value::Int(b), Value::Int(e), Value::Int(m_val)) => {
-                    if *m_val == 0 {
-                        Err(SimpleException::new_msg(ExcType::ValueError, "pow() 3rd argument cannot be 0").into())
-                    } else if *e < 0 {
-                        Err(SimpleException::new_msg(
-                            ExcType::ValueError,
-                            "pow() 2nd argument cannot be negative when 3rd argument specified",
-                        )
-                        .into())
-                    } else {
-                        // Use modular exponentiation
-                        let result = mod_pow(
-                            *b,
-                            u64::try_from(*e).expect("pow exponent >= 0 but failed u64 conversion"),
-                            *m_val,
-                        );
-                        Ok(Value::Int(result))
-                    }
-                }
-                _ => Err(SimpleException::new_msg(
-                    ExcType::TypeError,
-                    "pow() 3rd argument not allowed unless all arguments are integers",
-                )
-                .into()),
-            }
+            three_arg_pow(base, exp, m, heap)
         }
         args => Err(SimpleException::new_msg(
             ExcType::TypeError,
@@ -82,6 +57,45 @@ fn normalize_bool(value: &Value) -> &Value {
     }
 }
 
+/// Implements three-argument pow: modular exponentiation without computing `base ** exp`.
+///
+/// Operands that all fit in i64 with a non-negative exponent take the `mod_pow` fast path.
+/// Otherwise the computation uses `BigInt`, where a negative exponent raises the modular
+/// inverse of the base to the absolute exponent, as CPython does.
+fn three_arg_pow(base: &Value, exp: &Value, m: &Value, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+    if let (Value::Int(b), Value::Int(e), Value::Int(m_val)) = (base, exp, m)
+        && *e >= 0
+        && *m_val != 0
+    {
+        let e = u64::try_from(*e).expect("pow exponent >= 0 but failed u64 conversion");
+        return Ok(Value::Int(mod_pow(*b, e, *m_val)));
+    }
+
+    let (Some(b), Some(e), Some(m)) = (
+        extract_bigint(base, heap),
+        extract_bigint(exp, heap),
+        extract_bigint(m, heap),
+    ) else {
+        return Err(SimpleException::new_msg(
+            ExcType::TypeError,
+            "pow() 3rd argument not allowed unless all arguments are integers",
+        )
+        .into());
+    };
+    if m.is_zero() {
+        return Err(SimpleException::new_msg(ExcType::ValueError, "pow() 3rd argument cannot be 0").into());
+    }
+    let b = if e.is_negative() {
+        b.modinv(&m).ok_or_else(|| {
+            SimpleException::new_msg(ExcType::ValueError, "base is not invertible for the given modulus")
+        })?
+    } else {
+        b
+    };
+    // modpow rounds like floor division, so the result takes the sign of the modulus
+    Ok(LongInt::new(b.modpow(&e.abs(), &m)).into_value(heap)?)
+}
+
 /// Computes (base^exp) % modulo using binary exponentiation.
 ///
 /// Handles negative bases correctly using Python's modulo semantics.
@@ -2,6 +2,7 @@
 
 use crate::{
     args::{ArgValues, KwargsValues},
+    bounded_repr::{ReprLimits, bounded_container_repr},
     defer_drop,
     exception_private::{ExcType, RunError, RunResult, SimpleException},
     heap::{Heap, HeapData},
@@ -44,7 +45,12 @@ pub fn builtin_print(
         } else {
             print.stdout_push(' ')?;
         }
-        print.stdout_write(value.py_str(heap, &mut guard, interns))?;
+        // containers are bounded so a huge one can't flood the output; anything else is
+        // written in full, like CPython
+        match bounded_container_repr(value, heap, interns, ReprLimits::PRINT) {
+            Some(repr) => print.stdout_write(repr.into())?,
+            None => print.stdout_write(value.py_str(heap, &mut guard, interns))?,
+        }
     }
 
     // Append end string
@@ -0,0 +1,50 @@
+//! Implementation of the resources() builtin function.
+
+use crate::{
+    args::ArgValues,
+    exception_private::{ExcType, RunResult},
+    heap::{Heap, HeapData},
+    intern::{Interns, StaticStrings},
+    resource::ResourceTracker,
+    types::Dict,
+    value::Value,
+};
+
+/// Implementation of the resources() builtin function.
+///
+/// Monty-specific: returns a dict of the remaining budget so scripts can adapt their work
+/// (e.g. process smaller batches) instead of being killed by a limit. Keys are `memory`
+/// (bytes), `allocations`, `time` (seconds, as a float) and `instructions` (remaining fuel),
+/// each `None` when that resource is unlimited.
+///
+/// `fuel` is owned by the VM rather than the tracker, so the VM passes it in; callers
+/// without access to it pass `None`. If the tracker hides the budget, this raises the same
+/// `NameError` as an undefined name, so scripts can't distinguish a hidden budget from an
+/// interpreter without the builtin.
+pub fn builtin_resources(
+    heap: &mut Heap<impl ResourceTracker>,
+    args: ArgValues,
+    interns: &Interns,
+    fuel: Option<u64>,
+) -> RunResult<Value> {
+    args.check_zero_args("resources", heap)?;
+    let Some(budget) = heap.tracker().remaining_budget() else {
+        return Err(ExcType::name_error("resources").into());
+    };
+
+    let int_or_none = |n: Option<usize>| n.map_or(Value::None, |n| Value::Int(i64::try_from(n).unwrap_or(i64::MAX)));
+    let pairs = vec![
+        (StaticStrings::Memory.into(), int_or_none(budget.memory)),
+        (StaticStrings::Allocations.into(), int_or_none(budget.allocations)),
+        (
+            StaticStrings::Time.into(),
+            budget.time.map_or(Value::None, |t| Value::Float(t.as_secs_f64())),
+        ),
+        (
+            StaticStrings::Instructions.into(),
+            fuel.map_or(Value::None, |f| Value::Int(i64::try_from(f).unwrap_or(i64::MAX))),
+        ),
+    ];
+    let dict = Dict::from_pairs(pairs, heap, interns)?;
+    Ok(Value::Ref(heap.allocate(HeapData::Dict(dict))?))
+}
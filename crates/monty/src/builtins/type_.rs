@@ -2,15 +2,24 @@
 
 use super::Builtins;
 use crate::{
-    args::ArgValues, defer_drop, exception_private::RunResult, heap::Heap, resource::ResourceTracker, types::PyTrait,
+    args::ArgValues,
+    defer_drop,
+    exception_private::RunResult,
+    heap::Heap,
+    resource::ResourceTracker,
+    types::{PyTrait, Type},
     value::Value,
 };
 
 /// Implementation of the type() builtin function.
 ///
-/// Returns the type of an object.
+/// Returns the type of an object. Exception types are returned as the exception class
+/// itself, so `type(e) is ValueError` holds and `type(e)('msg')` builds a new instance.
 pub fn builtin_type(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<Value> {
     let value = args.get_one_arg("type", heap)?;
     defer_drop!(value, heap);
-    Ok(Value::Builtin(Builtins::Type(value.py_type(heap))))
+    Ok(match value.py_type(heap) {
+        Type::Exception(exc_type) => Value::Builtin(Builtins::ExcType(exc_type)),
+        t => Value::Builtin(Builtins::Type(t)),
+    })
 }
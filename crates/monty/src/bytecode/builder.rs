@@ -6,7 +6,7 @@
 use std::collections::HashSet;
 
 use super::{
-    code::{Code, ConstPool, ExceptionEntry, LocationEntry},
+    code::{Code, ConstPool, ExceptionEntry, LocationEntry, LocationTable},
     op::Opcode,
 };
 use crate::{intern::StringId, parse::CodeRange, value::Value};
@@ -37,7 +37,7 @@ pub struct CodeBuilder {
     constants: Vec<Value>,
 
     /// Source location entries for traceback generation.
-    location_table: Vec<LocationEntry>,
+    location_table: LocationTable,
 
     /// Exception handler entries.
     exception_table: Vec<ExceptionEntry>,
@@ -54,6 +54,9 @@ pub struct CodeBuilder {
     /// Maximum stack depth seen during compilation.
     max_stack_depth: u16,
 
+    /// Number of inline cache slots handed out to `LoadAttr` and `CallAttr` sites.
+    num_inline_caches: u16,
+
     /// Local variable names indexed by slot number.
     ///
     /// Populated during compilation to enable proper NameError messages
@@ -144,7 +147,6 @@ impl CodeBuilder {
     /// Emits an instruction with a u16 operand followed by a u8 operand.
     ///
     /// Used for MakeFunction: func_id (u16) + defaults_count (u8)
-    /// Used for CallAttr: attr_name_id (u16) + arg_count (u8)
     pub fn emit_u16_u8(&mut self, op: Opcode, operand1: u16, operand2: u8) {
         self.record_location();
         self.bytecode.push(op as u8);
@@ -156,10 +158,6 @@ impl CodeBuilder {
                 // pops defaults_count defaults, pushes function: 1 - defaults_count
                 self.adjust_stack(1 - i16::from(operand2));
             }
-            Opcode::CallAttr => {
-                // pops obj + args, pushes result: 1 - (1 + arg_count) = -arg_count
-                self.adjust_stack(-i16::from(operand2));
-            }
             _ => {
                 if let Some(effect) = op.stack_effect() {
                     self.adjust_stack(effect);
@@ -241,6 +239,32 @@ impl CodeBuilder {
         self.adjust_stack(-total_args);
     }
 
+    /// Emits `LoadAttr` with a fresh inline cache slot.
+    ///
+    /// Operands: attr_name_id (u16) + cache_slot (u16)
+    pub fn emit_load_attr(&mut self, attr_name_id: u16) {
+        let cache_slot = self.next_inline_cache();
+        self.record_location();
+        self.bytecode.push(Opcode::LoadAttr as u8);
+        self.bytecode.extend_from_slice(&attr_name_id.to_le_bytes());
+        self.bytecode.extend_from_slice(&cache_slot.to_le_bytes());
+        // LoadAttr pops the object and pushes the attribute: no net stack effect
+    }
+
+    /// Emits `CallAttr` with a fresh inline cache slot.
+    ///
+    /// Operands: attr_name_id (u16) + arg_count (u8) + cache_slot (u16)
+    pub fn emit_call_attr(&mut self, attr_name_id: u16, arg_count: u8) {
+        let cache_slot = self.next_inline_cache();
+        self.record_location();
+        self.bytecode.push(Opcode::CallAttr as u8);
+        self.bytecode.extend_from_slice(&attr_name_id.to_le_bytes());
+        self.bytecode.push(arg_count);
+        self.bytecode.extend_from_slice(&cache_slot.to_le_bytes());
+        // CallAttr: pops obj + args, pushes result: 1 - (1 + arg_count) = -arg_count
+        self.adjust_stack(-i16::from(arg_count));
+    }
+
     /// Emits CallAttrKw with inline keyword names.
     ///
     /// Operands: attr_name_id (u16) + pos_count (u8) + kw_count (u8) + kw_count * name_id (u16 each)
@@ -400,6 +424,15 @@ impl CodeBuilder {
         }
     }
 
+    /// Emits `DeleteLocal`, using wide variant for slots > 255.
+    pub fn emit_delete_local(&mut self, slot: u16) {
+        if let Ok(s) = u8::try_from(slot) {
+            self.emit_u8(Opcode::DeleteLocal, s);
+        } else {
+            self.emit_u16(Opcode::DeleteLocalW, slot);
+        }
+    }
+
     /// Adds a constant to the pool, returning its index.
     ///
     /// # Panics
@@ -444,11 +477,24 @@ impl CodeBuilder {
             self.exception_table,
             num_locals,
             self.max_stack_depth,
+            self.num_inline_caches,
             local_names,
             self.assigned_locals,
         )
     }
 
+    /// Hands out the next inline cache slot.
+    ///
+    /// Once every slot is taken, further sites share `u16::MAX`, which is past the end of
+    /// any cache table and so never cached.
+    fn next_inline_cache(&mut self) -> u16 {
+        let slot = self.num_inline_caches;
+        if slot < u16::MAX {
+            self.num_inline_caches += 1;
+        }
+        slot
+    }
+
     /// Records the current location in the location table if set.
     fn record_location(&mut self) {
         if let Some(range) = self.current_location {
@@ -491,8 +537,8 @@ impl CodeBuilder {
             Opcode::CallFunction => -i16::from(operand),
             // UnpackSequence pops 1, pushes n: n - 1
             Opcode::UnpackSequence => i16::from(operand) - 1,
-            // ListAppend/SetAdd pop value: -1 (depth operand doesn't affect stack count)
-            Opcode::ListAppend | Opcode::SetAdd => -1,
+            // ListAppend/SetAdd/SumAdd pop value: -1 (depth operand doesn't affect stack count)
+            Opcode::ListAppend | Opcode::SetAdd | Opcode::SumAdd => -1,
             // DictSetItem pops key and value: -2
             Opcode::DictSetItem => -2,
             // Default: use fixed effect if available
@@ -570,6 +616,34 @@ mod tests {
         assert_eq!(code.bytecode(), &[Opcode::LoadConst as u8, 0x34, 0x12]);
     }
 
+    #[test]
+    fn test_attr_sites_get_distinct_cache_slots() {
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadNone);
+        builder.emit_load_attr(7);
+        builder.emit_call_attr(8, 0);
+
+        let code = builder.build(0);
+        assert_eq!(
+            code.bytecode(),
+            &[
+                Opcode::LoadNone as u8,
+                Opcode::LoadAttr as u8,
+                7,
+                0,
+                0,
+                0,
+                Opcode::CallAttr as u8,
+                8,
+                0,
+                0,
+                1,
+                0,
+            ]
+        );
+        assert_eq!(code.num_inline_caches(), 2);
+    }
+
     #[test]
     fn test_forward_jump() {
         let mut builder = CodeBuilder::new();
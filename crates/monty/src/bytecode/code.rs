@@ -4,9 +4,15 @@
 //! bytecode instructions, a constant pool, source location information for tracebacks,
 //! and an exception handler table.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
-use crate::{intern::StringId, parse::CodeRange, value::Value};
+use super::op::{Opcode, RawInstr, try_decode};
+use crate::{
+    intern::{FunctionId, Interns, StringId},
+    modules::BuiltinModule,
+    parse::CodeRange,
+    value::Value,
+};
 
 /// Compiled bytecode for a function or module.
 ///
@@ -30,8 +36,9 @@ pub struct Code {
     /// Source location table for tracebacks.
     ///
     /// Maps bytecode offsets to source locations. Used to generate Python-style
-    /// tracebacks with line numbers and caret markers when exceptions occur.
-    location_table: Vec<LocationEntry>,
+    /// tracebacks with line numbers and caret markers when exceptions occur, and
+    /// to report lines to trace hooks, the debugger, profiling and coverage.
+    location_table: LocationTable,
 
     /// Exception handler table.
     ///
@@ -51,6 +58,12 @@ pub struct Code {
     /// compilation by tracking push/pop operations.
     stack_size: u16,
 
+    /// Number of inline cache slots used by `LoadAttr` and `CallAttr` sites.
+    ///
+    /// The VM allocates a table of this size per code object the first time one of
+    /// its sites fills a cache entry.
+    num_inline_caches: u16,
+
     /// Local variable names for error messages.
     ///
     /// Maps slot indices to variable names. Used to generate proper NameError
@@ -61,6 +74,7 @@ pub struct Code {
     ///
     /// Used to determine whether to raise `UnboundLocalError` (slot is assigned somewhere
     /// but accessed before assignment) or `NameError` (name doesn't exist in any scope).
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_set")]
     assigned_locals: HashSet<u16>,
 }
 
@@ -73,10 +87,11 @@ impl Code {
     pub fn new(
         bytecode: Vec<u8>,
         constants: ConstPool,
-        location_table: Vec<LocationEntry>,
+        location_table: LocationTable,
         exception_table: Vec<ExceptionEntry>,
         num_locals: u16,
         stack_size: u16,
+        num_inline_caches: u16,
         local_names: Vec<StringId>,
         assigned_locals: HashSet<u16>,
     ) -> Self {
@@ -87,6 +102,7 @@ impl Code {
             exception_table,
             num_locals,
             stack_size,
+            num_inline_caches,
             local_names,
             assigned_locals,
         }
@@ -104,6 +120,47 @@ impl Code {
         &self.constants
     }
 
+    /// Returns the source location table.
+    #[must_use]
+    pub(super) fn location_table(&self) -> &LocationTable {
+        &self.location_table
+    }
+
+    /// Returns the exception handler table, innermost entries first.
+    #[must_use]
+    pub(super) fn exception_table(&self) -> &[ExceptionEntry] {
+        &self.exception_table
+    }
+
+    /// Returns the maximum stack depth computed during compilation.
+    #[must_use]
+    pub(super) fn stack_size(&self) -> u16 {
+        self.stack_size
+    }
+
+    /// Returns the number of inline cache slots the compiler assigned.
+    #[must_use]
+    pub fn num_inline_caches(&self) -> usize {
+        usize::from(self.num_inline_caches)
+    }
+
+    /// Replaces the bytecode along with every table that records offsets into it.
+    ///
+    /// Used by the peephole optimizer, which rewrites the instruction stream after
+    /// compilation and moves location and exception entries with the instructions.
+    pub(super) fn replace_bytecode(
+        &mut self,
+        bytecode: Vec<u8>,
+        location_table: LocationTable,
+        exception_table: Vec<ExceptionEntry>,
+        stack_size: u16,
+    ) {
+        self.bytecode = bytecode;
+        self.location_table = location_table;
+        self.exception_table = exception_table;
+        self.stack_size = stack_size;
+    }
+
     /// Returns the local variable name for a given slot index.
     ///
     /// Used to generate proper NameError messages when accessing undefined locals.
@@ -123,20 +180,29 @@ impl Code {
 
     /// Finds the location entry for a given bytecode offset.
     ///
-    /// Location entries are recorded at instruction boundaries. This method finds
-    /// the most recent entry at or before the given offset.
-    ///
-    /// Returns `None` if the location table is empty or the offset is before
-    /// the first recorded location.
+    /// See [`LocationTable::lookup`].
     #[must_use]
     pub fn location_for_offset(&self, offset: usize) -> Option<&LocationEntry> {
-        // Location entries are in order by bytecode offset.
-        // Find the last entry where bytecode_offset <= offset.
-        let offset_u32 = u32::try_from(offset).expect("bytecode offset exceeds u32");
+        self.location_table.lookup(offset)
+    }
+
+    /// Finds the location entry for a given bytecode offset, along with the range of
+    /// offsets it covers.
+    ///
+    /// See [`LocationTable::lookup_span`].
+    #[must_use]
+    pub fn location_span_for_offset(&self, offset: usize) -> Option<(&LocationEntry, Range<usize>)> {
+        self.location_table.lookup_span(offset)
+    }
+
+    /// Returns the source lines that have code, as the start line of each location entry.
+    ///
+    /// Lines may repeat, and lines of code removed by constant folding don't appear.
+    pub fn lines(&self) -> impl Iterator<Item = u16> + '_ {
         self.location_table
+            .entries()
             .iter()
-            .rev()
-            .find(|entry| entry.bytecode_offset <= offset_u32)
+            .map(|entry| entry.range.start().line)
     }
 
     /// Finds an exception handler for the given bytecode offset.
@@ -150,6 +216,196 @@ impl Code {
     pub fn find_exception_handler(&self, offset: u32) -> Option<&ExceptionEntry> {
         self.exception_table.iter().find(|entry| entry.contains(offset))
     }
+
+    /// Checks deserialized bytecode before it is run, describing the first problem found.
+    ///
+    /// Every instruction must decode, jumps and exception handlers must land on an
+    /// instruction, and the code must end with an instruction that leaves it, so the VM
+    /// never reads an opcode or operand that isn't there. The constants, local, global and
+    /// cell slots, names, functions and modules that operands refer to must exist. `globals`
+    /// is the size of the global namespace and `cells` the number of cells of a frame
+    /// running this code.
+    ///
+    /// How instructions use the operand stack is not checked.
+    pub(crate) fn validate(&self, interns: &Interns, globals: usize, cells: usize) -> Result<(), String> {
+        let instrs = try_decode(&self.bytecode)?;
+        match instrs.last().map(|instr| instr.op) {
+            Some(
+                Opcode::ReturnValue
+                | Opcode::Raise
+                | Opcode::RaiseFrom
+                | Opcode::Reraise
+                | Opcode::Jump
+                | Opcode::RaiseImportError,
+            ) => {}
+            Some(op) => return Err(format!("code ends with {op:?}, which falls through")),
+            None => return Err("code is empty".to_owned()),
+        }
+        let starts: HashSet<u32> = instrs.iter().map(|instr| instr.offset).collect();
+
+        let bounds = interns.bounds();
+        for value in &self.constants.values {
+            if matches!(value, Value::Ref(_)) {
+                return Err("constant refers to the heap".to_owned());
+            }
+            bounds.check(value).map_err(|err| format!("constant refers to {err}"))?;
+        }
+        for instr in &instrs {
+            self.validate_instr(instr, &starts, interns, globals, cells)
+                .map_err(|err| format!("{:?} at offset {} {err}", instr.op, instr.offset))?;
+        }
+        for entry in &self.exception_table {
+            let in_range =
+                entry.start <= entry.end && usize::try_from(entry.end).is_ok_and(|end| end <= self.bytecode.len());
+            if !in_range || !starts.contains(&entry.handler) {
+                return Err(format!(
+                    "exception table entry {}..{} with handler {} is out of range",
+                    entry.start, entry.end, entry.handler
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the jump target and the operands of one instruction; see `validate`.
+    fn validate_instr(
+        &self,
+        instr: &RawInstr<'_>,
+        starts: &HashSet<u32>,
+        interns: &Interns,
+        globals: usize,
+        cells: usize,
+    ) -> Result<(), String> {
+        if let Some(target) = instr.target
+            && !starts.contains(&target)
+        {
+            return Err(format!("jumps to {target}, which is not an instruction"));
+        }
+        let ops = instr.operands;
+        let u16_at = |idx: usize| u16::from_le_bytes([ops[idx], ops[idx + 1]]);
+        let exists = |kind: &str, index: usize, len: usize| {
+            if index < len {
+                Ok(())
+            } else {
+                Err(format!("refers to {kind} {index}, which does not exist"))
+            }
+        };
+        let name = |index: u16| {
+            interns
+                .bounds()
+                .check(&Value::InternString(StringId::from_index(index)))
+                .map_err(|err| format!("refers to {err}"))
+        };
+        let keyword_names = |names: &[u8]| {
+            names
+                .chunks_exact(2)
+                .try_for_each(|name_bytes| name(u16::from_le_bytes([name_bytes[0], name_bytes[1]])))
+        };
+        let locals = usize::from(self.num_locals);
+        let constants = self.constants.values.len();
+
+        match instr.op {
+            Opcode::LoadConst | Opcode::CompareModEq | Opcode::RaiseImportError => {
+                exists("constant", u16_at(0).into(), constants)
+            }
+            Opcode::LoadLocal0 => exists("local", 0, locals),
+            Opcode::LoadLocal1 => exists("local", 1, locals),
+            Opcode::LoadLocal2 => exists("local", 2, locals),
+            Opcode::LoadLocal3 => exists("local", 3, locals),
+            Opcode::LoadLocal | Opcode::StoreLocal | Opcode::DeleteLocal | Opcode::ForIterStoreLocal => {
+                exists("local", usize::from(ops[0]), locals)
+            }
+            Opcode::LoadLocalW | Opcode::StoreLocalW | Opcode::DeleteLocalW => {
+                exists("local", u16_at(0).into(), locals)
+            }
+            Opcode::LoadLocalPair => {
+                exists("local", usize::from(ops[0] >> 4), locals)?;
+                exists("local", usize::from(ops[0] & 0x0F), locals)
+            }
+            Opcode::LoadGlobal | Opcode::StoreGlobal => exists("global", u16_at(0).into(), globals),
+            Opcode::LoadCell | Opcode::StoreCell => exists("cell", u16_at(0).into(), cells),
+            Opcode::LoadAttr
+            | Opcode::LoadAttrImport
+            | Opcode::StoreAttr
+            | Opcode::CallAttr
+            | Opcode::CallAttrExtended => name(u16_at(0)),
+            // 0xFFFF stands for a call without a function name
+            Opcode::DictMerge if u16_at(0) == u16::MAX => Ok(()),
+            Opcode::DictMerge => name(u16_at(0)),
+            Opcode::CallFunctionKw => keyword_names(&ops[2..]),
+            Opcode::CallAttrKw => {
+                name(u16_at(0))?;
+                keyword_names(&ops[4..])
+            }
+            Opcode::MakeFunction | Opcode::MakeClosure => {
+                exists("function", u16_at(0).into(), interns.function_count())?;
+                let function = interns.get_function(FunctionId::from_index(u16_at(0)));
+                let free_vars = function.free_var_enclosing_slots.len();
+                // frames get the captured cells after their own, so they must match
+                let captured = if instr.op == Opcode::MakeClosure { ops[3] } else { 0 };
+                if usize::from(captured) == free_vars {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "captures {captured} cells for a function with {free_vars} free variables"
+                    ))
+                }
+            }
+            Opcode::CompareJumpIfFalse => {
+                let is_comparison = Opcode::try_from(ops[0]).is_ok_and(|compare| {
+                    (Opcode::CompareEq as u8..=Opcode::CompareNotIn as u8).contains(&(compare as u8))
+                });
+                if is_comparison {
+                    Ok(())
+                } else {
+                    Err(format!("compares with opcode {}, which is not a comparison", ops[0]))
+                }
+            }
+            Opcode::LoadModule => match BuiltinModule::from_repr(ops[0]) {
+                Some(_) => Ok(()),
+                None => Err(format!("loads module {}, which does not exist", ops[0])),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks the bytecode of a deserialized program: `module_code`, if there is one, and that
+/// of every function in `interns`; see `Code::validate`. `globals` is the size of the global
+/// namespace the program runs with.
+pub(crate) fn validate_program(module_code: Option<&Code>, interns: &Interns, globals: usize) -> Result<(), String> {
+    if let Some(code) = module_code {
+        // module-level code runs in the global namespace and has no cells
+        if usize::from(code.num_locals) > globals {
+            return Err(format!(
+                "module code uses {} slots of a global namespace of {globals}",
+                code.num_locals
+            ));
+        }
+        code.validate(interns, globals, 0)
+            .map_err(|err| format!("module code: {err}"))?;
+    }
+    for index in 0..interns.function_count() {
+        let id = u16::try_from(index).map_err(|_| format!("function {index} has no valid id"))?;
+        let function = interns.get_function(FunctionId::from_index(id));
+        let params = function.signature.total_slots();
+        if function.namespace_size < usize::from(function.code.num_locals)
+            || function.cell_param_indices.len() != function.cell_var_count
+            || function
+                .cell_param_indices
+                .iter()
+                .flatten()
+                .any(|&param| param >= params)
+        {
+            return Err(format!("function {index} has an inconsistent namespace layout"));
+        }
+        let cells = function.cell_var_count + function.free_var_enclosing_slots.len();
+        function
+            .code
+            .validate(interns, globals, cells)
+            .map_err(|err| format!("function {index}: {err}"))?;
+    }
+    Ok(())
 }
 
 /// TODO remove, this doesn't add any value
@@ -191,6 +447,73 @@ impl ConstPool {
     }
 }
 
+/// Maps bytecode offsets to the source locations they were compiled from.
+///
+/// An entry applies from its offset up to the next entry's, so consecutive instructions
+/// compiled from the same expression share one entry. The table is built alongside the
+/// bytecode by `CodeBuilder` and rebuilt by the peephole optimizer as it moves, fuses and
+/// removes instructions, so every instruction keeps the location it was compiled with and
+/// tracebacks and trace events point at the original source whatever the optimizer did.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct LocationTable {
+    /// Entries in strictly increasing bytecode offset order.
+    entries: Vec<LocationEntry>,
+}
+
+impl LocationTable {
+    /// Records that the code from `entry`'s offset on comes from its location.
+    ///
+    /// Entries must be pushed in non-decreasing offset order. An entry at the same offset
+    /// as the last one replaces it, and an entry with the same location as the last one
+    /// is dropped since the last entry already covers its offset.
+    pub fn push(&mut self, entry: LocationEntry) {
+        if let Some(last) = self.entries.last_mut() {
+            debug_assert!(
+                last.bytecode_offset <= entry.bytecode_offset,
+                "location entries pushed out of order"
+            );
+            if last.bytecode_offset == entry.bytecode_offset {
+                *last = entry;
+                return;
+            }
+            if last.same_location(&entry) {
+                return;
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    /// Finds the entry that applies at `offset`: the last one at or before it.
+    ///
+    /// Returns `None` if the table is empty or `offset` is before the first entry.
+    #[must_use]
+    pub fn lookup(&self, offset: usize) -> Option<&LocationEntry> {
+        let offset = u32::try_from(offset).expect("bytecode offset exceeds u32");
+        let after = self.entries.partition_point(|entry| entry.bytecode_offset <= offset);
+        after.checked_sub(1).map(|idx| &self.entries[idx])
+    }
+
+    /// Like [`lookup`](Self::lookup), but also returns the offsets the entry applies to:
+    /// from its own offset up to the next entry's, or to `usize::MAX` for the last one.
+    #[must_use]
+    pub fn lookup_span(&self, offset: usize) -> Option<(&LocationEntry, Range<usize>)> {
+        let offset = u32::try_from(offset).expect("bytecode offset exceeds u32");
+        let after = self.entries.partition_point(|entry| entry.bytecode_offset <= offset);
+        let entry = &self.entries[after.checked_sub(1)?];
+        let end = self
+            .entries
+            .get(after)
+            .map_or(usize::MAX, |next| next.bytecode_offset as usize);
+        Some((entry, entry.bytecode_offset as usize..end))
+    }
+
+    /// Returns the entries in bytecode offset order.
+    #[must_use]
+    pub fn entries(&self) -> &[LocationEntry] {
+        &self.entries
+    }
+}
+
 /// Source location for a bytecode instruction, used for tracebacks.
 ///
 /// Python 3.11+ tracebacks show carets under the relevant expression:
@@ -236,6 +559,26 @@ impl LocationEntry {
     pub fn range(&self) -> CodeRange {
         self.range
     }
+
+    /// Returns the bytecode offset this entry starts at.
+    #[must_use]
+    pub(super) fn bytecode_offset(&self) -> u32 {
+        self.bytecode_offset
+    }
+
+    /// Returns true if both entries point at the same source range and focus.
+    fn same_location(&self, other: &Self) -> bool {
+        self.range == other.range && self.focus == other.focus
+    }
+
+    /// Returns a copy of this entry starting at `bytecode_offset` instead.
+    #[must_use]
+    pub(super) fn moved_to(&self, bytecode_offset: u32) -> Self {
+        Self {
+            bytecode_offset,
+            ..self.clone()
+        }
+    }
 }
 
 /// Entry in the exception table - maps a protected bytecode range to its handler.
@@ -290,6 +633,18 @@ impl ExceptionEntry {
         }
     }
 
+    /// Returns the start of the protected range (inclusive).
+    #[must_use]
+    pub(super) fn start(&self) -> u32 {
+        self.start
+    }
+
+    /// Returns the end of the protected range (exclusive).
+    #[must_use]
+    pub(super) fn end(&self) -> u32 {
+        self.end
+    }
+
     /// Returns the handler bytecode offset.
     #[must_use]
     pub fn handler(&self) -> u32 {
@@ -308,3 +663,81 @@ impl ExceptionEntry {
         offset >= self.start && offset < self.end
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intern::InternerBuilder;
+
+    /// Validates `bytecode` as the code of a frame with one local, one global and no cells.
+    fn validate(bytecode: Vec<u8>) -> Result<(), String> {
+        let interns = Interns::new(InternerBuilder::new(""), Vec::new(), Vec::new());
+        let code = Code::new(
+            bytecode,
+            ConstPool::default(),
+            LocationTable::default(),
+            Vec::new(),
+            1,
+            1,
+            0,
+            Vec::new(),
+            HashSet::new(),
+        );
+        code.validate(&interns, 1, 0)
+    }
+
+    #[test]
+    fn validate_accepts_well_formed_code() {
+        validate(vec![
+            Opcode::LoadLocal0 as u8,
+            Opcode::StoreGlobal as u8,
+            0,
+            0,
+            Opcode::LoadNone as u8,
+            Opcode::ReturnValue as u8,
+        ])
+        .unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_malformed_code() {
+        for (bytecode, expected) in [
+            (vec![], "code is empty"),
+            (vec![255], "invalid opcode byte: 255"),
+            (vec![Opcode::LoadConst as u8, 0], "cut off"),
+            (vec![Opcode::LoadNone as u8], "falls through"),
+            (
+                vec![Opcode::Jump as u8, 0xFF, 0xFF],
+                "jumps to 2, which is not an instruction",
+            ),
+            (vec![Opcode::Jump as u8, 0x00, 0x80], "jumps out of range"),
+            (
+                vec![Opcode::LoadConst as u8, 0, 0, Opcode::ReturnValue as u8],
+                "constant 0, which does not exist",
+            ),
+            (
+                vec![Opcode::LoadLocal1 as u8, Opcode::ReturnValue as u8],
+                "local 1, which does not exist",
+            ),
+            (
+                vec![Opcode::LoadGlobal as u8, 1, 0, Opcode::ReturnValue as u8],
+                "global 1, which does not exist",
+            ),
+            (
+                vec![Opcode::LoadCell as u8, 0, 0, Opcode::ReturnValue as u8],
+                "cell 0, which does not exist",
+            ),
+            (
+                vec![Opcode::MakeFunction as u8, 0, 0, 0, Opcode::ReturnValue as u8],
+                "function 0, which does not exist",
+            ),
+            (
+                vec![Opcode::LoadModule as u8, 255, Opcode::ReturnValue as u8],
+                "module 255, which does not exist",
+            ),
+        ] {
+            let err = validate(bytecode.clone()).expect_err(&format!("{bytecode:?} should be rejected"));
+            assert!(err.contains(expected), "{bytecode:?}: {err}");
+        }
+    }
+}
@@ -8,6 +8,8 @@
 //! its body is compiled to bytecode and a `Function` struct is created. All compiled
 //! functions are collected and returned along with the module code.
 
+mod peephole;
+
 use std::borrow::Cow;
 
 use super::{
@@ -17,7 +19,7 @@ use super::{
 };
 use crate::{
     args::{ArgExprs, Kwarg},
-    builtins::Builtins,
+    builtins::{Builtins, BuiltinsFunctions},
     exception_private::ExcType,
     exception_public::{MontyException, StackFrame},
     expressions::{
@@ -29,6 +31,7 @@ use crate::{
     intern::{Interns, StringId},
     modules::BuiltinModule,
     parse::{CodeRange, ExceptHandler, Try},
+    types::Type,
     value::{EitherStr, Value},
 };
 
@@ -39,6 +42,13 @@ use crate::{
 /// such limit but we need one for our bytecode encoding.
 const MAX_CALL_ARGS: usize = 255;
 
+/// Largest capacity the compiler will pre-allocate for a comprehension.
+///
+/// Pre-sizing is an optimization, not a guarantee: capping it keeps a comprehension
+/// that raises early or filters out most items from reserving a large block up front.
+/// The VM checks each reservation against the memory limit as well.
+const MAX_PRESIZE_CAPACITY: u16 = 1024;
+
 /// Compiles prepared AST nodes to bytecode.
 ///
 /// The compiler traverses the AST and emits bytecode instructions using
@@ -81,12 +91,17 @@ pub struct Compiler<'a> {
     /// finally block. The finally block will then execute the return.
     finally_targets: Vec<FinallyTarget>,
 
-    /// Tracks nesting depth inside exception handlers.
+    /// The enclosing except handlers and finally blocks that hold state to discard when
+    /// break/continue/return leaves them, innermost last.
     ///
-    /// When break/continue/return is inside an except handler, we need to
-    /// clear the current exception (`ClearException`) and pop the exception
-    /// value from the stack before jumping to the finally path or loop target.
-    except_handler_depth: usize,
+    /// When leaving an except handler, we need to clear the current exception
+    /// (`ClearException`), pop the exception value from the stack and unbind the
+    /// handler's name before jumping to the finally path or loop target, otherwise the
+    /// exception leaks into the code that runs next.
+    unwind_stack: Vec<UnwindEntry>,
+
+    /// Whether to run the peephole optimizer over each finished code object.
+    optimize: bool,
 }
 
 /// Information about a loop for break/continue handling.
@@ -106,6 +121,23 @@ struct LoopInfo {
     /// Whether this loop has an iterator on the stack.
     /// True for `for` loops, false for `while` loops.
     has_iterator_on_stack: bool,
+    /// Length of `unwind_stack` when the loop started; break/continue only
+    /// unwind the blocks inside the loop.
+    unwind_depth: usize,
+}
+
+/// A block whose state break/continue/return must discard when leaving it.
+#[derive(Debug, Clone, Copy)]
+enum UnwindEntry {
+    /// Inside an except handler: the exception is on the operand stack as well as the
+    /// exception stack, and bound to the name if the handler has one.
+    Handler(Option<Identifier>),
+    /// Inside a finally block run because of an exception: the exception is only on the
+    /// exception stack, and is re-raised when the block ends.
+    FinallyAfterException,
+    /// Inside a finally block run because of a return: the return value is on the
+    /// operand stack, and is returned when the block ends.
+    FinallyAfterReturn,
 }
 
 /// A break or continue that needs to go through a finally block.
@@ -134,6 +166,9 @@ struct FinallyTarget {
     /// The loop depth when this finally was entered.
     /// Used to determine if break/continue targets a loop outside this finally.
     loop_depth_at_entry: usize,
+    /// Length of `unwind_stack` when this finally was entered; jumps to the finally
+    /// paths only unwind the blocks inside the try.
+    unwind_depth: usize,
 }
 
 /// Result of module compilation: the module code and all compiled functions.
@@ -146,20 +181,12 @@ pub struct CompileResult {
 
 impl<'a> Compiler<'a> {
     /// Creates a new compiler with access to the string interner.
-    fn new(interns: &'a Interns, functions: Vec<Function>) -> Self {
-        Self {
-            code: CodeBuilder::new(),
-            interns,
-            functions,
-            loop_stack: Vec::new(),
-            cell_base: 0,
-            finally_targets: Vec::new(),
-            except_handler_depth: 0,
-        }
+    fn new(interns: &'a Interns, functions: Vec<Function>, optimize: bool) -> Self {
+        Self::new_with_cell_base(interns, functions, 0, optimize)
     }
 
     /// Creates a new compiler with a specific cell base offset.
-    fn new_with_cell_base(interns: &'a Interns, functions: Vec<Function>, cell_base: u16) -> Self {
+    fn new_with_cell_base(interns: &'a Interns, functions: Vec<Function>, cell_base: u16, optimize: bool) -> Self {
         Self {
             code: CodeBuilder::new(),
             interns,
@@ -167,7 +194,8 @@ impl<'a> Compiler<'a> {
             loop_stack: Vec::new(),
             cell_base,
             finally_targets: Vec::new(),
-            except_handler_depth: 0,
+            unwind_stack: Vec::new(),
+            optimize,
         }
     }
 
@@ -176,12 +204,15 @@ impl<'a> Compiler<'a> {
     /// Returns the compiled module Code and all compiled Functions, or a compile
     /// error if limits were exceeded. The module implicitly returns the value
     /// of the last expression, or None if empty.
+    ///
+    /// With `optimize`, every code object is passed through the peephole optimizer.
     pub fn compile_module(
         nodes: &[PreparedNode],
         interns: &Interns,
         num_locals: u16,
+        optimize: bool,
     ) -> Result<CompileResult, CompileError> {
-        Self::compile_module_with_functions(nodes, interns, num_locals, Vec::new())
+        Self::compile_module_with_functions(nodes, interns, num_locals, Vec::new(), optimize)
     }
 
     /// Compiles module-level code while preserving an existing function table prefix.
@@ -194,19 +225,17 @@ impl<'a> Compiler<'a> {
         interns: &Interns,
         num_locals: u16,
         existing_functions: Vec<Function>,
+        optimize: bool,
     ) -> Result<CompileResult, CompileError> {
-        let mut compiler = Compiler::new(interns, Vec::new());
-        compiler.functions = existing_functions;
+        let mut compiler = Compiler::new(interns, existing_functions, optimize);
         compiler.compile_block(nodes)?;
 
         // Module returns None if no explicit return
         compiler.code.emit(Opcode::LoadNone);
         compiler.code.emit(Opcode::ReturnValue);
 
-        Ok(CompileResult {
-            code: compiler.code.build(num_locals),
-            functions: compiler.functions,
-        })
+        let (code, functions) = compiler.finish(num_locals);
+        Ok(CompileResult { code, functions })
     }
 
     /// Compiles a function body to bytecode, returning the Code and any nested functions.
@@ -226,15 +255,25 @@ impl<'a> Compiler<'a> {
         functions: Vec<Function>,
         num_locals: u16,
         cell_base: u16,
+        optimize: bool,
     ) -> Result<(Code, Vec<Function>), CompileError> {
-        let mut compiler = Compiler::new_with_cell_base(interns, functions, cell_base);
+        let mut compiler = Compiler::new_with_cell_base(interns, functions, cell_base, optimize);
         compiler.compile_block(body)?;
 
         // Implicit return None if no explicit return
         compiler.code.emit(Opcode::LoadNone);
         compiler.code.emit(Opcode::ReturnValue);
 
-        Ok((compiler.code.build(num_locals), compiler.functions))
+        Ok(compiler.finish(num_locals))
+    }
+
+    /// Builds the finished code object, running the peephole optimizer if enabled.
+    fn finish(self, num_locals: u16) -> (Code, Vec<Function>) {
+        let mut code = self.code.build(num_locals);
+        if self.optimize {
+            peephole::optimize(&mut code);
+        }
+        (code, self.functions)
     }
 
     /// Compiles a block of statements.
@@ -296,7 +335,7 @@ impl<'a> Compiler<'a> {
                 // After UnpackSequence/UnpackEx, values are on stack with first item on top
                 // Store them in order (first target gets first item), handling nesting
                 for target in targets {
-                    self.compile_unpack_target(target);
+                    self.compile_unpack_target(target)?;
                 }
             }
             Node::OpAssign { target, op, object } => {
@@ -359,6 +398,11 @@ impl<'a> Compiler<'a> {
                     self.code.emit(Opcode::Reraise);
                 }
             }
+            Node::RaiseFrom { exc, cause } => {
+                self.compile_expr(exc)?;
+                self.compile_expr(cause)?;
+                self.code.emit(Opcode::RaiseFrom);
+            }
             Node::FunctionDef(func_def) => self.compile_function_def(func_def)?,
             Node::Try(try_block) => self.compile_try(try_block)?,
             Node::Import { module_name, binding } => self.compile_import(*module_name, binding),
@@ -404,8 +448,14 @@ impl<'a> Compiler<'a> {
         let functions = std::mem::take(&mut self.functions);
         let cell_base = u16::try_from(func_def.signature.param_count()).expect("function parameter count exceeds u16");
         let namespace_size = u16::try_from(func_def.namespace_size).expect("function namespace size exceeds u16");
-        let (body_code, mut functions) =
-            Self::compile_function_body(&func_def.body, self.interns, functions, namespace_size, cell_base)?;
+        let (body_code, mut functions) = Self::compile_function_body(
+            &func_def.body,
+            self.interns,
+            functions,
+            namespace_size,
+            cell_base,
+            self.optimize,
+        )?;
 
         // 2. Create the compiled Function and add to the vector
         let func_id = functions.len();
@@ -484,8 +534,14 @@ impl<'a> Compiler<'a> {
         let functions = std::mem::take(&mut self.functions);
         let cell_base = u16::try_from(func_def.signature.param_count()).expect("function parameter count exceeds u16");
         let namespace_size = u16::try_from(func_def.namespace_size).expect("function namespace size exceeds u16");
-        let (body_code, mut functions) =
-            Self::compile_function_body(&func_def.body, self.interns, functions, namespace_size, cell_base)?;
+        let (body_code, mut functions) = Self::compile_function_body(
+            &func_def.body,
+            self.interns,
+            functions,
+            namespace_size,
+            cell_base,
+            self.optimize,
+        )?;
 
         // 2. Create the compiled Function and add to the vector
         let func_id = functions.len();
@@ -722,10 +778,8 @@ impl<'a> Compiler<'a> {
                 // Restore the full expression's position for traceback caret range
                 self.code.set_location(expr_loc.position, None);
                 let name_id = attr.string_id().expect("LoadAttr requires interned attr name");
-                self.code.emit_u16(
-                    Opcode::LoadAttr,
-                    u16::try_from(name_id.index()).expect("name index exceeds u16"),
-                );
+                self.code
+                    .emit_load_attr(u16::try_from(name_id.index()).expect("name index exceeds u16"));
             }
 
             Expr::Call { callable, args } => {
@@ -758,6 +812,12 @@ impl<'a> Compiler<'a> {
                 self.compile_list_comp(elt, generators)?;
             }
 
+            Expr::Generator { elt, generators } => {
+                // Without generator objects, a generator expression that isn't consumed
+                // directly by sum(), any() or all() is materialized as a list
+                self.compile_list_comp(elt, generators)?;
+            }
+
             Expr::SetComp { elt, generators } => {
                 self.compile_set_comp(elt, generators)?;
             }
@@ -1097,6 +1157,20 @@ impl<'a> Compiler<'a> {
     ///
     /// The `call_pos` is the position of the full call expression for proper traceback caret.
     fn compile_call(&mut self, callable: &Callable, args: &ArgExprs, call_pos: CodeRange) -> Result<(), CompileError> {
+        // sum(), any() and all() over a generator expression consume it item by item
+        // instead of materializing it as a list and calling the builtin
+        if let Callable::Builtin(Builtins::Function(builtin_func)) = callable
+            && let ArgExprs::One(arg) = args
+            && let Expr::Generator { elt, generators } = &arg.expr
+        {
+            match builtin_func {
+                BuiltinsFunctions::Sum => return self.compile_sum_generator(elt, generators, call_pos),
+                BuiltinsFunctions::Any => return self.compile_any_all_generator(elt, generators, true),
+                BuiltinsFunctions::All => return self.compile_any_all_generator(elt, generators, false),
+                _ => {}
+            }
+        }
+
         // Check if we can use the optimized CallBuiltinFunction path:
         // - Callable must be a builtin function (known at compile time)
         // - Arguments must be positional-only (Empty, One, Two, or Args)
@@ -1564,30 +1638,21 @@ impl<'a> Compiler<'a> {
         match args {
             ArgExprs::Empty => {
                 self.code.set_location(call_pos, None);
-                self.code.emit_u16_u8(
-                    Opcode::CallAttr,
-                    u16::try_from(name_id.index()).expect("name index exceeds u16"),
-                    0,
-                );
+                self.code
+                    .emit_call_attr(u16::try_from(name_id.index()).expect("name index exceeds u16"), 0);
             }
             ArgExprs::One(arg) => {
                 self.compile_expr(arg)?;
                 self.code.set_location(call_pos, None);
-                self.code.emit_u16_u8(
-                    Opcode::CallAttr,
-                    u16::try_from(name_id.index()).expect("name index exceeds u16"),
-                    1,
-                );
+                self.code
+                    .emit_call_attr(u16::try_from(name_id.index()).expect("name index exceeds u16"), 1);
             }
             ArgExprs::Two(arg1, arg2) => {
                 self.compile_expr(arg1)?;
                 self.compile_expr(arg2)?;
                 self.code.set_location(call_pos, None);
-                self.code.emit_u16_u8(
-                    Opcode::CallAttr,
-                    u16::try_from(name_id.index()).expect("name index exceeds u16"),
-                    2,
-                );
+                self.code
+                    .emit_call_attr(u16::try_from(name_id.index()).expect("name index exceeds u16"), 2);
             }
             ArgExprs::Args(args) => {
                 // Check argument count limit
@@ -1602,8 +1667,7 @@ impl<'a> Compiler<'a> {
                 }
                 let arg_count = u8::try_from(args.len()).expect("argument count exceeds u8");
                 self.code.set_location(call_pos, None);
-                self.code.emit_u16_u8(
-                    Opcode::CallAttr,
+                self.code.emit_call_attr(
                     u16::try_from(name_id.index()).expect("name index exceeds u16"),
                     arg_count,
                 );
@@ -1785,13 +1849,14 @@ impl<'a> Compiler<'a> {
             start: loop_start,
             break_jumps: Vec::new(),
             has_iterator_on_stack: true,
+            unwind_depth: self.unwind_stack.len(),
         });
 
         // ForIter: advance iterator or jump to end
         let end_jump = self.code.emit_jump(Opcode::ForIter);
 
         // Store current value to target (handles both single identifiers and tuple unpacking)
-        self.compile_unpack_target(target);
+        self.compile_unpack_target(target)?;
 
         // Compile body
         self.compile_block(body)?;
@@ -1832,6 +1897,8 @@ impl<'a> Compiler<'a> {
     /// [break patches here]
     /// ```
     ///
+    /// When optimizing, `while True:` leaves out the condition and the else block.
+    ///
     /// Key differences from `for` loops:
     /// - No `GetIter` (no iterator)
     /// - No `ForIter` (use `JumpIfFalse` instead)
@@ -1849,19 +1916,29 @@ impl<'a> Compiler<'a> {
             start: loop_start,
             break_jumps: Vec::new(),
             has_iterator_on_stack: false,
+            unwind_depth: self.unwind_stack.len(),
         });
 
-        self.compile_expr(test)?;
-        let end_jump = self.code.emit_jump(Opcode::JumpIfFalse);
+        // `while True:` only exits through break, return or an exception, so when
+        // optimizing, skip testing the condition on every iteration and the else block
+        // that can never run
+        let always_true = self.optimize && matches!(test.expr, Expr::Literal(Literal::Bool(true)));
+        let end_jump = if always_true {
+            None
+        } else {
+            self.compile_expr(test)?;
+            Some(self.code.emit_jump(Opcode::JumpIfFalse))
+        };
 
         self.compile_block(body)?;
         self.code.emit_jump_to(Opcode::Jump, loop_start);
 
-        self.code.patch_jump(end_jump);
         let loop_info = self.loop_stack.pop().expect("loop stack underflow");
-
-        if !or_else.is_empty() {
-            self.compile_block(or_else)?;
+        if let Some(end_jump) = end_jump {
+            self.code.patch_jump(end_jump);
+            if !or_else.is_empty() {
+                self.compile_block(or_else)?;
+            }
         }
 
         for break_jump in loop_info.break_jumps {
@@ -1877,69 +1954,21 @@ impl<'a> Compiler<'a> {
     /// try-finally, the finally block must run first.
     ///
     /// The bytecode without finally:
-    /// 1. Clean up exception state if inside except handler
+    /// 1. Clean up exception state if inside except handlers or finally blocks
     /// 2. Pop the iterator if in a `for` loop (still on stack during loop body)
     /// 3. Jump to after the else block
     ///
     /// With finally:
-    /// 1. Clean up exception state if inside except handler
-    /// 2. Pop the iterator if in a `for` loop
-    /// 3. Jump to "finally with break" path (patched when try compilation completes)
-    /// 4. That path runs finally, then jumps to after the else block
+    /// 1. Clean up exception state of the handlers inside the try
+    /// 2. Jump to "finally with break" path (patched when try compilation completes)
+    /// 3. That path runs finally, then continues with the steps above, possibly
+    ///    through more finally blocks
     fn compile_break(&mut self, position: CodeRange) -> Result<(), CompileError> {
         if self.loop_stack.is_empty() {
             return Err(CompileError::new("'break' outside loop", position));
         }
-
         let target_loop_depth = self.loop_stack.len() - 1;
-
-        // If inside except handlers, clean up ALL exception states
-        // Each nested except handler has pushed an exception onto the stack,
-        // so we need to clear/pop each one when breaking out
-        for _ in 0..self.except_handler_depth {
-            self.code.emit(Opcode::ClearException);
-            self.code.emit(Opcode::Pop); // Pop the exception value
-        }
-
-        // Pop the iterator only for `for` loops (has iterator on stack)
-        // `while` loops don't have an iterator to pop
-        if self.loop_stack[target_loop_depth].has_iterator_on_stack {
-            self.code.emit(Opcode::Pop);
-        }
-
-        // Check if we need to go through any finally blocks
-        // We need to run finally if break crosses the try boundary, i.e., if
-        // we're breaking from a loop that existed before the try started.
-        if let Some(finally_target) = self.finally_targets.last_mut()
-            && target_loop_depth < finally_target.loop_depth_at_entry
-        {
-            // Breaking from a loop that's outside (or at the start of) this try-finally,
-            // so finally must run before the break
-            let jump = self.code.emit_jump(Opcode::Jump);
-            finally_target.break_jumps.push(BreakContinueThruFinally {
-                jump,
-                target_loop_depth,
-            });
-            // Set stack depth for unreachable cleanup code (see comment below)
-            if self.except_handler_depth > 0 {
-                self.code
-                    .set_stack_depth(u16::try_from(self.except_handler_depth).unwrap_or(u16::MAX));
-            }
-            return Ok(());
-        }
-
-        // No finally to go through, jump directly to loop end
-        let jump = self.code.emit_jump(Opcode::Jump);
-        self.loop_stack[target_loop_depth].break_jumps.push(jump);
-
-        // The code following this break is unreachable at runtime, but the compiler
-        // will still emit cleanup code for each enclosing except handler (ClearException + Pop).
-        // Set stack depth so those unreachable pops don't cause negative stack tracking.
-        if self.except_handler_depth > 0 {
-            self.code
-                .set_stack_depth(u16::try_from(self.except_handler_depth).unwrap_or(u16::MAX));
-        }
-
+        self.compile_loop_exit(target_loop_depth, true);
         Ok(())
     }
 
@@ -1952,99 +1981,91 @@ impl<'a> Compiler<'a> {
         if self.loop_stack.is_empty() {
             return Err(CompileError::new("'continue' not properly in loop", position));
         }
-
         let target_loop_depth = self.loop_stack.len() - 1;
-
-        // If inside except handlers, clean up ALL exception states
-        // Each nested except handler has pushed an exception onto the stack,
-        // so we need to clear/pop each one when continuing
-        for _ in 0..self.except_handler_depth {
-            self.code.emit(Opcode::ClearException);
-            self.code.emit(Opcode::Pop); // Pop the exception value
-        }
-
-        // Check if we need to go through any finally blocks
-        // We need to run finally if continue crosses the try boundary
-        if let Some(finally_target) = self.finally_targets.last_mut()
-            && target_loop_depth < finally_target.loop_depth_at_entry
-        {
-            // Continuing a loop that's outside (or at the start of) this try-finally,
-            // so finally must run before the continue
-            let jump = self.code.emit_jump(Opcode::Jump);
-            finally_target.continue_jumps.push(BreakContinueThruFinally {
-                jump,
-                target_loop_depth,
-            });
-            // Set stack depth for unreachable cleanup code (see comment below)
-            if self.except_handler_depth > 0 {
-                self.code
-                    .set_stack_depth(u16::try_from(self.except_handler_depth).unwrap_or(u16::MAX));
-            }
-            return Ok(());
-        }
-
-        // No finally to go through, jump directly to loop start
-        let loop_start = self.loop_stack[target_loop_depth].start;
-        self.code.emit_jump_to(Opcode::Jump, loop_start);
-
-        // The code following this continue is unreachable at runtime, but the compiler
-        // will still emit cleanup code for each enclosing except handler (ClearException + Pop).
-        // Set stack depth so those unreachable pops don't cause negative stack tracking.
-        if self.except_handler_depth > 0 {
-            self.code
-                .set_stack_depth(u16::try_from(self.except_handler_depth).unwrap_or(u16::MAX));
-        }
-
+        self.compile_loop_exit(target_loop_depth, false);
         Ok(())
     }
 
-    /// Compiles break or continue after a finally block has run.
-    ///
-    /// Called from `compile_try` after the finally block code. Each control flow
-    /// statement may target a different loop, so we check if there's another finally
-    /// to go through or if we can jump directly to the loop's target.
+    /// Compiles the jump of a break or continue targeting the loop at `target_loop_depth`.
     ///
-    /// Note: All items in the list jumped to the same finally block, so they all
-    /// have the same starting point. After finally runs, we need to route each
-    /// to its target loop, potentially through more finally blocks.
-    fn compile_control_flow_after_finally(&mut self, items: &[BreakContinueThruFinally], is_break: bool) {
-        // All items went through the same finally, now we need to dispatch to
-        // potentially different loops. For simplicity, we assume all items in
-        // a single finally target the same loop (the innermost one at the time).
-        // This is always true since break/continue only targets the innermost loop.
-        let Some(first) = items.first() else {
-            return;
-        };
-        let target_loop_depth = first.target_loop_depth;
-
-        // Check if there's another finally between us and the target loop
-        if let Some(finally_target) = self.finally_targets.last_mut()
+    /// Used both for the statement itself and after a finally block it went through has
+    /// run. Each step only unwinds the blocks entered since the innermost boundary it
+    /// crosses (a try-finally or the loop), so the finally blocks on the way run while the
+    /// handlers outside them are still active, like in CPython.
+    fn compile_loop_exit(&mut self, target_loop_depth: usize, is_break: bool) {
+        // The code following the jump is unreachable, but the enclosing handlers still emit
+        // their cleanup after it; keep tracking the depth as if the jump wasn't taken.
+        let depth = self.code.stack_depth();
+
+        // Check if there's a finally between us and the target loop
+        if let Some(finally_target) = self.finally_targets.last()
             && target_loop_depth < finally_target.loop_depth_at_entry
         {
-            // Need to go through another finally
+            self.compile_unwind(finally_target.unwind_depth, false);
             let jump = self.code.emit_jump(Opcode::Jump);
             let jump_info = BreakContinueThruFinally {
                 jump,
                 target_loop_depth,
             };
+            let finally_target = self.finally_targets.last_mut().expect("finally target checked above");
             if is_break {
                 finally_target.break_jumps.push(jump_info);
             } else {
-                // else continue
                 finally_target.continue_jumps.push(jump_info);
             }
+            self.code.set_stack_depth(depth);
             return;
         }
 
-        // No more finally blocks, jump directly to the loop target
+        // No finally to go through, jump directly to the loop target
+        self.compile_unwind(self.loop_stack[target_loop_depth].unwind_depth, false);
         if is_break {
+            // Pop the iterator only for `for` loops, `while` loops don't have one
+            if self.loop_stack[target_loop_depth].has_iterator_on_stack {
+                self.code.emit(Opcode::Pop);
+            }
             let jump = self.code.emit_jump(Opcode::Jump);
             self.loop_stack[target_loop_depth].break_jumps.push(jump);
         } else {
-            // else continue
             let loop_start = self.loop_stack[target_loop_depth].start;
             self.code.emit_jump_to(Opcode::Jump, loop_start);
         }
+        self.code.set_stack_depth(depth);
+    }
+
+    /// Compiles break or continue after a finally block has run.
+    ///
+    /// Called from `compile_try` after the finally block code. All items in the list
+    /// jumped to the same finally block and target the same loop, the innermost one
+    /// enclosing the try, since break/continue only targets the innermost loop.
+    fn compile_control_flow_after_finally(&mut self, items: &[BreakContinueThruFinally], is_break: bool) {
+        if let Some(first) = items.first() {
+            self.compile_loop_exit(first.target_loop_depth, is_break);
+        }
+    }
+
+    /// Discards the state of the blocks at `unwind_stack[from..]`, innermost first.
+    ///
+    /// Exceptions being handled are cleared from the exception stack, handler exceptions
+    /// and pending return values are popped from the operand stack, and handler names
+    /// are unbound. With `value_on_top` (a return value), the popped values sit below
+    /// the top of the stack.
+    fn compile_unwind(&mut self, from: usize, value_on_top: bool) {
+        for entry in self.unwind_stack[from..].to_vec().into_iter().rev() {
+            if !matches!(entry, UnwindEntry::FinallyAfterReturn) {
+                self.code.emit(Opcode::ClearException);
+            }
+            if matches!(entry, UnwindEntry::FinallyAfterException) {
+                continue;
+            }
+            if value_on_top {
+                self.code.emit(Opcode::Rot2);
+            }
+            self.code.emit(Opcode::Pop); // Pop the exception or return value
+            if let UnwindEntry::Handler(Some(name)) = entry {
+                self.compile_unbind_except_name(name);
+            }
+        }
     }
 
     // ========================================================================
@@ -2070,8 +2091,11 @@ impl<'a> Compiler<'a> {
     /// ; result list on stack
     /// ```
     fn compile_list_comp(&mut self, elt: &ExprLoc, generators: &[Comprehension]) -> Result<(), CompileError> {
-        // Build empty list
-        self.code.emit_u16(Opcode::BuildList, 0);
+        // Build empty list, pre-sized when the number of items is known
+        match comprehension_size_hint(generators) {
+            Some(capacity) => self.code.emit_u16(Opcode::BuildListSized, capacity),
+            None => self.code.emit_u16(Opcode::BuildList, 0),
+        }
 
         // Compile the nested generators, which will eventually append to the list
         let depth = u8::try_from(generators.len()).expect("too many generators in list comprehension");
@@ -2086,8 +2110,11 @@ impl<'a> Compiler<'a> {
 
     /// Compiles a set comprehension: `{elt for target in iter if cond...}`
     fn compile_set_comp(&mut self, elt: &ExprLoc, generators: &[Comprehension]) -> Result<(), CompileError> {
-        // Build empty set
-        self.code.emit_u16(Opcode::BuildSet, 0);
+        // Build empty set, pre-sized when the number of items is known
+        match comprehension_size_hint(generators) {
+            Some(capacity) => self.code.emit_u16(Opcode::BuildSetSized, capacity),
+            None => self.code.emit_u16(Opcode::BuildSet, 0),
+        }
 
         // Compile the nested generators, which will eventually add to the set
         let depth = u8::try_from(generators.len()).expect("too many generators in set comprehension");
@@ -2107,8 +2134,11 @@ impl<'a> Compiler<'a> {
         value: &ExprLoc,
         generators: &[Comprehension],
     ) -> Result<(), CompileError> {
-        // Build empty dict
-        self.code.emit_u16(Opcode::BuildDict, 0);
+        // Build empty dict, pre-sized when the number of items is known
+        match comprehension_size_hint(generators) {
+            Some(capacity) => self.code.emit_u16(Opcode::BuildDictSized, capacity),
+            None => self.code.emit_u16(Opcode::BuildDict, 0),
+        }
 
         // Compile the nested generators, which will eventually set items in the dict
         let depth = u8::try_from(generators.len()).expect("too many generators in dict comprehension");
@@ -2122,6 +2152,90 @@ impl<'a> Compiler<'a> {
         Ok(())
     }
 
+    /// Compiles `sum(elt for target in iter if cond...)` without building a list.
+    ///
+    /// The running total starts at 0 below the iterators and each element is added to it
+    /// in place, so memory use doesn't grow with the number of items:
+    /// ```text
+    /// LOAD_SMALL_INT 0      ; running total
+    /// <comprehension loops as in compile_list_comp>
+    ///   <compile elt>
+    ///   SUM_ADD depth
+    /// ; total on stack
+    /// ```
+    fn compile_sum_generator(
+        &mut self,
+        elt: &ExprLoc,
+        generators: &[Comprehension],
+        call_pos: CodeRange,
+    ) -> Result<(), CompileError> {
+        self.code.emit_i8(Opcode::LoadSmallInt, 0);
+
+        let depth = u8::try_from(generators.len()).expect("too many generators in generator expression");
+        self.compile_comprehension_generators(generators, 0, |compiler| {
+            compiler.compile_expr(elt)?;
+            // Errors adding an item are reported at the sum() call, like in CPython
+            compiler.code.set_location(call_pos, None);
+            compiler.code.emit_u8(Opcode::SumAdd, depth);
+            Ok(())
+        })
+    }
+
+    /// Compiles `any(...)` (`is_any`) or `all(...)` over a generator expression,
+    /// stopping at the first element that decides the result.
+    ///
+    /// Bytecode structure for `any` (`all` swaps the jump and the constants):
+    /// ```text
+    /// <comprehension loops as in compile_list_comp>
+    ///   <compile elt>
+    ///   JUMP_IF_TRUE found
+    /// LOAD_FALSE            ; exhausted without a match
+    /// JUMP end
+    /// found:
+    /// POP                   ; once per iterator still on the stack
+    /// LOAD_TRUE
+    /// end:
+    /// ```
+    fn compile_any_all_generator(
+        &mut self,
+        elt: &ExprLoc,
+        generators: &[Comprehension],
+        is_any: bool,
+    ) -> Result<(), CompileError> {
+        let (found_jump_op, found_result, exhausted_result) = if is_any {
+            (Opcode::JumpIfTrue, Opcode::LoadTrue, Opcode::LoadFalse)
+        } else {
+            (Opcode::JumpIfFalse, Opcode::LoadFalse, Opcode::LoadTrue)
+        };
+
+        let base_depth = self.code.stack_depth();
+        let mut found_jumps = Vec::new();
+        self.compile_comprehension_generators(generators, 0, |compiler| {
+            compiler.compile_expr(elt)?;
+            found_jumps.push(compiler.code.emit_jump(found_jump_op));
+            Ok(())
+        })?;
+
+        // Every loop finished: no element decided the result
+        self.code.set_stack_depth(base_depth);
+        self.code.emit(exhausted_result);
+        let end_jump = self.code.emit_jump(Opcode::Jump);
+
+        // An element decided the result with all the iterators still on the stack
+        let iterator_count = u16::try_from(generators.len()).expect("too many generators in generator expression");
+        self.code.set_stack_depth(base_depth + iterator_count);
+        for jump in found_jumps {
+            self.code.patch_jump(jump);
+        }
+        for _ in generators {
+            self.code.emit(Opcode::Pop);
+        }
+        self.code.emit(found_result);
+        self.code.patch_jump(end_jump);
+
+        Ok(())
+    }
+
     /// Recursively compiles comprehension generators (the for/if clauses).
     ///
     /// For each generator:
@@ -2152,7 +2266,7 @@ impl<'a> Compiler<'a> {
         let end_jump = self.code.emit_jump(Opcode::ForIter);
 
         // Store current value to target (single variable or tuple unpacking)
-        self.compile_unpack_target(&generator.target);
+        self.compile_unpack_target(&generator.target)?;
 
         // Compile filter conditions - jump back to loop start if any fails
         for cond in &generator.ifs {
@@ -2184,7 +2298,9 @@ impl<'a> Compiler<'a> {
     /// For single identifiers: emits a simple store.
     /// For nested tuples: emits `UnpackSequence` (or `UnpackEx` with starred) and recursively
     /// handles each sub-target.
-    fn compile_unpack_target(&mut self, target: &UnpackTarget) {
+    /// For subscripts and attributes: evaluates the object (and index) with the value on the
+    /// stack below them, then stores like `SubscriptAssign` and `AttrAssign`.
+    fn compile_unpack_target(&mut self, target: &UnpackTarget) -> Result<(), CompileError> {
         match target {
             UnpackTarget::Name(ident) => {
                 // Single identifier - just store directly
@@ -2215,10 +2331,32 @@ impl<'a> Compiler<'a> {
                 // After UnpackSequence/UnpackEx, values are on stack with first item on top
                 // Store them in order, recursively handling further nesting
                 for target in targets {
-                    self.compile_unpack_target(target);
+                    self.compile_unpack_target(target)?;
                 }
             }
+            UnpackTarget::Subscript {
+                object,
+                index,
+                position,
+            } => {
+                // Stack order for StoreSubscr: value, obj, index
+                self.compile_expr(object)?;
+                self.compile_expr(index)?;
+                self.code.set_location(*position, None);
+                self.code.emit(Opcode::StoreSubscr);
+            }
+            UnpackTarget::Attr { object, attr, position } => {
+                // Stack order for StoreAttr: value, obj
+                self.compile_expr(object)?;
+                let name_id = attr.string_id().expect("StoreAttr requires interned attr name");
+                self.code.set_location(*position, None);
+                self.code.emit_u16(
+                    Opcode::StoreAttr,
+                    u16::try_from(name_id.index()).expect("name index exceeds u16"),
+                );
+            }
         }
+        Ok(())
     }
 
     // ========================================================================
@@ -2361,10 +2499,18 @@ impl<'a> Compiler<'a> {
 
     /// Compiles a return statement, handling finally blocks properly.
     ///
+    /// The except handlers and finally blocks being left are unwound first, so handled
+    /// exceptions don't become the context of exceptions the caller raises and a pending
+    /// return value is replaced.
     /// If we're inside a try-finally block, the return value is kept on the stack
     /// and we jump to a "finally with return" section that runs finally then returns.
     /// Otherwise, we emit a direct `ReturnValue`.
     fn compile_return(&mut self) {
+        // The code following the return is unreachable, but the enclosing handlers still
+        // emit their cleanup after it; keep tracking the depth as if only the value was popped.
+        let depth = self.code.stack_depth();
+        let unwind_depth = self.finally_targets.last().map_or(0, |target| target.unwind_depth);
+        self.compile_unwind(unwind_depth, true);
         if let Some(finally_target) = self.finally_targets.last_mut() {
             // Inside a try-finally: jump to finally, then return
             // Return value is already on stack
@@ -2374,6 +2520,7 @@ impl<'a> Compiler<'a> {
             // Normal return
             self.code.emit(Opcode::ReturnValue);
         }
+        self.code.set_stack_depth(depth.saturating_sub(1));
     }
 
     /// Compiles a try/except/else/finally block.
@@ -2381,34 +2528,43 @@ impl<'a> Compiler<'a> {
     /// The bytecode structure is:
     /// ```text
     /// <try_body>                     # protected range
-    /// JUMP to_else_or_finally        # skip handlers if no exception
+    /// JUMP else_block                # skip handlers if no exception
     /// handler_dispatch:              # exception pushed by VM
     ///   # for each handler:
     ///   <check exception type>
     ///   <handler body>
     ///   CLEAR_EXCEPTION
-    ///   JUMP to_finally
+    ///   JUMP finally_block
     /// reraise:
     ///   RERAISE                      # no handler matched
     /// else_block:
     ///   <else_body>
     /// finally_block:
     ///   <finally_body>
+    ///   JUMP end
+    /// finally_cleanup:               # exception escaping handlers or else
+    ///   POP
+    ///   <finally_body>
+    ///   RERAISE
+    /// <finally_body + return/break/continue, for each that left the try>
     /// end:
     /// ```
     ///
     /// For finally blocks, exceptions that propagate through the handler dispatch
-    /// (including RERAISE when no handler matches) are caught by a second exception
-    /// entry that ensures finally runs before propagation.
+    /// (including RERAISE when no handler matches) or out of the else block are caught
+    /// by a second exception entry that ensures finally runs before propagation.
     ///
-    /// Returns inside try/except/else jump to a "finally with return" path that
-    /// runs the finally code then returns the value.
+    /// Returns, breaks and continues inside try/except/else jump to a path that runs
+    /// the finally code then carries on returning or jumping. The copies of the finally
+    /// body are compiled after the try's `FinallyTarget` is popped, so a return, break
+    /// or continue inside the finally body itself leaves without running it again, and
+    /// an exception raised by it propagates without running it again.
     ///
     /// **Note:** The finally block code is emitted multiple times (once for each
     /// control flow path: normal, exception, return, break, continue). This is the
     /// same approach CPython uses - each path has different stack state at entry
-    /// (e.g., return has a value on stack, break has popped the iterator), so we
-    /// can't easily share a single copy. The duplication is intentional.
+    /// (e.g., return has a value on stack), so we can't easily share a single copy.
+    /// The duplication is intentional.
     fn compile_try(&mut self, try_block: &Try<PreparedNode>) -> Result<(), CompileError> {
         let has_finally = !try_block.finally.is_empty();
         let has_handlers = !try_block.handlers.is_empty();
@@ -2424,6 +2580,7 @@ impl<'a> Compiler<'a> {
                 break_jumps: Vec::new(),
                 continue_jumps: Vec::new(),
                 loop_depth_at_entry: self.loop_stack.len(),
+                unwind_depth: self.unwind_stack.len(),
             });
         }
 
@@ -2464,44 +2621,74 @@ impl<'a> Compiler<'a> {
         // Mark end of handler dispatch (for finally exception entry)
         let handler_dispatch_end = self.code.current_offset();
 
-        // === Finally cleanup handler (for exceptions during handler dispatch) ===
-        // This catches exceptions from RERAISE (and any other exceptions in handlers)
-        // and ensures finally runs before the exception propagates.
-        let finally_cleanup_start = if has_finally {
+        // === Else block (runs if no exception) ===
+        // Compiled while the finally target is still active, so returns, breaks and
+        // continues in it go through finally too
+        self.code.patch_jump(after_try_jump);
+        // Normal path from try body, stack = stack_depth
+        self.code.set_stack_depth(stack_depth);
+        let else_start = self.code.current_offset();
+        if has_else {
+            self.compile_block(&try_block.or_else)?;
+        }
+        let else_end = self.code.current_offset();
+
+        // === Normal finally path (no exception pending, no return) ===
+        // Patch all jumps from handlers to go here
+        for jump in finally_jumps {
+            self.code.patch_jump(jump);
+        }
+
+        // === Add exception table entries ===
+        // Order matters: entries are searched in order, so inner entries must come first.
+        // Entries of try statements nested in the blocks were added while compiling them.
+
+        // Entry 1: Try body -> handler dispatch
+        if has_handlers || has_finally {
+            self.code.add_exception_entry(ExceptionEntry::new(
+                u32::try_from(try_start).expect("bytecode offset exceeds u32"),
+                u32::try_from(try_end).expect("bytecode offset exceeds u32") + 3, // +3 to include the JUMP instruction
+                u32::try_from(handler_start).expect("bytecode offset exceeds u32"),
+                stack_depth,
+            ));
+        }
+
+        if has_finally {
+            let finally_target = self.finally_targets.pop().expect("finally_targets should not be empty");
+
+            // Stack = stack_depth (no exception, no return value)
+            self.code.set_stack_depth(stack_depth);
+            self.compile_block(&try_block.finally)?;
+            let end_jump = self.code.emit_jump(Opcode::Jump);
+
+            // === Finally cleanup handler (for exceptions during handler dispatch or else) ===
+            // This catches exceptions from RERAISE (and any other exceptions in handlers)
+            // and ensures finally runs before the exception propagates.
             let cleanup_start = self.code.current_offset();
             // Exception value is on stack (pushed by VM), so stack = stack_depth + 1
             self.code.set_stack_depth(stack_depth + 1);
-            // We need to pop it, run finally, then reraise
-            // But we can't easily save the exception, so we use a different approach:
             // The exception is already on the exception_stack from handle_exception,
-            // so we can just pop from operand stack, run finally, then reraise.
-            self.code.emit(Opcode::Pop); // Pop exception from operand stack
+            // so we can just pop it from the operand stack, run finally, then reraise.
+            // A return, break or continue in the finally body discards it instead.
+            self.code.emit(Opcode::Pop);
+            self.unwind_stack.push(UnwindEntry::FinallyAfterException);
             self.compile_block(&try_block.finally)?;
+            self.unwind_stack.pop();
             self.code.emit(Opcode::Reraise); // Re-raise from exception_stack
-            Some(cleanup_start)
-        } else {
-            None
-        };
-
-        // === Finally with return/break/continue paths ===
-        // Pop finally target and get all the jumps that need to go through finally
-        let finally_with_return_start = if has_finally {
-            let finally_target = self.finally_targets.pop().expect("finally_targets should not be empty");
 
             // === Finally with return path ===
-            let return_start = if finally_target.return_jumps.is_empty() {
-                None
-            } else {
-                let start = self.code.current_offset();
+            if !finally_target.return_jumps.is_empty() {
                 for jump in finally_target.return_jumps {
                     self.code.patch_jump(jump);
                 }
                 // Return value is on stack, stack = stack_depth + 1
+                // A return, break or continue in the finally body replaces it
                 self.code.set_stack_depth(stack_depth + 1);
+                self.unwind_stack.push(UnwindEntry::FinallyAfterReturn);
                 self.compile_block(&try_block.finally)?;
+                self.unwind_stack.pop();
                 self.compile_return();
-                Some(start)
-            };
+            }
 
             // === Finally with break path ===
             // For each break, run finally then either:
@@ -2511,9 +2698,8 @@ impl<'a> Compiler<'a> {
                 for break_info in &finally_target.break_jumps {
                     self.code.patch_jump(break_info.jump);
                 }
-                // Break already popped the iterator, so stack = stack_depth - 1
-                // (the iterator was on stack at try entry, break removed it)
-                self.code.set_stack_depth(stack_depth.saturating_sub(1));
+                // The iterator is popped once the break reaches its loop, stack = stack_depth
+                self.code.set_stack_depth(stack_depth);
                 self.compile_block(&try_block.finally)?;
                 // After finally, compile the break again (handles nested finally or direct jump)
                 self.compile_control_flow_after_finally(&finally_target.break_jumps, true);
@@ -2531,77 +2717,28 @@ impl<'a> Compiler<'a> {
                 self.compile_control_flow_after_finally(&finally_target.continue_jumps, false);
             }
 
-            return_start
-        } else {
-            None
-        };
-
-        // === Else block (runs if no exception) ===
-        self.code.patch_jump(after_try_jump);
-        // Normal path from try body, stack = stack_depth
-        self.code.set_stack_depth(stack_depth);
-        let else_start = self.code.current_offset();
-        if has_else {
-            self.compile_block(&try_block.or_else)?;
-        }
-        let else_end = self.code.current_offset();
-
-        // === Normal finally path (no exception pending, no return) ===
-        // Patch all jumps from handlers to go here
-        for jump in finally_jumps {
-            self.code.patch_jump(jump);
-        }
-
-        if has_finally {
-            // Stack = stack_depth (no exception, no return value)
+            self.code.patch_jump(end_jump);
             self.code.set_stack_depth(stack_depth);
-            self.compile_block(&try_block.finally)?;
-        }
-
-        // === Add exception table entries ===
-        // Order matters: entries are searched in order, so inner entries must come first.
 
-        // Entry 1: Try body -> handler dispatch
-        if has_handlers || has_finally {
-            self.code.add_exception_entry(ExceptionEntry::new(
-                u32::try_from(try_start).expect("bytecode offset exceeds u32"),
-                u32::try_from(try_end).expect("bytecode offset exceeds u32") + 3, // +3 to include the JUMP instruction
-                u32::try_from(handler_start).expect("bytecode offset exceeds u32"),
-                stack_depth,
-            ));
-        }
-
-        // Entry 2: Handler dispatch -> finally cleanup (only if has_finally)
-        // This ensures finally runs when RERAISE is executed or any exception occurs in handlers
-        if let Some(cleanup_start) = finally_cleanup_start {
+            // Entry 2: Handler dispatch -> finally cleanup
+            // This ensures finally runs when RERAISE is executed or any exception occurs in handlers
             self.code.add_exception_entry(ExceptionEntry::new(
                 u32::try_from(handler_start).expect("bytecode offset exceeds u32"),
                 u32::try_from(handler_dispatch_end).expect("bytecode offset exceeds u32"),
                 u32::try_from(cleanup_start).expect("bytecode offset exceeds u32"),
                 stack_depth,
             ));
-        }
 
-        // Entry 3: Finally with return -> finally cleanup
-        // If an exception occurs while running finally (in the return path), catch it
-        if let (Some(return_start), Some(cleanup_start)) = (finally_with_return_start, finally_cleanup_start) {
-            self.code.add_exception_entry(ExceptionEntry::new(
-                u32::try_from(return_start).expect("bytecode offset exceeds u32"),
-                u32::try_from(else_start).expect("bytecode offset exceeds u32"), // End at else_start (before else block)
-                u32::try_from(cleanup_start).expect("bytecode offset exceeds u32"),
-                stack_depth,
-            ));
-        }
-
-        // Entry 4: Else block -> finally cleanup (only if has_finally and has_else)
-        // Exceptions in else block should go through finally
-        if has_else && let Some(cleanup_start) = finally_cleanup_start {
-            self.code.add_exception_entry(ExceptionEntry::new(
-                u32::try_from(else_start).expect("bytecode offset exceeds u32"),
-                u32::try_from(else_end).expect("bytecode offset exceeds u32"),
-                u32::try_from(cleanup_start).expect("bytecode offset exceeds u32"),
-                stack_depth,
-            ));
+            // Entry 3: Else block -> finally cleanup
+            // Exceptions in else block should go through finally
+            if has_else {
+                self.code.add_exception_entry(ExceptionEntry::new(
+                    u32::try_from(else_start).expect("bytecode offset exceeds u32"),
+                    u32::try_from(else_end).expect("bytecode offset exceeds u32"),
+                    u32::try_from(cleanup_start).expect("bytecode offset exceeds u32"),
+                    stack_depth,
+                ));
+            }
         }
 
         Ok(())
@@ -2682,17 +2819,17 @@ impl<'a> Compiler<'a> {
                 }
 
                 // Track that we're inside an except handler (for break/continue cleanup)
-                self.except_handler_depth += 1;
+                self.unwind_stack.push(UnwindEntry::Handler(handler.name));
 
                 // Compile handler body
-                self.compile_block(&handler.body)?;
+                self.compile_handler_body(handler, handler_entry_depth)?;
 
                 // Exit except handler context
-                self.except_handler_depth -= 1;
+                self.unwind_stack.pop();
 
                 // Delete exception variable (Python 3 behavior)
-                if let Some(name) = &handler.name {
-                    self.compile_delete(name);
+                if let Some(name) = handler.name {
+                    self.compile_unbind_except_name(name);
                 }
 
                 // Clear current_exception
@@ -2725,17 +2862,17 @@ impl<'a> Compiler<'a> {
                 }
 
                 // Track that we're inside an except handler (for break/continue cleanup)
-                self.except_handler_depth += 1;
+                self.unwind_stack.push(UnwindEntry::Handler(handler.name));
 
                 // Compile handler body
-                self.compile_block(&handler.body)?;
+                self.compile_handler_body(handler, handler_entry_depth)?;
 
                 // Exit except handler context
-                self.except_handler_depth -= 1;
+                self.unwind_stack.pop();
 
                 // Delete exception variable
-                if let Some(name) = &handler.name {
-                    self.compile_delete(name);
+                if let Some(name) = handler.name {
+                    self.compile_unbind_except_name(name);
                 }
 
                 // Clear current_exception
@@ -2752,18 +2889,61 @@ impl<'a> Compiler<'a> {
         Ok(())
     }
 
+    /// Compiles the body of an except handler.
+    ///
+    /// For `except ... as name`, the name must be unbound however the body exits, so an
+    /// exception escaping the body is caught by an extra exception entry whose cleanup
+    /// unbinds the name and re-raises (CPython wraps the body in `try/finally` for the
+    /// same reason).
+    ///
+    /// `handler_entry_depth` is the stack depth inside the handler (exception on stack).
+    fn compile_handler_body(
+        &mut self,
+        handler: &ExceptHandler<PreparedNode>,
+        handler_entry_depth: u16,
+    ) -> Result<(), CompileError> {
+        let body_start = self.code.current_offset();
+        self.compile_block(&handler.body)?;
+        let Some(name) = handler.name else {
+            return Ok(());
+        };
+        let body_end = self.code.current_offset();
+        let after_cleanup = self.code.emit_jump(Opcode::Jump);
+
+        // VM pushes the escaping exception: [exception, new_exception]
+        let cleanup_start = self.code.current_offset();
+        self.code.set_stack_depth(handler_entry_depth + 1);
+        self.code.emit(Opcode::Pop);
+        self.compile_unbind_except_name(name);
+        // Re-raise from exception_stack, keeping the original traceback
+        self.code.emit(Opcode::Reraise);
+
+        self.code.patch_jump(after_cleanup);
+        self.code.set_stack_depth(handler_entry_depth);
+        self.code.add_exception_entry(ExceptionEntry::new(
+            u32::try_from(body_start).expect("bytecode offset exceeds u32"),
+            u32::try_from(body_end).expect("bytecode offset exceeds u32"),
+            u32::try_from(cleanup_start).expect("bytecode offset exceeds u32"),
+            handler_entry_depth,
+        ));
+        Ok(())
+    }
+
+    /// Unbinds the name bound by `except ... as name`.
+    ///
+    /// Assigns `None` before deleting, like CPython, so this can't fail when the handler
+    /// body already deleted the name itself.
+    fn compile_unbind_except_name(&mut self, name: Identifier) {
+        self.code.emit(Opcode::LoadNone);
+        self.compile_store(&name);
+        self.compile_delete(&name);
+    }
+
     /// Compiles deletion of a variable.
     fn compile_delete(&mut self, target: &Identifier) {
         let slot = u16::try_from(target.namespace_id().index()).expect("local slot exceeds u16");
         match target.scope {
-            NameScope::Local | NameScope::LocalUnassigned => {
-                if let Ok(s) = u8::try_from(slot) {
-                    self.code.emit_u8(Opcode::DeleteLocal, s);
-                } else {
-                    // Wide variant not implemented yet
-                    todo!("DeleteLocalW for slot > 255");
-                }
-            }
+            NameScope::Local | NameScope::LocalUnassigned => self.code.emit_delete_local(slot),
             NameScope::Global | NameScope::Cell => {
                 // Delete global/cell not commonly needed
                 // For now, just store Undefined
@@ -2896,3 +3076,55 @@ fn cmp_operator_to_opcode(op: &CmpOperator) -> Opcode {
         CmpOperator::ModEq(_) => unreachable!("ModEq handled at call site"),
     }
 }
+
+// ============================================================================
+// Comprehension Sizing
+// ============================================================================
+
+/// Returns the capacity to pre-allocate for a comprehension, if it can be bounded at compile time.
+///
+/// Only a single generator iterating over a list/tuple/set literal or a `range()` of
+/// integer literals is bounded. Filters only make the bound looser, so they're allowed.
+/// Returns `None` for empty or unknown sizes, and caps the result at `MAX_PRESIZE_CAPACITY`.
+fn comprehension_size_hint(generators: &[Comprehension]) -> Option<u16> {
+    let [generator] = generators else {
+        return None;
+    };
+    let len = match &generator.iter.expr {
+        Expr::List(elements) | Expr::Tuple(elements) | Expr::Set(elements) => elements.len(),
+        Expr::Call {
+            callable: Callable::Builtin(Builtins::Type(Type::Range)),
+            args,
+        } => literal_range_len(args)?,
+        _ => return None,
+    };
+    if len == 0 {
+        return None;
+    }
+    Some(u16::try_from(len).unwrap_or(u16::MAX).min(MAX_PRESIZE_CAPACITY))
+}
+
+/// Computes the length of `range(...)` when all arguments are integer literals.
+fn literal_range_len(args: &ArgExprs) -> Option<usize> {
+    let int_arg = |arg: &ExprLoc| match arg.expr {
+        Expr::Literal(Literal::Int(v)) => Some(i128::from(v)),
+        _ => None,
+    };
+    let (start, stop, step) = match args {
+        ArgExprs::One(stop) => (0, int_arg(stop)?, 1),
+        ArgExprs::Two(start, stop) => (int_arg(start)?, int_arg(stop)?, 1),
+        ArgExprs::Args(args) => match args.as_slice() {
+            [start, stop, step] => (int_arg(start)?, int_arg(stop)?, int_arg(step)?),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let len = if step > 0 && start < stop {
+        (stop - start - 1) / step + 1
+    } else if step < 0 && start > stop {
+        (start - stop - 1) / -step + 1
+    } else {
+        0
+    };
+    usize::try_from(len).ok()
+}
@@ -0,0 +1,795 @@
+//! Peephole optimizer run over each code object after compilation.
+//!
+//! The compiler emits straightforward code for every construct; this pass cleans up
+//! the patterns that produces when constructs meet:
+//!
+//! - **Jump threading**: a jump whose target is an unconditional `Jump` goes straight
+//!   to the final destination, and a `Jump` to the very next instruction is removed.
+//! - **Redundant loads and stores**: a constant (or `Dup`) that is immediately popped
+//!   is dropped, and `StoreLocal x; LoadLocal x` becomes `Dup; StoreLocal x`.
+//! - **Compare and branch fusion**: a comparison followed by `JumpIfFalse` becomes a
+//!   single `CompareJumpIfFalse`, and `UnaryNot` before a conditional jump flips the jump.
+//! - **Superinstructions**: two loads of locals below slot 16 become one `LoadLocalPair`,
+//!   and `ForIter` followed by `StoreLocal` becomes `ForIterStoreLocal`, so the hottest
+//!   pairs in loop bodies and loop heads cost a single dispatch.
+//!
+//! The bytecode is decoded into instructions, rewritten, and re-encoded, with jump
+//! offsets, the location table and the exception table remapped to the new layout.
+//! A pair of instructions is only rewritten when nothing can jump between them, so
+//! every path through the code sees the same sequence of effects as before. The location
+//! of the second half of a superinstruction is kept one byte into the fused instruction,
+//! where the VM points `instruction_ip` before running that half, and instructions from
+//! different source lines are never merged, so tracebacks, trace hooks, the debugger and
+//! coverage see the same positions and lines with and without the optimizer.
+
+use std::collections::HashSet;
+
+use crate::bytecode::{
+    code::{Code, ExceptionEntry, LocationTable},
+    op::{self, Opcode},
+};
+
+/// Rewrites `code` in place.
+pub(super) fn optimize(code: &mut Code) {
+    let mut instrs = decode(code.bytecode());
+    for instr in &mut instrs {
+        instr.line = code
+            .location_for_offset(instr.offset as usize)
+            .map(|entry| entry.range().start().line);
+    }
+    thread_jumps(&mut instrs);
+
+    // offsets that control can arrive at from somewhere other than the previous instruction,
+    // or where an exception handler's protected range starts or ends
+    let mut boundaries: HashSet<u32> = instrs.iter().filter_map(|instr| instr.target).collect();
+    for entry in code.exception_table() {
+        boundaries.extend([entry.start(), entry.end(), entry.handler()]);
+    }
+
+    let mut grew_stack = false;
+    while rewrite_pairs(&mut instrs, &boundaries, &mut grew_stack) {}
+    remove_jumps_to_next(&mut instrs);
+
+    encode(code, &instrs, grew_stack);
+}
+
+/// A decoded instruction.
+#[derive(Debug, Clone)]
+struct Instr {
+    /// Offset of the instruction in the original bytecode.
+    offset: u32,
+    op: Opcode,
+    /// Operand bytes, not including the jump offset of jump instructions.
+    operands: Vec<u8>,
+    /// Absolute jump target, as an offset in the original bytecode.
+    target: Option<u32>,
+    /// Whether a rewrite has removed this instruction.
+    removed: bool,
+    /// Whether this instruction was removed by fusing it into the previous kept one.
+    fused: bool,
+    /// Source line the instruction was compiled from, if it has a location.
+    line: Option<u16>,
+}
+
+impl Instr {
+    /// Returns the encoded size of the instruction in bytes.
+    fn len(&self) -> u32 {
+        let jump_len = if self.target.is_some() { 2 } else { 0 };
+        1 + u32::try_from(self.operands.len()).expect("operand length exceeds u32") + jump_len
+    }
+
+    /// Returns the local slot loaded by a `LoadLocal*` instruction.
+    fn loaded_local(&self) -> Option<u16> {
+        match self.op {
+            Opcode::LoadLocal0 => Some(0),
+            Opcode::LoadLocal1 => Some(1),
+            Opcode::LoadLocal2 => Some(2),
+            Opcode::LoadLocal3 => Some(3),
+            Opcode::LoadLocal => Some(u16::from(self.operands[0])),
+            Opcode::LoadLocalW => Some(u16::from_le_bytes([self.operands[0], self.operands[1]])),
+            _ => None,
+        }
+    }
+
+    /// Returns the local slot written by a `StoreLocal*` instruction.
+    fn stored_local(&self) -> Option<u16> {
+        match self.op {
+            Opcode::StoreLocal => Some(u16::from(self.operands[0])),
+            Opcode::StoreLocalW => Some(u16::from_le_bytes([self.operands[0], self.operands[1]])),
+            _ => None,
+        }
+    }
+
+    fn remove(&mut self) {
+        self.removed = true;
+    }
+
+    /// Removes this instruction as the second half of a superinstruction.
+    fn fuse_into_previous(&mut self) {
+        self.removed = true;
+        self.fused = true;
+    }
+}
+
+/// Decodes raw bytecode into instructions.
+fn decode(bytecode: &[u8]) -> Vec<Instr> {
+    op::decode(bytecode)
+        .into_iter()
+        .map(|raw| Instr {
+            offset: raw.offset,
+            op: raw.op,
+            operands: raw.operands.to_vec(),
+            target: raw.target,
+            removed: false,
+            fused: false,
+            line: None,
+        })
+        .collect()
+}
+
+/// Returns the index of the instruction starting at `offset`, if any.
+fn index_at(instrs: &[Instr], offset: u32) -> Option<usize> {
+    instrs.binary_search_by_key(&offset, |instr| instr.offset).ok()
+}
+
+/// Returns whether a jump ending at `from` can reach `to` with an i16 offset.
+fn fits_jump(from: u32, to: u32) -> bool {
+    i16::try_from(i64::from(to) - i64::from(from)).is_ok()
+}
+
+/// Points every jump whose target is an unconditional `Jump` at that jump's destination.
+///
+/// Offsets only shrink during encoding, so a threaded jump that fits in an i16 against
+/// the original layout still fits afterwards.
+fn thread_jumps(instrs: &mut [Instr]) {
+    for idx in 0..instrs.len() {
+        let Some(mut target) = instrs[idx].target else {
+            continue;
+        };
+        let jump_end = instrs[idx].offset + instrs[idx].len();
+        // bounded by the instruction count so a cycle of jumps can't loop forever
+        for _ in 0..instrs.len() {
+            let Some(next) = index_at(instrs, target)
+                .filter(|&t| instrs[t].op == Opcode::Jump)
+                .and_then(|t| instrs[t].target)
+            else {
+                break;
+            };
+            if next == target || !fits_jump(jump_end, next) {
+                break;
+            }
+            target = next;
+        }
+        instrs[idx].target = Some(target);
+    }
+}
+
+/// Makes one pass over adjacent instruction pairs, returning whether anything changed.
+fn rewrite_pairs(instrs: &mut [Instr], boundaries: &HashSet<u32>, grew_stack: &mut bool) -> bool {
+    let kept: Vec<usize> = (0..instrs.len()).filter(|&i| !instrs[i].removed).collect();
+    let mut changed = false;
+    let mut k = 0;
+    while k + 1 < kept.len() {
+        let (first, second) = (kept[k], kept[k + 1]);
+        // removed instructions between the pair still count: jumps to them land on `second`
+        let joined = instrs[first + 1..=second]
+            .iter()
+            .any(|instr| boundaries.contains(&instr.offset));
+        if !joined && rewrite_pair(instrs, first, second, grew_stack) {
+            changed = true;
+            k += 2;
+        } else {
+            k += 1;
+        }
+    }
+    changed
+}
+
+/// Rewrites a pair of adjacent instructions if they match a known pattern.
+fn rewrite_pair(instrs: &mut [Instr], first: usize, second: usize, grew_stack: &mut bool) -> bool {
+    let (head, tail) = instrs.split_at_mut(second);
+    let (a, b) = (&mut head[first], &mut tail[0]);
+    // Every rewrite but the store and load swap leaves at most one instruction where there
+    // were two, which would skip the line event of the second if it starts a new line
+    if a.line != b.line && a.stored_local().is_none() {
+        return false;
+    }
+    match (a.op, b.op) {
+        // a value pushed only to be popped again
+        (
+            Opcode::LoadConst
+            | Opcode::LoadNone
+            | Opcode::LoadTrue
+            | Opcode::LoadFalse
+            | Opcode::LoadSmallInt
+            | Opcode::Dup,
+            Opcode::Pop,
+        ) => {
+            a.remove();
+            b.remove();
+        }
+        // `x = ...` followed by a read of `x`: keep the value instead of reloading it
+        (Opcode::StoreLocal | Opcode::StoreLocalW, _)
+            if a.stored_local().is_some_and(|s| b.loaded_local() == Some(s)) =>
+        {
+            b.op = a.op;
+            b.operands = std::mem::take(&mut a.operands);
+            a.op = Opcode::Dup;
+            *grew_stack = true;
+        }
+        (Opcode::UnaryNot, Opcode::JumpIfFalse | Opcode::JumpIfTrue) => {
+            a.remove();
+            b.op = if b.op == Opcode::JumpIfFalse {
+                Opcode::JumpIfTrue
+            } else {
+                Opcode::JumpIfFalse
+            };
+        }
+        (compare, Opcode::JumpIfFalse | Opcode::JumpIfTrue) => {
+            let fused = if b.op == Opcode::JumpIfFalse {
+                fusable_compare(compare)
+            } else {
+                negated_compare(compare)
+            };
+            let Some(fused) = fused else {
+                return false;
+            };
+            a.op = Opcode::CompareJumpIfFalse;
+            a.operands = vec![fused as u8];
+            a.target = b.target;
+            b.remove();
+        }
+        (
+            Opcode::LoadLocal0 | Opcode::LoadLocal1 | Opcode::LoadLocal2 | Opcode::LoadLocal3 | Opcode::LoadLocal,
+            Opcode::LoadLocal0 | Opcode::LoadLocal1 | Opcode::LoadLocal2 | Opcode::LoadLocal3 | Opcode::LoadLocal,
+        ) => {
+            let (Some(first), Some(second)) = (a.loaded_local(), b.loaded_local()) else {
+                return false;
+            };
+            if first >= 16 || second >= 16 {
+                return false;
+            }
+            a.op = Opcode::LoadLocalPair;
+            a.operands = vec![u8::try_from((first << 4) | second).expect("slots are below 16")];
+            b.fuse_into_previous();
+        }
+        (Opcode::ForIter, Opcode::StoreLocal) => {
+            a.op = Opcode::ForIterStoreLocal;
+            a.operands = std::mem::take(&mut b.operands);
+            b.fuse_into_previous();
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Returns `compare` if it can be fused into `CompareJumpIfFalse`.
+fn fusable_compare(compare: Opcode) -> Option<Opcode> {
+    matches!(
+        compare,
+        Opcode::CompareEq
+            | Opcode::CompareNe
+            | Opcode::CompareLt
+            | Opcode::CompareLe
+            | Opcode::CompareGt
+            | Opcode::CompareGe
+            | Opcode::CompareIs
+            | Opcode::CompareIsNot
+            | Opcode::CompareIn
+            | Opcode::CompareNotIn
+    )
+    .then_some(compare)
+}
+
+/// Returns the comparison that is false exactly when `compare` is true.
+///
+/// Ordering comparisons have no such inverse: `a < b` and `a >= b` are both false
+/// when either side is NaN.
+fn negated_compare(compare: Opcode) -> Option<Opcode> {
+    match compare {
+        Opcode::CompareEq => Some(Opcode::CompareNe),
+        Opcode::CompareNe => Some(Opcode::CompareEq),
+        Opcode::CompareIs => Some(Opcode::CompareIsNot),
+        Opcode::CompareIsNot => Some(Opcode::CompareIs),
+        Opcode::CompareIn => Some(Opcode::CompareNotIn),
+        Opcode::CompareNotIn => Some(Opcode::CompareIn),
+        _ => None,
+    }
+}
+
+/// Removes unconditional jumps that land on the instruction right after them.
+fn remove_jumps_to_next(instrs: &mut [Instr]) {
+    for idx in 0..instrs.len() {
+        if instrs[idx].removed || instrs[idx].op != Opcode::Jump {
+            continue;
+        }
+        let target = instrs[idx].target.expect("Jump has a target");
+        // the next instruction that will still be emitted
+        let next_kept = instrs[idx + 1..].iter().find(|instr| !instr.removed);
+        // the instruction the jump lands on once removed instructions are skipped
+        let landing = instrs[instrs.partition_point(|instr| instr.offset < target)..]
+            .iter()
+            .find(|instr| !instr.removed);
+        if let (Some(next), Some(landing)) = (next_kept, landing)
+            && next.offset == landing.offset
+        {
+            instrs[idx].remove();
+        }
+    }
+}
+
+/// Re-encodes `instrs` into `code`, remapping jumps, locations and exception ranges.
+fn encode(code: &mut Code, instrs: &[Instr], grew_stack: bool) {
+    // Removed instructions take the offset of the next emitted one, which is where
+    // jumps and exception ranges that pointed at them now land.
+    let mut new_offsets = Vec::with_capacity(instrs.len());
+    let mut size = 0;
+    for instr in instrs {
+        new_offsets.push(size);
+        if !instr.removed {
+            size += instr.len();
+        }
+    }
+    let remap = |old: u32| match instrs.binary_search_by_key(&old, |instr| instr.offset) {
+        Ok(idx) => new_offsets[idx],
+        Err(idx) if idx == instrs.len() => size,
+        Err(_) => panic!("offset {old} is not an instruction boundary"),
+    };
+
+    let mut bytecode = Vec::with_capacity(size as usize);
+    for (instr, &offset) in instrs.iter().zip(&new_offsets) {
+        if instr.removed {
+            continue;
+        }
+        bytecode.push(instr.op as u8);
+        bytecode.extend_from_slice(&instr.operands);
+        if let Some(target) = instr.target {
+            let relative = i64::from(remap(target)) - i64::from(offset + instr.len());
+            let relative = i16::try_from(relative).expect("jump offset grew during peephole optimization");
+            bytecode.extend_from_slice(&relative.to_le_bytes());
+        }
+    }
+
+    // Entries only mark where the location changes, so the entry of a removed instruction
+    // moves to the next emitted one, which may have been relying on it; if that instruction
+    // has an entry of its own, it replaces the moved one.
+    let mut location_table = LocationTable::default();
+    for entry in code.location_table().entries() {
+        let Some(idx) = index_at(instrs, entry.bytecode_offset()) else {
+            continue;
+        };
+        let offset = if instrs[idx].fused {
+            let Some(fused_into) = instrs[..idx].iter().rposition(|instr| !instr.removed) else {
+                continue;
+            };
+            new_offsets[fused_into] + 1
+        } else {
+            new_offsets[idx]
+        };
+        if offset < size {
+            location_table.push(entry.moved_to(offset));
+        }
+    }
+    let exception_table = code
+        .exception_table()
+        .iter()
+        .map(|entry| {
+            ExceptionEntry::new(
+                remap(entry.start()),
+                remap(entry.end()),
+                remap(entry.handler()),
+                entry.stack_depth(),
+            )
+        })
+        .collect();
+    let stack_size = code.stack_size() + u16::from(grew_stack);
+
+    code.replace_bytecode(bytecode, location_table, exception_table, stack_size);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+
+    use super::*;
+    use crate::{
+        bytecode::{builder::CodeBuilder, dis::disassemble_code},
+        heap::Heap,
+        intern::{InternerBuilder, Interns, StringId},
+        parse::CodeRange,
+        resource::NoLimitTracker,
+    };
+
+    /// Renders one instruction per line as `offset: Opcode operands`, showing the comparison
+    /// fused into `CompareJumpIfFalse` by name.
+    fn disassemble(code: &Code) -> String {
+        let heap = Heap::new(0, NoLimitTracker);
+        let interns = Interns::new(InternerBuilder::new(""), vec![], vec![]);
+        let mut out = String::new();
+        for instr in disassemble_code(String::new(), code, code, &heap, &interns).instructions {
+            write!(out, "{}: {}", instr.offset, instr.opname).unwrap();
+            if instr.opname == "CompareJumpIfFalse" {
+                write!(out, " {}", instr.argrepr).unwrap();
+            } else {
+                for arg in &instr.args {
+                    write!(out, " {arg}").unwrap();
+                }
+            }
+            if let Some(target) = instr.jump_target {
+                write!(out, " -> {target}").unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn optimized(builder: CodeBuilder) -> String {
+        let mut code = builder.build(8);
+        optimize(&mut code);
+        disassemble(&code)
+    }
+
+    #[test]
+    fn threads_jump_to_jump() {
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadTrue);
+        let to_trampoline = builder.emit_jump(Opcode::JumpIfFalse);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(to_trampoline);
+        let to_end = builder.emit_jump(Opcode::Jump);
+        builder.emit(Opcode::LoadTrue);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(to_end);
+        builder.emit(Opcode::LoadFalse);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadTrue
+1: JumpIfFalse -> 11
+4: LoadNone
+5: ReturnValue
+6: Jump -> 11
+9: LoadTrue
+10: ReturnValue
+11: LoadFalse
+12: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn removes_jump_to_next_instruction() {
+        let mut builder = CodeBuilder::new();
+        let jump = builder.emit_jump(Opcode::Jump);
+        builder.patch_jump(jump);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(optimized(builder), "0: LoadNone\n1: ReturnValue\n");
+    }
+
+    #[test]
+    fn drops_constants_that_are_popped() {
+        let mut builder = CodeBuilder::new();
+        let idx = builder.add_const(crate::value::Value::Int(1));
+        builder.emit_u16(Opcode::LoadConst, idx);
+        builder.emit(Opcode::Pop);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::Pop);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(optimized(builder), "0: LoadNone\n1: ReturnValue\n");
+    }
+
+    #[test]
+    fn store_then_load_becomes_dup_store() {
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadTrue);
+        builder.emit_store_local(5);
+        builder.emit_load_local(5);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadTrue
+1: Dup
+2: StoreLocal 5
+4: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn store_then_load_of_other_slot_is_kept() {
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadTrue);
+        builder.emit_store_local(1);
+        builder.emit_load_local(2);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadTrue
+1: StoreLocal 1
+3: LoadLocal2
+4: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn fuses_compare_and_branch() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(0);
+        builder.emit_load_local(1);
+        builder.emit(Opcode::CompareLt);
+        let else_jump = builder.emit_jump(Opcode::JumpIfFalse);
+        builder.emit(Opcode::LoadTrue);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(else_jump);
+        builder.emit(Opcode::LoadFalse);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocalPair 1
+2: CompareJumpIfFalse CompareLt -> 8
+6: LoadTrue
+7: ReturnValue
+8: LoadFalse
+9: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn negates_equality_before_jump_if_true() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(0);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::CompareIs);
+        let skip = builder.emit_jump(Opcode::JumpIfTrue);
+        builder.emit(Opcode::LoadTrue);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(skip);
+        builder.emit(Opcode::LoadFalse);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocal0
+1: LoadNone
+2: CompareJumpIfFalse CompareIsNot -> 8
+6: LoadTrue
+7: ReturnValue
+8: LoadFalse
+9: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn ordering_compare_before_jump_if_true_is_kept() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(0);
+        builder.emit_load_local(1);
+        builder.emit(Opcode::CompareLt);
+        let skip = builder.emit_jump(Opcode::JumpIfTrue);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(skip);
+        builder.emit(Opcode::LoadTrue);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocalPair 1
+2: CompareLt
+3: JumpIfTrue -> 8
+6: LoadNone
+7: ReturnValue
+8: LoadTrue
+9: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn not_flips_conditional_jump() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(0);
+        builder.emit(Opcode::UnaryNot);
+        let skip = builder.emit_jump(Opcode::JumpIfFalse);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+        builder.patch_jump(skip);
+        builder.emit(Opcode::LoadTrue);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocal0
+1: JumpIfTrue -> 6
+4: LoadNone
+5: ReturnValue
+6: LoadTrue
+7: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn pair_split_by_jump_target_is_kept() {
+        // a loop whose head re-reads the variable stored just before it
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadTrue);
+        builder.emit_store_local(0);
+        let loop_start = builder.current_offset();
+        builder.emit_load_local(0);
+        let exit = builder.emit_jump(Opcode::JumpIfFalse);
+        builder.emit(Opcode::LoadFalse);
+        builder.emit_store_local(0);
+        builder.emit_jump_to(Opcode::Jump, loop_start);
+        builder.patch_jump(exit);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadTrue
+1: StoreLocal 0
+3: LoadLocal0
+4: JumpIfFalse -> 13
+7: LoadFalse
+8: StoreLocal 0
+10: Jump -> 3
+13: LoadNone
+14: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn fuses_adjacent_local_loads() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(2);
+        builder.emit_load_local(15);
+        builder.emit(Opcode::BinaryAdd);
+        builder.emit_load_local(3);
+        builder.emit_load_local(16);
+        builder.emit(Opcode::BinaryAdd);
+        builder.emit(Opcode::BinaryAdd);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocalPair 47
+2: BinaryAdd
+3: LoadLocal3
+4: LoadLocal 16
+6: BinaryAdd
+7: BinaryAdd
+8: ReturnValue
+"
+        );
+    }
+
+    #[test]
+    fn fuses_for_iter_and_store() {
+        let mut builder = CodeBuilder::new();
+        builder.emit_load_local(0);
+        builder.emit(Opcode::GetIter);
+        let loop_start = builder.current_offset();
+        let exit = builder.emit_jump(Opcode::ForIter);
+        builder.emit_store_local(5);
+        builder.emit_jump_to(Opcode::Jump, loop_start);
+        builder.patch_jump(exit);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+
+        assert_eq!(
+            optimized(builder),
+            "\
+0: LoadLocal0
+1: GetIter
+2: ForIterStoreLocal 5 -> 9
+6: Jump -> 2
+9: LoadNone
+10: ReturnValue
+"
+        );
+    }
+
+    /// Returns a source range that differs from `location(m)` for every `m != n`.
+    fn location(n: u16) -> CodeRange {
+        let mut range = CodeRange::default();
+        range.filename = StringId::from_index(n);
+        range
+    }
+
+    #[test]
+    fn fused_half_keeps_its_location() {
+        let mut builder = CodeBuilder::new();
+        builder.set_location(location(0), None);
+        builder.emit_load_local(0);
+        builder.set_location(location(1), None);
+        builder.emit_load_local(1);
+        builder.set_location(location(2), None);
+        builder.emit(Opcode::BinaryAdd);
+        builder.set_location(location(3), None);
+        builder.emit(Opcode::ReturnValue);
+
+        let mut code = builder.build(2);
+        optimize(&mut code);
+        assert_eq!(disassemble(&code), "0: LoadLocalPair 1\n2: BinaryAdd\n3: ReturnValue\n");
+        // the second load's entry sits inside the fused instruction, where the VM looks it up
+        let offsets: Vec<u32> = code
+            .location_table()
+            .entries()
+            .iter()
+            .map(crate::bytecode::code::LocationEntry::bytecode_offset)
+            .collect();
+        assert_eq!(offsets, [0, 1, 2, 3]);
+        assert_eq!(code.location_for_offset(1).unwrap().range(), location(1));
+    }
+
+    #[test]
+    fn removed_instruction_passes_its_location_on() {
+        let mut builder = CodeBuilder::new();
+        builder.set_location(location(0), None);
+        builder.emit_load_local(0);
+        // only `LoadNone` gets an entry, the two instructions after it share it
+        builder.set_location(location(1), None);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::Pop);
+        builder.emit(Opcode::ReturnValue);
+
+        let mut code = builder.build(1);
+        optimize(&mut code);
+        assert_eq!(disassemble(&code), "0: LoadLocal0\n1: ReturnValue\n");
+        assert_eq!(code.location_for_offset(0).unwrap().range(), location(0));
+        assert_eq!(code.location_for_offset(1).unwrap().range(), location(1));
+    }
+
+    #[test]
+    fn remaps_exception_table_and_jumps_after_removed_code() {
+        let mut builder = CodeBuilder::new();
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::Pop);
+        let try_start = builder.current_offset();
+        builder.emit_load_local(0);
+        builder.emit(Opcode::ReturnValue);
+        let handler = builder.current_offset();
+        builder.emit(Opcode::Pop);
+        builder.emit(Opcode::LoadNone);
+        builder.emit(Opcode::ReturnValue);
+        let to_u32 = |offset: usize| u32::try_from(offset).unwrap();
+        builder.add_exception_entry(ExceptionEntry::new(
+            to_u32(try_start),
+            to_u32(handler),
+            to_u32(handler),
+            0,
+        ));
+
+        let mut code = builder.build(1);
+        optimize(&mut code);
+        assert_eq!(
+            disassemble(&code),
+            "0: LoadLocal0\n1: ReturnValue\n2: Pop\n3: LoadNone\n4: ReturnValue\n"
+        );
+        let entry = code.exception_table()[0];
+        assert_eq!((entry.start(), entry.end(), entry.handler()), (0, 2, 2));
+    }
+}
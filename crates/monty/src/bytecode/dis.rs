@@ -0,0 +1,233 @@
+//! Disassembler for compiled code, in the spirit of CPython's `dis` module.
+//!
+//! Used to debug the compiler and check what the peephole optimizer produced. Operands
+//! are shown raw and, where the compiler's tables allow, resolved to names and constant
+//! reprs.
+
+use std::fmt::{self, Write};
+
+use crate::{
+    bytecode::{
+        code::Code,
+        op::{self, Opcode, Operands},
+    },
+    heap::Heap,
+    intern::{FunctionId, Interns, StringId},
+    resource::{DepthGuard, NoLimitTracker},
+    types::PyTrait,
+};
+
+/// One decoded bytecode instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Byte offset of the instruction in its code object.
+    pub offset: u32,
+    /// Opcode name, e.g. `"LoadConst"`.
+    pub opname: String,
+    /// Operand values in encoding order, not including jump offsets.
+    pub args: Vec<i32>,
+    /// Operands resolved to names, constant reprs or comparison opcodes; empty if there's
+    /// nothing to resolve.
+    pub argrepr: String,
+    /// Absolute byte offset a jump instruction jumps to.
+    pub jump_target: Option<u32>,
+    /// Source line (1-based) the instruction was compiled from.
+    pub line: Option<u16>,
+    /// Whether any jump in the same code object lands on this instruction.
+    pub is_jump_target: bool,
+}
+
+/// The instructions of one code object: the module or a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeDisassembly {
+    /// `"<module>"` or the function's name.
+    pub name: String,
+    /// Instructions in bytecode order.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Renders like `dis.dis()`: a line number where the line changes, `>>` on jump
+/// targets, then offset, opcode, raw operands and resolved operands.
+impl fmt::Display for CodeDisassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Disassembly of {}:", self.name)?;
+        let mut last_line = None;
+        for instr in &self.instructions {
+            if instr.line != last_line {
+                match instr.line {
+                    Some(line) => write!(f, "{line:>4}")?,
+                    None => f.write_str("    ")?,
+                }
+                last_line = instr.line;
+            } else {
+                f.write_str("    ")?;
+            }
+            let marker = if instr.is_jump_target { ">>" } else { "" };
+            let mut args: Vec<String> = instr.args.iter().map(ToString::to_string).collect();
+            if let Some(target) = instr.jump_target {
+                args.push(format!("to {target}"));
+            }
+            let line = format!(
+                "{marker:>6} {:>5} {:<24} {}",
+                instr.offset,
+                instr.opname,
+                args.join(" ")
+            );
+            if instr.argrepr.is_empty() {
+                writeln!(f, "{}", line.trim_end())?;
+            } else {
+                writeln!(f, "{line} ({})", instr.argrepr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Disassembles the module code followed by every function, in definition order.
+pub(crate) fn disassemble(module_code: &Code, interns: &Interns) -> Vec<CodeDisassembly> {
+    // constants are immediate values, so the heap is only needed to call `py_repr` and never read
+    let heap = Heap::new(0, NoLimitTracker);
+    let mut codes = vec![disassemble_code(
+        "<module>".to_owned(),
+        module_code,
+        module_code,
+        &heap,
+        interns,
+    )];
+    for index in 0..interns.function_count() {
+        let index = u16::try_from(index).expect("function index exceeds u16");
+        let function = interns.get_function(FunctionId::from_index(index));
+        let name = interns.get_str(function.name.name_id).to_owned();
+        codes.push(disassemble_code(name, &function.code, module_code, &heap, interns));
+    }
+    codes
+}
+
+/// Disassembles one code object.
+///
+/// `module_code` supplies the names of global slots, which are the module's locals, and
+/// `heap` is only passed to the `repr()` of constants.
+pub(crate) fn disassemble_code(
+    name: String,
+    code: &Code,
+    module_code: &Code,
+    heap: &Heap<NoLimitTracker>,
+    interns: &Interns,
+) -> CodeDisassembly {
+    let raw = op::decode(code.bytecode());
+    let instructions = raw
+        .iter()
+        .map(|instr| {
+            let args = decode_args(instr.op, instr.operands);
+            Instruction {
+                offset: instr.offset,
+                opname: format!("{:?}", instr.op),
+                argrepr: argrepr(instr.op, &args, code, module_code, heap, interns),
+                args,
+                jump_target: instr.target,
+                line: code
+                    .location_for_offset(instr.offset as usize)
+                    .map(|location| location.range().start().line),
+                is_jump_target: raw.iter().any(|other| other.target == Some(instr.offset)),
+            }
+        })
+        .collect();
+    CodeDisassembly { name, instructions }
+}
+
+/// Splits operand bytes into values following the opcode's layout.
+fn decode_args(op: Opcode, operands: &[u8]) -> Vec<i32> {
+    let u8_at = |i: usize| i32::from(operands[i]);
+    let u16_at = |i: usize| i32::from(u16::from_le_bytes([operands[i], operands[i + 1]]));
+    match Operands::of(op) {
+        Operands::None | Operands::Jump => vec![],
+        Operands::U8 if op == Opcode::LoadSmallInt => vec![i32::from(operands[0].cast_signed())],
+        Operands::U8 | Operands::U8Jump => vec![u8_at(0)],
+        Operands::U16 => vec![u16_at(0)],
+        Operands::U8U8 => vec![u8_at(0), u8_at(1)],
+        Operands::U16U8 => vec![u16_at(0), u8_at(2)],
+        Operands::U16U8U8 => vec![u16_at(0), u8_at(2), u8_at(3)],
+        Operands::U16U16 => vec![u16_at(0), u16_at(2)],
+        Operands::U16U8U16 => vec![u16_at(0), u8_at(2), u16_at(3)],
+        Operands::CallFunctionKw => {
+            let mut args = vec![u8_at(0), u8_at(1)];
+            args.extend((2..operands.len()).step_by(2).map(u16_at));
+            args
+        }
+        Operands::CallAttrKw => {
+            let mut args = vec![u16_at(0), u8_at(2), u8_at(3)];
+            args.extend((4..operands.len()).step_by(2).map(u16_at));
+            args
+        }
+    }
+}
+
+/// Resolves operands to something readable, or returns an empty string.
+fn argrepr(
+    op: Opcode,
+    args: &[i32],
+    code: &Code,
+    module_code: &Code,
+    heap: &Heap<NoLimitTracker>,
+    interns: &Interns,
+) -> String {
+    let arg = |i: usize| u16::try_from(args[i]).expect("operand is a u16");
+    let string = |i: usize| interns.get_str(StringId::from_index(arg(i))).to_owned();
+    let local = |code: &Code, slot: u16| {
+        code.local_name(slot)
+            .filter(|id| *id != StringId::default())
+            .map_or_else(String::new, |id| interns.get_str(id).to_owned())
+    };
+    match op {
+        Opcode::LoadConst | Opcode::RaiseImportError => {
+            let value = code.constants().get(arg(0));
+            value.py_repr(heap, &mut DepthGuard::default(), interns).into_owned()
+        }
+        Opcode::LoadLocal0 => local(code, 0),
+        Opcode::LoadLocal1 => local(code, 1),
+        Opcode::LoadLocal2 => local(code, 2),
+        Opcode::LoadLocal3 => local(code, 3),
+        Opcode::LoadLocal
+        | Opcode::LoadLocalW
+        | Opcode::StoreLocal
+        | Opcode::StoreLocalW
+        | Opcode::DeleteLocal
+        | Opcode::DeleteLocalW
+        | Opcode::ForIterStoreLocal => local(code, arg(0)),
+        Opcode::LoadLocalPair => {
+            let slots = arg(0);
+            format!("{}, {}", local(code, slots >> 4), local(code, slots & 0xF))
+        }
+        Opcode::LoadGlobal | Opcode::StoreGlobal => local(module_code, arg(0)),
+        Opcode::LoadAttr | Opcode::LoadAttrImport | Opcode::StoreAttr | Opcode::CallAttr | Opcode::CallAttrExtended => {
+            string(0)
+        }
+        Opcode::CallAttrKw => {
+            let kwargs: Vec<String> = (3..args.len()).map(string).collect();
+            format!("{}, kwargs: {}", string(0), kwargs.join(", "))
+        }
+        Opcode::CallFunctionKw => {
+            let kwargs: Vec<String> = (2..args.len()).map(string).collect();
+            format!("kwargs: {}", kwargs.join(", "))
+        }
+        Opcode::MakeFunction | Opcode::MakeClosure => {
+            let function = interns.get_function(FunctionId::from_index(arg(0)));
+            interns.get_str(function.name.name_id).to_owned()
+        }
+        Opcode::CompareJumpIfFalse => Opcode::try_from(u8::try_from(args[0]).expect("operand is a u8"))
+            .map_or_else(|_| String::new(), |compare| format!("{compare:?}")),
+        _ => String::new(),
+    }
+}
+
+/// Renders every code object, separated by blank lines.
+pub(crate) fn render(codes: &[CodeDisassembly]) -> String {
+    let mut out = String::new();
+    for (i, code) in codes.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        write!(out, "{code}").expect("writing to a String never fails");
+    }
+    out
+}
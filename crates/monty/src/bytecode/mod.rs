@@ -10,14 +10,18 @@
 //! - `code` - Code object containing bytecode and metadata
 //! - `builder` - CodeBuilder for emitting bytecode during compilation
 //! - `compiler` - AST to bytecode compiler
+//! - `dis` - Disassembler for debugging compiled code
 //! - `vm` - Virtual machine for bytecode execution
 
 mod builder;
 mod code;
 mod compiler;
+mod dis;
 mod op;
 mod vm;
 
 pub use code::Code;
+pub(crate) use code::validate_program;
 pub use compiler::Compiler;
-pub use vm::{FrameExit, VM, VMSnapshot};
+pub use dis::{CodeDisassembly, Instruction, disassemble, render as render_disassembly};
+pub use vm::{Debugger, FrameExit, VM, VMSnapshot};
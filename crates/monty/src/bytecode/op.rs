@@ -57,6 +57,11 @@ pub enum Opcode {
     LoadLocal,
     /// Push local (wide, slot > 255). Operand: u16 slot.
     LoadLocalW,
+    /// Push two locals, first the high nibble's slot then the low nibble's.
+    /// Operand: u8 packing two slots below 16.
+    ///
+    /// Emitted by the peephole optimizer in place of two adjacent local loads.
+    LoadLocalPair,
     /// Pop and store to local. Operand: u8 slot.
     StoreLocal,
     /// Store local (wide). Operand: u16 slot.
@@ -71,6 +76,8 @@ pub enum Opcode {
     StoreCell,
     /// Delete local variable. Operand: u8 slot.
     DeleteLocal,
+    /// Delete local variable (wide). Operand: u16 slot.
+    DeleteLocalW,
 
     // === Binary Operations (no operand) ===
     /// Add: a + b.
@@ -172,6 +179,14 @@ pub enum Opcode {
     BuildDict,
     /// Pop n items, build set. Operand: u16 count.
     BuildSet,
+    /// Push an empty list with pre-allocated capacity. Operand: u16 capacity.
+    ///
+    /// Emitted for comprehensions whose size the compiler can bound at compile time.
+    BuildListSized,
+    /// Push an empty dict with pre-allocated capacity. Operand: u16 capacity.
+    BuildDictSized,
+    /// Push an empty set with pre-allocated capacity. Operand: u16 capacity.
+    BuildSetSized,
     /// Format a value for f-string interpolation. Operand: u8 flags.
     ///
     /// Flags encoding:
@@ -225,6 +240,13 @@ pub enum Opcode {
     /// Dict is at stack position (len - 3 - depth).
     /// May raise TypeError if key is unhashable.
     DictSetItem,
+    /// Add TOS to the running total of `sum()` over a generator expression.
+    /// Operand: u8 depth (number of iterators).
+    ///
+    /// Stack: [..., total, iter1, ..., iterN, value] -> [..., total', iter1, ..., iterN]
+    /// Pops value (TOS) and replaces the total at stack position (len - 2 - depth)
+    /// with `total + value`. May raise TypeError if they can't be added.
+    SumAdd,
 
     // === Subscript & Attribute ===
     /// a[b]: pop index, pop obj, push result.
@@ -232,7 +254,11 @@ pub enum Opcode {
     /// a[b] = c: pop value, pop index, pop obj.
     StoreSubscr,
     // NOTE: DeleteSubscr removed - `del` statement not supported by parser
-    /// Pop obj, push obj.attr. Operand: u16 name_id.
+    /// Pop obj, push obj.attr. Operands: u16 name_id, u16 cache_slot.
+    ///
+    /// `cache_slot` indexes the VM's inline cache table for this code object, which
+    /// remembers attributes loaded from modules and dataclasses; see
+    /// `CodeBuilder::emit_load_attr`.
     LoadAttr,
     /// Pop module, push module.attr for `from ... import`. Operand: u16 name_id.
     ///
@@ -267,11 +293,12 @@ pub enum Opcode {
     /// After the two count bytes, there are kw_count little-endian u16 values,
     /// each being a StringId index for the corresponding keyword argument name.
     CallFunctionKw,
-    /// Call attribute on object. Operands: u16 name_id, u8 arg_count.
+    /// Call attribute on object. Operands: u16 name_id, u8 arg_count, u16 cache_slot.
     ///
     /// This is used for both method calls (`obj.method(args)`) and module
     /// attribute calls (`module.func(args)`). The attribute is looked up
-    /// on the object and called with the given arguments.
+    /// on the object and called with the given arguments. Module function
+    /// lookups are remembered in the inline cache slot.
     CallAttr,
     /// Call attribute with keyword args. Operands: u16 name_id, u8 pos_count, u8 kw_count, then kw_count u16 name indices.
     ///
@@ -315,12 +342,23 @@ pub enum Opcode {
     JumpIfTrueOrPop,
     /// Jump if TOS falsy (keep), else pop. Operand: i16 offset.
     JumpIfFalseOrPop,
+    /// Pop two values, compare them, and jump if the result is false.
+    /// Operands: u8 comparison opcode (`CompareEq` .. `CompareNotIn`), i16 offset.
+    ///
+    /// Emitted by the peephole optimizer in place of a comparison followed by
+    /// `JumpIfFalse`, so the intermediate bool is never pushed.
+    CompareJumpIfFalse,
 
     // === Iteration ===
     /// Convert TOS to iterator.
     GetIter,
     /// Advance iterator or jump to end. Operand: i16 offset.
     ForIter,
+    /// Advance iterator and store the value to a local, or jump to end.
+    /// Operands: u8 slot, i16 offset.
+    ///
+    /// Emitted by the peephole optimizer in place of `ForIter` followed by `StoreLocal`.
+    ForIterStoreLocal,
 
     // === Function Definition ===
     /// Create function object. Operand: u16 func_id.
@@ -332,7 +370,8 @@ pub enum Opcode {
     // Note: No SetupTry/PopExceptHandler - we use static exception_table
     /// Raise TOS as exception.
     Raise,
-    // NOTE: RaiseFrom removed - `raise ... from ...` not supported by parser
+    /// Raise TOS1 as exception with TOS as its cause (`raise exc from cause`).
+    RaiseFrom,
     /// Re-raise current exception (bare `raise`).
     Reraise,
     /// Clear current_exception when exiting except block.
@@ -406,18 +445,19 @@ impl Opcode {
     pub const fn stack_effect(self) -> Option<i16> {
         use Opcode::{
             Await, BinaryAdd, BinaryAnd, BinaryDiv, BinaryFloorDiv, BinaryLShift, BinaryMatMul, BinaryMod, BinaryMul,
-            BinaryOr, BinaryPow, BinaryRShift, BinarySub, BinarySubscr, BinaryXor, BuildDict, BuildFString, BuildList,
-            BuildSet, BuildSlice, BuildTuple, CallAttr, CallAttrExtended, CallAttrKw, CallBuiltinFunction,
-            CallBuiltinType, CallFunction, CallFunctionExtended, CallFunctionKw, CheckExcMatch, ClearException,
-            CompareEq, CompareGe, CompareGt, CompareIn, CompareIs, CompareIsNot, CompareLe, CompareLt, CompareModEq,
-            CompareNe, CompareNotIn, DeleteLocal, DictMerge, DictSetItem, Dup, ForIter, FormatValue, GetIter,
-            InplaceAdd, InplaceAnd, InplaceDiv, InplaceFloorDiv, InplaceLShift, InplaceMod, InplaceMul, InplaceOr,
-            InplacePow, InplaceRShift, InplaceSub, InplaceXor, Jump, JumpIfFalse, JumpIfFalseOrPop, JumpIfTrue,
-            JumpIfTrueOrPop, ListAppend, ListExtend, ListToTuple, LoadAttr, LoadAttrImport, LoadCell, LoadConst,
-            LoadFalse, LoadGlobal, LoadLocal, LoadLocal0, LoadLocal1, LoadLocal2, LoadLocal3, LoadLocalW, LoadModule,
-            LoadNone, LoadSmallInt, LoadTrue, MakeClosure, MakeFunction, Nop, Pop, Raise, RaiseImportError, Reraise,
+            BinaryOr, BinaryPow, BinaryRShift, BinarySub, BinarySubscr, BinaryXor, BuildDict, BuildDictSized,
+            BuildFString, BuildList, BuildListSized, BuildSet, BuildSetSized, BuildSlice, BuildTuple, CallAttr,
+            CallAttrExtended, CallAttrKw, CallBuiltinFunction, CallBuiltinType, CallFunction, CallFunctionExtended,
+            CallFunctionKw, CheckExcMatch, ClearException, CompareEq, CompareGe, CompareGt, CompareIn, CompareIs,
+            CompareIsNot, CompareJumpIfFalse, CompareLe, CompareLt, CompareModEq, CompareNe, CompareNotIn, DeleteLocal,
+            DeleteLocalW, DictMerge, DictSetItem, Dup, ForIter, ForIterStoreLocal, FormatValue, GetIter, InplaceAdd,
+            InplaceAnd, InplaceDiv, InplaceFloorDiv, InplaceLShift, InplaceMod, InplaceMul, InplaceOr, InplacePow,
+            InplaceRShift, InplaceSub, InplaceXor, Jump, JumpIfFalse, JumpIfFalseOrPop, JumpIfTrue, JumpIfTrueOrPop,
+            ListAppend, ListExtend, ListToTuple, LoadAttr, LoadAttrImport, LoadCell, LoadConst, LoadFalse, LoadGlobal,
+            LoadLocal, LoadLocal0, LoadLocal1, LoadLocal2, LoadLocal3, LoadLocalPair, LoadLocalW, LoadModule, LoadNone,
+            LoadSmallInt, LoadTrue, MakeClosure, MakeFunction, Nop, Pop, Raise, RaiseFrom, RaiseImportError, Reraise,
             ReturnValue, Rot2, Rot3, SetAdd, StoreAttr, StoreCell, StoreGlobal, StoreLocal, StoreLocalW, StoreSubscr,
-            UnaryInvert, UnaryNeg, UnaryNot, UnaryPos, UnpackEx, UnpackSequence,
+            SumAdd, UnaryInvert, UnaryNeg, UnaryNot, UnaryPos, UnpackEx, UnpackSequence,
         };
         Some(match self {
             // Stack operations
@@ -431,8 +471,9 @@ impl Opcode {
             // Variables - loads push, stores pop
             LoadLocal0 | LoadLocal1 | LoadLocal2 | LoadLocal3 => 1,
             LoadLocal | LoadLocalW | LoadGlobal | LoadCell => 1,
+            LoadLocalPair => 2,
             StoreLocal | StoreLocalW | StoreGlobal | StoreCell => -1,
-            DeleteLocal => 0, // doesn't affect stack
+            DeleteLocal | DeleteLocalW => 0, // doesn't affect stack
 
             // Binary operations: pop 2, push 1 = -1
             BinaryAdd | BinarySub | BinaryMul | BinaryDiv | BinaryFloorDiv | BinaryMod | BinaryPow | BinaryAnd
@@ -451,6 +492,8 @@ impl Opcode {
 
             // Collection building - depends on operand, return None
             BuildList | BuildTuple | BuildDict | BuildSet | BuildFString => return None,
+            // Sized empty collections: push 1 (operand is capacity, not a count)
+            BuildListSized | BuildDictSized | BuildSetSized => 1,
             // FormatValue: pops 1 value (+ optional fmt_spec), pushes 1. Variable.
             FormatValue => return None,
             // BuildSlice: pop 3, push 1 = -2
@@ -463,7 +506,7 @@ impl Opcode {
             DictMerge => -1,
 
             // Comprehension building - pops value, no push (stores in collection below)
-            ListAppend | SetAdd => -1,
+            ListAppend | SetAdd | SumAdd => -1,
             DictSetItem => -2, // pops key and value
 
             // Subscript & Attribute
@@ -479,11 +522,13 @@ impl Opcode {
             // Control flow - no stack effect (jumps don't push/pop)
             Jump => 0,
             JumpIfTrue | JumpIfFalse => -1,                    // always pop condition
+            CompareJumpIfFalse => -2,                          // pop both operands
             JumpIfTrueOrPop | JumpIfFalseOrPop => return None, // variable (0 or -1)
 
             // Iteration
-            GetIter => 0,           // pop iterable, push iterator
-            ForIter => return None, // pushes value or jumps (variable)
+            GetIter => 0,                     // pop iterable, push iterator
+            ForIter => return None,           // pushes value or jumps (variable)
+            ForIterStoreLocal => return None, // stores value or pops iterator and jumps (variable)
 
             // Async/await
             Await => 0, // pop awaitable, push result
@@ -493,6 +538,7 @@ impl Opcode {
 
             // Exception handling
             Raise => -1,         // pop exception
+            RaiseFrom => -2,     // pop cause and exception
             Reraise => 0,        // no stack change (reads from exception_stack)
             ClearException => 0, // clears exception_stack, no operand stack change
             CheckExcMatch => 0,  // pop exc_type, push bool (net 0, but exc stays)
@@ -513,6 +559,255 @@ impl Opcode {
     }
 }
 
+/// How an opcode's operands are laid out in the bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Operands {
+    None,
+    U8,
+    U16,
+    U8U8,
+    U16U8,
+    U16U8U8,
+    U16U16,
+    U16U8U16,
+    /// An i16 offset relative to the end of the instruction.
+    Jump,
+    /// A u8 operand followed by a jump offset.
+    U8Jump,
+    /// u8 pos_count, u8 kw_count, then kw_count u16 names.
+    CallFunctionKw,
+    /// u16 name, u8 pos_count, u8 kw_count, then kw_count u16 names.
+    CallAttrKw,
+}
+
+impl Operands {
+    /// Returns the operand layout of `op`.
+    pub(super) fn of(op: Opcode) -> Self {
+        match op {
+            Opcode::Pop
+            | Opcode::Dup
+            | Opcode::Rot2
+            | Opcode::Rot3
+            | Opcode::LoadNone
+            | Opcode::LoadTrue
+            | Opcode::LoadFalse
+            | Opcode::LoadLocal0
+            | Opcode::LoadLocal1
+            | Opcode::LoadLocal2
+            | Opcode::LoadLocal3
+            | Opcode::BinaryAdd
+            | Opcode::BinarySub
+            | Opcode::BinaryMul
+            | Opcode::BinaryDiv
+            | Opcode::BinaryFloorDiv
+            | Opcode::BinaryMod
+            | Opcode::BinaryPow
+            | Opcode::BinaryAnd
+            | Opcode::BinaryOr
+            | Opcode::BinaryXor
+            | Opcode::BinaryLShift
+            | Opcode::BinaryRShift
+            | Opcode::BinaryMatMul
+            | Opcode::CompareEq
+            | Opcode::CompareNe
+            | Opcode::CompareLt
+            | Opcode::CompareLe
+            | Opcode::CompareGt
+            | Opcode::CompareGe
+            | Opcode::CompareIs
+            | Opcode::CompareIsNot
+            | Opcode::CompareIn
+            | Opcode::CompareNotIn
+            | Opcode::UnaryNot
+            | Opcode::UnaryNeg
+            | Opcode::UnaryPos
+            | Opcode::UnaryInvert
+            | Opcode::InplaceAdd
+            | Opcode::InplaceSub
+            | Opcode::InplaceMul
+            | Opcode::InplaceDiv
+            | Opcode::InplaceFloorDiv
+            | Opcode::InplaceMod
+            | Opcode::InplacePow
+            | Opcode::InplaceAnd
+            | Opcode::InplaceOr
+            | Opcode::InplaceXor
+            | Opcode::InplaceLShift
+            | Opcode::InplaceRShift
+            | Opcode::BuildSlice
+            | Opcode::ListExtend
+            | Opcode::ListToTuple
+            | Opcode::BinarySubscr
+            | Opcode::StoreSubscr
+            | Opcode::GetIter
+            | Opcode::Raise
+            | Opcode::RaiseFrom
+            | Opcode::Reraise
+            | Opcode::ClearException
+            | Opcode::CheckExcMatch
+            | Opcode::ReturnValue
+            | Opcode::Await
+            | Opcode::Nop => Self::None,
+            Opcode::LoadSmallInt
+            | Opcode::LoadLocal
+            | Opcode::LoadLocalPair
+            | Opcode::StoreLocal
+            | Opcode::DeleteLocal
+            | Opcode::FormatValue
+            | Opcode::ListAppend
+            | Opcode::SetAdd
+            | Opcode::DictSetItem
+            | Opcode::SumAdd
+            | Opcode::CallFunction
+            | Opcode::CallFunctionExtended
+            | Opcode::UnpackSequence
+            | Opcode::LoadModule => Self::U8,
+            Opcode::LoadConst
+            | Opcode::LoadLocalW
+            | Opcode::StoreLocalW
+            | Opcode::DeleteLocalW
+            | Opcode::LoadGlobal
+            | Opcode::StoreGlobal
+            | Opcode::LoadCell
+            | Opcode::StoreCell
+            | Opcode::CompareModEq
+            | Opcode::BuildList
+            | Opcode::BuildTuple
+            | Opcode::BuildDict
+            | Opcode::BuildSet
+            | Opcode::BuildListSized
+            | Opcode::BuildDictSized
+            | Opcode::BuildSetSized
+            | Opcode::BuildFString
+            | Opcode::DictMerge
+            | Opcode::LoadAttrImport
+            | Opcode::StoreAttr
+            | Opcode::RaiseImportError => Self::U16,
+            Opcode::CallBuiltinFunction | Opcode::CallBuiltinType | Opcode::UnpackEx => Self::U8U8,
+            Opcode::LoadAttr => Self::U16U16,
+            Opcode::CallAttrExtended | Opcode::MakeFunction => Self::U16U8,
+            Opcode::CallAttr => Self::U16U8U16,
+            Opcode::MakeClosure => Self::U16U8U8,
+            Opcode::Jump
+            | Opcode::JumpIfTrue
+            | Opcode::JumpIfFalse
+            | Opcode::JumpIfTrueOrPop
+            | Opcode::JumpIfFalseOrPop
+            | Opcode::ForIter => Self::Jump,
+            Opcode::CompareJumpIfFalse | Opcode::ForIterStoreLocal => Self::U8Jump,
+            Opcode::CallFunctionKw => Self::CallFunctionKw,
+            Opcode::CallAttrKw => Self::CallAttrKw,
+        }
+    }
+
+    /// Returns the number of operand bytes, given the bytes following the opcode.
+    pub(super) fn len(self, operands: &[u8]) -> usize {
+        match self {
+            Self::None => 0,
+            Self::U8 => 1,
+            Self::U16 | Self::U8U8 | Self::Jump => 2,
+            Self::U16U8 | Self::U8Jump => 3,
+            Self::U16U8U8 | Self::U16U16 => 4,
+            Self::U16U8U16 => 5,
+            Self::CallFunctionKw => 2 + 2 * usize::from(operands[1]),
+            Self::CallAttrKw => 4 + 2 * usize::from(operands[3]),
+        }
+    }
+
+    /// Like `len()`, but returns `None` if `operands` is too short to hold the operands.
+    pub(super) fn checked_len(self, operands: &[u8]) -> Option<usize> {
+        // the keyword count that sizes these layouts is itself an operand
+        let count_len = match self {
+            Self::CallFunctionKw => 2,
+            Self::CallAttrKw => 4,
+            _ => 0,
+        };
+        if operands.len() < count_len {
+            return None;
+        }
+        let len = self.len(operands);
+        (len <= operands.len()).then_some(len)
+    }
+}
+
+/// An instruction decoded from raw bytecode.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RawInstr<'a> {
+    /// Offset of the opcode byte.
+    pub offset: u32,
+    pub op: Opcode,
+    /// Operand bytes, not including the jump offset of jump instructions.
+    pub operands: &'a [u8],
+    /// Absolute jump target, as a bytecode offset.
+    pub target: Option<u32>,
+}
+
+/// Decodes raw bytecode into instructions.
+///
+/// # Panics
+/// Panics on malformed bytecode; bytecode only ever comes from the compiler, or was
+/// checked with `try_decode()` when it was deserialized.
+pub(super) fn decode(bytecode: &[u8]) -> Vec<RawInstr<'_>> {
+    try_decode(bytecode).unwrap_or_else(|err| panic!("invalid bytecode: {err}"))
+}
+
+/// Decodes raw bytecode into instructions, describing the first malformed one instead of
+/// panicking: an invalid opcode, operands cut off by the end of the bytecode, or a jump
+/// outside the range of bytecode offsets.
+pub(super) fn try_decode(bytecode: &[u8]) -> Result<Vec<RawInstr<'_>>, String> {
+    let mut instrs = Vec::new();
+    let mut pos = 0;
+    while pos < bytecode.len() {
+        let op = Opcode::try_from(bytecode[pos]).map_err(|err| format!("{err} at offset {pos}"))?;
+        let layout = Operands::of(op);
+        let operand_start = pos + 1;
+        let Some(len) = layout.checked_len(&bytecode[operand_start..]) else {
+            return Err(format!("{op:?} at offset {pos} is cut off by the end of the bytecode"));
+        };
+        let end = operand_start + len;
+        let mut operands = &bytecode[operand_start..end];
+        let mut target = None;
+        if matches!(layout, Operands::Jump | Operands::U8Jump) {
+            let (rest, offset) = operands.split_at(operands.len() - 2);
+            let offset = i16::from_le_bytes([offset[0], offset[1]]);
+            let absolute = i64::try_from(end)
+                .ok()
+                .and_then(|end| u32::try_from(end + i64::from(offset)).ok());
+            let Some(absolute) = absolute else {
+                return Err(format!("{op:?} at offset {pos} jumps out of range"));
+            };
+            target = Some(absolute);
+            operands = rest;
+        }
+        instrs.push(RawInstr {
+            offset: u32::try_from(pos).map_err(|_| "bytecode offset exceeds u32".to_owned())?,
+            op,
+            operands,
+            target,
+        });
+        pos = end;
+    }
+    Ok(instrs)
+}
+
+/// Returns whether an instruction of `bytecode` starts at `offset`.
+///
+/// Unlike `decode()` this never panics, so it can check the instruction pointers of a
+/// deserialized snapshot: an invalid opcode or cut-off operands before `offset` give `false`.
+pub(super) fn is_instruction_start(bytecode: &[u8], offset: usize) -> bool {
+    let mut pos = 0;
+    while pos < offset {
+        let Some(Ok(op)) = bytecode.get(pos).map(|&byte| Opcode::try_from(byte)) else {
+            return false;
+        };
+        let Some(len) = Operands::of(op).checked_len(&bytecode[pos + 1..]) else {
+            return false;
+        };
+        pos += 1 + len;
+    }
+    pos == offset
+}
+
 /// Error returned when attempting to convert an invalid byte to an Opcode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct InvalidOpcodeError(pub u8);
@@ -538,6 +833,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn instruction_starts_skip_operands() {
+        let bytecode = [Opcode::LoadLocal as u8, 7, Opcode::ReturnValue as u8];
+        assert!(is_instruction_start(&bytecode, 0));
+        assert!(!is_instruction_start(&bytecode, 1));
+        assert!(is_instruction_start(&bytecode, 2));
+        assert!(!is_instruction_start(&[255, 0], 1));
+    }
+
     #[test]
     fn test_invalid_opcode() {
         // Byte just after the last valid opcode should fail
@@ -0,0 +1,68 @@
+//! Checking values passed to and returned from external functions against the
+//! annotations declared for them in `CompileOptions::check_external_calls` stubs.
+
+use super::{FrameExit, VM};
+use crate::{
+    args::{ArgValues, KwargsValues},
+    exception_private::{ExcType, RunError, RunResult, SimpleException},
+    heap::DropWithHeap,
+    intern::ExtFunctionId,
+    object::MontyObject,
+    resource::ResourceTracker,
+    types::PyTrait,
+    value::Value,
+};
+
+impl<T: ResourceTracker> VM<'_, '_, T> {
+    /// Raises `TypeError` if an argument doesn't match the annotation of its parameter.
+    pub(super) fn check_external_args(&self, ext_function_id: ExtFunctionId, args: &ArgValues) -> RunResult<()> {
+        let Some(signature) = self.interns.get_external_signature(ext_function_id) else {
+            return Ok(());
+        };
+        let name = self.interns.get_external_function_name(ext_function_id);
+        let (positional, kwargs): (Vec<&Value>, _) = match args {
+            ArgValues::Empty => (vec![], None),
+            ArgValues::One(arg) => (vec![arg], None),
+            ArgValues::Two(first, second) => (vec![first, second], None),
+            ArgValues::Kwargs(kwargs) => (vec![], Some(kwargs)),
+            ArgValues::ArgsKargs { args, kwargs } => (args.iter().collect(), Some(kwargs)),
+        };
+        for (index, arg) in positional.into_iter().enumerate() {
+            signature.check_positional(&name, index, arg.py_type(self.heap))?;
+        }
+        match kwargs {
+            Some(KwargsValues::Inline(kwargs)) => {
+                for (key, value) in kwargs {
+                    signature.check_keyword(&name, self.interns.get_str(*key), value.py_type(self.heap))?;
+                }
+            }
+            Some(KwargsValues::Dict(kwargs)) => {
+                for (key, value) in kwargs {
+                    if let Some(key) = key.as_either_str(self.heap) {
+                        signature.check_keyword(&name, key.as_str(self.interns), value.py_type(self.heap))?;
+                    }
+                }
+            }
+            Some(KwargsValues::Empty) | None => {}
+        }
+        Ok(())
+    }
+
+    /// Resumes execution with the value external function `ext_function_id` returned,
+    /// raising `TypeError` at the call instead if it doesn't match the declared return type.
+    pub fn resume_external(&mut self, ext_function_id: ExtFunctionId, obj: MontyObject) -> Result<FrameExit, RunError> {
+        let Some(signature) = self.interns.get_external_signature(ext_function_id) else {
+            return self.resume(obj);
+        };
+        let value = obj
+            .to_value(self.heap, self.interns)
+            .map_err(|e| SimpleException::new(ExcType::RuntimeError, Some(format!("invalid return type: {e}"))))?;
+        let name = self.interns.get_external_function_name(ext_function_id);
+        if let Err(err) = signature.check_return(&name, value.py_type(self.heap)) {
+            value.drop_with_heap(self.heap);
+            return self.resume_with_exception(err);
+        }
+        self.push(value);
+        self.run()
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
     defer_drop,
     exception_private::{ExcType, RunError, SimpleException},
     heap::{HeapData, HeapGuard, HeapId},
-    intern::FunctionId,
+    intern::{ExtFunctionId, FunctionId},
     resource::ResourceTracker,
     types::{List, PyTrait},
     value::Value,
@@ -308,7 +308,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let namespace_idx = self.namespaces.register_prebuilt(namespace_values, self.heap)?;
 
         // Push frame to execute the coroutine
-        self.frames.push(CallFrame::new_function(
+        self.push_function_frame(CallFrame::new_function(
             &func.code,
             self.stack.len(),
             namespace_idx,
@@ -734,7 +734,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         // don't have a parent frame - the coroutine is the root)
         let func = self.interns.get_function(func_id);
         let namespace_idx = self.namespaces.register_prebuilt(namespace_values, self.heap)?;
-        self.frames.push(CallFrame::new_function(
+        self.push_function_frame(CallFrame::new_function(
             &func.code,
             self.stack.len(),
             namespace_idx,
@@ -935,12 +935,32 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         scheduler.add_pending_call(
             call_id,
             PendingCallData {
+                batched: None,
                 args: ArgValues::Empty,
                 creator_task: current_task,
             },
         );
     }
 
+    /// Queues a call to a batched external function instead of yielding it to the host.
+    ///
+    /// The call is handed to the host together with every other queued call the next time
+    /// all tasks are blocked. Returns the future the script gets in place of the result.
+    pub(super) fn batch_external_call(&mut self, ext_function_id: ExtFunctionId, args: ArgValues) -> Value {
+        let call_id = self.allocate_call_id();
+        let scheduler = self.get_or_create_scheduler();
+        let current_task = scheduler.current_task_id().unwrap_or_default();
+        scheduler.add_pending_call(
+            call_id,
+            PendingCallData {
+                batched: Some(ext_function_id),
+                args,
+                creator_task: current_task,
+            },
+        );
+        Value::ExternalFuture(call_id)
+    }
+
     /// Prepares the current task to continue after futures are resolved.
     ///
     /// When the current task (main or spawned) was blocked on an external future and
@@ -5,24 +5,116 @@ use crate::{
     bytecode::vm::CallResult,
     defer_drop,
     exception_private::{ExcType, RunError},
+    heap::HeapData,
     intern::StringId,
     resource::ResourceTracker,
+    value::Value,
 };
 
 impl<T: ResourceTracker> VM<'_, '_, T> {
     /// Loads an attribute from an object and pushes it onto the stack.
     ///
     /// Returns an AttributeError if the attribute doesn't exist.
-    pub(super) fn load_attr(&mut self, name_id: StringId) -> Result<CallResult, RunError> {
+    pub(super) fn load_attr(&mut self, name_id: StringId, cache_slot: u16) -> Result<CallResult, RunError> {
         let this = self;
 
         let obj = this.pop();
         defer_drop!(obj, this);
 
+        if let Some(value) = this.cached_module_attr(obj, cache_slot) {
+            return Ok(CallResult::Push(value));
+        }
+        if let Some(value) = this.cached_dataclass_attr(obj, name_id, cache_slot) {
+            return Ok(CallResult::Push(value));
+        }
         let result = obj.py_getattr(name_id, this.heap, this.interns)?;
+        this.fill_module_attr_cache(obj, name_id, cache_slot);
+        this.fill_dataclass_attr_cache(obj, name_id, cache_slot);
         Ok(result.into())
     }
 
+    /// Returns the attribute cached at `cache_slot` of the current code, if it was
+    /// filled for the module `obj` and no module namespace has changed since.
+    pub(super) fn cached_module_attr(&self, obj: &Value, cache_slot: u16) -> Option<Value> {
+        let Value::Ref(id) = *obj else {
+            return None;
+        };
+        self.inline_caches.lookup_module(
+            self.current_frame().function_id,
+            cache_slot,
+            id,
+            self.heap.namespace_generation(),
+        )
+    }
+
+    /// Remembers `obj.name` at `cache_slot` of the current code if `obj` is a module and
+    /// the attribute is an immediate value.
+    ///
+    /// Only modules cache the value itself: their namespaces are revalidated by the heap's
+    /// namespace generation, which nothing equivalent tracks for other objects.
+    pub(super) fn fill_module_attr_cache(&mut self, obj: &Value, name_id: StringId, cache_slot: u16) {
+        let Value::Ref(id) = *obj else {
+            return;
+        };
+        let HeapData::Module(module) = self.heap.get(id) else {
+            return;
+        };
+        if let Some(value) = module.cacheable_attr(name_id, self.heap, self.interns) {
+            let frame = self.current_frame();
+            let (function_id, code) = (frame.function_id, frame.code);
+            let generation = self.heap.namespace_generation();
+            self.inline_caches
+                .store_module(function_id, code, cache_slot, id, generation, value);
+        }
+    }
+
+    /// Returns `obj.name` if `obj` is a dataclass and `cache_slot` of the current code
+    /// remembers where that attribute sits for the dataclass's type.
+    ///
+    /// The remembered position is checked against the attribute name, so an instance
+    /// whose attributes were added, removed or built in a different order just misses.
+    fn cached_dataclass_attr(&mut self, obj: &Value, name_id: StringId, cache_slot: u16) -> Option<Value> {
+        let Value::Ref(id) = *obj else {
+            return None;
+        };
+        let HeapData::Dataclass(dataclass) = self.heap.get(id) else {
+            return None;
+        };
+        let index =
+            self.inline_caches
+                .lookup_dataclass(self.current_frame().function_id, cache_slot, dataclass.type_id())?;
+        let value = dataclass
+            .attrs()
+            .get_by_str_at(index, self.interns.get_str(name_id), self.heap, self.interns)?
+            .copy_for_extend();
+        if let Value::Ref(value_id) = value {
+            self.heap.inc_ref(value_id);
+        }
+        Some(value)
+    }
+
+    /// Remembers where `obj.name` sits in the attribute dict at `cache_slot` of the current
+    /// code if `obj` is a dataclass, keyed on the dataclass's type.
+    fn fill_dataclass_attr_cache(&mut self, obj: &Value, name_id: StringId, cache_slot: u16) {
+        let Value::Ref(id) = *obj else {
+            return;
+        };
+        let HeapData::Dataclass(dataclass) = self.heap.get(id) else {
+            return;
+        };
+        let Some(index) = dataclass
+            .attrs()
+            .index_of_str(self.interns.get_str(name_id), self.heap, self.interns)
+        else {
+            return;
+        };
+        let type_id = dataclass.type_id();
+        let frame = self.current_frame();
+        let (function_id, code) = (frame.function_id, frame.code);
+        self.inline_caches
+            .store_dataclass(function_id, code, cache_slot, type_id, index);
+    }
+
     /// Loads an attribute from a module for `from ... import` and pushes it onto the stack.
     ///
     /// Returns an ImportError (not AttributeError) if the attribute doesn't exist,
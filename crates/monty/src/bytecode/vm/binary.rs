@@ -1,13 +1,15 @@
 //! Binary and in-place operation helpers for the VM.
 
-use super::VM;
+use super::{CachedFrame, VM};
 use crate::{
+    bytecode::op::Opcode,
     defer_drop,
     exception_private::{ExcType, RunError},
-    heap::HeapGuard,
+    heap::{HeapData, HeapGuard, HeapId},
+    namespace::{GLOBAL_NS_IDX, NamespaceId},
     resource::ResourceTracker,
     types::PyTrait,
-    value::BitwiseOp,
+    value::{BitwiseOp, Value},
 };
 
 impl<T: ResourceTracker> VM<'_, '_, T> {
@@ -207,13 +209,14 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     /// In-place addition (uses py_iadd for mutable containers, falls back to py_add).
     ///
     /// For mutable types like lists, `py_iadd` mutates in place and returns true.
-    /// For immutable types, we fall back to regular addition.
+    /// For immutable types, we fall back to regular addition, except that a str or bytes
+    /// nothing else can observe is extended in place (see `may_add_in_place`).
     ///
     /// Uses lazy type capture: only calls `py_type()` in error paths.
     ///
     /// Note: Cannot use `defer_drop!` for `lhs` here because on successful in-place
     /// operation, we need to push `lhs` back onto the stack rather than drop it.
-    pub(super) fn inplace_add(&mut self) -> Result<(), RunError> {
+    pub(super) fn inplace_add(&mut self, cached_frame: &CachedFrame<'_>) -> Result<(), RunError> {
         let this = self;
 
         let rhs = this.pop();
@@ -223,7 +226,9 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let (lhs, this) = lhs_guard.as_parts_mut();
 
         // Try in-place operation first (for mutable types like lists)
-        if lhs.py_iadd(rhs.clone_with_heap(this.heap), this.heap, lhs.ref_id(), this.interns)? {
+        if this.may_add_in_place(lhs, cached_frame)
+            && lhs.py_iadd(rhs.clone_with_heap(this.heap), this.heap, lhs.ref_id(), this.interns)?
+        {
             // In-place operation succeeded - push lhs back
             let (lhs, this) = lhs_guard.into_parts();
             this.push(lhs);
@@ -241,6 +246,55 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         Err(ExcType::binary_type_error("+=", lhs_type, rhs_type))
     }
 
+    /// Returns whether `+=` may change `lhs` in place rather than creating a new value.
+    ///
+    /// `str` and `bytes` are immutable, so they're only extended in place when no one else
+    /// can see the change: the next instruction stores the result back into the variable
+    /// `lhs` was loaded from, and that variable and the operand stack hold the only references.
+    /// Appending to the existing buffer then makes `s += x` in a loop amortized O(1) instead
+    /// of copying the whole string every iteration.
+    fn may_add_in_place(&mut self, lhs: &Value, cached_frame: &CachedFrame<'_>) -> bool {
+        let Value::Ref(id) = lhs else {
+            return true;
+        };
+        if !matches!(self.heap.get(*id), HeapData::Str(_) | HeapData::Bytes(_)) {
+            return true;
+        }
+        if self.heap.get_refcount(*id) != 2 || !self.stores_back_to(cached_frame, *id) {
+            return false;
+        }
+        self.heap.clear_hash(*id);
+        true
+    }
+
+    /// Returns whether the next instruction stores into a writable variable currently holding `id`.
+    ///
+    /// A `Dup` before the store is looked through: the peephole pass turns a store followed
+    /// by a load of the same variable into `Dup; Store`, and the copy it pushes is only made
+    /// once the in-place addition is done.
+    fn stores_back_to(&self, cached_frame: &CachedFrame<'_>, id: HeapId) -> bool {
+        let bytecode = cached_frame.code.bytecode();
+        let mut ip = cached_frame.ip;
+        if matches!(
+            bytecode.get(ip).map(|&byte| Opcode::try_from(byte)),
+            Some(Ok(Opcode::Dup))
+        ) {
+            ip += 1;
+        }
+        let operand_u16 = || u16::from_le_bytes([bytecode[ip + 1], bytecode[ip + 2]]);
+        let (namespace_idx, slot) = match bytecode.get(ip).map(|&byte| Opcode::try_from(byte)) {
+            Some(Ok(Opcode::StoreLocal)) => (cached_frame.namespace_idx, u16::from(bytecode[ip + 1])),
+            Some(Ok(Opcode::StoreLocalW)) => (cached_frame.namespace_idx, operand_u16()),
+            Some(Ok(Opcode::StoreGlobal)) => (GLOBAL_NS_IDX, operand_u16()),
+            _ => return false,
+        };
+        let slot = NamespaceId::new(usize::from(slot));
+        if namespace_idx == GLOBAL_NS_IDX && self.namespaces.frozen_global_name(slot).is_some() {
+            return false;
+        }
+        matches!(self.namespaces.get(namespace_idx).get(slot), Value::Ref(held) if *held == id)
+    }
+
     /// Binary matrix multiplication (`@` operator).
     ///
     /// Currently not implemented - returns a `NotImplementedError`.
@@ -4,11 +4,16 @@
 //! functions for executing function calls. The main entry points are the `exec_*`
 //! methods which are called from the VM's main dispatch loop.
 
-use super::{CallFrame, VM};
+use std::time::Duration;
+
+use num_bigint::BigInt;
+
+use super::{CallFrame, FrameExit, VM};
 use crate::{
     args::{ArgValues, KwargsValues},
     asyncio::Coroutine,
-    builtins::{Builtins, BuiltinsFunctions},
+    builtins::{Builtins, BuiltinsFunctions, builtin_emit, builtin_resources},
+    bytecode::code::Code,
     defer_drop,
     exception_private::{ExcType, RunError},
     heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
@@ -19,6 +24,7 @@ use crate::{
         AttrCallResult, Dict, PyTrait, Type,
         bytes::{bytes_fromhex, call_bytes_method},
         dict::dict_fromkeys,
+        int::{call_int_method, int_from_bytes},
         str::call_str_method,
     },
     value::{EitherStr, Value},
@@ -52,6 +58,14 @@ pub(super) enum CallResult {
     /// Used by `asyncio.run()` to execute a coroutine without an explicit `await`.
     /// The VM will push the value onto the stack and execute `exec_get_awaitable`.
     AwaitValue(Value),
+    /// `emit(value)` was called - VM should yield `FrameExit::Emit` to host.
+    ///
+    /// The host consumes the value and resumes the VM with `emit()`'s return value.
+    Emit(Value),
+    /// `time.sleep()` was called - VM should yield `FrameExit::Sleep` to host.
+    ///
+    /// The host resumes the VM once it is done waiting.
+    Sleep(Duration),
 }
 
 impl From<AttrCallResult> for CallResult {
@@ -62,7 +76,35 @@ impl From<AttrCallResult> for CallResult {
             AttrCallResult::ExternalCall(ext_id, args) => Self::External(ext_id, args),
             AttrCallResult::MethodCall(name, args) => Self::MethodCall(name, args),
             AttrCallResult::AwaitValue(v) => Self::AwaitValue(v),
+            AttrCallResult::Sleep(duration) => Self::Sleep(duration),
+        }
+    }
+}
+
+impl<'a, T: ResourceTracker> VM<'a, '_, T> {
+    /// Calls a function from the host as the outermost frame and runs it to completion.
+    ///
+    /// Used to invoke a script's functions after its module code has run; the function
+    /// returning ends execution like a module-level return would. Builtins return their
+    /// value directly, while callables that need the host, such as external functions,
+    /// are rejected.
+    pub fn run_function(
+        &mut self,
+        module_code: &'a Code,
+        callable: Value,
+        args: Vec<Value>,
+    ) -> Result<FrameExit, RunError> {
+        self.module_code = Some(module_code);
+        match self.call_function(callable, Self::build_args_positional_only(args))? {
+            CallResult::Push(value) => return Ok(FrameExit::Return(value)),
+            CallResult::FramePushed => return self.run(),
+            CallResult::External(_, args) | CallResult::OsCall(_, args) | CallResult::MethodCall(_, args) => {
+                args.drop_with_heap(self.heap);
+            }
+            CallResult::AwaitValue(value) | CallResult::Emit(value) => value.drop_with_heap(self.heap),
+            CallResult::Sleep(_) => {}
         }
+        Err(ExcType::not_implemented("only functions defined by the script can be called from the host").into())
     }
 }
 
@@ -88,16 +130,36 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     ///
     /// Calls a builtin function directly without stack manipulation for the callable.
     /// This is an optimization that avoids constant pool lookup and stack manipulation.
-    pub(super) fn exec_call_builtin_function(&mut self, builtin_id: u8, arg_count: usize) -> Result<Value, RunError> {
+    pub(super) fn exec_call_builtin_function(
+        &mut self,
+        builtin_id: u8,
+        arg_count: usize,
+    ) -> Result<CallResult, RunError> {
         // Convert u8 to BuiltinsFunctions via FromRepr
         if let Some(builtin) = BuiltinsFunctions::from_repr(builtin_id) {
             let args = self.pop_n_args(arg_count);
-            builtin.call(self.heap, args, self.interns, self.print_writer)
+            self.call_builtin(Builtins::Function(builtin), args)
         } else {
             Err(RunError::internal("CallBuiltinFunction: invalid builtin_id"))
         }
     }
 
+    /// Calls a builtin, supplying VM state that `Builtins::call` has no access to.
+    ///
+    /// `resources()` reports the remaining fuel, which lives on the VM rather than the heap,
+    /// and `emit()` suspends the VM, which only the run loop can do.
+    fn call_builtin(&mut self, builtin: Builtins, args: ArgValues) -> Result<CallResult, RunError> {
+        match builtin {
+            Builtins::Function(BuiltinsFunctions::Resources) => {
+                builtin_resources(self.heap, args, self.interns, self.fuel).map(CallResult::Push)
+            }
+            Builtins::Function(BuiltinsFunctions::Emit) => builtin_emit(self.heap, args).map(CallResult::Emit),
+            _ => builtin
+                .call(self.heap, args, self.interns, self.print_writer)
+                .map(CallResult::Push),
+        }
+    }
+
     /// Executes `CallBuiltinType` opcode.
     ///
     /// Calls a builtin type constructor directly without stack manipulation for the callable.
@@ -154,9 +216,22 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     ///
     /// Pops the object and arguments from the stack, calls the attribute,
     /// and returns a `CallResult` which may indicate an OS or external call.
-    pub(super) fn exec_call_attr(&mut self, name_id: StringId, arg_count: usize) -> Result<CallResult, RunError> {
+    ///
+    /// Module function calls go through the inline cache at `cache_slot`, skipping
+    /// the module's attribute lookup once the slot is filled.
+    pub(super) fn exec_call_attr(
+        &mut self,
+        name_id: StringId,
+        arg_count: usize,
+        cache_slot: u16,
+    ) -> Result<CallResult, RunError> {
         let args = self.pop_n_args(arg_count);
         let obj = self.pop();
+        if let Some(Value::ModuleFunction(function)) = self.cached_module_attr(&obj, cache_slot) {
+            obj.drop_with_heap(self.heap);
+            return function.call(self.heap, args).map(Into::into);
+        }
+        self.fill_module_attr_cache(&obj, name_id, cache_slot);
         self.call_attr(obj, name_id, args)
     }
 
@@ -283,12 +358,18 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
             Value::InternString(string_id) => {
                 // Call string method on interned string literal using the unified dispatcher
                 let s = this.interns.get_str(string_id);
-                call_str_method(s, name_id, args, this.heap, this.interns).map(CallResult::Push)
+                call_str_method(s, &attr, args, this.heap, this.interns).map(CallResult::Push)
             }
             Value::InternBytes(bytes_id) => {
                 // Call bytes method on interned bytes literal using the unified dispatcher
                 let b = this.interns.get_bytes(bytes_id);
-                call_bytes_method(b, name_id, args, this.heap, this.interns).map(CallResult::Push)
+                call_bytes_method(b, &attr, args, this.heap, this.interns).map(CallResult::Push)
+            }
+            Value::Int(i) => {
+                call_int_method(&BigInt::from(i), &attr, args, this.heap, this.interns).map(CallResult::Push)
+            }
+            Value::Bool(b) => {
+                call_int_method(&BigInt::from(u8::from(b)), &attr, args, this.heap, this.interns).map(CallResult::Push)
             }
             Value::Builtin(Builtins::Type(t)) => {
                 // Handle classmethods on type objects like dict.fromkeys()
@@ -306,23 +387,31 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     /// Calls a callable value with the given arguments.
     ///
     /// Dispatches based on the callable type:
-    /// - `Value::Builtin`: calls builtin directly, returns `Push`
+    /// - `Value::Builtin`: calls builtin directly, returns `Push` (`Emit` for `emit()`)
     /// - `Value::ModuleFunction`: calls module function directly, returns `Push`
-    /// - `Value::ExtFunction`: returns `External` for caller to execute
+    /// - `Value::ExtFunction`: returns `External` for caller to execute (host iterables raise `TypeError`)
     /// - `Value::DefFunction`: pushes a new frame, returns `FramePushed`
     /// - `Value::Ref`: checks for closure/function on heap
     fn call_function(&mut self, callable: Value, args: ArgValues) -> Result<CallResult, RunError> {
         match callable {
-            Value::Builtin(builtin) => {
-                let result = builtin.call(self.heap, args, self.interns, self.print_writer)?;
-                Ok(CallResult::Push(result))
-            }
+            Value::Builtin(builtin) => self.call_builtin(builtin, args),
             Value::ModuleFunction(mf) => {
-                let result = mf.call(self.heap, args)?;
+                let result = mf.call(self.heap, self.interns, args)?;
                 Ok(result.into())
             }
+            Value::ExtFunction(ext_id) if self.interns.is_external_iterable(ext_id) => {
+                args.drop_with_heap(self.heap);
+                let name = self.interns.get_external_function_name(ext_id);
+                Err(ExcType::type_error(format!(
+                    "host iterable '{name}' is not callable, loop over it instead"
+                )))
+            }
             Value::ExtFunction(ext_id) => {
                 // External function - return to caller to execute
+                if let Err(err) = self.check_external_args(ext_id, &args) {
+                    args.drop_with_heap(self.heap);
+                    return Err(err);
+                }
                 Ok(CallResult::External(ext_id, args))
             }
             Value::DefFunction(func_id) => {
@@ -779,7 +868,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
 
         let code = &func.code;
         // 6. Push new frame
-        self.frames.push(CallFrame::new_function(
+        self.push_function_frame(CallFrame::new_function(
             code,
             self.stack.len(),
             namespace_idx,
@@ -794,7 +883,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
 
 /// Dispatches a classmethod call on a type object.
 ///
-/// Handles classmethods like `dict.fromkeys()` and `bytes.fromhex()` that are
+/// Handles classmethods like `dict.fromkeys()`, `bytes.fromhex()` and `int.from_bytes()` that are
 /// called on the type itself rather than on an instance.
 fn call_type_method(
     t: Type,
@@ -806,6 +895,7 @@ fn call_type_method(
     match (t, method_id) {
         (Type::Dict, m) if m == StaticStrings::Fromkeys => return dict_fromkeys(args, heap, interns),
         (Type::Bytes, m) if m == StaticStrings::Fromhex => return bytes_fromhex(args, heap, interns),
+        (Type::Int, m) if m == StaticStrings::FromBytes => return int_from_bytes(args, heap, interns),
         _ => {}
     }
     // Other types or unknown methods - report actual type name, not 'type'
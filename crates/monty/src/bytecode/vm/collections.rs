@@ -6,10 +6,13 @@ use super::VM;
 use crate::{
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunError, SimpleException},
-    heap::{HeapData, HeapGuard},
+    heap::{DropWithHeap, HeapData, HeapGuard},
     intern::StringId,
     resource::ResourceTracker,
-    types::{Dict, List, PyTrait, Set, Slice, Type, allocate_tuple, slice::value_to_option_i64, str::allocate_char},
+    types::{
+        Dict, List, MontyIter, PyTrait, Set, Slice, Type, allocate_tuple, slice::value_to_option_i64,
+        str::allocate_char,
+    },
     value::Value,
 };
 
@@ -37,7 +40,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     /// Builds a dict from the top 2n stack values (key/value pairs).
     pub(super) fn build_dict(&mut self, count: usize) -> Result<(), RunError> {
         let items = self.pop_n(count * 2);
-        let mut dict = Dict::new();
+        let mut dict = Dict::with_capacity(count);
         // Use into_iter to consume items by value, avoiding clone and proper ownership transfer
         let mut iter = items.into_iter();
         while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
@@ -51,7 +54,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     /// Builds a set from the top n stack values.
     pub(super) fn build_set(&mut self, count: usize) -> Result<(), RunError> {
         let items = self.pop_n(count);
-        let mut set = Set::new();
+        let mut set = Set::with_capacity(count);
         for item in items {
             set.add(item, self.heap, self.interns)?;
         }
@@ -60,6 +63,50 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         Ok(())
     }
 
+    /// Pushes an empty list with room for `capacity` items.
+    ///
+    /// Used by comprehensions with a compile-time size bound so the list is
+    /// allocated once rather than grown on every append.
+    pub(super) fn build_list_sized(&mut self, capacity: usize) -> Result<(), RunError> {
+        self.check_presize(capacity, size_of::<Value>())?;
+        let list = List::new(Vec::with_capacity(capacity));
+        let heap_id = self.heap.allocate(HeapData::List(list))?;
+        self.push(Value::Ref(heap_id));
+        Ok(())
+    }
+
+    /// Pushes an empty dict with room for `capacity` entries.
+    pub(super) fn build_dict_sized(&mut self, capacity: usize) -> Result<(), RunError> {
+        // as counted by `Dict::py_estimate_size`: a key and a value per entry
+        self.check_presize(capacity, 2 * size_of::<Value>())?;
+        let heap_id = self.heap.allocate(HeapData::Dict(Dict::with_capacity(capacity)))?;
+        self.push(Value::Ref(heap_id));
+        Ok(())
+    }
+
+    /// Pushes an empty set with room for `capacity` items.
+    pub(super) fn build_set_sized(&mut self, capacity: usize) -> Result<(), RunError> {
+        // an item and its cached hash
+        self.check_presize(capacity, size_of::<Value>() + size_of::<u64>())?;
+        let heap_id = self.heap.allocate(HeapData::Set(Set::with_capacity(capacity)))?;
+        self.push(Value::Ref(heap_id));
+        Ok(())
+    }
+
+    /// Checks that reserving `capacity` slots of `slot_size` bytes fits in the memory limit.
+    ///
+    /// The empty collection is charged for its length when allocated, which leaves out the
+    /// block reserved for its items, so the block is checked here. Every reservation is
+    /// checked rather than only those over `LARGE_RESULT_THRESHOLD`, and the capacity is
+    /// not trusted to be small: it comes from the bytecode, which may have been loaded
+    /// rather than compiled.
+    fn check_presize(&self, capacity: usize, slot_size: usize) -> Result<(), RunError> {
+        self.heap
+            .tracker()
+            .check_large_result(capacity.saturating_mul(slot_size))?;
+        Ok(())
+    }
+
     /// Builds a slice object from the top 3 stack values.
     ///
     /// Stack: [start, stop, step] -> [slice]
@@ -330,7 +377,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         // Append to the list using with_entry_mut to handle proper contains_refs tracking
         self.heap.with_entry_mut(list_id, |heap, data| {
             if let HeapData::List(list) = data {
-                list.append(heap, value);
+                list.append(heap, value)?;
                 Ok(())
             } else {
                 value.drop_with_heap(heap);
@@ -339,6 +386,33 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         })
     }
 
+    /// Adds TOS to the running total of `sum()` over a generator expression.
+    ///
+    /// Stack: [..., total, iter1, ..., iterN, value] -> [..., total', iter1, ..., iterN]
+    /// The `depth` parameter is the number of iterators between the total and the value.
+    /// Raises TypeError like `builtin_sum` if the total and value can't be added.
+    pub(super) fn sum_add(&mut self, depth: usize) -> Result<(), RunError> {
+        let this = self;
+
+        let value = this.pop();
+        defer_drop!(value, this);
+        let total_pos = this.stack.len() - 1 - depth;
+
+        match this.stack[total_pos].py_add(value, this.heap, this.interns) {
+            Ok(Some(new_total)) => {
+                let old = std::mem::replace(&mut this.stack[total_pos], new_total);
+                old.drop_with_heap(this.heap);
+                Ok(())
+            }
+            Ok(None) => {
+                let total_type = this.stack[total_pos].py_type(this.heap);
+                let value_type = value.py_type(this.heap);
+                Err(ExcType::binary_type_error("+", total_type, value_type))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Adds TOS to set for comprehension.
     ///
     /// Stack: [..., set, iter1, ..., iterN, value] -> [..., set, iter1, ..., iterN]
@@ -411,12 +485,15 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
 
     /// Unpacks a sequence into n values on the stack.
     ///
-    /// Supports lists, tuples, and strings. For strings, each character becomes
-    /// a separate single-character string.
+    /// Lists, tuples, and strings are unpacked directly; for strings, each character
+    /// becomes a separate single-character string. Any other iterable is iterated.
     pub(super) fn unpack_sequence(&mut self, count: usize) -> Result<(), RunError> {
         let this = self;
 
         let value = this.pop();
+        if !this.is_direct_unpack(&value) {
+            return this.unpack_iterable(value, count, None);
+        }
         defer_drop!(value, this);
 
         // Copy values without incrementing refcounts (avoids borrow conflict with heap.get).
@@ -427,7 +504,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                 let s = this.interns.get_str(*string_id);
                 let str_len = s.chars().count();
                 if str_len != count {
-                    return Err(unpack_size_error(count, str_len));
+                    return Err(unpack_size_error(count, str_len, false));
                 }
                 // Allocate each character as a new string
                 let mut items = Vec::with_capacity(str_len);
@@ -446,21 +523,21 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                     HeapData::List(list) => {
                         let list_len = list.len();
                         if list_len != count {
-                            return Err(unpack_size_error(count, list_len));
+                            return Err(unpack_size_error(count, list_len, true));
                         }
                         list.as_slice().iter().map(Value::copy_for_extend).collect()
                     }
                     HeapData::Tuple(tuple) => {
                         let tuple_len = tuple.as_slice().len();
                         if tuple_len != count {
-                            return Err(unpack_size_error(count, tuple_len));
+                            return Err(unpack_size_error(count, tuple_len, true));
                         }
                         tuple.as_slice().iter().map(Value::copy_for_extend).collect()
                     }
                     HeapData::Str(s) => {
                         let str_len = s.as_str().chars().count();
                         if str_len != count {
-                            return Err(unpack_size_error(count, str_len));
+                            return Err(unpack_size_error(count, str_len, false));
                         }
                         // Collect characters first to avoid borrow conflict with heap
                         let chars: Vec<char> = s.as_str().chars().collect();
@@ -475,17 +552,10 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                         }
                         return Ok(());
                     }
-                    other => {
-                        let type_name = other.py_type(this.heap);
-                        return Err(unpack_type_error(type_name));
-                    }
+                    _ => return Err(RunError::internal("UnpackSequence: expected list, tuple or str")),
                 }
             }
-            // Non-iterable types
-            _ => {
-                let type_name = value.py_type(this.heap);
-                return Err(unpack_type_error(type_name));
-            }
+            _ => return Err(RunError::internal("UnpackSequence: expected list, tuple or str")),
         };
 
         // IMPORTANT: Increment refcounts BEFORE dropping the container.
@@ -516,6 +586,9 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let this = self;
 
         let value = this.pop();
+        if !this.is_direct_unpack(&value) {
+            return this.unpack_iterable(value, before, Some(after));
+        }
         defer_drop_mut!(value, this);
 
         let min_items = before + after;
@@ -535,8 +608,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                     items.push(allocate_char(c, this.heap)?);
                 }
                 // String items are newly allocated, push and return
-                this.push_unpack_ex_results(&items, before, after)?;
-                return Ok(());
+                return this.push_unpack_ex_results(items, before, after);
             }
             Value::Ref(heap_id) => {
                 match this.heap.get(*heap_id) {
@@ -565,19 +637,12 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                             items.push(allocate_char(c, this.heap)?);
                         }
                         // String items are newly allocated, push and return
-                        this.push_unpack_ex_results(&items, before, after)?;
-                        return Ok(());
-                    }
-                    other => {
-                        let type_name = other.py_type(this.heap);
-                        return Err(unpack_type_error(type_name));
+                        return this.push_unpack_ex_results(items, before, after);
                     }
+                    _ => return Err(RunError::internal("UnpackEx: expected list, tuple or str")),
                 }
             }
-            _ => {
-                let type_name = value.py_type(this.heap);
-                return Err(unpack_type_error(type_name));
-            }
+            _ => return Err(RunError::internal("UnpackEx: expected list, tuple or str")),
         };
 
         // Increment refcounts BEFORE dropping the container.
@@ -594,48 +659,110 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         }
 
         // Now push the results
-        this.push_unpack_ex_results(&items, before, after)?;
-        Ok(())
+        this.push_unpack_ex_results(items, before, after)
     }
 
     /// Helper to push unpacked items with starred target onto the stack.
     ///
-    /// Takes a slice of items and creates the middle list.
-    fn push_unpack_ex_results(&mut self, items: &[Value], before: usize, after: usize) -> Result<(), RunError> {
-        let total = items.len();
+    /// Takes ownership of the items, which must already hold their references, and
+    /// moves the middle ones into a new list.
+    fn push_unpack_ex_results(&mut self, mut items: Vec<Value>, before: usize, after: usize) -> Result<(), RunError> {
+        let after_items = items.split_off(items.len() - after);
+        let middle = items.split_off(before);
 
-        // Collect results: before items, middle list, after items
-        let mut results = Vec::with_capacity(before + 1 + after);
+        // Middle items as a list (starred target)
+        let list_id = match self.heap.allocate(HeapData::List(List::new(middle))) {
+            Ok(list_id) => list_id,
+            Err(err) => {
+                items.drop_with_heap(self.heap);
+                after_items.drop_with_heap(self.heap);
+                return Err(err.into());
+            }
+        };
 
-        // Before items
-        for item in items.iter().take(before) {
-            results.push(item.copy_for_extend());
+        // Push in reverse order so first item is on top
+        for item in after_items.into_iter().rev() {
+            self.push(item);
         }
-
-        // Middle items as a list (starred target)
-        // Items already have their refcount incremented in unpack_ex (for list/tuple)
-        // or are freshly allocated (for strings), so no additional inc_ref needed here.
-        let middle_start = before;
-        let middle_end = total - after;
-        let mut middle = Vec::with_capacity(middle_end - middle_start);
-        for item in &items[middle_start..middle_end] {
-            middle.push(item.copy_for_extend());
+        self.push(Value::Ref(list_id));
+        for item in items.into_iter().rev() {
+            self.push(item);
         }
-        let middle_list = List::new(middle);
-        let list_id = self.heap.allocate(HeapData::List(middle_list))?;
-        results.push(Value::Ref(list_id));
 
-        // After items
-        for item in items.iter().skip(total - after) {
-            results.push(item.copy_for_extend());
+        Ok(())
+    }
+
+    /// Returns true for the values `unpack_sequence` and `unpack_ex` read directly: lists,
+    /// tuples and strings.
+    fn is_direct_unpack(&self, value: &Value) -> bool {
+        match value {
+            Value::InternString(_) => true,
+            Value::Ref(heap_id) => matches!(
+                self.heap.get(*heap_id),
+                HeapData::List(_) | HeapData::Tuple(_) | HeapData::Str(_)
+            ),
+            _ => false,
         }
+    }
 
-        // Push in reverse order so first item is on top
-        for item in results.into_iter().rev() {
-            self.push(item);
+    /// Unpacks any other iterable, such as a dict, set or range, by iterating it.
+    ///
+    /// Without `after`, exactly `before` values are expected, and iteration stops at the
+    /// first value too many, like CPython. With `after`, the iterable is exhausted and the
+    /// values between the first `before` and the last `after` go into a list.
+    fn unpack_iterable(&mut self, iterable: Value, before: usize, after: Option<usize>) -> Result<(), RunError> {
+        let type_name = iterable.py_type(self.heap);
+        // CPython only reports the length of a dict in the "too many values" message
+        let dict_len = match &iterable {
+            Value::Ref(heap_id) => match self.heap.get(*heap_id) {
+                HeapData::Dict(dict) => Some(dict.len()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Ok(mut iter) = MontyIter::new(iterable, self.heap, self.interns) else {
+            return Err(unpack_type_error(type_name));
+        };
+
+        let limit = if after.is_some() { usize::MAX } else { before + 1 };
+        let mut items = Vec::new();
+        let mut error = None;
+        while items.len() < limit {
+            match iter.for_next(self.heap, self.interns) {
+                Ok(Some(item)) => items.push(item),
+                Ok(None) => break,
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+        iter.drop_with_heap(self.heap);
+
+        let error = error.or_else(|| match after {
+            None if items.len() < before => Some(unpack_size_error(before, items.len(), false)),
+            None if items.len() > before => Some(match dict_len {
+                Some(len) => unpack_size_error(before, len, true),
+                None => unpack_size_error(before, items.len(), false),
+            }),
+            Some(after) if items.len() < before + after => Some(unpack_ex_too_few_error(before + after, items.len())),
+            _ => None,
+        });
+        if let Some(error) = error {
+            items.drop_with_heap(self.heap);
+            return Err(error);
         }
 
-        Ok(())
+        match after {
+            Some(after) => self.push_unpack_ex_results(items, before, after),
+            None => {
+                // Push items in reverse order so first item is on top
+                for item in items.into_iter().rev() {
+                    self.push(item);
+                }
+                Ok(())
+            }
+        }
     }
 }
 
@@ -649,12 +776,15 @@ fn unpack_ex_too_few_error(min_needed: usize, actual: usize) -> RunError {
 ///
 /// Python uses different messages depending on whether there are too few or too many values:
 /// - Too few: "not enough values to unpack (expected X, got Y)"
-/// - Too many: "too many values to unpack (expected X, got Y)"
-fn unpack_size_error(expected: usize, actual: usize) -> RunError {
+/// - Too many: "too many values to unpack (expected X, got Y)", where only lists, tuples
+///   and dicts report their length (`reports_length`), otherwise "(expected X)"
+fn unpack_size_error(expected: usize, actual: usize, reports_length: bool) -> RunError {
     let message = if actual < expected {
         format!("not enough values to unpack (expected {expected}, got {actual})")
-    } else {
+    } else if reports_length {
         format!("too many values to unpack (expected {expected}, got {actual})")
+    } else {
+        format!("too many values to unpack (expected {expected})")
     };
     SimpleException::new_msg(ExcType::ValueError, message).into()
 }
@@ -1,7 +1,10 @@
 //! Comparison operation helpers for the VM.
 
+use std::cmp::Ordering;
+
 use super::VM;
 use crate::{
+    bytecode::op::Opcode,
     defer_drop,
     exception_private::{ExcType, RunError},
     resource::{DepthGuard, ResourceTracker},
@@ -19,6 +22,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let lhs = this.pop();
         defer_drop!(lhs, this);
 
+        this.warn_bytes_str_comparison(lhs, rhs);
         let mut guard = DepthGuard::default();
         let result = lhs.py_eq(rhs, this.heap, &mut guard, this.interns)?;
         this.push(Value::Bool(result));
@@ -34,6 +38,7 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let lhs = this.pop();
         defer_drop!(lhs, this);
 
+        this.warn_bytes_str_comparison(lhs, rhs);
         let mut guard = DepthGuard::default();
         let result = !lhs.py_eq(rhs, this.heap, &mut guard, this.interns)?;
         this.push(Value::Bool(result));
@@ -58,6 +63,46 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         Ok(())
     }
 
+    /// Pops two operands and evaluates the comparison `compare` on them, returning the
+    /// result directly instead of pushing it.
+    ///
+    /// Used by `CompareJumpIfFalse`; each arm matches the standalone opcode's semantics.
+    pub(super) fn compare_to_bool(&mut self, compare: Opcode) -> Result<bool, RunError> {
+        let this = self;
+
+        let rhs = this.pop();
+        defer_drop!(rhs, this);
+        let lhs = this.pop();
+        defer_drop!(lhs, this);
+
+        if matches!(compare, Opcode::CompareEq | Opcode::CompareNe) {
+            this.warn_bytes_str_comparison(lhs, rhs);
+        }
+        let mut guard = DepthGuard::default();
+        let result = match compare {
+            Opcode::CompareEq => lhs.py_eq(rhs, this.heap, &mut guard, this.interns)?,
+            Opcode::CompareNe => !lhs.py_eq(rhs, this.heap, &mut guard, this.interns)?,
+            Opcode::CompareLt => lhs
+                .py_cmp(rhs, this.heap, &mut guard, this.interns)?
+                .is_some_and(Ordering::is_lt),
+            Opcode::CompareLe => lhs
+                .py_cmp(rhs, this.heap, &mut guard, this.interns)?
+                .is_some_and(Ordering::is_le),
+            Opcode::CompareGt => lhs
+                .py_cmp(rhs, this.heap, &mut guard, this.interns)?
+                .is_some_and(Ordering::is_gt),
+            Opcode::CompareGe => lhs
+                .py_cmp(rhs, this.heap, &mut guard, this.interns)?
+                .is_some_and(Ordering::is_ge),
+            Opcode::CompareIs => lhs.is(rhs),
+            Opcode::CompareIsNot => !lhs.is(rhs),
+            Opcode::CompareIn => rhs.py_contains(lhs, this.heap, this.interns)?,
+            Opcode::CompareNotIn => !rhs.py_contains(lhs, this.heap, this.interns)?,
+            _ => return Err(RunError::internal(format!("{compare:?} is not a comparison opcode"))),
+        };
+        Ok(result)
+    }
+
     /// Identity comparison (is/is not).
     ///
     /// Compares identity using `Value::is()` which compares IDs.
@@ -0,0 +1,121 @@
+//! Line breakpoints and single-stepping.
+//!
+//! A run started with breakpoints carries a [`Debugger`] in the VM and in its snapshots.
+//! Before each instruction that starts a new line, the VM checks it against the debugger
+//! and, on a hit, stops with `FrameExit::Breakpoint` at that instruction, so resuming
+//! continues exactly where it stopped.
+
+use std::collections::BTreeSet;
+
+use super::{VM, VMSnapshot};
+use crate::{
+    bytecode::code::Code,
+    intern::{FunctionId, Interns},
+    namespace::NamespaceId,
+    resource::ResourceTracker,
+};
+
+/// Breakpoints and stepping state of a debugged run.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Debugger {
+    /// Lines to stop at.
+    breakpoints: BTreeSet<u16>,
+    /// Whether to stop at the next new line, wherever it is.
+    stepping: bool,
+    /// Frame depth, instruction IP and line of the last instruction checked.
+    ///
+    /// Serialized with the snapshot so that resuming at a breakpoint doesn't stop at the
+    /// same instruction again.
+    last_line: Option<(usize, usize, u16)>,
+}
+
+impl Debugger {
+    /// Creates a debugger that stops at the given lines.
+    pub fn new(breakpoints: impl IntoIterator<Item = u16>) -> Self {
+        Self {
+            breakpoints: breakpoints.into_iter().collect(),
+            stepping: false,
+            last_line: None,
+        }
+    }
+
+    /// Returns the lines to stop at, in order.
+    pub fn breakpoints(&self) -> impl Iterator<Item = u16> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Replaces the lines to stop at.
+    pub fn set_breakpoints(&mut self, breakpoints: impl IntoIterator<Item = u16>) {
+        self.breakpoints = breakpoints.into_iter().collect();
+    }
+
+    /// Sets whether to stop at the next new line regardless of breakpoints.
+    pub fn set_stepping(&mut self, stepping: bool) {
+        self.stepping = stepping;
+    }
+
+    /// Records the instruction at `ip` and returns whether execution should stop there.
+    fn check(&mut self, depth: usize, ip: usize, line: u16) -> bool {
+        let same_line = matches!(
+            self.last_line,
+            Some((last_depth, last_ip, last_line)) if last_depth == depth && last_line == line && ip >= last_ip
+        );
+        self.last_line = Some((depth, ip, line));
+        !same_line && (self.stepping || self.breakpoints.contains(&line))
+    }
+}
+
+impl<T: ResourceTracker> VM<'_, '_, T> {
+    /// Attaches a debugger, making the VM stop at its breakpoints.
+    pub fn set_debugger(&mut self, debugger: Debugger) {
+        self.debugger = Some(debugger);
+        self.update_instrumented();
+    }
+
+    /// Returns whether the instruction at `instruction_ip`, which is on `line`, starts a
+    /// line the debugger should stop at.
+    pub(super) fn hit_breakpoint(&mut self, line: u16) -> bool {
+        let depth = self.frames.len();
+        let ip = self.instruction_ip;
+        self.debugger
+            .as_mut()
+            .is_some_and(|debugger| debugger.check(depth, ip, line))
+    }
+
+    /// Marks the rest of the calling line as already checked after returning into it, so
+    /// a breakpoint on a line that makes a call doesn't stop again once the call returns.
+    pub(super) fn debug_resume_caller(&mut self) {
+        let caller_line = self.caller_line();
+        if let Some(debugger) = &mut self.debugger {
+            debugger.last_line = caller_line;
+        }
+    }
+}
+
+impl VMSnapshot {
+    /// Returns the debugger of a debugged run.
+    pub fn debugger(&self) -> Option<&Debugger> {
+        self.debugger.as_ref()
+    }
+
+    /// Returns the debugger of a debugged run, to change breakpoints or stepping.
+    pub fn debugger_mut(&mut self) -> Option<&mut Debugger> {
+        self.debugger.as_mut()
+    }
+
+    /// Returns the function (`None` for module code), namespace and source line of the
+    /// innermost frame.
+    pub fn current_location(
+        &self,
+        module_code: &Code,
+        interns: &Interns,
+    ) -> Option<(Option<FunctionId>, NamespaceId, u16)> {
+        let frame = self.frames.last()?;
+        let code = match frame.function_id {
+            Some(func_id) => &interns.get_function(func_id).code,
+            None => module_code,
+        };
+        let line = code.location_for_offset(frame.ip)?.range().start().line;
+        Some((frame.function_id, frame.namespace_idx, line))
+    }
+}
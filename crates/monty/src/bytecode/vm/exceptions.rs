@@ -4,7 +4,9 @@ use super::VM;
 use crate::{
     builtins::Builtins,
     defer_drop,
-    exception_private::{ExcType, ExceptionRaise, RawStackFrame, RunError, SimpleException},
+    exception_private::{
+        ExcType, ExceptionInstance, ExceptionLinks, ExceptionRaise, RawStackFrame, RunError, SimpleException,
+    },
     heap::{HeapData, HeapGuard},
     intern::{StaticStrings, StringId},
     resource::ResourceTracker,
@@ -71,22 +73,39 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
         let this = self;
         defer_drop!(exc_value, this);
 
-        let simple_exc = match exc_value {
+        let (simple_exc, instance, links) = match exc_value {
             // Exception instance on heap
             Value::Ref(heap_id) => {
                 if let HeapData::Exception(exc) = this.heap.get(*heap_id) {
-                    // Clone the exception (guard handles cleanup at scope exit)
-                    exc.clone()
+                    // Clone the exception (guard handles cleanup at scope exit), remembering
+                    // the instance so a handler can bind the same object. An explicit cause
+                    // stays with the object; the context is set afresh for this raise.
+                    let links = exc
+                        .links()
+                        .filter(|links| links.cause.is_some() || links.suppress_context)
+                        .map(|links| {
+                            Box::new(ExceptionLinks {
+                                cause: links.cause.clone(),
+                                context: None,
+                                suppress_context: links.suppress_context,
+                            })
+                        });
+                    (exc.exc().clone(), Some(*heap_id), links)
                 } else {
                     // Not an exception type
-                    SimpleException::new_msg(ExcType::TypeError, "exceptions must derive from BaseException")
+                    let exc = SimpleException::new_msg(ExcType::TypeError, "exceptions must derive from BaseException");
+                    (exc, None, None)
                 }
             }
             // Exception type (e.g., `raise ValueError` instead of `raise ValueError()`)
             // Instantiate with no message
-            Value::Builtin(Builtins::ExcType(exc_type)) => SimpleException::new_none(*exc_type),
+            Value::Builtin(Builtins::ExcType(exc_type)) => (SimpleException::new_none(*exc_type), None, None),
             // Invalid exception value
-            _ => SimpleException::new_msg(ExcType::TypeError, "exceptions must derive from BaseException"),
+            _ => (
+                SimpleException::new_msg(ExcType::TypeError, "exceptions must derive from BaseException"),
+                None,
+                None,
+            ),
         };
 
         // Create frame with appropriate hide_caret setting
@@ -100,9 +119,81 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
             exc: simple_exc,
             frame: Some(frame),
             hide_caret: false,
+            instance,
+            links,
         })
     }
 
+    /// Creates a RunError for `raise exc from cause`.
+    ///
+    /// `cause` may be an exception instance or type, or `None` to only hide the context.
+    pub(super) fn make_exception_from(&mut self, exc_value: Value, cause_value: Value) -> RunError {
+        let this = self;
+        defer_drop!(cause_value, this);
+        // `None` if the cause isn't valid, `Some(None)` for `from None`
+        let cause = match cause_value {
+            Value::None => Some(None),
+            Value::Ref(heap_id) => match this.heap.get(*heap_id) {
+                HeapData::Exception(cause) => Some(Some(cause.to_raise())),
+                _ => None,
+            },
+            Value::Builtin(Builtins::ExcType(exc_type)) => Some(Some(SimpleException::new_none(*exc_type).into())),
+            _ => None,
+        };
+        let Some(cause) = cause else {
+            exc_value.drop_with_heap(this.heap);
+            return SimpleException::new_msg(ExcType::TypeError, "exception causes must derive from BaseException")
+                .into();
+        };
+        let mut error = this.make_exception(exc_value, true);
+        if let RunError::Exc(exc) = &mut error {
+            let links = exc.links.get_or_insert_default();
+            links.cause = cause;
+            links.suppress_context = true;
+        }
+        error
+    }
+
+    /// Records the exception being handled, if any, as the context of `exc`.
+    ///
+    /// Mirrors CPython's implicit `__context__`: an exception raised inside an `except` or
+    /// `finally` block is chained to the exception that block is handling. A context
+    /// already set (e.g. by a re-raise) is kept.
+    fn attach_context(&self, exc: &mut ExceptionRaise) {
+        if exc.links.as_ref().is_some_and(|links| links.context.is_some()) {
+            return;
+        }
+        if let Some(Value::Ref(handled_id)) = self.exception_stack.last()
+            && exc.instance != Some(*handled_id)
+            && let HeapData::Exception(handled) = self.heap.get(*handled_id)
+        {
+            exc.links.get_or_insert_default().context = Some(handled.to_raise());
+        }
+    }
+
+    /// Creates a RunError for a bare `raise` re-raising the exception being handled.
+    ///
+    /// Reuses the traceback recorded when the exception was caught, so the re-raised
+    /// exception still points at the original failure rather than at the `raise`
+    /// statement (or at the implicit re-raise ending a `finally` or `except ... as` block).
+    pub(super) fn make_reraise(&mut self, exc_value: Value) -> RunError {
+        let (traceback, links) = match &exc_value {
+            Value::Ref(heap_id) => match self.heap.get(*heap_id) {
+                HeapData::Exception(exc) => (exc.traceback().cloned(), exc.links().cloned().map(Box::new)),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+        let mut error = self.make_exception(exc_value, true);
+        if let RunError::Exc(exc) = &mut error {
+            if let Some(traceback) = traceback {
+                exc.frame = Some(traceback);
+            }
+            exc.links = links;
+        }
+        error
+    }
+
     /// Handles an exception by searching for a handler in the exception table.
     ///
     /// Returns:
@@ -117,6 +208,12 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
     pub(super) fn handle_exception(&mut self, mut error: RunError) -> Option<RunError> {
         // Ensure exception has initial frame info
         error = self.attach_frame_to_error(error);
+        if let RunError::Exc(exc) = &mut error {
+            self.attach_context(exc);
+        }
+        if self.is_tracing() {
+            self.trace_exception(&error);
+        }
 
         // For uncatchable exceptions (ResourceError like RecursionError),
         // we still need to unwind the stack to collect all frames for the traceback
@@ -159,6 +256,14 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
                     value.drop_with_heap(this.heap);
                 }
 
+                // Record the traceback on the exception object, for a later bare `raise`
+                if let (Value::Ref(id), RunError::Exc(exc)) = (exc_value, &error)
+                    && let HeapData::Exception(instance) = this.heap.get_mut(*id)
+                {
+                    instance.set_traceback(exc.frame.clone());
+                    instance.set_links(exc.links.clone());
+                }
+
                 // Push exception value onto stack (handler expects it)
                 let exc_for_stack = exc_value.clone_with_heap(this.heap);
                 this.push(exc_for_stack);
@@ -254,9 +359,18 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
 
     /// Creates an exception Value from exception info.
     ///
-    /// Allocates an Exception on the heap and returns a Value::Ref to it.
+    /// Returns a new reference to the object the exception was raised from if it is still
+    /// alive (so `e` in the handler is the very object that was raised, attributes and all),
+    /// otherwise allocates a new Exception on the heap and returns a Value::Ref to it.
     fn create_exception_value(&mut self, exc: &ExceptionRaise) -> Result<Value, RunError> {
-        let exception = exc.exc.clone();
+        if let Some(id) = exc.instance
+            && let Some(HeapData::Exception(instance)) = self.heap.get_if_live(id)
+            && *instance.exc() == exc.exc
+        {
+            self.heap.inc_ref(id);
+            return Ok(Value::Ref(id));
+        }
+        let exception = ExceptionInstance::new(exc.exc.clone());
         let heap_id = self.heap.allocate(HeapData::Exception(exception))?;
         Ok(Value::Ref(heap_id))
     }
@@ -55,6 +55,10 @@ impl<T: ResourceTracker> VM<'_, '_, T> {
 
         let value = this.pop();
         defer_drop!(value, this);
+        // without a conversion, or with `!s`, the value is converted with `str()`
+        if conversion <= 1 {
+            this.warn_bytes_to_str(value);
+        }
 
         // Format with spec applied to original value type, or convert and format as string
         let formatted = if let Some(spec_value) = format_spec {
@@ -0,0 +1,68 @@
+//! Waiting on the host for the items of host iterables (see `CompileOptions::host_iterables`)
+//! that `for` loops and comprehensions iterate over.
+
+use super::{FrameExit, VM};
+use crate::{
+    ExternalResult,
+    args::ArgValues,
+    exception_private::{ExcType, RunError, SimpleException},
+    heap::HeapData,
+    intern::ExtFunctionId,
+    object::MontyObject,
+    resource::ResourceTracker,
+    value::Value,
+};
+
+impl<T: ResourceTracker> VM<'_, '_, T> {
+    /// Pauses the run to ask the host for more items of host iterable `ext_function_id`,
+    /// like a call to it with no arguments.
+    ///
+    /// The frame is rewound to the start of the `ForIter` instruction, so once the host has
+    /// handed over more items the loop asks the iterator for its next item again.
+    pub(super) fn wait_on_host_iterable(&mut self, ext_function_id: ExtFunctionId) -> FrameExit {
+        self.current_frame_mut().ip = self.instruction_ip;
+        let call_id = self.allocate_call_id();
+        FrameExit::ExternalCall {
+            ext_function_id,
+            args: ArgValues::Empty,
+            call_id,
+        }
+    }
+
+    /// Resumes a loop waiting on the host for the items of a host iterable with the host's
+    /// answer: the next item, a `StopIteration` error once there are no more, or any other
+    /// error, which is raised by the loop.
+    pub fn resume_host_iterable(&mut self, result: ExternalResult) -> Result<FrameExit, RunError> {
+        match result {
+            ExternalResult::Return(item) => self.resume_host_items(vec![item], false),
+            ExternalResult::Error(exc) if exc.exc_type() == ExcType::StopIteration => {
+                self.resume_host_items(Vec::new(), true)
+            }
+            ExternalResult::Error(exc) => self.resume_with_exception(exc.into()),
+            ExternalResult::Future => self.resume_with_exception(
+                SimpleException::new(
+                    ExcType::TypeError,
+                    Some("host iterables can't hand over their items as futures".to_owned()),
+                )
+                .into(),
+            ),
+        }
+    }
+
+    /// Resumes a loop waiting on the host for the items of a host iterable, handing the
+    /// iterator `items`, with `exhausted` marking that the host has no more.
+    pub fn resume_host_items(&mut self, items: Vec<MontyObject>, exhausted: bool) -> Result<FrameExit, RunError> {
+        let Value::Ref(iter_id) = *self.peek() else {
+            return Err(RunError::internal("resume_host_items: expected iterator ref on stack"));
+        };
+        let HeapData::Iter(iter) = self.heap.get_mut(iter_id) else {
+            return Err(RunError::internal("resume_host_items: expected iterator on heap"));
+        };
+        if !iter.feed_host(items, exhausted) {
+            return Err(RunError::internal(
+                "resume_host_items: iterator isn't over a host iterable",
+            ));
+        }
+        self.run()
+    }
+}
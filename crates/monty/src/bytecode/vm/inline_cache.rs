@@ -0,0 +1,161 @@
+//! Per-call-site inline caches for attribute lookups.
+//!
+//! Each `LoadAttr` and `CallAttr` instruction carries a cache slot assigned by the
+//! compiler. The slot remembers what the last lookup at that site found, keyed on the
+//! receiver, so later executions of the same instruction skip the attribute dictionary
+//! probe:
+//!
+//! - For a module, the attribute value itself, together with the module's `HeapId` and the
+//!   heap's namespace generation.
+//! - For a dataclass instance, the attribute's position in the instance's attribute dict,
+//!   keyed on the dataclass type. Instances of one type are built with their fields in the
+//!   same order, so the position carries over to every instance the site sees; a hit checks
+//!   that the key stored there is still the attribute's name, so nothing needs
+//!   invalidating when attributes are added or removed.
+//!
+//! Globals are already resolved to namespace slots at compile time and builtins to
+//! dedicated opcodes, so neither probes a dictionary. Methods of builtin types such as
+//! `list.append` and `str.join` don't either: they are dispatched by matching the
+//! interned name's static string index, which a cache entry could not make cheaper.
+//!
+//! `Code` objects are shared immutably, so the tables live in the VM, one per code
+//! object, allocated the first time a site in that code fills an entry. They are not
+//! part of `VMSnapshot`; a resumed VM starts with empty caches.
+
+use crate::{bytecode::code::Code, heap::HeapId, intern::FunctionId, value::Value};
+
+/// Inline cache tables for module-level code and every function that has filled one.
+#[derive(Debug, Default)]
+pub(super) struct InlineCaches {
+    /// Table for module-level code.
+    module: Vec<Option<AttrCacheEntry>>,
+    /// Tables for function code, indexed by `FunctionId`. Empty until first filled.
+    functions: Vec<Vec<Option<AttrCacheEntry>>>,
+}
+
+/// An attribute lookup remembered by one call site.
+#[derive(Debug)]
+enum AttrCacheEntry {
+    /// An attribute found on a module.
+    Module {
+        /// The module the attribute was found on.
+        owner: HeapId,
+        /// Heap namespace generation when the entry was filled.
+        generation: u64,
+        /// The attribute value; always an immediate, so the cache holds no refcount.
+        value: Value,
+    },
+    /// An attribute found on a dataclass instance.
+    Dataclass {
+        /// The dataclass type the attribute was found on, see `Dataclass::type_id`.
+        type_id: u64,
+        /// Position of the attribute in the instance's attribute dict.
+        index: usize,
+    },
+}
+
+impl InlineCaches {
+    /// Returns the module attribute cached at `slot` if it was filled for `owner` under
+    /// `generation`.
+    #[inline]
+    pub fn lookup_module(
+        &self,
+        function_id: Option<FunctionId>,
+        slot: u16,
+        owner: HeapId,
+        generation: u64,
+    ) -> Option<Value> {
+        match self.entry(function_id, slot)? {
+            AttrCacheEntry::Module {
+                owner: cached_owner,
+                generation: cached_generation,
+                value,
+            } if *cached_owner == owner && *cached_generation == generation => Some(value.clone_immediate()),
+            _ => None,
+        }
+    }
+
+    /// Returns the attribute dict position cached at `slot` if it was filled for a
+    /// dataclass of type `type_id`.
+    #[inline]
+    pub fn lookup_dataclass(&self, function_id: Option<FunctionId>, slot: u16, type_id: u64) -> Option<usize> {
+        match self.entry(function_id, slot)? {
+            AttrCacheEntry::Dataclass {
+                type_id: cached_type_id,
+                index,
+            } if *cached_type_id == type_id => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Remembers the module attribute `value` at `slot` for `owner` under `generation`.
+    ///
+    /// `value` must be an immediate. Slots past the end of `code`'s table are ignored.
+    pub fn store_module(
+        &mut self,
+        function_id: Option<FunctionId>,
+        code: &Code,
+        slot: u16,
+        owner: HeapId,
+        generation: u64,
+        value: Value,
+    ) {
+        if let Some(entry) = self.entry_mut(function_id, code, slot) {
+            *entry = Some(AttrCacheEntry::Module {
+                owner,
+                generation,
+                value,
+            });
+        }
+    }
+
+    /// Remembers that the attribute loaded at `slot` sits at `index` of the attribute dict
+    /// of dataclasses of type `type_id`.
+    ///
+    /// Slots past the end of `code`'s table are ignored.
+    pub fn store_dataclass(
+        &mut self,
+        function_id: Option<FunctionId>,
+        code: &Code,
+        slot: u16,
+        type_id: u64,
+        index: usize,
+    ) {
+        if let Some(entry) = self.entry_mut(function_id, code, slot) {
+            *entry = Some(AttrCacheEntry::Dataclass { type_id, index });
+        }
+    }
+
+    /// Returns the filled entry at `slot` of the table for `function_id`.
+    #[inline]
+    fn entry(&self, function_id: Option<FunctionId>, slot: u16) -> Option<&AttrCacheEntry> {
+        let table = match function_id {
+            None => &self.module,
+            Some(id) => self.functions.get(id.index())?,
+        };
+        table.get(usize::from(slot))?.as_ref()
+    }
+
+    /// Returns the entry at `slot` of the table for `function_id`, allocating the table
+    /// with `code`'s slot count the first time it is filled.
+    fn entry_mut(
+        &mut self,
+        function_id: Option<FunctionId>,
+        code: &Code,
+        slot: u16,
+    ) -> Option<&mut Option<AttrCacheEntry>> {
+        let table = match function_id {
+            None => &mut self.module,
+            Some(id) => {
+                if self.functions.len() <= id.index() {
+                    self.functions.resize_with(id.index() + 1, Vec::new);
+                }
+                &mut self.functions[id.index()]
+            }
+        };
+        if table.is_empty() {
+            table.resize_with(code.num_inline_caches(), || None);
+        }
+        table.get_mut(usize::from(slot))
+    }
+}
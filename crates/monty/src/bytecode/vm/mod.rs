@@ -3,36 +3,53 @@
 //! The VM uses a stack-based execution model with an operand stack for computation
 //! and a call stack for function frames. Each frame owns its instruction pointer (IP).
 
+mod annotations;
 mod async_exec;
 mod attr;
 mod binary;
 mod call;
 mod collections;
 mod compare;
+mod debug;
 mod exceptions;
 mod format;
+mod host_iter;
+mod inline_cache;
 mod scheduler;
+mod trace;
+mod warnings;
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, time::Duration};
 
 use call::CallResult;
+pub use debug::Debugger;
+use inline_cache::InlineCaches;
 use scheduler::Scheduler;
 
 use crate::{
     MontyObject,
     args::ArgValues,
     asyncio::{CallId, TaskId},
-    bytecode::{code::Code, op::Opcode},
+    bytecode::{
+        code::Code,
+        op::{Opcode, is_instruction_start},
+    },
+    coverage::LineCoverage,
     exception_private::{ExcType, RunError, RunResult, SimpleException},
-    heap::{ContainsHeap, Heap, HeapData, HeapId},
+    heap::{ContainsHeap, Heap, HeapData, HeapId, HeapIdMap},
     intern::{ExtFunctionId, FunctionId, Interns, StringId},
     io::PrintWriter,
     modules::BuiltinModule,
     namespace::{GLOBAL_NS_IDX, NamespaceId, Namespaces},
     os::OsFunction,
     parse::CodeRange,
+    profile::Profiler,
     resource::ResourceTracker,
-    types::{LongInt, MontyIter, PyTrait, iter::advance_on_heap},
+    timeline::SpanKind,
+    types::{
+        LongInt, MontyIter, PyTrait,
+        iter::{ForLoopStep, advance_for_loop},
+    },
     value::{BitwiseOp, EitherStr, Value},
 };
 
@@ -159,16 +176,23 @@ macro_rules! jump_relative {
 /// Actions taken for each variant:
 /// - `Push(value)`: Push the value onto the stack
 /// - `FramePushed`: Reload the cached frame (a new frame was pushed)
-/// - `External(ext_id, args)`: Return `FrameExit::ExternalCall` to yield to host
+/// - `External(ext_id, args)`: Return `FrameExit::ExternalCall` to yield to host, or push a future
+///   for the call if the function is batched
 /// - `OsCall(func, args)`: Return `FrameExit::OsCall` to yield to host
 /// - `MethodCall(name, args)`: Return `FrameExit::MethodCall` to yield to host
 /// - `AwaitValue(value)`: Push value, then implicitly await it via `exec_get_awaitable`
+/// - `Emit(value)`: Return `FrameExit::Emit` to yield to host
+/// - `Sleep(duration)`: Return `FrameExit::Sleep` to yield to host
 /// - `Err(err)`: Handle the exception via `catch_sync!`
 macro_rules! handle_call_result {
     ($self:expr, $cached_frame:ident, $result:expr) => {
         match $result {
             Ok(CallResult::Push(result)) => $self.push(result),
             Ok(CallResult::FramePushed) => reload_cache!($self, $cached_frame),
+            Ok(CallResult::External(ext_id, args)) if $self.interns.is_external_batched(ext_id) => {
+                let future = $self.batch_external_call(ext_id, args);
+                $self.push(future);
+            }
             Ok(CallResult::External(ext_id, args)) => {
                 let call_id = $self.allocate_call_id();
                 // Sync cached IP back to frame before snapshot for resume
@@ -218,6 +242,18 @@ macro_rules! handle_call_result {
                     }
                 }
             }
+            Ok(CallResult::Emit(value)) => {
+                let call_id = $self.allocate_call_id();
+                // Sync cached IP back to frame before snapshot for resume
+                $self.current_frame_mut().ip = $cached_frame.ip;
+                return Ok(FrameExit::Emit { value, call_id });
+            }
+            Ok(CallResult::Sleep(duration)) => {
+                let call_id = $self.allocate_call_id();
+                // Sync cached IP back to frame before snapshot for resume
+                $self.current_frame_mut().ip = $cached_frame.ip;
+                return Ok(FrameExit::Sleep { duration, call_id });
+            }
             Err(err) => catch_sync!($self, $cached_frame, err),
         }
     };
@@ -277,6 +313,40 @@ pub enum FrameExit {
     /// This happens when await is called on an ExternalFuture that hasn't
     /// been resolved yet, and there are no other ready tasks to switch to.
     ResolveFutures(Vec<CallId>),
+
+    /// Execution paused because the script called `emit(value)`.
+    ///
+    /// The caller should hand `value` to the host and call `resume()` with `emit()`'s
+    /// return value, like for an external call.
+    Emit {
+        /// The emitted value.
+        value: Value,
+        /// Unique ID for this call, used for async correlation.
+        call_id: CallId,
+    },
+
+    /// Execution paused because the script called `time.sleep()`.
+    ///
+    /// The caller should hand `duration` to the host and call `resume()` with `None`
+    /// once the host's scheduler decides the script may continue.
+    Sleep {
+        /// The requested sleep duration.
+        duration: Duration,
+        /// Unique ID for this call, used for async correlation.
+        call_id: CallId,
+    },
+
+    /// Execution paused because the fuel budget set with `set_fuel()` ran out.
+    ///
+    /// The VM stopped at an instruction boundary, so it can be snapshotted and
+    /// later resumed with `run()` without pushing any value.
+    Paused,
+
+    /// Execution stopped at a line breakpoint, or at the next line while single-stepping.
+    ///
+    /// Like `Paused`, the VM stopped before the instruction and resumes without pushing
+    /// any value.
+    Breakpoint,
 }
 
 /// A single function activation record.
@@ -367,6 +437,25 @@ impl<'code> From<&CallFrame<'code>> for CachedFrame<'code> {
     }
 }
 
+/// Bytecode offsets of `code` covered by one location entry, and the line it starts on.
+///
+/// Remembered by the VM so line-based instrumentation only resolves the location of an
+/// instruction once execution leaves the span of the previous one.
+#[derive(Debug, Copy, Clone)]
+struct LineSpan<'code> {
+    code: &'code Code,
+    start: usize,
+    end: usize,
+    line: u16,
+}
+
+impl LineSpan<'_> {
+    /// Returns whether the instruction at `ip` of `code` falls in this span.
+    fn contains(&self, code: &Code, ip: usize) -> bool {
+        std::ptr::eq(self.code, code) && (self.start..self.end).contains(&ip)
+    }
+}
+
 /// Serializable representation of a call frame.
 ///
 /// Cannot store `&Code` (a reference) - instead stores `FunctionId` to look up
@@ -444,6 +533,112 @@ pub struct VMSnapshot {
     /// This enables async execution to be paused and resumed across host calls.
     /// None if no async operations have been performed yet.
     scheduler: Option<Scheduler>,
+
+    /// Remaining fuel (opcodes) before the VM pauses, `None` for unlimited.
+    fuel: Option<u64>,
+
+    /// Breakpoints of a debugged run, `None` if the run isn't being debugged.
+    debugger: Option<Debugger>,
+}
+
+impl VMSnapshot {
+    /// Takes the batched external calls the run queued since it last yielded, oldest first.
+    pub fn take_batched_calls(&mut self) -> Vec<(CallId, ExtFunctionId, ArgValues)> {
+        self.scheduler
+            .as_mut()
+            .map_or_else(Vec::new, Scheduler::take_batched_calls)
+    }
+
+    /// Pushes `value` onto the operand stack without checking it, for crafting snapshots
+    /// that decode but are inconsistent.
+    pub(crate) fn push_unchecked(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    /// Rewrites every heap id held by the suspended VM after heap compaction.
+    pub fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        map.remap_values(&mut self.stack);
+        for frame in &mut self.frames {
+            frame.cells.iter_mut().for_each(|cell_id| map.remap(cell_id));
+        }
+        map.remap_values(&mut self.exception_stack);
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.remap_heap_ids(map);
+        }
+    }
+
+    /// Checks that a deserialized snapshot's frames and heap ids are in range before resuming.
+    ///
+    /// Every frame (including those of suspended tasks) must run an existing function, point
+    /// at an instruction of its bytecode, and refer to an existing namespace and a valid
+    /// operand stack position. Heap ids and the interned ids of values are checked with
+    /// `checker`, from `Heap::validate`.
+    pub fn validate(
+        &mut self,
+        module_code: &Code,
+        interns: &Interns,
+        namespace_count: usize,
+        checker: &HeapIdMap,
+    ) -> Result<(), String> {
+        let check_frame = |function_id: Option<FunctionId>,
+                           ip: usize,
+                           stack_base: usize,
+                           namespace_idx: NamespaceId,
+                           stack_len: usize| {
+            let code = match function_id {
+                Some(id) if id.index() >= interns.function_count() => {
+                    return Err(format!("frame runs function {}, which does not exist", id.index()));
+                }
+                Some(id) => &interns.get_function(id).code,
+                None => module_code,
+            };
+            if ip >= code.bytecode().len() {
+                return Err(format!("frame instruction pointer {ip} is past the end of its code"));
+            }
+            if !is_instruction_start(code.bytecode(), ip) {
+                return Err(format!("frame instruction pointer {ip} is inside an instruction"));
+            }
+            if stack_base > stack_len {
+                return Err(format!("frame stack base {stack_base} is past the end of the stack"));
+            }
+            if namespace_idx.index() >= namespace_count {
+                return Err(format!(
+                    "frame refers to namespace {}, which does not exist",
+                    namespace_idx.index()
+                ));
+            }
+            Ok(())
+        };
+
+        for frame in &self.frames {
+            check_frame(
+                frame.function_id,
+                frame.ip,
+                frame.stack_base,
+                frame.namespace_idx,
+                self.stack.len(),
+            )?;
+        }
+        if let Some(scheduler) = &self.scheduler {
+            for task in scheduler.tasks() {
+                for frame in &task.frames {
+                    check_frame(
+                        frame.function_id,
+                        frame.ip,
+                        frame.stack_base,
+                        frame.namespace_idx,
+                        task.stack.len(),
+                    )?;
+                }
+            }
+        }
+
+        self.remap_heap_ids(checker);
+        match checker.problem() {
+            Some(problem) => Err(format!("execution state refers to {problem}")),
+            None => Ok(()),
+        }
+    }
 }
 
 // ============================================================================
@@ -509,6 +704,40 @@ pub struct VM<'a, 'p, T: ResourceTracker> {
     /// Stored here because the main task's frames have `function_id: None` and
     /// need a reference to the module code when being restored after task switching.
     module_code: Option<&'a Code>,
+
+    /// Number of opcodes the VM may still execute before yielding `FrameExit::Paused`.
+    ///
+    /// `None` (the default) means execution is not fuel-limited. The remaining budget
+    /// is carried through snapshots, so an external call in the middle of a slice
+    /// doesn't refill it.
+    fuel: Option<u64>,
+
+    /// Inline caches for module and dataclass attributes read by `LoadAttr` and `CallAttr`
+    /// sites, rebuilt from scratch on resume.
+    inline_caches: InlineCaches,
+
+    /// Frame depth, instruction IP and line of the last line reported to the trace hook.
+    ///
+    /// Only maintained while the tracker has a trace hook; not part of snapshots, so the
+    /// first instruction after resuming always reports a line event.
+    trace_line: Option<(usize, usize, u16)>,
+
+    /// Per-line profiler, attached with `set_profiler()` for `MontyRun::run_profiled`.
+    profiler: Option<&'a mut Profiler>,
+
+    /// Location span of the last instruction the debugger, profiler or coverage saw; not
+    /// part of snapshots.
+    line_span: Option<LineSpan<'a>>,
+
+    /// Executed-line recorder, attached with `set_coverage()` for `MontyRun::run_coverage`.
+    coverage: Option<&'a mut LineCoverage>,
+
+    /// Breakpoints to stop at, attached with `set_debugger()`; carried through snapshots.
+    debugger: Option<Debugger>,
+
+    /// Whether any of `fuel`, `debugger`, `profiler` or `coverage` is set, so `run()` needs
+    /// to call `instrument_step()` before each instruction. Kept up to date by their setters.
+    instrumented: bool,
 }
 
 impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
@@ -531,6 +760,14 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
             next_call_id: 0,
             scheduler: None, // Lazy - no allocation for sync code
             module_code: None,
+            fuel: None,
+            debugger: None,
+            inline_caches: InlineCaches::default(),
+            trace_line: None,
+            profiler: None,
+            line_span: None,
+            coverage: None,
+            instrumented: false,
         }
     }
 
@@ -555,6 +792,9 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
         interns: &'a Interns,
         print_writer: &'a mut PrintWriter<'p>,
     ) -> Self {
+        // Resuming ends any wait on the host started in `check_snapshot`
+        heap.tracker_mut().on_span_exit(SpanKind::ExternalWait);
+
         // Reconstruct call frames from serialized form
         let frames = snapshot
             .frames
@@ -576,6 +816,7 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
             })
             .collect();
 
+        let instrumented = snapshot.fuel.is_some() || snapshot.debugger.is_some();
         Self {
             stack: snapshot.stack,
             frames,
@@ -588,16 +829,42 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
             next_call_id: snapshot.next_call_id,
             scheduler: snapshot.scheduler,
             module_code: Some(module_code),
+            fuel: snapshot.fuel,
+            debugger: snapshot.debugger,
+            inline_caches: InlineCaches::default(),
+            trace_line: None,
+            profiler: None,
+            line_span: None,
+            coverage: None,
+            instrumented,
         }
     }
     /// Consumes the VM and creates a snapshot for pause/resume if needed.
     pub fn check_snapshot(mut self, result: &RunResult<FrameExit>) -> Option<VMSnapshot> {
+        let wait_name = match result {
+            Ok(FrameExit::ExternalCall { ext_function_id, .. }) => {
+                Some(self.interns.get_external_function_name(*ext_function_id))
+            }
+            Ok(FrameExit::OsCall { function, .. }) => Some(function.to_string()),
+            Ok(FrameExit::MethodCall { method_name, .. }) => Some(method_name.as_str(self.interns).to_owned()),
+            Ok(FrameExit::ResolveFutures(_)) => Some("<futures>".to_owned()),
+            Ok(FrameExit::Sleep { .. }) => Some("time.sleep".to_owned()),
+            _ => None,
+        };
+        if let Some(name) = wait_name {
+            self.heap.tracker_mut().on_span_enter(SpanKind::ExternalWait, &name);
+        }
+
         if matches!(
             result,
             Ok(FrameExit::ExternalCall { .. }
                 | FrameExit::OsCall { .. }
                 | FrameExit::MethodCall { .. }
-                | FrameExit::ResolveFutures(_))
+                | FrameExit::ResolveFutures(_)
+                | FrameExit::Emit { .. }
+                | FrameExit::Sleep { .. }
+                | FrameExit::Paused
+                | FrameExit::Breakpoint)
         ) {
             Some(self.snapshot())
         } else {
@@ -624,9 +891,108 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
             instruction_ip: self.instruction_ip,
             next_call_id: self.next_call_id,
             scheduler: self.scheduler,
+            fuel: self.fuel,
+            debugger: self.debugger,
         }
     }
 
+    /// Sets the number of opcodes to execute before pausing with `FrameExit::Paused`.
+    ///
+    /// Pass `None` to remove the limit.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+        self.update_instrumented();
+    }
+
+    /// Attaches a profiler that counts and times every opcode executed from now on.
+    pub fn set_profiler(&mut self, profiler: &'a mut Profiler) {
+        self.profiler = Some(profiler);
+        self.update_instrumented();
+    }
+
+    /// Attaches a recorder that marks the line of every opcode executed from now on.
+    pub fn set_coverage(&mut self, coverage: &'a mut LineCoverage) {
+        self.coverage = Some(coverage);
+        self.update_instrumented();
+    }
+
+    /// Recomputes `instrumented` after fuel, the debugger, the profiler or coverage changed.
+    fn update_instrumented(&mut self) {
+        self.instrumented =
+            self.fuel.is_some() || self.debugger.is_some() || self.profiler.is_some() || self.coverage.is_some();
+    }
+
+    /// Does the per-instruction work for fuel, breakpoints, profiling and coverage before
+    /// the instruction at `ip` of `code` runs. Only called while `instrumented` is set.
+    ///
+    /// Returns the exit to stop with instead of running the instruction, if any.
+    #[inline(never)]
+    fn instrument_step(&mut self, code: &'a Code, ip: usize) -> Option<FrameExit> {
+        // Pause at the instruction boundary once the fuel budget is spent
+        if let Some(fuel) = self.fuel {
+            if fuel == 0 {
+                return Some(FrameExit::Paused);
+            }
+            self.fuel = Some(fuel - 1);
+        }
+
+        // Everything else works per line; instructions before the first location entry
+        // have no line to stop at or charge
+        if self.debugger.is_none() && self.profiler.is_none() && self.coverage.is_none() {
+            return None;
+        }
+        let (line, line_changed) = self.locate_line(code, ip)?;
+
+        // Stop before the instruction if it starts a line with a breakpoint
+        if self.hit_breakpoint(line) {
+            return Some(FrameExit::Breakpoint);
+        }
+
+        // Count and time the opcode when profiling, and mark its line when measuring coverage.
+        // Both only hear about lines when they change.
+        if line_changed {
+            if let Some(profiler) = &mut self.profiler {
+                let function_id = self.frames.last().expect("no active frame").function_id;
+                profiler.enter_line(function_id, line);
+            }
+            if let Some(coverage) = &mut self.coverage {
+                coverage.record(line);
+            }
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.record_opcode();
+        }
+        None
+    }
+
+    /// Returns the line of the instruction at `ip` of `code`, and whether it differs from
+    /// the line of the previous instruction located here.
+    ///
+    /// The location is only resolved once execution leaves the span of the previous
+    /// instruction. Returns `None` for instructions before the first location entry.
+    fn locate_line(&mut self, code: &'a Code, ip: usize) -> Option<(u16, bool)> {
+        if let Some(span) = self.line_span
+            && span.contains(code, ip)
+        {
+            return Some((span.line, false));
+        }
+        let Some((location, offsets)) = code.location_span_for_offset(ip) else {
+            self.line_span = None;
+            return None;
+        };
+        let line = location.range().start().line;
+        let line_changed = self
+            .line_span
+            .is_none_or(|span| !std::ptr::eq(span.code, code) || span.line != line);
+        self.line_span = Some(LineSpan {
+            code,
+            start: offsets.start,
+            end: offsets.end,
+            line,
+        });
+        Some((line, line_changed))
+    }
+
     /// Pushes an initial frame for module-level code and runs the VM.
     pub fn run_module(&mut self, code: &'a Code) -> Result<FrameExit, RunError> {
         // Store module code for restoring main task frames during task switching
@@ -738,6 +1104,21 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
             // Track instruction IP for exception table lookup
             self.instruction_ip = cached_frame.ip;
 
+            // Fuel, breakpoints, profiling and coverage share one flag, so a plain run pays a
+            // single branch per instruction for all of them. An exit syncs the cached IP so
+            // the snapshot resumes at exactly this instruction.
+            if self.instrumented
+                && let Some(exit) = self.instrument_step(cached_frame.code, cached_frame.ip)
+            {
+                self.current_frame_mut().ip = cached_frame.ip;
+                return Ok(exit);
+            }
+
+            // Report new lines to the trace hook, if any. Compiles away for trackers without one.
+            if self.is_tracing() {
+                self.trace_line();
+            }
+
             // Fetch opcode using cached values (no frame access)
             let opcode = {
                 let byte = cached_frame.code.bytecode()[cached_frame.ip];
@@ -751,6 +1132,7 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                 // ============================================================
                 Opcode::Pop => {
                     let value = self.pop();
+                    self.warn_unawaited_coroutine(&value);
                     value.drop_with_heap(self.heap);
                 }
                 Opcode::Dup => {
@@ -816,17 +1198,33 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     let slot = fetch_u16!(cached_frame);
                     try_catch_sync!(self, cached_frame, self.load_local(&cached_frame, slot));
                 }
+                Opcode::LoadLocalPair => {
+                    let slots = fetch_u8!(cached_frame);
+                    let (first, second) = (u16::from(slots >> 4), u16::from(slots & 0x0F));
+                    match self.load_local(&cached_frame, first) {
+                        Ok(()) => {
+                            // the second load's location is recorded one byte into the instruction
+                            self.instruction_ip += 1;
+                            try_catch_sync!(self, cached_frame, self.load_local(&cached_frame, second));
+                        }
+                        Err(e) => catch_sync!(self, cached_frame, e),
+                    }
+                }
                 Opcode::StoreLocal => {
                     let slot = u16::from(fetch_u8!(cached_frame));
-                    self.store_local(&cached_frame, slot);
+                    try_catch_sync!(self, cached_frame, self.store_local(&cached_frame, slot));
                 }
                 Opcode::StoreLocalW => {
                     let slot = fetch_u16!(cached_frame);
-                    self.store_local(&cached_frame, slot);
+                    try_catch_sync!(self, cached_frame, self.store_local(&cached_frame, slot));
                 }
                 Opcode::DeleteLocal => {
                     let slot = u16::from(fetch_u8!(cached_frame));
-                    self.delete_local(&cached_frame, slot);
+                    try_catch_sync!(self, cached_frame, self.delete_local(&cached_frame, slot));
+                }
+                Opcode::DeleteLocalW => {
+                    let slot = fetch_u16!(cached_frame);
+                    try_catch_sync!(self, cached_frame, self.delete_local(&cached_frame, slot));
                 }
                 // Variables - Global Operations
                 Opcode::LoadGlobal => {
@@ -835,7 +1233,7 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                 }
                 Opcode::StoreGlobal => {
                     let slot = fetch_u16!(cached_frame);
-                    self.store_global(slot);
+                    try_catch_sync!(self, cached_frame, self.store_global(slot));
                 }
                 // Variables - Cell Operations (closures)
                 Opcode::LoadCell => {
@@ -980,7 +1378,7 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     }
                 }
                 // In-place Operations - route through exception handling
-                Opcode::InplaceAdd => try_catch_sync!(self, cached_frame, self.inplace_add()),
+                Opcode::InplaceAdd => try_catch_sync!(self, cached_frame, self.inplace_add(&cached_frame)),
                 // Other in-place ops use the same logic as binary ops for now
                 Opcode::InplaceSub => try_catch_sync!(self, cached_frame, self.binary_sub()),
                 Opcode::InplaceMul => try_catch_sync!(self, cached_frame, self.binary_mult()),
@@ -1018,6 +1416,18 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     let count = fetch_u16!(cached_frame) as usize;
                     try_catch_sync!(self, cached_frame, self.build_set(count));
                 }
+                Opcode::BuildListSized => {
+                    let capacity = fetch_u16!(cached_frame) as usize;
+                    try_catch_sync!(self, cached_frame, self.build_list_sized(capacity));
+                }
+                Opcode::BuildDictSized => {
+                    let capacity = fetch_u16!(cached_frame) as usize;
+                    try_catch_sync!(self, cached_frame, self.build_dict_sized(capacity));
+                }
+                Opcode::BuildSetSized => {
+                    let capacity = fetch_u16!(cached_frame) as usize;
+                    try_catch_sync!(self, cached_frame, self.build_set_sized(capacity));
+                }
                 Opcode::FormatValue => {
                     let flags = fetch_u8!(cached_frame);
                     try_catch_sync!(self, cached_frame, self.format_value(flags));
@@ -1052,6 +1462,10 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     let depth = fetch_u8!(cached_frame) as usize;
                     try_catch_sync!(self, cached_frame, self.dict_set_item(depth));
                 }
+                Opcode::SumAdd => {
+                    let depth = fetch_u8!(cached_frame) as usize;
+                    try_catch_sync!(self, cached_frame, self.sum_add(depth));
+                }
                 // Subscript & Attribute - route through exception handling
                 Opcode::BinarySubscr => {
                     let index = self.pop();
@@ -1077,8 +1491,9 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                 }
                 Opcode::LoadAttr => {
                     let name_idx = fetch_u16!(cached_frame);
+                    let cache_slot = fetch_u16!(cached_frame);
                     let name_id = StringId::from_index(name_idx);
-                    handle_call_result!(self, cached_frame, self.load_attr(name_id));
+                    handle_call_result!(self, cached_frame, self.load_attr(name_id, cache_slot));
                 }
                 Opcode::LoadAttrImport => {
                     let name_idx = fetch_u16!(cached_frame);
@@ -1129,6 +1544,15 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                         jump_relative!(cached_frame.ip, offset);
                     }
                 }
+                Opcode::CompareJumpIfFalse => {
+                    let compare = Opcode::try_from(fetch_u8!(cached_frame)).expect("invalid comparison opcode");
+                    let offset = fetch_i16!(cached_frame);
+                    match self.compare_to_bool(compare) {
+                        Ok(true) => {}
+                        Ok(false) => jump_relative!(cached_frame.ip, offset),
+                        Err(e) => catch_sync!(self, cached_frame, e),
+                    }
+                }
                 // Iteration - route through exception handling
                 Opcode::GetIter => {
                     let value = self.pop();
@@ -1143,27 +1567,30 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                 }
                 Opcode::ForIter => {
                     let offset = fetch_i16!(cached_frame);
-                    // Peek at the iterator on TOS and extract heap_id
-                    let Value::Ref(heap_id) = *self.peek() else {
-                        return Err(RunError::internal("ForIter: expected iterator ref on stack"));
-                    };
-
-                    // Use advance_iterator which avoids std::mem::replace overhead
-                    // by using a two-phase approach: read state, get value, update index
-                    match advance_on_heap(self.heap, heap_id, self.interns) {
-                        Ok(Some(value)) => self.push(value),
-                        Ok(None) => {
-                            // Iterator exhausted - pop it and jump to end
-                            let iter = self.pop();
-                            iter.drop_with_heap(self.heap);
-                            jump_relative!(cached_frame.ip, offset);
+                    match self.for_iter_next() {
+                        Ok(ForLoopStep::Item(value)) => self.push(value),
+                        Ok(ForLoopStep::Exhausted) => jump_relative!(cached_frame.ip, offset),
+                        Ok(ForLoopStep::WaitOnHost(ext_function_id)) => {
+                            return Ok(self.wait_on_host_iterable(ext_function_id));
                         }
-                        Err(e) => {
-                            // Error during iteration (e.g., dict size changed)
-                            let iter = self.pop();
-                            iter.drop_with_heap(self.heap);
-                            catch_sync!(self, cached_frame, e);
+                        Err(e) => catch_sync!(self, cached_frame, e),
+                    }
+                }
+                Opcode::ForIterStoreLocal => {
+                    let slot = u16::from(fetch_u8!(cached_frame));
+                    let offset = fetch_i16!(cached_frame);
+                    match self.for_iter_next() {
+                        Ok(ForLoopStep::Item(value)) => {
+                            self.push(value);
+                            // the store's location is recorded one byte into the instruction
+                            self.instruction_ip += 1;
+                            try_catch_sync!(self, cached_frame, self.store_local(&cached_frame, slot));
+                        }
+                        Ok(ForLoopStep::Exhausted) => jump_relative!(cached_frame.ip, offset),
+                        Ok(ForLoopStep::WaitOnHost(ext_function_id)) => {
+                            return Ok(self.wait_on_host_iterable(ext_function_id));
                         }
+                        Err(e) => catch_sync!(self, cached_frame, e),
                     }
                 }
                 // Function Calls - sync IP before call, reload cache after frame changes
@@ -1180,11 +1607,12 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     let builtin_id = fetch_u8!(cached_frame);
                     let arg_count = fetch_u8!(cached_frame) as usize;
 
-                    match self.exec_call_builtin_function(builtin_id, arg_count) {
-                        Ok(result) => self.push(result),
-                        // IP sync deferred to error path (no frame push possible)
-                        Err(err) => catch_sync!(self, cached_frame, err),
-                    }
+                    // IP sync deferred to the error and `emit()` paths (no frame push possible)
+                    handle_call_result!(
+                        self,
+                        cached_frame,
+                        self.exec_call_builtin_function(builtin_id, arg_count)
+                    );
                 }
                 Opcode::CallBuiltinType => {
                     // Fetch operands: type_id (u8) + arg_count (u8)
@@ -1214,16 +1642,17 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     handle_call_result!(self, cached_frame, self.exec_call_function_kw(pos_count, kwname_ids));
                 }
                 Opcode::CallAttr => {
-                    // CallAttr: u16 name_id, u8 arg_count
+                    // CallAttr: u16 name_id, u8 arg_count, u16 cache_slot
                     // Stack: [obj, arg1, arg2, ..., argN] -> [result]
                     let name_idx = fetch_u16!(cached_frame);
                     let arg_count = fetch_u8!(cached_frame) as usize;
+                    let cache_slot = fetch_u16!(cached_frame);
                     let name_id = StringId::from_index(name_idx);
 
                     // Sync IP before call (may yield to host for OS/external calls)
                     self.current_frame_mut().ip = cached_frame.ip;
 
-                    handle_call_result!(self, cached_frame, self.exec_call_attr(name_id, arg_count));
+                    handle_call_result!(self, cached_frame, self.exec_call_attr(name_id, arg_count, cache_slot));
                 }
                 Opcode::CallAttrKw => {
                     // CallAttrKw: u16 name_id, u8 pos_count, u8 kw_count, then kw_count u16 name indices
@@ -1331,11 +1760,17 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
                     let error = self.make_exception(exc, true); // is_raise=true, hide caret
                     catch_sync!(self, cached_frame, error);
                 }
+                Opcode::RaiseFrom => {
+                    let cause = self.pop();
+                    let exc = self.pop();
+                    let error = self.make_exception_from(exc, cause);
+                    catch_sync!(self, cached_frame, error);
+                }
                 Opcode::Reraise => {
                     // Pop the current exception from the stack to re-raise it
                     // If caught, handle_exception will push it back
                     let error = if let Some(exc) = self.exception_stack.pop() {
-                        self.make_exception(exc, true) // is_raise=true for reraise
+                        self.make_reraise(exc)
                     } else {
                         // No active exception - create a RuntimeError
                         SimpleException::new_msg(ExcType::RuntimeError, "No active exception to reraise").into()
@@ -1542,7 +1977,20 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
     ///
     /// Cleans up the frame's stack region and namespace (except for global namespace).
     pub(super) fn pop_frame(&mut self) {
+        let tracing = self.is_tracing();
+        if tracing {
+            self.trace_return(self.frames.len() - 1);
+        }
         let frame = self.frames.pop().expect("no frame to pop");
+        if tracing {
+            self.trace_resume_caller();
+        }
+        if self.debugger.is_some() {
+            self.debug_resume_caller();
+        }
+        if frame.function_id.is_some() {
+            self.heap.tracker_mut().on_span_exit(SpanKind::FunctionCall);
+        }
         // Clean up frame's stack region
         while self.stack.len() > frame.stack_base {
             let value = self.stack.pop().unwrap();
@@ -1559,7 +2007,16 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
     /// Used when a task completes or fails and we need to switch to another task.
     /// Properly cleans up each frame's namespace and cell references.
     pub(super) fn cleanup_current_frames(&mut self) {
+        if self.is_tracing() {
+            for index in (0..self.frames.len()).rev() {
+                self.trace_return(index);
+            }
+            self.trace_line = None;
+        }
         for frame in self.frames.drain(..) {
+            if frame.function_id.is_some() {
+                self.heap.tracker_mut().on_span_exit(SpanKind::FunctionCall);
+            }
             // Clean up cell references
             for cell_id in frame.cells {
                 self.heap.dec_ref(cell_id);
@@ -1575,6 +2032,8 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
     ///
     /// GC roots include values in namespaces, the operand stack, and exception stack.
     fn run_gc(&mut self) {
+        self.heap.tracker_mut().on_span_enter(SpanKind::GarbageCollection, "gc");
+
         // Collect roots from all reachable values
         let stack_roots = self.stack.iter().filter_map(Value::ref_id);
         let exc_roots = self.exception_stack.iter().filter_map(Value::ref_id);
@@ -1584,14 +2043,33 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
         let roots: Vec<HeapId> = stack_roots.chain(exc_roots).chain(ns_roots).collect();
 
         self.heap.collect_garbage(roots);
+
+        self.heap.tracker_mut().on_span_exit(SpanKind::GarbageCollection);
+    }
+
+    /// Pushes a new frame for a function call and reports the start of its timeline span.
+    pub(super) fn push_function_frame(&mut self, frame: CallFrame<'a>) {
+        if self.is_tracing() && frame.function_id.is_some() {
+            self.trace_call(&frame);
+        }
+        if let Some(func_id) = frame.function_id {
+            let interns = self.interns;
+            let name = interns.get_str(interns.get_function(func_id).name.name_id);
+            self.heap.tracker_mut().on_span_enter(SpanKind::FunctionCall, name);
+        }
+        self.frames.push(frame);
     }
 
     /// Returns the current source position for traceback generation.
     ///
     /// Uses `instruction_ip` which is set at the start of each instruction in the run loop,
     /// ensuring accurate position tracking even when using cached IP for bytecode fetching.
+    /// Returns a default range when no frame is active, i.e. when the host calls a function
+    /// directly with `run_function()`.
     pub(super) fn current_position(&self) -> CodeRange {
-        let frame = self.current_frame();
+        let Some(frame) = self.frames.last() else {
+            return CodeRange::default();
+        };
         // Use instruction_ip which points to the start of the current instruction
         // (set at the beginning of each loop iteration in run())
         frame
@@ -1663,21 +2141,63 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
         ExcType::name_error(&name_str).into()
     }
 
+    /// Returns a TypeError if global `slot` has been frozen by `MontyRepl::freeze_globals`.
+    fn check_global_writable(&self, slot: u16) -> RunResult<()> {
+        match self.namespaces.frozen_global_name(NamespaceId::new(slot as usize)) {
+            Some(name) => Err(ExcType::frozen_global(name).into()),
+            None => Ok(()),
+        }
+    }
+
     /// Pops the top of stack and stores it in a local variable.
-    fn store_local(&mut self, cached_frame: &CachedFrame<'a>, slot: u16) {
+    ///
+    /// At module level locals are globals, so frozen global slots are rejected.
+    fn store_local(&mut self, cached_frame: &CachedFrame<'a>, slot: u16) -> RunResult<()> {
         let value = self.pop();
+        if cached_frame.namespace_idx == GLOBAL_NS_IDX
+            && let Err(err) = self.check_global_writable(slot)
+        {
+            value.drop_with_heap(self.heap);
+            return Err(err);
+        }
         let namespace = self.namespaces.get_mut(cached_frame.namespace_idx);
         let ns_slot = NamespaceId::new(slot as usize);
         let old_value = std::mem::replace(namespace.get_mut(ns_slot), value);
         old_value.drop_with_heap(self.heap);
+        Ok(())
+    }
+
+    /// Advances the iterator on top of the stack for `ForIter` and `ForIterStoreLocal`.
+    ///
+    /// The iterator is popped when it is exhausted or raises (e.g., dict size changed), so
+    /// the caller only has to jump or handle the exception. It stays on the stack while the
+    /// loop waits on the host for the items of a host iterable.
+    fn for_iter_next(&mut self) -> RunResult<ForLoopStep> {
+        // Peek at the iterator on TOS and extract heap_id
+        let Value::Ref(heap_id) = *self.peek() else {
+            return Err(RunError::internal("ForIter: expected iterator ref on stack"));
+        };
+
+        // Use advance_iterator which avoids std::mem::replace overhead
+        // by using a two-phase approach: read state, get value, update index
+        let result = advance_for_loop(self.heap, heap_id, self.interns);
+        if matches!(result, Ok(ForLoopStep::Exhausted) | Err(_)) {
+            let iter = self.pop();
+            iter.drop_with_heap(self.heap);
+        }
+        result
     }
 
     /// Deletes a local variable (sets it to Undefined).
-    fn delete_local(&mut self, cached_frame: &CachedFrame<'a>, slot: u16) {
+    fn delete_local(&mut self, cached_frame: &CachedFrame<'a>, slot: u16) -> RunResult<()> {
+        if cached_frame.namespace_idx == GLOBAL_NS_IDX {
+            self.check_global_writable(slot)?;
+        }
         let namespace = self.namespaces.get_mut(cached_frame.namespace_idx);
         let ns_slot = NamespaceId::new(slot as usize);
         let old_value = std::mem::replace(namespace.get_mut(ns_slot), Value::Undefined);
         old_value.drop_with_heap(self.heap);
+        Ok(())
     }
 
     /// Loads a global variable and pushes it onto the stack.
@@ -1702,12 +2222,17 @@ impl<'a, 'p, T: ResourceTracker> VM<'a, 'p, T> {
     }
 
     /// Pops the top of stack and stores it in a global variable.
-    fn store_global(&mut self, slot: u16) {
+    fn store_global(&mut self, slot: u16) -> RunResult<()> {
         let value = self.pop();
+        if let Err(err) = self.check_global_writable(slot) {
+            value.drop_with_heap(self.heap);
+            return Err(err);
+        }
         let namespace = self.namespaces.get_mut(GLOBAL_NS_IDX);
         let ns_slot = NamespaceId::new(slot as usize);
         let old_value = std::mem::replace(namespace.get_mut(ns_slot), value);
         old_value.drop_with_heap(self.heap);
+        Ok(())
     }
 
     /// Loads from a closure cell and pushes onto the stack.
@@ -18,7 +18,8 @@ use crate::{
     args::ArgValues,
     asyncio::{CallId, TaskId},
     exception_private::RunError,
-    heap::{DropWithHeap, HeapId},
+    heap::{DropWithHeap, HeapId, HeapIdMap},
+    intern::ExtFunctionId,
     namespace::{GLOBAL_NS_IDX, NamespaceId, Namespaces},
     parse::CodeRange,
     value::Value,
@@ -142,9 +143,12 @@ impl Task {
 /// along with tracking information for the task that created it.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub(crate) struct PendingCallData {
-    /// The external function being called.
-    // pub ext_function_id: ExtFunctionId,
+    /// The batched external function being called, while the call waits to be handed to the
+    /// host. `None` once it has been, and for calls the host deferred with `run_pending()`.
+    pub batched: Option<ExtFunctionId>,
     /// Arguments for the function (includes both positional and keyword args).
+    ///
+    /// Only batched calls hold arguments, until they are handed to the host.
     pub args: ArgValues,
     /// Task that created this call (for ignoring results if task is cancelled).
     pub creator_task: TaskId,
@@ -177,14 +181,18 @@ pub(crate) struct Scheduler {
     next_call_id: u32,
     /// Maps CallId -> pending call data for unresolved external calls.
     /// Populated when host calls `run_pending()`.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
     pending_calls: AHashMap<CallId, PendingCallData>,
     /// Maps CallId -> resolved Value for futures that have been resolved.
     /// Entry is removed when the value is consumed by awaiting.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
     resolved: AHashMap<CallId, Value>,
     /// CallIds that have been awaited (to detect double-await).
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_set")]
     consumed: AHashSet<CallId>,
     /// Maps CallId -> (gather_heap_id, result_index) for gathers waiting on external futures.
     /// When a CallId is resolved, the result is stored in the gather's results at the given index.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
     gather_waiters: AHashMap<CallId, (HeapId, usize)>,
 }
 
@@ -225,6 +233,12 @@ impl Scheduler {
         self.tasks.len()
     }
 
+    /// Returns all tasks, the main task first.
+    #[inline]
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
     /// Returns a reference to a task by ID.
     ///
     /// # Panics
@@ -369,6 +383,23 @@ impl Scheduler {
         }
     }
 
+    /// Takes the batched calls that haven't been handed to the host yet, in the order they were made.
+    ///
+    /// The calls stay pending until the host resolves them.
+    pub fn take_batched_calls(&mut self) -> Vec<(CallId, ExtFunctionId, ArgValues)> {
+        let mut calls: Vec<_> = self
+            .pending_calls
+            .iter_mut()
+            .filter_map(|(&call_id, data)| {
+                let ext_function_id = data.batched.take()?;
+                let args = std::mem::replace(&mut data.args, ArgValues::Empty);
+                Some((call_id, ext_function_id, args))
+            })
+            .collect();
+        calls.sort_by_key(|(call_id, _, _)| *call_id);
+        calls
+    }
+
     /// Returns all pending (unresolved) CallIds.
     pub fn pending_call_ids(&self) -> Vec<CallId> {
         self.pending_calls.keys().copied().collect()
@@ -620,6 +651,35 @@ impl Scheduler {
             }
         }
     }
+
+    /// Rewrites every heap id held by the scheduler after heap compaction.
+    pub fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        for data in self.pending_calls.values_mut() {
+            data.args.remap_heap_ids(map);
+        }
+        map.remap_values(self.resolved.values_mut());
+        for (gather_id, _) in self.gather_waiters.values_mut() {
+            map.remap(gather_id);
+        }
+        for task in &mut self.tasks {
+            for frame in &mut task.frames {
+                frame.cells.iter_mut().for_each(|cell_id| map.remap(cell_id));
+            }
+            map.remap_values(&mut task.stack);
+            map.remap_values(&mut task.exception_stack);
+            if let Some(coroutine_id) = &mut task.coroutine_id {
+                map.remap(coroutine_id);
+            }
+            if let Some(gather_id) = &mut task.gather_id {
+                map.remap(gather_id);
+            }
+            match &mut task.state {
+                TaskState::BlockedOnGather(gather_id) => map.remap(gather_id),
+                TaskState::Completed(value) => map.remap_value(value),
+                TaskState::Ready | TaskState::BlockedOnCall(_) | TaskState::Failed(_) => {}
+            }
+        }
+    }
 }
 
 impl Default for Scheduler {
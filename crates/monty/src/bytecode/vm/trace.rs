@@ -0,0 +1,130 @@
+//! Reporting trace events to the resource tracker's `TraceHook`.
+//!
+//! Every helper here is only called after checking that `trace_hook()` returns a hook, so
+//! untraced runs never look up source positions.
+
+use super::{CallFrame, VM};
+use crate::{
+    bytecode::code::{Code, LocationEntry},
+    exception_private::RunError,
+    intern::{FunctionId, StaticStrings},
+    resource::ResourceTracker,
+    trace::{TraceHook, TracePosition},
+};
+
+impl<'a, T: ResourceTracker> VM<'a, '_, T> {
+    /// Returns whether the tracker has a trace hook.
+    #[inline]
+    pub(super) fn is_tracing(&mut self) -> bool {
+        self.heap.tracker_mut().trace_hook().is_some()
+    }
+
+    /// Reports a line event if the instruction at `instruction_ip` starts a new line in the
+    /// current frame, or jumps back to an earlier instruction of the same line.
+    pub(super) fn trace_line(&mut self) {
+        let depth = self.frames.len();
+        let ip = self.instruction_ip;
+        let frame = self.current_frame();
+        let (code, function_id) = (frame.code, frame.function_id);
+        let Some(line) = line_at(code, ip) else {
+            return;
+        };
+        let same_line = matches!(
+            self.trace_line,
+            Some((last_depth, last_ip, last_line)) if last_depth == depth && last_line == line && ip >= last_ip
+        );
+        self.trace_line = Some((depth, ip, line));
+        if !same_line {
+            self.emit_trace(code, ip, function_id, depth, |hook, position| hook.on_line(position));
+        }
+    }
+
+    /// Reports a call event for a function frame about to be pushed.
+    pub(super) fn trace_call(&mut self, frame: &CallFrame<'a>) {
+        let depth = self.frames.len() + 1;
+        self.emit_trace(frame.code, frame.ip, frame.function_id, depth, |hook, position| {
+            hook.on_call(position);
+        });
+    }
+
+    /// Reports a return event for the function frame at `index`, which is about to be popped.
+    ///
+    /// The position is the last line reported for the frame, or the call the frame is
+    /// suspended in when it's being unwound from below. Module frames are skipped.
+    pub(super) fn trace_return(&mut self, index: usize) {
+        let depth = index + 1;
+        let frame = &self.frames[index];
+        let (code, function_id) = (frame.code, frame.function_id);
+        if function_id.is_none() {
+            return;
+        }
+        let ip = match self.trace_line {
+            Some((last_depth, last_ip, _)) if last_depth == depth => last_ip,
+            _ => frame.ip.saturating_sub(1),
+        };
+        self.emit_trace(code, ip, function_id, depth, |hook, position| hook.on_return(position));
+    }
+
+    /// Marks the current frame's line as already reported after returning into it, so the
+    /// rest of the calling line doesn't report a second line event.
+    pub(super) fn trace_resume_caller(&mut self) {
+        self.trace_line = self.caller_line();
+    }
+
+    /// Returns the frame depth, IP and line of the call the current frame is suspended in.
+    pub(super) fn caller_line(&self) -> Option<(usize, usize, u16)> {
+        self.frames.last().and_then(|frame| {
+            let ip = frame.ip.saturating_sub(1);
+            line_at(frame.code, ip).map(|line| (self.frames.len(), ip, line))
+        })
+    }
+
+    /// Reports an exception event for `error` at the current instruction.
+    pub(super) fn trace_exception(&mut self, error: &RunError) {
+        let (RunError::Exc(exc) | RunError::UncatchableExc(exc)) = error else {
+            return;
+        };
+        let exc_type = exc.exc.exc_type();
+        let depth = self.frames.len();
+        let frame = self.current_frame();
+        let (code, function_id) = (frame.code, frame.function_id);
+        self.emit_trace(code, self.instruction_ip, function_id, depth, |hook, position| {
+            hook.on_exception(position, exc_type, exc.exc.arg().map(String::as_str));
+        });
+    }
+
+    /// Builds the position of `offset` in `code` and passes it to the trace hook.
+    fn emit_trace(
+        &mut self,
+        code: &Code,
+        offset: usize,
+        function_id: Option<FunctionId>,
+        depth: usize,
+        event: impl FnOnce(&mut dyn TraceHook, &TracePosition<'_>),
+    ) {
+        let interns = self.interns;
+        let range = code
+            .location_for_offset(offset)
+            .map(LocationEntry::range)
+            .unwrap_or_default();
+        let function = match function_id {
+            Some(func_id) => interns.get_function(func_id).name.name_id,
+            None => StaticStrings::Module.into(),
+        };
+        let position = TracePosition {
+            filename: interns.get_str(range.filename),
+            function: interns.get_str(function),
+            line: range.start().line,
+            column: range.start().column,
+            depth,
+        };
+        if let Some(hook) = self.heap.tracker_mut().trace_hook() {
+            event(hook, &position);
+        }
+    }
+}
+
+/// Returns the source line of the instruction at `offset`.
+fn line_at(code: &Code, offset: usize) -> Option<u16> {
+    code.location_for_offset(offset).map(|entry| entry.range().start().line)
+}
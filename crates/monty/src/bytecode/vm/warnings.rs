@@ -0,0 +1,74 @@
+//! Reporting runtime warnings to the resource tracker's `WarningSink`.
+//!
+//! Checks here first ask whether the tracker has a sink, so runs without one never inspect
+//! operands or look up source positions.
+
+use super::VM;
+use crate::{
+    asyncio::CoroutineState,
+    heap::HeapData,
+    resource::ResourceTracker,
+    types::{PyTrait, Type},
+    value::Value,
+    warnings::{MontyWarning, WarningCategory},
+};
+
+impl<T: ResourceTracker> VM<'_, '_, T> {
+    /// Returns whether the tracker has a warning sink.
+    #[inline]
+    pub(super) fn has_warning_sink(&mut self) -> bool {
+        self.heap.tracker_mut().warning_sink().is_some()
+    }
+
+    /// Reports a warning at the current instruction.
+    pub(super) fn warn(&mut self, category: WarningCategory, message: impl Into<String>) {
+        let position = self.current_position();
+        let warning = MontyWarning {
+            category,
+            message: message.into(),
+            filename: self.interns.get_str(position.filename).to_owned(),
+            start: position.start(),
+        };
+        if let Some(sink) = self.heap.tracker_mut().warning_sink() {
+            sink.warn(warning);
+        }
+    }
+
+    /// Warns about comparing `bytes` with `str` for equality, which is always false.
+    pub(super) fn warn_bytes_str_comparison(&mut self, lhs: &Value, rhs: &Value) {
+        if !self.has_warning_sink() {
+            return;
+        }
+        if matches!(
+            (lhs.py_type(self.heap), rhs.py_type(self.heap)),
+            (Type::Bytes, Type::Str) | (Type::Str, Type::Bytes)
+        ) {
+            self.warn(WarningCategory::BytesWarning, "Comparison between bytes and string");
+        }
+    }
+
+    /// Warns about discarding the last reference to a coroutine that never started, e.g.
+    /// calling an `async def` function without awaiting the result.
+    pub(super) fn warn_unawaited_coroutine(&mut self, value: &Value) {
+        let Value::Ref(id) = value else {
+            return;
+        };
+        if !self.has_warning_sink() || !self.heap.is_last_ref(*id) {
+            return;
+        }
+        if let HeapData::Coroutine(coroutine) = self.heap.get(*id)
+            && coroutine.state == CoroutineState::New
+        {
+            let name_id = self.interns.get_function(coroutine.func_id).name.name_id;
+            let message = format!("coroutine '{}' was never awaited", self.interns.get_str(name_id));
+            self.warn(WarningCategory::RuntimeWarning, message);
+        }
+    }
+
+    /// Warns about converting `bytes` to `str`, which gives its repr rather than decoding it.
+    pub(super) fn warn_bytes_to_str(&mut self, value: &Value) {
+        if self.has_warning_sink() && value.py_type(self.heap) == Type::Bytes {
+            self.warn(WarningCategory::BytesWarning, "str() on a bytes instance");
+        }
+    }
+}
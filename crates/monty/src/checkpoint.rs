@@ -0,0 +1,201 @@
+//! Automatic periodic checkpoints of long-running runs.
+//!
+//! A [`Checkpointer`] runs a script in fuel slices (see `MontyRun::start_fuel`) and,
+//! whenever its [`CheckpointPolicy`] says a checkpoint is due, dumps the paused run with
+//! `RunProgress::dump()` and hands the bytes to a host callback. After a crash the host
+//! restores the last checkpoint with `RunProgress::load()` and passes it to
+//! [`Checkpointer::run`] to carry on from there.
+//!
+//! Checkpoints are only taken between fuel slices, never while the run is waiting on the
+//! host; the host already holds the state at those points.
+
+use std::{fmt, time::Duration};
+
+use crate::{
+    clock::Instant,
+    exception_public::MontyException,
+    io::PrintWriter,
+    object::MontyObject,
+    resource::ResourceTracker,
+    run::{MontyRun, RunProgress},
+};
+
+/// Opcodes run between clock checks when checkpointing by time.
+const TIME_CHECK_OPCODES: u64 = 10_000;
+
+/// When a [`Checkpointer`] takes checkpoints.
+///
+/// A checkpoint is taken as soon as either configured interval has passed. With neither
+/// set, no checkpoints are taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CheckpointPolicy {
+    every_opcodes: Option<u64>,
+    every: Option<Duration>,
+}
+
+impl CheckpointPolicy {
+    /// Creates a policy that takes no checkpoints.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a checkpoint every `opcodes` executed opcodes.
+    #[must_use]
+    pub fn every_opcodes(mut self, opcodes: u64) -> Self {
+        self.every_opcodes = Some(opcodes.max(1));
+        self
+    }
+
+    /// Takes a checkpoint once `interval` has passed since the last one.
+    ///
+    /// The clock is checked every 10,000 opcodes, so checkpoints can come slightly later
+    /// than `interval`.
+    #[must_use]
+    pub fn every(mut self, interval: Duration) -> Self {
+        self.every = Some(interval);
+        self
+    }
+
+    /// Returns the fuel budget for each slice the run is split into.
+    fn slice(&self) -> u64 {
+        match (self.every_opcodes, self.every) {
+            (Some(opcodes), None) => opcodes,
+            (Some(opcodes), Some(_)) => opcodes.min(TIME_CHECK_OPCODES),
+            (None, _) => TIME_CHECK_OPCODES,
+        }
+    }
+}
+
+/// Error returned by [`Checkpointer`].
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The run raised an exception.
+    Exception(MontyException),
+    /// The paused run could not be serialized.
+    Dump(postcard::Error),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exception(exc) => write!(f, "{exc}"),
+            Self::Dump(err) => write!(f, "failed to dump checkpoint: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Exception(exc) => Some(exc),
+            Self::Dump(err) => Some(err),
+        }
+    }
+}
+
+impl From<MontyException> for CheckpointError {
+    fn from(exc: MontyException) -> Self {
+        Self::Exception(exc)
+    }
+}
+
+impl From<postcard::Error> for CheckpointError {
+    fn from(err: postcard::Error) -> Self {
+        Self::Dump(err)
+    }
+}
+
+/// Runs scripts in fuel slices, passing a `RunProgress::dump()` of the paused run to
+/// `callback` whenever the policy says a checkpoint is due.
+///
+/// Start a run with [`Checkpointer::start`]. External calls, `emit()` and pending futures
+/// are returned to the host as usual; pass the progress from resuming them back through
+/// [`Checkpointer::run`] so checkpointing continues.
+#[derive(Debug)]
+pub struct Checkpointer<F: FnMut(&[u8])> {
+    policy: CheckpointPolicy,
+    callback: F,
+    /// Opcodes run since the last checkpoint.
+    opcodes: u64,
+    /// When the last checkpoint was taken, or the checkpointer was created.
+    last: Instant,
+    /// Number of checkpoints taken.
+    checkpoints: usize,
+}
+
+impl<F: FnMut(&[u8])> Checkpointer<F> {
+    /// Creates a checkpointer that passes each checkpoint to `callback`.
+    #[must_use]
+    pub fn new(policy: CheckpointPolicy, callback: F) -> Self {
+        Self {
+            policy,
+            callback,
+            opcodes: 0,
+            last: Instant::now(),
+            checkpoints: 0,
+        }
+    }
+
+    /// Returns the number of checkpoints taken so far.
+    #[must_use]
+    pub fn checkpoints(&self) -> usize {
+        self.checkpoints
+    }
+
+    /// Starts `runner` like `MontyRun::start()`, taking checkpoints as it goes.
+    ///
+    /// # Errors
+    /// Returns `CheckpointError::Exception` if the run raises, or `CheckpointError::Dump`
+    /// if a checkpoint can't be serialized.
+    pub fn start<T: ResourceTracker + serde::Serialize>(
+        &mut self,
+        runner: MontyRun,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, CheckpointError> {
+        let progress = runner.start_fuel(inputs, resource_tracker, self.policy.slice(), print)?;
+        self.run(progress, print)
+    }
+
+    /// Continues `progress`, taking checkpoints as it goes.
+    ///
+    /// Returns at the first progress that needs the host: an external call, OS call,
+    /// `emit()`, pending futures, or completion. `progress` can also be a checkpoint
+    /// restored with `RunProgress::load()`.
+    ///
+    /// # Errors
+    /// Same as [`Checkpointer::start`].
+    pub fn run<T: ResourceTracker + serde::Serialize>(
+        &mut self,
+        mut progress: RunProgress<T>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, CheckpointError> {
+        let slice = self.policy.slice();
+        loop {
+            let mut state = match progress {
+                RunProgress::Paused(state) => state,
+                other => return Ok(other),
+            };
+            self.opcodes += slice;
+            if self.is_due() {
+                let paused = RunProgress::Paused(state);
+                (self.callback)(&paused.dump()?);
+                self.checkpoints += 1;
+                self.opcodes = 0;
+                self.last = Instant::now();
+                state = paused.into_paused().expect("checkpointed progress is Paused");
+            }
+            progress = state.run_fuel(slice, print)?;
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        self.policy.every_opcodes.is_some_and(|opcodes| self.opcodes >= opcodes)
+            || self
+                .policy
+                .every
+                .is_some_and(|interval| self.last.elapsed() >= interval)
+    }
+}
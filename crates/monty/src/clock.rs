@@ -0,0 +1,175 @@
+//! Clock types used for time limits, deadlines, profiling and timelines, and the host
+//! clock read by the `time` module.
+//!
+//! These are the `std::time` types on every target with an OS clock, WASI included. On
+//! `wasm32-unknown-unknown` (browsers and edge runtimes) `std::time::Instant::now()` panics,
+//! so the [`web-time`](https://docs.rs/web-time) equivalents backed by `performance.now()`
+//! and `Date.now()` are used instead. Their API is identical, so hosts setting a deadline
+//! should use [`Instant`] from here rather than naming `std::time::Instant` directly.
+//!
+//! Scripts read time through a [`Clock`]: the VM asks its `ResourceTracker` for one through
+//! `clock()`, which returns [`SystemClock`] by default. Wrapping a tracker in
+//! [`ClockTracker`] substitutes the host's clock, e.g. a virtual one that jumps forward
+//! whenever the host resumes a script after `time.sleep()`, and [`FrozenClock`] stops time
+//! altogether for deterministic runs.
+
+use std::sync::OnceLock;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    resource::{CollectionKind, ResourceBudget, ResourceError, ResourceTracker},
+    timeline::SpanKind,
+    trace::TraceHook,
+    warnings::WarningSink,
+};
+
+/// The source of the times returned by `time.time()`, `time.monotonic()` and
+/// `time.perf_counter()`.
+///
+/// Called synchronously from the VM. Methods take `&self`; clocks the host advances
+/// while a script is suspended need interior mutability.
+pub trait Clock: std::fmt::Debug {
+    /// Seconds since the Unix epoch, returned by `time.time()`.
+    fn time(&self) -> f64;
+
+    /// Seconds since an arbitrary fixed point, returned by `time.monotonic()` and
+    /// `time.perf_counter()`. Must never go backwards.
+    fn monotonic(&self) -> f64;
+}
+
+/// The clock of the machine running Monty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn time(&self) -> f64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+    }
+
+    fn monotonic(&self) -> f64 {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_secs_f64()
+    }
+}
+
+/// A clock that never moves: every reading is `0.0`, for both `time.time()` and
+/// `time.monotonic()`.
+///
+/// Used by `DeterministicTracker`, so a script's results can't depend on when it runs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrozenClock;
+
+impl Clock for FrozenClock {
+    fn time(&self) -> f64 {
+        0.0
+    }
+
+    fn monotonic(&self) -> f64 {
+        0.0
+    }
+}
+
+/// A resource tracker that wraps another tracker and reads time from a host [`Clock`].
+///
+/// All limit checks are delegated to the inner tracker, so time limits still use the real
+/// clock. The clock is not serialized: a deserialized tracker starts with `C::default()`;
+/// use `set_clock` to reattach the host's clock after loading a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ClockTracker<T: ResourceTracker, C: Clock> {
+    inner: T,
+    #[serde(skip)]
+    clock: C,
+}
+
+impl<T: ResourceTracker, C: Clock> ClockTracker<T, C> {
+    /// Creates a tracker wrapping `inner` and reading time from `clock`.
+    #[must_use]
+    pub fn new(inner: T, clock: C) -> Self {
+        Self { inner, clock }
+    }
+
+    /// Returns a reference to the clock.
+    #[must_use]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns a mutable reference to the clock.
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Replaces the clock, e.g. after deserializing a snapshot.
+    pub fn set_clock(&mut self, clock: C) {
+        self.clock = clock;
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker, C: Clock> ResourceTracker for ClockTracker<T, C> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        self.inner.trace_hook()
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        self.inner.warning_sink()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &self.clock
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
+}
@@ -0,0 +1,255 @@
+//! Text codecs behind `str.encode()` and `bytes.decode()`.
+//!
+//! Supports the UTF-8, ASCII and Latin-1 codecs under the names CPython accepts for them, together
+//! with the `'strict'`, `'ignore'`, `'replace'`, `'backslashreplace'` and (encoding only)
+//! `'xmlcharrefreplace'` error handlers. As in CPython, the error handler name is only looked up
+//! once an error actually occurs, so `'abc'.encode('ascii', 'bogus')` succeeds.
+//!
+//! Error positions are code point indices when encoding and byte offsets when decoding, and the
+//! resulting `UnicodeEncodeError`/`UnicodeDecodeError` messages match CPython's exactly.
+
+use std::fmt::Write;
+
+use crate::{
+    exception_private::{ExcType, RunResult},
+    heap::Heap,
+    intern::Interns,
+    resource::ResourceTracker,
+    types::PyTrait,
+    value::Value,
+};
+
+/// A text encoding supported by `str.encode()` and `bytes.decode()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Codec {
+    Utf8,
+    Ascii,
+    Latin1,
+}
+
+impl Codec {
+    /// Looks up a codec by name, accepting the aliases CPython's `encodings` package accepts.
+    ///
+    /// Names are normalized like CPython's: case-insensitive, with runs of punctuation and
+    /// whitespace treated as a single underscore, so `'UTF-8'`, `'utf 8'` and `'utf_8'` all match.
+    pub fn lookup(name: &str) -> RunResult<Self> {
+        let mut normalized = String::with_capacity(name.len());
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                normalized.push(c.to_ascii_lowercase());
+            } else if !normalized.is_empty() && !normalized.ends_with('_') {
+                normalized.push('_');
+            }
+        }
+        let normalized = normalized.trim_end_matches('_');
+
+        match normalized {
+            "utf_8" | "utf8" | "u8" | "utf" | "cp65001" => Ok(Self::Utf8),
+            "ascii" | "us_ascii" | "us" | "646" | "cp367" | "csascii" | "ibm367" | "iso646_us" | "ansi_x3.4_1968"
+            | "ansi_x3_4_1968" | "ansi_x3.4_1986" => Ok(Self::Ascii),
+            "latin_1" | "latin1" | "latin" | "l1" | "iso8859_1" | "iso_8859_1" | "iso8859" | "8859" | "cp819"
+            | "ibm819" | "iso_ir_100" | "csisolatin1" => Ok(Self::Latin1),
+            _ => Err(ExcType::lookup_error_unknown_encoding(name)),
+        }
+    }
+
+    /// The codec's canonical name, as it appears in error messages.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Ascii => "ascii",
+            Self::Latin1 => "latin-1",
+        }
+    }
+
+    /// Encodes `s`, resolving characters the codec can't represent with the `errors` handler.
+    pub fn encode(self, s: &str, errors: &str) -> RunResult<Vec<u8>> {
+        let limit = match self {
+            // Rust strings never hold lone surrogates, so every character has a UTF-8 encoding
+            Self::Utf8 => return Ok(s.as_bytes().to_vec()),
+            Self::Ascii => 0x80,
+            Self::Latin1 => 0x100,
+        };
+
+        let mut out = Vec::with_capacity(s.len());
+        let mut chars = s.chars().enumerate().peekable();
+        while let Some((start, c)) = chars.next() {
+            if u32::from(c) < limit {
+                out.push(u8::try_from(u32::from(c)).expect("char below the codec limit fits in a byte"));
+                continue;
+            }
+            // like CPython, a run of unencodable characters is reported as a single error
+            let mut run = vec![c];
+            while let Some(&(_, next)) = chars.peek()
+                && u32::from(next) >= limit
+            {
+                run.push(next);
+                chars.next();
+            }
+
+            match ErrorHandler::lookup(errors)? {
+                ErrorHandler::Strict => {
+                    let reason = format!("ordinal not in range({limit})");
+                    return Err(ExcType::unicode_encode_error(
+                        self.name(),
+                        c,
+                        start,
+                        start + run.len(),
+                        &reason,
+                    ));
+                }
+                ErrorHandler::Ignore => {}
+                ErrorHandler::Replace => out.extend(std::iter::repeat_n(b'?', run.len())),
+                ErrorHandler::BackslashReplace => {
+                    for c in run {
+                        out.extend_from_slice(backslash_escape(u32::from(c)).as_bytes());
+                    }
+                }
+                ErrorHandler::XmlCharRefReplace => {
+                    for c in run {
+                        out.extend_from_slice(format!("&#{};", u32::from(c)).as_bytes());
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Decodes `bytes`, resolving invalid input with the `errors` handler.
+    pub fn decode(self, bytes: &[u8], errors: &str) -> RunResult<String> {
+        match self {
+            Self::Utf8 => decode_utf8(bytes, errors),
+            Self::Ascii => {
+                let mut out = String::with_capacity(bytes.len());
+                for (pos, &b) in bytes.iter().enumerate() {
+                    if b.is_ascii() {
+                        out.push(char::from(b));
+                    } else {
+                        ErrorHandler::lookup(errors)?.decode_error(
+                            self,
+                            bytes,
+                            pos,
+                            pos + 1,
+                            "ordinal not in range(128)",
+                            &mut out,
+                        )?;
+                    }
+                }
+                Ok(out)
+            }
+            // every byte is the code point of the same value
+            Self::Latin1 => Ok(bytes.iter().copied().map(char::from).collect()),
+        }
+    }
+}
+
+/// Decodes UTF-8, classifying invalid sequences with the same reasons and ranges as CPython.
+fn decode_utf8(bytes: &[u8], errors: &str) -> RunResult<String> {
+    let mut out = String::with_capacity(bytes.len());
+    let mut pos = 0;
+    loop {
+        match std::str::from_utf8(&bytes[pos..]) {
+            Ok(valid) => {
+                out.push_str(valid);
+                return Ok(out);
+            }
+            Err(err) => {
+                let valid_up_to = pos + err.valid_up_to();
+                out.push_str(std::str::from_utf8(&bytes[pos..valid_up_to]).expect("prefix is valid UTF-8"));
+
+                let (end, reason) = match err.error_len() {
+                    None => (bytes.len(), "unexpected end of data"),
+                    Some(len) => {
+                        let reason = match bytes[valid_up_to] {
+                            0x80..=0xc1 | 0xf5..=0xff => "invalid start byte",
+                            _ => "invalid continuation byte",
+                        };
+                        (valid_up_to + len, reason)
+                    }
+                };
+                ErrorHandler::lookup(errors)?.decode_error(Codec::Utf8, bytes, valid_up_to, end, reason, &mut out)?;
+                pos = end;
+            }
+        }
+    }
+}
+
+/// Formats a code point the way the `'backslashreplace'` handler and encoding error messages do.
+pub(crate) fn backslash_escape(code: u32) -> String {
+    if code <= 0xff {
+        format!("\\x{code:02x}")
+    } else if code <= 0xffff {
+        format!("\\u{code:04x}")
+    } else {
+        format!("\\U{code:08x}")
+    }
+}
+
+/// One of the codec error handlers named by the `errors` argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorHandler {
+    Strict,
+    Ignore,
+    Replace,
+    BackslashReplace,
+    XmlCharRefReplace,
+}
+
+impl ErrorHandler {
+    fn lookup(name: &str) -> RunResult<Self> {
+        match name {
+            "strict" => Ok(Self::Strict),
+            "ignore" => Ok(Self::Ignore),
+            "replace" => Ok(Self::Replace),
+            "backslashreplace" => Ok(Self::BackslashReplace),
+            "xmlcharrefreplace" => Ok(Self::XmlCharRefReplace),
+            _ => Err(ExcType::lookup_error_unknown_error_handler(name)),
+        }
+    }
+
+    /// Handles the undecodable bytes `start..end`, appending any replacement to `out`.
+    fn decode_error(
+        self,
+        codec: Codec,
+        bytes: &[u8],
+        start: usize,
+        end: usize,
+        reason: &str,
+        out: &mut String,
+    ) -> RunResult<()> {
+        match self {
+            Self::Strict => return Err(ExcType::unicode_decode_error(codec.name(), bytes, start, end, reason)),
+            Self::Ignore => {}
+            Self::Replace => out.push(char::REPLACEMENT_CHARACTER),
+            Self::BackslashReplace => {
+                for b in &bytes[start..end] {
+                    // writing to a String never fails
+                    write!(out, "\\x{b:02x}").unwrap();
+                }
+            }
+            Self::XmlCharRefReplace => return Err(ExcType::type_error_decode_error_callback()),
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the `encoding` or `errors` argument of `method`, which must be a `str`.
+///
+/// Matches CPython's format: `TypeError: decode() argument 'errors' must be str, not int`
+pub(crate) fn codec_arg(
+    method: &str,
+    param: &str,
+    value: &Value,
+    heap: &Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<String> {
+    match value.as_either_str(heap) {
+        Some(s) => Ok(s.as_str(interns).to_owned()),
+        None => {
+            let t = value.py_type(heap);
+            Err(ExcType::type_error(format!(
+                "{method}() argument '{param}' must be str, not {t}"
+            )))
+        }
+    }
+}
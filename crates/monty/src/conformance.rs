@@ -0,0 +1,210 @@
+//! Running a snippet under both Monty and CPython and comparing what each produced.
+//!
+//! Test cases check Monty against expectations written by hand; this module checks it
+//! against CPython itself, so any snippet can be used as a test without working out the
+//! expected result first. [`compare`] runs the snippet in both interpreters, recording the
+//! `repr()` of the value of its last expression, the exception it raised and everything it
+//! printed, and reports where they differ.
+//!
+//! CPython is embedded through pyo3, so this module is only available when the
+//! `conformance` feature is enabled, and needs a Python interpreter to link against.
+
+use std::{ffi::CString, fmt};
+
+use pyo3::{prelude::*, sync::PyOnceLock, types::PyModule};
+
+use crate::{MontyException, MontyRun, NoLimitTracker, PrintWriter};
+
+/// Script name used for the snippet in both interpreters.
+const SCRIPT_NAME: &str = "snippet.py";
+
+/// Runs a snippet the way `MontyRun` does: statements at module level, with the value of a
+/// trailing expression as the result, and print output captured.
+const CPYTHON_RUNNER: &str = r#"
+import ast
+import contextlib
+import io
+
+
+def run(source):
+    stdout = io.StringIO()
+    value = None
+    with contextlib.redirect_stdout(stdout):
+        try:
+            tree = ast.parse(source, 'snippet.py')
+            last = None
+            if tree.body and isinstance(tree.body[-1], ast.Expr):
+                last = ast.Expression(tree.body.pop().value)
+            namespace = {'__name__': '__main__'}
+            exec(compile(tree, 'snippet.py', 'exec'), namespace)
+            if last is not None:
+                value = eval(compile(last, 'snippet.py', 'eval'), namespace)
+        except BaseException as exc:
+            message = str(exc.args[0]) if len(exc.args) == 1 else (str(exc) or None)
+            return None, (type(exc).__name__, message), stdout.getvalue()
+    return repr(value), None, stdout.getvalue()
+"#;
+
+/// What running a snippet produced in one interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// `repr()` of the value of the last expression, `"None"` if the snippet doesn't end
+    /// with one, or `None` if it raised.
+    pub value: Option<String>,
+    /// The exception the snippet raised, if any.
+    pub exception: Option<RaisedException>,
+    /// Everything the snippet printed before it finished or raised.
+    pub stdout: String,
+}
+
+/// An exception that escaped a snippet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaisedException {
+    /// Name of the exception type, e.g. `"ValueError"`.
+    pub type_name: String,
+    /// The exception's message, `None` if it was raised without arguments.
+    pub message: Option<String>,
+}
+
+impl fmt::Display for RaisedException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {message}", self.type_name),
+            None => f.write_str(&self.type_name),
+        }
+    }
+}
+
+/// The outcomes of one snippet in Monty and in CPython.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    /// What Monty produced.
+    pub monty: Outcome,
+    /// What CPython produced.
+    pub cpython: Outcome,
+}
+
+impl Comparison {
+    /// Returns true if both interpreters produced the same value, exception and output.
+    #[must_use]
+    pub fn matches(&self) -> bool {
+        self.monty == self.cpython
+    }
+
+    /// Describes each way the outcomes differ, one line per difference.
+    #[must_use]
+    pub fn differences(&self) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.monty.value != self.cpython.value {
+            differences.push(format!(
+                "value: monty {}, cpython {}",
+                self.monty.value.as_deref().unwrap_or("<raised>"),
+                self.cpython.value.as_deref().unwrap_or("<raised>")
+            ));
+        }
+        if self.monty.exception != self.cpython.exception {
+            let describe = |exception: &Option<RaisedException>| {
+                exception
+                    .as_ref()
+                    .map_or_else(|| "no exception".to_owned(), ToString::to_string)
+            };
+            differences.push(format!(
+                "exception: monty {}, cpython {}",
+                describe(&self.monty.exception),
+                describe(&self.cpython.exception)
+            ));
+        }
+        if self.monty.stdout != self.cpython.stdout {
+            differences.push(format!(
+                "stdout: monty {:?}, cpython {:?}",
+                self.monty.stdout, self.cpython.stdout
+            ));
+        }
+        differences
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.matches() {
+            return f.write_str("outcomes match");
+        }
+        f.write_str(&self.differences().join("\n"))
+    }
+}
+
+/// Runs `code` under Monty and CPython and returns both outcomes.
+///
+/// Monty runs without resource limits, so the snippet must terminate.
+///
+/// # Example
+/// ```
+/// let comparison = monty::conformance::compare("print(sorted({3, 1, 2}))\n7 // 2");
+/// assert!(comparison.matches(), "{comparison}");
+/// assert_eq!(comparison.monty.value.as_deref(), Some("3"));
+/// ```
+///
+/// # Panics
+/// Panics if the embedded CPython interpreter can't run the comparison helper.
+#[must_use]
+pub fn compare(code: &str) -> Comparison {
+    Comparison {
+        monty: run_monty(code),
+        cpython: run_cpython(code),
+    }
+}
+
+/// Runs `code` under Monty.
+fn run_monty(code: &str) -> Outcome {
+    let mut print = PrintWriter::Collect(String::new());
+    let result = MontyRun::new(code.to_owned(), SCRIPT_NAME, vec![], vec![])
+        .and_then(|runner| runner.run(vec![], NoLimitTracker, &mut print));
+    let stdout = print.collected_output().unwrap_or_default().to_owned();
+    match result {
+        Ok(value) => Outcome {
+            value: Some(value.py_repr()),
+            exception: None,
+            stdout,
+        },
+        Err(exc) => Outcome {
+            value: None,
+            exception: Some(raised(&exc)),
+            stdout,
+        },
+    }
+}
+
+/// Converts a Monty exception to the form CPython's is reported in.
+fn raised(exc: &MontyException) -> RaisedException {
+    RaisedException {
+        type_name: exc.exc_type().to_string(),
+        message: exc.message().map(str::to_owned),
+    }
+}
+
+/// Runs `code` under CPython, in a fresh namespace.
+fn run_cpython(code: &str) -> Outcome {
+    // PyOnceLock rather than OnceLock, so a thread waiting for another to load the runner
+    // doesn't hold the interpreter the loading thread needs
+    static RUNNER: PyOnceLock<Py<PyModule>> = PyOnceLock::new();
+
+    Python::attach(|py| {
+        let runner = RUNNER.get_or_init(py, || {
+            let source = CString::new(CPYTHON_RUNNER).expect("runner source has no nul bytes");
+            PyModule::from_code(py, &source, c"monty_conformance.py", c"monty_conformance")
+                .expect("failed to load the CPython runner")
+                .unbind()
+        });
+        let (value, exception, stdout): (Option<String>, Option<(String, Option<String>)>, String) = runner
+            .bind(py)
+            .getattr("run")
+            .and_then(|run| run.call1((code,)))
+            .and_then(|outcome| outcome.extract())
+            .expect("CPython runner failed");
+        Outcome {
+            value,
+            exception: exception.map(|(type_name, message)| RaisedException { type_name, message }),
+            stdout,
+        }
+    })
+}
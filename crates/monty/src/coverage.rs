@@ -0,0 +1,154 @@
+//! Opt-in line coverage.
+//!
+//! `MontyRun::run_coverage` runs a script with a [`LineCoverage`] attached to the VM, which
+//! marks the line of every opcode executed, and returns a [`CoverageReport`] comparing the
+//! executed lines against every line that has code.
+
+use std::fmt;
+
+use crate::{
+    bytecode::Code,
+    intern::{FunctionId, Interns},
+};
+
+/// Which source lines a run executed, returned by `MontyRun::run_coverage`.
+///
+/// Lines are 1-based. A line is executable if the compiler emitted code for it: blank
+/// lines, comments, and code removed by constant folding (e.g. the body of `if False:`)
+/// are neither executed nor missed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CoverageReport {
+    /// `executable[i]` is whether line `i + 1` has code.
+    executable: Vec<bool>,
+    /// `executed[i]` is whether line `i + 1` ran.
+    executed: Vec<bool>,
+}
+
+impl CoverageReport {
+    /// Returns a bitmap of executed lines, where index `i` is line `i + 1`.
+    ///
+    /// The bitmap ends at the last executed line.
+    #[must_use]
+    pub fn executed_bitmap(&self) -> &[bool] {
+        &self.executed
+    }
+
+    /// Returns whether `line` ran.
+    #[must_use]
+    pub fn is_executed(&self, line: u16) -> bool {
+        bit(&self.executed, line)
+    }
+
+    /// Returns whether `line` has code.
+    #[must_use]
+    pub fn is_executable(&self, line: u16) -> bool {
+        bit(&self.executable, line)
+    }
+
+    /// Returns the lines that ran, in order.
+    #[must_use]
+    pub fn executed_lines(&self) -> Vec<u16> {
+        set_lines(&self.executed).collect()
+    }
+
+    /// Returns the lines that have code, in order.
+    #[must_use]
+    pub fn executable_lines(&self) -> Vec<u16> {
+        set_lines(&self.executable).collect()
+    }
+
+    /// Returns the lines that have code but never ran, in order.
+    #[must_use]
+    pub fn missed_lines(&self) -> Vec<u16> {
+        set_lines(&self.executable)
+            .filter(|line| !self.is_executed(*line))
+            .collect()
+    }
+
+    /// Returns the percentage of executable lines that ran, 100 if there are none.
+    #[must_use]
+    #[expect(clippy::cast_precision_loss, reason = "line counts fit in u16")]
+    pub fn percent_covered(&self) -> f64 {
+        let executable = set_lines(&self.executable).count();
+        if executable == 0 {
+            return 100.0;
+        }
+        let executed = executable - self.missed_lines().len();
+        executed as f64 / executable as f64 * 100.0
+    }
+}
+
+/// Renders a one-line summary, e.g. `4/5 lines covered (80.0%), missed: 7`.
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let executable = set_lines(&self.executable).count();
+        let missed = self.missed_lines();
+        write!(
+            f,
+            "{}/{executable} lines covered ({:.1}%)",
+            executable - missed.len(),
+            self.percent_covered()
+        )?;
+        if !missed.is_empty() {
+            let missed: Vec<String> = missed.iter().map(ToString::to_string).collect();
+            write!(f, ", missed: {}", missed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn bit(bitmap: &[bool], line: u16) -> bool {
+    line > 0 && bitmap.get(usize::from(line) - 1).copied().unwrap_or(false)
+}
+
+fn set_lines(bitmap: &[bool]) -> impl Iterator<Item = u16> + '_ {
+    bitmap
+        .iter()
+        .enumerate()
+        .filter(|(_, set)| **set)
+        .map(|(index, _)| u16::try_from(index + 1).expect("line number exceeds u16"))
+}
+
+fn set_bit(bitmap: &mut Vec<bool>, line: u16) {
+    let Some(index) = usize::from(line).checked_sub(1) else {
+        return;
+    };
+    if index >= bitmap.len() {
+        bitmap.resize(index + 1, false);
+    }
+    bitmap[index] = true;
+}
+
+/// Marks executed lines while the VM runs.
+///
+/// The VM calls `record` each time execution moves to a new line, not for every opcode.
+#[derive(Debug, Default)]
+pub(crate) struct LineCoverage {
+    executed: Vec<bool>,
+}
+
+impl LineCoverage {
+    /// Marks `line` as executed.
+    #[inline]
+    pub fn record(&mut self, line: u16) {
+        set_bit(&mut self.executed, line);
+    }
+
+    /// Builds the report, collecting executable lines from the module and every function.
+    pub fn finish(self, module_code: &Code, interns: &Interns) -> CoverageReport {
+        let mut executable = Vec::new();
+        let functions = (0..interns.function_count()).map(|index| {
+            let index = u16::try_from(index).expect("function index exceeds u16");
+            &interns.get_function(FunctionId::from_index(index)).code
+        });
+        for code in std::iter::once(module_code).chain(functions) {
+            for line in code.lines() {
+                set_bit(&mut executable, line);
+            }
+        }
+        CoverageReport {
+            executable,
+            executed: self.executed,
+        }
+    }
+}
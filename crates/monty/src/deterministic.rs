@@ -0,0 +1,110 @@
+//! Deterministic execution, for hosts that replay runs or compare them across machines.
+//!
+//! Most of Monty is deterministic by construction:
+//! - `hash()` uses fixed keys, so there is no equivalent of CPython's hash randomization
+//!   (`PYTHONHASHSEED`), and equal values hash the same in every process.
+//! - Dicts and sets iterate in insertion order.
+//! - Compiling the same code assigns the same namespace slots, and dumps write hash maps in
+//!   sorted order, so the same state always serializes to the same bytes.
+//! - There is no `random` module to seed.
+//!
+//! What remains is time: `time.time()`, `time.monotonic()` and `time.perf_counter()` read
+//! the host clock, and `resources()` reports the time left before a time limit. Wrapping a
+//! tracker in [`DeterministicTracker`] (or calling `MontyRunBuilder::deterministic()`) stubs
+//! both, so two runs of the same code with the same inputs and the same external call
+//! results produce the same results and byte-identical snapshots.
+//!
+//! Time limits are still checked against the real clock as a safety net. A run that hits one
+//! raises `TimeoutError` at a point that varies between runs, so hosts comparing runs should
+//! treat a timeout as "no result" and bound work with allocation, memory or fuel limits.
+
+use crate::{
+    clock::{Clock, FrozenClock},
+    resource::{CollectionKind, ResourceBudget, ResourceError, ResourceTracker},
+    timeline::SpanKind,
+    trace::TraceHook,
+    warnings::WarningSink,
+};
+
+/// A resource tracker that wraps another tracker and hides the passage of time from scripts.
+///
+/// Scripts read time from a [`FrozenClock`], and `resources()` reports no time budget even
+/// when the inner tracker has a time limit. Everything else, including every limit check,
+/// is delegated to the inner tracker.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct DeterministicTracker<T: ResourceTracker> {
+    inner: T,
+}
+
+impl<T: ResourceTracker> DeterministicTracker<T> {
+    /// Creates a deterministic tracker wrapping `inner`.
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker> ResourceTracker for DeterministicTracker<T> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        self.inner.trace_hook()
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        self.inner.warning_sink()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        &FrozenClock
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner
+            .remaining_budget()
+            .map(|budget| ResourceBudget { time: None, ..budget })
+    }
+}
@@ -0,0 +1,201 @@
+//! Diagnostics for editors: every error in a script, with positions and suggested fixes.
+//!
+//! [`MontyRun::diagnostics`](crate::MontyRun::diagnostics) checks code without stopping at
+//! the first problem. Syntax errors are all reported together; when the syntax is valid,
+//! each statement using a construct Monty doesn't support is reported, and the rest of the
+//! code is still checked. The later prepare and compile passes stop at their first error, so
+//! those are reported once the code parses cleanly.
+//!
+//! [`MontyRun::check`](crate::MontyRun::check) adds the reads of names that nothing has
+//! assigned, which only fail once they run, to the diagnostics of code that compiles.
+
+use std::ops::Range;
+
+use crate::{
+    ExcType, MontyException,
+    exception_public::CodeLoc,
+    expressions::Identifier,
+    intern::InternerBuilder,
+    parse::{CodeRange, RecoveredParse, parse, parse_recovering},
+    prepare::prepare,
+    run::{CompileOptions, MontyRun},
+    undefined_names::find_undefined_names,
+};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Severity {
+    /// The code can't be run.
+    Error,
+    /// The code runs, but probably not as intended.
+    Warning,
+}
+
+/// A problem found in a script, located by byte offsets and line/column positions.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The exception running the code would raise, or `None` for warnings.
+    pub exc_type: Option<ExcType>,
+    /// Description of the problem.
+    pub message: String,
+    /// Byte offsets of the offending code.
+    pub span: Range<usize>,
+    /// Start of the offending code.
+    pub start: CodeLoc,
+    /// End of the offending code.
+    pub end: CodeLoc,
+    /// A suggested edit that resolves the problem, if there's an obvious one.
+    pub fix: Option<Fix>,
+}
+
+/// An edit that replaces `span` of the source with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Fix {
+    /// Short description of the edit, e.g. "insert ':'".
+    pub message: String,
+    /// Byte offsets of the code to replace; empty to insert.
+    pub span: Range<usize>,
+    /// The replacement text; empty to delete.
+    pub replacement: String,
+}
+
+/// Collects diagnostics for `code`, see [`MontyRun::diagnostics`].
+pub(crate) fn collect(
+    code: &str,
+    script_name: &str,
+    input_names: Vec<String>,
+    external_functions: Vec<String>,
+) -> Vec<Diagnostic> {
+    let RecoveredParse { result, unreachable } = parse_recovering(code, script_name);
+    let mut diagnostics: Vec<Diagnostic> = match result {
+        Ok(_) => MontyRun::new(code.to_owned(), script_name, input_names, external_functions)
+            .err()
+            .map(|exc| from_exception(code, &exc))
+            .into_iter()
+            .collect(),
+        Err(errors) => errors
+            .into_iter()
+            .map(|err| from_exception(code, &err.into_python_exc(script_name, code)))
+            .collect(),
+    };
+    diagnostics.extend(unreachable.into_iter().map(|position| unreachable_code(code, position)));
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+/// Collects diagnostics for `code` including undefined names, see [`MontyRun::check`].
+pub(crate) fn check(
+    code: &str,
+    script_name: &str,
+    input_names: Vec<String>,
+    external_functions: Vec<String>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = collect(code, script_name, input_names.clone(), external_functions.clone());
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return diagnostics;
+    }
+    // the code compiles, so parsing and preparing it again succeeds
+    let options = CompileOptions::default();
+    if let Ok(parse_result) = parse(code, script_name)
+        && let Ok(prepared) = prepare(parse_result, input_names, &external_functions, &options)
+    {
+        diagnostics.extend(
+            find_undefined_names(&prepared.nodes)
+                .into_iter()
+                .map(|ident| undefined_name(code, &prepared.interner, &ident)),
+        );
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    }
+    diagnostics
+}
+
+/// Converts an exception raised while preparing the code into an error diagnostic.
+fn from_exception(code: &str, exc: &MontyException) -> Diagnostic {
+    let (start, end) = exc
+        .traceback()
+        .first()
+        .map_or((CodeLoc::default(), CodeLoc::default()), |frame| {
+            (frame.start, frame.end)
+        });
+    let span = byte_offset(code, start)..byte_offset(code, end);
+    let message = exc.message().unwrap_or_default().to_owned();
+    Diagnostic {
+        severity: Severity::Error,
+        exc_type: Some(exc.exc_type()),
+        fix: suggest_fix(&message, &span),
+        message,
+        span,
+        start,
+        end,
+    }
+}
+
+fn undefined_name(code: &str, interner: &InternerBuilder, ident: &Identifier) -> Diagnostic {
+    let (start, end) = (ident.position.start(), ident.position.end());
+    Diagnostic {
+        severity: Severity::Error,
+        exc_type: Some(ExcType::NameError),
+        message: format!("name '{}' is not defined", interner.get_str(ident.name_id)),
+        span: byte_offset(code, start)..byte_offset(code, end),
+        start,
+        end,
+        fix: None,
+    }
+}
+
+fn unreachable_code(code: &str, position: CodeRange) -> Diagnostic {
+    let (start, end) = (position.start(), position.end());
+    let span = byte_offset(code, start)..byte_offset(code, end);
+    Diagnostic {
+        severity: Severity::Warning,
+        exc_type: None,
+        message: "code is unreachable".to_owned(),
+        fix: Some(Fix {
+            message: "remove unreachable code".to_owned(),
+            span: span.clone(),
+            replacement: String::new(),
+        }),
+        span,
+        start,
+        end,
+    }
+}
+
+/// Suggests a fix for errors whose message says exactly what's wrong.
+fn suggest_fix(message: &str, span: &Range<usize>) -> Option<Fix> {
+    // the parser's "Expected ':', found newline" and similar
+    if let Some(rest) = message.strip_prefix("Expected '")
+        && let Some((token, _)) = rest.split_once("', found")
+    {
+        return Some(Fix {
+            message: format!("insert '{token}'"),
+            span: span.start..span.start,
+            replacement: token.to_owned(),
+        });
+    }
+    let statement = match message {
+        "'break' outside loop" => "break",
+        "'continue' not properly in loop" => "continue",
+        _ => return None,
+    };
+    Some(Fix {
+        message: format!("remove '{statement}'"),
+        span: span.clone(),
+        replacement: String::new(),
+    })
+}
+
+/// Converts a 1-based line and column back to a byte offset into `code`.
+fn byte_offset(code: &str, loc: CodeLoc) -> usize {
+    let line_start: usize = code
+        .split_inclusive('\n')
+        .take(usize::from(loc.line).saturating_sub(1))
+        .map(str::len)
+        .sum();
+    (line_start + usize::from(loc.column).saturating_sub(1)).min(code.len())
+}
@@ -0,0 +1,262 @@
+//! Evaluation of single expressions against host-supplied variables.
+//!
+//! [`eval_expr`] is meant for "formula field" style inputs: the source must be exactly
+//! one expression, and by default it may not call functions or loop (comprehensions).
+//! Both are checked on the prepared nodes, so disallowed code is rejected with a
+//! `SyntaxError` before any of it runs.
+
+use std::time::Duration;
+
+use crate::{
+    ExcType, MontyException,
+    args::ArgExprs,
+    expressions::{Comprehension, Expr, ExprLoc, Node, PreparedNode},
+    fstring::{FStringPart, FormatSpec},
+    io::PrintWriter,
+    object::MontyObject,
+    parse::ParseError,
+    resource::{LimitedTracker, ResourceLimits, ResourceTracker},
+    run::MontyRun,
+};
+
+/// Filename used in tracebacks of evaluated expressions.
+const EVAL_FILENAME: &str = "<expr>";
+
+/// Time an expression evaluated by [`eval_expr`] may run for.
+const EVAL_MAX_DURATION: Duration = Duration::from_secs(1);
+
+/// Memory an expression evaluated by [`eval_expr`] may use, including its bindings.
+const EVAL_MAX_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Constructs an expression passed to [`eval_expr_with_options`] may use.
+///
+/// Calls and comprehensions are rejected by default; arithmetic, comparisons, boolean
+/// logic, conditional expressions, literals, subscripts, attribute reads and f-strings are
+/// always allowed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalOptions {
+    /// Whether the expression may call functions, including builtins and methods, and
+    /// define lambdas.
+    pub allow_calls: bool,
+    /// Whether the expression may use list, set and dict comprehensions.
+    pub allow_comprehensions: bool,
+}
+
+impl EvalOptions {
+    /// Creates the default options, which reject calls and comprehensions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether calls and lambdas are allowed.
+    #[must_use]
+    pub fn allow_calls(mut self, allow_calls: bool) -> Self {
+        self.allow_calls = allow_calls;
+        self
+    }
+
+    /// Sets whether comprehensions are allowed.
+    #[must_use]
+    pub fn allow_comprehensions(mut self, allow_comprehensions: bool) -> Self {
+        self.allow_comprehensions = allow_comprehensions;
+        self
+    }
+}
+
+/// Evaluates a single expression with `bindings` as its variables, rejecting calls and
+/// comprehensions.
+///
+/// Even without calls an expression like `'x' * 10 ** 9` can do a lot of work, so it runs
+/// with a time limit of one second and a memory limit of 64 MiB. Use
+/// [`eval_expr_with_options`] to pick other limits.
+///
+/// # Example
+/// ```
+/// use monty::{MontyObject, eval_expr};
+///
+/// let bindings = [("price".to_owned(), MontyObject::Int(40)), ("qty".to_owned(), MontyObject::Int(3))];
+/// let total = eval_expr("price * qty if qty > 0 else 0", bindings).unwrap();
+/// assert_eq!(total, MontyObject::Int(120));
+/// ```
+///
+/// # Errors
+/// Returns a `SyntaxError` if `src` isn't a single expression or uses a disallowed
+/// construct, a `MemoryError` or `TimeoutError` if it goes over the limits, and any
+/// exception the expression raises while evaluating.
+pub fn eval_expr(
+    src: &str,
+    bindings: impl IntoIterator<Item = (String, MontyObject)>,
+) -> Result<MontyObject, MontyException> {
+    let limits = ResourceLimits::new()
+        .max_duration(EVAL_MAX_DURATION)
+        .max_memory(EVAL_MAX_MEMORY);
+    eval_expr_with_options(src, bindings, EvalOptions::default(), LimitedTracker::new(limits))
+}
+
+/// Evaluates a single expression like [`eval_expr`], with the given options and resource
+/// tracker.
+///
+/// # Errors
+/// Same as [`eval_expr`], plus resource errors raised by `resource_tracker`.
+pub fn eval_expr_with_options(
+    src: &str,
+    bindings: impl IntoIterator<Item = (String, MontyObject)>,
+    options: EvalOptions,
+    resource_tracker: impl ResourceTracker,
+) -> Result<MontyObject, MontyException> {
+    let (names, values): (Vec<String>, Vec<MontyObject>) = bindings.into_iter().unzip();
+    let runner = MontyRun::new_checked(src.to_owned(), EVAL_FILENAME, names, |nodes| {
+        check_expression(nodes, options, src)
+    })?;
+    runner.run(values, resource_tracker, &mut PrintWriter::Disabled)
+}
+
+/// Checks that `nodes` are a single expression using only constructs `options` allows.
+fn check_expression(nodes: &[PreparedNode], options: EvalOptions, src: &str) -> Result<(), MontyException> {
+    match nodes {
+        // prepare turns a trailing expression into an implicit return, unless it's `None`
+        [Node::Return(expr) | Node::Expr(expr)] => Checker { options }
+            .visit_expr(expr)
+            .map_err(|err| err.into_python_exc(EVAL_FILENAME, src)),
+        // statements have no single position to point at
+        _ => Err(MontyException::new(
+            ExcType::SyntaxError,
+            Some("expected a single expression".to_owned()),
+        )),
+    }
+}
+
+struct Checker {
+    options: EvalOptions,
+}
+
+impl Checker {
+    fn reject(expr_loc: &ExprLoc, what: &'static str) -> Result<(), ParseError> {
+        Err(ParseError::syntax(
+            format!("{what} are not allowed in this expression"),
+            expr_loc.position,
+        ))
+    }
+
+    fn visit_exprs(&self, exprs: &[ExprLoc]) -> Result<(), ParseError> {
+        exprs.iter().try_for_each(|expr| self.visit_expr(expr))
+    }
+
+    fn visit_args(&self, args: &ArgExprs) -> Result<(), ParseError> {
+        match args {
+            ArgExprs::Empty => Ok(()),
+            ArgExprs::One(arg) => self.visit_expr(arg),
+            ArgExprs::Two(first, second) => {
+                self.visit_expr(first)?;
+                self.visit_expr(second)
+            }
+            ArgExprs::Args(args) => self.visit_exprs(args),
+            ArgExprs::Kwargs(kwargs) => kwargs.iter().try_for_each(|kwarg| self.visit_expr(&kwarg.value)),
+            ArgExprs::ArgsKargs {
+                args,
+                var_args,
+                kwargs,
+                var_kwargs,
+            } => {
+                self.visit_exprs(args.as_deref().unwrap_or_default())?;
+                for kwarg in kwargs.as_deref().unwrap_or_default() {
+                    self.visit_expr(&kwarg.value)?;
+                }
+                for unpacked in [var_args, var_kwargs].into_iter().flatten() {
+                    self.visit_expr(unpacked)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn visit_comprehension(&self, expr_loc: &ExprLoc, generators: &[Comprehension]) -> Result<(), ParseError> {
+        if !self.options.allow_comprehensions {
+            return Self::reject(expr_loc, "comprehensions");
+        }
+        for generator in generators {
+            self.visit_expr(&generator.iter)?;
+            self.visit_exprs(&generator.ifs)?;
+        }
+        Ok(())
+    }
+
+    fn visit_fstring(&self, parts: &[FStringPart]) -> Result<(), ParseError> {
+        for part in parts {
+            if let FStringPart::Interpolation { expr, format_spec, .. } = part {
+                self.visit_expr(expr)?;
+                if let Some(FormatSpec::Dynamic(spec_parts)) = format_spec {
+                    self.visit_fstring(spec_parts)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_expr(&self, expr_loc: &ExprLoc) -> Result<(), ParseError> {
+        match &expr_loc.expr {
+            Expr::Call { .. } | Expr::AttrCall { .. } | Expr::IndirectCall { .. } if !self.options.allow_calls => {
+                Self::reject(expr_loc, "function calls")
+            }
+            Expr::Call { args, .. } => self.visit_args(args),
+            Expr::AttrCall { object, args, .. } | Expr::IndirectCall { callable: object, args } => {
+                self.visit_expr(object)?;
+                self.visit_args(args)
+            }
+            Expr::Lambda { .. } | Expr::LambdaRaw { .. } => {
+                if self.options.allow_calls {
+                    Ok(())
+                } else {
+                    Self::reject(expr_loc, "lambdas")
+                }
+            }
+            Expr::Await(_) => Self::reject(expr_loc, "await expressions"),
+            Expr::AttrGet { object, .. }
+            | Expr::Not(object)
+            | Expr::UnaryMinus(object)
+            | Expr::UnaryPlus(object)
+            | Expr::UnaryInvert(object) => self.visit_expr(object),
+            Expr::Op { left, right, .. } | Expr::CmpOp { left, right, .. } => {
+                self.visit_expr(left)?;
+                self.visit_expr(right)
+            }
+            Expr::ChainCmp { left, comparisons } => {
+                self.visit_expr(left)?;
+                comparisons.iter().try_for_each(|(_, operand)| self.visit_expr(operand))
+            }
+            Expr::List(elements) | Expr::Tuple(elements) | Expr::Set(elements) => self.visit_exprs(elements),
+            Expr::Dict(pairs) => pairs.iter().try_for_each(|(key, value)| {
+                self.visit_expr(key)?;
+                self.visit_expr(value)
+            }),
+            Expr::Subscript { object, index } => {
+                self.visit_expr(object)?;
+                self.visit_expr(index)
+            }
+            Expr::Slice { lower, upper, step } => [lower, upper, step]
+                .into_iter()
+                .flatten()
+                .try_for_each(|part| self.visit_expr(part)),
+            Expr::FString(parts) => self.visit_fstring(parts),
+            Expr::IfElse { test, body, orelse } => {
+                self.visit_expr(test)?;
+                self.visit_expr(body)?;
+                self.visit_expr(orelse)
+            }
+            Expr::ListComp { elt, generators }
+            | Expr::SetComp { elt, generators }
+            | Expr::Generator { elt, generators } => {
+                self.visit_comprehension(expr_loc, generators)?;
+                self.visit_expr(elt)
+            }
+            Expr::DictComp { key, value, generators } => {
+                self.visit_comprehension(expr_loc, generators)?;
+                self.visit_expr(key)?;
+                self.visit_expr(value)
+            }
+            Expr::Named { value, .. } => self.visit_expr(value),
+            Expr::Name(_) | Expr::Literal(_) | Expr::Builtin(_) => Ok(()),
+        }
+    }
+}
@@ -1,23 +1,28 @@
 use std::{
     borrow::Cow,
     fmt::{self, Display, Write},
+    hash::{Hash, Hasher},
 };
 
 use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
-use strum::{Display, EnumString, IntoStaticStr};
+use strum::{Display, EnumIter, EnumString, IntoStaticStr};
 
 use crate::{
     args::ArgValues,
+    bounded_repr::{ReprLimits, bounded_str},
+    codecs::backslash_escape,
     defer_drop,
     exception_public::{MontyException, StackFrame},
     fstring::FormatError,
-    heap::{Heap, HeapData},
+    heap::{Heap, HeapData, HeapId, HeapIdMap},
     intern::{Interns, StaticStrings, StringId},
+    messages::{ErrorCode, Hint},
+    object::MontyObject,
     parse::CodeRange,
     resource::{DepthGuard, ResourceTracker},
     types::{
-        AttrCallResult, PyTrait, Str, Type, allocate_tuple,
+        AttrCallResult, Dict, PyTrait, Str, Type, allocate_tuple,
         str::{StringRepr, string_repr_fmt},
     },
     value::Value,
@@ -30,7 +35,9 @@ pub type RunResult<T> = Result<T, RunError>;
 ///
 /// Uses strum derives for automatic `Display`, `FromStr`, and `Into<&'static str>` implementations.
 /// The string representation matches the variant name exactly (e.g., `ValueError` -> "ValueError").
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, IntoStaticStr, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, EnumString, IntoStaticStr, Serialize, Deserialize,
+)]
 pub enum ExcType {
     /// primary exception class - matches any exception in isinstance checks.
     Exception,
@@ -76,8 +83,10 @@ pub enum ExcType {
 
     // --- ValueError hierarchy ---
     ValueError,
-    /// Subclass of ValueError - for encoding/decoding errors.
+    /// Subclass of ValueError - for decoding errors.
     UnicodeDecodeError,
+    /// Subclass of ValueError - for encoding errors.
+    UnicodeEncodeError,
 
     // --- ImportError hierarchy ---
     /// Import-related errors (module not found, name not in module).
@@ -136,8 +145,8 @@ impl ExcType {
             Self::AttributeError => matches!(self, Self::FrozenInstanceError),
             // NameError catches UnboundLocalError
             Self::NameError => matches!(self, Self::UnboundLocalError),
-            // ValueError catches UnicodeDecodeError
-            Self::ValueError => matches!(self, Self::UnicodeDecodeError),
+            // ValueError catches UnicodeDecodeError and UnicodeEncodeError
+            Self::ValueError => matches!(self, Self::UnicodeDecodeError | Self::UnicodeEncodeError),
             // ImportError catches ModuleNotFoundError
             Self::ImportError => matches!(self, Self::ModuleNotFoundError),
             // OSError catches FileNotFoundError, FileExistsError, IsADirectoryError, NotADirectoryError
@@ -153,7 +162,8 @@ impl ExcType {
     /// Creates an exception instance from an exception type and arguments.
     ///
     /// Handles exception constructors like `ValueError('message')`.
-    /// Currently supports zero or one string argument.
+    /// Currently supports zero or one string argument, plus any single exit code for
+    /// `SystemExit` (see `system_exit`).
     ///
     /// The `interns` parameter provides access to interned string content.
     /// Returns a heap-allocated exception value.
@@ -166,6 +176,7 @@ impl ExcType {
         defer_drop!(args, heap);
         let exc = match args {
             ArgValues::Empty => Ok(SimpleException::new_none(self)),
+            ArgValues::One(value) if self == Self::SystemExit => Ok(Self::system_exit(value, heap, interns)),
             ArgValues::One(value) => match value {
                 Value::InternString(string_id) => {
                     Ok(SimpleException::new_msg(self, interns.get_str(*string_id).to_owned()))
@@ -187,7 +198,7 @@ impl ExcType {
                 "exceptions can only be called with zero or one string argument",
             )),
         }?;
-        let heap_id = heap.allocate(HeapData::Exception(exc))?;
+        let heap_id = heap.allocate(HeapData::Exception(ExceptionInstance::new(exc)))?;
         Ok(Value::Ref(heap_id))
     }
 
@@ -198,12 +209,14 @@ impl ExcType {
     pub(crate) fn attribute_error(type_name: impl Display, attr: &str) -> RunError {
         let exc = SimpleException::new_msg(
             Self::AttributeError,
-            format!("'{type_name}' object has no attribute '{attr}'"),
+            ErrorCode::AttributeNotFound.format(&[&type_name, &attr]),
         );
         RunError::Exc(ExceptionRaise {
             exc,
             frame: None,
             hide_caret: true, // CPython doesn't show carets for attribute GET errors
+            instance: None,
+            links: None,
         })
     }
 
@@ -227,12 +240,14 @@ impl ExcType {
     pub(crate) fn attribute_error_module(module_name: &str, attr_name: &str) -> RunError {
         let exc = SimpleException::new_msg(
             Self::AttributeError,
-            format!("module '{module_name}' has no attribute '{attr_name}'"),
+            ErrorCode::ModuleAttributeNotFound.format(&[&module_name, &attr_name]),
         );
         RunError::Exc(ExceptionRaise {
             exc,
             frame: None,
             hide_caret: true, // CPython doesn't show carets for attribute GET errors
+            instance: None,
+            links: None,
         })
     }
 
@@ -251,7 +266,7 @@ impl ExcType {
 
     #[must_use]
     pub(crate) fn type_error_not_sub(type_: Type) -> RunError {
-        SimpleException::new_msg(Self::TypeError, format!("'{type_}' object is not subscriptable")).into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::NotSubscriptable.format(&[&type_])).into()
     }
 
     /// Creates a TypeError for awaiting a non-awaitable object.
@@ -279,7 +294,7 @@ impl ExcType {
     /// This matches Python 3.14's error message: `TypeError: unhashable type: 'list'`
     #[must_use]
     pub(crate) fn type_error_unhashable(type_: Type) -> RunError {
-        SimpleException::new_msg(Self::TypeError, format!("unhashable type: '{type_}'")).into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::Unhashable.format(&[&type_])).into()
     }
 
     /// Creates a TypeError for unhashable types used as dict keys.
@@ -308,13 +323,31 @@ impl ExcType {
         .into()
     }
 
+    /// Creates the `SystemExit` raised by `sys.exit(code)` and `SystemExit(code)`.
+    ///
+    /// The message is the code's `str()`, as for any exception argument, and the code
+    /// itself is kept alongside it for `Exit::from_result`, so `sys.exit('0')` still exits
+    /// with a string. `None` means a clean exit and is stored as no argument, matching
+    /// `str(SystemExit(None)) == ''`.
+    pub(crate) fn system_exit(code: &Value, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> SimpleException {
+        match code {
+            Value::None => SimpleException::new_none(Self::SystemExit),
+            _ => {
+                let mut guard = DepthGuard::default();
+                let mut exc = SimpleException::new_msg(Self::SystemExit, code.py_str(heap, &mut guard, interns));
+                exc.exit_code = Some(Box::new(MontyObject::from_value(code, heap, interns)));
+                exc
+            }
+        }
+    }
+
     /// Creates a KeyError for a missing dict key.
     ///
-    /// For string keys, uses the raw string value without extra quoting.
+    /// For string keys, uses the raw string value without extra quoting. Huge or deeply
+    /// nested keys are cut short (see `ReprLimits::ERROR_MESSAGE`).
     #[must_use]
     pub(crate) fn key_error(key: &Value, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> RunError {
-        let mut guard = DepthGuard::default();
-        let key_str = key.py_str(heap, &mut guard, interns).into_owned();
+        let key_str = bounded_str(key, heap, interns, ReprLimits::ERROR_MESSAGE);
         SimpleException::new_msg(Self::KeyError, key_str).into()
     }
 
@@ -397,9 +430,10 @@ impl ExcType {
     #[must_use]
     pub(crate) fn type_error_at_most(name: &str, max: usize, actual: usize) -> RunError {
         // CPython: "get expected at most 2 arguments, got 3"
+        let plural = if max == 1 { "" } else { "s" };
         SimpleException::new_msg(
             Self::TypeError,
-            format!("{name} expected at most {max} arguments, got {actual}"),
+            format!("{name} expected at most {max} argument{plural}, got {actual}"),
         )
         .into()
     }
@@ -583,7 +617,7 @@ impl ExcType {
     /// Matches CPython's format: `TypeError: '{type}' object is not callable`
     #[must_use]
     pub(crate) fn type_error_not_callable_object(type_: Type) -> RunError {
-        SimpleException::new_msg(Self::TypeError, format!("'{type_}' object is not callable")).into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::NotCallable.format(&[&type_])).into()
     }
 
     /// Creates a TypeError for non-iterable type in list/tuple/etc constructors.
@@ -591,7 +625,7 @@ impl ExcType {
     /// Matches CPython's format: `TypeError: '{type}' object is not iterable`
     #[must_use]
     pub(crate) fn type_error_not_iterable(type_: Type) -> RunError {
-        SimpleException::new_msg(Self::TypeError, format!("'{type_}' object is not iterable")).into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::NotIterable.format(&[&type_])).into()
     }
 
     /// Creates a TypeError for int() constructor with invalid type.
@@ -707,7 +741,7 @@ impl ExcType {
     /// Matches CPython's format: `IndexError('list index out of range')`
     #[must_use]
     pub(crate) fn list_index_error() -> RunError {
-        SimpleException::new_msg(Self::IndexError, "list index out of range").into()
+        SimpleException::new_msg(Self::IndexError, ErrorCode::ListIndexOutOfRange.template()).into()
     }
 
     /// Creates an IndexError for list assignment index out of range (setitem).
@@ -723,7 +757,7 @@ impl ExcType {
     /// Matches CPython's format: `IndexError('tuple index out of range')`
     #[must_use]
     pub(crate) fn tuple_index_error() -> RunError {
-        SimpleException::new_msg(Self::IndexError, "tuple index out of range").into()
+        SimpleException::new_msg(Self::IndexError, ErrorCode::TupleIndexOutOfRange.template()).into()
     }
 
     /// Creates an IndexError for string index out of range.
@@ -731,7 +765,7 @@ impl ExcType {
     /// Matches CPython's format: `IndexError('string index out of range')`
     #[must_use]
     pub(crate) fn str_index_error() -> RunError {
-        SimpleException::new_msg(Self::IndexError, "string index out of range").into()
+        SimpleException::new_msg(Self::IndexError, ErrorCode::StrIndexOutOfRange.template()).into()
     }
 
     /// Creates an IndexError for bytes index out of range.
@@ -780,10 +814,13 @@ impl ExcType {
     /// associated with a value in enclosing scope`
     #[must_use]
     pub(crate) fn name_error_free_variable(name: &str) -> SimpleException {
-        SimpleException::new_msg(
-            Self::NameError,
-            format!("cannot access free variable '{name}' where it is not associated with a value in enclosing scope"),
-        )
+        SimpleException::new_msg(Self::NameError, ErrorCode::UnboundFreeVariable.format(&[&name]))
+    }
+
+    /// Creates a TypeError for rebinding or deleting a global frozen by `MontyRepl::freeze_globals`.
+    #[must_use]
+    pub(crate) fn frozen_global(name: &str) -> SimpleException {
+        SimpleException::new_msg(Self::TypeError, format!("cannot assign to frozen global '{name}'"))
     }
 
     /// Creates a NameError for accessing an undefined variable.
@@ -791,10 +828,10 @@ impl ExcType {
     /// Matches CPython's format: `NameError: name 'x' is not defined`
     #[must_use]
     pub(crate) fn name_error(name: &str) -> SimpleException {
-        let mut msg = format!("name '{name}' is not defined");
+        let mut msg = ErrorCode::NameNotDefined.format(&[&name]);
         // add the same suffix as cpython, but only for the modules supported by Monty
         if matches!(name, "asyncio" | "sys" | "typing" | "types") {
-            write!(&mut msg, ". Did you forget to import '{name}'?").unwrap();
+            msg.push_str(&Hint::ForgotImport.format(&[&name]));
         }
         SimpleException::new_msg(Self::NameError, msg)
     }
@@ -804,10 +841,7 @@ impl ExcType {
     /// Matches CPython's format: `UnboundLocalError: cannot access local variable 'x' where it is not associated with a value`
     #[must_use]
     pub(crate) fn unbound_local_error(name: &str) -> SimpleException {
-        SimpleException::new_msg(
-            Self::UnboundLocalError,
-            format!("cannot access local variable '{name}' where it is not associated with a value"),
-        )
+        SimpleException::new_msg(Self::UnboundLocalError, ErrorCode::UnboundLocal.format(&[&name]))
     }
 
     /// Creates a ModuleNotFoundError for when a module cannot be found.
@@ -816,11 +850,16 @@ impl ExcType {
     /// Sets `hide_caret: true` because CPython doesn't show carets for module not found errors.
     #[must_use]
     pub(crate) fn module_not_found_error(module_name: &str) -> RunError {
-        let exc = SimpleException::new_msg(Self::ModuleNotFoundError, format!("No module named '{module_name}'"));
+        let exc = SimpleException::new_msg(
+            Self::ModuleNotFoundError,
+            ErrorCode::ModuleNotFound.format(&[&module_name]),
+        );
         RunError::Exc(ExceptionRaise {
             exc,
             frame: None,
             hide_caret: true, // CPython doesn't show carets for module not found errors
+            instance: None,
+            links: None,
         })
     }
 
@@ -838,7 +877,7 @@ impl ExcType {
     /// Matches CPython 3.14's format: `ZeroDivisionError('division by zero')`
     #[must_use]
     pub(crate) fn zero_division() -> SimpleException {
-        SimpleException::new_msg(Self::ZeroDivisionError, "division by zero")
+        SimpleException::new_msg(Self::ZeroDivisionError, ErrorCode::DivisionByZero.template())
     }
 
     /// Creates an OverflowError for string/sequence repetition with count too large.
@@ -867,12 +906,14 @@ impl ExcType {
     pub(crate) fn cannot_import_name(name: &str, module_name: &str) -> RunError {
         let exc = SimpleException::new_msg(
             Self::ImportError,
-            format!("cannot import name '{name}' from '{module_name}' (unknown location)"),
+            ErrorCode::CannotImportName.format(&[&name, &module_name]),
         );
         RunError::Exc(ExceptionRaise {
             exc,
             frame: None,
             hide_caret: true,
+            instance: None,
+            links: None,
         })
     }
 
@@ -903,9 +944,9 @@ impl ExcType {
     #[must_use]
     pub(crate) fn binary_type_error(op: &str, lhs_type: Type, rhs_type: Type) -> RunError {
         let message = if (op == "+" || op == "+=") && (lhs_type == Type::Str || lhs_type == Type::List) {
-            format!("can only concatenate {lhs_type} (not \"{rhs_type}\") to {lhs_type}")
+            ErrorCode::ConcatenateMismatch.format(&[&lhs_type, &rhs_type])
         } else {
-            format!("unsupported operand type(s) for {op}: '{lhs_type}' and '{rhs_type}'")
+            ErrorCode::UnsupportedOperand.format(&[&op, &lhs_type, &rhs_type])
         };
         SimpleException::new_msg(Self::TypeError, message).into()
     }
@@ -915,11 +956,7 @@ impl ExcType {
     /// Uses CPython's format: `bad operand type for unary {op}: '{type}'`
     #[must_use]
     pub(crate) fn unary_type_error(op: &str, value_type: Type) -> RunError {
-        SimpleException::new_msg(
-            Self::TypeError,
-            format!("bad operand type for unary {op}: '{value_type}'"),
-        )
-        .into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::BadUnaryOperand.format(&[&op, &value_type])).into()
     }
 
     /// Creates a TypeError for functions that require an integer argument.
@@ -927,11 +964,7 @@ impl ExcType {
     /// Matches CPython's format: `TypeError: '{type}' object cannot be interpreted as an integer`
     #[must_use]
     pub(crate) fn type_error_not_integer(type_: Type) -> RunError {
-        SimpleException::new_msg(
-            Self::TypeError,
-            format!("'{type_}' object cannot be interpreted as an integer"),
-        )
-        .into()
+        SimpleException::new_msg(Self::TypeError, ErrorCode::NotAnInteger.format(&[&type_])).into()
     }
 
     /// Creates a ZeroDivisionError for zero raised to a negative power.
@@ -940,7 +973,7 @@ impl ExcType {
     /// Note: CPython uses the same message for both int and float zero ** negative.
     #[must_use]
     pub(crate) fn zero_negative_power() -> RunError {
-        SimpleException::new_msg(Self::ZeroDivisionError, "zero to a negative power").into()
+        SimpleException::new_msg(Self::ZeroDivisionError, ErrorCode::ZeroNegativePower.template()).into()
     }
 
     /// Creates an OverflowError for exponents that are too large.
@@ -957,7 +990,7 @@ impl ExcType {
     /// Note: CPython uses the same message for both integer and float divmod.
     #[must_use]
     pub(crate) fn divmod_by_zero() -> RunError {
-        SimpleException::new_msg(Self::ZeroDivisionError, "division by zero").into()
+        SimpleException::new_msg(Self::ZeroDivisionError, ErrorCode::DivisionByZero.template()).into()
     }
 
     /// Creates a TypeError for str.join() when an item is not a string.
@@ -1068,14 +1101,52 @@ impl ExcType {
         SimpleException::new_msg(Self::LookupError, format!("unknown encoding: {encoding}")).into()
     }
 
-    /// Creates a UnicodeDecodeError for invalid UTF-8 bytes in decode().
+    /// Creates a UnicodeDecodeError for the bytes `start..end` that `codec` could not decode.
+    ///
+    /// Matches CPython's format: `UnicodeDecodeError: 'utf-8' codec can't decode byte 0xff in position 0: invalid start byte`,
+    /// switching to `bytes in position {start}-{end - 1}` when the range covers more than one byte.
+    #[must_use]
+    pub(crate) fn unicode_decode_error(codec: &str, bytes: &[u8], start: usize, end: usize, reason: &str) -> RunError {
+        let msg = if end == start + 1 {
+            format!(
+                "'{codec}' codec can't decode byte 0x{:02x} in position {start}: {reason}",
+                bytes[start]
+            )
+        } else {
+            format!(
+                "'{codec}' codec can't decode bytes in position {start}-{}: {reason}",
+                end - 1
+            )
+        };
+        SimpleException::new_msg(Self::UnicodeDecodeError, msg).into()
+    }
+
+    /// Creates a UnicodeEncodeError for the characters `start..end` that `codec` could not encode.
+    ///
+    /// Matches CPython's format: `UnicodeEncodeError: 'ascii' codec can't encode character '\xe9' in position 0: ordinal not in range(128)`,
+    /// switching to `characters in position {start}-{end - 1}` when the range covers more than one character.
+    #[must_use]
+    pub(crate) fn unicode_encode_error(codec: &str, first: char, start: usize, end: usize, reason: &str) -> RunError {
+        let msg = if end == start + 1 {
+            let escaped = backslash_escape(u32::from(first));
+            format!("'{codec}' codec can't encode character '{escaped}' in position {start}: {reason}")
+        } else {
+            format!(
+                "'{codec}' codec can't encode characters in position {start}-{}: {reason}",
+                end - 1
+            )
+        };
+        SimpleException::new_msg(Self::UnicodeEncodeError, msg).into()
+    }
+
+    /// Creates a TypeError for an error handler that cannot handle decoding errors.
     ///
-    /// Matches CPython's format: `UnicodeDecodeError: 'utf-8' codec can't decode bytes...`
+    /// Matches CPython's format: `TypeError: don't know how to handle UnicodeDecodeError in error callback`
     #[must_use]
-    pub(crate) fn unicode_decode_error_invalid_utf8() -> RunError {
+    pub(crate) fn type_error_decode_error_callback() -> RunError {
         SimpleException::new_msg(
-            Self::UnicodeDecodeError,
-            "'utf-8' codec can't decode bytes: invalid utf-8 sequence",
+            Self::TypeError,
+            "don't know how to handle UnicodeDecodeError in error callback",
         )
         .into()
     }
@@ -1101,10 +1172,13 @@ impl ExcType {
 ///
 /// This is used for performance reasons for common exception patterns.
 /// Exception messages use `String` for owned storage.
-#[derive(Debug, Clone, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub(crate) struct SimpleException {
     exc_type: ExcType,
     arg: Option<String>,
+    /// For `SystemExit`, the code it was raised with, kept as a value since `arg` only holds
+    /// its `str()`. Boxed so the exceptions raised everywhere else stay small.
+    exit_code: Option<Box<MontyObject>>,
 }
 
 impl fmt::Display for SimpleException {
@@ -1112,10 +1186,20 @@ impl fmt::Display for SimpleException {
         self.py_repr_fmt(f)
     }
 }
+
+/// Leaves out `exit_code`, which can be an unhashable value like a list; equal exceptions
+/// still hash equally as they have the same type and message.
+impl Hash for SimpleException {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.exc_type.hash(state);
+        self.arg.hash(state);
+    }
+}
 impl From<MontyException> for SimpleException {
     fn from(exc: MontyException) -> Self {
         Self {
             exc_type: exc.exc_type(),
+            exit_code: exc.exit_code().cloned().map(Box::new),
             arg: exc.into_message(),
         }
     }
@@ -1125,7 +1209,11 @@ impl SimpleException {
     /// Creates a new exception with the given type and optional argument message.
     #[must_use]
     pub fn new(exc_type: ExcType, arg: Option<String>) -> Self {
-        Self { exc_type, arg }
+        Self {
+            exc_type,
+            arg,
+            exit_code: None,
+        }
     }
 
     /// Creates a new exception with the given type and argument message.
@@ -1134,13 +1222,18 @@ impl SimpleException {
         Self {
             exc_type,
             arg: Some(arg.to_string()),
+            exit_code: None,
         }
     }
 
     /// Creates a new exception with the given type and no argument message.
     #[must_use]
     pub fn new_none(exc_type: ExcType) -> Self {
-        Self { exc_type, arg: None }
+        Self {
+            exc_type,
+            arg: None,
+            exit_code: None,
+        }
     }
 
     #[must_use]
@@ -1185,6 +1278,8 @@ impl SimpleException {
             exc: self,
             frame: Some(frame),
             hide_caret: false,
+            instance: None,
+            links: None,
         }
     }
 
@@ -1193,6 +1288,8 @@ impl SimpleException {
             exc: self,
             frame: Some(RawStackFrame::from_position(position)),
             hide_caret: false,
+            instance: None,
+            links: None,
         }
     }
 
@@ -1221,6 +1318,139 @@ impl SimpleException {
     }
 }
 
+/// An exception object on the heap, e.g. the value bound by `except ... as e`.
+///
+/// Wraps the [`SimpleException`] carried by raised errors with the state that only the
+/// Python object has: attributes assigned by user code (`e.note = ...`) and the traceback
+/// recorded when the exception was last caught. A bare `raise` reuses that traceback, so
+/// the re-raised exception still points at the original failure.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct ExceptionInstance {
+    exc: SimpleException,
+    /// Attributes set on the instance, keyed by attribute name.
+    attrs: Dict,
+    /// Traceback from the most recent time this exception was caught.
+    traceback: Option<RawStackFrame>,
+    /// Chained exceptions from the most recent time this exception was caught.
+    #[serde(default)]
+    links: Option<Box<ExceptionLinks>>,
+}
+
+impl ExceptionInstance {
+    /// Creates an instance with no attributes and no traceback.
+    #[must_use]
+    pub fn new(exc: SimpleException) -> Self {
+        Self {
+            exc,
+            attrs: Dict::new(),
+            traceback: None,
+            links: None,
+        }
+    }
+
+    #[must_use]
+    pub fn exc(&self) -> &SimpleException {
+        &self.exc
+    }
+
+    /// Returns the traceback recorded when this exception was last caught.
+    #[must_use]
+    pub fn traceback(&self) -> Option<&RawStackFrame> {
+        self.traceback.as_ref()
+    }
+
+    /// Records the traceback of the raise that was just caught.
+    pub fn set_traceback(&mut self, traceback: Option<RawStackFrame>) {
+        self.traceback = traceback;
+    }
+
+    /// Returns the chained exceptions recorded when this exception was last caught.
+    #[must_use]
+    pub fn links(&self) -> Option<&ExceptionLinks> {
+        self.links.as_deref()
+    }
+
+    /// Records the chained exceptions of the raise that was just caught.
+    pub fn set_links(&mut self, links: Option<Box<ExceptionLinks>>) {
+        self.links = links;
+    }
+
+    /// Returns this exception as it was last raised, for chaining it to another exception.
+    #[must_use]
+    pub fn to_raise(&self) -> ExceptionRaise {
+        ExceptionRaise {
+            exc: self.exc.clone(),
+            frame: self.traceback.clone(),
+            hide_caret: false,
+            instance: None,
+            links: self
+                .links
+                .as_deref()
+                .map(|links| Box::new(links.clone_to_depth(MAX_CHAIN_DEPTH))),
+        }
+    }
+
+    /// Returns whether any attribute set on the instance holds a heap reference.
+    #[must_use]
+    pub fn has_refs(&self) -> bool {
+        self.attrs.has_refs()
+    }
+
+    /// Rewrites heap ids in the attrs after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        self.attrs.remap_heap_ids(map);
+    }
+
+    /// Returns the attributes set on the instance.
+    #[must_use]
+    pub fn attrs(&self) -> &Dict {
+        &self.attrs
+    }
+
+    /// Sets an attribute value.
+    ///
+    /// The caller transfers ownership of both `name` and `value`. Returns the old value
+    /// if the attribute existed (caller must drop it), or None if this is a new attribute.
+    pub fn set_attr(
+        &mut self,
+        name: Value,
+        value: Value,
+        heap: &mut Heap<impl ResourceTracker>,
+        interns: &Interns,
+    ) -> RunResult<Option<Value>> {
+        if matches!(value, Value::Ref(_)) {
+            // `e.cause = e` and friends can form cycles through the instance
+            heap.mark_potential_cycle();
+        }
+        self.attrs.set(name, value, heap, interns)
+    }
+
+    #[must_use]
+    pub fn py_estimate_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.exc.arg().map_or(0, String::len) + self.attrs.py_estimate_size()
+    }
+
+    pub fn py_dec_ref_ids(&mut self, stack: &mut Vec<HeapId>) {
+        self.attrs.py_dec_ref_ids(stack);
+    }
+
+    /// Gets an attribute from this exception.
+    ///
+    /// Attributes set on the instance take precedence; otherwise defers to
+    /// [`SimpleException::py_getattr`] for built-in attributes like `.args`.
+    pub fn py_getattr(
+        &self,
+        attr_id: StringId,
+        heap: &mut Heap<impl ResourceTracker>,
+        interns: &Interns,
+    ) -> RunResult<Option<AttrCallResult>> {
+        if let Some(value) = self.attrs.get_by_str(interns.get_str(attr_id), heap, interns) {
+            return Ok(Some(AttrCallResult::Value(value.clone_with_heap(heap))));
+        }
+        self.exc.py_getattr(attr_id, heap, interns)
+    }
+}
+
 /// A raised exception with optional stack frame for traceback.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ExceptionRaise {
@@ -1234,6 +1464,57 @@ pub struct ExceptionRaise {
     /// whether the caret should be hidden.
     #[serde(default)]
     pub hide_caret: bool,
+    /// The heap exception object this was raised from (`raise e` or a bare `raise`), if any.
+    ///
+    /// Held without a reference: if the object is still alive when a handler catches the
+    /// exception, the handler binds that same object instead of a copy, so attributes set
+    /// on it survive re-raising. Not serialized; a restored exception is caught as a copy.
+    #[serde(skip)]
+    pub(crate) instance: Option<HeapId>,
+    /// The exceptions chained to this one, if any.
+    #[serde(default)]
+    pub links: Option<Box<ExceptionLinks>>,
+}
+
+/// Exceptions chained to a raised exception, like CPython's `__cause__` and `__context__`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ExceptionLinks {
+    /// The exception given by `raise ... from cause`.
+    pub cause: Option<ExceptionRaise>,
+    /// The exception that was being handled when this one was raised.
+    pub context: Option<ExceptionRaise>,
+    /// Set by `raise ... from`, so the context isn't reported.
+    pub suppress_context: bool,
+}
+
+/// How many exceptions deep a cause/context chain is kept.
+///
+/// Each chained exception is a copy, so without a limit a script re-raising from the
+/// previous exception in a loop would copy a chain that grows every iteration.
+const MAX_CHAIN_DEPTH: usize = 16;
+
+impl ExceptionLinks {
+    /// Clones the links, dropping exceptions chained more than `depth` levels further down.
+    fn clone_to_depth(&self, depth: usize) -> Self {
+        let clone_raise = |raise: &ExceptionRaise| ExceptionRaise {
+            exc: raise.exc.clone(),
+            frame: raise.frame.clone(),
+            hide_caret: raise.hide_caret,
+            instance: None,
+            links: match depth.checked_sub(1) {
+                Some(depth) => raise
+                    .links
+                    .as_deref()
+                    .map(|links| Box::new(links.clone_to_depth(depth))),
+                None => None,
+            },
+        };
+        Self {
+            cause: self.cause.as_ref().map(clone_raise),
+            context: self.context.as_ref().map(clone_raise),
+            suppress_context: self.suppress_context,
+        }
+    }
 }
 
 impl From<SimpleException> for ExceptionRaise {
@@ -1242,6 +1523,8 @@ impl From<SimpleException> for ExceptionRaise {
             exc,
             frame: None,
             hide_caret: false,
+            instance: None,
+            links: None,
         }
     }
 }
@@ -1252,6 +1535,8 @@ impl From<MontyException> for ExceptionRaise {
             exc: exc.into(),
             frame: None,
             hide_caret: false,
+            instance: None,
+            links: None,
         }
     }
 }
@@ -1317,7 +1602,22 @@ impl ExceptionRaise {
             })
             .unwrap_or_default();
 
-        MontyException::new_full(self.exc.exc_type(), self.exc.arg().cloned(), traceback)
+        let mut exception = MontyException::new_full(self.exc.exc_type(), self.exc.arg().cloned(), traceback);
+        exception.set_exit_code(self.exc.exit_code.map(|code| *code));
+        if let Some(links) = self.links {
+            let ExceptionLinks {
+                cause,
+                context,
+                suppress_context,
+            } = *links;
+            exception.set_chain(
+                cause.map(|cause| cause.into_python_exception(interns, source)),
+                context
+                    .filter(|_| !suppress_context)
+                    .map(|context| context.into_python_exception(interns, source)),
+            );
+        }
+        exception
     }
 }
 
@@ -3,6 +3,8 @@ use std::fmt::{self, Write};
 use crate::{
     exception_private::{ExcType, RawStackFrame},
     intern::Interns,
+    messages::ErrorCode,
+    object::MontyObject,
     parse::CodeRange,
     types::str::StringRepr,
 };
@@ -16,6 +18,12 @@ pub struct MontyException {
     message: Option<String>,
     /// Stack trace of the exception, first is the outermost frame shown first in the traceback
     traceback: Vec<StackFrame>,
+    /// The exception given with `raise ... from cause`, Python's `__cause__`
+    cause: Option<Box<Self>>,
+    /// The exception being handled when this one was raised, Python's `__context__`
+    context: Option<Box<Self>>,
+    /// For `SystemExit`, the code it was raised with, Python's `exc.code`
+    exit_code: Option<MontyObject>,
 }
 
 /// Number of identical consecutive frames to show before collapsing.
@@ -26,6 +34,16 @@ const REPEAT_FRAMES_SHOWN: usize = 3;
 /// Display implementation for MontyException should exactly match python traceback format.
 impl fmt::Display for MontyException {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_traceback(f, None)
+    }
+}
+
+impl std::error::Error for MontyException {}
+
+impl MontyException {
+    /// Writes the traceback, taking each frame's source line from `source` if given, or
+    /// from the frame's `preview_line` otherwise.
+    fn write_traceback(&self, f: &mut impl Write, source: Option<&str>) -> fmt::Result {
         // Print the traceback header if we have frames
         if !self.traceback.is_empty() {
             writeln!(f, "Traceback (most recent call last):")?;
@@ -47,7 +65,7 @@ impl fmt::Display for MontyException {
             if repeat_count > REPEAT_FRAMES_SHOWN {
                 // Show first REPEAT_FRAMES_SHOWN frames, then collapse the rest
                 for j in 0..REPEAT_FRAMES_SHOWN {
-                    write!(f, "{}", &self.traceback[i + j])?;
+                    self.traceback[i + j].write_to(f, source)?;
                 }
                 let collapsed = repeat_count - REPEAT_FRAMES_SHOWN;
                 writeln!(f, "  [Previous line repeated {collapsed} more times]")?;
@@ -55,7 +73,7 @@ impl fmt::Display for MontyException {
             } else {
                 // Show all frames in this group
                 for j in 0..repeat_count {
-                    write!(f, "{}", &self.traceback[i + j])?;
+                    self.traceback[i + j].write_to(f, source)?;
                 }
                 i += repeat_count;
             }
@@ -67,11 +85,7 @@ impl fmt::Display for MontyException {
             write!(f, "{}", self.exc_type)
         }
     }
-}
 
-impl std::error::Error for MontyException {}
-
-impl MontyException {
     /// Create a new MontyException with the given exception type and message.
     ///
     /// You can't provide a traceback here, it's send when raising the exception.
@@ -81,6 +95,9 @@ impl MontyException {
             exc_type,
             message,
             traceback: vec![],
+            cause: None,
+            context: None,
+            exit_code: None,
         }
     }
 
@@ -98,6 +115,30 @@ impl MontyException {
         self.message.as_deref()
     }
 
+    /// The exception's arguments, equivalent of python's `exc.args`.
+    ///
+    /// Monty exceptions carry at most one argument, the message, so this is a tuple of
+    /// that string or an empty tuple.
+    #[must_use]
+    pub fn args(&self) -> MontyObject {
+        MontyObject::Tuple(self.message.iter().cloned().map(MontyObject::String).collect())
+    }
+
+    /// For `SystemExit`, the code passed to `sys.exit()` or `SystemExit()`, as the value it
+    /// was rather than its `str()`; `None` for a bare `sys.exit()`, `sys.exit(None)` and
+    /// every other exception.
+    ///
+    /// Equivalent of python's `exc.code`.
+    #[must_use]
+    pub fn exit_code(&self) -> Option<&MontyObject> {
+        self.exit_code.as_ref()
+    }
+
+    /// Sets the code returned by [`exit_code`](Self::exit_code).
+    pub(crate) fn set_exit_code(&mut self, exit_code: Option<MontyObject>) {
+        self.exit_code = exit_code;
+    }
+
     /// Optional exception message explaining what went wrong.
     ///
     /// This takes ownership of the MontyException and returns an owned String.
@@ -108,12 +149,79 @@ impl MontyException {
         self.message
     }
 
+    /// Stable code identifying the message, if it is one the interpreter has a code for.
+    ///
+    /// See `MessageCatalog` for presenting the message in other wording.
+    #[must_use]
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        let message = self.message.as_deref()?;
+        ErrorCode::classify(self.exc_type, message).map(|classified| classified.code)
+    }
+
+    /// Replaces the message, keeping the exception type and traceback.
+    pub(crate) fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
     /// Stack trace of the exception, first is the outermost frame shown first in the traceback
+    ///
+    /// Each frame gives the function name, filename, and the line and column range of the
+    /// code that was executing.
     #[must_use]
     pub fn traceback(&self) -> &[StackFrame] {
         &self.traceback
     }
 
+    /// The exception this one was explicitly chained to with `raise ... from cause`.
+    ///
+    /// Equivalent of python's `exc.__cause__`; `None` for `raise ... from None`.
+    #[must_use]
+    pub fn cause(&self) -> Option<&Self> {
+        self.cause.as_deref()
+    }
+
+    /// The exception that was being handled when this one was raised.
+    ///
+    /// Equivalent of python's `exc.__context__`, except that it is `None` when the context
+    /// is suppressed by `raise ... from ...`, as python hides it from the traceback then.
+    #[must_use]
+    pub fn context(&self) -> Option<&Self> {
+        self.context.as_deref()
+    }
+
+    /// Sets the exceptions chained to this one.
+    pub(crate) fn set_chain(&mut self, cause: Option<Self>, context: Option<Self>) {
+        self.cause = cause.map(Box::new);
+        self.context = context.map(Box::new);
+    }
+
+    /// Renders the full traceback like CPython 3.11+, reading each frame's line from `source`.
+    ///
+    /// Unlike `Display`, which shows the lines captured when the exception was raised and
+    /// underlines them with `~`, this marks the failing expression with `^` and omits the
+    /// markers when they would cover the whole line, as CPython does. `source` should be the
+    /// code the exception came from.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::MontyRun;
+    ///
+    /// let code = "x = 1\ny = x + None";
+    /// let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    /// let err = runner.run_no_limits(vec![]).unwrap_err();
+    /// assert_eq!(
+    ///     err.render(code),
+    ///     "Traceback (most recent call last):\n  File \"test.py\", line 2, in <module>\n    y = x + None\n        ^^^^^^^^\nTypeError: unsupported operand type(s) for +: 'int' and 'NoneType'"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let mut rendered = String::new();
+        self.write_traceback(&mut rendered, Some(source))
+            .expect("writing to a String can't fail");
+        rendered
+    }
+
     /// Returns a compact summary of the exception.
     ///
     /// Format: `ExceptionType: message` (e.g., `NotImplementedError: feature not supported`)
@@ -146,6 +254,9 @@ impl MontyException {
             exc_type,
             message,
             traceback,
+            cause: None,
+            context: None,
+            exit_code: None,
         }
     }
 
@@ -154,6 +265,9 @@ impl MontyException {
             exc_type: ExcType::RuntimeError,
             message: Some(err.to_string()),
             traceback: vec![],
+            cause: None,
+            context: None,
+            exit_code: None,
         }
     }
 }
@@ -203,6 +317,14 @@ pub struct StackFrame {
 
 impl fmt::Display for StackFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, None)
+    }
+}
+
+impl StackFrame {
+    /// Writes the frame, with its line read from `source` and marked with `^` if given, or
+    /// with `preview_line` marked with `~` otherwise.
+    fn write_to(&self, f: &mut impl Write, source: Option<&str>) -> fmt::Result {
         // SyntaxError format: `  File "...", line N`
         // Runtime error format: `  File "...", line N, in <module>`
         if self.hide_frame_name {
@@ -216,7 +338,11 @@ impl fmt::Display for StackFrame {
             }
         }
 
-        if let Some(line) = &self.preview_line {
+        let line = match source {
+            Some(source) => source.lines().nth(usize::from(self.start.line) - 1),
+            None => self.preview_line.as_deref(),
+        };
+        if let Some(line) = line {
             // Strip leading whitespace like CPython does
             let trimmed = line.trim_start();
             writeln!(f, "\n    {trimmed}")?;
@@ -226,22 +352,29 @@ impl fmt::Display for StackFrame {
                 let leading_spaces = line.len() - trimmed.len();
                 // Calculate caret position relative to the trimmed line
                 // Column is 1-indexed, so subtract 1, then subtract leading spaces we stripped
-                let caret_start = if self.start.column as usize > leading_spaces {
-                    4 + self.start.column as usize - leading_spaces - 1
+                let caret_offset = (self.start.column as usize).saturating_sub(leading_spaces + 1);
+                if source.is_some() {
+                    // a span continuing onto later lines is marked to the end of this one
+                    let caret_len = if self.end.line == self.start.line {
+                        (self.end.column - self.start.column) as usize
+                    } else {
+                        trimmed.len().saturating_sub(caret_offset)
+                    };
+                    if caret_len > 0 && !(caret_offset == 0 && caret_len >= trimmed.trim_end().len()) {
+                        f.write_str(&" ".repeat(4 + caret_offset))?;
+                        writeln!(f, "{}", "^".repeat(caret_len))?;
+                    }
                 } else {
-                    4
-                };
-                f.write_str(&" ".repeat(caret_start))?;
-                writeln!(f, "{}", "~".repeat((self.end.column - self.start.column) as usize))?;
+                    f.write_str(&" ".repeat(4 + caret_offset))?;
+                    writeln!(f, "{}", "~".repeat((self.end.column - self.start.column) as usize))?;
+                }
             }
         } else {
             f.write_char('\n')?;
         }
         Ok(())
     }
-}
 
-impl StackFrame {
     pub(crate) fn from_raw(f: &RawStackFrame, interns: &Interns, source: &str) -> Self {
         let filename = interns.get_str(f.position.filename).to_string();
         Self {
@@ -0,0 +1,67 @@
+//! How a script ended, telling `sys.exit()` apart from running to the end.
+//!
+//! Inside the interpreter `sys.exit(code)` raises `SystemExit`, so `try`/`finally` blocks
+//! run and `except SystemExit` can intercept it like in CPython. Once it escapes the
+//! script it is not an error, so hosts convert the run's result with
+//! [`Exit::from_result`] rather than treating it as an uncaught exception.
+
+use crate::{ExcType, MontyException, MontyObject};
+
+/// The outcome of a script that didn't fail.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exit {
+    /// The script ran to the end, producing the value of its last expression.
+    Complete(MontyObject),
+    /// The script called `sys.exit(code)` or raised `SystemExit(code)` without catching it.
+    ///
+    /// The code is the value the script passed, `MontyObject::None` for a bare
+    /// `sys.exit()`. CPython exits with an int code as the status, and prints any other
+    /// code to stderr before exiting with status 1.
+    SystemExit(MontyObject),
+}
+
+impl Exit {
+    /// Converts the result of a run, turning an uncaught `SystemExit` into
+    /// `Exit::SystemExit`; other exceptions are returned unchanged.
+    ///
+    /// The code is [`MontyException::exit_code`]. A `SystemExit` made by the host rather
+    /// than the script carries only a message, which is taken as a string code.
+    pub fn from_result(result: Result<MontyObject, MontyException>) -> Result<Self, MontyException> {
+        match result {
+            Ok(value) => Ok(Self::Complete(value)),
+            Err(exc) if exc.exc_type() == ExcType::SystemExit => {
+                let code = exc.exit_code().cloned();
+                let code = code.unwrap_or_else(|| exc.into_message().map_or(MontyObject::None, MontyObject::String));
+                Ok(Self::SystemExit(code))
+            }
+            Err(exc) => Err(exc),
+        }
+    }
+
+    /// Returns the process exit status CPython would use for this outcome.
+    ///
+    /// `0` for a completed run or a bare `sys.exit()`, the code itself for an int (or
+    /// bool) that fits in an `i32`, and `1` otherwise, including for a string of digits.
+    #[must_use]
+    pub fn status(&self) -> i32 {
+        match self {
+            Self::Complete(_) | Self::SystemExit(MontyObject::None) => 0,
+            Self::SystemExit(MontyObject::Int(code)) => i32::try_from(*code).unwrap_or(1),
+            Self::SystemExit(MontyObject::Bool(code)) => i32::from(*code),
+            Self::SystemExit(_) => 1,
+        }
+    }
+
+    /// Returns what CPython prints to stderr for this outcome: the `str()` of a code that
+    /// isn't an int or `None`, and nothing otherwise.
+    #[must_use]
+    pub fn message(&self) -> Option<String> {
+        match self {
+            Self::Complete(_)
+            | Self::SystemExit(
+                MontyObject::None | MontyObject::Int(_) | MontyObject::BigInt(_) | MontyObject::Bool(_),
+            ) => None,
+            Self::SystemExit(code) => Some(code.to_string()),
+        }
+    }
+}
@@ -242,6 +242,15 @@ pub enum Expr {
         value: Box<ExprLoc>,
         generators: Vec<Comprehension>,
     },
+    /// Generator expression: `(elt for target in iter if cond...)`
+    ///
+    /// Passed as the only argument to `sum()`, `any()` or `all()`, it is consumed one
+    /// item at a time without building a collection. Anywhere else it is materialized
+    /// as a list, since Monty has no generator objects.
+    Generator {
+        elt: Box<ExprLoc>,
+        generators: Vec<Comprehension>,
+    },
     /// Raw lambda expression from the parser, before preparation.
     ///
     /// This variant is produced during parsing and contains unprepared data.
@@ -283,7 +292,8 @@ pub enum Expr {
 /// Target for tuple unpacking - can be a single name, nested tuple, or starred target.
 ///
 /// Supports recursive structures like `(a, b), c` or `a, (b, c)`.
-/// Also supports starred targets like `first, *rest = [1, 2, 3, 4]`.
+/// Also supports starred targets like `first, *rest = [1, 2, 3, 4]`, and subscript and
+/// attribute targets like `items[i], items[j] = items[j], items[i]`.
 /// Used in assignment statements, for loop targets, and comprehension targets.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum UnpackTarget {
@@ -300,6 +310,47 @@ pub enum UnpackTarget {
     ///
     /// Only one starred target is allowed per unpacking level.
     Starred(Identifier),
+    /// Subscript target: `items[i]` - the object and index are evaluated after the
+    /// right-hand side, when the value is stored.
+    ///
+    /// Not allowed in comprehension targets.
+    Subscript {
+        object: ExprLoc,
+        index: ExprLoc,
+        /// Position of the subscript expression for traceback carets
+        position: CodeRange,
+    },
+    /// Attribute target: `point.x` - the object is evaluated when the value is stored.
+    ///
+    /// Not allowed in comprehension targets.
+    Attr {
+        object: ExprLoc,
+        attr: EitherStr,
+        /// Position of the attribute expression for traceback carets
+        position: CodeRange,
+    },
+}
+
+impl UnpackTarget {
+    /// Calls `f` with each expression evaluated when storing into this target, in order.
+    ///
+    /// Only subscript and attribute targets evaluate expressions; names and starred names
+    /// are just bound.
+    pub fn for_each_expr<'a>(&'a self, f: &mut impl FnMut(&'a ExprLoc)) {
+        match self {
+            Self::Name(_) | Self::Starred(_) => {}
+            Self::Tuple { targets, .. } => {
+                for target in targets {
+                    target.for_each_expr(f);
+                }
+            }
+            Self::Subscript { object, index, .. } => {
+                f(object);
+                f(index);
+            }
+            Self::Attr { object, .. } => f(object),
+        }
+    }
 }
 
 /// A generator clause in a comprehension: `for target in iter [if cond1] [if cond2]...`
@@ -398,6 +449,11 @@ pub enum Node<F> {
     Return(ExprLoc),
     ReturnNone,
     Raise(Option<ExprLoc>),
+    /// `raise exc from cause`, where `cause` may be `None` to suppress the context.
+    RaiseFrom {
+        exc: ExprLoc,
+        cause: ExprLoc,
+    },
     Assert {
         test: ExprLoc,
         msg: Option<ExprLoc>,
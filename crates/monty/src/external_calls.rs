@@ -0,0 +1,359 @@
+//! Static listing of the external functions a program references.
+//!
+//! External functions occupy the first slots of the global namespace (see
+//! `Prepare::new_module`), so after `prepare` every reference to one is an identifier
+//! whose namespace slot is below the number of external functions: a `Local` at module
+//! level, where the local namespace is the global one, or a `Global` inside a function.
+//!
+//! The walk runs on the prepared nodes before constant folding, so calls inside
+//! statically dead branches are still reported. Hosts use the result to reject a program
+//! that calls an unknown or disallowed function before running any of it.
+
+use crate::{
+    args::ArgExprs,
+    exception_public::CodeLoc,
+    expressions::{
+        Callable, Comprehension, Expr, ExprLoc, Identifier, NameScope, Node, PreparedFunctionDef, PreparedNode,
+    },
+    fstring::{FStringPart, FormatSpec},
+    parse::{CodeRange, Try},
+};
+
+/// One place in the source where an external function is referenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct ExternalCallSite {
+    /// Start of the call expression, or of the name if it isn't called directly.
+    pub start: CodeLoc,
+    /// End of the call expression, or of the name if it isn't called directly.
+    pub end: CodeLoc,
+    /// Number of positional and keyword arguments passed.
+    ///
+    /// `None` when the count can't be known before running: the call unpacks `*args` or
+    /// `**kwargs`, or the function is referenced without being called directly (e.g. passed
+    /// to `map()` or assigned to another name).
+    pub arg_count: Option<usize>,
+}
+
+/// An external function referenced by a program, with every place it is referenced.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExternalFunctionUsage {
+    /// Name of the external function, as passed to `MontyRun::new`.
+    pub name: String,
+    /// Call sites and other references, in source order.
+    pub call_sites: Vec<ExternalCallSite>,
+}
+
+impl ExternalFunctionUsage {
+    /// Returns the largest number of arguments any call site can pass.
+    ///
+    /// Returns `None` if any call site's argument count is unknown.
+    #[must_use]
+    pub fn max_args(&self) -> Option<usize> {
+        self.call_sites
+            .iter()
+            .try_fold(0, |max, site| site.arg_count.map(|count| max.max(count)))
+    }
+}
+
+/// Collects the external functions referenced by `nodes`, in the order they were declared.
+///
+/// External functions the program never references are omitted.
+pub(crate) fn collect_external_calls(
+    nodes: &[PreparedNode],
+    external_functions: &[String],
+) -> Vec<ExternalFunctionUsage> {
+    let mut collector = Collector {
+        sites: vec![Vec::new(); external_functions.len()],
+        in_function: false,
+    };
+    collector.visit_block(nodes);
+    external_functions
+        .iter()
+        .zip(collector.sites)
+        .filter(|(_, call_sites)| !call_sites.is_empty())
+        .map(|(name, call_sites)| ExternalFunctionUsage {
+            name: name.clone(),
+            call_sites,
+        })
+        .collect()
+}
+
+struct Collector {
+    /// Sites found so far, indexed by external function slot.
+    sites: Vec<Vec<ExternalCallSite>>,
+    /// Whether the walk is inside a function body, where global names have `Global` scope.
+    in_function: bool,
+}
+
+impl Collector {
+    /// Returns the external function slot `ident` refers to, if any.
+    fn external_index(&self, ident: &Identifier) -> Option<usize> {
+        let refers_to_global = match ident.scope {
+            NameScope::Global => true,
+            NameScope::Local | NameScope::LocalUnassigned => !self.in_function,
+            NameScope::Cell => false,
+        };
+        let index = ident.namespace_id().index();
+        (refers_to_global && index < self.sites.len()).then_some(index)
+    }
+
+    fn record(&mut self, ident: &Identifier, position: CodeRange, arg_count: Option<usize>) {
+        if let Some(index) = self.external_index(ident) {
+            self.sites[index].push(ExternalCallSite {
+                start: position.start(),
+                end: position.end(),
+                arg_count,
+            });
+        }
+    }
+
+    fn visit_block(&mut self, nodes: &[PreparedNode]) {
+        for node in nodes {
+            self.visit_node(node);
+        }
+    }
+
+    fn visit_node(&mut self, node: &PreparedNode) {
+        match node {
+            Node::Expr(expr) | Node::Return(expr) => self.visit_expr(expr),
+            Node::Raise(expr) => {
+                if let Some(expr) = expr {
+                    self.visit_expr(expr);
+                }
+            }
+            Node::RaiseFrom { exc, cause } => {
+                self.visit_expr(exc);
+                self.visit_expr(cause);
+            }
+            Node::Assert { test, msg } => {
+                self.visit_expr(test);
+                if let Some(msg) = msg {
+                    self.visit_expr(msg);
+                }
+            }
+            Node::Assign { object, .. } => self.visit_expr(object),
+            Node::UnpackAssign { targets, object, .. } => {
+                self.visit_expr(object);
+                for target in targets {
+                    target.for_each_expr(&mut |expr| self.visit_expr(expr));
+                }
+            }
+            Node::OpAssign { target, object, .. } => {
+                // `ext += x` reads `ext` before rebinding it
+                self.record(target, target.position, None);
+                self.visit_expr(object);
+            }
+            Node::SubscriptAssign {
+                target, index, value, ..
+            } => {
+                self.record(target, target.position, None);
+                self.visit_expr(index);
+                self.visit_expr(value);
+            }
+            Node::AttrAssign { object, value, .. } => {
+                self.visit_expr(object);
+                self.visit_expr(value);
+            }
+            Node::For {
+                target,
+                iter,
+                body,
+                or_else,
+            } => {
+                self.visit_expr(iter);
+                target.for_each_expr(&mut |expr| self.visit_expr(expr));
+                self.visit_block(body);
+                self.visit_block(or_else);
+            }
+            Node::While { test, body, or_else } | Node::If { test, body, or_else } => {
+                self.visit_expr(test);
+                self.visit_block(body);
+                self.visit_block(or_else);
+            }
+            Node::FunctionDef(func_def) => self.visit_function(func_def),
+            Node::Try(Try {
+                body,
+                handlers,
+                or_else,
+                finally,
+            }) => {
+                self.visit_block(body);
+                for handler in handlers {
+                    if let Some(exc_type) = &handler.exc_type {
+                        self.visit_expr(exc_type);
+                    }
+                    self.visit_block(&handler.body);
+                }
+                self.visit_block(or_else);
+                self.visit_block(finally);
+            }
+            Node::Pass
+            | Node::ReturnNone
+            | Node::Break { .. }
+            | Node::Continue { .. }
+            | Node::Global { .. }
+            | Node::Nonlocal { .. }
+            | Node::Import { .. }
+            | Node::ImportFrom { .. } => {}
+        }
+    }
+
+    fn visit_function(&mut self, func_def: &PreparedFunctionDef) {
+        // defaults are evaluated in the enclosing scope when the function is defined
+        for default in &func_def.default_exprs {
+            self.visit_expr(default);
+        }
+        let outer = std::mem::replace(&mut self.in_function, true);
+        self.visit_block(&func_def.body);
+        self.in_function = outer;
+    }
+
+    fn visit_exprs(&mut self, exprs: &[ExprLoc]) {
+        for expr in exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_args(&mut self, args: &ArgExprs) {
+        match args {
+            ArgExprs::Empty => {}
+            ArgExprs::One(arg) => self.visit_expr(arg),
+            ArgExprs::Two(first, second) => {
+                self.visit_expr(first);
+                self.visit_expr(second);
+            }
+            ArgExprs::Args(args) => self.visit_exprs(args),
+            ArgExprs::Kwargs(kwargs) => {
+                for kwarg in kwargs {
+                    self.visit_expr(&kwarg.value);
+                }
+            }
+            ArgExprs::ArgsKargs {
+                args,
+                var_args,
+                kwargs,
+                var_kwargs,
+            } => {
+                self.visit_exprs(args.as_deref().unwrap_or_default());
+                if let Some(var_args) = var_args {
+                    self.visit_expr(var_args);
+                }
+                for kwarg in kwargs.as_deref().unwrap_or_default() {
+                    self.visit_expr(&kwarg.value);
+                }
+                if let Some(var_kwargs) = var_kwargs {
+                    self.visit_expr(var_kwargs);
+                }
+            }
+        }
+    }
+
+    fn visit_generators(&mut self, generators: &[Comprehension]) {
+        for generator in generators {
+            self.visit_expr(&generator.iter);
+            self.visit_exprs(&generator.ifs);
+        }
+    }
+
+    fn visit_fstring(&mut self, parts: &[FStringPart]) {
+        for part in parts {
+            if let FStringPart::Interpolation { expr, format_spec, .. } = part {
+                self.visit_expr(expr);
+                if let Some(FormatSpec::Dynamic(spec_parts)) = format_spec {
+                    self.visit_fstring(spec_parts);
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr_loc: &ExprLoc) {
+        match &expr_loc.expr {
+            Expr::Name(ident) => self.record(ident, ident.position, None),
+            Expr::Call { callable, args } => {
+                if let Callable::Name(ident) = callable {
+                    self.record(ident, expr_loc.position, static_arg_count(args));
+                }
+                self.visit_args(args);
+            }
+            Expr::AttrCall { object, args, .. } => {
+                self.visit_expr(object);
+                self.visit_args(args);
+            }
+            Expr::IndirectCall { callable, args } => {
+                self.visit_expr(callable);
+                self.visit_args(args);
+            }
+            Expr::AttrGet { object, .. }
+            | Expr::Not(object)
+            | Expr::UnaryMinus(object)
+            | Expr::UnaryPlus(object)
+            | Expr::UnaryInvert(object)
+            | Expr::Await(object) => self.visit_expr(object),
+            Expr::Op { left, right, .. } | Expr::CmpOp { left, right, .. } => {
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            Expr::ChainCmp { left, comparisons } => {
+                self.visit_expr(left);
+                for (_, operand) in comparisons {
+                    self.visit_expr(operand);
+                }
+            }
+            Expr::List(elements) | Expr::Tuple(elements) | Expr::Set(elements) => self.visit_exprs(elements),
+            Expr::Dict(pairs) => {
+                for (key, value) in pairs {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expr::Subscript { object, index } => {
+                self.visit_expr(object);
+                self.visit_expr(index);
+            }
+            Expr::Slice { lower, upper, step } => {
+                for part in [lower, upper, step].into_iter().flatten() {
+                    self.visit_expr(part);
+                }
+            }
+            Expr::FString(parts) => self.visit_fstring(parts),
+            Expr::IfElse { test, body, orelse } => {
+                self.visit_expr(test);
+                self.visit_expr(body);
+                self.visit_expr(orelse);
+            }
+            Expr::ListComp { elt, generators }
+            | Expr::SetComp { elt, generators }
+            | Expr::Generator { elt, generators } => {
+                self.visit_expr(elt);
+                self.visit_generators(generators);
+            }
+            Expr::DictComp { key, value, generators } => {
+                self.visit_expr(key);
+                self.visit_expr(value);
+                self.visit_generators(generators);
+            }
+            Expr::Lambda { func_def } => self.visit_function(func_def),
+            Expr::Named { value, .. } => self.visit_expr(value),
+            Expr::Literal(_) | Expr::Builtin(_) | Expr::LambdaRaw { .. } => {}
+        }
+    }
+}
+
+/// Returns how many positional and keyword arguments a call passes, or `None` if it
+/// unpacks `*args` or `**kwargs`.
+fn static_arg_count(args: &ArgExprs) -> Option<usize> {
+    match args {
+        ArgExprs::Empty => Some(0),
+        ArgExprs::One(_) => Some(1),
+        ArgExprs::Two(..) => Some(2),
+        ArgExprs::Args(args) => Some(args.len()),
+        ArgExprs::Kwargs(kwargs) => Some(kwargs.len()),
+        ArgExprs::ArgsKargs {
+            args,
+            var_args: None,
+            kwargs,
+            var_kwargs: None,
+        } => Some(args.as_ref().map_or(0, Vec::len) + kwargs.as_ref().map_or(0, Vec::len)),
+        ArgExprs::ArgsKargs { .. } => None,
+    }
+}
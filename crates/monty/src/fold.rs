@@ -0,0 +1,560 @@
+//! Constant folding and dead-code elimination over prepared AST nodes.
+//!
+//! Runs after `prepare` and before bytecode compilation, so every name is already
+//! resolved and scope analysis is unaffected by the branches removed here. The pass
+//! only rewrites expressions whose result is fully determined at compile time and
+//! cannot raise:
+//!
+//! - integer arithmetic and bitwise operators, skipped if the result would overflow
+//!   `i64` (the runtime promotes those to `LongInt`) or divide by zero
+//! - float `+`, `-`, `*`
+//! - string concatenation, up to [`MAX_FOLDED_STR_LEN`] bytes
+//! - unary operators, `not`, `and`/`or` and `x if c else y` with a constant operand
+//! - f-string pieces: adjacent literal text is merged and constant str/int
+//!   interpolations without a conversion or format spec become literal text
+//! - `if`/`while` statements with a constant test keep only the branch that can run
+//!
+//! Anything else is left for the VM, so folding never changes which exception a
+//! program raises.
+
+use num_integer::Integer;
+
+use crate::{
+    args::ArgExprs,
+    expressions::{
+        Comprehension, Expr, ExprLoc, Literal, Node, Operator, PreparedFunctionDef, PreparedNode, UnpackTarget,
+    },
+    fstring::{ConversionFlag, FStringPart, FormatSpec},
+    intern::InternerBuilder,
+    parse::{ExceptHandler, Try},
+};
+
+/// Longest string the folder will build from concatenating literals.
+///
+/// Folded strings are stored in the interner for the life of the program, so this keeps
+/// a long chain of `+` from bloating it; longer results are concatenated at runtime.
+pub(crate) const MAX_FOLDED_STR_LEN: usize = 4096;
+
+/// Folds constant expressions and removes statically dead branches from `nodes`.
+///
+/// New strings produced by folding are interned into `interner`.
+pub(crate) fn fold_constants(nodes: Vec<PreparedNode>, interner: &mut InternerBuilder) -> Vec<PreparedNode> {
+    Folder { interner }.fold_block(nodes)
+}
+
+struct Folder<'a> {
+    interner: &'a mut InternerBuilder,
+}
+
+impl Folder<'_> {
+    fn fold_block(&mut self, nodes: Vec<PreparedNode>) -> Vec<PreparedNode> {
+        let mut folded = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            self.fold_node(node, &mut folded);
+        }
+        folded
+    }
+
+    /// Folds one statement, appending the result (zero or more statements) to `out`.
+    fn fold_node(&mut self, node: PreparedNode, out: &mut Vec<PreparedNode>) {
+        let node = match node {
+            Node::Expr(expr) => Node::Expr(self.fold_expr(expr)),
+            Node::Return(expr) => Node::Return(self.fold_expr(expr)),
+            Node::Raise(expr) => Node::Raise(expr.map(|e| self.fold_expr(e))),
+            Node::RaiseFrom { exc, cause } => Node::RaiseFrom {
+                exc: self.fold_expr(exc),
+                cause: self.fold_expr(cause),
+            },
+            Node::Assert { test, msg } => Node::Assert {
+                test: self.fold_expr(test),
+                msg: msg.map(|m| self.fold_expr(m)),
+            },
+            Node::Assign { target, object } => Node::Assign {
+                target,
+                object: self.fold_expr(object),
+            },
+            Node::UnpackAssign {
+                targets,
+                targets_position,
+                object,
+            } => Node::UnpackAssign {
+                targets: targets.into_iter().map(|target| self.fold_target(target)).collect(),
+                targets_position,
+                object: self.fold_expr(object),
+            },
+            Node::OpAssign { target, op, object } => Node::OpAssign {
+                target,
+                op,
+                object: self.fold_expr(object),
+            },
+            Node::SubscriptAssign {
+                target,
+                index,
+                value,
+                target_position,
+            } => Node::SubscriptAssign {
+                target,
+                index: self.fold_expr(index),
+                value: self.fold_expr(value),
+                target_position,
+            },
+            Node::AttrAssign {
+                object,
+                attr,
+                target_position,
+                value,
+            } => Node::AttrAssign {
+                object: self.fold_expr(object),
+                attr,
+                target_position,
+                value: self.fold_expr(value),
+            },
+            Node::For {
+                target,
+                iter,
+                body,
+                or_else,
+            } => Node::For {
+                target: self.fold_target(target),
+                iter: self.fold_expr(iter),
+                body: self.fold_block(body),
+                or_else: self.fold_block(or_else),
+            },
+            Node::While { test, body, or_else } => {
+                let test = self.fold_expr(test);
+                // `while False:` never enters the body, so only the else block runs
+                if self.truthiness(&test.expr) == Some(false) {
+                    out.extend(self.fold_block(or_else));
+                    return;
+                }
+                Node::While {
+                    test,
+                    body: self.fold_block(body),
+                    or_else: self.fold_block(or_else),
+                }
+            }
+            Node::If { test, body, or_else } => {
+                let test = self.fold_expr(test);
+                match self.truthiness(&test.expr) {
+                    Some(true) => {
+                        out.extend(self.fold_block(body));
+                        return;
+                    }
+                    Some(false) => {
+                        out.extend(self.fold_block(or_else));
+                        return;
+                    }
+                    None => Node::If {
+                        test,
+                        body: self.fold_block(body),
+                        or_else: self.fold_block(or_else),
+                    },
+                }
+            }
+            Node::FunctionDef(func_def) => Node::FunctionDef(self.fold_function(func_def)),
+            Node::Try(Try {
+                body,
+                handlers,
+                or_else,
+                finally,
+            }) => Node::Try(Try {
+                body: self.fold_block(body),
+                handlers: handlers
+                    .into_iter()
+                    .map(|handler| ExceptHandler {
+                        exc_type: handler.exc_type.map(|e| self.fold_expr(e)),
+                        name: handler.name,
+                        body: self.fold_block(handler.body),
+                    })
+                    .collect(),
+                or_else: self.fold_block(or_else),
+                finally: self.fold_block(finally),
+            }),
+            node @ (Node::Pass
+            | Node::ReturnNone
+            | Node::Break { .. }
+            | Node::Continue { .. }
+            | Node::Global { .. }
+            | Node::Nonlocal { .. }
+            | Node::Import { .. }
+            | Node::ImportFrom { .. }) => node,
+        };
+        out.push(node);
+    }
+
+    fn fold_function(&mut self, mut func_def: PreparedFunctionDef) -> PreparedFunctionDef {
+        func_def.body = self.fold_block(std::mem::take(&mut func_def.body));
+        func_def.default_exprs = std::mem::take(&mut func_def.default_exprs)
+            .into_iter()
+            .map(|e| self.fold_expr(e))
+            .collect();
+        func_def
+    }
+
+    fn fold_boxed(&mut self, expr: Box<ExprLoc>) -> Box<ExprLoc> {
+        Box::new(self.fold_expr(*expr))
+    }
+
+    fn fold_exprs(&mut self, exprs: Vec<ExprLoc>) -> Vec<ExprLoc> {
+        exprs.into_iter().map(|e| self.fold_expr(e)).collect()
+    }
+
+    fn fold_args(&mut self, mut args: Box<ArgExprs>) -> Box<ArgExprs> {
+        args.prepare_args(|expr| Ok(self.fold_expr(expr)))
+            .expect("constant folding never fails");
+        args
+    }
+
+    fn fold_target(&mut self, target: UnpackTarget) -> UnpackTarget {
+        match target {
+            UnpackTarget::Name(_) | UnpackTarget::Starred(_) => target,
+            UnpackTarget::Tuple { targets, position } => UnpackTarget::Tuple {
+                targets: targets.into_iter().map(|target| self.fold_target(target)).collect(),
+                position,
+            },
+            UnpackTarget::Subscript {
+                object,
+                index,
+                position,
+            } => UnpackTarget::Subscript {
+                object: self.fold_expr(object),
+                index: self.fold_expr(index),
+                position,
+            },
+            UnpackTarget::Attr { object, attr, position } => UnpackTarget::Attr {
+                object: self.fold_expr(object),
+                attr,
+                position,
+            },
+        }
+    }
+
+    fn fold_generators(&mut self, generators: Vec<Comprehension>) -> Vec<Comprehension> {
+        generators
+            .into_iter()
+            .map(|generator| Comprehension {
+                target: generator.target,
+                iter: self.fold_expr(generator.iter),
+                ifs: self.fold_exprs(generator.ifs),
+            })
+            .collect()
+    }
+
+    /// Folds the children of `expr_loc`, then the expression itself where possible.
+    fn fold_expr(&mut self, expr_loc: ExprLoc) -> ExprLoc {
+        let ExprLoc { position, expr } = expr_loc;
+        let expr = match expr {
+            Expr::Op { left, op, right } => {
+                let left = self.fold_expr(*left);
+                let right = self.fold_expr(*right);
+                match self.fold_binary(&left.expr, &op, &right.expr) {
+                    Some(Folded::Literal(literal)) => Expr::Literal(literal),
+                    Some(Folded::Left) => return ExprLoc { position, ..left },
+                    Some(Folded::Right) => return ExprLoc { position, ..right },
+                    None => Expr::Op {
+                        left: Box::new(left),
+                        op,
+                        right: Box::new(right),
+                    },
+                }
+            }
+            Expr::CmpOp { left, op, right } => Expr::CmpOp {
+                left: self.fold_boxed(left),
+                op,
+                right: self.fold_boxed(right),
+            },
+            Expr::ChainCmp { left, comparisons } => Expr::ChainCmp {
+                left: self.fold_boxed(left),
+                comparisons: comparisons.into_iter().map(|(op, e)| (op, self.fold_expr(e))).collect(),
+            },
+            Expr::Call { callable, args } => Expr::Call {
+                callable,
+                args: self.fold_args(args),
+            },
+            Expr::AttrCall { object, attr, args } => Expr::AttrCall {
+                object: self.fold_boxed(object),
+                attr,
+                args: self.fold_args(args),
+            },
+            Expr::IndirectCall { callable, args } => Expr::IndirectCall {
+                callable: self.fold_boxed(callable),
+                args: self.fold_args(args),
+            },
+            Expr::AttrGet { object, attr } => Expr::AttrGet {
+                object: self.fold_boxed(object),
+                attr,
+            },
+            Expr::List(elements) => Expr::List(self.fold_exprs(elements)),
+            Expr::Tuple(elements) => Expr::Tuple(self.fold_exprs(elements)),
+            Expr::Set(elements) => Expr::Set(self.fold_exprs(elements)),
+            Expr::Dict(pairs) => Expr::Dict(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (self.fold_expr(k), self.fold_expr(v)))
+                    .collect(),
+            ),
+            Expr::Subscript { object, index } => Expr::Subscript {
+                object: self.fold_boxed(object),
+                index: self.fold_boxed(index),
+            },
+            Expr::Slice { lower, upper, step } => Expr::Slice {
+                lower: lower.map(|e| self.fold_boxed(e)),
+                upper: upper.map(|e| self.fold_boxed(e)),
+                step: step.map(|e| self.fold_boxed(e)),
+            },
+            Expr::Not(operand) => {
+                let operand = self.fold_expr(*operand);
+                match self.truthiness(&operand.expr) {
+                    Some(truthy) => Expr::Literal(Literal::Bool(!truthy)),
+                    None => Expr::Not(Box::new(operand)),
+                }
+            }
+            Expr::UnaryMinus(operand) => {
+                let operand = self.fold_expr(*operand);
+                match operand.expr {
+                    Expr::Literal(Literal::Int(v)) if v != i64::MIN => Expr::Literal(Literal::Int(-v)),
+                    Expr::Literal(Literal::Float(v)) => Expr::Literal(Literal::Float(-v)),
+                    _ => Expr::UnaryMinus(Box::new(operand)),
+                }
+            }
+            Expr::UnaryPlus(operand) => {
+                let operand = self.fold_expr(*operand);
+                match operand.expr {
+                    Expr::Literal(literal @ (Literal::Int(_) | Literal::Float(_))) => Expr::Literal(literal),
+                    _ => Expr::UnaryPlus(Box::new(operand)),
+                }
+            }
+            Expr::UnaryInvert(operand) => {
+                let operand = self.fold_expr(*operand);
+                match operand.expr {
+                    Expr::Literal(Literal::Int(v)) => Expr::Literal(Literal::Int(!v)),
+                    _ => Expr::UnaryInvert(Box::new(operand)),
+                }
+            }
+            Expr::Await(value) => Expr::Await(self.fold_boxed(value)),
+            Expr::FString(parts) => self.fold_fstring(parts),
+            Expr::IfElse { test, body, orelse } => {
+                let test = self.fold_expr(*test);
+                match self.truthiness(&test.expr) {
+                    Some(true) => {
+                        return ExprLoc {
+                            position,
+                            ..self.fold_expr(*body)
+                        };
+                    }
+                    Some(false) => {
+                        return ExprLoc {
+                            position,
+                            ..self.fold_expr(*orelse)
+                        };
+                    }
+                    None => Expr::IfElse {
+                        test: Box::new(test),
+                        body: self.fold_boxed(body),
+                        orelse: self.fold_boxed(orelse),
+                    },
+                }
+            }
+            Expr::ListComp { elt, generators } => Expr::ListComp {
+                elt: self.fold_boxed(elt),
+                generators: self.fold_generators(generators),
+            },
+            Expr::SetComp { elt, generators } => Expr::SetComp {
+                elt: self.fold_boxed(elt),
+                generators: self.fold_generators(generators),
+            },
+            Expr::Generator { elt, generators } => Expr::Generator {
+                elt: self.fold_boxed(elt),
+                generators: self.fold_generators(generators),
+            },
+            Expr::DictComp { key, value, generators } => Expr::DictComp {
+                key: self.fold_boxed(key),
+                value: self.fold_boxed(value),
+                generators: self.fold_generators(generators),
+            },
+            Expr::Lambda { func_def } => Expr::Lambda {
+                func_def: Box::new(self.fold_function(*func_def)),
+            },
+            Expr::Named { target, value } => Expr::Named {
+                target,
+                value: self.fold_boxed(value),
+            },
+            expr @ (Expr::Literal(_) | Expr::Builtin(_) | Expr::Name(_) | Expr::LambdaRaw { .. }) => expr,
+        };
+        ExprLoc { position, expr }
+    }
+
+    /// Folds a binary operator whose operands have already been folded.
+    fn fold_binary(&mut self, left: &Expr, op: &Operator, right: &Expr) -> Option<Folded> {
+        // `and`/`or` only need the left operand to be constant
+        if matches!(op, Operator::And | Operator::Or) {
+            let truthy = self.truthiness(left)?;
+            return Some(if truthy == matches!(op, Operator::And) {
+                Folded::Right
+            } else {
+                Folded::Left
+            });
+        }
+
+        let (Expr::Literal(left), Expr::Literal(right)) = (left, right) else {
+            return None;
+        };
+        let literal = match (left, right) {
+            (Literal::Int(a), Literal::Int(b)) => Literal::Int(fold_int_op(*a, op, *b)?),
+            (Literal::Float(a), Literal::Float(b)) => Literal::Float(match op {
+                Operator::Add => a + b,
+                Operator::Sub => a - b,
+                Operator::Mult => a * b,
+                _ => return None,
+            }),
+            (Literal::Str(a), Literal::Str(b)) if *op == Operator::Add => {
+                let (a, b) = (self.interner.get_str(*a), self.interner.get_str(*b));
+                if a.len() + b.len() > MAX_FOLDED_STR_LEN {
+                    return None;
+                }
+                let concat = format!("{a}{b}");
+                Literal::Str(self.interner.intern(&concat))
+            }
+            _ => return None,
+        };
+        Some(Folded::Literal(literal))
+    }
+
+    /// Merges constant f-string pieces, collapsing a fully constant f-string to a literal.
+    fn fold_fstring(&mut self, parts: Vec<FStringPart>) -> Expr {
+        let mut folded: Vec<FStringPart> = Vec::with_capacity(parts.len());
+        // text of the literal run currently being built, flushed on the next interpolation
+        let mut text: Option<String> = None;
+        for part in parts {
+            let literal_text = match part {
+                FStringPart::Literal(id) => self.interner.get_str(id).to_owned(),
+                FStringPart::Interpolation {
+                    expr,
+                    conversion,
+                    format_spec,
+                    debug_prefix,
+                } => {
+                    let expr = self.fold_boxed(expr);
+                    let format_spec = match format_spec {
+                        Some(FormatSpec::Dynamic(spec_parts)) => Some(FormatSpec::Dynamic(
+                            spec_parts.into_iter().map(|p| self.fold_fstring_part(p)).collect(),
+                        )),
+                        spec => spec,
+                    };
+                    let constant = match &expr.expr {
+                        Expr::Literal(Literal::Str(id))
+                            if conversion == ConversionFlag::None
+                                && format_spec.is_none()
+                                && debug_prefix.is_none() =>
+                        {
+                            Some(self.interner.get_str(*id).to_owned())
+                        }
+                        Expr::Literal(Literal::Int(v))
+                            if conversion == ConversionFlag::None
+                                && format_spec.is_none()
+                                && debug_prefix.is_none() =>
+                        {
+                            Some(v.to_string())
+                        }
+                        _ => None,
+                    };
+                    if let Some(constant) = constant {
+                        constant
+                    } else {
+                        if let Some(text) = text.take() {
+                            folded.push(FStringPart::Literal(self.interner.intern(&text)));
+                        }
+                        folded.push(FStringPart::Interpolation {
+                            expr,
+                            conversion,
+                            format_spec,
+                            debug_prefix,
+                        });
+                        continue;
+                    }
+                }
+            };
+            text.get_or_insert_with(String::new).push_str(&literal_text);
+        }
+
+        if folded.is_empty() {
+            let text = text.unwrap_or_default();
+            return Expr::Literal(Literal::Str(self.interner.intern(&text)));
+        }
+        if let Some(text) = text {
+            folded.push(FStringPart::Literal(self.interner.intern(&text)));
+        }
+        Expr::FString(folded)
+    }
+
+    /// Folds the expressions inside a nested format-spec part without merging pieces.
+    fn fold_fstring_part(&mut self, part: FStringPart) -> FStringPart {
+        match part {
+            FStringPart::Literal(id) => FStringPart::Literal(id),
+            FStringPart::Interpolation {
+                expr,
+                conversion,
+                format_spec,
+                debug_prefix,
+            } => FStringPart::Interpolation {
+                expr: self.fold_boxed(expr),
+                conversion,
+                format_spec,
+                debug_prefix,
+            },
+        }
+    }
+
+    /// Returns the truthiness of a constant expression, or `None` if it isn't constant.
+    fn truthiness(&self, expr: &Expr) -> Option<bool> {
+        let Expr::Literal(literal) = expr else {
+            return None;
+        };
+        match literal {
+            Literal::None => Some(false),
+            Literal::Ellipsis => Some(true),
+            Literal::Bool(b) => Some(*b),
+            Literal::Int(v) => Some(*v != 0),
+            Literal::Str(id) => Some(!self.interner.get_str(*id).is_empty()),
+            _ => None,
+        }
+    }
+}
+
+/// Result of folding a binary operator.
+enum Folded {
+    /// The operation evaluated to a new constant.
+    Literal(Literal),
+    /// The expression reduces to its left operand (e.g. `False and x`).
+    Left,
+    /// The expression reduces to its right operand (e.g. `True and x`).
+    Right,
+}
+
+/// Applies an integer operator, returning `None` when the result isn't an `i64` or the
+/// operation would raise (division by zero, negative shift counts).
+fn fold_int_op(a: i64, op: &Operator, b: i64) -> Option<i64> {
+    match op {
+        Operator::Add => a.checked_add(b),
+        Operator::Sub => a.checked_sub(b),
+        Operator::Mult => a.checked_mul(b),
+        // checked_div rejects both zero divisors and `i64::MIN // -1`
+        Operator::FloorDiv => a.checked_div(b).map(|_| a.div_floor(&b)),
+        Operator::Mod => a.checked_rem(b).map(|_| a.mod_floor(&b)),
+        Operator::Pow => u32::try_from(b).ok().and_then(|exp| a.checked_pow(exp)),
+        Operator::LShift => {
+            let shift = u32::try_from(b).ok().filter(|shift| *shift < 64)?;
+            let shifted = a << shift;
+            (shifted >> shift == a).then_some(shifted)
+        }
+        Operator::RShift => {
+            let shift = u32::try_from(b).ok()?;
+            Some(a >> shift.min(63))
+        }
+        Operator::BitAnd => Some(a & b),
+        Operator::BitOr => Some(a | b),
+        Operator::BitXor => Some(a ^ b),
+        // true division produces a float whose rounding the runtime owns
+        Operator::Div | Operator::MatMult | Operator::And | Operator::Or => None,
+    }
+}
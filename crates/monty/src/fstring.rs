@@ -253,7 +253,7 @@ impl std::fmt::Display for FormatError {
 ///
 /// Dispatches to the appropriate formatting function based on the value type and format spec:
 /// - Integers: `format_int`, `format_int_base`, `format_char`
-/// - Floats: `format_float_f`, `format_float_e`, `format_float_g`, `format_float_percent`
+/// - Floats: `format_float_default`, `format_float_f`, `format_float_e`, `format_float_g`, `format_float_percent`
 /// - Strings: `format_string`
 ///
 /// Returns a `ValueError` if the format type character is incompatible with the value type.
@@ -276,7 +276,8 @@ pub fn format_with_spec(
         (Value::Int(n), Some('c')) => Ok(format_char(*n, spec)?),
 
         // Float formatting
-        (Value::Float(f), None | Some('g' | 'G')) => Ok(format_float_g(*f, spec)),
+        (Value::Float(f), None) => Ok(format_float_default(*f, spec)),
+        (Value::Float(f), Some('g' | 'G')) => Ok(format_float_g(*f, spec)),
         (Value::Float(f), Some('f' | 'F')) => Ok(format_float_f(*f, spec)),
         (Value::Float(f), Some('e')) => Ok(format_float_e(*f, spec, false)),
         (Value::Float(f), Some('E')) => Ok(format_float_e(*f, spec, true)),
@@ -667,6 +668,79 @@ pub fn format_float_g(f: f64, spec: &ParsedFormatSpec) -> String {
     pad_string(&value, spec.width, align, spec.fill)
 }
 
+/// Formats a float with no presentation type, e.g. `f'{x:>10}'` or `f'{x:.3}'`.
+///
+/// Without a precision the digits are those of `repr(x)`. With one this is `g` formatting,
+/// except that fixed-point results keep at least one digit after the decimal point.
+pub fn format_float_default(f: f64, spec: &ParsedFormatSpec) -> String {
+    let Some(precision) = spec.precision else {
+        let repr = float_repr(f);
+        let value = match (repr.starts_with('-') || f.is_nan(), spec.sign) {
+            (false, Some('+')) => format!("+{repr}"),
+            (false, Some(' ')) => format!(" {repr}"),
+            _ => repr,
+        };
+        let align = spec.align.unwrap_or('>');
+        return pad_string(&value, spec.width, align, spec.fill);
+    };
+    let value = format_float_g(
+        f,
+        &ParsedFormatSpec {
+            precision: Some(precision),
+            width: 0,
+            ..spec.clone()
+        },
+    );
+    let value = if f.is_finite() && !value.contains(['.', 'e']) {
+        format!("{value}.0")
+    } else {
+        value
+    };
+    let align = spec.align.unwrap_or('>');
+    pad_string(&value, spec.width, align, spec.fill)
+}
+
+/// Returns CPython's `repr()` of a float, which `str()` shares.
+///
+/// The digits are the shortest that round-trip to the same `f64` (Rust's `{:e}` formatting
+/// computes these with Grisu, falling back to Dragon4), so `0.1` stays `0.1`. Like CPython,
+/// scientific notation is used when the decimal exponent is below -4 or at least 16.
+pub fn float_repr(f: f64) -> String {
+    if f.is_nan() {
+        return "nan".to_owned();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+    let scientific = format!("{f:e}");
+    let (mantissa, exp) = scientific
+        .split_once('e')
+        .expect("`{:e}` formatting always includes an exponent");
+    let exp: i32 = exp.parse().expect("`{:e}` exponent is a valid integer");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+
+    if !(-4..16).contains(&exp) {
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        return format!("{sign}{mantissa}e{exp_sign}{:02}", exp.unsigned_abs());
+    }
+    let digits = mantissa.replace('.', "");
+    let leading_zeros = usize::try_from(-exp).unwrap_or(0);
+    if leading_zeros > 0 {
+        let zeros = "0".repeat(leading_zeros - 1);
+        return format!("{sign}0.{zeros}{digits}");
+    }
+    let int_len = usize::try_from(exp).expect("exp is non-negative") + 1;
+    if digits.len() > int_len {
+        format!("{sign}{}.{}", &digits[..int_len], &digits[int_len..])
+    } else {
+        let zeros = "0".repeat(int_len - digits.len());
+        format!("{sign}{digits}{zeros}.0")
+    }
+}
+
 /// Applies ASCII conversion to a string (escapes non-ASCII characters).
 ///
 /// Used for the `!a` conversion flag in f-strings. Takes a string (typically a repr)
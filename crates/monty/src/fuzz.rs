@@ -0,0 +1,173 @@
+//! Entry points for fuzzing the inputs an embedder exposes to untrusted users.
+//!
+//! Which of Monty's inputs are attack surface depends on the host: most take source code,
+//! some also store snapshots where users can tamper with them, and each configures its
+//! own limits. The functions here run one of those surfaces the way Monty itself does and
+//! ignore every error, so any panic they produce is a bug. They are meant to be called
+//! from `cargo fuzz` targets in the embedding crate:
+//!
+//! ```ignore
+//! #![no_main]
+//! use libfuzzer_sys::fuzz_target;
+//! use monty::{ResourceLimits, fuzz};
+//!
+//! fuzz_target!(|input: (String, ResourceLimits)| {
+//!     fuzz::fuzz_run_with_limits(&input.0, &input.1);
+//! });
+//! ```
+//!
+//! With the `fuzzing` feature, [`ResourceLimits`] implements [`Arbitrary`] with every
+//! generated configuration bounded in time, memory and recursion, and [`FramedPayload`]
+//! wraps arbitrary bytes in a valid snapshot header, so the fuzzer reaches the decoder and
+//! the consistency checks behind it instead of stopping at the magic or the checksum.
+
+use std::time::Duration;
+
+pub use arbitrary;
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::{
+    LimitedTracker, MontyRepl, MontyRun, PrintWriter, ReplProgress, ResourceLimits, RunProgress,
+    sectest::resume_once,
+    snapshot_format::{self, SnapshotKind},
+};
+
+/// Longest `max_duration` an arbitrary `ResourceLimits` gets, and the time a loaded
+/// snapshot may run for.
+const MAX_FUZZ_DURATION: Duration = Duration::from_millis(100);
+
+/// Largest `max_memory` an arbitrary `ResourceLimits` gets, in bytes.
+const MAX_FUZZ_MEMORY: usize = 4 * 1024 * 1024;
+
+/// Deepest `max_recursion_depth` an arbitrary `ResourceLimits` gets.
+const MAX_FUZZ_RECURSION: usize = 200;
+
+/// Largest `max_str_len`, `max_list_len` and `max_dict_entries` an arbitrary
+/// `ResourceLimits` gets.
+const MAX_FUZZ_COLLECTION: usize = 100_000;
+
+/// Script name used by every entry point.
+const SCRIPT_NAME: &str = "fuzz.py";
+
+/// Generates limits that always stop a run within 100 ms and 4 MiB, with the remaining
+/// limits either unset or bounded.
+impl<'a> Arbitrary<'a> for ResourceLimits {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let max_millis = u64::try_from(MAX_FUZZ_DURATION.as_millis()).expect("fuzz duration fits in u64");
+        let mut limits = Self::new()
+            .max_duration(Duration::from_millis(u.int_in_range(1..=max_millis)?))
+            .max_memory(u.int_in_range(1..=MAX_FUZZ_MEMORY)?)
+            .max_recursion_depth(Some(u.int_in_range(1..=MAX_FUZZ_RECURSION)?))
+            .hide_resources(u.arbitrary()?);
+        if u.arbitrary()? {
+            limits = limits.max_allocations(u.int_in_range(0..=MAX_FUZZ_COLLECTION)?);
+        }
+        if u.arbitrary()? {
+            limits = limits.gc_interval(u.int_in_range(1..=1000)?);
+        }
+        if u.arbitrary()? {
+            limits = limits.max_str_len(u.int_in_range(0..=MAX_FUZZ_COLLECTION)?);
+        }
+        if u.arbitrary()? {
+            limits = limits.max_list_len(u.int_in_range(0..=MAX_FUZZ_COLLECTION)?);
+        }
+        if u.arbitrary()? {
+            limits = limits.max_dict_entries(u.int_in_range(0..=MAX_FUZZ_COLLECTION)?);
+        }
+        Ok(limits)
+    }
+}
+
+/// Which kind of serialized state a [`FramedPayload`] claims to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Arbitrary)]
+pub enum SnapshotTarget {
+    /// Bytes for `RunProgress::load()`.
+    RunProgress,
+    /// Bytes for `ReplProgress::load()`.
+    ReplProgress,
+    /// Bytes for `MontyRun::load_code()`.
+    Code,
+}
+
+impl From<SnapshotTarget> for SnapshotKind {
+    fn from(target: SnapshotTarget) -> Self {
+        match target {
+            SnapshotTarget::RunProgress => Self::RunProgress,
+            SnapshotTarget::ReplProgress => Self::ReplProgress,
+            SnapshotTarget::Code => Self::Code,
+        }
+    }
+}
+
+/// An arbitrary payload behind a valid snapshot header for the current Monty version.
+///
+/// Fuzzing `fuzz_snapshot_load` with raw bytes almost never gets past the header check;
+/// framing the payload exercises the postcard decoder and the snapshot validation instead.
+#[derive(Debug, Clone, PartialEq, Eq, Arbitrary)]
+pub struct FramedPayload {
+    /// The header to write.
+    pub target: SnapshotTarget,
+    /// The bytes following the header, normally the postcard encoding of the state.
+    pub payload: Vec<u8>,
+}
+
+impl FramedPayload {
+    /// Returns the header followed by the payload, with a matching checksum.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        snapshot_format::frame(self.target.into(), &self.payload)
+    }
+}
+
+/// Parses and compiles `code`, discarding the result and any error.
+pub fn fuzz_parse(code: &str) {
+    let _ = MontyRun::new(code.to_owned(), SCRIPT_NAME, vec![], vec![]);
+}
+
+/// Loads `bytes` as every kind of serialized state Monty accepts.
+///
+/// `RunProgress` snapshots that load are resumed by one step, with their time limit
+/// replaced by 100 ms since the limits stored in the snapshot are as arbitrary as the
+/// rest of it. Compiled code that loads is run with the same bound.
+pub fn fuzz_snapshot_load(bytes: &[u8]) {
+    if let Ok(mut progress) = RunProgress::<LimitedTracker>::load(bytes)
+        && bound_run_time(&mut progress)
+    {
+        let _ = resume_once(progress);
+    }
+    let _ = ReplProgress::<LimitedTracker>::load(bytes);
+    let _ = MontyRepl::<LimitedTracker>::load(bytes);
+    let _ = MontyRun::load(bytes);
+    if let Ok(runner) = MontyRun::load_code(bytes) {
+        let limits = ResourceLimits::new()
+            .max_duration(MAX_FUZZ_DURATION)
+            .max_memory(MAX_FUZZ_MEMORY);
+        let _ = runner.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Disabled);
+    }
+}
+
+/// Compiles and runs `code` under `limits`, discarding the result and any error.
+///
+/// The caller is responsible for bounding `limits`, e.g. by taking them from
+/// `ResourceLimits::arbitrary()`; unlimited code can run forever.
+pub fn fuzz_run_with_limits(code: &str, limits: &ResourceLimits) {
+    if let Ok(runner) = MontyRun::new(code.to_owned(), SCRIPT_NAME, vec![], vec![]) {
+        let _ = runner.run(vec![], LimitedTracker::new(limits.clone()), &mut PrintWriter::Disabled);
+    }
+}
+
+/// Caps the run time of a loaded snapshot, returning false for states without a tracker
+/// to cap.
+fn bound_run_time(progress: &mut RunProgress<LimitedTracker>) -> bool {
+    let tracker = match progress {
+        RunProgress::FunctionCall { state, .. }
+        | RunProgress::OsCall { state, .. }
+        | RunProgress::Emit { state, .. }
+        | RunProgress::Sleep { state, .. } => state.tracker_mut(),
+        RunProgress::Paused(state) => state.tracker_mut(),
+        RunProgress::Breakpoint(state) => state.tracker_mut(),
+        RunProgress::ResolveFutures(_) | RunProgress::Complete(_) => return false,
+    };
+    tracker.set_max_duration(MAX_FUZZ_DURATION);
+    true
+}
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    cell::RefCell,
     collections::hash_map::DefaultHasher,
     fmt::Write,
     hash::{Hash, Hasher},
@@ -12,34 +13,156 @@ use ahash::AHashSet;
 use num_integer::Integer;
 use smallvec::SmallVec;
 
+#[cfg(feature = "heap-audit")]
+use crate::audit::{AuditedEntry, HeapAudit};
 use crate::{
     args::ArgValues,
     asyncio::{Coroutine, GatherFuture, GatherItem},
-    exception_private::{ExcType, RunResult, SimpleException},
-    intern::{FunctionId, Interns, StringId},
+    exception_private::{ExcType, ExceptionInstance, RunResult},
+    intern::{FunctionId, InternBounds, Interns, StringId},
     io::PrintWriter,
-    resource::{DepthGuard, ResourceError, ResourceTracker, check_mult_size, check_repeat_size},
+    resource::{CollectionKind, DepthGuard, ResourceError, ResourceTracker, check_mult_size, check_repeat_size},
     types::{
         AttrCallResult, Bytes, Dataclass, Dict, FrozenSet, List, LongInt, Module, MontyIter, NamedTuple, Path, PyTrait,
-        Range, Set, Slice, Str, Tuple, Type, allocate_tuple,
+        Range, Set, Slice, Str, Tuple, Type, allocate_tuple, int::call_int_method, str::allocate_str,
     },
     value::{EitherStr, Value},
 };
 
 /// Unique identifier for values stored inside the heap arena.
+///
+/// Pairs a slot index with the generation of the value occupying it. Freed slots are
+/// reused by later allocations under the next generation, so an id kept past its value's
+/// lifetime no longer matches its slot and panics on access instead of silently aliasing
+/// whatever was allocated there next.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
-pub struct HeapId(usize);
+pub struct HeapId {
+    index: u32,
+    generation: u32,
+}
 
 impl HeapId {
+    /// Creates an id for the given slot index and generation.
+    fn new(index: usize, generation: u32) -> Self {
+        Self {
+            index: u32::try_from(index).expect("HeapId: heap index exceeds u32::MAX"),
+            generation,
+        }
+    }
+
     /// Returns the raw index value.
     #[inline]
     pub fn index(self) -> usize {
-        self.0
+        self.index as usize
+    }
+
+    /// Returns the id the same slot gets when it is next reused.
+    fn next_generation(self) -> Self {
+        Self {
+            index: self.index,
+            generation: self.generation.wrapping_add(1),
+        }
+    }
+
+    /// Returns an id that never refers to a live entry: the slot of the empty tuple, which is
+    /// never freed, under a later generation.
+    pub(crate) fn stale() -> Self {
+        EMPTY_TUPLE_ID.next_generation()
     }
 }
 
 /// The empty tuple is a singleton which is allocated at startup.
-const EMPTY_TUPLE_ID: HeapId = HeapId(0);
+const EMPTY_TUPLE_ID: HeapId = HeapId {
+    index: 0,
+    generation: 0,
+};
+
+/// Asserts that a heap entry still belongs to the generation `id` was issued for.
+macro_rules! check_generation {
+    ($entry:expr, $id:expr, $func_name:literal) => {
+        assert!(
+            $entry.generation == $id.generation,
+            concat!("Heap::", $func_name, ": stale HeapId, slot has been reused")
+        );
+    };
+}
+
+/// Mapping from old to new `HeapId`s produced by [`Heap::compact`].
+///
+/// Compaction renumbers live entries inside the heap itself; everything outside the heap
+/// that holds heap ids (operand stacks, frames, namespaces, the async scheduler) must be
+/// passed through the same map before execution resumes.
+///
+/// [`Heap::validate`] reuses the same traversal to check ids instead of rewriting them.
+#[derive(Debug)]
+pub(crate) struct HeapIdMap {
+    /// New id for each old slot index, `None` for slots that were free.
+    new_ids: Vec<Option<HeapId>>,
+    /// Set for maps made by `Heap::validate`: ids are left unchanged and checked instead.
+    check: Option<IdCheck>,
+}
+
+/// What a checking `HeapIdMap` checks values against, and the first problem it found.
+#[derive(Debug)]
+struct IdCheck {
+    /// Sizes of the interned tables that values' string and function ids index.
+    bounds: InternBounds,
+    /// Description of the first id that doesn't refer to a live entry or interned item.
+    problem: RefCell<Option<String>>,
+}
+
+impl IdCheck {
+    fn report(&self, problem: impl FnOnce() -> String) {
+        let mut first = self.problem.borrow_mut();
+        if first.is_none() {
+            *first = Some(problem());
+        }
+    }
+}
+
+impl HeapIdMap {
+    /// Rewrites `id` to its post-compaction value.
+    ///
+    /// # Panics
+    /// Panics if `id` referred to a freed slot, which means a dangling reference survived
+    /// reference counting.
+    pub fn remap(&self, id: &mut HeapId) {
+        let new_id = self.new_ids.get(id.index()).copied().flatten();
+        if let Some(check) = &self.check {
+            if new_id != Some(*id) {
+                check.report(|| format!("dangling heap id {id:?}"));
+            }
+            return;
+        }
+        *id = new_id.expect("HeapIdMap::remap: id refers to a freed slot");
+    }
+
+    /// Returns what the first id passed to a checking map that didn't refer to a live heap
+    /// entry or interned item referred to, e.g. `"dangling heap id ..."`.
+    pub fn problem(&self) -> Option<String> {
+        self.check.as_ref().and_then(|check| check.problem.borrow().clone())
+    }
+
+    /// Rewrites the id inside `value` if it is a `Value::Ref`.
+    ///
+    /// A checking map also checks the interned string and function ids of other values.
+    pub fn remap_value(&self, value: &mut Value) {
+        if let Value::Ref(id) = value {
+            self.remap(id);
+        } else if let Some(check) = &self.check
+            && let Err(problem) = check.bounds.check(value)
+        {
+            check.report(|| problem);
+        }
+    }
+
+    /// Rewrites every `Value::Ref` id in `values`.
+    pub fn remap_values<'v>(&self, values: impl IntoIterator<Item = &'v mut Value>) {
+        for value in values {
+            self.remap_value(value);
+        }
+    }
+}
 
 /// HeapData captures every runtime value that must live in the arena.
 ///
@@ -92,8 +215,9 @@ pub(crate) enum HeapData {
     /// An exception instance (e.g., `ValueError('message')`).
     ///
     /// Stored on the heap to keep `Value` enum small (16 bytes). Exceptions
-    /// are created when exception types are called or when `raise` is executed.
-    Exception(SimpleException),
+    /// are created when exception types are called or when a handler catches a raise.
+    /// They can hold refs to other heap values through attributes set by user code.
+    Exception(ExceptionInstance),
     /// A dataclass instance with fields and method references.
     ///
     /// Contains a class name, a Dict of field name -> value mappings, and a set
@@ -137,7 +261,7 @@ impl HeapData {
     /// Returns whether this heap data type can participate in reference cycles.
     ///
     /// Only container types that can hold references to other heap objects need to be
-    /// tracked for GC purposes. Leaf types like Str, Bytes, Range, and Exception cannot
+    /// tracked for GC purposes. Leaf types like Str, Bytes, Range, and LongInt cannot
     /// form cycles and should not count toward the GC allocation threshold.
     ///
     /// This optimization allows programs that allocate many leaf objects (like strings)
@@ -155,6 +279,7 @@ impl HeapData {
                 | Self::Closure(_, _, _)
                 | Self::FunctionDefaults(_, _)
                 | Self::Cell(_)
+                | Self::Exception(_)
                 | Self::Dataclass(_)
                 | Self::Iter(_)
                 | Self::Module(_)
@@ -163,6 +288,15 @@ impl HeapData {
         )
     }
 
+    /// Returns true for numbers that live on the heap rather than inline in `Value`.
+    ///
+    /// Their slots are pooled separately by `Heap::allocate` so numeric churn reuses
+    /// numeric slots.
+    #[inline]
+    pub fn is_boxed_number(&self) -> bool {
+        matches!(self, Self::LongInt(_))
+    }
+
     /// Returns whether this heap data currently contains any heap references (`Value::Ref`).
     ///
     /// Used during allocation to determine if this data could create reference cycles.
@@ -185,6 +319,7 @@ impl HeapData {
             }
             Self::FunctionDefaults(_, defaults) => defaults.iter().any(|v| matches!(v, Value::Ref(_))),
             Self::Cell(value) => matches!(value, Value::Ref(_)),
+            Self::Exception(e) => e.has_refs(),
             Self::Dataclass(dc) => dc.has_refs(),
             Self::Iter(iter) => iter.has_refs(),
             Self::Module(m) => m.has_refs(),
@@ -204,13 +339,7 @@ impl HeapData {
                         .any(|r| r.as_ref().is_some_and(|v| matches!(v, Value::Ref(_))))
             }
             // Leaf types cannot have refs
-            Self::Str(_)
-            | Self::Bytes(_)
-            | Self::Range(_)
-            | Self::Slice(_)
-            | Self::Exception(_)
-            | Self::LongInt(_)
-            | Self::Path(_) => false,
+            Self::Str(_) | Self::Bytes(_) | Self::Range(_) | Self::Slice(_) | Self::LongInt(_) | Self::Path(_) => false,
         }
     }
 
@@ -220,6 +349,44 @@ impl HeapData {
         matches!(self, Self::Coroutine(_))
     }
 
+    /// Rewrites every heap id held by this value after the heap has been compacted.
+    ///
+    /// Must visit exactly the references reported by `collect_child_ids`, plus any ids
+    /// that aren't references (like an iterator's cached container id).
+    fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        match self {
+            Self::List(list) => list.remap_heap_ids(map),
+            Self::Tuple(tuple) => tuple.remap_heap_ids(map),
+            Self::NamedTuple(nt) => nt.remap_heap_ids(map),
+            Self::Dict(dict) => dict.remap_heap_ids(map),
+            Self::Set(set) => set.remap_heap_ids(map),
+            Self::FrozenSet(fset) => fset.remap_heap_ids(map),
+            Self::Closure(_, cells, defaults) => {
+                cells.iter_mut().for_each(|cell_id| map.remap(cell_id));
+                map.remap_values(defaults);
+            }
+            Self::FunctionDefaults(_, defaults) => map.remap_values(defaults),
+            Self::Cell(value) => map.remap_value(value),
+            Self::Exception(e) => e.remap_heap_ids(map),
+            Self::Dataclass(dc) => dc.remap_heap_ids(map),
+            Self::Iter(iter) => iter.remap_heap_ids(map),
+            Self::Module(m) => m.remap_heap_ids(map),
+            Self::Coroutine(coro) => {
+                coro.frame_cells.iter_mut().for_each(|cell_id| map.remap(cell_id));
+                map.remap_values(&mut coro.namespace);
+            }
+            Self::GatherFuture(gather) => {
+                for item in &mut gather.items {
+                    if let GatherItem::Coroutine(coro_id) = item {
+                        map.remap(coro_id);
+                    }
+                }
+                map.remap_values(gather.results.iter_mut().flatten());
+            }
+            Self::Str(_) | Self::Bytes(_) | Self::Range(_) | Self::Slice(_) | Self::LongInt(_) | Self::Path(_) => {}
+        }
+    }
+
     /// Computes hash for immutable heap types that can be used as dict keys.
     ///
     /// Returns Some(hash) for immutable types (Str, Bytes, Tuple of hashables).
@@ -334,7 +501,7 @@ impl PyTrait for HeapData {
             Self::Cell(_) => Type::Cell,
             Self::Range(_) => Type::Range,
             Self::Slice(_) => Type::Slice,
-            Self::Exception(e) => e.py_type(),
+            Self::Exception(e) => e.exc().py_type(),
             Self::Dataclass(dc) => dc.py_type(heap),
             Self::Iter(_) => Type::Iterator,
             // LongInt is still `int` in Python - it's an implementation detail
@@ -360,7 +527,7 @@ impl PyTrait for HeapData {
             Self::Cell(v) => std::mem::size_of::<Value>() + v.py_estimate_size(),
             Self::Range(_) => std::mem::size_of::<Range>(),
             Self::Slice(s) => s.py_estimate_size(),
-            Self::Exception(e) => std::mem::size_of::<SimpleException>() + e.arg().map_or(0, String::len),
+            Self::Exception(e) => e.py_estimate_size(),
             Self::Dataclass(dc) => dc.py_estimate_size(),
             Self::Iter(_) => std::mem::size_of::<MontyIter>(),
             Self::LongInt(li) => li.estimate_size(),
@@ -488,6 +655,7 @@ impl PyTrait for HeapData {
                 }
             }
             Self::Cell(v) => v.py_dec_ref_ids(stack),
+            Self::Exception(e) => e.py_dec_ref_ids(stack),
             Self::Dataclass(dc) => dc.py_dec_ref_ids(stack),
             Self::Iter(iter) => iter.py_dec_ref_ids(stack),
             Self::Module(m) => m.py_dec_ref_ids(stack),
@@ -511,8 +679,8 @@ impl PyTrait for HeapData {
                     result.py_dec_ref_ids(stack);
                 }
             }
-            // Range, Slice, Exception, LongInt, and Path have no nested heap references
-            Self::Range(_) | Self::Slice(_) | Self::Exception(_) | Self::LongInt(_) | Self::Path(_) => {}
+            // Range, Slice, LongInt, and Path have no nested heap references
+            Self::Range(_) | Self::Slice(_) | Self::LongInt(_) | Self::Path(_) => {}
         }
     }
 
@@ -565,7 +733,7 @@ impl PyTrait for HeapData {
             Self::Cell(v) => write!(f, "<cell: {} object>", v.py_type(heap)),
             Self::Range(r) => r.py_repr_fmt(f, heap, heap_ids, guard, interns),
             Self::Slice(s) => s.py_repr_fmt(f, heap, heap_ids, guard, interns),
-            Self::Exception(e) => e.py_repr_fmt(f),
+            Self::Exception(e) => e.exc().py_repr_fmt(f),
             Self::Dataclass(dc) => dc.py_repr_fmt(f, heap, heap_ids, guard, interns),
             Self::Iter(_) => write!(f, "<iterator>"),
             Self::LongInt(li) => write!(f, "{li}"),
@@ -592,7 +760,7 @@ impl PyTrait for HeapData {
             // LongInt returns its string representation
             Self::LongInt(li) => Cow::Owned(li.to_string()),
             // Exceptions return just the message (or empty string if no message)
-            Self::Exception(e) => Cow::Owned(e.py_str()),
+            Self::Exception(e) => Cow::Owned(e.exc().py_str()),
             // Paths return the path string without the PosixPath() wrapper
             Self::Path(p) => Cow::Owned(p.as_str().to_owned()),
             // All other types use repr
@@ -709,6 +877,7 @@ impl PyTrait for HeapData {
             Self::FrozenSet(fs) => fs.py_call_attr(heap, attr, args, interns),
             Self::Dataclass(dc) => dc.py_call_attr(heap, attr, args, interns),
             Self::Path(p) => p.py_call_attr(heap, attr, args, interns),
+            Self::LongInt(li) => call_int_method(li.inner(), attr, args, heap, interns),
             _ => Err(ExcType::attribute_error(self.py_type(heap), attr.as_str(interns))),
         }
     }
@@ -853,6 +1022,8 @@ impl HashState {
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct HeapValue {
     refcount: usize,
+    /// Generation of the slot this value occupies; must match the `HeapId` used to access it.
+    generation: u32,
     /// The payload data. Temporarily `None` while borrowed via `with_entry_mut`/`call_attr`.
     data: Option<HeapData>,
     /// Current hashing status / cached hash value
@@ -865,6 +1036,11 @@ pub struct HeapValue {
 /// constant for long-running loops that repeatedly allocate and free values.
 /// When an value is freed via `dec_ref`, its slot ID is added to the free list.
 /// New allocations pop from the free list when available, otherwise append.
+/// Each reuse bumps the slot's generation, so stale `HeapId`s are detected on access.
+///
+/// Slots freed by boxed numbers (`LongInt`) go to a separate pool that numeric
+/// allocations draw from first, so arithmetic-heavy loops cycle through the same few
+/// slots instead of scattering short-lived numbers across slots freed by containers.
 ///
 /// Generic over `T: ResourceTracker` to support different resource tracking strategies.
 /// When `T = NoLimitTracker` (the default), all resource checks compile away to no-ops.
@@ -876,6 +1052,8 @@ pub(crate) struct Heap<T: ResourceTracker> {
     entries: Vec<Option<HeapValue>>,
     /// IDs of freed slots available for reuse. Populated by `dec_ref`, consumed by `allocate`.
     free_list: Vec<HeapId>,
+    /// IDs of freed slots last used by boxed numbers, reused by numeric allocations first.
+    numeric_free_list: Vec<HeapId>,
     /// Resource tracker for enforcing limits and scheduling GC.
     tracker: T,
     /// True if reference cycles may exist. Set when a container stores a Ref,
@@ -883,14 +1061,24 @@ pub(crate) struct Heap<T: ResourceTracker> {
     may_have_cycles: bool,
     /// Number of GC applicable allocations since the last GC.
     allocations_since_gc: u32,
+    /// Number of string results served from the interned empty/ASCII cache instead of
+    /// being allocated. Only kept with the `stats` feature, and not serialized.
+    #[cfg(feature = "stats")]
+    string_cache_hits: u64,
+    /// Incremented whenever a module namespace is written or heap ids are renumbered.
+    ///
+    /// VM inline caches record the generation they were filled under and are ignored once
+    /// it moves on. Caches don't outlive a VM, so this is not serialized.
+    namespace_generation: u64,
 }
 
 impl<T: ResourceTracker + serde::Serialize> serde::Serialize for Heap<T> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("Heap", 6)?;
+        let mut state = serializer.serialize_struct("Heap", 7)?;
         state.serialize_field("entries", &self.entries)?;
         state.serialize_field("free_list", &self.free_list)?;
+        state.serialize_field("numeric_free_list", &self.numeric_free_list)?;
         state.serialize_field("tracker", &self.tracker)?;
         state.serialize_field("may_have_cycles", &self.may_have_cycles)?;
         state.serialize_field("allocations_since_gc", &self.allocations_since_gc)?;
@@ -904,6 +1092,7 @@ impl<'de, T: ResourceTracker + serde::Deserialize<'de>> serde::Deserialize<'de>
         struct HeapFields<T> {
             entries: Vec<Option<HeapValue>>,
             free_list: Vec<HeapId>,
+            numeric_free_list: Vec<HeapId>,
             tracker: T,
             may_have_cycles: bool,
             allocations_since_gc: u32,
@@ -912,25 +1101,31 @@ impl<'de, T: ResourceTracker + serde::Deserialize<'de>> serde::Deserialize<'de>
         Ok(Self {
             entries: fields.entries,
             free_list: fields.free_list,
+            numeric_free_list: fields.numeric_free_list,
             tracker: fields.tracker,
             may_have_cycles: fields.may_have_cycles,
             allocations_since_gc: fields.allocations_since_gc,
+            #[cfg(feature = "stats")]
+            string_cache_hits: 0,
+            namespace_generation: 0,
         })
     }
 }
 
 macro_rules! take_data {
-    ($self:ident, $id:expr, $func_name:literal) => {
-        $self
+    ($self:ident, $id:expr, $func_name:literal) => {{
+        let entry = $self
             .entries
             .get_mut($id.index())
             .expect(concat!("Heap::", $func_name, ": slot missing"))
             .as_mut()
-            .expect(concat!("Heap::", $func_name, ": object already freed"))
+            .expect(concat!("Heap::", $func_name, ": object already freed"));
+        check_generation!(entry, $id, $func_name);
+        entry
             .data
             .take()
             .expect(concat!("Heap::", $func_name, ": data already borrowed"))
-    };
+    }};
 }
 
 macro_rules! restore_data {
@@ -959,9 +1154,13 @@ impl<T: ResourceTracker> Heap<T> {
         let mut this = Self {
             entries: Vec::with_capacity(capacity),
             free_list: Vec::new(),
+            numeric_free_list: Vec::new(),
             tracker,
             may_have_cycles: false,
             allocations_since_gc: 0,
+            #[cfg(feature = "stats")]
+            string_cache_hits: 0,
+            namespace_generation: 0,
         };
         // TBC: should the empty tuple contribute to the resource limits?
         // If not, can just place it in `entries` directly without going through `allocate()`.
@@ -972,6 +1171,30 @@ impl<T: ResourceTracker> Heap<T> {
         this
     }
 
+    /// Frees every value and swaps in a new resource tracker, keeping the allocated capacity
+    /// so the heap can be reused for another run.
+    ///
+    /// Nothing outside the heap may still hold a `HeapId` from before the reset.
+    pub fn reset(&mut self, tracker: T) {
+        #[cfg(feature = "ref-count-panic")]
+        self.mark_dereferenced();
+        self.entries.clear();
+        self.free_list.clear();
+        self.numeric_free_list.clear();
+        self.tracker = tracker;
+        self.may_have_cycles = false;
+        self.allocations_since_gc = 0;
+        #[cfg(feature = "stats")]
+        {
+            self.string_cache_hits = 0;
+        }
+        self.namespace_generation += 1;
+        let empty_tuple = self
+            .allocate(HeapData::Tuple(Tuple::default()))
+            .expect("Failed to allocate empty tuple singleton");
+        debug_assert_eq!(empty_tuple, EMPTY_TUPLE_ID);
+    }
+
     /// Returns a reference to the resource tracker.
     pub fn tracker(&self) -> &T {
         &self.tracker
@@ -1001,6 +1224,111 @@ impl<T: ResourceTracker> Heap<T> {
         self.entries.len()
     }
 
+    /// Drops freed slots and renumbers live entries so they occupy `0..live_count`.
+    ///
+    /// Without compaction the entries vector stays at its high-water mark for the life of
+    /// a run, so snapshots of a long-running script serialize (and resumes reallocate) every
+    /// slot it ever used. Relative order of live entries is preserved, so the empty tuple
+    /// singleton keeps id 0.
+    ///
+    /// Returns the old-to-new id map; the caller must apply it to every heap id held outside
+    /// the heap before the heap is used again. Python-visible `id()` values change.
+    pub fn compact(&mut self) -> HeapIdMap {
+        let mut next = 0;
+        let new_ids = self
+            .entries
+            .iter()
+            .map(|entry| {
+                entry.as_ref().map(|_| {
+                    let id = HeapId::new(next, 0);
+                    next += 1;
+                    id
+                })
+            })
+            .collect();
+        let map = HeapIdMap { new_ids, check: None };
+
+        let mut entries: Vec<Option<HeapValue>> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .filter(Option::is_some)
+            .collect();
+        // collecting in place keeps the old allocation, so release the spare capacity explicitly
+        entries.shrink_to_fit();
+        for entry in entries.iter_mut().flatten() {
+            // every slot is fresh after compaction, so generations restart alongside the ids
+            entry.generation = 0;
+            if let Some(data) = &mut entry.data {
+                data.remap_heap_ids(&map);
+            }
+        }
+        self.entries = entries;
+        self.free_list = Vec::new();
+        self.numeric_free_list = Vec::new();
+        self.namespace_generation += 1;
+        debug_assert!(
+            matches!(self.entries.first(), Some(Some(_))),
+            "empty tuple singleton must survive"
+        );
+        map
+    }
+
+    /// Checks that a deserialized heap is internally consistent before it is used.
+    ///
+    /// The empty tuple singleton must be present, the free lists may only hold distinct free
+    /// slots, every heap id held inside the heap must refer to a live entry, and every
+    /// function, interned string and the like must be within `bounds`.
+    ///
+    /// On success returns a map that checks values held outside the heap the same way: pass it
+    /// to their `remap_heap_ids`, then call [`HeapIdMap::problem`].
+    pub fn validate(&mut self, bounds: InternBounds) -> Result<HeapIdMap, String> {
+        if !matches!(self.get_if_live(EMPTY_TUPLE_ID), Some(HeapData::Tuple(_))) {
+            return Err("empty tuple singleton is missing".to_owned());
+        }
+        let mut on_free_list = vec![false; self.entries.len()];
+        for id in self.free_list.iter().chain(&self.numeric_free_list) {
+            let index = id.index();
+            if !matches!(self.entries.get(index), Some(None)) || on_free_list[index] {
+                return Err(format!("free list holds slot {index}, which is not free"));
+            }
+            on_free_list[index] = true;
+        }
+
+        let checker = HeapIdMap {
+            new_ids: self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| entry.as_ref().map(|entry| HeapId::new(index, entry.generation)))
+                .collect(),
+            check: Some(IdCheck {
+                bounds,
+                problem: RefCell::new(None),
+            }),
+        };
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            let Some(entry) = entry else { continue };
+            let Some(data) = &mut entry.data else {
+                return Err(format!("heap entry {index} has no data"));
+            };
+            let function_id = match data {
+                HeapData::Closure(function_id, ..) | HeapData::FunctionDefaults(function_id, _) => Some(*function_id),
+                HeapData::Coroutine(coro) => Some(coro.func_id),
+                _ => None,
+            };
+            if let Some(function_id) = function_id.filter(|id| id.index() >= bounds.functions) {
+                return Err(format!(
+                    "heap entry {index} refers to function {}, which does not exist",
+                    function_id.index()
+                ));
+            }
+            data.remap_heap_ids(&checker);
+            if let Some(problem) = checker.problem() {
+                return Err(format!("heap entry {index} refers to {problem}"));
+            }
+        }
+        Ok(checker)
+    }
+
     /// Marks that a reference cycle may exist in the heap.
     ///
     /// Call this when a container (list, dict, tuple, etc.) stores a reference
@@ -1031,6 +1359,14 @@ impl<T: ResourceTracker> Heap<T> {
     /// When allocating a container that contains heap references, marks potential
     /// cycles to enable garbage collection.
     pub fn allocate(&mut self, data: HeapData) -> Result<HeapId, ResourceError> {
+        match &data {
+            HeapData::Str(s) => self
+                .tracker
+                .check_collection_len(CollectionKind::Str, s.as_str().len())?,
+            HeapData::List(list) => self.tracker.check_collection_len(CollectionKind::List, list.len())?,
+            HeapData::Dict(dict) => self.tracker.check_collection_len(CollectionKind::Dict, dict.len())?,
+            _ => {}
+        }
         self.tracker.on_allocate(|| data.py_estimate_size())?;
         if data.is_gc_tracked() {
             self.allocations_since_gc = self.allocations_since_gc.wrapping_add(1);
@@ -1042,27 +1378,76 @@ impl<T: ResourceTracker> Heap<T> {
             }
         }
 
-        let hash_state = HashState::for_data(&data);
+        // Reuse a freed slot (its id already carries the next generation) or append a new one,
+        // preferring the pool matching the value's kind
+        let reused = if data.is_boxed_number() {
+            self.numeric_free_list.pop().or_else(|| self.free_list.pop())
+        } else {
+            self.free_list.pop().or_else(|| self.numeric_free_list.pop())
+        };
+        let id = reused.unwrap_or_else(|| HeapId::new(self.entries.len(), 0));
         let new_entry = HeapValue {
             refcount: 1,
+            generation: id.generation,
+            hash_state: HashState::for_data(&data),
             data: Some(data),
-            hash_state,
         };
-
-        let id = if let Some(id) = self.free_list.pop() {
-            // Reuse a freed slot
+        if id.index() < self.entries.len() {
             self.entries[id.index()] = Some(new_entry);
-            id
         } else {
-            // No free slots, append new entry
-            let id = self.entries.len();
             self.entries.push(Some(new_entry));
-            HeapId(id)
-        };
+        }
 
         Ok(id)
     }
 
+    /// Charges `additional` bytes to the tracker before a heap value grows in place.
+    ///
+    /// Values are credited their estimated size when freed, so growing one without
+    /// charging the difference would let it slip past `max_memory` and leave the
+    /// tracked usage too low once it's freed.
+    pub fn charge_growth(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.tracker.on_grow(additional)
+    }
+
+    /// Returns a freed slot to the pool matching the kind of value that occupied it.
+    fn release_slot(&mut self, next_id: HeapId, data: Option<&HeapData>) {
+        if data.is_some_and(HeapData::is_boxed_number) {
+            self.numeric_free_list.push(next_id);
+        } else {
+            self.free_list.push(next_id);
+        }
+    }
+
+    /// Counts a string result served from the interned cache; a no-op without the `stats` feature.
+    #[inline]
+    pub fn record_string_cache_hit(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.string_cache_hits += 1;
+        }
+    }
+
+    /// Returns how many string results have been served from the interned cache.
+    #[cfg(feature = "stats")]
+    pub fn string_cache_hits(&self) -> u64 {
+        self.string_cache_hits
+    }
+
+    /// Returns the current namespace generation, see `bump_namespace_generation`.
+    #[inline]
+    pub fn namespace_generation(&self) -> u64 {
+        self.namespace_generation
+    }
+
+    /// Invalidates every inline cache filled so far.
+    ///
+    /// Must be called whenever a module's attributes change.
+    #[inline]
+    pub fn bump_namespace_generation(&mut self) {
+        self.namespace_generation += 1;
+    }
+
     /// Returns the singleton empty tuple.
     ///
     /// In Python, `() is ()` is always `True` because empty tuples are interned.
@@ -1088,25 +1473,86 @@ impl<T: ResourceTracker> Heap<T> {
             .expect("Heap::inc_ref: slot missing")
             .as_mut()
             .expect("Heap::inc_ref: object already freed");
+        check_generation!(value, id, "inc_ref");
         value.refcount += 1;
     }
 
+    /// Increments the reference count for an existing heap entry by `count` in one step.
+    ///
+    /// Used when a single operation creates many references to the same value, like
+    /// sequence repetition, so the cost is per distinct value rather than per copy.
+    ///
+    /// # Panics
+    /// Panics if the value ID is invalid or the value has already been freed.
+    pub fn inc_ref_by(&mut self, id: HeapId, count: usize) {
+        let value = self
+            .entries
+            .get_mut(id.index())
+            .expect("Heap::inc_ref_by: slot missing")
+            .as_mut()
+            .expect("Heap::inc_ref_by: object already freed");
+        check_generation!(value, id, "inc_ref_by");
+        value.refcount += count;
+    }
+
+    /// Returns whether `key` is a slice selecting every item of the immutable sequence
+    /// (`str`, `bytes` or `tuple`) at `id`, in order.
+    ///
+    /// This is a fast path for one case only: such a slice returns the sequence itself
+    /// instead of a copy, as CPython does for `t[:]`, and since the value can never change
+    /// that is unobservable apart from identity. Any other slice still copies its items.
+    ///
+    /// A `str` is measured by its length in bytes, which keeps the check O(1): a slice that
+    /// starts at 0 and reaches the last byte with step 1 covers every character too. Slices
+    /// that end at the character count of a non-ASCII string, like `'é'[0:1]`, miss the
+    /// fast path and copy.
+    pub fn is_whole_slice_of_immutable(&self, id: HeapId, key: &Value) -> bool {
+        let Value::Ref(key_id) = key else {
+            return false;
+        };
+        let HeapData::Slice(slice) = self.get(*key_id) else {
+            return false;
+        };
+        let len = match self.get(id) {
+            HeapData::Tuple(tuple) => tuple.as_slice().len(),
+            HeapData::Bytes(bytes) => bytes.as_slice().len(),
+            HeapData::Str(s) => s.as_str().len(),
+            _ => return false,
+        };
+        slice.indices(len) == Ok((0, len, 1))
+    }
+
     /// Decrements the reference count and frees the value (plus children) once it hits zero.
     ///
     /// When an value is freed, its slot ID is added to the free list for reuse by
-    /// future allocations. Uses recursion for child cleanup - avoiding repeated Vec
-    /// allocations and benefiting from call stack locality.
+    /// future allocations. Children of freed values are released from a work stack rather
+    /// than by recursion, so freeing deeply nested data can't overflow the call stack; the
+    /// stack only allocates once a freed value has children.
     ///
     /// # Panics
     /// Panics if the value ID is invalid or the value has already been freed.
     pub fn dec_ref(&mut self, id: HeapId) {
+        let mut pending = Vec::new();
+        self.dec_ref_one(id, &mut pending);
+        while let Some(child_id) = pending.pop() {
+            self.dec_ref_one(child_id, &mut pending);
+        }
+    }
+
+    /// Decrements the reference count of one value, pushing the IDs of its children onto
+    /// `pending` if that frees it.
+    ///
+    /// Children are pushed in reverse so popping them releases them in the order a
+    /// recursive walk would.
+    fn dec_ref_one(&mut self, id: HeapId, pending: &mut Vec<HeapId>) {
         let slot = self.entries.get_mut(id.index()).expect("Heap::dec_ref: slot missing");
         let entry = slot.as_mut().expect("Heap::dec_ref: object already freed");
+        check_generation!(entry, id, "dec_ref");
         if entry.refcount > 1 {
             entry.refcount -= 1;
         } else if let Some(value) = slot.take() {
-            // refcount == 1, free the value and add slot to free list for reuse
-            self.free_list.push(id);
+            // refcount == 1, free the value and add slot to free list for reuse under the next generation
+            self.release_slot(id.next_generation(), value.data.as_ref());
 
             // Notify tracker of freed memory
             if let Some(ref data) = value.data {
@@ -1115,13 +1561,9 @@ impl<T: ResourceTracker> Heap<T> {
 
             // Collect child IDs and mark Values as Dereferenced (when ref-count-panic enabled)
             if let Some(mut data) = value.data {
-                let mut child_ids = Vec::new();
-                data.py_dec_ref_ids(&mut child_ids);
-                drop(data);
-                // Recursively decrement children
-                for child_id in child_ids {
-                    self.dec_ref(child_id);
-                }
+                let first_child = pending.len();
+                data.py_dec_ref_ids(pending);
+                pending[first_child..].reverse();
             }
         }
     }
@@ -1133,14 +1575,28 @@ impl<T: ResourceTracker> Heap<T> {
     /// or the data is currently borrowed via `with_entry_mut`/`call_attr`.
     #[must_use]
     pub fn get(&self, id: HeapId) -> &HeapData {
-        self.entries
+        let entry = self
+            .entries
             .get(id.index())
             .expect("Heap::get: slot missing")
             .as_ref()
-            .expect("Heap::get: object already freed")
-            .data
-            .as_ref()
-            .expect("Heap::get: data currently borrowed")
+            .expect("Heap::get: object already freed");
+        check_generation!(entry, id, "get");
+        entry.data.as_ref().expect("Heap::get: data currently borrowed")
+    }
+
+    /// Returns the heap data stored at the given ID if that value is still alive.
+    ///
+    /// Unlike [`Heap::get`], returns `None` instead of panicking when the slot has been
+    /// freed or reused, or its data is currently borrowed. For ids held without a reference.
+    #[must_use]
+    pub fn get_if_live(&self, id: HeapId) -> Option<&HeapData> {
+        let entry = self.entries.get(id.index())?.as_ref()?;
+        if entry.generation == id.generation {
+            entry.data.as_ref()
+        } else {
+            None
+        }
     }
 
     /// Returns a mutable reference to the heap data stored at the given ID.
@@ -1149,14 +1605,29 @@ impl<T: ResourceTracker> Heap<T> {
     /// Panics if the value ID is invalid, the value has already been freed,
     /// or the data is currently borrowed via `with_entry_mut`/`call_attr`.
     pub fn get_mut(&mut self, id: HeapId) -> &mut HeapData {
-        self.entries
+        let entry = self
+            .entries
             .get_mut(id.index())
             .expect("Heap::get_mut: slot missing")
             .as_mut()
-            .expect("Heap::get_mut: object already freed")
-            .data
+            .expect("Heap::get_mut: object already freed");
+        check_generation!(entry, id, "get_mut");
+        entry.data.as_mut().expect("Heap::get_mut: data currently borrowed")
+    }
+
+    /// Forgets the cached hash of the value at `id`, whose contents are about to change in place.
+    ///
+    /// # Panics
+    /// Panics if the value ID is invalid or the value has already been freed.
+    pub fn clear_hash(&mut self, id: HeapId) {
+        let entry = self
+            .entries
+            .get_mut(id.index())
+            .expect("Heap::clear_hash: slot missing")
             .as_mut()
-            .expect("Heap::get_mut: data currently borrowed")
+            .expect("Heap::clear_hash: object already freed");
+        check_generation!(entry, id, "clear_hash");
+        entry.hash_state = HashState::Unknown;
     }
 
     /// Returns or computes the hash for the heap entry at the given ID.
@@ -1174,6 +1645,7 @@ impl<T: ResourceTracker> Heap<T> {
             .expect("Heap::get_or_compute_hash: slot missing")
             .as_mut()
             .expect("Heap::get_or_compute_hash: object already freed");
+        check_generation!(entry, id, "get_or_compute_hash");
 
         match entry.hash_state {
             HashState::Unhashable => return None,
@@ -1291,14 +1763,23 @@ impl<T: ResourceTracker> Heap<T> {
         }
     }
 
+    /// Returns whether the value at `id` has a single reference, so dropping it frees the value.
+    #[must_use]
+    pub fn is_last_ref(&self, id: HeapId) -> bool {
+        self.entries
+            .get(id.index())
+            .and_then(Option::as_ref)
+            .is_some_and(|entry| entry.refcount == 1)
+    }
+
     /// Returns the reference count for the heap entry at the given ID.
     ///
-    /// This is primarily used for testing reference counting behavior.
+    /// Used to tell whether an immutable value can be changed in place unobserved, and
+    /// for testing reference counting behavior.
     ///
     /// # Panics
     /// Panics if the value ID is invalid or the value has already been freed.
     #[must_use]
-    #[cfg(feature = "ref-count-return")]
     pub fn get_refcount(&self, id: HeapId) -> usize {
         self.entries
             .get(id.index())
@@ -1480,16 +1961,30 @@ impl<T: ResourceTracker> Heap<T> {
     /// * `Ok(Some(Value))` - The new repeated sequence
     /// * `Ok(None)` - If the heap entry is not a sequence type
     /// * `Err` - If allocation fails due to resource limits
+    ///
+    /// Apart from the repeat-by-one fast path, the result always gets its own items: there
+    /// is no copy-on-write backing shared between sequences. Refcounts are still bumped once
+    /// per distinct item via [`Heap::inc_ref_by`] rather than once per copy.
     pub fn mult_sequence(&mut self, id: HeapId, count: usize) -> RunResult<Option<Value>> {
+        // Fast path: repeating an immutable sequence once yields an equal value that can never
+        // diverge from the original, so share it rather than copying (matching CPython's
+        // `t * 1 is t`); every other count builds a new sequence below
+        if count == 1 && matches!(self.get(id), HeapData::Str(_) | HeapData::Bytes(_) | HeapData::Tuple(_)) {
+            self.inc_ref(id);
+            return Ok(Some(Value::Ref(id)));
+        }
+
         // Take the data out to avoid borrow conflicts
         let data = take_data!(self, id, "mult_sequence");
 
         match &data {
             HeapData::Str(s) => {
                 check_repeat_size(s.len(), count, &self.tracker)?;
+                self.tracker
+                    .check_collection_len(CollectionKind::Str, s.len().saturating_mul(count))?;
                 let repeated = s.as_str().repeat(count);
                 restore_data!(self, id, data, "mult_sequence");
-                Ok(Some(Value::Ref(self.allocate(HeapData::Str(repeated.into()))?)))
+                Ok(Some(allocate_str(repeated, self)?))
             }
             HeapData::Bytes(b) => {
                 check_repeat_size(b.len(), count, &self.tracker)?;
@@ -1504,6 +1999,8 @@ impl<T: ResourceTracker> Heap<T> {
                 } else {
                     // Pre-check memory limit for large results
                     check_repeat_size(list.len().saturating_mul(size_of::<Value>()), count, &self.tracker)?;
+                    self.tracker
+                        .check_collection_len(CollectionKind::List, list.len().saturating_mul(count))?;
 
                     // Copy items and track which refs need incrementing
                     let items: Vec<Value> = list.as_slice().iter().map(Value::copy_for_extend).collect();
@@ -1513,18 +2010,17 @@ impl<T: ResourceTracker> Heap<T> {
                     // Restore data before heap operations
                     restore_data!(self, id, data, "mult_sequence");
 
-                    // Now increment refcounts for each copy we'll make
-                    // We need (count) copies of each ref
-                    for ref_id in &ref_ids {
-                        for _ in 0..count {
-                            self.inc_ref(*ref_id);
-                        }
-                    }
-
-                    // Build the repeated list with overflow check
+                    // Check for overflow before touching any refcounts
                     let capacity = original_len
                         .checked_mul(count)
                         .ok_or_else(ExcType::overflow_repeat_count)?;
+
+                    // Each ref gets (count) new references, bumped once per distinct item
+                    for ref_id in &ref_ids {
+                        self.inc_ref_by(*ref_id, count);
+                    }
+
+                    // Build the repeated list
                     let mut result = Vec::with_capacity(capacity);
                     for _ in 0..count {
                         self.check_time()?;
@@ -1561,18 +2057,17 @@ impl<T: ResourceTracker> Heap<T> {
                     // Restore data before heap operations
                     restore_data!(self, id, data, "mult_sequence");
 
-                    // Now increment refcounts for each copy we'll make
-                    // We need (count) copies of each ref
-                    for ref_id in &ref_ids {
-                        for _ in 0..count {
-                            self.inc_ref(*ref_id);
-                        }
-                    }
-
-                    // Build the repeated tuple with overflow check
+                    // Check for overflow before touching any refcounts
                     let capacity = original_len
                         .checked_mul(count)
                         .ok_or_else(ExcType::overflow_repeat_count)?;
+
+                    // Each ref gets (count) new references, bumped once per distinct item
+                    for ref_id in &ref_ids {
+                        self.inc_ref_by(*ref_id, count);
+                    }
+
+                    // Build the repeated tuple
                     let mut result = SmallVec::with_capacity(capacity);
                     for _ in 0..count {
                         self.check_time()?;
@@ -1642,19 +2137,20 @@ impl<T: ResourceTracker> Heap<T> {
         }
 
         // Sweep phase: free unreachable values
-        for (id, value) in self.entries.iter_mut().enumerate() {
-            if reachable[id] {
+        for (id, &is_reachable) in reachable.iter().enumerate() {
+            if is_reachable {
                 continue;
             }
 
             // This entry is unreachable - free it
-            if let Some(value) = value.take() {
+            if let Some(value) = self.entries[id].take() {
                 // Notify tracker of freed memory
                 if let Some(ref data) = value.data {
                     self.tracker.on_free(|| data.py_estimate_size());
                 }
 
-                self.free_list.push(HeapId(id));
+                let next_id = HeapId::new(id, value.generation.wrapping_add(1));
+                self.release_slot(next_id, value.data.as_ref());
 
                 // Mark Values as Dereferenced when ref-count-panic is enabled
                 #[cfg(feature = "ref-count-panic")]
@@ -1668,6 +2164,75 @@ impl<T: ResourceTracker> Heap<T> {
         self.may_have_cycles = false;
         self.allocations_since_gc = 0;
     }
+
+    /// Counts the live entries and checks each reference count against the references
+    /// actually held by `roots` and by other live entries.
+    ///
+    /// Unlike `collect_garbage()` this frees nothing, so entries kept alive by a leaked
+    /// reference are still there to report. The empty tuple singleton is referenced once by
+    /// the heap itself and left out of the object counts.
+    #[cfg(feature = "heap-audit")]
+    pub fn audit(&self, roots: impl IntoIterator<Item = HeapId>) -> HeapAudit {
+        let mut work_list: Vec<HeapId> = roots.into_iter().collect();
+        work_list.push(EMPTY_TUPLE_ID);
+
+        // Every id held by a root or a live entry accounts for one reference
+        let mut held = work_list.clone();
+        for entry in self.entries.iter().flatten() {
+            if let Some(data) = &entry.data {
+                collect_child_ids(data, &mut held);
+            }
+        }
+        let mut references = vec![0usize; self.entries.len()];
+        for id in held {
+            if let Some(count) = references.get_mut(id.index()) {
+                *count += 1;
+            }
+        }
+
+        // Mark phase as in `collect_garbage()`
+        let mut reachable = vec![false; self.entries.len()];
+        while let Some(id) = work_list.pop() {
+            let index = id.index();
+            if index >= reachable.len() || reachable[index] {
+                continue;
+            }
+            reachable[index] = true;
+            if let Some(Some(entry)) = self.entries.get(index)
+                && let Some(ref data) = entry.data
+            {
+                collect_child_ids(data, &mut work_list);
+            }
+        }
+
+        let mut audit = HeapAudit::default();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let Some(entry) = entry else { continue };
+            let type_name = entry
+                .data
+                .as_ref()
+                .map_or_else(|| "<borrowed>".to_owned(), |data| data.py_type(self).to_string());
+            audit.refcount_total += entry.refcount;
+            audit.reference_total += references[index];
+            if index != EMPTY_TUPLE_ID.index() {
+                audit.live_objects += 1;
+                *audit.objects_by_type.entry(type_name.clone()).or_default() += 1;
+            }
+            let audited = AuditedEntry {
+                index,
+                type_name,
+                refcount: entry.refcount,
+                references: references[index],
+            };
+            if entry.refcount != audited.references {
+                audit.miscounted.push(audited.clone());
+            }
+            if !reachable[index] {
+                audit.unreachable.push(audited);
+            }
+        }
+        audit
+    }
 }
 
 /// Computes the number of significant bits in an `i64`.
@@ -1715,7 +2280,6 @@ fn collect_child_ids(data: &HeapData, work_list: &mut Vec<HeapId>) {
         HeapData::Str(_)
         | HeapData::Bytes(_)
         | HeapData::Range(_)
-        | HeapData::Exception(_)
         | HeapData::LongInt(_)
         | HeapData::Slice(_)
         | HeapData::Path(_) => {}
@@ -1806,6 +2370,17 @@ fn collect_child_ids(data: &HeapData, work_list: &mut Vec<HeapId>) {
                 work_list.push(*id);
             }
         }
+        HeapData::Exception(e) => {
+            // Exception attrs are stored in a Dict - iterate through entries
+            for (k, v) in e.attrs() {
+                if let Value::Ref(id) = k {
+                    work_list.push(*id);
+                }
+                if let Value::Ref(id) = v {
+                    work_list.push(*id);
+                }
+            }
+        }
         HeapData::Dataclass(dc) => {
             // Dataclass attrs are stored in a Dict - iterate through entries
             for (k, v) in dc.attrs() {
@@ -1871,7 +2446,14 @@ fn collect_child_ids(data: &HeapData, work_list: &mut Vec<HeapId>) {
 #[cfg(feature = "ref-count-panic")]
 impl<T: ResourceTracker> Drop for Heap<T> {
     fn drop(&mut self) {
-        // Mark all contained Objects as Dereferenced before dropping.
+        self.mark_dereferenced();
+    }
+}
+
+#[cfg(feature = "ref-count-panic")]
+impl<T: ResourceTracker> Heap<T> {
+    /// Marks all contained Objects as Dereferenced so the entries can be dropped.
+    fn mark_dereferenced(&mut self) {
         // We use py_dec_ref_ids for this since it handles the marking
         // (we ignore the collected IDs since we're dropping everything anyway).
         let mut dummy_stack = Vec::new();
@@ -2094,3 +2676,163 @@ macro_rules! defer_drop_mut {
         let ($value, $heap) = _guard.as_parts_mut();
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::*;
+    use crate::resource::NoLimitTracker;
+
+    fn allocate_long_int(heap: &mut Heap<NoLimitTracker>, n: i64) -> HeapId {
+        heap.allocate(HeapData::LongInt(LongInt::new(BigInt::from(n)))).unwrap()
+    }
+
+    #[test]
+    fn freed_slot_is_reused_under_new_generation() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let first = allocate_long_int(&mut heap, 1);
+        heap.dec_ref(first);
+        let second = allocate_long_int(&mut heap, 2);
+
+        assert_eq!(second.index(), first.index());
+        assert_ne!(second, first);
+        // empty tuple singleton plus the reused slot
+        assert_eq!(heap.size(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale HeapId")]
+    fn stale_id_is_rejected_after_slot_reuse() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let first = allocate_long_int(&mut heap, 1);
+        heap.dec_ref(first);
+        allocate_long_int(&mut heap, 2);
+
+        let _ = heap.get(first);
+    }
+
+    #[test]
+    fn compaction_restarts_generations() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let first = allocate_long_int(&mut heap, 1);
+        heap.dec_ref(first);
+        let mut live = allocate_long_int(&mut heap, 2);
+
+        let map = heap.compact();
+        map.remap(&mut live);
+
+        assert_eq!(live, HeapId::new(1, 0));
+        assert!(matches!(heap.get(live), HeapData::LongInt(_)));
+    }
+
+    #[test]
+    fn numeric_slots_are_pooled_separately() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let number = allocate_long_int(&mut heap, 1);
+        let bytes = heap.allocate(HeapData::Bytes(Bytes::new(vec![1]))).unwrap();
+        heap.dec_ref(number);
+        heap.dec_ref(bytes);
+
+        // the bytes slot was freed last, but a new number takes the slot freed by a number
+        let new_number = allocate_long_int(&mut heap, 2);
+        assert_eq!(new_number.index(), number.index());
+        // other allocations fall back to the numeric pool once their own pool is empty
+        let first_str = heap.allocate(HeapData::Str(Str::from("a"))).unwrap();
+        assert_eq!(first_str.index(), bytes.index());
+        heap.dec_ref(new_number);
+        let second_str = heap.allocate(HeapData::Str(Str::from("b"))).unwrap();
+        assert_eq!(second_str.index(), number.index());
+    }
+
+    #[test]
+    fn validate_accepts_consistent_heap() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let number = allocate_long_int(&mut heap, 1);
+        let list = heap
+            .allocate(HeapData::List(List::new(vec![Value::Ref(number)])))
+            .unwrap();
+        let freed = allocate_long_int(&mut heap, 2);
+        heap.dec_ref(freed);
+
+        let checker = heap.validate(InternBounds::default()).unwrap();
+        let mut outside = list;
+        checker.remap(&mut outside);
+        assert_eq!(outside, list);
+        assert_eq!(checker.problem(), None);
+
+        let mut stale = freed;
+        checker.remap(&mut stale);
+        assert_eq!(checker.problem(), Some(format!("dangling heap id {freed:?}")));
+        heap.dec_ref(list);
+    }
+
+    #[test]
+    fn validate_rejects_dangling_id_inside_heap() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let number = allocate_long_int(&mut heap, 1);
+        // the list doesn't own a reference, so freeing the number leaves it dangling
+        heap.allocate(HeapData::List(List::new(vec![Value::Ref(number)])))
+            .unwrap();
+        heap.dec_ref(number);
+
+        let err = heap.validate(InternBounds::default()).unwrap_err();
+        assert!(err.contains("dangling heap id"), "{err}");
+        std::mem::forget(heap);
+    }
+
+    #[test]
+    fn validate_rejects_function_id_out_of_range() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let function = Value::DefFunction(FunctionId::from_index(3));
+        heap.allocate(HeapData::List(List::new(vec![function]))).unwrap();
+
+        let err = heap.validate(InternBounds::default()).unwrap_err();
+        assert!(err.contains("function 3, which does not exist"), "{err}");
+    }
+
+    #[test]
+    fn validate_rejects_bad_free_list() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let number = allocate_long_int(&mut heap, 1);
+        heap.free_list.push(number);
+
+        let err = heap.validate(InternBounds::default()).unwrap_err();
+        assert!(err.contains("not free"), "{err}");
+    }
+
+    #[cfg(feature = "heap-audit")]
+    #[test]
+    fn audit_reports_miscounted_and_unreachable_entries() {
+        let mut heap = Heap::new(4, NoLimitTracker);
+        let number = allocate_long_int(&mut heap, 1);
+        // the list takes over the only reference to the number
+        let list = heap
+            .allocate(HeapData::List(List::new(vec![Value::Ref(number)])))
+            .unwrap();
+
+        let audit = heap.audit([list]);
+        audit.assert_no_leaks();
+        assert_eq!(audit.live_objects, 2);
+        assert_eq!(audit.objects_by_type["list"], 1);
+        assert_eq!(audit.objects_by_type["int"], 1);
+        assert!(audit.unreachable.is_empty());
+
+        // a reference that nothing holds keeps the number alive after the list is freed
+        heap.inc_ref(number);
+        let audit = heap.audit([list]);
+        assert!(!audit.is_clean());
+        assert_eq!(audit.miscounted.len(), 1);
+        assert_eq!(audit.miscounted[0].index, number.index());
+        assert_eq!((audit.miscounted[0].refcount, audit.miscounted[0].references), (2, 1));
+        assert_eq!(audit.refcount_total, audit.reference_total + 1);
+
+        heap.dec_ref(list);
+        let audit = heap.audit([]);
+        assert_eq!(audit.live_objects, 1);
+        assert_eq!(audit.unreachable.len(), 1);
+        assert_eq!(audit.unreachable[0].type_name, "int");
+        heap.dec_ref(number);
+        heap.audit([]).assert_no_leaks();
+    }
+}
@@ -0,0 +1,79 @@
+//! Streaming a Rust iterator into a script as a host iterable.
+//!
+//! A host iterable (see [`CompileOptions::host_iterables`]) is an external function scripts
+//! loop over. Each time a loop over it runs out of items, the run yields a
+//! [`RunProgress::FunctionCall`] for it and the host hands over more. [`HostIterable`]
+//! answers those requests from a Rust iterator, a chunk at a time, so the host never has to
+//! collect the whole dataset and the script only ever holds one chunk of it.
+//!
+//! [`CompileOptions::host_iterables`]: crate::CompileOptions::host_iterables
+
+use std::fmt;
+
+use crate::{ExcType, MontyException, MontyObject, PrintWriter, ResourceTracker, RunProgress, Snapshot};
+
+/// Chunk size used unless [`HostIterable::chunk_size`] sets another.
+const DEFAULT_CHUNK_SIZE: usize = 64;
+
+/// Hands the items of a Rust iterator to a script looping over a host iterable.
+///
+/// See [`CompileOptions::host_iterables`] for an example.
+///
+/// [`CompileOptions::host_iterables`]: crate::CompileOptions::host_iterables
+pub struct HostIterable {
+    items: Box<dyn Iterator<Item = MontyObject> + Send>,
+    chunk_size: usize,
+}
+
+impl HostIterable {
+    /// Creates a host iterable yielding `items`.
+    #[must_use]
+    pub fn new(items: impl IntoIterator<Item = MontyObject, IntoIter: Send + 'static>) -> Self {
+        Self {
+            items: Box::new(items.into_iter()),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Sets how many items are handed over each time the script runs out, 64 by default.
+    ///
+    /// Larger chunks mean fewer round trips, smaller ones less memory held by the script.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    #[must_use]
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be at least 1");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Hands the next chunk of items to a run waiting on this iterable and continues it.
+    ///
+    /// # Errors
+    /// Returns a `RuntimeError` if the run is waiting on a call rather than a host iterable,
+    /// or an exception the script raised while continuing.
+    pub fn resume<T: ResourceTracker>(
+        &mut self,
+        snapshot: Snapshot<T>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, MontyException> {
+        if !snapshot.is_host_iterable() {
+            return Err(MontyException::new(
+                ExcType::RuntimeError,
+                Some("the run is waiting on a call, not a host iterable".to_owned()),
+            ));
+        }
+        let items: Vec<MontyObject> = self.items.by_ref().take(self.chunk_size).collect();
+        let exhausted = items.len() < self.chunk_size;
+        snapshot.run_host_items(items, exhausted, print)
+    }
+}
+
+impl fmt::Debug for HostIterable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostIterable")
+            .field("chunk_size", &self.chunk_size)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,107 @@
+//! Calling the functions a script defines, many times, from the host.
+//!
+//! `MontyRun::run()` executes a whole module per invocation. Event-handler style hosts
+//! instead want to load a script that defines `def handler(event): ...` once and then call
+//! `handler` for every event. A [`MontyInstance`] runs the module code once and keeps its
+//! heap and globals, so each `call()` only executes the function body and globals the
+//! script updates persist between calls. `MontyRun::call()` does the same with fresh
+//! globals for every call.
+
+use std::sync::Arc;
+
+use crate::{
+    exception_public::MontyException,
+    heap::Heap,
+    io::PrintWriter,
+    namespace::Namespaces,
+    object::MontyObject,
+    resource::ResourceTracker,
+    run::{CompiledProgram, MontyRun},
+};
+
+/// A script whose module code has run, ready to have its functions called.
+///
+/// Module-level variables persist between calls, so a handler can keep a counter or a
+/// cache in a global. All calls share the resource tracker given to `new()`, so limits
+/// such as `max_allocations` apply to the instance as a whole.
+///
+/// # Example
+/// ```
+/// use monty::{MontyInstance, MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+///
+/// let code = "seen = 0\ndef handler(event):\n    global seen\n    seen += 1\n    return [seen, event * 2]";
+/// let runner = MontyRun::new(code.to_owned(), "handler.py", vec![], vec![]).unwrap();
+/// let mut instance = MontyInstance::new(&runner, vec![], NoLimitTracker, &mut PrintWriter::Disabled).unwrap();
+///
+/// let print = &mut PrintWriter::Disabled;
+/// instance.call("handler", vec![MontyObject::Int(5)], print).unwrap();
+/// let result = instance.call("handler", vec![MontyObject::Int(7)], print).unwrap();
+/// assert_eq!(result, MontyObject::List(vec![MontyObject::Int(2), MontyObject::Int(14)]));
+/// ```
+#[derive(Debug)]
+pub struct MontyInstance<T: ResourceTracker> {
+    program: Arc<CompiledProgram>,
+    heap: Heap<T>,
+    namespaces: Namespaces,
+}
+
+impl<T: ResourceTracker> MontyInstance<T> {
+    /// Runs the module code of the program compiled by `runner` and keeps its globals.
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the inputs are invalid or the module code raises.
+    pub fn new(
+        runner: &MontyRun,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<Self, MontyException> {
+        let program = Arc::clone(runner.program());
+        let mut heap = program.new_heap(resource_tracker);
+        let mut namespaces = program.new_namespaces();
+        let result = program.run_with(
+            &mut heap,
+            &mut namespaces,
+            inputs,
+            runner.initial_globals(),
+            None,
+            None,
+            None,
+            print,
+        );
+        let instance = Self {
+            program,
+            heap,
+            namespaces,
+        };
+        result.map(|_| instance)
+    }
+
+    /// Calls the module-level function `function_name` with positional `args`.
+    ///
+    /// # Errors
+    /// Returns `MontyException` with `NameError` if there is no such global, or any
+    /// exception the call raises. A failed call leaves the instance usable.
+    pub fn call(
+        &mut self,
+        function_name: &str,
+        args: Vec<MontyObject>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<MontyObject, MontyException> {
+        self.program
+            .call_with(&mut self.heap, &mut self.namespaces, function_name, args, print)
+    }
+
+    /// Returns a mutable reference to the resource tracker, e.g. to extend a deadline
+    /// before the next call.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        self.heap.tracker_mut()
+    }
+}
+
+impl<T: ResourceTracker> Drop for MontyInstance<T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "ref-count-panic")]
+        self.namespaces.drop_global_with_heap(&mut self.heap);
+    }
+}
@@ -5,6 +5,8 @@
 //! This avoids the overhead of cloning strings or using atomic reference counting.
 //!
 //! The interners are populated during parsing and preparation, then owned by the `Executor`.
+//! The finished `Interns` table holds each value behind an `Arc`, so programs compiled with
+//! a `SharedInterns` table can share the values they have in common.
 //! During execution, lookups are needed only for error messages and repr output.
 //!
 //! StringIds are laid out as follows:
@@ -12,13 +14,13 @@
 //! * 1000 to count(StaticStrings) - strings StaticStrings
 //! * 10_000+ - strings interned per executor
 
-use std::{str::FromStr, sync::LazyLock};
+use std::sync::{Arc, LazyLock};
 
 use ahash::AHashMap;
 use num_bigint::BigInt;
 use strum::{EnumString, FromRepr, IntoStaticStr};
 
-use crate::{function::Function, value::Value};
+use crate::{annotations::ExternalSignature, function::Function, shared_interns::SharedInterns, value::Value};
 
 /// Index into the string interner's storage.
 ///
@@ -68,6 +70,23 @@ static ASCII_STRS: LazyLock<[&'static str; 128]> = LazyLock::new(|| {
     })
 });
 
+/// Process-wide lookup table from static string text to its `StaticStrings` variant.
+///
+/// Built once on first use and shared by every `MontyRun::new`, so interning a name is a
+/// single hash lookup rather than strum's generated comparison against every variant.
+static STATIC_STRINGS_MAP: LazyLock<AHashMap<&'static str, StaticStrings>> = LazyLock::new(|| {
+    (0..=u8::MAX)
+        .filter_map(StaticStrings::from_repr)
+        .map(|ss| (ss.into(), ss))
+        .collect()
+});
+
+/// Forces initialization of the process-wide interning tables.
+pub(crate) fn warm_up() {
+    LazyLock::force(&ASCII_STRS);
+    LazyLock::force(&STATIC_STRINGS_MAP);
+}
+
 /// Static string values which are known at compile time and don't need to be interned.
 #[repr(u8)]
 #[derive(
@@ -187,6 +206,13 @@ pub enum StaticStrings {
     Hex,
     Fromhex,
 
+    // ==========================
+    // int methods
+    BitLength,
+    BitCount,
+    ToBytes,
+    FromBytes,
+
     // ==========================
     // sys module strings
     Sys,
@@ -197,6 +223,7 @@ pub enum StaticStrings {
     Platform,
     Stdout,
     Stderr,
+    Exit,
     Major,
     Minor,
     Micro,
@@ -291,6 +318,12 @@ pub enum StaticStrings {
     Environ,
     Default,
 
+    // ==========================
+    // time module strings (`time` itself is shared with the resources() keys below)
+    Monotonic,
+    PerfCounter,
+    Sleep,
+
     // ==========================
     // Exception attributes
     Args,
@@ -349,6 +382,12 @@ pub enum StaticStrings {
     Start,
     Stop,
     Step,
+
+    // resources() dict keys
+    Memory,
+    Allocations,
+    Time,
+    Instructions,
 }
 
 impl StaticStrings {
@@ -360,6 +399,13 @@ impl StaticStrings {
         let enum_id = id.0.checked_sub(STATIC_STRING_ID_OFFSET)?;
         u8::try_from(enum_id).ok().and_then(Self::from_repr)
     }
+
+    /// Looks up the static string variant whose text is exactly `s`.
+    ///
+    /// Equivalent to the strum-derived `FromStr`, but backed by the shared `STATIC_STRINGS_MAP`.
+    pub fn from_static_str(s: &str) -> Option<Self> {
+        STATIC_STRINGS_MAP.get(s).copied()
+    }
 }
 
 /// Converts this static string variant to its corresponding `StringId`.
@@ -511,9 +557,9 @@ impl InternerBuilder {
     /// values keep stable IDs, and newly interned values are appended.
     pub(crate) fn from_interns(interns: &Interns, code: &str) -> Self {
         let mut builder = Self::new(code);
-        builder.strings.clone_from(&interns.strings);
-        builder.bytes.clone_from(&interns.bytes);
-        builder.long_ints.clone_from(&interns.long_ints);
+        builder.strings = interns.strings.iter().map(|s| String::from(&**s)).collect();
+        builder.bytes = interns.bytes.iter().map(|b| b.to_vec()).collect();
+        builder.long_ints = interns.long_ints.iter().map(|n| (**n).clone()).collect();
 
         builder.string_map = builder
             .strings
@@ -538,7 +584,7 @@ impl InternerBuilder {
     pub fn intern(&mut self, s: &str) -> StringId {
         if s.len() == 1 {
             StringId::from_ascii(s.as_bytes()[0])
-        } else if let Ok(ss) = StaticStrings::from_str(s) {
+        } else if let Some(ss) = StaticStrings::from_static_str(s) {
             ss.into()
         } else {
             *self.string_map.entry(s.to_owned()).or_insert_with(|| {
@@ -580,37 +626,94 @@ impl InternerBuilder {
 /// # Panics
 ///
 /// Panics if the `StringId` is invalid - not from this interner or ascii chars or StaticStrings.
-fn get_str(strings: &[String], id: StringId) -> &str {
+fn get_str<S: AsRef<str>>(strings: &[S], id: StringId) -> &str {
     if let Ok(c) = u8::try_from(id.0) {
         ASCII_STRS[c as usize]
     } else if let Some(intern_index) = id.index().checked_sub(INTERN_STRING_ID_OFFSET) {
-        &strings[intern_index]
+        strings[intern_index].as_ref()
     } else {
         let static_str = StaticStrings::from_string_id(id).expect("Invalid static string ID");
         static_str.into()
     }
 }
 
+/// How many entries each table of an `Interns` holds.
+///
+/// Deserialized values hold raw indexes into these tables, so a crafted snapshot can hold
+/// ids that would make lookups panic; `check` finds them before the values are used.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct InternBounds {
+    strings: usize,
+    bytes: usize,
+    long_ints: usize,
+    /// Also the bound for `FunctionId`s held by heap data; see `Interns::function_count`.
+    pub functions: usize,
+    external_functions: usize,
+}
+
+impl InternBounds {
+    /// Checks that an interned string, bytes, long integer, function or external function
+    /// held by `value` exists, returning a description of the missing one otherwise.
+    pub fn check(self, value: &Value) -> Result<(), String> {
+        let (kind, index, len) = match value {
+            Value::InternString(id) => {
+                // mirrors the lookup in `get_str`
+                let exists = match id.index().checked_sub(INTERN_STRING_ID_OFFSET) {
+                    Some(index) => index < self.strings,
+                    None => u8::try_from(id.0).is_ok() || StaticStrings::from_string_id(*id).is_some(),
+                };
+                return if exists {
+                    Ok(())
+                } else {
+                    Err(format!("string {}, which does not exist", id.index()))
+                };
+            }
+            Value::InternBytes(id) => ("bytes", id.index(), self.bytes),
+            Value::InternLongInt(id) => ("long integer", id.index(), self.long_ints),
+            Value::DefFunction(id) => ("function", id.index(), self.functions),
+            Value::ExtFunction(id) => ("external function", id.index(), self.external_functions),
+            _ => return Ok(()),
+        };
+        if index < len {
+            Ok(())
+        } else {
+            Err(format!("{kind} {index}, which does not exist"))
+        }
+    }
+}
+
 /// Read-only storage for interned strings, bytes, and long integers.
 ///
 /// This provides lookup by `StringId`, `BytesId`, `LongIntId` and `FunctionId` for interned literals and functions.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub(crate) struct Interns {
-    strings: Vec<String>,
-    bytes: Vec<Vec<u8>>,
-    long_ints: Vec<BigInt>,
+    strings: Vec<Arc<str>>,
+    bytes: Vec<Arc<[u8]>>,
+    long_ints: Vec<Arc<BigInt>>,
     functions: Vec<Function>,
     external_functions: Vec<String>,
+    /// Annotations of the external functions, indexed like `external_functions`; empty
+    /// unless external calls are checked.
+    external_signatures: Vec<Option<ExternalSignature>>,
+    /// Whether calls to each external function are batched, indexed like `external_functions`;
+    /// empty unless some are.
+    external_batched: Vec<bool>,
+    /// Whether each external function is a host iterable, indexed like `external_functions`;
+    /// empty unless some are.
+    external_iterables: Vec<bool>,
 }
 
 impl Interns {
     pub fn new(interner: InternerBuilder, functions: Vec<Function>, external_functions: Vec<String>) -> Self {
         Self {
-            strings: interner.strings,
-            bytes: interner.bytes,
-            long_ints: interner.long_ints,
+            strings: interner.strings.into_iter().map(Arc::from).collect(),
+            bytes: interner.bytes.into_iter().map(Arc::from).collect(),
+            long_ints: interner.long_ints.into_iter().map(Arc::new).collect(),
             functions,
             external_functions,
+            external_signatures: Vec::new(),
+            external_batched: Vec::new(),
+            external_iterables: Vec::new(),
         }
     }
 
@@ -644,6 +747,12 @@ impl Interns {
         &self.long_ints[id.index()]
     }
 
+    /// Swaps the interned values for copies shared through `shared`, so programs using the
+    /// same table keep one copy of each value they have in common.
+    pub fn share(&mut self, shared: &SharedInterns) {
+        shared.share(&mut self.strings, &mut self.bytes, &mut self.long_ints);
+    }
+
     /// Lookup a function by its `FunctionId`
     ///
     /// # Panics
@@ -654,6 +763,23 @@ impl Interns {
         self.functions.get(id.index()).expect("Function not found")
     }
 
+    /// Returns the number of compiled functions, the bound for valid `FunctionId`s.
+    #[inline]
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Returns the sizes of the tables, for checking ids from a deserialized snapshot.
+    pub fn bounds(&self) -> InternBounds {
+        InternBounds {
+            strings: self.strings.len(),
+            bytes: self.bytes.len(),
+            long_ints: self.long_ints.len(),
+            functions: self.functions.len(),
+            external_functions: self.external_functions.len(),
+        }
+    }
+
     /// Lookup an external function name by its `ExtFunctionId`
     ///
     /// # Panics
@@ -667,6 +793,40 @@ impl Interns {
             .clone()
     }
 
+    /// Looks up the annotations declared for an external function, if any.
+    #[inline]
+    pub fn get_external_signature(&self, id: ExtFunctionId) -> Option<&ExternalSignature> {
+        self.external_signatures.get(id.index()).and_then(Option::as_ref)
+    }
+
+    /// Sets the annotations external calls are checked against.
+    pub fn set_external_signatures(&mut self, signatures: Vec<Option<ExternalSignature>>) {
+        self.external_signatures = signatures;
+    }
+
+    /// Returns whether calls to an external function are batched rather than yielded one at a time.
+    #[inline]
+    pub fn is_external_batched(&self, id: ExtFunctionId) -> bool {
+        self.external_batched.get(id.index()).copied().unwrap_or(false)
+    }
+
+    /// Sets which external functions have their calls batched.
+    pub fn set_external_batched(&mut self, batched: Vec<bool>) {
+        self.external_batched = batched;
+    }
+
+    /// Returns whether an external function is a host iterable that scripts loop over
+    /// rather than call.
+    #[inline]
+    pub fn is_external_iterable(&self, id: ExtFunctionId) -> bool {
+        self.external_iterables.get(id.index()).copied().unwrap_or(false)
+    }
+
+    /// Sets which external functions are host iterables.
+    pub fn set_external_iterables(&mut self, iterables: Vec<bool>) {
+        self.external_iterables = iterables;
+    }
+
     /// Sets the compiled functions.
     ///
     /// This is called after compilation to populate the functions that were
@@ -10,13 +10,14 @@ use crate::exception_public::MontyException;
 ///
 /// # Variants
 /// - `Disabled` - Silently discards all output (useful for benchmarking or suppressing output)
-/// - `Stdout` - Writes to standard output (the default behavior)
+/// - `Stdout` - Writes to standard output (the default behavior, requires the `stdout` feature)
 /// - `Collect` - Accumulates output into an owned `String` for programmatic access
 /// - `Callback` - Delegates to a user-provided [`PrintWriterCallback`] implementation
 pub enum PrintWriter<'a> {
     /// Silently discard all output.
     Disabled,
     /// Write to standard output.
+    #[cfg(feature = "stdout")]
     Stdout,
     /// Collect all output into a string.
     Collect(String),
@@ -33,6 +34,7 @@ impl PrintWriter<'_> {
     pub fn stdout_write(&mut self, output: Cow<'_, str>) -> Result<(), MontyException> {
         match self {
             Self::Disabled => Ok(()),
+            #[cfg(feature = "stdout")]
             Self::Stdout => {
                 print!("{output}");
                 Ok(())
@@ -52,6 +54,7 @@ impl PrintWriter<'_> {
     pub fn stdout_push(&mut self, end: char) -> Result<(), MontyException> {
         match self {
             Self::Disabled => Ok(()),
+            #[cfg(feature = "stdout")]
             Self::Stdout => {
                 print!("{end}");
                 Ok(())
@@ -2,43 +2,103 @@
 // first to include defer_drop macro
 mod heap;
 
+mod annotations;
 mod args;
+pub mod ast;
 mod asyncio;
+#[cfg(feature = "heap-audit")]
+mod audit;
+mod bounded_repr;
+mod builder;
 mod builtins;
 mod bytecode;
+mod checkpoint;
+pub mod clock;
+mod codecs;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+mod coverage;
+mod deterministic;
+mod diagnostics;
+mod eval;
 mod exception_private;
 mod exception_public;
+mod exit;
 mod expressions;
+mod external_calls;
+mod fold;
 mod fstring;
 mod function;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+mod host_iterable;
+mod instance;
 mod intern;
 mod io;
+mod messages;
 mod modules;
 mod namespace;
 mod object;
 mod os;
 mod parse;
+mod pool;
 mod prepare;
+mod profile;
 mod repl;
+mod replay;
 mod resource;
 mod run;
+pub mod sectest;
+mod shared_interns;
 mod signature;
+mod snapshot_format;
+mod timeline;
+mod trace;
 mod types;
+mod undefined_names;
 mod value;
+mod warnings;
 
+#[cfg(feature = "heap-audit")]
+pub use crate::audit::{AuditedEntry, HeapAudit};
 #[cfg(feature = "ref-count-return")]
 pub use crate::run::RefCountOutput;
 pub use crate::{
+    builder::{ConfiguredRun, DeterministicLayer, MontyRunBuilder, NoLayer, TraceLayer, TrackerLayer},
+    bytecode::{CodeDisassembly, Instruction},
+    checkpoint::{CheckpointError, CheckpointPolicy, Checkpointer},
+    clock::{Clock, ClockTracker, FrozenClock, SystemClock},
+    coverage::CoverageReport,
+    deterministic::DeterministicTracker,
+    diagnostics::{Diagnostic, Fix, Severity},
+    eval::{EvalOptions, eval_expr, eval_expr_with_options},
     exception_private::ExcType,
     exception_public::{CodeLoc, MontyException, StackFrame},
+    exit::Exit,
+    external_calls::{ExternalCallSite, ExternalFunctionUsage},
+    host_iterable::HostIterable,
+    instance::MontyInstance,
     io::{PrintWriter, PrintWriterCallback},
-    object::{DictPairs, InvalidInputError, MontyObject},
+    messages::{ClassifiedMessage, ErrorCode, Hint, MessageCatalog},
+    object::{ConversionError, ConversionErrorKind, DictPairs, InvalidInputError, MontyObject},
     os::{OsFunction, dir_stat, file_stat, stat_result, symlink_stat},
+    pool::Pool,
+    profile::{LineProfile, ProfileReport},
     repl::{
         MontyRepl, ReplContinuationMode, ReplFutureSnapshot, ReplProgress, ReplSnapshot, detect_repl_continuation_mode,
     },
+    replay::{CallLog, CallLogEntry, RecordedCall, RecordedCallKind, RecordedResult, ReplayError, Replayer},
     resource::{
-        DEFAULT_MAX_RECURSION_DEPTH, LimitedTracker, NoLimitTracker, ResourceError, ResourceLimits, ResourceTracker,
+        CancelHandle, CancellableTracker, CollectionKind, DEFAULT_MAX_DATA_DEPTH, DEFAULT_MAX_RECURSION_DEPTH,
+        LimitedTracker, NoLimitTracker, ResourceBudget, ResourceError, ResourceLimits, ResourceTracker,
     },
-    run::{ExternalResult, FutureSnapshot, MontyFuture, MontyRun, RunProgress, Snapshot},
+    run::{
+        BatchedCall, BreakpointSnapshot, CompileOptions, CompiledProgram, ExternalResult, FutureSnapshot, MontyFuture,
+        MontyRun, PausedSnapshot, RunProgress, Snapshot,
+    },
+    shared_interns::SharedInterns,
+    snapshot_format::{SNAPSHOT_FORMAT_VERSION, SnapshotError},
+    timeline::{DEFAULT_MAX_TIMELINE_SPANS, SpanKind, Timeline, TimelineHandle, TimelineSpan, TimelineTracker},
+    trace::{TraceHook, TracePosition, TracingTracker},
+    warnings::{MontyWarning, WarningCategory, WarningSink, WarningTracker},
 };
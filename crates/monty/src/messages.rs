@@ -0,0 +1,460 @@
+//! Stable error codes for interpreter messages, and a catalog hosts can use to reword them.
+//!
+//! Exception messages inside the sandbox must stay exactly as CPython writes them, since
+//! scripts can inspect `str(e)`. Rewording therefore happens only when an exception is
+//! presented to an end user: [`ErrorCode::classify`] recognizes a finished English message
+//! by its template and extracts the values that were substituted into it, and a
+//! [`MessageCatalog`] renders those values into the host's own template instead.
+//!
+//! Templates use `{0}`, `{1}`, ... as placeholders. The interpreter builds the messages
+//! listed here from the same templates, so recognition can't drift from what is raised.
+
+use std::fmt::{self, Write};
+
+use ahash::AHashMap;
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+
+use crate::{ExcType, MontyException};
+
+/// Stable identifier for an interpreter error message.
+///
+/// The identifiers (see [`ErrorCode::code`]) never change once published, even if the
+/// English wording does, so hosts can key translations on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `name '{0}' is not defined`
+    NameNotDefined,
+    /// `cannot access local variable '{0}' where it is not associated with a value`
+    UnboundLocal,
+    /// `cannot access free variable '{0}' where it is not associated with a value in enclosing scope`
+    UnboundFreeVariable,
+    /// `'{0}' object has no attribute '{1}'`
+    AttributeNotFound,
+    /// `module '{0}' has no attribute '{1}'`
+    ModuleAttributeNotFound,
+    /// `No module named '{0}'`
+    ModuleNotFound,
+    /// `cannot import name '{0}' from '{1}' (unknown location)`
+    CannotImportName,
+    /// `'{0}' object is not subscriptable`
+    NotSubscriptable,
+    /// `'{0}' object is not callable`
+    NotCallable,
+    /// `'{0}' object is not iterable`
+    NotIterable,
+    /// `'{0}' object cannot be interpreted as an integer`
+    NotAnInteger,
+    /// `unhashable type: '{0}'`
+    Unhashable,
+    /// `unsupported operand type(s) for {0}: '{1}' and '{2}'`
+    UnsupportedOperand,
+    /// `can only concatenate {0} (not "{1}") to {0}`
+    ConcatenateMismatch,
+    /// `bad operand type for unary {0}: '{1}'`
+    BadUnaryOperand,
+    /// `division by zero`
+    DivisionByZero,
+    /// `zero to a negative power`
+    ZeroNegativePower,
+    /// `list index out of range`
+    ListIndexOutOfRange,
+    /// `tuple index out of range`
+    TupleIndexOutOfRange,
+    /// `string index out of range`
+    StrIndexOutOfRange,
+}
+
+impl ErrorCode {
+    /// Returns the stable identifier, e.g. `"name-not-defined"`.
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        self.into()
+    }
+
+    /// Returns the exception type raised with this message.
+    #[must_use]
+    pub fn exc_type(self) -> ExcType {
+        match self {
+            Self::NameNotDefined | Self::UnboundFreeVariable => ExcType::NameError,
+            Self::UnboundLocal => ExcType::UnboundLocalError,
+            Self::AttributeNotFound | Self::ModuleAttributeNotFound => ExcType::AttributeError,
+            Self::ModuleNotFound => ExcType::ModuleNotFoundError,
+            Self::CannotImportName => ExcType::ImportError,
+            Self::NotSubscriptable
+            | Self::NotCallable
+            | Self::NotIterable
+            | Self::NotAnInteger
+            | Self::Unhashable
+            | Self::UnsupportedOperand
+            | Self::ConcatenateMismatch
+            | Self::BadUnaryOperand => ExcType::TypeError,
+            Self::DivisionByZero | Self::ZeroNegativePower => ExcType::ZeroDivisionError,
+            Self::ListIndexOutOfRange | Self::TupleIndexOutOfRange | Self::StrIndexOutOfRange => ExcType::IndexError,
+        }
+    }
+
+    /// Returns the English template the interpreter uses for this message.
+    #[must_use]
+    pub fn template(self) -> &'static str {
+        match self {
+            Self::NameNotDefined => "name '{0}' is not defined",
+            Self::UnboundLocal => "cannot access local variable '{0}' where it is not associated with a value",
+            Self::UnboundFreeVariable => {
+                "cannot access free variable '{0}' where it is not associated with a value in enclosing scope"
+            }
+            Self::AttributeNotFound => "'{0}' object has no attribute '{1}'",
+            Self::ModuleAttributeNotFound => "module '{0}' has no attribute '{1}'",
+            Self::ModuleNotFound => "No module named '{0}'",
+            Self::CannotImportName => "cannot import name '{0}' from '{1}' (unknown location)",
+            Self::NotSubscriptable => "'{0}' object is not subscriptable",
+            Self::NotCallable => "'{0}' object is not callable",
+            Self::NotIterable => "'{0}' object is not iterable",
+            Self::NotAnInteger => "'{0}' object cannot be interpreted as an integer",
+            Self::Unhashable => "unhashable type: '{0}'",
+            Self::UnsupportedOperand => "unsupported operand type(s) for {0}: '{1}' and '{2}'",
+            Self::ConcatenateMismatch => "can only concatenate {0} (not \"{1}\") to {0}",
+            Self::BadUnaryOperand => "bad operand type for unary {0}: '{1}'",
+            Self::DivisionByZero => "division by zero",
+            Self::ZeroNegativePower => "zero to a negative power",
+            Self::ListIndexOutOfRange => "list index out of range",
+            Self::TupleIndexOutOfRange => "tuple index out of range",
+            Self::StrIndexOutOfRange => "string index out of range",
+        }
+    }
+
+    /// Recognizes an exception message, returning its code, the substituted values and any hint.
+    ///
+    /// Returns `None` for messages that have no code yet.
+    #[must_use]
+    pub fn classify(exc_type: ExcType, message: &str) -> Option<ClassifiedMessage> {
+        let (base, hint) = split_hint(exc_type, message);
+        let (code, args) = Self::iter()
+            .filter(|code| code.exc_type() == exc_type)
+            .find_map(|code| Some((code, match_template(code.template(), base)?)))?;
+        Some(ClassifiedMessage { code, args, hint })
+    }
+
+    /// Renders this message's English template with `args`.
+    pub(crate) fn format(self, args: &[&dyn fmt::Display]) -> String {
+        render(self.template(), args)
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Stable identifier for a hint appended to an error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Hint {
+    /// `. Did you forget to import '{0}'?`, appended to `NameError` for known modules.
+    ForgotImport,
+}
+
+impl Hint {
+    /// Returns the stable identifier, e.g. `"forgot-import"`.
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        self.into()
+    }
+
+    /// Returns the exception type whose messages can carry this hint.
+    #[must_use]
+    pub fn exc_type(self) -> ExcType {
+        match self {
+            Self::ForgotImport => ExcType::NameError,
+        }
+    }
+
+    /// Returns the English template appended to the message, including its leading separator.
+    #[must_use]
+    pub fn template(self) -> &'static str {
+        match self {
+            Self::ForgotImport => ". Did you forget to import '{0}'?",
+        }
+    }
+
+    /// Renders this hint's English template with `args`.
+    pub(crate) fn format(self, args: &[&dyn fmt::Display]) -> String {
+        render(self.template(), args)
+    }
+}
+
+/// An exception message broken down by [`ErrorCode::classify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassifiedMessage {
+    /// Which message this is.
+    pub code: ErrorCode,
+    /// Values substituted into the template, indexed by placeholder number.
+    pub args: Vec<String>,
+    /// The hint appended to the message, with its own values.
+    pub hint: Option<(Hint, Vec<String>)>,
+}
+
+/// Host-provided replacements for error messages and hints, keyed by stable code.
+///
+/// Codes without a replacement keep the interpreter's English text, so a catalog can be
+/// filled in incrementally.
+///
+/// # Example
+/// ```
+/// use monty::{ErrorCode, MessageCatalog, MontyRun};
+///
+/// let catalog = MessageCatalog::new().message(ErrorCode::NameNotDefined, "le nom « {0} » n'est pas défini");
+///
+/// let runner = MontyRun::new("x + 1".to_owned(), "test.py", vec![], vec![]).unwrap();
+/// let err = runner.run_no_limits(vec![]).unwrap_err();
+/// assert_eq!(err.message(), Some("name 'x' is not defined"));
+/// assert_eq!(catalog.localize(&err).message(), Some("le nom « x » n'est pas défini"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    messages: AHashMap<ErrorCode, String>,
+    hints: AHashMap<Hint, String>,
+}
+
+impl MessageCatalog {
+    /// Creates an empty catalog, which leaves every message unchanged.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the template used for `code`, with the same placeholders as [`ErrorCode::template`].
+    #[must_use]
+    pub fn message(mut self, code: ErrorCode, template: impl Into<String>) -> Self {
+        self.messages.insert(code, template.into());
+        self
+    }
+
+    /// Sets the template used for `hint`, with the same placeholders as [`Hint::template`].
+    ///
+    /// The template replaces the whole suffix, including its leading separator.
+    #[must_use]
+    pub fn hint(mut self, hint: Hint, template: impl Into<String>) -> Self {
+        self.hints.insert(hint, template.into());
+        self
+    }
+
+    /// Returns the message `exc` should be presented with.
+    ///
+    /// Unrecognized messages, and recognized ones with no replacement, are returned as is.
+    #[must_use]
+    pub fn render_message(&self, exc_type: ExcType, message: &str) -> String {
+        let Some(classified) = ErrorCode::classify(exc_type, message) else {
+            return message.to_owned();
+        };
+        let template = self
+            .messages
+            .get(&classified.code)
+            .map_or(classified.code.template(), String::as_str);
+        let mut out = render(template, &display_args(&classified.args));
+        if let Some((hint, args)) = &classified.hint {
+            let template = self.hints.get(hint).map_or(hint.template(), String::as_str);
+            out.push_str(&render(template, &display_args(args)));
+        }
+        out
+    }
+
+    /// Returns a copy of `exc` with its message replaced from this catalog.
+    ///
+    /// The exception type and traceback are unchanged.
+    #[must_use]
+    pub fn localize(&self, exc: &MontyException) -> MontyException {
+        let mut localized = exc.clone();
+        if let Some(message) = exc.message() {
+            localized.set_message(self.render_message(exc.exc_type(), message));
+        }
+        localized
+    }
+}
+
+/// Part of a template: literal text or a numbered placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    Placeholder(usize),
+}
+
+/// Splits a template into literal text and `{N}` placeholders.
+///
+/// Braces that don't form a placeholder are kept as literal text.
+fn segments(template: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let placeholder = rest[open + 1..]
+            .find('}')
+            .and_then(|close| Some((rest[open + 1..open + 1 + close].parse().ok()?, open + close + 2)));
+        let Some((index, end)) = placeholder else {
+            segments.push(Segment::Literal(&rest[..=open]));
+            rest = &rest[open + 1..];
+            continue;
+        };
+        if open > 0 {
+            segments.push(Segment::Literal(&rest[..open]));
+        }
+        segments.push(Segment::Placeholder(index));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+    segments
+}
+
+/// Substitutes `args` into `template`; placeholders without a value render as `{N}`.
+fn render(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    for segment in segments(template) {
+        match segment {
+            Segment::Literal(text) => out.push_str(text),
+            Segment::Placeholder(index) => match args.get(index) {
+                Some(arg) => write!(out, "{arg}").unwrap(),
+                None => write!(out, "{{{index}}}").unwrap(),
+            },
+        }
+    }
+    out
+}
+
+/// Borrows extracted values for passing to `render`.
+fn display_args(args: &[String]) -> Vec<&dyn fmt::Display> {
+    args.iter().map(|arg| arg as &dyn fmt::Display).collect()
+}
+
+/// Matches `message` against `template`, returning the value of each placeholder.
+///
+/// A placeholder extends to the first occurrence of the literal that follows it, or to the
+/// end of the message if that literal ends the template. A placeholder used more than once
+/// must have the same value each time.
+fn match_template(template: &str, message: &str) -> Option<Vec<String>> {
+    let segments = segments(template);
+    let mut args: Vec<Option<&str>> = Vec::new();
+    let mut rest = message;
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            Segment::Literal(text) => rest = rest.strip_prefix(text)?,
+            Segment::Placeholder(index) => {
+                let value = match segments.get(i + 1) {
+                    None => std::mem::take(&mut rest),
+                    Some(Segment::Literal(next)) if i + 2 == segments.len() => {
+                        let value = rest.strip_suffix(next)?;
+                        rest = &rest[value.len()..];
+                        value
+                    }
+                    Some(Segment::Literal(next)) => {
+                        let (value, after) = rest.split_at(rest.find(next)?);
+                        rest = after;
+                        value
+                    }
+                    // adjacent placeholders can't be told apart
+                    Some(Segment::Placeholder(_)) => return None,
+                };
+                if args.len() <= index {
+                    args.resize(index + 1, None);
+                }
+                match args[index] {
+                    Some(previous) if previous != value => return None,
+                    _ => args[index] = Some(value),
+                }
+            }
+        }
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    args.into_iter().map(|arg| arg.map(str::to_owned)).collect()
+}
+
+/// Separates a trailing hint from `message`, if one for `exc_type` matches.
+fn split_hint(exc_type: ExcType, message: &str) -> (&str, Option<(Hint, Vec<String>)>) {
+    for hint in Hint::iter().filter(|hint| hint.exc_type() == exc_type) {
+        let template = hint.template();
+        let lead = match segments(template).first() {
+            Some(Segment::Literal(lead)) => *lead,
+            _ => continue,
+        };
+        let Some(start) = message.rfind(lead) else {
+            continue;
+        };
+        if let Some(args) = match_template(template, &message[start..]) {
+            return (&message[..start], Some((hint, args)));
+        }
+    }
+    (message, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_round_trip() {
+        for code in ErrorCode::iter() {
+            let args = ["a", "b", "c"];
+            let args: Vec<&dyn fmt::Display> = args.iter().map(|arg| arg as &dyn fmt::Display).collect();
+            let message = code.format(&args);
+            let classified = ErrorCode::classify(code.exc_type(), &message)
+                .unwrap_or_else(|| panic!("{code} did not match its own template: {message}"));
+            assert_eq!(classified.code, code);
+        }
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let codes: Vec<&str> = ErrorCode::iter().map(ErrorCode::code).collect();
+        for (i, code) in codes.iter().enumerate() {
+            assert!(!codes[i + 1..].contains(code), "duplicate code {code}");
+        }
+    }
+
+    #[test]
+    fn match_template_extracts_args() {
+        assert_eq!(
+            match_template(
+                "'{0}' object has no attribute '{1}'",
+                "'list' object has no attribute 'it's'"
+            ),
+            Some(vec!["list".to_owned(), "it's".to_owned()])
+        );
+        assert_eq!(
+            match_template(
+                "can only concatenate {0} (not \"{1}\") to {0}",
+                "can only concatenate str (not \"int\") to str"
+            ),
+            Some(vec!["str".to_owned(), "int".to_owned()])
+        );
+        assert_eq!(
+            match_template(
+                "can only concatenate {0} (not \"{1}\") to {0}",
+                "can only concatenate str (not \"int\") to list"
+            ),
+            None
+        );
+        assert_eq!(match_template("division by zero", "division by zero!"), None);
+    }
+
+    #[test]
+    fn hint_is_split_from_message() {
+        let classified = ErrorCode::classify(
+            ExcType::NameError,
+            "name 'sys' is not defined. Did you forget to import 'sys'?",
+        )
+        .unwrap();
+        assert_eq!(classified.code, ErrorCode::NameNotDefined);
+        assert_eq!(classified.args, vec!["sys".to_owned()]);
+        assert_eq!(classified.hint, Some((Hint::ForgotImport, vec!["sys".to_owned()])));
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders() {
+        assert_eq!(render("{0} and {1} {x}", &[&"a"]), "a and {1} {x}");
+    }
+}
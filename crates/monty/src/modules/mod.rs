@@ -1,7 +1,7 @@
 //! Built-in module implementations.
 //!
 //! This module provides implementations for Python built-in modules like `sys`, `typing`,
-//! and `asyncio`. These are created on-demand when import statements are executed.
+//! `asyncio` and `time`. These are created on-demand when import statements are executed.
 
 use std::fmt::{self, Write};
 
@@ -20,6 +20,7 @@ pub(crate) mod asyncio;
 pub(crate) mod os;
 pub(crate) mod pathlib;
 pub(crate) mod sys;
+pub(crate) mod time;
 pub(crate) mod typing;
 
 /// Built-in modules that can be imported.
@@ -36,6 +37,8 @@ pub(crate) enum BuiltinModule {
     Pathlib,
     /// The `os` module providing operating system interface (only `getenv()` implemented).
     Os,
+    /// The `time` module reading the host's clock, with `sleep()` suspending to the host.
+    Time,
 }
 
 impl BuiltinModule {
@@ -47,6 +50,7 @@ impl BuiltinModule {
             StaticStrings::Asyncio => Some(Self::Asyncio),
             StaticStrings::Pathlib => Some(Self::Pathlib),
             StaticStrings::Os => Some(Self::Os),
+            StaticStrings::Time => Some(Self::Time),
             _ => None,
         }
     }
@@ -65,6 +69,7 @@ impl BuiltinModule {
             Self::Asyncio => asyncio::create_module(heap, interns),
             Self::Pathlib => pathlib::create_module(heap, interns),
             Self::Os => os::create_module(heap, interns),
+            Self::Time => time::create_module(heap, interns),
         }
     }
 }
@@ -73,14 +78,18 @@ impl BuiltinModule {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub(crate) enum ModuleFunctions {
     Asyncio(asyncio::AsyncioFunctions),
+    Sys(sys::SysFunctions),
     Os(os::OsFunctions),
+    Time(time::TimeFunctions),
 }
 
 impl fmt::Display for ModuleFunctions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Asyncio(func) => write!(f, "{func}"),
+            Self::Sys(func) => write!(f, "{func}"),
             Self::Os(func) => write!(f, "{func}"),
+            Self::Time(func) => write!(f, "{func}"),
         }
     }
 }
@@ -88,12 +97,20 @@ impl fmt::Display for ModuleFunctions {
 impl ModuleFunctions {
     /// Calls the module function with the given arguments.
     ///
-    /// Returns `AttrCallResult` to support both immediate values and OS calls that
-    /// require host involvement (e.g., `os.getenv()` needs the host to provide environment variables).
-    pub fn call(self, heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<AttrCallResult> {
+    /// Returns `AttrCallResult` to support both immediate values and calls that require host
+    /// involvement (e.g., `os.getenv()` needs the host to provide environment variables, and
+    /// `time.sleep()` needs the host to resume execution).
+    pub fn call(
+        self,
+        heap: &mut Heap<impl ResourceTracker>,
+        interns: &Interns,
+        args: ArgValues,
+    ) -> RunResult<AttrCallResult> {
         match self {
             Self::Asyncio(functions) => asyncio::call(heap, functions, args),
+            Self::Sys(functions) => sys::call(heap, interns, functions, args),
             Self::Os(functions) => os::call(heap, functions, args),
+            Self::Time(functions) => time::call(heap, functions, args),
         }
     }
 
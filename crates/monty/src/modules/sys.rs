@@ -6,15 +6,27 @@
 //! - `platform`: Platform identifier ("monty")
 //! - `stdout`: Marker for standard output (no real functionality)
 //! - `stderr`: Marker for standard error (no real functionality)
+//! - `exit(code=None)`: Raises `SystemExit`, which the host sees as `Exit::SystemExit`
 
 use crate::{
+    args::ArgValues,
+    defer_drop,
+    exception_private::{ExcType, RunResult},
     heap::{Heap, HeapData, HeapId},
     intern::{Interns, StaticStrings},
+    modules::ModuleFunctions,
     resource::{ResourceError, ResourceTracker},
-    types::{Module, NamedTuple},
+    types::{AttrCallResult, Module, NamedTuple},
     value::{Marker, Value},
 };
 
+/// Sys module functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, serde::Serialize, serde::Deserialize)]
+#[strum(serialize_all = "lowercase")]
+pub(crate) enum SysFunctions {
+    Exit,
+}
+
 /// Creates the `sys` module and allocates it on the heap.
 ///
 /// Returns a HeapId pointing to the newly allocated module.
@@ -70,5 +82,29 @@ pub fn create_module(heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -
     let version_info_id = heap.allocate(HeapData::NamedTuple(version_info))?;
     module.set_attr(StaticStrings::VersionInfo, Value::Ref(version_info_id), heap, interns);
 
+    // sys.exit
+    module.set_attr(
+        StaticStrings::Exit,
+        Value::ModuleFunction(ModuleFunctions::Sys(SysFunctions::Exit)),
+        heap,
+        interns,
+    );
+
     heap.allocate(HeapData::Module(module))
 }
+
+/// Dispatches a call to a sys module function.
+pub(super) fn call(
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+    functions: SysFunctions,
+    args: ArgValues,
+) -> RunResult<AttrCallResult> {
+    match functions {
+        SysFunctions::Exit => {
+            let code = args.get_zero_one_arg("exit", heap)?.unwrap_or(Value::None);
+            defer_drop!(code, heap);
+            Err(ExcType::system_exit(code, heap, interns).into())
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Implementation of the `time` module.
+//!
+//! Provides a minimal implementation of Python's `time` module with:
+//! - `time()`: Seconds since the Unix epoch
+//! - `monotonic()` / `perf_counter()`: Seconds from a clock that never goes backwards
+//! - `sleep(secs)`: Suspends the script until the host resumes it
+//!
+//! Times are read from the resource tracker's `Clock`, so hosts can substitute their own.
+//! `sleep()` never blocks inside the VM: execution yields `RunProgress::Sleep` with the
+//! requested duration and the host's scheduler decides when to resume.
+
+use std::time::Duration;
+
+use crate::{
+    args::ArgValues,
+    defer_drop,
+    exception_private::{ExcType, RunResult, SimpleException},
+    heap::{Heap, HeapData, HeapId},
+    intern::{Interns, StaticStrings},
+    modules::ModuleFunctions,
+    resource::{ResourceError, ResourceTracker},
+    types::{AttrCallResult, Module, PyTrait},
+    value::Value,
+};
+
+/// Time module functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, serde::Serialize, serde::Deserialize)]
+#[strum(serialize_all = "snake_case")]
+pub(crate) enum TimeFunctions {
+    Time,
+    Monotonic,
+    PerfCounter,
+    Sleep,
+}
+
+/// Creates the `time` module and allocates it on the heap.
+///
+/// # Returns
+/// A HeapId pointing to the newly allocated module.
+///
+/// # Panics
+/// Panics if the required strings have not been pre-interned during prepare phase.
+pub fn create_module(heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> Result<HeapId, ResourceError> {
+    let mut module = Module::new(StaticStrings::Time);
+
+    for (name, function) in [
+        (StaticStrings::Time, TimeFunctions::Time),
+        (StaticStrings::Monotonic, TimeFunctions::Monotonic),
+        (StaticStrings::PerfCounter, TimeFunctions::PerfCounter),
+        (StaticStrings::Sleep, TimeFunctions::Sleep),
+    ] {
+        module.set_attr(
+            name,
+            Value::ModuleFunction(ModuleFunctions::Time(function)),
+            heap,
+            interns,
+        );
+    }
+
+    heap.allocate(HeapData::Module(module))
+}
+
+/// Dispatches a call to a time module function.
+///
+/// Returns `AttrCallResult::Sleep` for `sleep()`, which the host must resume, and
+/// `AttrCallResult::Value` for the clock readings.
+pub(super) fn call(
+    heap: &mut Heap<impl ResourceTracker>,
+    functions: TimeFunctions,
+    args: ArgValues,
+) -> RunResult<AttrCallResult> {
+    let seconds = match functions {
+        TimeFunctions::Time => {
+            args.check_zero_args("time.time", heap)?;
+            heap.tracker().clock().time()
+        }
+        TimeFunctions::Monotonic => {
+            args.check_zero_args("time.monotonic", heap)?;
+            heap.tracker().clock().monotonic()
+        }
+        TimeFunctions::PerfCounter => {
+            args.check_zero_args("time.perf_counter", heap)?;
+            heap.tracker().clock().monotonic()
+        }
+        TimeFunctions::Sleep => return sleep(heap, args),
+    };
+    Ok(AttrCallResult::Value(Value::Float(seconds)))
+}
+
+/// Implementation of `time.sleep(secs)`.
+///
+/// Validates the duration like CPython, then returns `AttrCallResult::Sleep` so the VM
+/// yields to the host instead of blocking.
+///
+/// # Errors
+/// Returns `TypeError` if `secs` isn't a number, `ValueError` if it is negative or NaN,
+/// and `OverflowError` if it is too large for a `Duration`.
+fn sleep(heap: &mut Heap<impl ResourceTracker>, args: ArgValues) -> RunResult<AttrCallResult> {
+    let secs = args.get_one_arg("time.sleep", heap)?;
+    defer_drop!(secs, heap);
+
+    let seconds = match secs {
+        Value::Bool(b) => f64::from(u8::from(*b)),
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        Value::Ref(id) if matches!(heap.get(*id), HeapData::LongInt(_)) => f64::INFINITY,
+        _ => {
+            let type_name = secs.py_type(heap);
+            return Err(ExcType::type_error(format!(
+                "'{type_name}' object cannot be interpreted as an integer"
+            )));
+        }
+    };
+    if seconds.is_nan() {
+        return Err(SimpleException::new_msg(ExcType::ValueError, "Invalid value NaN (not a number)").into());
+    }
+    if seconds < 0.0 {
+        return Err(SimpleException::new_msg(ExcType::ValueError, "sleep length must be non-negative").into());
+    }
+    let Ok(duration) = Duration::try_from_secs_f64(seconds) else {
+        return Err(
+            SimpleException::new_msg(ExcType::OverflowError, "timestamp out of range for platform time_t").into(),
+        );
+    };
+    Ok(AttrCallResult::Sleep(duration))
+}
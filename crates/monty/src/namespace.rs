@@ -1,6 +1,6 @@
 use crate::{
     exception_private::ExceptionRaise,
-    heap::{Heap, HeapId},
+    heap::{Heap, HeapId, HeapIdMap},
     parse::CodeRange,
     resource::{ResourceError, ResourceTracker},
     value::Value,
@@ -52,6 +52,10 @@ impl Namespace {
     pub fn mut_vec(&mut self) -> &mut Vec<Value> {
         &mut self.0
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 impl IntoIterator for Namespace {
@@ -104,6 +108,12 @@ pub struct Namespaces {
     /// When set, the next call to `take_ext_return_value` will return this error,
     /// allowing it to propagate through try/except blocks.
     ext_exception: Option<ExceptionRaise>,
+    /// Names of the leading global slots that are read-only, indexed by slot.
+    ///
+    /// Set by `MontyRepl::freeze_globals`; slots past the end of this list (including
+    /// globals introduced by later snippets) remain writable.
+    #[serde(default)]
+    frozen_globals: Vec<String>,
 }
 
 impl Namespaces {
@@ -117,9 +127,23 @@ impl Namespaces {
             ext_return_values: vec![],
             next_ext_return_value: 0,
             ext_exception: None,
+            frozen_globals: Vec::new(),
         }
     }
 
+    /// Makes the first `names.len()` global slots read-only.
+    ///
+    /// `names[i]` is the name of global slot `i`, used in the error raised on writes.
+    pub fn freeze_globals(&mut self, names: Vec<String>) {
+        self.frozen_globals = names;
+    }
+
+    /// Returns the name of the global at `slot` if it is frozen, `None` if it is writable.
+    #[inline]
+    pub fn frozen_global_name(&self, slot: NamespaceId) -> Option<&str> {
+        self.frozen_globals.get(slot.index()).map(String::as_str)
+    }
+
     /// Gets an immutable slice reference to a namespace by index.
     ///
     /// Used for reading from the enclosing namespace when defining closures,
@@ -236,6 +260,23 @@ impl Namespaces {
         self.reuse_ids.push(namespace_id);
     }
 
+    /// Drops every value left by a finished run, keeping the namespaces' allocations for
+    /// another run: the global namespace is emptied and function namespaces are queued for reuse.
+    pub fn reset(&mut self, heap: &mut Heap<impl ResourceTracker>) {
+        for namespace in &mut self.stack {
+            for value in namespace.0.drain(..) {
+                value.drop_with_heap(heap);
+            }
+        }
+        for (_, value) in self.ext_return_values.drain(..) {
+            value.drop_with_heap(heap);
+        }
+        self.next_ext_return_value = 0;
+        self.ext_exception = None;
+        self.reuse_ids.clear();
+        self.reuse_ids.extend((1..self.stack.len()).rev().map(NamespaceId::new));
+    }
+
     /// Cleans up the global namespace by dropping all values with proper ref counting.
     ///
     /// Call this before the namespaces is dropped to properly decrement reference counts
@@ -277,4 +318,17 @@ impl Namespaces {
             .iter()
             .flat_map(|namespace| namespace.0.iter().filter_map(Value::ref_id))
     }
+
+    /// Returns the number of namespaces, the bound for valid frame `NamespaceId`s.
+    pub fn count(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Rewrites heap ids in all namespaces and pending external return values after heap compaction.
+    pub fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        for namespace in &mut self.stack {
+            map.remap_values(&mut namespace.0);
+        }
+        map.remap_values(self.ext_return_values.iter_mut().map(|(_, value)| value));
+    }
 }
@@ -6,11 +6,13 @@ use std::{
 use ahash::AHashSet;
 use indexmap::IndexMap;
 use num_bigint::BigInt;
-use num_traits::Zero;
+use num_traits::{NumCast, Signed, ToPrimitive, Zero};
 
 use crate::{
+    bounded_repr::write_bounded,
     builtins::{Builtins, BuiltinsFunctions},
-    exception_private::{ExcType, SimpleException},
+    exception_private::{ExcType, ExceptionInstance, SimpleException},
+    fstring::float_repr,
     heap::{Heap, HeapData, HeapId},
     intern::Interns,
     resource::{DepthGuard, ResourceError, ResourceTracker},
@@ -266,7 +268,7 @@ impl MontyObject {
                 Ok(Value::Ref(heap.allocate(HeapData::FrozenSet(frozenset))?))
             }
             Self::Exception { exc_type, arg } => {
-                let exc = SimpleException::new(exc_type, arg);
+                let exc = ExceptionInstance::new(SimpleException::new(exc_type, arg));
                 Ok(Value::Ref(heap.allocate(HeapData::Exception(exc))?))
             }
             Self::Dataclass {
@@ -295,7 +297,8 @@ impl MontyObject {
         }
     }
 
-    fn from_value(object: &Value, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> Self {
+    /// Converts a `Value` into a `MontyObject` without consuming it.
+    pub(crate) fn from_value(object: &Value, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> Self {
         let mut visited = AHashSet::new();
         let mut guard = DepthGuard::default();
         Self::from_value_inner(object, heap, &mut visited, &mut guard, interns)
@@ -423,9 +426,9 @@ impl MontyObject {
                         let _ = range.py_repr_fmt(&mut s, heap, visited, guard, interns);
                         Self::Repr(s)
                     }
-                    HeapData::Exception(exc) => Self::Exception {
-                        exc_type: exc.exc_type(),
-                        arg: exc.arg().map(ToString::to_string),
+                    HeapData::Exception(e) => Self::Exception {
+                        exc_type: e.exc().exc_type(),
+                        arg: e.exc().arg().map(ToString::to_string),
                     },
                     HeapData::Dataclass(dc) => {
                         // Convert attrs to DictPairs
@@ -500,6 +503,24 @@ impl MontyObject {
         s
     }
 
+    /// Returns the [`Display`](fmt::Display) text of this object, cut to at most `limit`
+    /// characters ending in `...` if it doesn't fit.
+    ///
+    /// Unlike `to_string()`, formatting stops as soon as the limit is reached, so showing a
+    /// huge or deeply nested object costs no more than the text returned.
+    ///
+    /// ```
+    /// use monty::MontyObject;
+    ///
+    /// let numbers = MontyObject::List((0..1_000_000).map(MontyObject::Int).collect());
+    /// assert_eq!(numbers.display_truncated(16), "[0, 1, 2, 3, ...");
+    /// assert_eq!(MontyObject::Int(42).display_truncated(16), "42");
+    /// ```
+    #[must_use]
+    pub fn display_truncated(&self, limit: usize) -> String {
+        write_bounded(limit, |f| write!(f, "{self}"))
+    }
+
     fn repr_fmt(&self, f: &mut impl Write) -> fmt::Result {
         match self {
             Self::Ellipsis => f.write_str("Ellipsis"),
@@ -508,14 +529,7 @@ impl MontyObject {
             Self::Bool(false) => f.write_str("False"),
             Self::Int(v) => write!(f, "{v}"),
             Self::BigInt(v) => write!(f, "{v}"),
-            Self::Float(v) => {
-                let s = v.to_string();
-                f.write_str(&s)?;
-                if !s.contains('.') {
-                    f.write_str(".0")?;
-                }
-                Ok(())
-            }
+            Self::Float(v) => f.write_str(&float_repr(*v)),
             Self::String(s) => string_repr_fmt(s, f),
             Self::Bytes(b) => f.write_str(&bytes_repr(b)),
             Self::List(l) => {
@@ -688,6 +702,38 @@ impl MontyObject {
         }
     }
 
+    /// Converts a numeric value to `f64`, allowing precision loss.
+    ///
+    /// `Int` and `BigInt` are rounded to the nearest float; a `BigInt` too large for
+    /// `f64` becomes `inf` or `-inf`. `Float` is returned unchanged.
+    ///
+    /// # Errors
+    /// Returns a `ConversionError` if the value is not an int or float.
+    pub fn as_f64_lossy(&self) -> Result<f64, ConversionError> {
+        match self {
+            Self::Float(f) => Ok(*f),
+            Self::Int(i) => Ok(*i as f64),
+            Self::BigInt(bi) => Ok(bi.to_f64().unwrap_or(if bi.is_negative() {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            })),
+            _ => Err(ConversionError::new("float", self.type_name())),
+        }
+    }
+
+    /// Converts an `Int` or `BigInt` value to a `BigInt`.
+    ///
+    /// # Errors
+    /// Returns a `ConversionError` if the value is not an int.
+    pub fn to_bigint(&self) -> Result<BigInt, ConversionError> {
+        match self {
+            Self::Int(i) => Ok(BigInt::from(*i)),
+            Self::BigInt(bi) => Ok(bi.clone()),
+            _ => Err(ConversionError::new("int", self.type_name())),
+        }
+    }
+
     /// Returns the Python type name for this value (e.g., `"int"`, `"str"`, `"list"`).
     ///
     /// These are the same names returned by Python's `type(x).__name__`.
@@ -716,6 +762,99 @@ impl MontyObject {
             Self::Cycle(_, _) => "cycle",
         }
     }
+
+    /// Returns the number of items, like Python's `len()`.
+    ///
+    /// Strings are measured in characters, not bytes. Returns `None` for values that have
+    /// no length, such as ints or dataclasses.
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Self::String(s) => Some(s.chars().count()),
+            Self::Bytes(b) => Some(b.len()),
+            Self::List(items) | Self::Tuple(items) | Self::Set(items) | Self::FrozenSet(items) => Some(items.len()),
+            Self::NamedTuple { values, .. } => Some(values.len()),
+            Self::Dict(d) => Some(d.0.len()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the value has no items, or `None` if it has no length (see `len()`).
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Iterates over the items of a list, tuple, named tuple, set or frozenset.
+    ///
+    /// # Errors
+    /// Returns a `ConversionError` for any other value, including dicts; use `as_dict()`
+    /// to iterate over a dict's items.
+    pub fn iter(&self) -> Result<std::slice::Iter<'_, Self>, ConversionError> {
+        match self {
+            Self::List(items) | Self::Tuple(items) | Self::Set(items) | Self::FrozenSet(items) => Ok(items.iter()),
+            Self::NamedTuple { values, .. } => Ok(values.iter()),
+            _ => Err(ConversionError::new("sequence", self.type_name())),
+        }
+    }
+
+    /// Iterates over the `(key, value)` pairs of a dict in insertion order.
+    ///
+    /// # Errors
+    /// Returns a `ConversionError` if the value is not a dict.
+    pub fn as_dict(&self) -> Result<impl Iterator<Item = (&Self, &Self)>, ConversionError> {
+        match self {
+            Self::Dict(d) => Ok(d.iter().map(|(key, value)| (key, value))),
+            _ => Err(ConversionError::new("dict", self.type_name())),
+        }
+    }
+
+    /// Looks up `key` like Python's `obj[key]`.
+    ///
+    /// Lists, tuples and named tuples take an int index, negative indices counting from the
+    /// end; dicts take any key. Returns `None` if the index is out of range, the key is
+    /// missing, or the value can't be subscripted.
+    #[must_use]
+    pub fn get_item(&self, key: impl Into<Self>) -> Option<&Self> {
+        let key = key.into();
+        match self {
+            Self::List(items) | Self::Tuple(items) => sequence_item(items, &key),
+            Self::NamedTuple { values, .. } => sequence_item(values, &key),
+            Self::Dict(d) => d.iter().find(|(k, _)| *k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Converts the value to a Rust type, e.g. `obj.extract::<Vec<i64>>()`.
+    ///
+    /// Accepts any type with a `TryFrom<&MontyObject>` implementation: the integer types,
+    /// `f64`, `bool` and `String`, plus `Vec<T>` for sequences and `Option<T>` where `None`
+    /// becomes `None`.
+    ///
+    /// # Errors
+    /// Returns a `ConversionError` if the value, or any item of it, has the wrong type or
+    /// is out of range.
+    pub fn extract<'a, T>(&'a self) -> Result<T, ConversionError>
+    where
+        T: TryFrom<&'a Self, Error = ConversionError>,
+    {
+        T::try_from(self)
+    }
+}
+
+/// Returns the item of a sequence at a Python-style index, supporting negative indices.
+fn sequence_item<'a>(items: &'a [MontyObject], key: &MontyObject) -> Option<&'a MontyObject> {
+    let index = match key {
+        MontyObject::Int(i) => *i,
+        MontyObject::Bool(b) => i64::from(*b),
+        _ => return None,
+    };
+    let index = if index < 0 {
+        index.checked_add(i64::try_from(items.len()).ok()?)?
+    } else {
+        index
+    };
+    items.get(usize::try_from(index).ok()?)
 }
 
 impl Hash for MontyObject {
@@ -840,26 +979,55 @@ impl AsRef<Self> for MontyObject {
 /// Error returned when a `MontyObject` cannot be converted to the requested Rust type.
 ///
 /// This error is returned by the `TryFrom` implementations when attempting to extract
-/// a specific type from a `MontyObject` that holds a different variant.
+/// a specific type from a `MontyObject` that holds a different variant, or when a
+/// Python int doesn't fit in the requested Rust integer type.
 #[derive(Debug)]
 pub struct ConversionError {
-    /// The type name that was expected (e.g., "int", "str").
+    /// The type name that was expected (e.g., "int", "str", or "u32" for overflow).
     pub expected: &'static str,
     /// The actual type name of the `MontyObject` (e.g., "list", "NoneType").
     pub actual: &'static str,
+    /// Why the conversion failed.
+    pub kind: ConversionErrorKind,
+}
+
+/// The reason a `MontyObject` conversion failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionErrorKind {
+    /// The object is not of the expected Python type.
+    WrongType,
+    /// The object is an int, but its value is out of range for the target Rust type.
+    Overflow,
 }
 
 impl ConversionError {
     /// Creates a new `ConversionError` with the expected and actual type names.
     #[must_use]
     pub fn new(expected: &'static str, actual: &'static str) -> Self {
-        Self { expected, actual }
+        Self {
+            expected,
+            actual,
+            kind: ConversionErrorKind::WrongType,
+        }
+    }
+
+    /// Creates a `ConversionError` for a value that doesn't fit in the `expected` Rust type.
+    #[must_use]
+    pub fn overflow(expected: &'static str, actual: &'static str) -> Self {
+        Self {
+            expected,
+            actual,
+            kind: ConversionErrorKind::Overflow,
+        }
     }
 }
 
 impl fmt::Display for ConversionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "expected {}, got {}", self.expected, self.actual)
+        match self.kind {
+            ConversionErrorKind::WrongType => write!(f, "expected {}, got {}", self.expected, self.actual),
+            ConversionErrorKind::Overflow => write!(f, "{} value out of range for {}", self.actual, self.expected),
+        }
     }
 }
 
@@ -904,16 +1072,81 @@ impl From<crate::resource::ResourceError> for InvalidInputError {
     }
 }
 
-/// Attempts to convert a MontyObject to an i64 integer.
-/// Returns an error if the object is not an Int variant.
-impl TryFrom<&MontyObject> for i64 {
-    type Error = ConversionError;
+/// Implements checked `TryFrom<&MontyObject>` for Rust integer types.
+///
+/// Both `Int` and `BigInt` are accepted; values that don't fit in the target type
+/// return an overflow `ConversionError` rather than being truncated. Other variants
+/// (including `Bool`) return a wrong-type error.
+macro_rules! impl_try_from_int {
+    ($($ty:ty),* $(,)?) => {$(
+        impl TryFrom<&MontyObject> for $ty {
+            type Error = ConversionError;
 
-    fn try_from(value: &MontyObject) -> Result<Self, Self::Error> {
-        match value {
-            MontyObject::Int(i) => Ok(*i),
-            _ => Err(ConversionError::new("int", value.type_name())),
+            fn try_from(value: &MontyObject) -> Result<Self, Self::Error> {
+                let converted = match value {
+                    MontyObject::Int(i) => <Self as NumCast>::from(*i),
+                    MontyObject::BigInt(bi) => Self::try_from(bi).ok(),
+                    _ => return Err(ConversionError::new("int", value.type_name())),
+                };
+                converted.ok_or_else(|| ConversionError::overflow(stringify!($ty), value.type_name()))
+            }
         }
+    )*};
+}
+
+impl_try_from_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Implements `From<int>` for `MontyObject` for integer types that always fit in an `i64`.
+macro_rules! impl_from_small_int {
+    ($($ty:ty),* $(,)?) => {$(
+        impl From<$ty> for MontyObject {
+            fn from(value: $ty) -> Self {
+                Self::Int(i64::from(value))
+            }
+        }
+    )*};
+}
+
+impl_from_small_int!(i8, i16, i32, i64, u8, u16, u32);
+
+/// Implements `From<int>` for `MontyObject` for integer types that may exceed `i64`,
+/// falling back to `BigInt` for values out of range.
+macro_rules! impl_from_wide_int {
+    ($($ty:ty),* $(,)?) => {$(
+        impl From<$ty> for MontyObject {
+            fn from(value: $ty) -> Self {
+                match i64::try_from(value) {
+                    Ok(i) => Self::Int(i),
+                    Err(_) => Self::BigInt(BigInt::from(value)),
+                }
+            }
+        }
+    )*};
+}
+
+impl_from_wide_int!(i128, isize, u64, u128, usize);
+
+impl From<bool> for MontyObject {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for MontyObject {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for MontyObject {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for MontyObject {
+    fn from(value: String) -> Self {
+        Self::String(value)
     }
 }
 
@@ -960,6 +1193,35 @@ impl TryFrom<&MontyObject> for bool {
     }
 }
 
+/// Converts a list, tuple, named tuple, set or frozenset to a `Vec`, converting each item.
+///
+/// Note that `bytes` is not a sequence here: `Vec<u8>` extracts a list of ints.
+impl<T> TryFrom<&MontyObject> for Vec<T>
+where
+    T: for<'a> TryFrom<&'a MontyObject, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &MontyObject) -> Result<Self, Self::Error> {
+        value.iter()?.map(T::try_from).collect()
+    }
+}
+
+/// Converts `None` to `None` and anything else to `Some`, like an `Optional[T]` annotation.
+impl<T> TryFrom<&MontyObject> for Option<T>
+where
+    T: for<'a> TryFrom<&'a MontyObject, Error = ConversionError>,
+{
+    type Error = ConversionError;
+
+    fn try_from(value: &MontyObject) -> Result<Self, Self::Error> {
+        match value {
+            MontyObject::None => Ok(None),
+            _ => T::try_from(value).map(Some),
+        }
+    }
+}
+
 /// A collection of key-value pairs representing Python dictionary contents.
 ///
 /// Used internally by `MontyObject::Dict` to store dictionary entries while preserving
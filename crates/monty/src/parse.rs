@@ -3,14 +3,15 @@ use std::{borrow::Cow, fmt};
 use num_bigint::BigInt;
 use ruff_python_ast::{
     self as ast, BoolOp, CmpOp, ConversionFlag as RuffConversionFlag, ElifElseClause, Expr as AstExpr,
-    InterpolatedStringElement, Keyword, Number, Operator as AstOperator, ParameterWithDefault, Stmt, UnaryOp,
-    name::Name,
+    InterpolatedStringElement, Keyword, Number, Operator as AstOperator, ParameterWithDefault, PySourceType, Stmt,
+    UnaryOp, name::Name,
 };
-use ruff_python_parser::parse_module;
+use ruff_python_parser::{parse_module, parse_unchecked_source};
 use ruff_text_size::{Ranged, TextRange};
 
 use crate::{
     StackFrame,
+    annotations::Annotation,
     args::{ArgExprs, Kwarg},
     exception_private::ExcType,
     exception_public::{CodeLoc, MontyException},
@@ -20,6 +21,7 @@ use crate::{
     fstring::{ConversionFlag, FStringPart, FormatSpec},
     intern::{InternerBuilder, StringId},
     value::EitherStr,
+    warnings::WarningCategory,
 };
 
 /// Maximum nesting depth for AST structures during parsing.
@@ -40,6 +42,8 @@ pub struct ParsedParam {
     pub name: StringId,
     /// The default value expression (evaluated at definition time).
     pub default: Option<ExprLoc>,
+    /// The type annotation, e.g. `int` in `x: int`.
+    pub annotation: Option<Annotation>,
 }
 
 /// A parsed function signature with all parameter types.
@@ -89,6 +93,8 @@ pub struct RawFunctionDef {
     pub signature: ParsedSignature,
     /// The unprepared function body (names not yet resolved).
     pub body: Vec<ParseNode>,
+    /// The return annotation, e.g. `str` in `def f() -> str:`.
+    pub returns: Option<Annotation>,
     /// Whether this is an async function (`async def`).
     pub is_async: bool,
 }
@@ -127,6 +133,16 @@ pub struct ExceptHandler<N> {
 pub struct ParseResult {
     pub nodes: Vec<ParseNode>,
     pub interner: InternerBuilder,
+    /// Compile-time warnings found while parsing.
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// A compile-time warning found while parsing, see [`crate::warnings`].
+#[derive(Debug)]
+pub struct ParseWarning {
+    pub category: WarningCategory,
+    pub message: String,
+    pub position: CodeRange,
 }
 
 pub(crate) fn parse(code: &str, filename: &str) -> Result<ParseResult, ParseError> {
@@ -149,9 +165,70 @@ pub(crate) fn parse_with_interner(
     Ok(ParseResult {
         nodes,
         interner: parser.interner,
+        warnings: parser.warnings,
     })
 }
 
+/// Output of [`parse_recovering`].
+pub(crate) struct RecoveredParse {
+    /// The parse result, or every error found.
+    pub result: Result<ParseResult, Vec<ParseError>>,
+    /// Statements that can never run because an earlier statement in the same body always
+    /// leaves it, one range per body.
+    pub unreachable: Vec<CodeRange>,
+}
+
+/// Parses code like [`parse`], but reports every error instead of stopping at the first.
+///
+/// If the code has syntax errors, all of them are returned. Otherwise each statement Monty
+/// can't convert is reported and skipped, so later statements (including the rest of the
+/// enclosing body) are still checked.
+pub(crate) fn parse_recovering(code: &str, filename: &str) -> RecoveredParse {
+    let mut parser = Parser::new(code, filename, InternerBuilder::new(code));
+    let parsed = parse_unchecked_source(code, PySourceType::Python);
+    if !parsed.errors().is_empty() {
+        let errors = parsed
+            .errors()
+            .iter()
+            .map(|e| ParseError::syntax(e.error.to_string(), parser.convert_range(e.range())))
+            .collect();
+        return RecoveredParse {
+            result: Err(errors),
+            unreachable: Vec::new(),
+        };
+    }
+
+    parser.recovery = Some(Recovery::default());
+    let nodes = parser.parse_statements(parsed.into_syntax().body);
+    let recovery = parser.recovery.take().expect("recovery is only taken here");
+    let unreachable = recovery
+        .unreachable
+        .into_iter()
+        .map(|r| parser.convert_range(r))
+        .collect();
+    let mut errors = recovery.errors;
+    let result = match nodes {
+        Ok(nodes) if errors.is_empty() => Ok(ParseResult {
+            nodes,
+            interner: parser.interner,
+            warnings: parser.warnings,
+        }),
+        Ok(_) => Err(errors),
+        Err(err) => {
+            errors.push(err);
+            Err(errors)
+        }
+    };
+    RecoveredParse { result, unreachable }
+}
+
+/// Errors and unreachable code collected while parsing with [`parse_recovering`].
+#[derive(Default)]
+struct Recovery {
+    errors: Vec<ParseError>,
+    unreachable: Vec<TextRange>,
+}
+
 /// Parser for converting ruff AST to Monty's intermediate ParseNode representation.
 ///
 /// Holds references to the source code and owns a string interner for names.
@@ -167,6 +244,11 @@ pub struct Parser<'a> {
     /// Starts at MAX_NESTING_DEPTH and decrements on each nested level.
     /// When it reaches zero, we return a "too many nested parentheses" error.
     depth_remaining: u16,
+    /// Set when parsing with [`parse_recovering`], to collect statement errors instead of
+    /// returning the first one.
+    recovery: Option<Recovery>,
+    /// Compile-time warnings found so far.
+    warnings: Vec<ParseWarning>,
 }
 
 impl<'a> Parser<'a> {
@@ -185,11 +267,46 @@ impl<'a> Parser<'a> {
             filename_id,
             interner,
             depth_remaining: MAX_NESTING_DEPTH,
+            recovery: None,
+            warnings: Vec::new(),
         }
     }
 
+    /// Records a compile-time warning for the code at `position`.
+    fn warn(&mut self, category: WarningCategory, message: impl Into<String>, position: CodeRange) {
+        self.warnings.push(ParseWarning {
+            category,
+            message: message.into(),
+            position,
+        });
+    }
+
     fn parse_statements(&mut self, statements: Vec<Stmt>) -> Result<Vec<ParseNode>, ParseError> {
-        statements.into_iter().map(|f| self.parse_statement(f)).collect()
+        let exit = statements
+            .iter()
+            .position(|s| matches!(s, Stmt::Return(_) | Stmt::Raise(_) | Stmt::Break(_) | Stmt::Continue(_)));
+        if let Some(exit) = exit
+            && let (Some(first), Some(last)) = (statements.get(exit + 1), statements.last())
+        {
+            let unreachable = TextRange::new(first.start(), last.end());
+            let position = self.convert_range(unreachable);
+            self.warn(WarningCategory::SyntaxWarning, "code is unreachable", position);
+            if let Some(recovery) = &mut self.recovery {
+                recovery.unreachable.push(unreachable);
+            }
+        }
+        if self.recovery.is_none() {
+            return statements.into_iter().map(|f| self.parse_statement(f)).collect();
+        }
+
+        let mut nodes = Vec::with_capacity(statements.len());
+        for statement in statements {
+            match self.parse_statement(statement) {
+                Ok(node) => nodes.push(node),
+                Err(err) => self.recovery.as_mut().expect("recovering").errors.push(err),
+            }
+        }
+        Ok(nodes)
     }
 
     fn parse_elif_else_clauses(&mut self, clauses: Vec<ElifElseClause>) -> Result<Vec<ParseNode>, ParseError> {
@@ -267,11 +384,13 @@ impl<'a> Parser<'a> {
                 // Parse function body recursively
                 let body = self.parse_statements(function.body)?;
                 let is_async = function.is_async;
+                let returns = function.returns.map(|returns| Annotation::new(&returns, self.code));
 
                 Ok(Node::FunctionDef(RawFunctionDef {
                     name,
                     signature,
                     body,
+                    returns,
                     is_async,
                 }))
             }
@@ -355,13 +474,18 @@ impl<'a> Parser<'a> {
                 "pattern matching (match statements)",
                 self.convert_range(m.range),
             )),
-            Stmt::Raise(ast::StmtRaise { exc, .. }) => {
-                // TODO add cause to Node::Raise
+            Stmt::Raise(ast::StmtRaise { exc, cause, .. }) => {
                 let expr = match exc {
                     Some(expr) => Some(self.parse_expression(*expr)?),
                     None => None,
                 };
-                Ok(Node::Raise(expr))
+                match (expr, cause) {
+                    (Some(exc), Some(cause)) => Ok(Node::RaiseFrom {
+                        exc,
+                        cause: self.parse_expression(*cause)?,
+                    }),
+                    (expr, _) => Ok(Node::Raise(expr)),
+                }
             }
             Stmt::Try(ast::StmtTry {
                 body,
@@ -393,7 +517,17 @@ impl<'a> Parser<'a> {
                     }))
                 }
             }
-            Stmt::Assert(ast::StmtAssert { test, msg, .. }) => {
+            Stmt::Assert(ast::StmtAssert { test, msg, range, .. }) => {
+                if let AstExpr::Tuple(tuple) = &*test
+                    && !tuple.elts.is_empty()
+                {
+                    let position = self.convert_range(range);
+                    self.warn(
+                        WarningCategory::SyntaxWarning,
+                        "assertion is always true, perhaps remove parentheses?",
+                        position,
+                    );
+                }
                 let test = self.parse_expression(*test)?;
                 let msg = match msg {
                     Some(m) => Some(self.parse_expression(*m)?),
@@ -532,6 +666,7 @@ impl<'a> Parser<'a> {
                     .into_iter()
                     .map(|e| self.parse_unpack_target(e)) // Use parse_unpack_target for recursion
                     .collect::<Result<Vec<_>, _>>()?;
+                check_starred_targets(&targets, targets_position)?;
                 Ok(Node::UnpackAssign {
                     targets,
                     targets_position,
@@ -545,12 +680,17 @@ impl<'a> Parser<'a> {
                     .into_iter()
                     .map(|e| self.parse_unpack_target(e))
                     .collect::<Result<Vec<_>, _>>()?;
+                check_starred_targets(&targets, targets_position)?;
                 Ok(Node::UnpackAssign {
                     targets,
                     targets_position,
                     object: self.parse_expression(rhs)?,
                 })
             }
+            AstExpr::Starred(ast::ExprStarred { range, .. }) => Err(ParseError::syntax(
+                "starred assignment target must be in a list or tuple",
+                self.convert_range(range),
+            )),
             // Simple identifier assignment like x = value
             _ => Ok(Node::Assign {
                 target: self.parse_identifier(lhs)?,
@@ -768,14 +908,11 @@ impl<'a> Parser<'a> {
             AstExpr::Generator(ast::ExprGenerator {
                 elt, generators, range, ..
             }) => {
-                // TODO: When proper generators are implemented, this should produce
-                // Expr::Generator instead of Expr::ListComp. Currently we treat generator
-                // expressions as list comprehensions since we don't have generator support.
                 let elt = Box::new(self.parse_expression(*elt)?);
                 let generators = self.parse_comprehension_generators(generators)?;
                 Ok(ExprLoc::new(
                     self.convert_range(range),
-                    Expr::ListComp { elt, generators },
+                    Expr::Generator { elt, generators },
                 ))
             }
             AstExpr::Await(a) => {
@@ -800,6 +937,7 @@ impl<'a> Parser<'a> {
                 let position = self.convert_range(range);
                 let ops_vec = ops.into_vec();
                 let comparators_vec = comparators.into_vec();
+                self.check_identity_with_literal(&left, &ops_vec, &comparators_vec, position);
 
                 // Simple case: single comparison (most common)
                 if ops_vec.len() == 1 {
@@ -1051,6 +1189,34 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Warns about `is` and `is not` comparisons with a literal operand, like CPython.
+    fn check_identity_with_literal(
+        &mut self,
+        left: &AstExpr,
+        ops: &[CmpOp],
+        comparators: &[AstExpr],
+        position: CodeRange,
+    ) {
+        let mut operand = left;
+        for (op, comparator) in ops.iter().zip(comparators) {
+            let check = match op {
+                CmpOp::Is => Some(("is", "==")),
+                CmpOp::IsNot => Some(("is not", "!=")),
+                _ => None,
+            };
+            if let Some((op, suggestion)) = check
+                && let Some(type_name) = literal_type_name(operand).or_else(|| literal_type_name(comparator))
+            {
+                self.warn(
+                    WarningCategory::SyntaxWarning,
+                    format!("\"{op}\" with '{type_name}' literal. Did you mean \"{suggestion}\"?"),
+                    position,
+                );
+            }
+            operand = comparator;
+        }
+    }
+
     /// Parses a chain comparison expression like `a < b < c < d`.
     ///
     /// Chain comparisons evaluate each intermediate value only once and short-circuit
@@ -1103,14 +1269,7 @@ impl<'a> Parser<'a> {
                 if targets.is_empty() {
                     return Err(ParseError::syntax("empty tuple in unpack target", position));
                 }
-                // Validate at most one starred target
-                let starred_count = targets.iter().filter(|t| matches!(t, UnpackTarget::Starred(_))).count();
-                if starred_count > 1 {
-                    return Err(ParseError::syntax(
-                        "multiple starred expressions in assignment",
-                        position,
-                    ));
-                }
+                check_starred_targets(&targets, position)?;
                 Ok(UnpackTarget::Tuple { targets, position })
             }
             AstExpr::Starred(ast::ExprStarred { value, range, .. }) => {
@@ -1135,16 +1294,22 @@ impl<'a> Parser<'a> {
                 if targets.is_empty() {
                     return Err(ParseError::syntax("empty list in unpack target", position));
                 }
-                // Validate at most one starred target
-                let starred_count = targets.iter().filter(|t| matches!(t, UnpackTarget::Starred(_))).count();
-                if starred_count > 1 {
-                    return Err(ParseError::syntax(
-                        "multiple starred expressions in assignment",
-                        position,
-                    ));
-                }
+                check_starred_targets(&targets, position)?;
                 Ok(UnpackTarget::Tuple { targets, position })
             }
+            // Item and attribute targets like `items[i], items[j] = items[j], items[i]`
+            AstExpr::Subscript(ast::ExprSubscript {
+                value, slice, range, ..
+            }) => Ok(UnpackTarget::Subscript {
+                object: self.parse_expression(*value)?,
+                index: self.parse_expression(*slice)?,
+                position: self.convert_range(range),
+            }),
+            AstExpr::Attribute(ast::ExprAttribute { value, attr, range, .. }) => Ok(UnpackTarget::Attr {
+                object: self.parse_expression(*value)?,
+                attr: EitherStr::Interned(self.interner.intern(attr.id())),
+                position: self.convert_range(range),
+            }),
             other => Err(ParseError::syntax(
                 format!("invalid unpacking target: {other:?}"),
                 self.convert_range(other.range()),
@@ -1171,7 +1336,16 @@ impl<'a> Parser<'a> {
                     Some(expr) => Some(self.parse_expression((**expr).clone())?),
                     None => None,
                 };
-                Ok(ParsedParam { name, default })
+                let annotation = p
+                    .parameter
+                    .annotation
+                    .as_ref()
+                    .map(|annotation| Annotation::new(annotation, self.code));
+                Ok(ParsedParam {
+                    name,
+                    default,
+                    annotation,
+                })
             })
             .collect()
     }
@@ -1195,6 +1369,12 @@ impl<'a> Parser<'a> {
                     ));
                 }
                 let target = self.parse_unpack_target(comp.target)?;
+                if let Some(position) = item_target_position(&target) {
+                    return Err(ParseError::not_implemented(
+                        "subscript or attribute targets in comprehensions",
+                        position,
+                    ));
+                }
                 let iter = self.parse_expression(comp.iter)?;
                 let ifs = comp
                     .ifs
@@ -1418,6 +1598,21 @@ fn convert_bool_op(op: BoolOp) -> Operator {
     }
 }
 
+/// Returns the type name of `expr` if it's a literal whose identity is unspecified, so
+/// comparing it with `is` is almost certainly a mistake.
+fn literal_type_name(expr: &AstExpr) -> Option<&'static str> {
+    match expr {
+        AstExpr::NumberLiteral(ast::ExprNumberLiteral { value, .. }) => Some(match value {
+            Number::Int(_) => "int",
+            Number::Float(_) => "float",
+            Number::Complex { .. } => "complex",
+        }),
+        AstExpr::StringLiteral(_) => Some("str"),
+        AstExpr::BytesLiteral(_) => Some("bytes"),
+        _ => None,
+    }
+}
+
 fn convert_compare_op(op: CmpOp) -> CmpOperator {
     match op {
         CmpOp::Eq => CmpOperator::Eq,
@@ -1523,6 +1718,11 @@ pub enum ParseError {
         msg: Cow<'static, str>,
         position: CodeRange,
     },
+    /// Name error (e.g., a builtin the compile options don't allow).
+    Name {
+        msg: Cow<'static, str>,
+        position: CodeRange,
+    },
 }
 
 impl ParseError {
@@ -1553,6 +1753,13 @@ impl ParseError {
             position,
         }
     }
+
+    pub(crate) fn name_error(msg: impl Into<Cow<'static, str>>, position: CodeRange) -> Self {
+        Self::Name {
+            msg: msg.into(),
+            position,
+        }
+    }
 }
 
 impl ParseError {
@@ -1578,10 +1785,36 @@ impl ParseError {
                 Some(msg.into_owned()),
                 vec![StackFrame::from_position_no_caret(position, filename, source)],
             ),
+            Self::Name { msg, position } => MontyException::new_full(
+                ExcType::NameError,
+                Some(msg.into_owned()),
+                vec![StackFrame::from_position(position, filename, source)],
+            ),
         }
     }
 }
 
+/// Checks that an unpacking level has at most one starred target.
+fn check_starred_targets(targets: &[UnpackTarget], position: CodeRange) -> Result<(), ParseError> {
+    let starred_count = targets.iter().filter(|t| matches!(t, UnpackTarget::Starred(_))).count();
+    if starred_count > 1 {
+        return Err(ParseError::syntax(
+            "multiple starred expressions in assignment",
+            position,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the position of the first subscript or attribute target, searching nested tuples.
+fn item_target_position(target: &UnpackTarget) -> Option<CodeRange> {
+    match target {
+        UnpackTarget::Name(_) | UnpackTarget::Starred(_) => None,
+        UnpackTarget::Tuple { targets, .. } => targets.iter().find_map(item_target_position),
+        UnpackTarget::Subscript { position, .. } | UnpackTarget::Attr { position, .. } => Some(*position),
+    }
+}
+
 /// Parses an integer literal string into a `BigInt`, handling radix prefixes and underscores.
 ///
 /// Supports Python integer literal formats:
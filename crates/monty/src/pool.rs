@@ -0,0 +1,107 @@
+//! Reusing heaps and namespaces across many runs of one program.
+//!
+//! Every `MontyRun::run()` allocates a fresh heap and namespaces and frees them when the
+//! run finishes. For workloads that run the same small script thousands of times, that
+//! setup is a noticeable share of each invocation. A [`Pool`] keeps the heap and namespaces
+//! of its last run and clears them instead, so later runs start with the entries, global
+//! slots and function namespaces already allocated.
+
+use std::sync::Arc;
+
+use crate::{
+    exception_public::MontyException,
+    heap::Heap,
+    io::PrintWriter,
+    namespace::{NamespaceId, Namespaces},
+    object::MontyObject,
+    resource::ResourceTracker,
+    run::{CompiledProgram, MontyRun},
+};
+
+/// Runs one compiled program repeatedly, recycling the heap and namespaces between runs.
+///
+/// Runs behave exactly like `MontyRun::run()`: each gets its own resource tracker, and no
+/// value survives from one run into the next. A pool runs one script at a time, so use one
+/// pool per worker thread; pools created from clones of one `MontyRun` share its compiled
+/// program.
+///
+/// # Example
+/// ```
+/// use monty::{MontyObject, MontyRun, NoLimitTracker, Pool, PrintWriter};
+///
+/// let runner = MontyRun::new("[x] * x".to_owned(), "test.py", vec!["x".to_owned()], vec![]).unwrap();
+/// let mut pool = Pool::new(&runner);
+///
+/// let inputs = (1..=3).map(|x| vec![MontyObject::Int(x)]);
+/// let results = pool.run_batch(inputs, || NoLimitTracker, &mut PrintWriter::Disabled);
+/// assert_eq!(results[1], Ok(MontyObject::List(vec![MontyObject::Int(2), MontyObject::Int(2)])));
+/// ```
+#[derive(Debug)]
+pub struct Pool<T: ResourceTracker> {
+    program: Arc<CompiledProgram>,
+    initial_globals: Arc<[(NamespaceId, MontyObject)]>,
+    /// Heap and namespaces of the last run, already cleared.
+    idle: Option<(Heap<T>, Namespaces)>,
+}
+
+impl<T: ResourceTracker> Pool<T> {
+    /// Creates a pool running the program compiled by `runner`.
+    #[must_use]
+    pub fn new(runner: &MontyRun) -> Self {
+        Self {
+            program: Arc::clone(runner.program()),
+            initial_globals: Arc::clone(runner.initial_globals()),
+            idle: None,
+        }
+    }
+
+    /// Executes the code to completion like `MontyRun::run()`, reusing the previous run's
+    /// heap and namespaces if there was one.
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the inputs are invalid or the code raises.
+    pub fn run(
+        &mut self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<MontyObject, MontyException> {
+        let (mut heap, mut namespaces) = match self.idle.take() {
+            Some((mut heap, namespaces)) => {
+                heap.reset(resource_tracker);
+                (heap, namespaces)
+            }
+            None => (self.program.new_heap(resource_tracker), self.program.new_namespaces()),
+        };
+        let result = self.program.run_with(
+            &mut heap,
+            &mut namespaces,
+            inputs,
+            &self.initial_globals,
+            None,
+            None,
+            None,
+            print,
+        );
+        // clear now rather than before the next run, so the values don't outlive the run
+        namespaces.reset(&mut heap);
+        self.idle = Some((heap, namespaces));
+        result
+    }
+
+    /// Executes the code once per set of inputs, in order, returning each run's result.
+    ///
+    /// `new_tracker` is called for each run, so limits such as `max_duration` apply to every
+    /// run separately. A run that raises doesn't stop the batch.
+    pub fn run_batch(
+        &mut self,
+        inputs: impl IntoIterator<Item = Vec<MontyObject>>,
+        mut new_tracker: impl FnMut() -> T,
+        print: &mut PrintWriter<'_>,
+    ) -> Vec<Result<MontyObject, MontyException>> {
+        inputs
+            .into_iter()
+            .map(|inputs| self.run(inputs, new_tracker(), print))
+            .collect()
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
     intern::{InternerBuilder, StringId},
     namespace::NamespaceId,
     parse::{CodeRange, ExceptHandler, ParseError, ParseNode, ParseResult, ParsedSignature, RawFunctionDef, Try},
+    run::CompileOptions,
     signature::Signature,
 };
 
@@ -43,13 +44,16 @@ pub struct PrepareResult {
 ///
 /// The namespace will be converted to runtime Objects when execution begins and the heap is available.
 /// At module level, the local namespace IS the global namespace.
+///
+/// Builtins `options` doesn't allow are rejected with a `NameError` where they're referenced.
 pub(crate) fn prepare(
     parse_result: ParseResult,
     input_names: Vec<String>,
     external_functions: &[String],
+    options: &CompileOptions,
 ) -> Result<PrepareResult, ParseError> {
-    let ParseResult { nodes, interner } = parse_result;
-    let mut p = Prepare::new_module(input_names, external_functions, &interner);
+    let ParseResult { nodes, interner, .. } = parse_result;
+    let mut p = Prepare::new_module(input_names, external_functions, &interner, options);
     let mut prepared_nodes = p.prepare_nodes(nodes)?;
 
     // In the root frame, the last expression is implicitly returned
@@ -75,12 +79,15 @@ pub(crate) fn prepare(
 ///
 /// Existing bindings keep their original namespace slots; any new names are appended with new slots.
 /// This ensures snippets can be compiled independently while sharing one persistent global namespace.
+///
+/// Builtins `options` doesn't allow are rejected as in [`prepare`].
 pub(crate) fn prepare_with_existing_names(
     parse_result: ParseResult,
     existing_name_map: AHashMap<String, NamespaceId>,
+    options: &CompileOptions,
 ) -> Result<PrepareResult, ParseError> {
-    let ParseResult { nodes, interner } = parse_result;
-    let mut p = Prepare::new_module_with_name_map(existing_name_map, &interner);
+    let ParseResult { nodes, interner, .. } = parse_result;
+    let mut p = Prepare::new_module_with_name_map(existing_name_map, &interner, options);
     let mut prepared_nodes = p.prepare_nodes(nodes)?;
 
     // In the root frame, the last expression is implicitly returned to match REPL behavior.
@@ -148,6 +155,15 @@ struct Prepare<'i> {
     /// that are both nonlocal and captured by nested functions), then extended as new
     /// captures are discovered during nested function preparation.
     cell_var_map: AHashMap<String, NamespaceId>,
+    /// Compiler options, consulted for which builtins the code may use.
+    options: &'i CompileOptions,
+}
+
+/// Returns `names` in sorted order, for assigning namespace slots deterministically.
+fn sorted_names(names: AHashSet<String>) -> Vec<String> {
+    let mut names: Vec<String> = names.into_iter().collect();
+    names.sort_unstable();
+    names
 }
 
 impl<'i> Prepare<'i> {
@@ -160,7 +176,13 @@ impl<'i> Prepare<'i> {
     /// * `input_names` - Names that should be pre-registered in the namespace (e.g., external variables)
     /// * `external_functions` - Names of external functions to pre-register
     /// * `interner` - Reference to the string interner for looking up names
-    fn new_module(input_names: Vec<String>, external_functions: &[String], interner: &'i InternerBuilder) -> Self {
+    /// * `options` - Compiler options, for which builtins are allowed
+    fn new_module(
+        input_names: Vec<String>,
+        external_functions: &[String],
+        interner: &'i InternerBuilder,
+        options: &'i CompileOptions,
+    ) -> Self {
         let mut name_map = AHashMap::with_capacity(input_names.len() + external_functions.len());
         for (index, name) in external_functions.iter().enumerate() {
             name_map.insert(name.clone(), NamespaceId::new(index));
@@ -181,13 +203,18 @@ impl<'i> Prepare<'i> {
             enclosing_locals: None,
             free_var_map: AHashMap::new(),
             cell_var_map: AHashMap::new(),
+            options,
         }
     }
 
     /// Creates a module-scope Prepare instance from an existing global name map.
     ///
     /// Used by incremental REPL compilation to keep stable slot assignments across snippets.
-    fn new_module_with_name_map(name_map: AHashMap<String, NamespaceId>, interner: &'i InternerBuilder) -> Self {
+    fn new_module_with_name_map(
+        name_map: AHashMap<String, NamespaceId>,
+        interner: &'i InternerBuilder,
+        options: &'i CompileOptions,
+    ) -> Self {
         let namespace_size = name_map
             .values()
             .map(|id| id.index())
@@ -206,6 +233,7 @@ impl<'i> Prepare<'i> {
             enclosing_locals: None,
             free_var_map: AHashMap::new(),
             cell_var_map: AHashMap::new(),
+            options,
         }
     }
 
@@ -225,6 +253,7 @@ impl<'i> Prepare<'i> {
     /// * `enclosing_locals` - Names that exist as locals in the enclosing function (for nonlocal resolution)
     /// * `cell_var_names` - Names that are captured by nested functions (must be stored in cells)
     /// * `interner` - Reference to the string interner for looking up names
+    /// * `options` - Compiler options, for which builtins are allowed
     #[expect(clippy::too_many_arguments)]
     fn new_function(
         capacity: usize,
@@ -237,6 +266,7 @@ impl<'i> Prepare<'i> {
         enclosing_locals: Option<AHashSet<String>>,
         cell_var_names: AHashSet<String>,
         interner: &'i InternerBuilder,
+        options: &'i CompileOptions,
     ) -> Self {
         let mut name_map = AHashMap::with_capacity(capacity);
         for (index, string_id) in params.iter().enumerate() {
@@ -252,9 +282,11 @@ impl<'i> Prepare<'i> {
         // nested functions - these stay in free_var_map since we receive the cell, not create it).
         // NOTE: We intentionally do NOT add these to name_map here, because the scope
         // validation checks name_map to detect "used before declaration" errors
+        // Slots are assigned in sorted name order: hash set iteration order varies between
+        // processes, and the compiled code must not.
         let mut cell_var_map = AHashMap::with_capacity(cell_var_names.len());
         let mut namespace_size = namespace_size;
-        for name in cell_var_names {
+        for name in sorted_names(cell_var_names) {
             if !nonlocal_names.contains(&name) && !implicit_captures.contains(&name) {
                 let slot = namespace_size;
                 namespace_size += 1;
@@ -268,13 +300,13 @@ impl<'i> Prepare<'i> {
         // validation in prepare_nodes checks name_map to detect "used before nonlocal declaration"
         let free_var_capacity = nonlocal_names.len() + implicit_captures.len();
         let mut free_var_map = AHashMap::with_capacity(free_var_capacity);
-        for name in nonlocal_names {
+        for name in sorted_names(nonlocal_names) {
             let slot = namespace_size;
             namespace_size += 1;
             free_var_map.insert(name, NamespaceId::new(slot));
         }
         // Implicit captures (variables accessed from enclosing scope without explicit nonlocal)
-        for name in implicit_captures {
+        for name in sorted_names(implicit_captures) {
             let slot = namespace_size;
             namespace_size += 1;
             free_var_map.insert(name, NamespaceId::new(slot));
@@ -292,9 +324,20 @@ impl<'i> Prepare<'i> {
             enclosing_locals,
             free_var_map,
             cell_var_map,
+            options,
         }
     }
 
+    /// Returns the names this function captures from enclosing scopes, in slot order.
+    ///
+    /// The enclosing function allocates cell slots for these as it walks them, so they
+    /// must come out in the same order on every compile, unlike `free_var_map.keys()`.
+    fn captured_names(&self) -> Vec<&String> {
+        let mut entries: Vec<_> = self.free_var_map.iter().collect();
+        entries.sort_by_key(|&(_, slot)| *slot);
+        entries.into_iter().map(|(name, _)| name).collect()
+    }
+
     /// Recursively prepares a sequence of AST nodes by resolving names and transforming expressions.
     ///
     /// This method processes each node type differently:
@@ -316,26 +359,16 @@ impl<'i> Prepare<'i> {
                 Node::ReturnNone => new_nodes.push(Node::ReturnNone),
                 Node::Raise(exc) => {
                     let expr = match exc {
-                        Some(expr) => {
-                            let prepared = self.prepare_expression(expr)?;
-                            match prepared.expr {
-                                // Handle raising a builtin exception type without instantiation,
-                                // e.g. `raise TypeError`. Transform into `raise TypeError()`
-                                // so the exception is properly instantiated before being raised.
-                                Expr::Builtin(b) => {
-                                    let call_expr = Expr::Call {
-                                        callable: Callable::Builtin(b),
-                                        args: Box::new(ArgExprs::Empty),
-                                    };
-                                    Some(ExprLoc::new(prepared.position, call_expr))
-                                }
-                                _ => Some(prepared),
-                            }
-                        }
+                        Some(expr) => Some(self.prepare_raised(expr)?),
                         None => None,
                     };
                     new_nodes.push(Node::Raise(expr));
                 }
+                Node::RaiseFrom { exc, cause } => {
+                    let exc = self.prepare_raised(exc)?;
+                    let cause = self.prepare_expression(cause)?;
+                    new_nodes.push(Node::RaiseFrom { exc, cause });
+                }
                 Node::Assert { test, msg } => {
                     let test = self.prepare_expression(test)?;
                     let msg = match msg {
@@ -362,7 +395,7 @@ impl<'i> Prepare<'i> {
                     let targets = targets
                         .into_iter()
                         .map(|target| self.prepare_unpack_target(target))
-                        .collect();
+                        .collect::<Result<_, _>>()?;
                     new_nodes.push(Node::UnpackAssign {
                         targets,
                         targets_position,
@@ -417,7 +450,7 @@ impl<'i> Prepare<'i> {
                     or_else,
                 } => {
                     // Prepare target with normal scoping (not comprehension isolation)
-                    let target = self.prepare_unpack_target(target);
+                    let target = self.prepare_unpack_target(target)?;
                     new_nodes.push(Node::For {
                         target,
                         iter: self.prepare_expression(iter)?,
@@ -449,6 +482,7 @@ impl<'i> Prepare<'i> {
                     signature,
                     body,
                     is_async,
+                    ..
                 }) => {
                     let func_node = self.prepare_function_def(name, &signature, body, is_async)?;
                     new_nodes.push(func_node);
@@ -600,6 +634,25 @@ impl<'i> Prepare<'i> {
         Ok(ExceptHandler { exc_type, name, body })
     }
 
+    /// Prepares the exception expression of a `raise` statement.
+    ///
+    /// Raising a builtin exception type without instantiation, e.g. `raise TypeError`, is
+    /// transformed into `raise TypeError()` so the exception is properly instantiated
+    /// before being raised.
+    fn prepare_raised(&mut self, expr: ExprLoc) -> Result<ExprLoc, ParseError> {
+        let prepared = self.prepare_expression(expr)?;
+        match prepared.expr {
+            Expr::Builtin(b) => {
+                let call_expr = Expr::Call {
+                    callable: Callable::Builtin(b),
+                    args: Box::new(ArgExprs::Empty),
+                };
+                Ok(ExprLoc::new(prepared.position, call_expr))
+            }
+            _ => Ok(prepared),
+        }
+    }
+
     /// Prepares an expression by resolving names, transforming calls, and applying optimizations.
     ///
     /// Key transformations performed:
@@ -617,7 +670,7 @@ impl<'i> Prepare<'i> {
         let expr = match expr {
             Expr::Literal(object) => Expr::Literal(object),
             Expr::Builtin(callable) => Expr::Builtin(callable),
-            Expr::Name(name) => self.resolve_name_or_builtin(name),
+            Expr::Name(name) => self.resolve_name_or_builtin(name)?,
             Expr::Op { left, op, right } => Expr::Op {
                 left: Box::new(self.prepare_expression(*left)?),
                 op,
@@ -641,7 +694,7 @@ impl<'i> Prepare<'i> {
                 // For Name callables, resolve the identifier in the namespace
                 // Don't error here if undefined - let runtime raise NameError with proper traceback
                 let callable = match callable {
-                    Callable::Name(ident) => match self.resolve_name_or_builtin(ident) {
+                    Callable::Name(ident) => match self.resolve_name_or_builtin(ident)? {
                         Expr::Builtin(b) => Callable::Builtin(b),
                         Expr::Name(resolved) => Callable::Name(resolved),
                         _ => unreachable!("resolve_name_or_builtin returns Name or Builtin"),
@@ -729,6 +782,13 @@ impl<'i> Prepare<'i> {
                     generators,
                 }
             }
+            Expr::Generator { elt, generators } => {
+                let (generators, elt, _) = self.prepare_comprehension(generators, Some(*elt), None)?;
+                Expr::Generator {
+                    elt: Box::new(elt.expect("generator expression must have elt")),
+                    generators,
+                }
+            }
             Expr::DictComp { key, value, generators } => {
                 let (generators, _, key_value) = self.prepare_comprehension(generators, None, Some((*key, *value)))?;
                 let (key, value) = key_value.expect("dict comp must have key/value");
@@ -807,7 +867,10 @@ impl<'i> Prepare<'i> {
     /// We check before calling `get_id` to avoid allocating unnecessary namespace slots.
     /// At module level, a slot allocated for an unassigned builtin would leak into
     /// `global_name_map` for nested functions, causing incorrect resolution.
-    fn resolve_name_or_builtin(&mut self, name: Identifier) -> Expr {
+    ///
+    /// # Errors
+    /// Returns a NameError if the name resolves to a builtin the compile options don't allow.
+    fn resolve_name_or_builtin(&mut self, name: Identifier) -> Result<Expr, ParseError> {
         let name_str = self.interner.get_str(name.name_id);
 
         // Check if the name is assigned in the current scope. If so, it shadows
@@ -834,11 +897,17 @@ impl<'i> Prepare<'i> {
                     || self.global_name_map.as_ref().is_some_and(|m| m.contains_key(name_str)));
 
             if !is_otherwise_bound && let Ok(builtin) = name_str.parse::<Builtins>() {
-                return Expr::Builtin(builtin);
+                if !self.options.allows_builtin(name_str) {
+                    return Err(ParseError::name_error(
+                        format!("name '{name_str}' is not defined"),
+                        name.position,
+                    ));
+                }
+                return Ok(Expr::Builtin(builtin));
             }
         }
 
-        Expr::Name(self.get_id(name).0)
+        Ok(Expr::Name(self.get_id(name).0))
     }
 
     /// Prepares a comprehension with scope isolation for loop variables.
@@ -973,9 +1042,10 @@ impl<'i> Prepare<'i> {
 
     /// Prepares an unpack target by resolving identifiers recursively.
     ///
-    /// Handles both single identifiers and nested tuples like `(a, b), c`.
-    fn prepare_unpack_target(&mut self, target: UnpackTarget) -> UnpackTarget {
-        match target {
+    /// Handles single identifiers, nested tuples like `(a, b), c`, and the expressions
+    /// of subscript and attribute targets.
+    fn prepare_unpack_target(&mut self, target: UnpackTarget) -> Result<UnpackTarget, ParseError> {
+        Ok(match target {
             UnpackTarget::Name(ident) => {
                 self.names_assigned_in_order
                     .insert(self.interner.get_str(ident.name_id).to_string());
@@ -990,13 +1060,28 @@ impl<'i> Prepare<'i> {
                 let resolved_targets: Vec<UnpackTarget> = targets
                     .into_iter()
                     .map(|t| self.prepare_unpack_target(t)) // Recursive call
-                    .collect();
+                    .collect::<Result<_, _>>()?;
                 UnpackTarget::Tuple {
                     targets: resolved_targets,
                     position,
                 }
             }
-        }
+            // Item and attribute targets don't bind names, they modify an existing object
+            UnpackTarget::Subscript {
+                object,
+                index,
+                position,
+            } => UnpackTarget::Subscript {
+                object: self.prepare_expression(object)?,
+                index: self.prepare_expression(index)?,
+                position,
+            },
+            UnpackTarget::Attr { object, attr, position } => UnpackTarget::Attr {
+                object: self.prepare_expression(object)?,
+                attr,
+                position,
+            },
+        })
     }
 
     /// Prepares an unpack target for comprehension by allocating fresh namespace slots.
@@ -1045,6 +1130,9 @@ impl<'i> Prepare<'i> {
                     position,
                 }
             }
+            UnpackTarget::Subscript { .. } | UnpackTarget::Attr { .. } => {
+                unreachable!("the parser rejects subscript and attribute targets in comprehensions")
+            }
         }
     }
 
@@ -1104,6 +1192,9 @@ impl<'i> Prepare<'i> {
                     position,
                 }
             }
+            UnpackTarget::Subscript { .. } | UnpackTarget::Attr { .. } => {
+                unreachable!("the parser rejects subscript and attribute targets in comprehensions")
+            }
         }
     }
 
@@ -1201,6 +1292,7 @@ impl<'i> Prepare<'i> {
             Some(enclosing_locals),
             scope_info.cell_var_names,
             self.interner,
+            self.options,
         );
 
         // Prepare the function body
@@ -1209,7 +1301,7 @@ impl<'i> Prepare<'i> {
         // Mark variables that the inner function captures as our cell_vars
         // These are the names that appear in inner_prepare.free_var_map
         // Add to cell_var_map if not already present (may have been pre-populated or added earlier)
-        for captured_name in inner_prepare.free_var_map.keys() {
+        for captured_name in inner_prepare.captured_names() {
             if !self.cell_var_map.contains_key(captured_name) && !self.free_var_map.contains_key(captured_name) {
                 // Only add to cell_var_map if not already a free_var (pass-through case)
                 // Allocate a namespace slot for the cell reference
@@ -1415,13 +1507,14 @@ impl<'i> Prepare<'i> {
             Some(enclosing_locals),
             scope_info.cell_var_names,
             self.interner,
+            self.options,
         );
 
         // Prepare the lambda body
         let prepared_body = inner_prepare.prepare_nodes(body_nodes)?;
 
         // Mark variables that the inner function captures as our cell_vars
-        for captured_name in inner_prepare.free_var_map.keys() {
+        for captured_name in inner_prepare.captured_names() {
             if !self.cell_var_map.contains_key(captured_name) && !self.free_var_map.contains_key(captured_name) {
                 let slot = match self.name_map.entry(captured_name.clone()) {
                     Entry::Occupied(e) => *e.get(),
@@ -2003,6 +2096,10 @@ fn collect_scope_info_from_node(
         Node::Raise(Some(expr)) => {
             collect_assigned_names_from_expr(expr, assigned_names, interner);
         }
+        Node::RaiseFrom { exc, cause } => {
+            collect_assigned_names_from_expr(exc, assigned_names, interner);
+            collect_assigned_names_from_expr(cause, assigned_names, interner);
+        }
         Node::Assert { test, msg } => {
             collect_assigned_names_from_expr(test, assigned_names, interner);
             if let Some(m) = msg {
@@ -2080,7 +2177,9 @@ fn collect_assigned_names_from_expr(expr: &ExprLoc, assigned_names: &mut AHashSe
             collect_assigned_names_from_expr(orelse, assigned_names, interner);
         }
         // Per PEP 572, walrus in comprehensions assigns to the ENCLOSING scope
-        Expr::ListComp { elt, generators } | Expr::SetComp { elt, generators } => {
+        Expr::ListComp { elt, generators }
+        | Expr::SetComp { elt, generators }
+        | Expr::Generator { elt, generators } => {
             collect_assigned_names_from_expr(elt, assigned_names, interner);
             for generator in generators {
                 collect_assigned_names_from_expr(&generator.iter, assigned_names, interner);
@@ -2271,9 +2370,15 @@ fn collect_cell_vars_from_node(
         Node::Expr(expr) | Node::Return(expr) => {
             collect_cell_vars_from_expr(expr, our_locals, cell_vars, interner);
         }
-        Node::Assign { object, .. } | Node::UnpackAssign { object, .. } => {
+        Node::Assign { object, .. } => {
             collect_cell_vars_from_expr(object, our_locals, cell_vars, interner);
         }
+        Node::UnpackAssign { targets, object, .. } => {
+            collect_cell_vars_from_expr(object, our_locals, cell_vars, interner);
+            for target in targets {
+                target.for_each_expr(&mut |expr| collect_cell_vars_from_expr(expr, our_locals, cell_vars, interner));
+            }
+        }
         Node::OpAssign { object, .. } => {
             collect_cell_vars_from_expr(object, our_locals, cell_vars, interner);
         }
@@ -2395,7 +2500,9 @@ fn collect_cell_vars_from_expr(
             collect_cell_vars_from_expr(body, our_locals, cell_vars, interner);
             collect_cell_vars_from_expr(orelse, our_locals, cell_vars, interner);
         }
-        Expr::ListComp { elt, generators } | Expr::SetComp { elt, generators } => {
+        Expr::ListComp { elt, generators }
+        | Expr::SetComp { elt, generators }
+        | Expr::Generator { elt, generators } => {
             collect_cell_vars_from_expr(elt, our_locals, cell_vars, interner);
             for generator in generators {
                 collect_cell_vars_from_expr(&generator.iter, our_locals, cell_vars, interner);
@@ -2493,6 +2600,10 @@ fn collect_referenced_names_from_node(node: &ParseNode, referenced: &mut AHashSe
         Node::Return(expr) => collect_referenced_names_from_expr(expr, referenced, interner),
         Node::Raise(Some(expr)) => collect_referenced_names_from_expr(expr, referenced, interner),
         Node::Raise(None) => {}
+        Node::RaiseFrom { exc, cause } => {
+            collect_referenced_names_from_expr(exc, referenced, interner);
+            collect_referenced_names_from_expr(cause, referenced, interner);
+        }
         Node::Assert { test, msg } => {
             collect_referenced_names_from_expr(test, referenced, interner);
             if let Some(m) = msg {
@@ -2502,8 +2613,11 @@ fn collect_referenced_names_from_node(node: &ParseNode, referenced: &mut AHashSe
         Node::Assign { object, .. } => {
             collect_referenced_names_from_expr(object, referenced, interner);
         }
-        Node::UnpackAssign { object, .. } => {
+        Node::UnpackAssign { targets, object, .. } => {
             collect_referenced_names_from_expr(object, referenced, interner);
+            for target in targets {
+                target.for_each_expr(&mut |expr| collect_referenced_names_from_expr(expr, referenced, interner));
+            }
         }
         Node::OpAssign { target, object, .. } => {
             // OpAssign reads the target before writing
@@ -2522,9 +2636,13 @@ fn collect_referenced_names_from_node(node: &ParseNode, referenced: &mut AHashSe
             collect_referenced_names_from_expr(value, referenced, interner);
         }
         Node::For {
-            iter, body, or_else, ..
+            target,
+            iter,
+            body,
+            or_else,
         } => {
             collect_referenced_names_from_expr(iter, referenced, interner);
+            target.for_each_expr(&mut |expr| collect_referenced_names_from_expr(expr, referenced, interner));
             for n in body {
                 collect_referenced_names_from_node(n, referenced, interner);
             }
@@ -2657,7 +2775,9 @@ fn collect_referenced_names_from_expr(
             collect_referenced_names_from_expr(body, referenced, interner);
             collect_referenced_names_from_expr(orelse, referenced, interner);
         }
-        Expr::ListComp { elt, generators } | Expr::SetComp { elt, generators } => {
+        Expr::ListComp { elt, generators }
+        | Expr::SetComp { elt, generators }
+        | Expr::Generator { elt, generators } => {
             collect_referenced_names_from_comprehension(generators, Some(elt), None, referenced, interner);
         }
         Expr::DictComp { key, value, generators } => {
@@ -2837,5 +2957,9 @@ fn collect_names_from_unpack_target(target: &UnpackTarget, names: &mut AHashSet<
                 collect_names_from_unpack_target(t, names, interner);
             }
         }
+        // Item and attribute targets bind no names, but may contain walrus operators
+        UnpackTarget::Subscript { .. } | UnpackTarget::Attr { .. } => {
+            target.for_each_expr(&mut |expr| collect_assigned_names_from_expr(expr, names, interner));
+        }
     }
 }
@@ -0,0 +1,171 @@
+//! Opt-in per-line profiling.
+//!
+//! `MontyRun::run_profiled` runs a script with a [`Profiler`] attached to the VM, which
+//! counts every opcode executed and times every source line, and returns the resulting
+//! [`ProfileReport`] alongside the result.
+//!
+//! Time is measured between line changes, so it is self time: while a function called
+//! from a line runs, the time goes to the function's lines, not the caller's. Garbage
+//! collection pauses are charged to the line that triggered them.
+
+use std::{fmt, time::Duration};
+
+use ahash::AHashMap;
+
+use crate::{
+    clock::Instant,
+    intern::{FunctionId, Interns, StaticStrings},
+};
+
+/// Counts and time for one source line.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LineProfile {
+    /// Name of the function the line belongs to, or `"<module>"`.
+    pub function: String,
+    /// Line number (1-based).
+    pub line: u16,
+    /// Number of opcodes executed on this line.
+    pub opcodes: u64,
+    /// Wall-clock time spent executing this line, excluding functions it called.
+    pub time: Duration,
+}
+
+/// Per-line profile of a run, returned by `MontyRun::run_profiled`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProfileReport {
+    lines: Vec<LineProfile>,
+}
+
+impl ProfileReport {
+    /// Returns every line that executed, slowest first.
+    #[must_use]
+    pub fn lines(&self) -> &[LineProfile] {
+        &self.lines
+    }
+
+    /// Returns the profile of `line` in `function`, if it executed.
+    #[must_use]
+    pub fn line(&self, function: &str, line: u16) -> Option<&LineProfile> {
+        self.lines
+            .iter()
+            .find(|profile| profile.function == function && profile.line == line)
+    }
+
+    /// Returns the total number of opcodes executed.
+    #[must_use]
+    pub fn total_opcodes(&self) -> u64 {
+        self.lines.iter().map(|profile| profile.opcodes).sum()
+    }
+
+    /// Returns the total time spent executing code.
+    #[must_use]
+    pub fn total_time(&self) -> Duration {
+        self.lines.iter().map(|profile| profile.time).sum()
+    }
+}
+
+/// Renders a table of lines, slowest first.
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>12} {:>6} {:>12} {:>6}  function",
+            "time (us)", "%", "opcodes", "line"
+        )?;
+        let total = self.total_time().as_secs_f64();
+        for profile in &self.lines {
+            let percent = if total > 0.0 {
+                profile.time.as_secs_f64() / total * 100.0
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "{:>12} {percent:>6.1} {:>12} {:>6}  {}",
+                profile.time.as_micros(),
+                profile.opcodes,
+                profile.line,
+                profile.function
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Line a profiled opcode belongs to: the executing function (`None` for module code) and
+/// the line number.
+type LineKey = (Option<FunctionId>, u16);
+
+#[derive(Debug, Default)]
+struct LineStats {
+    opcodes: u64,
+    time: Duration,
+}
+
+/// Collects opcode counts and line timings while the VM runs.
+///
+/// The VM reports line changes with `enter_line` and every opcode with `record_opcode`,
+/// which only bumps a counter; both are charged to the line's stats when it is left.
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    lines: AHashMap<LineKey, LineStats>,
+    /// The line currently executing, when it started, and the opcodes it has run since.
+    current: Option<(LineKey, Instant, u64)>,
+}
+
+impl Profiler {
+    /// Records that execution moved to `line` of `function_id`.
+    pub fn enter_line(&mut self, function_id: Option<FunctionId>, line: u16) {
+        let key = (function_id, line);
+        if !matches!(self.current, Some((current, _, _)) if current == key) {
+            let now = Instant::now();
+            self.flush(now);
+            self.current = Some((key, now, 0));
+        }
+    }
+
+    /// Records one opcode executed on the current line.
+    #[inline]
+    pub fn record_opcode(&mut self) {
+        if let Some((_, _, opcodes)) = &mut self.current {
+            *opcodes += 1;
+        }
+    }
+
+    /// Charges the time and opcodes since the current line started to it.
+    fn flush(&mut self, now: Instant) {
+        if let Some((key, since, opcodes)) = self.current.take() {
+            let stats = self.lines.entry(key).or_default();
+            stats.time += now - since;
+            stats.opcodes += opcodes;
+        }
+    }
+
+    /// Stops timing and builds the report, resolving function names through `interns`.
+    pub fn finish(mut self, interns: &Interns) -> ProfileReport {
+        self.flush(Instant::now());
+        let mut lines: Vec<LineProfile> = self
+            .lines
+            .into_iter()
+            .map(|((function_id, line), stats)| {
+                let name_id = match function_id {
+                    Some(func_id) => interns.get_function(func_id).name.name_id,
+                    None => StaticStrings::Module.into(),
+                };
+                LineProfile {
+                    function: interns.get_str(name_id).to_owned(),
+                    line,
+                    opcodes: stats.opcodes,
+                    time: stats.time,
+                }
+            })
+            .collect();
+        lines.sort_by(|a, b| {
+            b.time
+                .cmp(&a.time)
+                .then(b.opcodes.cmp(&a.opcodes))
+                .then_with(|| (&a.function, a.line).cmp(&(&b.function, b.line)))
+        });
+        ProfileReport { lines }
+    }
+}
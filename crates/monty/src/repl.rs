@@ -2,7 +2,10 @@
 //!
 //! This module implements incremental snippet execution where each new snippet
 //! is compiled and executed against persistent heap/namespace state without
-//! replaying previously executed snippets.
+//! replaying previously executed snippets. `MontyRepl` is the session type for hosts
+//! that run code in cells, such as notebooks.
+
+use std::{collections::HashMap, time::Duration};
 
 use ahash::AHashMap;
 use ruff_python_ast::token::TokenKind;
@@ -11,8 +14,9 @@ use ruff_python_parser::{InterpolatedStringErrorType, LexicalErrorType, ParseErr
 use crate::{
     ExcType, MontyException,
     asyncio::CallId,
-    bytecode::{Code, Compiler, FrameExit, VM, VMSnapshot},
+    bytecode::{Code, Compiler, FrameExit, VM, VMSnapshot, validate_program},
     exception_private::{RunError, RunResult},
+    fold::fold_constants,
     heap::{DropWithHeap, Heap},
     intern::{ExtFunctionId, InternerBuilder, Interns},
     io::PrintWriter,
@@ -22,7 +26,8 @@ use crate::{
     parse::{parse, parse_with_interner},
     prepare::{prepare, prepare_with_existing_names},
     resource::ResourceTracker,
-    run::{ExternalResult, MontyFuture},
+    run::{CompileOptions, ExternalResult, MontyFuture, collect_globals, global_namespace_len, validate_state},
+    snapshot_format::{self, SnapshotError, SnapshotKind},
     value::Value,
 };
 
@@ -39,6 +44,7 @@ struct ReplExecutor {
     ///
     /// Stable slot assignment is required across snippets so previously created
     /// objects continue to resolve names correctly.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
     name_map: AHashMap<String, NamespaceId>,
     /// Compiled bytecode for the snippet/module.
     module_code: Code,
@@ -60,16 +66,20 @@ impl ReplExecutor {
         script_name: &str,
         input_names: Vec<String>,
         external_functions: Vec<String>,
+        options: &CompileOptions,
     ) -> Result<Self, MontyException> {
         let parse_result = parse(&code, script_name).map_err(|e| e.into_python_exc(script_name, &code))?;
-        let prepared = prepare(parse_result, input_names, &external_functions)
+        let mut prepared = prepare(parse_result, input_names, &external_functions, options)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
+        if options.optimize {
+            prepared.nodes = fold_constants(std::mem::take(&mut prepared.nodes), &mut prepared.interner);
+        }
 
         let external_function_ids = (0..external_functions.len()).map(ExtFunctionId::new).collect();
 
         let mut interns = Interns::new(prepared.interner, Vec::new(), external_functions);
         let namespace_size_u16 = u16::try_from(prepared.namespace_size).expect("module namespace size exceeds u16");
-        let compile_result = Compiler::compile_module(&prepared.nodes, &interns, namespace_size_u16)
+        let compile_result = Compiler::compile_module(&prepared.nodes, &interns, namespace_size_u16, options.optimize)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
         interns.set_functions(compile_result.functions);
 
@@ -96,21 +106,30 @@ impl ReplExecutor {
         external_functions: Vec<String>,
         existing_name_map: AHashMap<String, NamespaceId>,
         existing_interns: &Interns,
+        options: &CompileOptions,
     ) -> Result<Self, MontyException> {
         let seeded_interner = InternerBuilder::from_interns(existing_interns, &code);
         let parse_result = parse_with_interner(&code, script_name, seeded_interner)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
-        let prepared = prepare_with_existing_names(parse_result, existing_name_map)
+        let mut prepared = prepare_with_existing_names(parse_result, existing_name_map, options)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
+        if options.optimize {
+            prepared.nodes = fold_constants(std::mem::take(&mut prepared.nodes), &mut prepared.interner);
+        }
 
         let external_function_ids = (0..external_functions.len()).map(ExtFunctionId::new).collect();
 
         let existing_functions = existing_interns.functions_clone();
         let mut interns = Interns::new(prepared.interner, Vec::new(), external_functions);
         let namespace_size_u16 = u16::try_from(prepared.namespace_size).expect("module namespace size exceeds u16");
-        let compile_result =
-            Compiler::compile_module_with_functions(&prepared.nodes, &interns, namespace_size_u16, existing_functions)
-                .map_err(|e| e.into_python_exc(script_name, &code))?;
+        let compile_result = Compiler::compile_module_with_functions(
+            &prepared.nodes,
+            &interns,
+            namespace_size_u16,
+            existing_functions,
+            options.optimize,
+        )
+        .map_err(|e| e.into_python_exc(script_name, &code))?;
         interns.set_functions(compile_result.functions);
 
         Ok(Self {
@@ -157,6 +176,27 @@ impl ReplExecutor {
     }
 }
 
+/// Rejects compile options `MontyRepl` can't apply, and names in the builtin lists that
+/// aren't builtins.
+fn check_repl_options(options: &CompileOptions) -> Result<(), MontyException> {
+    options.check_builtin_names()?;
+    let unsupported = if options.external_stubs.is_some() {
+        "checking external calls"
+    } else if !options.batched_external_functions.is_empty() {
+        "batching external calls"
+    } else if !options.host_iterables.is_empty() {
+        "host iterables"
+    } else if options.shared_interns.is_some() {
+        "shared interns"
+    } else {
+        return Ok(());
+    };
+    Err(MontyException::new(
+        ExcType::ValueError,
+        Some(format!("MontyRepl doesn't support {unsupported}")),
+    ))
+}
+
 /// Converts module/frame exit results into plain `MontyObject` outputs.
 ///
 /// REPL initialization executes like normal module execution, which must reject
@@ -196,6 +236,15 @@ fn frame_exit_to_object(
         FrameExit::ResolveFutures(_) => {
             Err(ExcType::not_implemented("async futures not supported by standard execution.").into())
         }
+        FrameExit::Emit { value, .. } => {
+            value.drop_with_heap(heap);
+            Err(ExcType::not_implemented("emit() not implemented with standard execution").into())
+        }
+        FrameExit::Sleep { .. } => {
+            Err(ExcType::not_implemented("time.sleep() not implemented with standard execution").into())
+        }
+        FrameExit::Paused => unreachable!("REPL execution never sets a fuel limit"),
+        FrameExit::Breakpoint => unreachable!("REPL execution never sets breakpoints"),
     }
 }
 
@@ -255,6 +304,12 @@ pub fn detect_repl_continuation_mode(source: &str) -> ReplContinuationMode {
 /// `MontyRepl` preserves heap and global namespace state between snippets.
 /// Each `feed()` compiles and executes only the new snippet against the current
 /// state, avoiding the cost and semantic risks of replaying prior code.
+///
+/// This is the persistent session to build notebook-style hosts on, so there is no
+/// separate `Session` type: every cell is a `feed()` that sees the definitions of
+/// earlier cells, and `globals()` shows what they defined. `MontyRepl` doesn't split a
+/// cell into statements or echo intermediate values; `feed()` returns only the value of
+/// a trailing expression.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::de::DeserializeOwned"))]
 pub struct MontyRepl<T: ResourceTracker> {
@@ -268,7 +323,10 @@ pub struct MontyRepl<T: ResourceTracker> {
     next_input_id: u64,
     /// External function names declared for this session.
     external_function_names: Vec<String>,
+    /// Options every snippet is compiled with.
+    options: CompileOptions,
     /// Stable mapping of global variable names to namespace slot IDs.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
     global_name_map: AHashMap<String, NamespaceId>,
     /// Persistent intern table across snippets so intern/function IDs remain valid.
     interns: Interns,
@@ -300,7 +358,47 @@ impl<T: ResourceTracker> MontyRepl<T> {
         resource_tracker: T,
         print: &mut PrintWriter<'_>,
     ) -> Result<(Self, MontyObject), MontyException> {
-        let executor = ReplExecutor::new(code, script_name, input_names, external_function_names.clone())?;
+        Self::new_with_options(
+            code,
+            script_name,
+            input_names,
+            external_function_names,
+            inputs,
+            resource_tracker,
+            print,
+            CompileOptions::default(),
+        )
+    }
+
+    /// Creates a new REPL like [`MontyRepl::new`], compiling the initial code and every
+    /// snippet fed or started later with `options`.
+    ///
+    /// Only `optimize` and the builtin allow and deny lists apply to a REPL, and `options`
+    /// is kept with the session when it is dumped.
+    ///
+    /// # Errors
+    /// Returns `MontyException` for parse/compile/runtime failures, or `ValueError` if
+    /// `options` allows or denies a name that isn't a builtin or sets an option the REPL
+    /// doesn't support.
+    #[expect(clippy::too_many_arguments)]
+    pub fn new_with_options(
+        code: String,
+        script_name: &str,
+        input_names: Vec<String>,
+        external_function_names: Vec<String>,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        print: &mut PrintWriter<'_>,
+        options: CompileOptions,
+    ) -> Result<(Self, MontyObject), MontyException> {
+        check_repl_options(&options)?;
+        let executor = ReplExecutor::new(
+            code,
+            script_name,
+            input_names,
+            external_function_names.clone(),
+            &options,
+        )?;
 
         let mut heap = Heap::new(executor.namespace_size, resource_tracker);
         let mut namespaces = executor.prepare_namespaces(inputs, &mut heap)?;
@@ -316,6 +414,7 @@ impl<T: ResourceTracker> MontyRepl<T> {
             script_name: script_name.to_owned(),
             next_input_id: 0,
             external_function_names,
+            options,
             global_name_map: executor.name_map,
             interns: executor.interns,
             heap,
@@ -353,6 +452,7 @@ impl<T: ResourceTracker> MontyRepl<T> {
             this.external_function_names.clone(),
             this.global_name_map.clone(),
             &this.interns,
+            &this.options,
         )?;
 
         this.ensure_global_namespace_size(executor.namespace_size);
@@ -368,6 +468,7 @@ impl<T: ResourceTracker> MontyRepl<T> {
     }
 
     /// Starts snippet execution with `PrintWriter::Stdout` and no additional host output wiring.
+    #[cfg(feature = "stdout")]
     pub fn start_no_print(self, code: &str) -> Result<ReplProgress<T>, MontyException> {
         self.start(code, &mut PrintWriter::Stdout)
     }
@@ -394,6 +495,7 @@ impl<T: ResourceTracker> MontyRepl<T> {
             self.external_function_names.clone(),
             self.global_name_map.clone(),
             &self.interns,
+            &self.options,
         )?;
 
         let ReplExecutor {
@@ -422,10 +524,75 @@ impl<T: ResourceTracker> MontyRepl<T> {
     }
 
     /// Executes a snippet with no additional host output wiring.
+    #[cfg(feature = "stdout")]
     pub fn feed_no_print(&mut self, code: &str) -> Result<MontyObject, MontyException> {
         self.feed(code, &mut PrintWriter::Stdout)
     }
 
+    /// Returns every global bound so far, keyed by name.
+    ///
+    /// Useful for showing a notebook's variables between cells. Unassigned names and
+    /// external functions are left out, matching `MontyRun::run_capture_globals`.
+    #[must_use]
+    pub fn globals(&self) -> HashMap<String, MontyObject> {
+        collect_globals(&self.global_name_map, &self.namespaces, &self.heap, &self.interns)
+    }
+
+    /// Returns the value of global `name`, or `None` if it isn't bound.
+    #[must_use]
+    pub fn global(&self, name: &str) -> Option<MontyObject> {
+        let slot = *self.global_name_map.get(name)?;
+        match self.namespaces.get(GLOBAL_NS_IDX).get_opt(slot)? {
+            Value::Undefined | Value::ExtFunction(_) => None,
+            value => Some(MontyObject::from_value(value, &self.heap, &self.interns)),
+        }
+    }
+
+    /// Prevents later snippets from rebinding the globals defined so far.
+    ///
+    /// Rebinding or deleting a frozen global, at module level or via a `global`
+    /// statement, raises `TypeError`. Names first bound by later snippets stay writable.
+    ///
+    /// This only protects the bindings of this REPL session's globals, it does not isolate
+    /// snippets from each other: the objects frozen globals refer to are not frozen, so a
+    /// frozen global bound to a list can still be appended to, and it is visible to every
+    /// later snippet. There is no equivalent for `MontyRun`.
+    pub fn freeze_globals(&mut self) {
+        let global_len = self.namespaces.get(GLOBAL_NS_IDX).len();
+        let mut names: Vec<String> = (0..global_len).map(|slot| format!("<global {slot}>")).collect();
+        for (name, slot) in &self.global_name_map {
+            if let Some(entry) = names.get_mut(slot.index()) {
+                entry.clone_from(name);
+            }
+        }
+        self.namespaces.freeze_globals(names);
+    }
+
+    /// Returns how many string results this session served from the interned empty and
+    /// single-ASCII-character cache instead of allocating them on the heap.
+    ///
+    /// Only available with the `stats` feature, for checking that hot loops hit the cache.
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn string_cache_hits(&self) -> u64 {
+        self.heap.string_cache_hits()
+    }
+
+    /// Checks a deserialized session's functions, heap and globals; see `ReplProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_program(None, &self.interns, global_namespace_len(&self.namespaces)?)
+            .map_err(SnapshotError::Corrupt)?;
+        let checker = self
+            .heap
+            .validate(self.interns.bounds())
+            .map_err(SnapshotError::Corrupt)?;
+        self.namespaces.remap_heap_ids(&checker);
+        match checker.problem() {
+            Some(problem) => Err(SnapshotError::Corrupt(format!("namespace refers to {problem}"))),
+            None => Ok(()),
+        }
+    }
+
     /// Grows the global namespace to at least `namespace_size`.
     ///
     /// Newly introduced slots are initialized to `Undefined` to keep slot alignment
@@ -453,22 +620,32 @@ impl<T: ResourceTracker + serde::Serialize> MontyRepl<T> {
     /// Serializes the REPL session state to bytes.
     ///
     /// This includes heap + namespaces + global slot mapping, allowing snapshot/restore
-    /// of interactive state between process runs.
+    /// of interactive state between process runs. The output starts with the same versioned
+    /// header as `ReplProgress::dump()`.
     ///
     /// # Errors
     /// Returns an error if serialization fails.
     pub fn dump(&self) -> Result<Vec<u8>, postcard::Error> {
-        postcard::to_allocvec(self)
+        snapshot_format::dump(SnapshotKind::Repl, self)
     }
 }
 
 impl<T: ResourceTracker + serde::de::DeserializeOwned> MontyRepl<T> {
-    /// Restores a REPL session from bytes produced by `MontyRepl::dump`.
+    /// Restores a REPL session from bytes produced by `MontyRepl::dump`, checking its heap
+    /// and globals like `ReplProgress::load()` does.
     ///
     /// # Errors
-    /// Returns an error if deserialization fails.
-    pub fn load(bytes: &[u8]) -> Result<Self, postcard::Error> {
-        postcard::from_bytes(bytes)
+    /// Returns `SnapshotError::VersionMismatch` if the session was dumped by another Monty
+    /// version, or another `SnapshotError` if it is not valid `MontyRepl::dump()` output.
+    pub fn load(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut repl: Self = snapshot_format::load(SnapshotKind::Repl, bytes)?;
+        if let Err(err) = repl.validate() {
+            // see `ReplProgress::load`
+            #[cfg(feature = "ref-count-panic")]
+            std::mem::forget(repl);
+            return Err(err);
+        }
+        Ok(repl)
     }
 }
 
@@ -516,6 +693,26 @@ pub enum ReplProgress<T: ResourceTracker> {
     },
     /// All async tasks are blocked waiting for external futures to resolve.
     ResolveFutures(ReplFutureSnapshot<T>),
+    /// The snippet streamed a value to the host by calling `emit(value)`.
+    ///
+    /// Call `state.run(MontyObject::None)` to continue; the value passed in becomes the
+    /// return value of `emit()`.
+    Emit {
+        /// The emitted value.
+        value: MontyObject,
+        /// Repl execution state that can be resumed.
+        state: ReplSnapshot<T>,
+    },
+    /// The snippet called `time.sleep(duration)`.
+    ///
+    /// Call `state.run(MontyObject::None)` once the host's scheduler decides the
+    /// snippet may continue.
+    Sleep {
+        /// The requested sleep duration.
+        duration: Duration,
+        /// Repl execution state that can be resumed.
+        state: ReplSnapshot<T>,
+    },
     /// Snippet execution completed with the updated REPL and result value.
     Complete {
         /// Updated REPL session state to continue feeding snippets.
@@ -563,6 +760,24 @@ impl<T: ResourceTracker> ReplProgress<T> {
         }
     }
 
+    /// Consumes the progress and returns the emitted value and state.
+    #[must_use]
+    pub fn into_emit(self) -> Option<(MontyObject, ReplSnapshot<T>)> {
+        match self {
+            Self::Emit { value, state } => Some((value, state)),
+            _ => None,
+        }
+    }
+
+    /// Consumes the progress and returns the requested sleep duration and state.
+    #[must_use]
+    pub fn into_sleep(self) -> Option<(Duration, ReplSnapshot<T>)> {
+        match self {
+            Self::Sleep { duration, state } => Some((duration, state)),
+            _ => None,
+        }
+    }
+
     /// Consumes the progress and returns the completed REPL and value.
     #[must_use]
     pub fn into_complete(self) -> Option<(MontyRepl<T>, MontyObject)> {
@@ -576,10 +791,12 @@ impl<T: ResourceTracker> ReplProgress<T> {
 impl<T: ResourceTracker + serde::Serialize> ReplProgress<T> {
     /// Serializes the REPL execution progress to a binary format.
     ///
+    /// Like `RunProgress::dump()`, the output starts with a versioned, checksummed header.
+    ///
     /// # Errors
     /// Returns an error if serialization fails.
     pub fn dump(&self) -> Result<Vec<u8>, postcard::Error> {
-        postcard::to_allocvec(self)
+        snapshot_format::dump(SnapshotKind::ReplProgress, self)
     }
 }
 
@@ -587,9 +804,28 @@ impl<T: ResourceTracker + serde::de::DeserializeOwned> ReplProgress<T> {
     /// Deserializes REPL execution progress from a binary format.
     ///
     /// # Errors
-    /// Returns an error if deserialization fails.
-    pub fn load(bytes: &[u8]) -> Result<Self, postcard::Error> {
-        postcard::from_bytes(bytes)
+    /// Returns a `SnapshotError` if the bytes were dumped by another Monty version, are
+    /// corrupted, or are not `ReplProgress::dump()` output. Like `RunProgress::load()`,
+    /// the loaded state is checked and rejected as `SnapshotError::Corrupt` if it is
+    /// inconsistent.
+    pub fn load(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut progress: Self = snapshot_format::load(SnapshotKind::ReplProgress, bytes)?;
+        let result = match &mut progress {
+            Self::FunctionCall { state, .. }
+            | Self::OsCall { state, .. }
+            | Self::Emit { state, .. }
+            | Self::Sleep { state, .. } => state.validate(),
+            Self::ResolveFutures(state) => state.validate(),
+            Self::Complete { repl, .. } => repl.validate(),
+        };
+        if let Err(err) = result {
+            // values in a corrupt state may refer to heap entries that don't exist, so under
+            // `ref-count-panic` they can't be released; otherwise dropping just frees the memory
+            #[cfg(feature = "ref-count-panic")]
+            std::mem::forget(progress);
+            return Err(err);
+        }
+        Ok(progress)
     }
 }
 
@@ -612,6 +848,17 @@ pub struct ReplSnapshot<T: ResourceTracker> {
 }
 
 impl<T: ResourceTracker> ReplSnapshot<T> {
+    /// Checks the state after deserialization; see `ReplProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.executor.module_code,
+            &self.executor.interns,
+            &mut self.vm_state,
+            &mut self.repl.heap,
+            &mut self.repl.namespaces,
+        )
+    }
+
     /// Continues snippet execution with an external result.
     ///
     /// # Arguments
@@ -681,6 +928,17 @@ pub struct ReplFutureSnapshot<T: ResourceTracker> {
 }
 
 impl<T: ResourceTracker> ReplFutureSnapshot<T> {
+    /// Checks the state after deserialization; see `ReplProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.executor.module_code,
+            &self.executor.interns,
+            &mut self.vm_state,
+            &mut self.repl.heap,
+            &mut self.repl.namespaces,
+        )
+    }
+
     /// Returns unresolved call IDs for this suspended state.
     #[must_use]
     pub fn pending_call_ids(&self) -> &[u32] {
@@ -866,6 +1124,19 @@ fn handle_repl_vm_result<T: ResourceTracker>(
                 pending_call_ids,
             }))
         }
+        Ok(FrameExit::Emit { value, call_id }) => {
+            let value = MontyObject::new(value, &mut repl.heap, &executor.interns);
+            Ok(ReplProgress::Emit {
+                value,
+                state: new_repl_snapshot!(call_id),
+            })
+        }
+        Ok(FrameExit::Sleep { duration, call_id }) => Ok(ReplProgress::Sleep {
+            duration,
+            state: new_repl_snapshot!(call_id),
+        }),
+        Ok(FrameExit::Paused) => unreachable!("REPL execution never sets a fuel limit"),
+        Ok(FrameExit::Breakpoint) => unreachable!("REPL execution never sets breakpoints"),
         Err(err) => {
             #[cfg(feature = "ref-count-panic")]
             repl.namespaces.drop_global_with_heap(&mut repl.heap);
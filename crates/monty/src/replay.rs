@@ -0,0 +1,428 @@
+//! Recording and replaying the host's answers to external calls.
+//!
+//! Everything a script can't compute by itself comes from the host: the results of
+//! external functions and OS calls, the values `emit()` returns, and the results of
+//! resolved futures. A [`CallLog`] records those answers as the host gives them, and a
+//! [`Replayer`] feeds them back to a fresh run of the same code and inputs. The replayed
+//! run takes exactly the same path, so a run that paused, crashed the host, or misbehaved
+//! can be reproduced under a debugger without the services it originally talked to.
+//!
+//! The log is plain serde data. A host that needs to reproduce crashes should persist it
+//! after each `record` call rather than at the end of the run.
+
+use std::fmt;
+
+use crate::{
+    exception_private::ExcType,
+    exception_public::MontyException,
+    io::PrintWriter,
+    object::MontyObject,
+    resource::ResourceTracker,
+    run::{ExternalResult, RunProgress},
+};
+
+/// Which kind of host call a [`RecordedCall`] answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RecordedCallKind {
+    /// `RunProgress::FunctionCall`: an external function, or a dataclass method when
+    /// `method_call` is true.
+    Function {
+        /// Whether the first positional argument is the dataclass instance.
+        method_call: bool,
+    },
+    /// `RunProgress::OsCall`; the recorded name is the `OsFunction`'s display name.
+    Os,
+    /// `RunProgress::Emit`; the emitted value is the only argument.
+    Emit,
+    /// `RunProgress::Sleep`; the duration in seconds is the only argument.
+    Sleep,
+}
+
+/// How the host answered a call.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum RecordedResult {
+    /// The call returned this value.
+    Return(MontyObject),
+    /// The call raised. Only the type and message are kept; the host's traceback isn't
+    /// part of the run.
+    Error {
+        /// The exception type raised.
+        exc_type: ExcType,
+        /// The exception message, if any.
+        message: Option<String>,
+    },
+    /// The host answered with a future, resolved by a later `CallLogEntry::Resolve`.
+    Future,
+}
+
+impl From<&ExternalResult> for RecordedResult {
+    fn from(result: &ExternalResult) -> Self {
+        match result {
+            ExternalResult::Return(value) => Self::Return(value.clone()),
+            ExternalResult::Error(exc) => Self::Error {
+                exc_type: exc.exc_type(),
+                message: exc.message().map(str::to_owned),
+            },
+            ExternalResult::Future => Self::Future,
+        }
+    }
+}
+
+impl From<RecordedResult> for ExternalResult {
+    fn from(result: RecordedResult) -> Self {
+        match result {
+            RecordedResult::Return(value) => Self::Return(value),
+            RecordedResult::Error { exc_type, message } => Self::Error(MontyException::new(exc_type, message)),
+            RecordedResult::Future => Self::Future,
+        }
+    }
+}
+
+/// One call the run made to the host, and the host's answer.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedCall {
+    /// Which kind of call this was.
+    pub kind: RecordedCallKind,
+    /// Function name, OS function name, or `"emit"`.
+    pub name: String,
+    /// The positional arguments passed.
+    pub args: Vec<MontyObject>,
+    /// The keyword arguments passed (key, value pairs).
+    pub kwargs: Vec<(MontyObject, MontyObject)>,
+    /// How the host answered.
+    pub result: RecordedResult,
+}
+
+impl RecordedCall {
+    /// Returns whether this records the same call, ignoring the result.
+    fn matches(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.name == other.name && self.args == other.args && self.kwargs == other.kwargs
+    }
+}
+
+impl fmt::Display for RecordedCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        let mut first = true;
+        for arg in &self.args {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            f.write_str(&arg.py_repr())?;
+        }
+        for (key, value) in &self.kwargs {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "{key}={}", value.py_repr())?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// An entry in a [`CallLog`], in the order the host answered.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CallLogEntry {
+    /// The host answered a call with `Snapshot::run()`.
+    Call(RecordedCall),
+    /// The host resolved futures with `FutureSnapshot::resume()`: (call_id, result) pairs.
+    Resolve(Vec<(u32, RecordedResult)>),
+}
+
+impl fmt::Display for CallLogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Call(call) => write!(f, "call {call}"),
+            Self::Resolve(results) => {
+                f.write_str("resolve futures")?;
+                for (i, (call_id, _)) in results.iter().enumerate() {
+                    f.write_str(if i == 0 { " " } else { ", " })?;
+                    write!(f, "{call_id}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Log of the host's answers during a run, for replaying it with a [`Replayer`].
+///
+/// Record each answer with `record()` (or `record_resolved()`) just before handing it to
+/// the interpreter, so the log always holds every answer the run has already seen.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CallLog {
+    entries: Vec<CallLogEntry>,
+}
+
+impl CallLog {
+    /// Creates an empty log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded entries, in the order the host answered.
+    #[must_use]
+    pub fn entries(&self) -> &[CallLogEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of recorded entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether nothing has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records `result` as the host's answer to `progress`.
+    ///
+    /// Call this for `FunctionCall`, `OsCall` and `Emit` progress with the result about to
+    /// be passed to `state.run()` (`ExternalResult::Future` for `state.run_pending()`). Other progress
+    /// isn't answered with a result and is ignored; record future resolutions with
+    /// `record_resolved()`.
+    pub fn record<T: ResourceTracker>(&mut self, progress: &RunProgress<T>, result: &ExternalResult) {
+        let (kind, name, args, kwargs) = match progress {
+            RunProgress::FunctionCall {
+                function_name,
+                args,
+                kwargs,
+                method_call,
+                ..
+            } => (
+                RecordedCallKind::Function {
+                    method_call: *method_call,
+                },
+                function_name.clone(),
+                args.clone(),
+                kwargs.clone(),
+            ),
+            RunProgress::OsCall {
+                function, args, kwargs, ..
+            } => (RecordedCallKind::Os, function.to_string(), args.clone(), kwargs.clone()),
+            RunProgress::Emit { value, .. } => (RecordedCallKind::Emit, "emit".to_owned(), vec![value.clone()], vec![]),
+            RunProgress::Sleep { duration, .. } => (
+                RecordedCallKind::Sleep,
+                "time.sleep".to_owned(),
+                vec![MontyObject::Float(duration.as_secs_f64())],
+                vec![],
+            ),
+            RunProgress::ResolveFutures(_)
+            | RunProgress::Paused(_)
+            | RunProgress::Breakpoint(_)
+            | RunProgress::Complete(_) => return,
+        };
+        self.entries.push(CallLogEntry::Call(RecordedCall {
+            kind,
+            name,
+            args,
+            kwargs,
+            result: result.into(),
+        }));
+    }
+
+    /// Records the results about to be passed to `FutureSnapshot::resume()`.
+    pub fn record_resolved(&mut self, results: &[(u32, ExternalResult)]) {
+        self.entries.push(CallLogEntry::Resolve(
+            results
+                .iter()
+                .map(|(call_id, result)| (*call_id, result.into()))
+                .collect(),
+        ));
+    }
+}
+
+/// Error returned when replaying a [`CallLog`] fails.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The run asked the host for something other than the next log entry, so the code,
+    /// inputs or interpreter differ from the recorded run.
+    Diverged {
+        /// Index of the log entry that didn't match.
+        position: usize,
+        /// The log entry.
+        expected: String,
+        /// What the run asked for instead.
+        found: String,
+    },
+    /// The replayed run raised an exception.
+    ///
+    /// If the recorded run raised the same exception, this is the reproduction.
+    Exception(MontyException),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Diverged {
+                position,
+                expected,
+                found,
+            } => write!(
+                f,
+                "replay diverged at entry {position}: expected {expected}, found {found}"
+            ),
+            Self::Exception(exc) => write!(f, "{exc}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Exception(exc) => Some(exc),
+            Self::Diverged { .. } => None,
+        }
+    }
+}
+
+impl From<MontyException> for ReplayError {
+    fn from(exc: MontyException) -> Self {
+        Self::Exception(exc)
+    }
+}
+
+/// Feeds a [`CallLog`] back to a run, answering its calls without the host.
+///
+/// Start the run the same way as the recorded one (same code, inputs and external
+/// function names), then hand the first progress to [`Replayer::run`].
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    entries: Vec<CallLogEntry>,
+    position: usize,
+}
+
+impl Replayer {
+    /// Creates a replayer positioned at the start of `log`.
+    #[must_use]
+    pub fn new(log: CallLog) -> Self {
+        Self {
+            entries: log.entries,
+            position: 0,
+        }
+    }
+
+    /// Returns the number of log entries replayed so far.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns whether every log entry has been replayed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.position == self.entries.len()
+    }
+
+    /// Drives `progress` forward, answering each call from the log.
+    ///
+    /// Returns the progress reached once the run completes, runs out of fuel, or uses up
+    /// the log. In the last case the returned progress is the first call the log has no
+    /// answer for — for a run that crashed the host, the call it crashed on — and the host
+    /// can continue answering it live.
+    ///
+    /// # Errors
+    /// Returns `ReplayError::Diverged` if the run makes a call other than the one recorded
+    /// next, and `ReplayError::Exception` if the replayed run raises.
+    pub fn run<T: ResourceTracker>(
+        &mut self,
+        mut progress: RunProgress<T>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, ReplayError> {
+        loop {
+            if self.is_finished() {
+                return Ok(progress);
+            }
+            progress = match progress {
+                RunProgress::FunctionCall {
+                    function_name,
+                    args,
+                    kwargs,
+                    method_call,
+                    state,
+                    ..
+                } => {
+                    let kind = RecordedCallKind::Function { method_call };
+                    let result = self.next_call(kind, function_name, args, kwargs)?;
+                    state.run(result, print)?
+                }
+                RunProgress::OsCall {
+                    function,
+                    args,
+                    kwargs,
+                    state,
+                    ..
+                } => {
+                    let result = self.next_call(RecordedCallKind::Os, function.to_string(), args, kwargs)?;
+                    state.run(result, print)?
+                }
+                RunProgress::Emit { value, state } => {
+                    let result = self.next_call(RecordedCallKind::Emit, "emit".to_owned(), vec![value], vec![])?;
+                    state.run(result, print)?
+                }
+                RunProgress::Sleep { duration, state } => {
+                    let args = vec![MontyObject::Float(duration.as_secs_f64())];
+                    let result = self.next_call(RecordedCallKind::Sleep, "time.sleep".to_owned(), args, vec![])?;
+                    state.run(result, print)?
+                }
+                RunProgress::ResolveFutures(state) => {
+                    let results = match &self.entries[self.position] {
+                        CallLogEntry::Resolve(results) => results.clone(),
+                        CallLogEntry::Call(_) => {
+                            let found = format!("resolve futures {:?}", state.pending_call_ids());
+                            return Err(self.diverged(found));
+                        }
+                    };
+                    self.position += 1;
+                    let results = results
+                        .into_iter()
+                        .map(|(call_id, result)| (call_id, result.into()))
+                        .collect();
+                    state.resume(results, print)?
+                }
+                finished @ (RunProgress::Paused(_) | RunProgress::Breakpoint(_) | RunProgress::Complete(_)) => {
+                    return Ok(finished);
+                }
+            };
+        }
+    }
+
+    /// Checks the call the run made against the next log entry and returns the recorded answer.
+    fn next_call(
+        &mut self,
+        kind: RecordedCallKind,
+        name: String,
+        args: Vec<MontyObject>,
+        kwargs: Vec<(MontyObject, MontyObject)>,
+    ) -> Result<ExternalResult, ReplayError> {
+        let found = RecordedCall {
+            kind,
+            name,
+            args,
+            kwargs,
+            result: RecordedResult::Future,
+        };
+        match &self.entries[self.position] {
+            CallLogEntry::Call(expected) if expected.matches(&found) => {
+                let result = expected.result.clone().into();
+                self.position += 1;
+                Ok(result)
+            }
+            _ => Err(self.diverged(format!("call {found}"))),
+        }
+    }
+
+    fn diverged(&self, found: String) -> ReplayError {
+        ReplayError::Diverged {
+            position: self.position,
+            expected: self.entries[self.position].to_string(),
+            found,
+        }
+    }
+}
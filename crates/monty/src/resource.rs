@@ -1,12 +1,19 @@
 use std::{
     fmt,
-    sync::atomic::{AtomicU16, Ordering},
-    time::{Duration, Instant},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU16, Ordering},
+    },
+    time::Duration,
 };
 
 use crate::{
     ExcType, MontyException,
+    clock::{Clock, Instant, SystemClock},
     exception_private::{ExceptionRaise, RawStackFrame, RunError, SimpleException},
+    timeline::SpanKind,
+    trace::TraceHook,
+    warnings::WarningSink,
 };
 
 /// Threshold in bytes above which `check_large_result` is called.
@@ -121,9 +128,35 @@ pub const MAX_DATA_RECURSION_DEPTH: u16 = 500;
 pub struct DepthGuard {
     /// Remaining depth before limit is exceeded.
     depth_remaining: u16,
+    /// Whether the remaining depth also limits nesting walked without recursion.
+    limits_nesting: bool,
 }
 
 impl DepthGuard {
+    /// Creates a guard allowing at most `limit` levels of recursion, and never more than
+    /// `MAX_DATA_RECURSION_DEPTH`.
+    ///
+    /// Unlike the default guard, the limit also applies to lists and tuples nested in each
+    /// other, which are walked without recursion (see `nesting_remaining`).
+    #[must_use]
+    pub fn with_limit(limit: u16) -> Self {
+        Self {
+            depth_remaining: limit.min(MAX_DATA_RECURSION_DEPTH),
+            limits_nesting: true,
+        }
+    }
+
+    /// Returns how many more levels a walk over nested data without recursion may descend:
+    /// the remaining depth for guards from `with_limit`, unlimited otherwise.
+    #[must_use]
+    pub fn nesting_remaining(&self) -> usize {
+        if self.limits_nesting {
+            usize::from(self.depth_remaining)
+        } else {
+            usize::MAX
+        }
+    }
+
     /// Increases recursion depth, returning `true` if within limits, `false` if exceeded.
     ///
     /// When this returns `true`, you MUST call `decrease()` on every return path.
@@ -173,6 +206,7 @@ impl Default for DepthGuard {
     fn default() -> Self {
         Self {
             depth_remaining: MAX_DATA_RECURSION_DEPTH,
+            limits_nesting: false,
         }
     }
 }
@@ -191,6 +225,14 @@ pub enum ResourceError {
     Memory { limit: usize, used: usize },
     /// Maximum recursion depth exceeded.
     Recursion { limit: usize, depth: usize },
+    /// A single str, list or dict would exceed its configured maximum length.
+    CollectionSize {
+        kind: CollectionKind,
+        limit: usize,
+        len: usize,
+    },
+    /// Execution was cancelled via a `CancelHandle`.
+    Cancelled,
     /// Any other error, e.g. when propagating a python exception
     Exception(MontyException),
 }
@@ -210,6 +252,12 @@ impl fmt::Display for ResourceError {
             Self::Recursion { .. } => {
                 write!(f, "maximum recursion depth exceeded")
             }
+            Self::CollectionSize { kind, limit, len } => {
+                write!(f, "{kind} length limit exceeded: {len} > {limit}")
+            }
+            Self::Cancelled => {
+                write!(f, "execution cancelled")
+            }
             Self::Exception(exc) => {
                 write!(f, "{exc}")
             }
@@ -227,6 +275,8 @@ impl ResourceError {
     /// - `Memory` → `MemoryError`
     /// - `Time` → `TimeoutError`
     /// - `Recursion` → `RecursionError`
+    /// - `CollectionSize` → `MemoryError`
+    /// - `Cancelled` → `KeyboardInterrupt`
     #[must_use]
     pub(crate) fn into_exception(self, frame: Option<RawStackFrame>) -> ExceptionRaise {
         let (exc_type, msg) = match self {
@@ -246,6 +296,11 @@ impl ResourceError {
                 ExcType::RecursionError,
                 Some("maximum recursion depth exceeded".to_string()),
             ),
+            Self::CollectionSize { kind, limit, len } => (
+                ExcType::MemoryError,
+                Some(format!("{kind} length limit exceeded: {len} > {limit}")),
+            ),
+            Self::Cancelled => (ExcType::KeyboardInterrupt, Some("execution cancelled".to_string())),
             Self::Exception(exc) => (exc.exc_type(), exc.into_message()),
         };
         let exc = SimpleException::new(exc_type, msg);
@@ -286,6 +341,17 @@ pub trait ResourceTracker: fmt::Debug {
     /// * `size` - Size in bytes of the freed allocation
     fn on_free(&mut self, get_size: impl FnOnce() -> usize);
 
+    /// Called before an existing heap value grows in place (e.g. `s += x` on a str).
+    ///
+    /// Unlike [`on_allocate`](Self::on_allocate) this isn't a new allocation, so it
+    /// should only charge memory. The default forwards to `on_allocate`.
+    ///
+    /// # Arguments
+    /// * `additional` - Number of bytes the value grows by
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.on_allocate(|| additional)
+    }
+
     /// Called periodically (at statement boundaries) to check time limits.
     ///
     /// Returns `Ok(())` if within time limit, or `Err(ResourceError::Time)`
@@ -317,11 +383,104 @@ pub trait ResourceTracker: fmt::Debug {
     ///
     /// Returns `Ok(())` to allow the operation, or `Err(ResourceError)` to reject.
     fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError>;
+
+    /// Called before a str, list or dict is created or grown to `len`.
+    ///
+    /// For `str`, `len` is the length in bytes of the UTF-8 encoding. For lists it is the
+    /// number of items, and for dicts the number of entries.
+    ///
+    /// Default allows any length.
+    #[inline]
+    fn check_collection_len(&self, _kind: CollectionKind, _len: usize) -> Result<(), ResourceError> {
+        Ok(())
+    }
+
+    /// Returns how deeply nested lists and tuples may be before `==` on them raises
+    /// `RecursionError` and their `repr()` writes the deeper levels as `...`.
+    ///
+    /// These walks keep their own work stack rather than recursing, so the limit guards
+    /// against crafted inputs rather than stack overflow. Default is [`DEFAULT_MAX_DATA_DEPTH`].
+    #[inline]
+    fn max_data_depth(&self) -> usize {
+        DEFAULT_MAX_DATA_DEPTH
+    }
+
+    /// Called when a span of interest starts: a function call frame is pushed, execution
+    /// suspends to wait on the host, or a garbage collection pause begins.
+    ///
+    /// Default is a no-op; see `TimelineTracker` for a recording implementation.
+    #[inline]
+    fn on_span_enter(&mut self, _kind: SpanKind, _name: &str) {}
+
+    /// Called when the most recently entered span of `kind` ends.
+    ///
+    /// May be called without a matching `on_span_enter` (e.g. when resuming a snapshot
+    /// that was not waiting on the host), in which case it should be ignored.
+    #[inline]
+    fn on_span_exit(&mut self, _kind: SpanKind) {}
+
+    /// Returns the hook that receives line, call, return and exception events, if any.
+    ///
+    /// Default is `None`, in which case the VM skips tracing entirely; see `TracingTracker`.
+    #[inline]
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        None
+    }
+
+    /// Returns the sink that receives compile-time and runtime warnings, if any.
+    ///
+    /// Default is `None`, in which case warnings are dropped; see `WarningTracker`.
+    #[inline]
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        None
+    }
+
+    /// Returns the clock read by `time.time()`, `time.monotonic()` and `time.perf_counter()`.
+    ///
+    /// Default is the machine's clock; see `ClockTracker`.
+    #[inline]
+    fn clock(&self) -> &dyn Clock {
+        &SystemClock
+    }
+
+    /// Returns the remaining budget reported to scripts by the `resources()` builtin.
+    ///
+    /// `None` hides the budget from the script: `resources()` then raises `NameError`
+    /// as if the builtin did not exist. Default reports every limit as unlimited.
+    #[inline]
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        Some(ResourceBudget::default())
+    }
+}
+
+/// Remaining resource budget as seen by the script through `resources()`.
+///
+/// Each field is `None` when the corresponding resource is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceBudget {
+    /// Heap memory in bytes (approximate) that can still be allocated.
+    pub memory: Option<usize>,
+    /// Number of heap allocations still allowed.
+    pub allocations: Option<usize>,
+    /// Wall-clock time left before execution raises `TimeoutError`.
+    pub time: Option<Duration>,
+}
+
+/// The kind of collection checked by `ResourceTracker::check_collection_len`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum CollectionKind {
+    /// A `str`, measured in UTF-8 bytes.
+    Str,
+    /// A `list`, measured in items.
+    List,
+    /// A `dict`, measured in entries.
+    Dict,
 }
 
 /// A resource tracker that imposes no limits except default recursion limit.
 ///
-/// Recursion limit is set to the cpython default of 1000.
+/// Recursion limit is set to [`DEFAULT_MAX_RECURSION_DEPTH`], the cpython default of 1000.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NoLimitTracker;
 
@@ -334,21 +493,25 @@ impl ResourceTracker for NoLimitTracker {
     #[inline]
     fn on_free(&mut self, _: impl FnOnce() -> usize) {}
 
+    #[inline]
+    fn on_grow(&mut self, _: usize) -> Result<(), ResourceError> {
+        Ok(())
+    }
+
     #[inline]
     fn check_time(&self) -> Result<(), ResourceError> {
         Ok(())
     }
 
-    /// Set the recursion limit to 1000.
+    /// Set the recursion limit to [`DEFAULT_MAX_RECURSION_DEPTH`].
     ///
     /// The high limit here may cause stack overflow errors in debug mode, but do not those errors should
     /// not occur with release builds.
     #[inline]
     fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
-        const DEFAULT_RECURSION_LIMIT: usize = 1000;
-        if current_depth >= DEFAULT_RECURSION_LIMIT {
+        if current_depth >= DEFAULT_MAX_RECURSION_DEPTH {
             Err(ResourceError::Recursion {
-                limit: DEFAULT_RECURSION_LIMIT,
+                limit: DEFAULT_MAX_RECURSION_DEPTH,
                 depth: current_depth + 1,
             })
         } else {
@@ -368,29 +531,56 @@ impl ResourceTracker for NoLimitTracker {
 /// All limits are optional - set to `None` to disable a specific limit.
 /// Use `ResourceLimits::default()` for no limits, or build custom limits
 /// with the builder pattern.
+///
+/// Both time limits, `max_duration` and `wall_deadline`, measure wall-clock time, not CPU
+/// time: time the host's thread spends descheduled or blocked counts against them too.
+/// There is no CPU-time limit.
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct ResourceLimits {
     /// Maximum number of heap allocations allowed.
     pub max_allocations: Option<usize>,
-    /// Maximum execution time.
+    /// Maximum wall-clock execution time.
     pub max_duration: Option<Duration>,
+    /// Absolute wall-clock deadline, checked alongside `max_duration`.
+    ///
+    /// Not serialized: an `Instant` has no meaning outside the current process, so
+    /// the deadline is dropped when limits (or a tracker) are serialized.
+    #[serde(skip)]
+    pub wall_deadline: Option<Instant>,
     /// Maximum heap memory in bytes (approximate).
     pub max_memory: Option<usize>,
     /// Run garbage collection every N allocations.
     pub gc_interval: Option<usize>,
     /// Maximum recursion depth (function call stack depth).
     pub max_recursion_depth: Option<usize>,
+    /// Maximum length of a single str, in UTF-8 bytes.
+    pub max_str_len: Option<usize>,
+    /// Maximum number of items in a single list.
+    pub max_list_len: Option<usize>,
+    /// Maximum number of entries in a single dict.
+    pub max_dict_entries: Option<usize>,
+    /// Maximum nesting of lists and tuples compared with `==` or written by `repr()`;
+    /// `None` uses [`DEFAULT_MAX_DATA_DEPTH`].
+    #[serde(default)]
+    pub max_data_depth: Option<usize>,
+    /// Hide the remaining budget from scripts, making `resources()` raise `NameError`.
+    #[serde(default)]
+    pub hide_resources: bool,
 }
 
 /// Recommended maximum recursion depth if not otherwise specified.
 pub const DEFAULT_MAX_RECURSION_DEPTH: usize = 1000;
 
+/// Maximum nesting of lists and tuples walked by `==` and `repr()` if not otherwise specified.
+pub const DEFAULT_MAX_DATA_DEPTH: usize = 100_000;
+
 impl ResourceLimits {
-    /// Creates a new ResourceLimits with all limits disabled, except max recursion which is set to 1000.
+    /// Creates a new ResourceLimits with all limits disabled, except max recursion which is set
+    /// to [`DEFAULT_MAX_RECURSION_DEPTH`].
     #[must_use]
     pub fn new() -> Self {
         Self {
-            max_recursion_depth: Some(1000),
+            max_recursion_depth: Some(DEFAULT_MAX_RECURSION_DEPTH),
             ..Default::default()
         }
     }
@@ -402,13 +592,23 @@ impl ResourceLimits {
         self
     }
 
-    /// Sets the maximum execution duration.
+    /// Sets the maximum wall-clock execution duration.
     #[must_use]
     pub fn max_duration(mut self, limit: Duration) -> Self {
         self.max_duration = Some(limit);
         self
     }
 
+    /// Sets an absolute wall-clock deadline after which execution raises `TimeoutError`.
+    ///
+    /// Useful when several runs share one time budget; whichever of `max_duration`
+    /// and `wall_deadline` expires first wins.
+    #[must_use]
+    pub fn wall_deadline(mut self, deadline: Instant) -> Self {
+        self.wall_deadline = Some(deadline);
+        self
+    }
+
     /// Sets the maximum memory usage in bytes.
     #[must_use]
     pub fn max_memory(mut self, limit: usize) -> Self {
@@ -429,6 +629,41 @@ impl ResourceLimits {
         self.max_recursion_depth = limit;
         self
     }
+
+    /// Sets the maximum length of a single str, in UTF-8 bytes.
+    #[must_use]
+    pub fn max_str_len(mut self, limit: usize) -> Self {
+        self.max_str_len = Some(limit);
+        self
+    }
+
+    /// Sets the maximum number of items in a single list.
+    #[must_use]
+    pub fn max_list_len(mut self, limit: usize) -> Self {
+        self.max_list_len = Some(limit);
+        self
+    }
+
+    /// Sets the maximum number of entries in a single dict.
+    #[must_use]
+    pub fn max_dict_entries(mut self, limit: usize) -> Self {
+        self.max_dict_entries = Some(limit);
+        self
+    }
+
+    /// Sets the maximum nesting of lists and tuples compared with `==` or written by `repr()`.
+    #[must_use]
+    pub fn max_data_depth(mut self, limit: usize) -> Self {
+        self.max_data_depth = Some(limit);
+        self
+    }
+
+    /// Sets whether the `resources()` builtin is hidden from scripts.
+    #[must_use]
+    pub fn hide_resources(mut self, hide: bool) -> Self {
+        self.hide_resources = hide;
+        self
+    }
 }
 
 /// How often to actually check `Instant::elapsed()` in `check_time`.
@@ -508,6 +743,52 @@ impl LimitedTracker {
         self.limits.max_duration = Some(duration);
         self.start_time = Instant::now();
     }
+
+    /// Sets an absolute wall-clock deadline, replacing any existing one.
+    ///
+    /// Unlike `set_max_duration`, this does not reset the start time, so an existing
+    /// `max_duration` keeps counting from when execution started.
+    pub fn set_wall_deadline(&mut self, deadline: Instant) {
+        self.limits.wall_deadline = Some(deadline);
+    }
+
+    /// Returns the `ResourceError::Time` to raise if either the duration limit or the
+    /// deadline has passed, both measured in wall-clock time.
+    ///
+    /// For a missed deadline, `limit` is the time from start to the deadline so the
+    /// error message stays comparable with the `max_duration` case.
+    fn wall_time_exceeded(&self) -> Option<ResourceError> {
+        let elapsed = self.start_time.elapsed();
+        if let Some(max) = self.limits.max_duration
+            && elapsed > max
+        {
+            return Some(ResourceError::Time { limit: max, elapsed });
+        }
+        if let Some(deadline) = self.limits.wall_deadline
+            && Instant::now() > deadline
+        {
+            let limit = deadline.saturating_duration_since(self.start_time);
+            return Some(ResourceError::Time { limit, elapsed });
+        }
+        None
+    }
+
+    /// Returns the wall-clock time left before either the duration limit or the deadline
+    /// expires.
+    fn wall_time_remaining(&self) -> Option<Duration> {
+        let by_duration = self
+            .limits
+            .max_duration
+            .map(|max| max.saturating_sub(self.start_time.elapsed()));
+        let by_deadline = self
+            .limits
+            .wall_deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        match (by_duration, by_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
 }
 
 impl ResourceTracker for LimitedTracker {
@@ -545,21 +826,35 @@ impl ResourceTracker for LimitedTracker {
         self.current_memory = self.current_memory.saturating_sub(get_size());
     }
 
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        let new_memory = self.current_memory + additional;
+        if let Some(max) = self.limits.max_memory
+            && new_memory > max
+        {
+            return Err(ResourceError::Memory {
+                limit: max,
+                used: new_memory,
+            });
+        }
+        self.current_memory = new_memory;
+        Ok(())
+    }
+
     fn check_time(&self) -> Result<(), ResourceError> {
-        if let Some(max) = self.limits.max_duration {
-            let count = self.check_counter.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
-            if count.is_multiple_of(TIME_CHECK_INTERVAL) {
-                // Only call Instant::elapsed() every TIME_CHECK_INTERVAL calls
-                let elapsed = self.start_time.elapsed();
-                if elapsed > max {
-                    // Reset counter so the very next check_time call also triggers
-                    // an elapsed check. This is important because some callers
-                    // (e.g. repr_sequence_fmt) catch the error and return normally,
-                    // and we need the VM loop's next check_time to re-detect timeout.
-                    self.check_counter
-                        .store(TIME_CHECK_INTERVAL.wrapping_sub(1), Ordering::Relaxed);
-                    return Err(ResourceError::Time { limit: max, elapsed });
-                }
+        if self.limits.max_duration.is_none() && self.limits.wall_deadline.is_none() {
+            return Ok(());
+        }
+        let count = self.check_counter.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        if count.is_multiple_of(TIME_CHECK_INTERVAL) {
+            // Only call Instant::elapsed() every TIME_CHECK_INTERVAL calls
+            if let Some(err) = self.wall_time_exceeded() {
+                // Reset counter so the very next check_time call also triggers
+                // an elapsed check. This is important because some callers
+                // (e.g. repr_sequence_fmt) catch the error and return normally,
+                // and we need the VM loop's next check_time to re-detect timeout.
+                self.check_counter
+                    .store(TIME_CHECK_INTERVAL.wrapping_sub(1), Ordering::Relaxed);
+                return Err(err);
             }
         }
         Ok(())
@@ -591,4 +886,167 @@ impl ResourceTracker for LimitedTracker {
         }
         Ok(())
     }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        let limit = match kind {
+            CollectionKind::Str => self.limits.max_str_len,
+            CollectionKind::List => self.limits.max_list_len,
+            CollectionKind::Dict => self.limits.max_dict_entries,
+        };
+        match limit {
+            Some(limit) if len > limit => Err(ResourceError::CollectionSize { kind, limit, len }),
+            _ => Ok(()),
+        }
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.limits.max_data_depth.unwrap_or(DEFAULT_MAX_DATA_DEPTH)
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        if self.limits.hide_resources {
+            return None;
+        }
+        Some(ResourceBudget {
+            memory: self
+                .limits
+                .max_memory
+                .map(|max| max.saturating_sub(self.current_memory)),
+            allocations: self
+                .limits
+                .max_allocations
+                .map(|max| max.saturating_sub(self.allocation_count)),
+            time: self.wall_time_remaining(),
+        })
+    }
+}
+
+/// Cheap, cloneable handle used to cancel an in-flight run from another thread.
+///
+/// Clones share the same flag, so the host keeps one clone and hands another to a
+/// `CancellableTracker`. Once `cancel()` is called, the VM raises an uncatchable
+/// `KeyboardInterrupt` at its next time check.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Creates a new handle in the non-cancelled state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of any run using this handle.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether `cancel()` has been called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A resource tracker that wraps another tracker and checks a `CancelHandle`.
+///
+/// The cancel flag is checked on every `check_time` call (a single relaxed atomic load),
+/// after the inner tracker's own time check.
+///
+/// The handle is not serialized: a deserialized tracker gets a fresh handle, so use
+/// `set_cancel_handle` to reconnect it to the host's handle after loading a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct CancellableTracker<T: ResourceTracker> {
+    inner: T,
+    #[serde(skip)]
+    cancel_handle: CancelHandle,
+}
+
+impl<T: ResourceTracker> CancellableTracker<T> {
+    /// Creates a new cancellable tracker wrapping the given tracker.
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            cancel_handle: CancelHandle::new(),
+        }
+    }
+
+    /// Returns a clone of the handle that cancels runs using this tracker.
+    #[must_use]
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel_handle.clone()
+    }
+
+    /// Replaces the cancel handle, e.g. after deserializing a snapshot.
+    pub fn set_cancel_handle(&mut self, handle: CancelHandle) {
+        self.cancel_handle = handle;
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker> ResourceTracker for CancellableTracker<T> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()?;
+        if self.cancel_handle.is_cancelled() {
+            return Err(ResourceError::Cancelled);
+        }
+        Ok(())
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        self.inner.trace_hook()
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        self.inner.warning_sink()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.inner.clock()
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
 }
@@ -1,21 +1,47 @@
 //! Public interface for running Monty code.
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
+#[cfg(feature = "heap-audit")]
+use crate::audit::HeapAudit;
 use crate::{
     ExcType, MontyException,
+    annotations::external_signatures,
     asyncio::CallId,
-    bytecode::{Code, Compiler, FrameExit, VM, VMSnapshot},
+    builder::MontyRunBuilder,
+    builtins::Builtins,
+    bytecode::{
+        Code, CodeDisassembly, Compiler, Debugger, FrameExit, VM, VMSnapshot, disassemble, render_disassembly,
+        validate_program,
+    },
+    coverage::{CoverageReport, LineCoverage},
+    diagnostics::Diagnostic,
     exception_private::RunResult,
-    heap::{DropWithHeap, Heap},
-    intern::{ExtFunctionId, Interns},
+    expressions::PreparedNode,
+    external_calls::{ExternalFunctionUsage, collect_external_calls},
+    fold::fold_constants,
+    heap::{DropWithHeap, Heap, HeapData, HeapId},
+    instance::MontyInstance,
+    intern::{ExtFunctionId, FunctionId, Interns, StaticStrings, StringId},
     io::PrintWriter,
-    namespace::Namespaces,
+    messages::ErrorCode,
+    namespace::{GLOBAL_NS_IDX, NamespaceId, Namespaces},
     object::MontyObject,
     os::OsFunction,
     parse::parse,
     prepare::prepare,
+    profile::{ProfileReport, Profiler},
     resource::{NoLimitTracker, ResourceTracker},
+    shared_interns::SharedInterns,
+    snapshot_format::{self, SnapshotError, SnapshotKind},
     value::Value,
+    warnings::MontyWarning,
 };
 
 /// Primary interface for running Monty code.
@@ -33,10 +59,16 @@ use crate::{
 /// let result = runner.run_no_limits(vec![MontyObject::Int(41)]).unwrap();
 /// assert_eq!(result, MontyObject::Int(42));
 /// ```
+///
+/// The compiled code lives in a shared [`CompiledProgram`], so cloning a `MontyRun` is cheap
+/// and `MontyRun` is `Send + Sync`: compile a script once, then call `run()` through a shared
+/// reference or `start()` on a clone from as many threads as needed.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MontyRun {
-    /// The underlying executor containing parsed AST and interns.
-    executor: Executor,
+    /// The compiled program, shared by clones of this runner and by the runs they start.
+    program: Arc<CompiledProgram>,
+    /// Values set with `with_globals()`, stored in their namespace slots before each run.
+    initial_globals: Arc<[(NamespaceId, MontyObject)]>,
 }
 
 impl MontyRun {
@@ -58,19 +90,252 @@ impl MontyRun {
         input_names: Vec<String>,
         external_functions: Vec<String>,
     ) -> Result<Self, MontyException> {
-        Executor::new(code, script_name, input_names, external_functions).map(|executor| Self { executor })
+        Self::new_with_options(
+            code,
+            script_name,
+            input_names,
+            external_functions,
+            CompileOptions::default(),
+        )
+    }
+
+    /// Creates a new run snapshot like [`MontyRun::new`], with explicit compiler options.
+    ///
+    /// Use `CompileOptions::new().optimize(false)` to compile the code exactly as written,
+    /// e.g. when debugging the compiler or inspecting the bytecode for a given expression.
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the code cannot be parsed, or `ValueError` if `options`
+    /// allows or denies a name that isn't a builtin.
+    pub fn new_with_options(
+        code: String,
+        script_name: &str,
+        input_names: Vec<String>,
+        external_functions: Vec<String>,
+        options: CompileOptions,
+    ) -> Result<Self, MontyException> {
+        CompiledProgram::new(code, script_name, input_names, external_functions, options, |_| Ok(()))
+            .map(|program| Self::from(Arc::new(program)))
+    }
+
+    /// Starts configuring a run of `code` with a [`MontyRunBuilder`], which also takes the
+    /// limits, print sink and trace hook to run it with.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun};
+    ///
+    /// let mut run = MontyRun::builder("x * 2").inputs(["x"]).optimize(false).build().unwrap();
+    /// assert_eq!(run.run(vec![MontyObject::Int(21)]), Ok(MontyObject::Int(42)));
+    /// ```
+    #[must_use]
+    pub fn builder(code: impl Into<String>) -> MontyRunBuilder<'static> {
+        MontyRunBuilder::new(code.into())
+    }
+
+    /// Sets module-level variables before the code runs, so hosts can hand data to a script
+    /// without formatting it into the source.
+    ///
+    /// Unlike inputs, globals keep their values for every run, including runs of clones of
+    /// this runner. Setting a name again replaces its value. Names the code never refers to
+    /// are ignored, and names declared as inputs or external functions keep those values.
+    ///
+    /// Functions see a global the same way they see a variable the module assigns: only if
+    /// module-level code before the `def` refers to it. A name that only functions read
+    /// should be declared in `input_names` instead.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun};
+    ///
+    /// let payload = MontyObject::dict(vec![(MontyObject::from("qty"), MontyObject::Int(3))]);
+    /// let runner = MontyRun::new("payload['qty'] * 2".to_owned(), "test.py", vec![], vec![])
+    ///     .unwrap()
+    ///     .with_globals([("payload", payload)]);
+    /// assert_eq!(runner.run_no_limits(vec![]).unwrap(), MontyObject::Int(6));
+    /// ```
+    #[must_use]
+    pub fn with_globals<N: AsRef<str>>(mut self, globals: impl IntoIterator<Item = (N, MontyObject)>) -> Self {
+        let mut initial_globals = self.initial_globals.to_vec();
+        for (name, value) in globals {
+            let Some(&id) = self.program.name_map.get(name.as_ref()) else {
+                continue;
+            };
+            match initial_globals.iter_mut().find(|(existing, _)| *existing == id) {
+                Some(entry) => entry.1 = value,
+                None => initial_globals.push((id, value)),
+            }
+        }
+        self.initial_globals = initial_globals.into();
+        self
+    }
+
+    /// Returns the compiled program shared by this runner and the runs it starts.
+    #[must_use]
+    pub fn program(&self) -> &Arc<CompiledProgram> {
+        &self.program
+    }
+
+    /// Returns the values set with `with_globals()`, keyed by namespace slot.
+    pub(crate) fn initial_globals(&self) -> &Arc<[(NamespaceId, MontyObject)]> {
+        &self.initial_globals
+    }
+
+    /// Checks code without running it, returning every problem found instead of only the
+    /// first error `new()` would return.
+    ///
+    /// All syntax errors are reported together; unsupported constructs are reported per
+    /// statement once the syntax is valid, and errors from later compile stages once those
+    /// pass. Warnings point out code that can never run. An empty result means `new()`
+    /// succeeds with the same arguments.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyRun, Severity};
+    ///
+    /// let diagnostics = MontyRun::diagnostics("if x\n    pass\nclass A: pass", "test.py", vec![], vec![]);
+    /// assert_eq!(diagnostics[0].severity, Severity::Error);
+    /// assert_eq!(diagnostics[0].fix.as_ref().unwrap().replacement, ":");
+    /// ```
+    #[must_use]
+    pub fn diagnostics(
+        code: &str,
+        script_name: &str,
+        input_names: Vec<String>,
+        external_functions: Vec<String>,
+    ) -> Vec<Diagnostic> {
+        crate::diagnostics::collect(code, script_name, input_names, external_functions)
+    }
+
+    /// Checks code without running it, like `diagnostics()`, and also reports every read of
+    /// a name that nothing assigns before the read runs.
+    ///
+    /// Such reads raise `NameError` only when they execute, so `new()` accepts the code;
+    /// hosts use this to reject a script when it's uploaded instead. A name counts as
+    /// assigned if `input_names` or `external_functions` declares it, or if code before
+    /// the read (in source order) binds it. Reads inside a module-level loop are only
+    /// reported if nothing at module level assigns the name, since an earlier iteration may
+    /// have. Undefined names are only looked for once the code compiles.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{ExcType, MontyRun};
+    ///
+    /// let code = "total = price * 2\nprice = 3";
+    /// let diagnostics = MontyRun::check(code, "test.py", vec![], vec![]);
+    /// assert_eq!(diagnostics[0].exc_type, Some(ExcType::NameError));
+    /// assert_eq!(diagnostics[0].message, "name 'price' is not defined");
+    /// ```
+    #[must_use]
+    pub fn check(
+        code: &str,
+        script_name: &str,
+        input_names: Vec<String>,
+        external_functions: Vec<String>,
+    ) -> Vec<Diagnostic> {
+        crate::diagnostics::check(code, script_name, input_names, external_functions)
+    }
+
+    /// Creates a run like `new()`, rejecting the code if `check` fails on the prepared
+    /// nodes, before constant folding and compilation.
+    pub(crate) fn new_checked(
+        code: String,
+        script_name: &str,
+        input_names: Vec<String>,
+        check: impl FnOnce(&[PreparedNode]) -> Result<(), MontyException>,
+    ) -> Result<Self, MontyException> {
+        CompiledProgram::new(code, script_name, input_names, vec![], CompileOptions::default(), check)
+            .map(|program| Self::from(Arc::new(program)))
+    }
+
+    /// Builds the process-wide tables shared by every `MontyRun` (interned static strings
+    /// and builtin name lookups).
+    ///
+    /// The tables are built lazily on first use anyway; calling this once at process startup
+    /// moves that one-off cost out of the first request. Calling it again is a no-op.
+    pub fn warm_up() {
+        crate::intern::warm_up();
+        crate::builtins::warm_up();
     }
 
     /// Returns the code that was parsed to create this snapshot.
     #[must_use]
     pub fn code(&self) -> &str {
-        &self.executor.code
+        &self.program.code
+    }
+
+    /// Returns the external functions the code references, with every call site.
+    ///
+    /// Functions are listed in the order they were passed to `new()`; those the code never
+    /// references are omitted. Calls inside branches that can never run are still listed,
+    /// so hosts can reject code that names an unknown or disallowed function before
+    /// starting it.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::MontyRun;
+    ///
+    /// let code = "fetch('a')\nfetch('b', timeout=1)";
+    /// let externals = vec!["fetch".to_owned(), "log".to_owned()];
+    /// let runner = MontyRun::new(code.to_owned(), "test.py", vec![], externals).unwrap();
+    /// let calls = runner.external_calls();
+    /// assert_eq!(calls.len(), 1);
+    /// assert_eq!(calls[0].name, "fetch");
+    /// assert_eq!(calls[0].max_args(), Some(2));
+    /// ```
+    #[must_use]
+    pub fn external_calls(&self) -> &[ExternalFunctionUsage] {
+        &self.program.external_calls
+    }
+
+    /// Returns the compile-time warnings found in the code, in source order.
+    ///
+    /// These are also reported to the warning sink of every run, see `WarningTracker`.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyRun, WarningCategory};
+    ///
+    /// let runner = MontyRun::new("x = 1\nx is 1".to_owned(), "test.py", vec![], vec![]).unwrap();
+    /// let warnings = runner.warnings();
+    /// assert_eq!(warnings[0].category, WarningCategory::SyntaxWarning);
+    /// assert_eq!(warnings[0].start.line, 2);
+    /// assert_eq!(warnings[0].message, "\"is\" with 'int' literal. Did you mean \"==\"?");
+    /// ```
+    #[must_use]
+    pub fn warnings(&self) -> &[MontyWarning] {
+        &self.program.warnings
+    }
+
+    /// Returns a listing of the compiled bytecode, like CPython's `dis.dis()`.
+    ///
+    /// The module code comes first, then each function in definition order. Meant for
+    /// debugging the compiler and checking optimizations; the format isn't stable.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::MontyRun;
+    ///
+    /// let runner = MontyRun::new("x = 1\nx + 2".to_owned(), "test.py", vec![], vec![]).unwrap();
+    /// assert!(runner.disassemble().contains("StoreLocal"));
+    /// ```
+    #[must_use]
+    pub fn disassemble(&self) -> String {
+        render_disassembly(&self.disassembly())
+    }
+
+    /// Returns the compiled bytecode as instruction records with line numbers.
+    ///
+    /// Structured form of `disassemble()`, one entry per code object.
+    #[must_use]
+    pub fn disassembly(&self) -> Vec<CodeDisassembly> {
+        disassemble(&self.program.module_code, &self.program.interns)
     }
 
     /// Executes the code and returns both the result and reference count data, used for testing only.
     #[cfg(feature = "ref-count-return")]
     pub fn run_ref_counts(&self, inputs: Vec<MontyObject>) -> Result<RefCountOutput, MontyException> {
-        self.executor.run_ref_counts(inputs)
+        self.program.run_ref_counts(inputs, &self.initial_globals)
     }
 
     /// Executes the code to completion assuming not external functions or snapshotting.
@@ -88,10 +353,160 @@ impl MontyRun {
         resource_tracker: impl ResourceTracker,
         print: &mut PrintWriter<'_>,
     ) -> Result<MontyObject, MontyException> {
-        self.executor.run(inputs, resource_tracker, print)
+        self.program
+            .run(inputs, &self.initial_globals, resource_tracker, None, None, None, print)
+    }
+
+    /// Runs the module code, then calls the module-level function `function_name` with
+    /// positional `args`, returning its result.
+    ///
+    /// Every call starts from fresh globals. To call functions repeatedly while keeping
+    /// globals, and without re-running the module code each time, use `MontyInstance`.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+    ///
+    /// let code = "def handler(event):\n    return event['n'] + 1";
+    /// let runner = MontyRun::new(code.to_owned(), "handler.py", vec![], vec![]).unwrap();
+    /// let event = MontyObject::dict(vec![(MontyObject::from("n"), MontyObject::Int(41))]);
+    /// let result = runner.call("handler", vec![], vec![event], NoLimitTracker, &mut PrintWriter::Disabled);
+    /// assert_eq!(result, Ok(MontyObject::Int(42)));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the module code or the call raises, or with `NameError`
+    /// if there is no such global.
+    pub fn call<T: ResourceTracker>(
+        &self,
+        function_name: &str,
+        inputs: Vec<MontyObject>,
+        args: Vec<MontyObject>,
+        resource_tracker: T,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<MontyObject, MontyException> {
+        MontyInstance::new(self, inputs, resource_tracker, print)?.call(function_name, args, print)
+    }
+
+    /// Executes the code to completion like `run()`, also returning the final value of
+    /// every module-level variable.
+    ///
+    /// This lets a script produce several named outputs instead of ending with a single
+    /// expression. Variables that were never assigned (or were deleted) and external
+    /// functions are left out; inputs are included.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+    ///
+    /// let runner = MontyRun::new("total = 1 + 2\nlabel = 'sum'".to_owned(), "test.py", vec![], vec![]).unwrap();
+    /// let (_, globals) = runner
+    ///     .run_capture_globals(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+    ///     .unwrap();
+    /// assert_eq!(globals["total"], MontyObject::Int(3));
+    /// assert_eq!(globals["label"], MontyObject::String("sum".to_owned()));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `MontyException` if the code raises; the globals are discarded.
+    pub fn run_capture_globals(
+        &self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: impl ResourceTracker,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<(MontyObject, HashMap<String, MontyObject>), MontyException> {
+        let mut globals = HashMap::new();
+        let result = self.program.run(
+            inputs,
+            &self.initial_globals,
+            resource_tracker,
+            None,
+            None,
+            Some(&mut globals),
+            print,
+        )?;
+        Ok((result, globals))
+    }
+
+    /// Executes the code to completion like `run()`, counting the opcodes executed and the
+    /// time spent on each source line.
+    ///
+    /// The report is returned whether or not the run succeeds, so a script that hits a
+    /// time limit can still be profiled. Profiling slows execution down noticeably, so
+    /// times are best compared with each other rather than with unprofiled runs.
+    pub fn run_profiled(
+        &self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: impl ResourceTracker,
+        print: &mut PrintWriter<'_>,
+    ) -> (Result<MontyObject, MontyException>, ProfileReport) {
+        let mut profiler = Profiler::default();
+        let result = self.program.run(
+            inputs,
+            &self.initial_globals,
+            resource_tracker,
+            Some(&mut profiler),
+            None,
+            None,
+            print,
+        );
+        (result, profiler.finish(&self.program.interns))
+    }
+
+    /// Executes the code to completion like `run()`, recording which source lines ran.
+    ///
+    /// The report is returned whether or not the run succeeds; lines after the point of
+    /// failure show up as missed.
+    pub fn run_coverage(
+        &self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: impl ResourceTracker,
+        print: &mut PrintWriter<'_>,
+    ) -> (Result<MontyObject, MontyException>, CoverageReport) {
+        let mut coverage = LineCoverage::default();
+        let result = self.program.run(
+            inputs,
+            &self.initial_globals,
+            resource_tracker,
+            None,
+            Some(&mut coverage),
+            None,
+            print,
+        );
+        let report = coverage.finish(&self.program.module_code, &self.program.interns);
+        (result, report)
+    }
+
+    /// Executes the code to completion like `run()`, then audits the heap for leaked
+    /// references.
+    ///
+    /// The audit is taken once the result has been converted, while the globals are still
+    /// alive, so every remaining heap entry should be reachable from a global or be part of
+    /// a cycle the garbage collector hasn't freed yet.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+    ///
+    /// let runner = MontyRun::new("items = [[1], [2]]\nlen(items)".to_owned(), "test.py", vec![], vec![]).unwrap();
+    /// let (result, audit) = runner.run_audited(vec![], NoLimitTracker, &mut PrintWriter::Disabled);
+    /// assert_eq!(result, Ok(MontyObject::Int(2)));
+    /// assert_eq!(audit.objects_by_type["list"], 3);
+    /// audit.assert_no_leaks();
+    /// ```
+    #[cfg(feature = "heap-audit")]
+    pub fn run_audited(
+        &self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: impl ResourceTracker,
+        print: &mut PrintWriter<'_>,
+    ) -> (Result<MontyObject, MontyException>, HeapAudit) {
+        self.program
+            .run_audited(inputs, &self.initial_globals, resource_tracker, print)
     }
 
     /// Executes the code to completion with no resource limits, printing to stdout/stderr.
+    #[cfg(feature = "stdout")]
     pub fn run_no_limits(&self, inputs: Vec<MontyObject>) -> Result<MontyObject, MontyException> {
         self.run(inputs, NoLimitTracker, &mut PrintWriter::Stdout)
     }
@@ -109,13 +524,74 @@ impl MontyRun {
 
     /// Deserializes a runner from binary format.
     ///
+    /// The bytecode is checked like `load_code()` does, and malformed bytecode is reported
+    /// as `postcard::Error::SerdeDeCustom`.
+    ///
     /// # Arguments
     /// * `bytes` - The serialized runner data from `dump()`
     ///
     /// # Errors
     /// Returns an error if deserialization fails.
     pub fn load(bytes: &[u8]) -> Result<Self, postcard::Error> {
-        postcard::from_bytes(bytes)
+        let runner: Self = postcard::from_bytes(bytes)?;
+        runner.program.validate().map_err(|_| postcard::Error::SerdeDeCustom)?;
+        Ok(runner)
+    }
+
+    /// Serializes the compiled bytecode so it can be persisted and reused without re-parsing.
+    ///
+    /// Unlike `dump()`, the output starts with a versioned header (see `SnapshotError`), and
+    /// `load_code()` refuses bytecode from any other Monty version. Use this when the bytes outlive
+    /// the process, e.g. a compile cache on disk or shared between services.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{MontyObject, MontyRun};
+    ///
+    /// let runner = MontyRun::new("x * 2".to_owned(), "test.py", vec!["x".to_owned()], vec![]).unwrap();
+    /// let bytes = runner.dump_code().unwrap();
+    ///
+    /// let loaded = MontyRun::load_code(&bytes).unwrap();
+    /// assert_eq!(loaded.run_no_limits(vec![MontyObject::Int(21)]).unwrap(), MontyObject::Int(42));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails.
+    pub fn dump_code(&self) -> Result<Vec<u8>, postcard::Error> {
+        snapshot_format::dump(SnapshotKind::Code, self)
+    }
+
+    /// Restores a runner from `dump_code()` output.
+    ///
+    /// The bytecode is checked before it is returned: every instruction must decode, jumps
+    /// must land on instructions, and the constants, variables, names and functions that
+    /// instructions refer to must exist. How the bytecode uses the operand stack is not
+    /// checked, so deliberately crafted bytecode can still make the VM panic. Hosts that
+    /// keep compiled code where users can modify it should authenticate it, e.g. with an
+    /// HMAC, before loading it.
+    ///
+    /// # Errors
+    /// Returns `SnapshotError::VersionMismatch` if the bytes were written by another Monty
+    /// version, `SnapshotError::Corrupt` if the bytecode is malformed, or another
+    /// `SnapshotError` if they are not valid `dump_code()` output.
+    pub fn load_code(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let runner: Self = snapshot_format::load(SnapshotKind::Code, bytes)?;
+        runner.program.validate().map_err(SnapshotError::Corrupt)?;
+        Ok(runner)
+    }
+
+    /// Restores a runner from `dump_code()` output, sharing its constants with every other
+    /// program compiled or loaded with `shared`; see [`CompileOptions::share_interns`].
+    ///
+    /// # Errors
+    /// Returns the same errors as [`load_code()`](Self::load_code).
+    pub fn load_code_shared(bytes: &[u8], shared: &SharedInterns) -> Result<Self, SnapshotError> {
+        let mut runner = Self::load_code(bytes)?;
+        Arc::get_mut(&mut runner.program)
+            .expect("a freshly loaded program is not shared")
+            .interns
+            .share(shared);
+        Ok(runner)
     }
 
     /// Starts execution with the given inputs and resource tracker, consuming self.
@@ -124,6 +600,8 @@ impl MontyRun {
     ///
     /// For iterative execution, `start()` consumes self and returns a `RunProgress`:
     /// - `RunProgress::FunctionCall { ..., state }` - external function call, call `state.run(return_value)` to resume
+    /// - `RunProgress::Emit { value, state }` - the script streamed `value` with `emit()`, call `state.run(...)` to resume
+    /// - `RunProgress::Sleep { duration, state }` - the script called `time.sleep()`, call `state.run(None)` to resume
     /// - `RunProgress::Complete(value)` - execution finished
     ///
     /// This enables snapshotting execution state and returning control to the host
@@ -149,22 +627,93 @@ impl MontyRun {
         resource_tracker: T,
         print: &mut PrintWriter<'_>,
     ) -> Result<RunProgress<T>, MontyException> {
-        let executor = self.executor;
+        self.start_inner(inputs, resource_tracker, None, None, print)
+    }
+
+    /// Starts execution like `start()`, stopping before the first instruction of any of
+    /// the given source lines (1-based).
+    ///
+    /// Each stop returns `RunProgress::Breakpoint`, whose state exposes the current line
+    /// and locals and can continue to the next breakpoint or single-step. Breakpoints stay
+    /// active across external calls and snapshots.
+    ///
+    /// # Errors
+    /// Same as `start()`.
+    pub fn start_with_breakpoints<T: ResourceTracker>(
+        self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        breakpoints: impl IntoIterator<Item = u16>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, MontyException> {
+        let debugger = Debugger::new(breakpoints);
+        self.start_inner(inputs, resource_tracker, None, Some(debugger), print)
+    }
+
+    /// Starts execution like `start()`, but executes at most `fuel` opcodes.
+    ///
+    /// If the budget runs out before execution finishes, returns `RunProgress::Paused`;
+    /// call `state.run_fuel(n)` to continue with a fresh budget. This allows many scripts
+    /// to be scheduled cooperatively on a single thread.
+    ///
+    /// The remaining fuel is carried across external function calls, so resuming a
+    /// `FunctionCall` with `state.run(...)` continues with what's left of the current slice.
+    ///
+    /// # Errors
+    /// Same as `start()`.
+    pub fn start_fuel<T: ResourceTracker>(
+        self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        fuel: u64,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, MontyException> {
+        self.start_inner(inputs, resource_tracker, Some(fuel), None, print)
+    }
+
+    /// Shared implementation of `start()`, `start_fuel()` and `start_with_breakpoints()`.
+    fn start_inner<T: ResourceTracker>(
+        self,
+        inputs: Vec<MontyObject>,
+        resource_tracker: T,
+        fuel: Option<u64>,
+        debugger: Option<Debugger>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, MontyException> {
+        let Self {
+            program,
+            initial_globals,
+        } = self;
 
         // Create heap and prepare namespaces
-        let mut heap = Heap::new(executor.namespace_size, resource_tracker);
-        let mut namespaces = executor.prepare_namespaces(inputs, &mut heap)?;
+        let mut heap = Heap::new(program.namespace_size, resource_tracker);
+        program.report_warnings(&mut heap);
+        let mut namespaces = program.prepare_namespaces(inputs, &initial_globals, &mut heap)?;
 
         // Create and run VM
-        let mut vm = VM::new(&mut heap, &mut namespaces, &executor.interns, print);
+        let mut vm = VM::new(&mut heap, &mut namespaces, &program.interns, print);
+        vm.set_fuel(fuel);
+        if let Some(debugger) = debugger {
+            vm.set_debugger(debugger);
+        }
 
         // Start execution
-        let vm_result = vm.run_module(&executor.module_code);
+        let vm_result = vm.run_module(&program.module_code);
 
         let vm_state = vm.check_snapshot(&vm_result);
 
         // Handle the result using the destructured parts
-        handle_vm_result(vm_result, vm_state, executor, heap, namespaces)
+        handle_vm_result(vm_result, vm_state, program, heap, namespaces)
+    }
+}
+
+impl From<Arc<CompiledProgram>> for MontyRun {
+    /// Creates a runner for an already compiled program, e.g. one taken from another runner's `program()`.
+    fn from(program: Arc<CompiledProgram>) -> Self {
+        Self {
+            program,
+            initial_globals: Arc::default(),
+        }
     }
 }
 
@@ -173,6 +722,9 @@ impl MontyRun {
 /// This enum owns the execution state, ensuring type-safe state transitions.
 /// - `FunctionCall` contains info about an external function call and state to resume
 /// - `ResolveFutures` contains pending futures that need resolution before continuing
+/// - `Emit` contains a value the script streamed to the host, and state to resume
+/// - `Paused` contains state to resume after a fuel-limited run used up its budget
+/// - `Breakpoint` contains state stopped at a breakpoint, to inspect and resume
 /// - `Complete` contains just the final value (execution is done)
 ///
 /// # Type Parameters
@@ -230,8 +782,43 @@ pub enum RunProgress<T: ResourceTracker> {
     /// The host must resolve some or all of the pending calls before continuing.
     /// Use `state.resume(results)` to provide results for pending calls.
     ///
-    /// access the pending call ids with `.pending_call_ids()`
+    /// access the pending call ids with `.pending_call_ids()`, and the calls to batched
+    /// external functions queued since the run last yielded with `.batched_calls()`
     ResolveFutures(FutureSnapshot<T>),
+    /// The script streamed a value to the host by calling `emit(value)`.
+    ///
+    /// Each emission is handed over as soon as it is produced and released from the
+    /// interpreter's heap, so a script can transform an unbounded sequence row by row.
+    /// Call `state.run(MontyObject::None)` to continue; the value passed in becomes the
+    /// return value of `emit()`.
+    Emit {
+        /// The emitted value.
+        value: MontyObject,
+        /// The execution state that can be resumed with `emit()`'s return value.
+        state: Snapshot<T>,
+    },
+    /// The script called `time.sleep(duration)`.
+    ///
+    /// The VM never blocks: the host's scheduler decides when the script may continue,
+    /// e.g. after a real timer fires or by advancing a virtual clock. Call
+    /// `state.run(MontyObject::None)` to resume; `time.sleep()` returns that value.
+    Sleep {
+        /// The requested sleep duration.
+        duration: Duration,
+        /// The execution state to resume once the host is done waiting.
+        state: Snapshot<T>,
+    },
+    /// Execution ran out of fuel before finishing.
+    ///
+    /// Only returned by fuel-limited runs (`MontyRun::start_fuel()`, `PausedSnapshot::run_fuel()`).
+    /// Use `state.run_fuel(n)` to continue with a new budget or `state.run()` to run without one.
+    Paused(PausedSnapshot<T>),
+    /// Execution stopped at a line breakpoint, or at the next line after `state.step()`.
+    ///
+    /// Only returned by runs started with `MontyRun::start_with_breakpoints()`. Inspect
+    /// the stop with `state.line()` and `state.locals()`, then continue with
+    /// `state.resume()` or `state.step()`.
+    Breakpoint(BreakpointSnapshot<T>),
     /// Execution completed with a final result.
     Complete(MontyObject),
 }
@@ -284,25 +871,152 @@ impl<T: ResourceTracker> RunProgress<T> {
             _ => None,
         }
     }
+
+    /// Consumes the `RunProgress` and returns the emitted value and state.
+    ///
+    /// Returns `(value, state)` if this is `Emit`, None otherwise.
+    #[must_use]
+    pub fn into_emit(self) -> Option<(MontyObject, Snapshot<T>)> {
+        match self {
+            Self::Emit { value, state } => Some((value, state)),
+            _ => None,
+        }
+    }
+
+    /// Consumes the `RunProgress` and returns the requested sleep duration and state.
+    ///
+    /// Returns `(duration, state)` if this is `Sleep`, None otherwise.
+    #[must_use]
+    pub fn into_sleep(self) -> Option<(Duration, Snapshot<T>)> {
+        match self {
+            Self::Sleep { duration, state } => Some((duration, state)),
+            _ => None,
+        }
+    }
+
+    /// Consumes the `RunProgress` and returns the paused state.
+    ///
+    /// Returns the state if this is `Paused`, None otherwise.
+    #[must_use]
+    pub fn into_paused(self) -> Option<PausedSnapshot<T>> {
+        match self {
+            Self::Paused(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Consumes the `RunProgress` and returns the state stopped at a breakpoint.
+    ///
+    /// Returns the state if this is `Breakpoint`, None otherwise.
+    #[must_use]
+    pub fn into_breakpoint(self) -> Option<BreakpointSnapshot<T>> {
+        match self {
+            Self::Breakpoint(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    /// Compacts the heap of the contained snapshot, if any.
+    ///
+    /// See `Snapshot::compact_heap`; does nothing for `Complete`.
+    pub fn compact_heap(&mut self) {
+        match self {
+            Self::FunctionCall { state, .. }
+            | Self::OsCall { state, .. }
+            | Self::Emit { state, .. }
+            | Self::Sleep { state, .. } => state.compact_heap(),
+            Self::ResolveFutures(state) => state.compact_heap(),
+            Self::Paused(state) => state.compact_heap(),
+            Self::Breakpoint(state) => state.compact_heap(),
+            Self::Complete(_) => {}
+        }
+    }
 }
 
 impl<T: ResourceTracker + serde::Serialize> RunProgress<T> {
     /// Serializes the execution state to a binary format.
     ///
+    /// The output starts with a header recording the Monty version that wrote it and a
+    /// checksum of the state, which `load()` verifies.
+    ///
     /// # Errors
     /// Returns an error if serialization fails.
     pub fn dump(&self) -> Result<Vec<u8>, postcard::Error> {
-        postcard::to_allocvec(self)
+        snapshot_format::dump(SnapshotKind::RunProgress, self)
+    }
+
+    /// Dumps the run with a value referring to something it doesn't have pushed onto its
+    /// operand stack, so the output decodes but `load()` must reject it.
+    ///
+    /// Used to check snapshot validation; a finished run is dumped unchanged.
+    pub(crate) fn dump_inconsistent(mut self, inconsistency: Inconsistency) -> Result<Vec<u8>, postcard::Error> {
+        let (vm_state, program) = match &mut self {
+            Self::FunctionCall { state, .. }
+            | Self::OsCall { state, .. }
+            | Self::Emit { state, .. }
+            | Self::Sleep { state, .. } => (&mut state.vm_state, &state.program),
+            Self::ResolveFutures(state) => (&mut state.vm_state, &state.program),
+            Self::Paused(state) => (&mut state.vm_state, &state.program),
+            Self::Breakpoint(state) => (&mut state.vm_state, &state.program),
+            Self::Complete(_) => return self.dump(),
+        };
+        let value = match inconsistency {
+            Inconsistency::DanglingHeapId => Value::Ref(HeapId::stale()),
+            Inconsistency::MissingFunction => {
+                let count = program.interns.function_count();
+                Value::DefFunction(FunctionId::from_index(u16::try_from(count).unwrap_or(u16::MAX)))
+            }
+        };
+        vm_state.push_unchecked(value);
+        let bytes = self.dump();
+        // the pushed value doesn't exist, so the run can't be released
+        std::mem::forget(self);
+        bytes
     }
 }
 
+/// A value referring to something a run doesn't have; see `RunProgress::dump_inconsistent`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Inconsistency {
+    /// A heap id whose entry has been freed.
+    DanglingHeapId,
+    /// A function id past the program's functions.
+    MissingFunction,
+}
+
 impl<T: ResourceTracker + serde::de::DeserializeOwned> RunProgress<T> {
     /// Deserializes execution state from binary format.
     ///
+    /// The loaded state and its bytecode are checked before it is returned, so damaged bytes
+    /// that still decode are reported as `SnapshotError::Corrupt` instead of panicking on
+    /// resume. The bytecode is checked like `MontyRun::load_code()` does, which doesn't cover
+    /// how it uses the operand stack; hosts that store snapshots where users can modify them
+    /// should authenticate them, e.g. with an HMAC, before loading them.
+    ///
     /// # Errors
-    /// Returns an error if deserialization fails.
-    pub fn load(bytes: &[u8]) -> Result<Self, postcard::Error> {
-        postcard::from_bytes(bytes)
+    /// Returns `SnapshotError::VersionMismatch` if the state was dumped by another Monty
+    /// version, `SnapshotError::ChecksumMismatch` if it was corrupted, or another
+    /// `SnapshotError` if it is not `RunProgress::dump()` output.
+    pub fn load(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut progress: Self = snapshot_format::load(SnapshotKind::RunProgress, bytes)?;
+        let result = match &mut progress {
+            Self::FunctionCall { state, .. }
+            | Self::OsCall { state, .. }
+            | Self::Emit { state, .. }
+            | Self::Sleep { state, .. } => state.validate(),
+            Self::ResolveFutures(state) => state.validate(),
+            Self::Paused(state) => state.validate(),
+            Self::Breakpoint(state) => state.validate(),
+            Self::Complete(_) => Ok(()),
+        };
+        if let Err(err) = result {
+            // values in a corrupt state may refer to heap entries that don't exist, so under
+            // `ref-count-panic` they can't be released; otherwise dropping just frees the memory
+            #[cfg(feature = "ref-count-panic")]
+            std::mem::forget(progress);
+            return Err(err);
+        }
+        Ok(progress)
     }
 }
 
@@ -322,8 +1036,8 @@ impl<T: ResourceTracker + serde::de::DeserializeOwned> RunProgress<T> {
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::de::DeserializeOwned"))]
 pub struct Snapshot<T: ResourceTracker> {
-    /// The executor containing compiled code and interns.
-    executor: Executor,
+    /// The compiled program the run is executing.
+    program: Arc<CompiledProgram>,
     /// The VM state containing stack, frames, and exception state.
     vm_state: VMSnapshot,
     /// The heap containing all allocated objects.
@@ -333,6 +1047,9 @@ pub struct Snapshot<T: ResourceTracker> {
     /// The call_id from the most recent FunctionCall that created this Snapshot.
     /// Used by `run_pending()` to push the correct `ExternalFuture`.
     pending_call_id: u32,
+    /// The external function being called, whose return value is checked against its
+    /// declared return type; `None` for os calls, method calls and `emit()`.
+    pending_ext_function: Option<ExtFunctionId>,
 }
 
 #[derive(Debug)]
@@ -376,6 +1093,25 @@ impl<T: ResourceTracker> Snapshot<T> {
         self.heap.tracker_mut()
     }
 
+    /// Compacts the heap, dropping freed slots and renumbering live objects.
+    ///
+    /// Call before `dump()` so snapshots of long-running scripts don't carry every slot
+    /// the script ever used. Python-visible `id()` values of existing objects change.
+    pub fn compact_heap(&mut self) {
+        compact_heap(&mut self.heap, &mut self.vm_state, &mut self.namespaces);
+    }
+
+    /// Checks the state after deserialization; see `RunProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.program.module_code,
+            &self.program.interns,
+            &mut self.vm_state,
+            &mut self.heap,
+            &mut self.namespaces,
+        )
+    }
+
     /// Continues execution with the return value or exception from the external function.
     ///
     /// Consumes self and returns the next execution progress.
@@ -393,20 +1129,25 @@ impl<T: ResourceTracker> Snapshot<T> {
         print: &mut PrintWriter<'_>,
     ) -> Result<RunProgress<T>, MontyException> {
         let ext_result = result.into();
+        let host_iterable = self.is_host_iterable();
 
         // Restore the VM from the snapshot
         let mut vm = VM::restore(
             self.vm_state,
-            &self.executor.module_code,
+            &self.program.module_code,
             &mut self.heap,
             &mut self.namespaces,
-            &self.executor.interns,
+            &self.program.interns,
             print,
         );
 
         // Convert return value or exception before creating VM (to avoid borrow conflicts)
         let vm_result = match ext_result {
-            ExternalResult::Return(obj) => vm.resume(obj),
+            result if host_iterable => vm.resume_host_iterable(result),
+            ExternalResult::Return(obj) => match self.pending_ext_function {
+                Some(ext_function_id) => vm.resume_external(ext_function_id, obj),
+                None => vm.resume(obj),
+            },
             ExternalResult::Error(exc) => vm.resume_with_exception(exc.into()),
             ExternalResult::Future => {
                 // Get the call_id and ext_function_id that were stored when this Snapshot was created
@@ -428,7 +1169,7 @@ impl<T: ResourceTracker> Snapshot<T> {
         let vm_state = vm.check_snapshot(&vm_result);
 
         // Handle the result using the destructured parts
-        handle_vm_result(vm_result, vm_state, self.executor, self.heap, self.namespaces)
+        handle_vm_result(vm_result, vm_state, self.program, self.heap, self.namespaces)
     }
 
     /// Continues execution by pushing an ExternalFuture instead of a concrete value.
@@ -452,9 +1193,96 @@ impl<T: ResourceTracker> Snapshot<T> {
     pub fn run_pending(self, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
         self.run(MontyFuture, print)
     }
-}
 
-/// Execution state paused while waiting for external future results.
+    /// Returns whether the run is waiting on the host for the items of a host iterable
+    /// rather than for the result of a call; see [`CompileOptions::host_iterables`].
+    #[must_use]
+    pub fn is_host_iterable(&self) -> bool {
+        self.pending_ext_function
+            .is_some_and(|ext_function_id| self.program.interns.is_external_iterable(ext_function_id))
+    }
+
+    /// Continues a run waiting on the host for the items of a host iterable, handing over
+    /// `items` at once, with `exhausted` marking that there are no more.
+    ///
+    /// Handing over several items at a time saves a round trip per item. The loop waits on
+    /// the host again once it has used them all, unless `exhausted` is set.
+    ///
+    /// # Errors
+    /// Returns a `RuntimeError` if the run isn't waiting on a host iterable.
+    pub fn run_host_items(
+        mut self,
+        items: Vec<MontyObject>,
+        exhausted: bool,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<RunProgress<T>, MontyException> {
+        if !self.is_host_iterable() {
+            return Err(MontyException::new(
+                ExcType::RuntimeError,
+                Some("the run isn't waiting on a host iterable".to_owned()),
+            ));
+        }
+        let mut vm = VM::restore(
+            self.vm_state,
+            &self.program.module_code,
+            &mut self.heap,
+            &mut self.namespaces,
+            &self.program.interns,
+            print,
+        );
+        let vm_result = vm.resume_host_items(items, exhausted);
+        let vm_state = vm.check_snapshot(&vm_result);
+        handle_vm_result(vm_result, vm_state, self.program, self.heap, self.namespaces)
+    }
+}
+
+impl<T: ResourceTracker + serde::Serialize> Snapshot<T> {
+    /// Serializes the paused run on its own, for hosts that keep the snapshot apart from
+    /// the `RunProgress` it came in.
+    ///
+    /// The output has the same versioned header as `RunProgress::dump()`.
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails.
+    pub fn dump(&self) -> Result<Vec<u8>, postcard::Error> {
+        snapshot_format::dump(SnapshotKind::Snapshot, self)
+    }
+}
+
+impl<T: ResourceTracker + serde::de::DeserializeOwned> Snapshot<T> {
+    /// Restores a paused run from `Snapshot::dump()` output, checking it like
+    /// `RunProgress::load()` does.
+    ///
+    /// # Errors
+    /// Returns the same errors as `RunProgress::load()`.
+    pub fn load(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut snapshot: Self = snapshot_format::load(SnapshotKind::Snapshot, bytes)?;
+        if let Err(err) = snapshot.validate() {
+            // see `RunProgress::load`
+            #[cfg(feature = "ref-count-panic")]
+            std::mem::forget(snapshot);
+            return Err(err);
+        }
+        Ok(snapshot)
+    }
+}
+
+/// A call to a batched external function, handed to the host with the rest of its batch.
+///
+/// See [`CompileOptions::batch_external_calls`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BatchedCall {
+    /// Identifies the call when resuming with its result.
+    pub call_id: u32,
+    /// The name of the function being called.
+    pub function_name: String,
+    /// The positional arguments passed to the function.
+    pub args: Vec<MontyObject>,
+    /// The keyword arguments passed to the function (key, value pairs).
+    pub kwargs: Vec<(MontyObject, MontyObject)>,
+}
+
+/// Execution state paused while waiting for external future results.
 ///
 /// Unlike `Snapshot` (used for sync external calls), `FutureSnapshot` supports
 /// incremental resolution - you can provide partial results and Monty will
@@ -467,8 +1295,8 @@ impl<T: ResourceTracker> Snapshot<T> {
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::de::DeserializeOwned"))]
 pub struct FutureSnapshot<T: ResourceTracker> {
-    /// The executor containing compiled code and interns.
-    executor: Executor,
+    /// The compiled program the run is executing.
+    program: Arc<CompiledProgram>,
     /// The VM state containing stack, frames, and exception state.
     vm_state: VMSnapshot,
     /// The heap containing all allocated objects.
@@ -478,6 +1306,8 @@ pub struct FutureSnapshot<T: ResourceTracker> {
     /// The pending call_ids that this snapshot is waiting on.
     /// Used to validate that resume() only receives known call_ids.
     pending_call_ids: Vec<u32>,
+    /// Calls to batched external functions queued since the run last yielded.
+    batched_calls: Vec<BatchedCall>,
 }
 
 impl<T: ResourceTracker> FutureSnapshot<T> {
@@ -485,6 +1315,35 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
         &self.pending_call_ids
     }
 
+    /// Returns the calls to batched external functions the script made since the run last
+    /// yielded, in the order it made them; see [`CompileOptions::batch_external_calls`].
+    ///
+    /// Each batched call is listed once, on the first `ResolveFutures` after it was made, and
+    /// its `call_id` is also in [`pending_call_ids()`](Self::pending_call_ids) until the host
+    /// resolves it.
+    pub fn batched_calls(&self) -> &[BatchedCall] {
+        &self.batched_calls
+    }
+
+    /// Compacts the heap, dropping freed slots and renumbering live objects.
+    ///
+    /// Call before `dump()` so snapshots of long-running scripts don't carry every slot
+    /// the script ever used. Python-visible `id()` values of existing objects change.
+    pub fn compact_heap(&mut self) {
+        compact_heap(&mut self.heap, &mut self.vm_state, &mut self.namespaces);
+    }
+
+    /// Checks the state after deserialization; see `RunProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.program.module_code,
+            &self.program.interns,
+            &mut self.vm_state,
+            &mut self.heap,
+            &mut self.namespaces,
+        )
+    }
+
     /// Resumes execution with results for some or all pending futures.
     ///
     /// **Incremental resolution**: You don't need to provide all results at once.
@@ -521,11 +1380,12 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
 
         // Destructure self to avoid partial move issues
         let Self {
-            executor,
+            program,
             vm_state,
             mut heap,
             mut namespaces,
             pending_call_ids,
+            batched_calls: _,
         } = self;
 
         // Validate that all provided call_ids are in the pending set before restoring VM
@@ -537,10 +1397,10 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
         // Restore the VM from the snapshot (must happen before any error return to clean up properly)
         let mut vm = VM::restore(
             vm_state,
-            &executor.module_code,
+            &program.module_code,
             &mut heap,
             &mut namespaces,
-            &executor.interns,
+            &program.interns,
             print,
         );
 
@@ -573,7 +1433,7 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
             vm.cleanup();
             #[cfg(feature = "ref-count-panic")]
             namespaces.drop_global_with_heap(&mut heap);
-            return Err(error.into_python_exception(&executor.interns, &executor.code));
+            return Err(error.into_python_exception(&program.interns, &program.code));
         }
 
         // Push resolved value for main task if it was blocked.
@@ -588,7 +1448,7 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
                 vm.cleanup();
                 #[cfg(feature = "ref-count-panic")]
                 namespaces.drop_global_with_heap(&mut heap);
-                return Err(e.into_python_exception(&executor.interns, &executor.code));
+                return Err(e.into_python_exception(&program.interns, &program.code));
             }
         };
 
@@ -601,12 +1461,14 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
             if !pending_call_ids.is_empty() {
                 let vm_state = vm.snapshot();
                 let pending_call_ids: Vec<u32> = pending_call_ids.iter().map(|id| id.raw()).collect();
+                // nothing ran, so no calls were batched since the host last saw them
                 return Ok(RunProgress::ResolveFutures(Self {
-                    executor,
+                    program,
                     vm_state,
                     heap,
                     namespaces,
                     pending_call_ids,
+                    batched_calls: Vec::new(),
                 }));
             }
         }
@@ -617,7 +1479,322 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
         let vm_state = vm.check_snapshot(&result);
 
         // Handle the result using the destructured parts
-        handle_vm_result(result, vm_state, executor, heap, namespaces)
+        handle_vm_result(result, vm_state, program, heap, namespaces)
+    }
+}
+
+impl<T: ResourceTracker + serde::Serialize> FutureSnapshot<T> {
+    /// Serializes the blocked run on its own; see `Snapshot::dump()`.
+    ///
+    /// # Errors
+    /// Returns an error if serialization fails.
+    pub fn dump(&self) -> Result<Vec<u8>, postcard::Error> {
+        snapshot_format::dump(SnapshotKind::FutureSnapshot, self)
+    }
+}
+
+impl<T: ResourceTracker + serde::de::DeserializeOwned> FutureSnapshot<T> {
+    /// Restores a blocked run from `FutureSnapshot::dump()` output, checking it like
+    /// `RunProgress::load()` does.
+    ///
+    /// # Errors
+    /// Returns the same errors as `RunProgress::load()`.
+    pub fn load(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut snapshot: Self = snapshot_format::load(SnapshotKind::FutureSnapshot, bytes)?;
+        if let Err(err) = snapshot.validate() {
+            // see `RunProgress::load`
+            #[cfg(feature = "ref-count-panic")]
+            std::mem::forget(snapshot);
+            return Err(err);
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Execution state paused because a fuel-limited run used up its budget.
+///
+/// Unlike `Snapshot`, no value is pushed on resume - the VM continues at the
+/// exact instruction where it stopped.
+///
+/// # Type Parameters
+/// * `T` - Resource tracker implementation
+///
+/// Serialization requires `T: Serialize + Deserialize`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::de::DeserializeOwned"))]
+pub struct PausedSnapshot<T: ResourceTracker> {
+    /// The compiled program the run is executing.
+    program: Arc<CompiledProgram>,
+    /// The VM state containing stack, frames, and exception state.
+    vm_state: VMSnapshot,
+    /// The heap containing all allocated objects.
+    heap: Heap<T>,
+    /// The namespaces containing all variable bindings.
+    namespaces: Namespaces,
+}
+
+impl<T: ResourceTracker> PausedSnapshot<T> {
+    /// Returns a mutable reference to the resource tracker.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        self.heap.tracker_mut()
+    }
+
+    /// Compacts the heap, dropping freed slots and renumbering live objects.
+    ///
+    /// Call before `dump()` so snapshots of long-running scripts don't carry every slot
+    /// the script ever used. Python-visible `id()` values of existing objects change.
+    pub fn compact_heap(&mut self) {
+        compact_heap(&mut self.heap, &mut self.vm_state, &mut self.namespaces);
+    }
+
+    /// Checks the state after deserialization; see `RunProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.program.module_code,
+            &self.program.interns,
+            &mut self.vm_state,
+            &mut self.heap,
+            &mut self.namespaces,
+        )
+    }
+
+    /// Continues execution for at most `fuel` more opcodes.
+    ///
+    /// # Arguments
+    /// * `fuel` - Number of opcodes to execute before pausing again
+    /// * `print` - Writer for print output
+    pub fn run_fuel(self, fuel: u64, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        self.resume(Some(fuel), print)
+    }
+
+    /// Continues execution without a fuel limit.
+    ///
+    /// # Arguments
+    /// * `print` - Writer for print output
+    pub fn run(self, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        self.resume(None, print)
+    }
+
+    fn resume(mut self, fuel: Option<u64>, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        let mut vm = VM::restore(
+            self.vm_state,
+            &self.program.module_code,
+            &mut self.heap,
+            &mut self.namespaces,
+            &self.program.interns,
+            print,
+        );
+        vm.set_fuel(fuel);
+
+        let vm_result = vm.run();
+
+        let vm_state = vm.check_snapshot(&vm_result);
+
+        handle_vm_result(vm_result, vm_state, self.program, self.heap, self.namespaces)
+    }
+}
+
+/// Execution state stopped at a breakpoint, or at the next line while single-stepping.
+///
+/// The VM stopped before the first instruction of `line()`, so nothing on that line has
+/// run yet.
+///
+/// # Type Parameters
+/// * `T` - Resource tracker implementation
+///
+/// Serialization requires `T: Serialize + Deserialize`.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::de::DeserializeOwned"))]
+pub struct BreakpointSnapshot<T: ResourceTracker> {
+    /// The compiled program the run is executing.
+    program: Arc<CompiledProgram>,
+    /// The VM state containing stack, frames, and exception state.
+    vm_state: VMSnapshot,
+    /// The heap containing all allocated objects.
+    heap: Heap<T>,
+    /// The namespaces containing all variable bindings.
+    namespaces: Namespaces,
+}
+
+impl<T: ResourceTracker> BreakpointSnapshot<T> {
+    /// Returns the source line (1-based) execution stopped at.
+    #[must_use]
+    pub fn line(&self) -> u16 {
+        self.location().2
+    }
+
+    /// Returns the name of the function execution stopped in, or `"<module>"`.
+    #[must_use]
+    pub fn function_name(&self) -> &str {
+        let name_id = match self.location().0 {
+            Some(func_id) => self.program.interns.get_function(func_id).name.name_id,
+            None => StaticStrings::Module.into(),
+        };
+        self.program.interns.get_str(name_id)
+    }
+
+    /// Returns the bound local variables of the current frame, in slot order.
+    ///
+    /// At module level these are the script's globals. Variables that haven't been
+    /// assigned yet are left out.
+    #[must_use]
+    pub fn locals(&self) -> Vec<(String, MontyObject)> {
+        let (function_id, namespace_idx, _) = self.location();
+        let code = match function_id {
+            Some(func_id) => &self.program.interns.get_function(func_id).code,
+            None => &self.program.module_code,
+        };
+        let namespace = self.namespaces.get(namespace_idx);
+        let interns = &self.program.interns;
+        (0..namespace.len())
+            .filter_map(|slot| {
+                let name_id = code.local_name(u16::try_from(slot).ok()?)?;
+                let mut value = namespace.get(NamespaceId::new(slot));
+                // closure variables live in cells
+                if let Value::Ref(id) = value
+                    && let HeapData::Cell(inner) = self.heap.get(*id)
+                {
+                    value = inner;
+                }
+                if name_id == StringId::default() || matches!(value, Value::Undefined) {
+                    return None;
+                }
+                let name = interns.get_str(name_id).to_owned();
+                Some((name, MontyObject::from_value(value, &self.heap, interns)))
+            })
+            .collect()
+    }
+
+    /// Returns the lines execution stops at, in order.
+    #[must_use]
+    pub fn breakpoints(&self) -> Vec<u16> {
+        self.debugger().breakpoints().collect()
+    }
+
+    /// Replaces the lines execution stops at.
+    pub fn set_breakpoints(&mut self, breakpoints: impl IntoIterator<Item = u16>) {
+        self.vm_state
+            .debugger_mut()
+            .expect("breakpoint snapshot has a debugger")
+            .set_breakpoints(breakpoints);
+    }
+
+    /// Returns a mutable reference to the resource tracker.
+    pub fn tracker_mut(&mut self) -> &mut T {
+        self.heap.tracker_mut()
+    }
+
+    /// Compacts the heap, dropping freed slots and renumbering live objects.
+    ///
+    /// See `PausedSnapshot::compact_heap`.
+    pub fn compact_heap(&mut self) {
+        compact_heap(&mut self.heap, &mut self.vm_state, &mut self.namespaces);
+    }
+
+    /// Checks the state after deserialization; see `RunProgress::load`.
+    fn validate(&mut self) -> Result<(), SnapshotError> {
+        validate_state(
+            &self.program.module_code,
+            &self.program.interns,
+            &mut self.vm_state,
+            &mut self.heap,
+            &mut self.namespaces,
+        )?;
+        if self.vm_state.debugger().is_none()
+            || self
+                .vm_state
+                .current_location(&self.program.module_code, &self.program.interns)
+                .is_none()
+        {
+            return Err(SnapshotError::Corrupt(
+                "breakpoint state has no debugger or frame".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Continues execution until the next breakpoint, or to completion.
+    ///
+    /// # Arguments
+    /// * `print` - Writer for print output
+    pub fn resume(self, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        self.continue_run(false, print)
+    }
+
+    /// Executes until the next new source line, in this function or any other, and stops
+    /// there as if it had a breakpoint.
+    ///
+    /// # Arguments
+    /// * `print` - Writer for print output
+    pub fn step(self, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        self.continue_run(true, print)
+    }
+
+    fn continue_run(mut self, stepping: bool, print: &mut PrintWriter<'_>) -> Result<RunProgress<T>, MontyException> {
+        self.vm_state
+            .debugger_mut()
+            .expect("breakpoint snapshot has a debugger")
+            .set_stepping(stepping);
+        let mut vm = VM::restore(
+            self.vm_state,
+            &self.program.module_code,
+            &mut self.heap,
+            &mut self.namespaces,
+            &self.program.interns,
+            print,
+        );
+
+        let vm_result = vm.run();
+
+        let vm_state = vm.check_snapshot(&vm_result);
+
+        handle_vm_result(vm_result, vm_state, self.program, self.heap, self.namespaces)
+    }
+
+    fn debugger(&self) -> &Debugger {
+        self.vm_state.debugger().expect("breakpoint snapshot has a debugger")
+    }
+
+    /// Returns the function, namespace and line of the frame execution stopped in.
+    fn location(&self) -> (Option<FunctionId>, NamespaceId, u16) {
+        self.vm_state
+            .current_location(&self.program.module_code, &self.program.interns)
+            .expect("breakpoint snapshot has a current frame")
+    }
+}
+
+/// Compacts a suspended run's heap and rewrites every heap id held outside it.
+fn compact_heap<T: ResourceTracker>(heap: &mut Heap<T>, vm_state: &mut VMSnapshot, namespaces: &mut Namespaces) {
+    let map = heap.compact();
+    vm_state.remap_heap_ids(&map);
+    namespaces.remap_heap_ids(&map);
+}
+
+/// Checks a deserialized run's heap, namespaces and VM state against each other.
+pub(crate) fn validate_state<T: ResourceTracker>(
+    module_code: &Code,
+    interns: &Interns,
+    vm_state: &mut VMSnapshot,
+    heap: &mut Heap<T>,
+    namespaces: &mut Namespaces,
+) -> Result<(), SnapshotError> {
+    validate_program(Some(module_code), interns, global_namespace_len(namespaces)?).map_err(SnapshotError::Corrupt)?;
+    let checker = heap.validate(interns.bounds()).map_err(SnapshotError::Corrupt)?;
+    namespaces.remap_heap_ids(&checker);
+    if let Some(problem) = checker.problem() {
+        return Err(SnapshotError::Corrupt(format!("namespace refers to {problem}")));
+    }
+    vm_state
+        .validate(module_code, interns, namespaces.count(), &checker)
+        .map_err(SnapshotError::Corrupt)
+}
+
+/// Returns the size of a deserialized run's global namespace, which must exist.
+pub(crate) fn global_namespace_len(namespaces: &Namespaces) -> Result<usize, SnapshotError> {
+    if namespaces.count() > GLOBAL_NS_IDX.index() {
+        Ok(namespaces.get(GLOBAL_NS_IDX).len())
+    } else {
+        Err(SnapshotError::Corrupt("there is no global namespace".to_owned()))
     }
 }
 
@@ -628,18 +1805,19 @@ impl<T: ResourceTracker> FutureSnapshot<T> {
 fn handle_vm_result<T: ResourceTracker>(
     result: RunResult<FrameExit>,
     vm_state: Option<VMSnapshot>,
-    executor: Executor,
+    program: Arc<CompiledProgram>,
     mut heap: Heap<T>,
     mut namespaces: Namespaces,
 ) -> Result<RunProgress<T>, MontyException> {
     macro_rules! new_snapshot {
-        ($call_id: expr) => {
+        ($call_id: expr, $ext_function_id: expr) => {
             Snapshot {
-                executor,
+                program,
                 vm_state: vm_state.expect("snapshot should exist for ExternalCall"),
                 heap,
                 namespaces,
                 pending_call_id: $call_id.raw(),
+                pending_ext_function: $ext_function_id,
             }
         };
     }
@@ -649,7 +1827,7 @@ fn handle_vm_result<T: ResourceTracker>(
             #[cfg(feature = "ref-count-panic")]
             namespaces.drop_global_with_heap(&mut heap);
 
-            let obj = MontyObject::new(value, &mut heap, &executor.interns);
+            let obj = MontyObject::new(value, &mut heap, &program.interns);
             Ok(RunProgress::Complete(obj))
         }
         Ok(FrameExit::ExternalCall {
@@ -657,8 +1835,8 @@ fn handle_vm_result<T: ResourceTracker>(
             args,
             call_id,
         }) => {
-            let function_name = executor.interns.get_external_function_name(ext_function_id);
-            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &executor.interns);
+            let function_name = program.interns.get_external_function_name(ext_function_id);
+            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &program.interns);
 
             Ok(RunProgress::FunctionCall {
                 function_name,
@@ -666,7 +1844,7 @@ fn handle_vm_result<T: ResourceTracker>(
                 kwargs: kwargs_py,
                 call_id: call_id.raw(),
                 method_call: false,
-                state: new_snapshot!(call_id),
+                state: new_snapshot!(call_id, Some(ext_function_id)),
             })
         }
         Ok(FrameExit::OsCall {
@@ -674,14 +1852,14 @@ fn handle_vm_result<T: ResourceTracker>(
             args,
             call_id,
         }) => {
-            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &executor.interns);
+            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &program.interns);
 
             Ok(RunProgress::OsCall {
                 function,
                 args: args_py,
                 kwargs: kwargs_py,
                 call_id: call_id.raw(),
-                state: new_snapshot!(call_id),
+                state: new_snapshot!(call_id, None),
             })
         }
         Ok(FrameExit::MethodCall {
@@ -689,8 +1867,8 @@ fn handle_vm_result<T: ResourceTracker>(
             args,
             call_id,
         }) => {
-            let function_name = method_name.into_string(&executor.interns);
-            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &executor.interns);
+            let function_name = method_name.into_string(&program.interns);
+            let (args_py, kwargs_py) = args.into_py_objects(&mut heap, &program.interns);
 
             Ok(RunProgress::FunctionCall {
                 function_name,
@@ -698,105 +1876,423 @@ fn handle_vm_result<T: ResourceTracker>(
                 kwargs: kwargs_py,
                 call_id: call_id.raw(),
                 method_call: true,
-                state: new_snapshot!(call_id),
+                state: new_snapshot!(call_id, None),
             })
         }
         Ok(FrameExit::ResolveFutures(pending_call_ids)) => {
             let pending_call_ids: Vec<u32> = pending_call_ids.iter().map(|id| id.raw()).collect();
+            let mut vm_state = vm_state.expect("snapshot should exist for ResolveFutures");
+            let batched_calls = vm_state
+                .take_batched_calls()
+                .into_iter()
+                .map(|(call_id, ext_function_id, args)| {
+                    let (args, kwargs) = args.into_py_objects(&mut heap, &program.interns);
+                    BatchedCall {
+                        call_id: call_id.raw(),
+                        function_name: program.interns.get_external_function_name(ext_function_id),
+                        args,
+                        kwargs,
+                    }
+                })
+                .collect();
             Ok(RunProgress::ResolveFutures(FutureSnapshot {
-                executor,
-                vm_state: vm_state.expect("snapshot should exist for ResolveFutures"),
+                program,
+                vm_state,
                 heap,
                 namespaces,
                 pending_call_ids,
+                batched_calls,
             }))
         }
+        Ok(FrameExit::Emit { value, call_id }) => {
+            let value = MontyObject::new(value, &mut heap, &program.interns);
+            Ok(RunProgress::Emit {
+                value,
+                state: new_snapshot!(call_id, None),
+            })
+        }
+        Ok(FrameExit::Sleep { duration, call_id }) => Ok(RunProgress::Sleep {
+            duration,
+            state: new_snapshot!(call_id, None),
+        }),
+        Ok(FrameExit::Paused) => Ok(RunProgress::Paused(PausedSnapshot {
+            program,
+            vm_state: vm_state.expect("snapshot should exist for Paused"),
+            heap,
+            namespaces,
+        })),
+        Ok(FrameExit::Breakpoint) => Ok(RunProgress::Breakpoint(BreakpointSnapshot {
+            program,
+            vm_state: vm_state.expect("snapshot should exist for Breakpoint"),
+            heap,
+            namespaces,
+        })),
         Err(err) => {
             #[cfg(feature = "ref-count-panic")]
             namespaces.drop_global_with_heap(&mut heap);
 
-            Err(err.into_python_exception(&executor.interns, &executor.code))
+            Err(err.into_python_exception(&program.interns, &program.code))
+        }
+    }
+}
+
+/// Options controlling how [`MontyRun::new_with_options`] and
+/// [`MontyRepl::new_with_options`](crate::MontyRepl::new_with_options) compile code.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CompileOptions {
+    /// Whether to fold constant expressions and drop statically dead branches
+    /// (`60 * 60 * 24`, constant f-string pieces, `if False:` blocks) before emitting bytecode,
+    /// and to run the peephole optimizer over the emitted bytecode.
+    pub optimize: bool,
+    /// The only builtins the code may use, or `None` to allow every builtin.
+    pub allowed_builtins: Option<BTreeSet<String>>,
+    /// Builtins the code may not use, even if `allowed_builtins` lists them.
+    pub denied_builtins: BTreeSet<String>,
+    /// Annotated declarations of the external functions, checked at every external call.
+    pub external_stubs: Option<String>,
+    /// External functions whose calls are batched rather than yielded one at a time.
+    pub batched_external_functions: BTreeSet<String>,
+    /// External functions that are host iterables, which scripts loop over rather than call.
+    pub host_iterables: BTreeSet<String>,
+    /// Table of constants the compiled program shares with other programs compiled with it.
+    ///
+    /// Not serialized: the table only exists in the process that made it.
+    #[serde(skip)]
+    pub shared_interns: Option<SharedInterns>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            optimize: true,
+            allowed_builtins: None,
+            denied_builtins: BTreeSet::new(),
+            external_stubs: None,
+            batched_external_functions: BTreeSet::new(),
+            host_iterables: BTreeSet::new(),
+            shared_interns: None,
+        }
+    }
+}
+
+impl CompileOptions {
+    /// Creates the default options, with optimizations enabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether constant folding, dead-code elimination and peephole optimization run.
+    #[must_use]
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    /// Restricts the code to the given builtins, e.g. `["len", "range"]`.
+    ///
+    /// This covers every builtin name, including types like `int` and exception classes
+    /// like `ValueError`. A disallowed builtin is treated as undefined: referencing it
+    /// raises `NameError` when the code is compiled, unless the script defines that name
+    /// itself. Calling this again replaces the previous list. Compiling fails with
+    /// `ValueError` if a name isn't a builtin, so a typo can't leave a builtin allowed.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{CompileOptions, ExcType, MontyRun};
+    ///
+    /// let options = CompileOptions::new().allow_builtins(["len", "range"]);
+    /// let code = "len(range(3)) + sum([1])";
+    /// let err = MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec![], options).unwrap_err();
+    /// assert_eq!(err.exc_type(), ExcType::NameError);
+    /// assert_eq!(err.message(), Some("name 'sum' is not defined"));
+    /// ```
+    #[must_use]
+    pub fn allow_builtins(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allowed_builtins = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Forbids the code from using the given builtins, e.g. `["print"]`.
+    ///
+    /// Denied builtins are treated like those missing from [`allow_builtins`](Self::allow_builtins).
+    /// Calling this again adds to the previous list. Compiling fails with `ValueError` if a
+    /// name isn't a builtin, so a typo can't leave a builtin allowed.
+    #[must_use]
+    pub fn deny_builtins(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.denied_builtins.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Checks the values passed to and returned from external functions against the
+    /// annotations `stubs` declares for them.
+    ///
+    /// `stubs` is Python source with an annotated `def` for each external function to check;
+    /// bodies are ignored, so `...` is enough. A call whose arguments don't match raises
+    /// `TypeError` in the script before the host sees it, and so does resuming with a
+    /// return value that doesn't match. Only the outer type is checked (`list[int]` accepts
+    /// any list), annotations Monty can't resolve accept anything, and results of calls
+    /// resolved as futures aren't checked.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{CompileOptions, ExcType, MontyObject, MontyRun};
+    ///
+    /// let options = CompileOptions::new().check_external_calls("def fetch(url: str) -> dict: ...");
+    /// let runner =
+    ///     MontyRun::new_with_options("fetch(42)".to_owned(), "test.py", vec![], vec!["fetch".to_owned()], options)
+    ///         .unwrap();
+    /// let err = runner.run_no_limits(vec![]).unwrap_err();
+    /// assert_eq!(err.exc_type(), ExcType::TypeError);
+    /// assert_eq!(err.message(), Some("fetch() argument 'url' must be str, not int"));
+    /// ```
+    #[must_use]
+    pub fn check_external_calls(mut self, stubs: impl Into<String>) -> Self {
+        self.external_stubs = Some(stubs.into());
+        self
+    }
+
+    /// Batches calls to the given external functions, e.g. `["fetch"]`.
+    ///
+    /// Instead of pausing at each call, a call to a batched function immediately returns a
+    /// future, so the script must `await` its result, and the run carries on. Once every
+    /// task is waiting, the run yields [`RunProgress::ResolveFutures`] and
+    /// [`FutureSnapshot::batched_calls()`] lists every call queued since the run last
+    /// yielded, so the host can make them in one round trip and resume with all the results.
+    /// A batched call whose future is never awaited is never handed to the host. Names that
+    /// aren't external functions are ignored, and calling this again adds to the previous list.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{CompileOptions, ExternalResult, MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+    ///
+    /// let code = "import asyncio\nawait asyncio.gather(*[fetch(n) for n in range(3)])";
+    /// let options = CompileOptions::new().batch_external_calls(["fetch"]);
+    /// let runner =
+    ///     MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec!["fetch".to_owned()], options).unwrap();
+    /// let progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Disabled).unwrap();
+    ///
+    /// let state = progress.into_resolve_futures().unwrap();
+    /// let results = state
+    ///     .batched_calls()
+    ///     .iter()
+    ///     .map(|call| (call.call_id, ExternalResult::Return(call.args[0].clone())))
+    ///     .collect();
+    /// let result = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    /// let expected = MontyObject::List(vec![MontyObject::Int(0), MontyObject::Int(1), MontyObject::Int(2)]);
+    /// assert_eq!(result.into_complete(), Some(expected));
+    /// ```
+    #[must_use]
+    pub fn batch_external_calls(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.batched_external_functions
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Makes the given external functions host iterables, e.g. `["rows"]`, which scripts loop
+    /// over with `for row in rows:` (or a comprehension) instead of calling.
+    ///
+    /// The script only ever holds the items the host has handed over and not yet consumed, so
+    /// it can stream over datasets far larger than its heap. Each time the loop runs out of
+    /// items, the run yields [`RunProgress::FunctionCall`] for the iterable with no arguments.
+    /// The host resumes with [`Snapshot::run_host_items()`] to hand over the next items, or
+    /// with [`Snapshot::run()`] and either the next item or a `StopIteration` error once there
+    /// are no more. [`HostIterable`](crate::HostIterable) does this for a Rust iterator.
+    ///
+    /// Only `for` loops and comprehensions can wait on the host, so other ways of consuming a
+    /// host iterable, like `list(rows)` or `next(iter(rows))`, only see the items already
+    /// handed over and raise `TypeError` when they run out. Calling a host iterable raises
+    /// `TypeError`. Names that aren't external functions are ignored, and calling this again
+    /// adds to the previous list.
+    ///
+    /// # Example
+    /// ```
+    /// use monty::{CompileOptions, HostIterable, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+    ///
+    /// let code = "total = 0\nfor n in numbers:\n    total += n\ntotal";
+    /// let options = CompileOptions::new().host_iterables(["numbers"]);
+    /// let runner =
+    ///     MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec!["numbers".to_owned()], options)
+    ///         .unwrap();
+    /// let mut numbers = HostIterable::new((1..=1000).map(MontyObject::Int)).chunk_size(100);
+    ///
+    /// let mut progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Disabled).unwrap();
+    /// while let RunProgress::FunctionCall { state, .. } = progress {
+    ///     progress = numbers.resume(state, &mut PrintWriter::Disabled).unwrap();
+    /// }
+    /// assert_eq!(progress.into_complete(), Some(MontyObject::Int(500_500)));
+    /// ```
+    #[must_use]
+    pub fn host_iterables(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.host_iterables.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Shares the compiled program's constants (names, string and bytes literals and long
+    /// integer literals) with every other program compiled or loaded with `shared`, so
+    /// programs keep one copy of each constant they have in common.
+    ///
+    /// See [`SharedInterns`] for an example.
+    #[must_use]
+    pub fn share_interns(mut self, shared: SharedInterns) -> Self {
+        self.shared_interns = Some(shared);
+        self
+    }
+
+    /// Checks that every name in `allowed_builtins` and `denied_builtins` is a builtin.
+    pub(crate) fn check_builtin_names(&self) -> Result<(), MontyException> {
+        let mut names = self.allowed_builtins.iter().flatten().chain(&self.denied_builtins);
+        match names.find(|name| name.parse::<Builtins>().is_err()) {
+            Some(name) => Err(MontyException::new(
+                ExcType::ValueError,
+                Some(format!("'{name}' is not a builtin")),
+            )),
+            None => Ok(()),
         }
     }
+
+    /// Returns whether the code may use the builtin called `name`.
+    pub(crate) fn allows_builtin(&self, name: &str) -> bool {
+        self.allowed_builtins
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(name))
+            && !self.denied_builtins.contains(name)
+    }
 }
 
-/// Lower level interface to parse code and run it to completion.
+/// The immutable result of compiling a script: bytecode, interns, constants and source code.
 ///
-/// This is an internal type used by [`MontyRun`]. It stores the compiled bytecode and source code
-/// for error reporting.
+/// A [`MontyRun`] and every run started from it hold the program behind an `Arc`, so it's
+/// compiled once and never copied. Each run keeps its own heap, namespaces and VM state, so
+/// any number of runs can execute one program concurrently on different threads.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
-struct Executor {
+pub struct CompiledProgram {
     /// Number of slots needed in the global namespace.
     namespace_size: usize,
-    /// Maps variable names to their indices in the namespace. Used for ref-count testing.
-    #[cfg(feature = "ref-count-return")]
-    name_map: ahash::AHashMap<String, crate::namespace::NamespaceId>,
+    /// Maps variable names to their indices in the namespace. Used to report final globals
+    /// and for ref-count testing.
+    #[serde(serialize_with = "crate::snapshot_format::serialize_sorted_map")]
+    name_map: ahash::AHashMap<String, NamespaceId>,
     /// Compiled bytecode for the module.
     module_code: Code,
     /// Interned strings used for looking up names and filenames during execution.
     interns: Interns,
     /// IDs to create values to inject into the the namespace to represent external functions.
     external_function_ids: Vec<ExtFunctionId>,
+    /// External functions referenced by the code, found after name resolution.
+    external_calls: Vec<ExternalFunctionUsage>,
     /// Source code for error reporting (extracting preview lines for tracebacks).
     code: String,
+    /// Compile-time warnings, reported to the warning sink at the start of each run.
+    warnings: Vec<MontyWarning>,
     /// Estimated heap capacity for pre-allocation on subsequent runs.
-    /// Uses AtomicUsize for thread-safety (required by PyO3's Sync bound).
+    /// Uses AtomicUsize so concurrent runs of the program can share it.
     heap_capacity: AtomicUsize,
 }
 
-impl Clone for Executor {
-    fn clone(&self) -> Self {
-        Self {
-            namespace_size: self.namespace_size,
-            #[cfg(feature = "ref-count-return")]
-            name_map: self.name_map.clone(),
-            module_code: self.module_code.clone(),
-            interns: self.interns.clone(),
-            external_function_ids: self.external_function_ids.clone(),
-            code: self.code.clone(),
-            heap_capacity: AtomicUsize::new(self.heap_capacity.load(Ordering::Relaxed)),
-        }
+impl CompiledProgram {
+    /// Checks the bytecode of a deserialized program; see `MontyRun::load_code`.
+    fn validate(&self) -> Result<(), String> {
+        validate_program(Some(&self.module_code), &self.interns, self.namespace_size)
     }
-}
 
-impl Executor {
-    /// Creates a new executor with the given code, filename, input names, and external functions.
+    /// Compiles a program from the given code, filename, input names, and external functions.
+    ///
+    /// `check` runs on the prepared nodes and can reject the code before it is compiled.
     fn new(
         code: String,
         script_name: &str,
         input_names: Vec<String>,
         external_functions: Vec<String>,
+        options: CompileOptions,
+        check: impl FnOnce(&[PreparedNode]) -> Result<(), MontyException>,
     ) -> Result<Self, MontyException> {
-        let parse_result = parse(&code, script_name).map_err(|e| e.into_python_exc(script_name, &code))?;
-        let prepared = prepare(parse_result, input_names, &external_functions)
+        options.check_builtin_names()?;
+        let mut parse_result = parse(&code, script_name).map_err(|e| e.into_python_exc(script_name, &code))?;
+        let mut warnings: Vec<MontyWarning> = std::mem::take(&mut parse_result.warnings)
+            .into_iter()
+            .map(|warning| MontyWarning {
+                category: warning.category,
+                message: warning.message,
+                filename: script_name.to_owned(),
+                start: warning.position.start(),
+            })
+            .collect();
+        warnings.sort_by_key(|warning| (warning.start.line, warning.start.column));
+        let mut prepared = prepare(parse_result, input_names, &external_functions, &options)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
+        check(&prepared.nodes)?;
+        // collected before folding so calls in dead branches are still reported
+        let external_calls = collect_external_calls(&prepared.nodes, &external_functions);
+        if options.optimize {
+            prepared.nodes = fold_constants(std::mem::take(&mut prepared.nodes), &mut prepared.interner);
+        }
 
         // Incrementing order matches the indexes used in intern::Interns::get_external_function_name
         let external_function_ids = (0..external_functions.len()).map(ExtFunctionId::new).collect();
 
+        let external_signatures = match &options.external_stubs {
+            Some(stubs) => external_signatures(stubs, &external_functions)?,
+            None => Vec::new(),
+        };
+
+        let external_batched = if options.batched_external_functions.is_empty() {
+            Vec::new()
+        } else {
+            external_functions
+                .iter()
+                .map(|name| options.batched_external_functions.contains(name))
+                .collect()
+        };
+
+        let external_iterables = if options.host_iterables.is_empty() {
+            Vec::new()
+        } else {
+            external_functions
+                .iter()
+                .map(|name| options.host_iterables.contains(name))
+                .collect()
+        };
+
         // Create interns with empty functions (functions will be set after compilation)
         let mut interns = Interns::new(prepared.interner, Vec::new(), external_functions);
+        interns.set_external_signatures(external_signatures);
+        interns.set_external_batched(external_batched);
+        interns.set_external_iterables(external_iterables);
 
         // Compile the module to bytecode, which also compiles all nested functions
         let namespace_size_u16 = u16::try_from(prepared.namespace_size).expect("module namespace size exceeds u16");
-        let compile_result = Compiler::compile_module(&prepared.nodes, &interns, namespace_size_u16)
+        let compile_result = Compiler::compile_module(&prepared.nodes, &interns, namespace_size_u16, options.optimize)
             .map_err(|e| e.into_python_exc(script_name, &code))?;
 
         // Set the compiled functions in the interns
         interns.set_functions(compile_result.functions);
+        if let Some(shared) = &options.shared_interns {
+            interns.share(shared);
+        }
 
         Ok(Self {
             namespace_size: prepared.namespace_size,
-            #[cfg(feature = "ref-count-return")]
             name_map: prepared.name_map,
             module_code: compile_result.code,
             interns,
             external_function_ids,
+            external_calls,
             code,
+            warnings,
             heap_capacity: AtomicUsize::new(prepared.namespace_size),
         })
     }
 
+    /// Reports the compile-time warnings to the tracker's warning sink, if it has one.
+    fn report_warnings(&self, heap: &mut Heap<impl ResourceTracker>) {
+        if let Some(sink) = heap.tracker_mut().warning_sink() {
+            for warning in &self.warnings {
+                sink.warn(warning.clone());
+            }
+        }
+    }
+
     /// Executes the code with a custom resource tracker.
     ///
     /// This provides full control over resource tracking and garbage collection
@@ -805,35 +2301,172 @@ impl Executor {
     ///
     /// # Arguments
     /// * `inputs` - Values to fill the first N slots of the namespace
+    /// * `initial_globals` - Values to store in other slots before the code runs
     /// * `resource_tracker` - Custom resource tracker implementation
+    /// * `profiler` - Profiler to count and time opcodes with, if profiling
+    /// * `coverage` - Recorder to mark executed lines with, if measuring coverage
+    /// * `globals` - Map to fill with the final module-level variables, if capturing them
     /// * `print` - Print output writer (mutably borrowed so `Collect` data is preserved)
+    #[expect(clippy::too_many_arguments)]
     fn run(
         &self,
         inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
         resource_tracker: impl ResourceTracker,
+        profiler: Option<&mut Profiler>,
+        coverage: Option<&mut LineCoverage>,
+        globals: Option<&mut HashMap<String, MontyObject>>,
         print: &mut PrintWriter<'_>,
     ) -> Result<MontyObject, MontyException> {
-        let heap_capacity = self.heap_capacity.load(Ordering::Relaxed);
-        let mut heap = Heap::new(heap_capacity, resource_tracker);
-        let mut namespaces = self.prepare_namespaces(inputs, &mut heap)?;
+        let mut heap = self.new_heap(resource_tracker);
+        let mut namespaces = self.new_namespaces();
+        let result = self.run_with(
+            &mut heap,
+            &mut namespaces,
+            inputs,
+            initial_globals,
+            profiler,
+            coverage,
+            globals,
+            print,
+        );
+
+        // Clean up the global namespace before returning (only needed with ref-count-panic)
+        #[cfg(feature = "ref-count-panic")]
+        namespaces.drop_global_with_heap(&mut heap);
+
+        result
+    }
+
+    /// Calls the module-level function `function_name` with positional `args`, using the
+    /// heap and namespaces left behind by an earlier `run_with()`.
+    ///
+    /// Globals the function assigns stay in `namespaces` for later calls.
+    pub(crate) fn call_with<T: ResourceTracker>(
+        &self,
+        heap: &mut Heap<T>,
+        namespaces: &mut Namespaces,
+        function_name: &str,
+        args: Vec<MontyObject>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<MontyObject, MontyException> {
+        let callable = match self.name_map.get(function_name) {
+            Some(&id) => namespaces.get(GLOBAL_NS_IDX).get_opt(id),
+            None => None,
+        };
+        let callable = match callable {
+            Some(Value::Undefined) | None => {
+                let message = ErrorCode::NameNotDefined.format(&[&function_name]);
+                return Err(MontyException::new(ExcType::NameError, Some(message)));
+            }
+            Some(value) => value.clone_with_heap(heap),
+        };
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg.to_value(heap, &self.interns) {
+                Ok(value) => arg_values.push(value),
+                Err(err) => {
+                    callable.drop_with_heap(heap);
+                    arg_values.drop_with_heap(heap);
+                    return Err(MontyException::runtime_error(format!("invalid input type: {err}")));
+                }
+            }
+        }
+
+        let mut vm = VM::new(heap, namespaces, &self.interns, print);
+        let frame_exit_result = vm.run_function(&self.module_code, callable, arg_values);
+        vm.cleanup();
+
+        frame_exit_to_object(frame_exit_result, heap, &self.interns)
+            .map_err(|e| e.into_python_exception(&self.interns, &self.code))
+    }
+
+    /// Creates an empty heap for a run, sized from the heap usage of earlier runs.
+    pub(crate) fn new_heap<T: ResourceTracker>(&self, resource_tracker: T) -> Heap<T> {
+        Heap::new(self.heap_capacity.load(Ordering::Relaxed), resource_tracker)
+    }
+
+    /// Creates namespaces with an empty global namespace, filled by `run_with()`.
+    pub(crate) fn new_namespaces(&self) -> Namespaces {
+        Namespaces::new(Vec::with_capacity(self.namespace_size))
+    }
+
+    /// Executes the code like `run()`, using the given empty heap and namespaces.
+    ///
+    /// Values the run leaves in `namespaces` are not dropped, so the caller can read globals
+    /// or clear them to reuse the heap and namespaces for another run.
+    #[expect(clippy::too_many_arguments)]
+    pub(crate) fn run_with<T: ResourceTracker>(
+        &self,
+        heap: &mut Heap<T>,
+        namespaces: &mut Namespaces,
+        inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
+        profiler: Option<&mut Profiler>,
+        coverage: Option<&mut LineCoverage>,
+        globals: Option<&mut HashMap<String, MontyObject>>,
+        print: &mut PrintWriter<'_>,
+    ) -> Result<MontyObject, MontyException> {
+        self.report_warnings(heap);
+        self.fill_global_namespace(
+            namespaces.get_mut(GLOBAL_NS_IDX).mut_vec(),
+            inputs,
+            initial_globals,
+            heap,
+        )?;
 
         // Create and run VM
-        let mut vm = VM::new(&mut heap, &mut namespaces, &self.interns, print);
+        let mut vm = VM::new(heap, namespaces, &self.interns, print);
+        if let Some(profiler) = profiler {
+            vm.set_profiler(profiler);
+        }
+        if let Some(coverage) = coverage {
+            vm.set_coverage(coverage);
+        }
         let frame_exit_result = vm.run_module(&self.module_code);
 
         // Clean up VM state before it goes out of scope
         vm.cleanup();
 
-        if heap.size() > heap_capacity {
+        if heap.size() > self.heap_capacity.load(Ordering::Relaxed) {
             self.heap_capacity.store(heap.size(), Ordering::Relaxed);
         }
 
-        // Clean up the global namespace before returning (only needed with ref-count-panic)
+        if let Some(globals) = globals {
+            *globals = collect_globals(&self.name_map, namespaces, heap, &self.interns);
+        }
+
+        frame_exit_to_object(frame_exit_result, heap, &self.interns)
+            .map_err(|e| e.into_python_exception(&self.interns, &self.code))
+    }
+
+    /// Executes the code like `run()`, auditing the heap once the result has been converted.
+    #[cfg(feature = "heap-audit")]
+    fn run_audited(
+        &self,
+        inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
+        resource_tracker: impl ResourceTracker,
+        print: &mut PrintWriter<'_>,
+    ) -> (Result<MontyObject, MontyException>, HeapAudit) {
+        let mut heap = self.new_heap(resource_tracker);
+        let mut namespaces = self.new_namespaces();
+        let result = self.run_with(
+            &mut heap,
+            &mut namespaces,
+            inputs,
+            initial_globals,
+            None,
+            None,
+            None,
+            print,
+        );
+        let audit = heap.audit(namespaces.iter_heap_ids());
+
         #[cfg(feature = "ref-count-panic")]
         namespaces.drop_global_with_heap(&mut heap);
 
-        frame_exit_to_object(frame_exit_result, &mut heap, &self.interns)
-            .map_err(|e| e.into_python_exception(&self.interns, &self.code))
+        (result, audit)
     }
 
     /// Executes the code and returns both the result and reference count data, used for testing only.
@@ -850,11 +2483,15 @@ impl Executor {
     ///
     /// Only available when the `ref-count-return` feature is enabled.
     #[cfg(feature = "ref-count-return")]
-    fn run_ref_counts(&self, inputs: Vec<MontyObject>) -> Result<RefCountOutput, MontyException> {
+    fn run_ref_counts(
+        &self,
+        inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
+    ) -> Result<RefCountOutput, MontyException> {
         use std::collections::HashSet;
 
         let mut heap = Heap::new(self.namespace_size, NoLimitTracker);
-        let mut namespaces = self.prepare_namespaces(inputs, &mut heap)?;
+        let mut namespaces = self.prepare_namespaces(inputs, initial_globals, &mut heap)?;
 
         // Create and run VM with Stdout for output
         let mut print = PrintWriter::Stdout;
@@ -902,8 +2539,23 @@ impl Executor {
     fn prepare_namespaces(
         &self,
         inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
         heap: &mut Heap<impl ResourceTracker>,
     ) -> Result<Namespaces, MontyException> {
+        let mut namespace: Vec<Value> = Vec::with_capacity(self.namespace_size);
+        self.fill_global_namespace(&mut namespace, inputs, initial_globals, heap)?;
+        Ok(Namespaces::new(namespace))
+    }
+
+    /// Fills the empty global namespace with the external functions, then the inputs,
+    /// then the initial globals, leaving the remaining slots undefined.
+    fn fill_global_namespace(
+        &self,
+        namespace: &mut Vec<Value>,
+        inputs: Vec<MontyObject>,
+        initial_globals: &[(NamespaceId, MontyObject)],
+        heap: &mut Heap<impl ResourceTracker>,
+    ) -> Result<(), MontyException> {
         let Some(extra) = self
             .namespace_size
             .checked_sub(self.external_function_ids.len() + inputs.len())
@@ -911,7 +2563,6 @@ impl Executor {
             return Err(MontyException::runtime_error("too many inputs for namespace"));
         };
         // register external functions in the namespace first, matching the logic in prepare
-        let mut namespace: Vec<Value> = Vec::with_capacity(self.namespace_size);
         for f_id in &self.external_function_ids {
             namespace.push(Value::ExtFunction(*f_id));
         }
@@ -926,10 +2577,39 @@ impl Executor {
         if extra > 0 {
             namespace.extend((0..extra).map(|_| Value::Undefined));
         }
-        Ok(Namespaces::new(namespace))
+        // inputs and external functions take precedence over globals with the same name
+        for (id, object) in initial_globals {
+            let slot = &mut namespace[id.index()];
+            if matches!(slot, Value::Undefined) {
+                *slot = object
+                    .clone()
+                    .to_value(heap, &self.interns)
+                    .map_err(|e| MontyException::runtime_error(format!("invalid global type: {e}")))?;
+            }
+        }
+        Ok(())
     }
 }
 
+/// Converts every bound module-level variable to a `MontyObject`, keyed by name.
+///
+/// Unassigned slots and external functions are left out.
+pub(crate) fn collect_globals(
+    name_map: &ahash::AHashMap<String, NamespaceId>,
+    namespaces: &Namespaces,
+    heap: &Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> HashMap<String, MontyObject> {
+    let global = namespaces.get(GLOBAL_NS_IDX);
+    name_map
+        .iter()
+        .filter_map(|(name, &namespace_id)| match global.get_opt(namespace_id)? {
+            Value::Undefined | Value::ExtFunction(_) => None,
+            value => Some((name.clone(), MontyObject::from_value(value, heap, interns))),
+        })
+        .collect()
+}
+
 fn frame_exit_to_object(
     frame_exit_result: RunResult<FrameExit>,
     heap: &mut Heap<impl ResourceTracker>,
@@ -965,6 +2645,15 @@ fn frame_exit_to_object(
         FrameExit::ResolveFutures(_) => {
             Err(ExcType::not_implemented("async futures not supported by standard execution.").into())
         }
+        FrameExit::Emit { value, .. } => {
+            value.drop_with_heap(heap);
+            Err(ExcType::not_implemented("emit() not implemented with standard execution").into())
+        }
+        FrameExit::Sleep { .. } => {
+            Err(ExcType::not_implemented("time.sleep() not implemented with standard execution").into())
+        }
+        FrameExit::Paused => unreachable!("standard execution never sets a fuel limit"),
+        FrameExit::Breakpoint => unreachable!("standard execution never sets breakpoints"),
     }
 }
 
@@ -984,3 +2673,67 @@ pub struct RefCountOutput {
     /// allocations. Compare this against expected allocation count to verify GC ran.
     pub allocations_since_gc: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a run paused partway through a loop.
+    fn paused_run() -> RunProgress<NoLimitTracker> {
+        let code = "total = 0\nfor i in range(100):\n    total += i\ntotal";
+        let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+        let progress = runner
+            .start_fuel(vec![], NoLimitTracker, 20, &mut PrintWriter::Disabled)
+            .unwrap();
+        assert!(matches!(progress, RunProgress::Paused(_)));
+        progress
+    }
+
+    #[test]
+    fn load_accepts_consistent_snapshot() {
+        let progress = paused_run();
+        let bytes = progress.dump().unwrap();
+        let loaded = RunProgress::<NoLimitTracker>::load(&bytes).unwrap();
+        // paused runs hold values that can't be dropped without their heap under `ref-count-panic`
+        std::mem::forget((progress, loaded));
+    }
+
+    #[test]
+    fn compiled_programs_pass_bytecode_validation() {
+        let code = r"
+def outer(a, b=2, *args, **kwargs):
+    total = a + b
+    def inner(c):
+        return total + c
+    try:
+        return inner(len(args) + len(kwargs))
+    except ValueError as e:
+        del e
+        raise
+    finally:
+        total += 1
+
+async def main():
+    return [outer(i, k=i) for i in range(3) if i % 2 == 0]
+
+outer(1, 2, 3, x=4) if main else None
+";
+        let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+        runner.program.validate().unwrap();
+    }
+
+    #[test]
+    fn load_rejects_decodable_inconsistent_snapshots() {
+        for (inconsistency, expected) in [
+            (Inconsistency::DanglingHeapId, "dangling heap id"),
+            (Inconsistency::MissingFunction, "function 0, which does not exist"),
+        ] {
+            let bytes = paused_run().dump_inconsistent(inconsistency).unwrap();
+            match RunProgress::<NoLimitTracker>::load(&bytes) {
+                Err(SnapshotError::Corrupt(message)) => assert!(message.contains(expected), "{message}"),
+                Err(err) => panic!("{inconsistency:?}: expected a corrupt snapshot, got {err}"),
+                Ok(_) => panic!("{inconsistency:?}: inconsistent snapshot was loaded"),
+            }
+        }
+    }
+}
@@ -0,0 +1,614 @@
+//! Adversarial programs for checking that a sandbox configuration holds.
+//!
+//! Monty's guarantees depend on how it is embedded: which [`ResourceLimits`] are set,
+//! which cargo features are enabled, and whether snapshots are stored somewhere a user
+//! can tamper with them. This module bundles known attacks so embedders can run them
+//! against their own configuration, e.g. as a test in their crate:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use monty::{ResourceLimits, sectest};
+//!
+//! let limits = ResourceLimits::new()
+//!     .max_memory(64 * 1024 * 1024)
+//!     .max_duration(Duration::from_millis(500))
+//!     .max_str_len(1_000_000)
+//!     .max_list_len(1_000_000)
+//!     .max_dict_entries(1_000_000);
+//! let report = sectest::run_suite(&limits);
+//! assert!(report.passed(), "{report}");
+//! ```
+//!
+//! A case the configured limits can't stop is reported as [`Outcome::Unprotected`]
+//! without being run, since running it would exhaust the host. Panics are caught and
+//! reported as escapes, but a native stack overflow or a real out-of-memory abort
+//! takes the whole process down; run the suite in a subprocess if that matters.
+//! Cases bounded only by `max_duration` take that long to run.
+
+use std::{
+    any::Any,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    ExcType, LimitedTracker, MontyException, MontyObject, MontyRun, PrintWriter, ResourceLimits, RunProgress,
+    run::Inconsistency,
+};
+
+/// The kind of attack a [`SecurityCase`] makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Programs that try to use unbounded memory or time.
+    ResourceBomb,
+    /// Deeply nested source or data that could overflow the native stack.
+    DeepNesting,
+    /// Truncated or foreign bytes passed to snapshot loading.
+    SnapshotCorruption,
+    /// Reference cycles and mutation that stress reference counting.
+    RefcountAbuse,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ResourceBomb => "resource_bomb",
+            Self::DeepNesting => "deep_nesting",
+            Self::SnapshotCorruption => "snapshot_corruption",
+            Self::RefcountAbuse => "refcount_abuse",
+        })
+    }
+}
+
+/// A resource limit that can stop a case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// `max_memory`.
+    Memory,
+    /// `max_duration` or `wall_deadline`.
+    Time,
+    /// `max_allocations`.
+    Allocations,
+    /// `max_recursion_depth`.
+    Recursion,
+    /// `max_str_len`.
+    StrLen,
+    /// `max_list_len`.
+    ListLen,
+    /// `max_dict_entries`.
+    DictEntries,
+}
+
+impl Limit {
+    /// Returns whether `limits` configures this limit.
+    #[must_use]
+    pub fn is_set(self, limits: &ResourceLimits) -> bool {
+        match self {
+            Self::Memory => limits.max_memory.is_some(),
+            Self::Time => limits.max_duration.is_some() || limits.wall_deadline.is_some(),
+            Self::Allocations => limits.max_allocations.is_some(),
+            Self::Recursion => limits.max_recursion_depth.is_some(),
+            Self::StrLen => limits.max_str_len.is_some(),
+            Self::ListLen => limits.max_list_len.is_some(),
+            Self::DictEntries => limits.max_dict_entries.is_some(),
+        }
+    }
+}
+
+/// One adversarial program or input.
+#[derive(Debug)]
+pub struct SecurityCase {
+    /// Short unique name, e.g. `string_doubling`.
+    pub name: &'static str,
+    /// What kind of attack this is.
+    pub category: Category,
+    /// One-line description of the attack.
+    pub description: &'static str,
+    /// Limits that stop this case; at least one must be configured for it to run.
+    ///
+    /// Empty when the interpreter must contain the case on its own.
+    pub requires_any: &'static [Limit],
+    attack: Attack,
+}
+
+#[derive(Debug)]
+enum Attack {
+    /// Run a program; it must end as `expect` says.
+    Program { source: Source, expect: Expect },
+    /// Load corrupted snapshot bytes; they must be rejected, or resume without panicking.
+    Snapshot(Corruption),
+}
+
+#[derive(Debug)]
+enum Source {
+    Static(&'static str),
+    /// `open` repeated `depth` times, then `inner`, then `close` repeated `depth` times.
+    Nested {
+        open: &'static str,
+        inner: &'static str,
+        close: &'static str,
+        depth: usize,
+    },
+    /// `header` as a block statement nested `depth` levels deep around `body`.
+    NestedBlocks {
+        header: &'static str,
+        body: &'static str,
+        depth: usize,
+    },
+}
+
+impl Source {
+    fn build(&self) -> String {
+        match *self {
+            Self::Static(code) => code.to_owned(),
+            Self::Nested {
+                open,
+                inner,
+                close,
+                depth,
+            } => format!("{}{inner}{}", open.repeat(depth), close.repeat(depth)),
+            Self::NestedBlocks { header, body, depth } => {
+                let mut code = String::new();
+                for level in 0..depth {
+                    code.push_str(&"    ".repeat(level));
+                    code.push_str(header);
+                    code.push('\n');
+                }
+                code.push_str(&"    ".repeat(depth));
+                code.push_str(body);
+                code
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expect {
+    /// The program must raise one of these exception types.
+    Raise(&'static [ExcType]),
+    /// The program may complete or raise anything, as long as it returns.
+    Finish,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Corruption {
+    /// A snapshot cut off halfway.
+    Truncated,
+    /// No bytes at all.
+    Empty,
+    /// A serialized `MontyRun` passed where a snapshot is expected.
+    ForeignFormat,
+    /// A well-formed snapshot whose operand stack holds a freed heap id.
+    DanglingHeapId,
+    /// A well-formed snapshot whose operand stack holds a function that doesn't exist.
+    MissingFunction,
+}
+
+impl Corruption {
+    /// Returns the inconsistency a snapshot that decodes is crafted with, if any; such
+    /// snapshots must be rejected by `load`, not merely survive resuming.
+    fn inconsistency(self) -> Option<Inconsistency> {
+        match self {
+            Self::Truncated | Self::Empty | Self::ForeignFormat => None,
+            Self::DanglingHeapId => Some(Inconsistency::DanglingHeapId),
+            Self::MissingFunction => Some(Inconsistency::MissingFunction),
+        }
+    }
+}
+
+/// Exceptions that show a resource limit stopped a program.
+const LIMIT_ERRORS: &[ExcType] = &[ExcType::MemoryError, ExcType::TimeoutError];
+
+static CASES: &[SecurityCase] = &[
+    SecurityCase {
+        name: "infinite_loop",
+        category: Category::ResourceBomb,
+        description: "a loop that never allocates and never ends",
+        requires_any: &[Limit::Time],
+        attack: Attack::Program {
+            source: Source::Static("while True:\n    pass"),
+            expect: Expect::Raise(&[ExcType::TimeoutError]),
+        },
+    },
+    SecurityCase {
+        name: "allocation_churn",
+        category: Category::ResourceBomb,
+        description: "endless short-lived allocations with flat memory use",
+        requires_any: &[Limit::Allocations, Limit::Time],
+        attack: Attack::Program {
+            source: Source::Static("while True:\n    x = [1, 2, 3]"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "string_doubling",
+        category: Category::ResourceBomb,
+        description: "a string that doubles in size every iteration",
+        requires_any: &[Limit::Memory, Limit::StrLen],
+        attack: Attack::Program {
+            source: Source::Static("s = 'ab'\nwhile True:\n    s += s"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "huge_repeat",
+        category: Category::ResourceBomb,
+        description: "a single string repetition of 2**62 bytes",
+        requires_any: &[Limit::Memory, Limit::StrLen],
+        attack: Attack::Program {
+            source: Source::Static("'x' * (1 << 62)"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "list_doubling",
+        category: Category::ResourceBomb,
+        description: "a list rebuilt at twice its size every iteration",
+        requires_any: &[Limit::Memory, Limit::ListLen],
+        attack: Attack::Program {
+            source: Source::Static("x = [0]\nwhile True:\n    x = x * 2"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "list_self_extend",
+        category: Category::ResourceBomb,
+        description: "a list extended with itself in place",
+        requires_any: &[Limit::ListLen],
+        attack: Attack::Program {
+            source: Source::Static("x = [0]\nwhile True:\n    x.extend(x)"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "dict_fill",
+        category: Category::ResourceBomb,
+        description: "a dict that gains an entry every iteration",
+        requires_any: &[Limit::DictEntries],
+        attack: Attack::Program {
+            source: Source::Static("d = {}\ni = 0\nwhile True:\n    d[i] = i\n    i += 1"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "bigint_power",
+        category: Category::ResourceBomb,
+        description: "an integer power with a trillion-bit result",
+        requires_any: &[Limit::Memory],
+        attack: Attack::Program {
+            source: Source::Static("2 ** (1 << 40)"),
+            expect: Expect::Raise(LIMIT_ERRORS),
+        },
+    },
+    SecurityCase {
+        name: "unbounded_recursion",
+        category: Category::DeepNesting,
+        description: "a function that calls itself forever",
+        requires_any: &[Limit::Recursion],
+        attack: Attack::Program {
+            source: Source::Static("def f(n):\n    return f(n + 1)\n\nf(0)"),
+            expect: Expect::Raise(&[ExcType::RecursionError, ExcType::MemoryError, ExcType::TimeoutError]),
+        },
+    },
+    SecurityCase {
+        name: "nested_parentheses",
+        category: Category::DeepNesting,
+        description: "250 levels of nested tuples in the source",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Nested {
+                open: "(",
+                inner: "1",
+                close: ",)",
+                depth: 250,
+            },
+            expect: Expect::Raise(&[ExcType::SyntaxError]),
+        },
+    },
+    SecurityCase {
+        name: "nested_list_literals",
+        category: Category::DeepNesting,
+        description: "250 levels of nested list literals in the source",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Nested {
+                open: "[",
+                inner: "1",
+                close: "]",
+                depth: 250,
+            },
+            expect: Expect::Raise(&[ExcType::SyntaxError]),
+        },
+    },
+    SecurityCase {
+        name: "nested_blocks",
+        category: Category::DeepNesting,
+        description: "250 levels of nested if statements",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::NestedBlocks {
+                header: "if 1:",
+                body: "pass",
+                depth: 250,
+            },
+            expect: Expect::Raise(&[ExcType::SyntaxError]),
+        },
+    },
+    SecurityCase {
+        name: "deep_data_repr",
+        category: Category::DeepNesting,
+        description: "repr of a list nested 100,000 levels deep",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static("x = []\nfor _ in range(100000):\n    x = [x]\nlen(repr(x))"),
+            expect: Expect::Finish,
+        },
+    },
+    SecurityCase {
+        name: "deep_data_compare",
+        category: Category::DeepNesting,
+        description: "equality of two lists nested 100,000 levels deep",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static("a = []\nb = []\nfor _ in range(100000):\n    a = [a]\n    b = [b]\na == b"),
+            expect: Expect::Finish,
+        },
+    },
+    SecurityCase {
+        name: "snapshot_truncated",
+        category: Category::SnapshotCorruption,
+        description: "a paused snapshot cut off halfway",
+        requires_any: &[],
+        attack: Attack::Snapshot(Corruption::Truncated),
+    },
+    SecurityCase {
+        name: "snapshot_empty",
+        category: Category::SnapshotCorruption,
+        description: "an empty byte string loaded as a snapshot",
+        requires_any: &[],
+        attack: Attack::Snapshot(Corruption::Empty),
+    },
+    SecurityCase {
+        name: "snapshot_foreign_format",
+        category: Category::SnapshotCorruption,
+        description: "a serialized runner loaded as a snapshot",
+        requires_any: &[],
+        attack: Attack::Snapshot(Corruption::ForeignFormat),
+    },
+    SecurityCase {
+        name: "snapshot_dangling_heap_id",
+        category: Category::SnapshotCorruption,
+        description: "a well-formed snapshot referring to a freed heap entry",
+        requires_any: &[],
+        attack: Attack::Snapshot(Corruption::DanglingHeapId),
+    },
+    SecurityCase {
+        name: "snapshot_missing_function",
+        category: Category::SnapshotCorruption,
+        description: "a well-formed snapshot referring to a function the program doesn't have",
+        requires_any: &[],
+        attack: Attack::Snapshot(Corruption::MissingFunction),
+    },
+    SecurityCase {
+        name: "self_referencing_lists",
+        category: Category::RefcountAbuse,
+        description: "50,000 lists that contain themselves, each dropped immediately",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static("for _ in range(50000):\n    a = []\n    a.append(a)"),
+            expect: Expect::Finish,
+        },
+    },
+    SecurityCase {
+        name: "mutual_dict_cycles",
+        category: Category::RefcountAbuse,
+        description: "50,000 pairs of dicts that refer to each other",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static("for _ in range(50000):\n    a = {}\n    b = {'a': a}\n    a['b'] = b"),
+            expect: Expect::Finish,
+        },
+    },
+    SecurityCase {
+        name: "retained_exceptions",
+        category: Category::RefcountAbuse,
+        description: "exceptions kept alive past their handlers in a loop",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static(
+                "errors = []\nfor i in range(10000):\n    try:\n        raise ValueError([i])\n    except ValueError as e:\n        errors.append(e)\nlen(errors)",
+            ),
+            expect: Expect::Finish,
+        },
+    },
+    SecurityCase {
+        name: "dict_mutated_while_iterating",
+        category: Category::RefcountAbuse,
+        description: "a dict that grows while a loop iterates over it",
+        requires_any: &[],
+        attack: Attack::Program {
+            source: Source::Static("d = {0: 0}\nfor k in d:\n    d[k + 1] = k"),
+            expect: Expect::Raise(&[ExcType::RuntimeError]),
+        },
+    },
+];
+
+/// Returns every bundled case.
+#[must_use]
+pub fn cases() -> &'static [SecurityCase] {
+    CASES
+}
+
+/// How a case ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The sandbox contained the case; the string says how.
+    Held(String),
+    /// Not run: none of the case's `requires_any` limits is configured.
+    Unprotected,
+    /// The case got past the sandbox; the string says how.
+    Escaped(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Held(detail) => write!(f, "held ({detail})"),
+            Self::Unprotected => f.write_str("unprotected (no configured limit stops it)"),
+            Self::Escaped(detail) => write!(f, "ESCAPED ({detail})"),
+        }
+    }
+}
+
+/// Result of running one case.
+#[derive(Debug)]
+pub struct CaseResult {
+    /// The case that was run.
+    pub case: &'static SecurityCase,
+    /// How it ended.
+    pub outcome: Outcome,
+}
+
+/// Results of running the whole suite, in case order.
+#[derive(Debug)]
+pub struct SecurityReport {
+    /// One result per case.
+    pub results: Vec<CaseResult>,
+}
+
+impl SecurityReport {
+    /// Returns whether every case was run and held.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|result| matches!(result.outcome, Outcome::Held(_)))
+    }
+
+    /// Returns the results of cases that escaped or were not protected.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.results
+            .iter()
+            .filter(|result| !matches!(result.outcome, Outcome::Held(_)))
+    }
+}
+
+impl fmt::Display for SecurityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{}/{}: {}", result.case.category, result.case.name, result.outcome)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every bundled case against `limits`.
+#[must_use]
+pub fn run_suite(limits: &ResourceLimits) -> SecurityReport {
+    let results = CASES
+        .iter()
+        .map(|case| CaseResult {
+            case,
+            outcome: run_case(case, limits),
+        })
+        .collect();
+    SecurityReport { results }
+}
+
+/// Runs one case against `limits`.
+#[must_use]
+pub fn run_case(case: &SecurityCase, limits: &ResourceLimits) -> Outcome {
+    if !case.requires_any.is_empty() && !case.requires_any.iter().any(|limit| limit.is_set(limits)) {
+        return Outcome::Unprotected;
+    }
+    let attempt = panic::catch_unwind(AssertUnwindSafe(|| match &case.attack {
+        Attack::Program { source, expect } => run_program(source.build(), expect, limits),
+        Attack::Snapshot(corruption) => load_corrupted(*corruption, limits),
+    }));
+    attempt.unwrap_or_else(|payload| Outcome::Escaped(format!("panicked: {}", panic_message(payload.as_ref()))))
+}
+
+fn run_program(code: String, expect: &Expect, limits: &ResourceLimits) -> Outcome {
+    let result = MontyRun::new(code, "sectest.py", vec![], vec![])
+        .and_then(|runner| runner.run(vec![], LimitedTracker::new(limits.clone()), &mut PrintWriter::Disabled));
+    match (result, expect) {
+        (Err(exc), Expect::Raise(allowed)) if allowed.contains(&exc.exc_type()) => Outcome::Held(exc.summary()),
+        (Err(exc), Expect::Raise(_)) => Outcome::Escaped(format!("raised unexpected {}", exc.summary())),
+        (Ok(_), Expect::Raise(_)) => Outcome::Escaped("ran to completion".to_owned()),
+        (Err(exc), Expect::Finish) => Outcome::Held(exc.summary()),
+        (Ok(_), Expect::Finish) => Outcome::Held("completed".to_owned()),
+    }
+}
+
+/// Program paused partway through to produce snapshot bytes.
+const SNAPSHOT_SOURCE: &str = "items = [str(i) for i in range(50)]\ntable = {'items': items}\nlen(table['items'])";
+
+fn load_corrupted(corruption: Corruption, limits: &ResourceLimits) -> Outcome {
+    let bytes = match corrupted_bytes(corruption, limits) {
+        Ok(bytes) => bytes,
+        Err(err) => return Outcome::Escaped(format!("could not build the snapshot to corrupt: {err}")),
+    };
+    match RunProgress::<LimitedTracker>::load(&bytes) {
+        Err(err) => Outcome::Held(format!("rejected: {err}")),
+        Ok(progress) if corruption.inconsistency().is_some() => {
+            // the state refers to things that don't exist, so it can't be released
+            std::mem::forget(progress);
+            Outcome::Escaped("inconsistent snapshot was loaded".to_owned())
+        }
+        Ok(progress) => match resume_once(progress) {
+            Ok(_) => Outcome::Held("loaded and resumed".to_owned()),
+            Err(exc) => Outcome::Held(format!("loaded, resuming raised {}", exc.summary())),
+        },
+    }
+}
+
+fn corrupted_bytes(corruption: Corruption, limits: &ResourceLimits) -> Result<Vec<u8>, String> {
+    let runner = MontyRun::new(SNAPSHOT_SOURCE.to_owned(), "sectest.py", vec![], vec![]).map_err(|e| e.summary())?;
+    match corruption {
+        Corruption::Empty => return Ok(Vec::new()),
+        Corruption::ForeignFormat => return runner.dump().map_err(|e| e.to_string()),
+        _ => {}
+    }
+    let progress = runner
+        .start_fuel(
+            vec![],
+            LimitedTracker::new(limits.clone()),
+            20,
+            &mut PrintWriter::Disabled,
+        )
+        .map_err(|e| e.summary())?;
+    if let Some(inconsistency) = corruption.inconsistency() {
+        return progress.dump_inconsistent(inconsistency).map_err(|e| e.to_string());
+    }
+    let mut bytes = progress.dump().map_err(|e| e.to_string())?;
+    bytes.truncate(bytes.len() / 2);
+    Ok(bytes)
+}
+
+/// Continues whatever state a loaded snapshot is in by one step.
+pub(crate) fn resume_once(
+    progress: RunProgress<LimitedTracker>,
+) -> Result<RunProgress<LimitedTracker>, MontyException> {
+    let print = &mut PrintWriter::Disabled;
+    match progress {
+        RunProgress::FunctionCall { state, .. }
+        | RunProgress::OsCall { state, .. }
+        | RunProgress::Emit { state, .. }
+        | RunProgress::Sleep { state, .. } => state.run(MontyObject::None, print),
+        RunProgress::ResolveFutures(state) => state.resume(Vec::new(), print),
+        RunProgress::Paused(state) => state.run(print),
+        RunProgress::Breakpoint(state) => state.resume(print),
+        complete @ RunProgress::Complete(_) => Ok(complete),
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "non-string panic payload"
+    }
+}
@@ -0,0 +1,134 @@
+//! Sharing interned constants between compiled programs.
+//!
+//! Every compiled program keeps a table of the names, string and bytes literals and long
+//! integer literals its code uses. A host compiling thousands of small scripts written
+//! from the same templates ends up holding the same constants thousands of times over.
+//! Programs compiled with one [`SharedInterns`] table keep a single copy of each constant
+//! they have in common. Each program still numbers its constants itself, so sharing never
+//! changes how code runs, and a shared constant is freed once no program uses it and the
+//! table has been [pruned](SharedInterns::prune).
+
+use std::{
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use ahash::AHashSet;
+use num_bigint::BigInt;
+
+/// A table of interned constants that programs compiled with it share.
+///
+/// Cloning is cheap and gives another handle to the same table, so one table can be used
+/// from any number of threads. Pass it to [`CompileOptions::share_interns`] when compiling,
+/// or to [`MontyRun::load_code_shared`] when loading compiled code.
+///
+/// [`CompileOptions::share_interns`]: crate::CompileOptions::share_interns
+/// [`MontyRun::load_code_shared`]: crate::MontyRun::load_code_shared
+///
+/// # Example
+/// ```
+/// use monty::{CompileOptions, MontyObject, MontyRun, SharedInterns};
+///
+/// let shared = SharedInterns::new();
+/// let compile = |code: &str| {
+///     let options = CompileOptions::new().share_interns(shared.clone());
+///     MontyRun::new_with_options(code.to_owned(), "tenant.py", vec![], vec![], options).unwrap()
+/// };
+/// let first = compile("greeting = 'hello tenant'\ngreeting");
+/// let constants = shared.len();
+/// let second = compile("greeting = 'hello tenant'\ngreeting");
+/// assert_eq!(shared.len(), constants);
+///
+/// for runner in [first, second] {
+///     assert_eq!(runner.run_no_limits(vec![]).unwrap(), MontyObject::from("hello tenant"));
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct SharedInterns(Arc<Mutex<Table>>);
+
+/// The distinct constants of every program sharing a [`SharedInterns`] table.
+#[derive(Default)]
+struct Table {
+    strings: AHashSet<Arc<str>>,
+    bytes: AHashSet<Arc<[u8]>>,
+    long_ints: AHashSet<Arc<BigInt>>,
+}
+
+impl SharedInterns {
+    /// Creates an empty table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct constants in the table.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let table = self.lock();
+        table.strings.len() + table.bytes.len() + table.long_ints.len()
+    }
+
+    /// Returns whether the table holds no constants.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops the constants no program uses anymore.
+    ///
+    /// The table keeps every constant it has seen until it is pruned, so hosts that drop
+    /// programs should call this now and then.
+    pub fn prune(&self) {
+        let mut table = self.lock();
+        table.strings.retain(|s| Arc::strong_count(s) > 1);
+        table.bytes.retain(|b| Arc::strong_count(b) > 1);
+        table.long_ints.retain(|n| Arc::strong_count(n) > 1);
+    }
+
+    /// Replaces each constant with the table's copy of an equal constant, adding the ones
+    /// the table doesn't have yet.
+    pub(crate) fn share(&self, strings: &mut [Arc<str>], bytes: &mut [Arc<[u8]>], long_ints: &mut [Arc<BigInt>]) {
+        let mut table = self.lock();
+        let Table {
+            strings: shared_strings,
+            bytes: shared_bytes,
+            long_ints: shared_long_ints,
+        } = &mut *table;
+        share_all(shared_strings, strings);
+        share_all(shared_bytes, bytes);
+        share_all(shared_long_ints, long_ints);
+    }
+
+    /// Locks the table. A poisoned lock is recovered, since the sets are only ever added to
+    /// or pruned and are consistent after a panic elsewhere.
+    fn lock(&self) -> MutexGuard<'_, Table> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl PartialEq for SharedInterns {
+    /// Handles are equal if they refer to the same table.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedInterns {}
+
+impl fmt::Debug for SharedInterns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedInterns").field("len", &self.len()).finish()
+    }
+}
+
+/// Swaps each of `values` for the equal value in `shared`, inserting those it lacks.
+fn share_all<T: Eq + Hash + ?Sized>(shared: &mut AHashSet<Arc<T>>, values: &mut [Arc<T>]) {
+    for value in values {
+        if let Some(existing) = shared.get(&**value) {
+            *value = Arc::clone(existing);
+        } else {
+            shared.insert(Arc::clone(value));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Framing for serialized snapshots and compiled code.
+//!
+//! Every `dump()` that is meant to outlive the process writes a small header before the
+//! postcard payload, so that `load()` can tell foreign bytes, bytes from another Monty
+//! release and corrupted bytes apart before trying to decode anything:
+//!
+//! ```text
+//! magic: [u8; 4] | format version: u16 LE | crate version: u8 length + UTF-8 | CRC-32: u32 LE | payload
+//! ```
+//!
+//! The payload layout follows Monty's internal types, which change between releases, so a
+//! snapshot is only loaded by the exact crate version that wrote it.
+//!
+//! Hash maps and sets in serialized state are written in sorted order through
+//! [`serialize_sorted_map`] and [`serialize_sorted_set`]. Their iteration order depends on a
+//! per-process random seed, so writing them directly would make two dumps of the same state
+//! differ byte for byte.
+
+use std::fmt;
+
+use serde::{Serialize, Serializer, de::DeserializeOwned};
+
+/// Version of the header layout above. Bump when the header itself changes.
+pub const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+
+/// The Monty release writing and accepting snapshots.
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Identifies what a serialized blob holds, so one kind is never decoded as another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SnapshotKind {
+    /// `RunProgress::dump()`
+    RunProgress,
+    /// `ReplProgress::dump()`
+    ReplProgress,
+    /// `MontyRun::dump_code()`
+    Code,
+    /// `MontyRepl::dump()`
+    Repl,
+    /// `Snapshot::dump()`
+    Snapshot,
+    /// `FutureSnapshot::dump()`
+    FutureSnapshot,
+}
+
+impl SnapshotKind {
+    fn magic(self) -> [u8; 4] {
+        match self {
+            Self::RunProgress => *b"MNTR",
+            Self::ReplProgress => *b"MNTP",
+            Self::Code => *b"MNTC",
+            Self::Repl => *b"MNTL",
+            Self::Snapshot => *b"MNTS",
+            Self::FutureSnapshot => *b"MNTF",
+        }
+    }
+}
+
+/// Error returned when loading a snapshot fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// The bytes don't start with the header for this kind of snapshot.
+    BadMagic,
+    /// The snapshot was written by a different Monty release or header layout.
+    VersionMismatch {
+        /// Header layout version found in the snapshot.
+        format_version: u16,
+        /// Monty version that wrote the snapshot.
+        crate_version: String,
+    },
+    /// The payload doesn't match the checksum in the header.
+    ChecksumMismatch,
+    /// The state decoded, but refers to heap entries, functions or interned values that don't
+    /// exist, or to a position that isn't an instruction.
+    ///
+    /// Resuming it would panic, so it is rejected up front. The message names the first
+    /// inconsistency found.
+    Corrupt(String),
+    /// The payload could not be serialized or deserialized.
+    Postcard(postcard::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("not a Monty snapshot of the expected kind"),
+            Self::VersionMismatch {
+                format_version,
+                crate_version,
+            } => write!(
+                f,
+                "snapshot was written by Monty {crate_version} (format {format_version}), \
+                 this is Monty {CRATE_VERSION} (format {SNAPSHOT_FORMAT_VERSION})"
+            ),
+            Self::ChecksumMismatch => f.write_str("snapshot checksum mismatch, the data is corrupted"),
+            Self::Corrupt(reason) => write!(f, "corrupt snapshot: {reason}"),
+            Self::Postcard(err) => write!(f, "invalid snapshot data: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Postcard(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<postcard::Error> for SnapshotError {
+    fn from(err: postcard::Error) -> Self {
+        Self::Postcard(err)
+    }
+}
+
+/// Serializes `value` behind a header for `kind`.
+pub(crate) fn dump<T: Serialize + ?Sized>(kind: SnapshotKind, value: &T) -> Result<Vec<u8>, postcard::Error> {
+    Ok(frame(kind, &postcard::to_allocvec(value)?))
+}
+
+/// Puts the header for `kind` in front of an already serialized `payload`.
+pub(crate) fn frame(kind: SnapshotKind, payload: &[u8]) -> Vec<u8> {
+    let version_len = u8::try_from(CRATE_VERSION.len()).expect("crate version fits in 255 bytes");
+
+    let mut bytes = Vec::with_capacity(11 + CRATE_VERSION.len() + payload.len());
+    bytes.extend_from_slice(&kind.magic());
+    bytes.extend_from_slice(&SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+    bytes.push(version_len);
+    bytes.extend_from_slice(CRATE_VERSION.as_bytes());
+    bytes.extend_from_slice(&crc32(payload).to_le_bytes());
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Checks the header for `kind` and deserializes the payload that follows.
+pub(crate) fn load<T: DeserializeOwned>(kind: SnapshotKind, bytes: &[u8]) -> Result<T, SnapshotError> {
+    let mut reader = Reader(bytes);
+    if reader.take(4).ok() != Some(kind.magic().as_slice()) {
+        return Err(SnapshotError::BadMagic);
+    }
+    let format_version = u16::from_le_bytes(reader.take_array()?);
+    let version_len = reader.take(1)?[0];
+    let crate_version = reader.take(usize::from(version_len))?;
+    if format_version != SNAPSHOT_FORMAT_VERSION || crate_version != CRATE_VERSION.as_bytes() {
+        return Err(SnapshotError::VersionMismatch {
+            format_version,
+            crate_version: String::from_utf8_lossy(crate_version).into_owned(),
+        });
+    }
+    let checksum = u32::from_le_bytes(reader.take_array()?);
+    if crc32(reader.0) != checksum {
+        return Err(SnapshotError::ChecksumMismatch);
+    }
+    Ok(postcard::from_bytes(reader.0)?)
+}
+
+/// Cursor over header bytes.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.0.len() < len {
+            return Err(postcard::Error::DeserializeUnexpectedEnd.into());
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().expect("took exactly N bytes"))
+    }
+}
+
+/// Serializes a hash map with its entries sorted by key, for `#[serde(serialize_with)]`.
+///
+/// The output is an ordinary map, so the field deserializes without a counterpart.
+pub(crate) fn serialize_sorted_map<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Ord + Serialize + 'a,
+    V: Serialize + 'a,
+    S: Serializer,
+{
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    serializer.collect_map(entries)
+}
+
+/// Serializes a hash set with its items sorted, for `#[serde(serialize_with)]`.
+///
+/// The output is an ordinary sequence, so the field deserializes without a counterpart.
+pub(crate) fn serialize_sorted_set<'a, C, T, S>(set: &'a C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a C: IntoIterator<Item = &'a T>,
+    T: Ord + Serialize + 'a,
+    S: Serializer,
+{
+    let mut items: Vec<_> = set.into_iter().collect();
+    items.sort_unstable();
+    serializer.collect_seq(items)
+}
+
+/// CRC-32 (IEEE) of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = dump(SnapshotKind::Code, &(1u32, "two")).unwrap();
+        let value: (u32, String) = load(SnapshotKind::Code, &bytes).unwrap();
+        assert_eq!(value, (1, "two".to_owned()));
+    }
+
+    #[test]
+    fn rejects_other_kind() {
+        let bytes = dump(SnapshotKind::RunProgress, &1u32).unwrap();
+        assert!(matches!(
+            load::<u32>(SnapshotKind::ReplProgress, &bytes),
+            Err(SnapshotError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupted_payload() {
+        let mut bytes = dump(SnapshotKind::Code, &12345u32).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            load::<u32>(SnapshotKind::Code, &bytes),
+            Err(SnapshotError::ChecksumMismatch)
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let bytes = dump(SnapshotKind::Code, &1u32).unwrap();
+        assert!(matches!(
+            load::<u32>(SnapshotKind::Code, &bytes[..6]),
+            Err(SnapshotError::Postcard(postcard::Error::DeserializeUnexpectedEnd))
+        ));
+    }
+}
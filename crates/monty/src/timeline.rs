@@ -0,0 +1,292 @@
+//! Optional timeline of spans recorded during a run.
+//!
+//! The VM reports span boundaries (function calls, waits on the host for external calls,
+//! and garbage collection pauses) through the `on_span_enter` / `on_span_exit` hooks on
+//! [`ResourceTracker`]. Those hooks are no-ops by default; wrapping a tracker in
+//! [`TimelineTracker`] records them into a [`Timeline`] shared with the host through a
+//! [`TimelineHandle`], so spans can be read after (or during) the run even though the
+//! tracker itself is consumed by it.
+//!
+//! Spans carry an `id`, an optional `parent_id`, an absolute start time and a duration,
+//! which is the shape `tracing` and OpenTelemetry exporters expect, so converting a
+//! timeline into exported spans is a straight mapping.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use crate::{
+    clock::{Clock, Instant, SystemTime},
+    resource::{CollectionKind, ResourceBudget, ResourceError, ResourceTracker},
+    trace::TraceHook,
+    warnings::WarningSink,
+};
+
+/// Default cap on the number of spans kept by a [`TimelineTracker`].
+///
+/// The timeline lives outside the sandbox heap, so it isn't covered by memory limits;
+/// the cap stops a script that makes millions of calls from growing host memory unboundedly.
+pub const DEFAULT_MAX_TIMELINE_SPANS: usize = 100_000;
+
+/// What a [`TimelineSpan`] measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum SpanKind {
+    /// A call to a Python function defined in the script, from frame push to frame pop.
+    FunctionCall,
+    /// Time spent waiting for the host to resolve an external function call, OS call,
+    /// dataclass method call, or pending futures, or to resume after `time.sleep()`.
+    ExternalWait,
+    /// A garbage collection pause.
+    GarbageCollection,
+}
+
+/// A completed span on a run's timeline.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TimelineSpan {
+    /// Unique (within the timeline) span identifier, assigned in the order spans are entered.
+    pub id: u64,
+    /// Identifier of the span that was open when this span was entered, if any.
+    pub parent_id: Option<u64>,
+    /// What the span measures.
+    pub kind: SpanKind,
+    /// Function name, external function name, or `"gc"` for garbage collection.
+    pub name: String,
+    /// Wall-clock time the span was entered.
+    pub start: SystemTime,
+    /// How long the span was open.
+    pub duration: Duration,
+}
+
+/// A span that has been entered but not yet exited.
+#[derive(Debug)]
+struct OpenSpan {
+    id: u64,
+    parent_id: Option<u64>,
+    kind: SpanKind,
+    name: String,
+    start: SystemTime,
+    started: Instant,
+}
+
+/// Spans recorded by a [`TimelineTracker`], accessed through a [`TimelineHandle`].
+///
+/// Completed spans are stored in the order they finish, so children appear before
+/// their parents; use `parent_id` to rebuild the tree.
+#[derive(Debug)]
+pub struct Timeline {
+    spans: Vec<TimelineSpan>,
+    open: Vec<OpenSpan>,
+    next_id: u64,
+    dropped: usize,
+    max_spans: usize,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_TIMELINE_SPANS)
+    }
+}
+
+impl Timeline {
+    fn new(max_spans: usize) -> Self {
+        Self {
+            spans: Vec::new(),
+            open: Vec::new(),
+            next_id: 0,
+            dropped: 0,
+            max_spans,
+        }
+    }
+
+    /// Returns the completed spans, in the order they finished.
+    #[must_use]
+    pub fn spans(&self) -> &[TimelineSpan] {
+        &self.spans
+    }
+
+    /// Returns the number of spans discarded because the span cap was reached.
+    #[must_use]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Removes and returns the completed spans, leaving open spans in place.
+    pub fn take_spans(&mut self) -> Vec<TimelineSpan> {
+        std::mem::take(&mut self.spans)
+    }
+
+    /// Returns the number of spans that have been entered but not yet exited.
+    #[must_use]
+    pub fn open_count(&self) -> usize {
+        self.open.len()
+    }
+
+    fn enter(&mut self, kind: SpanKind, name: &str) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.open.push(OpenSpan {
+            id,
+            parent_id: self.open.last().map(|span| span.id),
+            kind,
+            name: name.to_owned(),
+            start: SystemTime::now(),
+            started: Instant::now(),
+        });
+    }
+
+    /// Closes the most recently entered open span of `kind`.
+    ///
+    /// Matching on kind rather than strictly popping the last span keeps the timeline
+    /// consistent when async tasks interleave: a function span in one task may still be
+    /// open while another task's calls start and finish. Exits with no matching open
+    /// span are ignored.
+    fn exit(&mut self, kind: SpanKind) {
+        let Some(index) = self.open.iter().rposition(|span| span.kind == kind) else {
+            return;
+        };
+        let open = self.open.remove(index);
+        if self.spans.len() >= self.max_spans {
+            self.dropped += 1;
+            return;
+        }
+        self.spans.push(TimelineSpan {
+            id: open.id,
+            parent_id: open.parent_id,
+            kind: open.kind,
+            name: open.name,
+            start: open.start,
+            duration: open.started.elapsed(),
+        });
+    }
+}
+
+/// A shared handle to the [`Timeline`] recorded by a [`TimelineTracker`].
+///
+/// Cloning the handle is cheap; all clones refer to the same timeline.
+#[derive(Debug, Clone, Default)]
+pub struct TimelineHandle(Arc<Mutex<Timeline>>);
+
+impl TimelineHandle {
+    /// Creates a handle to an empty timeline that keeps at most `max_spans` completed spans.
+    #[must_use]
+    pub fn new(max_spans: usize) -> Self {
+        Self(Arc::new(Mutex::new(Timeline::new(max_spans))))
+    }
+
+    /// Locks and returns the timeline.
+    ///
+    /// A poisoned lock is recovered: the timeline is only ever appended to, so it is
+    /// still consistent after a panic elsewhere.
+    #[must_use]
+    pub fn lock(&self) -> MutexGuard<'_, Timeline> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A resource tracker that wraps another tracker and records a [`Timeline`] of spans.
+///
+/// All limit checks are delegated to the inner tracker. The handle is not serialized:
+/// `Instant`s have no meaning outside the current process, so a deserialized tracker
+/// starts recording into a fresh timeline; use `set_timeline_handle` to reconnect it
+/// to the host's handle after loading a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TimelineTracker<T: ResourceTracker> {
+    inner: T,
+    #[serde(skip)]
+    timeline: TimelineHandle,
+}
+
+impl<T: ResourceTracker> TimelineTracker<T> {
+    /// Creates a new timeline tracker wrapping the given tracker, keeping at most
+    /// [`DEFAULT_MAX_TIMELINE_SPANS`] spans.
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        Self::with_max_spans(inner, DEFAULT_MAX_TIMELINE_SPANS)
+    }
+
+    /// Creates a new timeline tracker that keeps at most `max_spans` completed spans.
+    #[must_use]
+    pub fn with_max_spans(inner: T, max_spans: usize) -> Self {
+        Self {
+            inner,
+            timeline: TimelineHandle::new(max_spans),
+        }
+    }
+
+    /// Returns a clone of the handle to the recorded timeline.
+    #[must_use]
+    pub fn timeline_handle(&self) -> TimelineHandle {
+        self.timeline.clone()
+    }
+
+    /// Replaces the timeline handle, e.g. after deserializing a snapshot.
+    pub fn set_timeline_handle(&mut self, handle: TimelineHandle) {
+        self.timeline = handle;
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker> ResourceTracker for TimelineTracker<T> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.timeline.lock().enter(kind, name);
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+        self.timeline.lock().exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        self.inner.trace_hook()
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        self.inner.warning_sink()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.inner.clock()
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
+}
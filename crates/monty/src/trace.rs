@@ -0,0 +1,156 @@
+//! Trace hooks for line, call, return and exception events.
+//!
+//! The VM asks its [`ResourceTracker`] for a [`TraceHook`] through `trace_hook()`, which
+//! returns `None` by default, so untraced runs never compute trace positions. Wrapping a
+//! tracker in [`TracingTracker`] attaches a hook, which lets hosts build debuggers,
+//! auditors and step loggers on top of the VM without changing the crate.
+//!
+//! Events follow CPython's `sys.settrace` closely: a line event fires before the first
+//! instruction of each new source line, and again when a loop jumps back to a line that
+//! already ran. Call and return events are only reported for Python functions defined in
+//! the script, not for module-level code.
+
+use std::fmt;
+
+use crate::{
+    clock::Clock,
+    exception_private::ExcType,
+    resource::{CollectionKind, ResourceBudget, ResourceError, ResourceTracker},
+    timeline::SpanKind,
+    warnings::WarningSink,
+};
+
+/// Where the VM is when it reports a trace event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracePosition<'a> {
+    /// Script name passed to `MontyRun::new`.
+    pub filename: &'a str,
+    /// Name of the executing function, or `"<module>"` for module-level code.
+    pub function: &'a str,
+    /// Line number (1-based).
+    pub line: u16,
+    /// Column number (1-based).
+    pub column: u16,
+    /// Number of frames on the call stack, 1 for module-level code.
+    pub depth: usize,
+}
+
+/// Receives trace events from the VM.
+///
+/// Every method defaults to a no-op, so implementations only override the events they
+/// care about. Hooks are called synchronously from the VM loop and can't affect
+/// execution; limits and cancellation belong in the wrapped [`ResourceTracker`].
+pub trait TraceHook: fmt::Debug {
+    /// Called before the first instruction of a new source line runs.
+    fn on_line(&mut self, _position: &TracePosition<'_>) {}
+
+    /// Called when a function frame is entered, with the position of its first instruction.
+    fn on_call(&mut self, _position: &TracePosition<'_>) {}
+
+    /// Called when a function frame is left, by a `return` or by an exception unwinding it.
+    fn on_return(&mut self, _position: &TracePosition<'_>) {}
+
+    /// Called when an exception is raised, before the VM looks for a handler.
+    ///
+    /// Re-raising from an `except` block reports the exception again.
+    fn on_exception(&mut self, _position: &TracePosition<'_>, _exc_type: ExcType, _message: Option<&str>) {}
+}
+
+/// A resource tracker that wraps another tracker and reports trace events to a [`TraceHook`].
+///
+/// All limit checks are delegated to the inner tracker. The hook is not serialized: a
+/// deserialized tracker starts with `H::default()`; use `set_hook` to reattach the host's
+/// hook after loading a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TracingTracker<T: ResourceTracker, H: TraceHook> {
+    inner: T,
+    #[serde(skip)]
+    hook: H,
+}
+
+impl<T: ResourceTracker, H: TraceHook> TracingTracker<T, H> {
+    /// Creates a tracing tracker wrapping `inner` and reporting events to `hook`.
+    #[must_use]
+    pub fn new(inner: T, hook: H) -> Self {
+        Self { inner, hook }
+    }
+
+    /// Returns a reference to the hook.
+    #[must_use]
+    pub fn hook(&self) -> &H {
+        &self.hook
+    }
+
+    /// Returns a mutable reference to the hook.
+    pub fn hook_mut(&mut self) -> &mut H {
+        &mut self.hook
+    }
+
+    /// Replaces the hook, e.g. after deserializing a snapshot.
+    pub fn set_hook(&mut self, hook: H) {
+        self.hook = hook;
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker, H: TraceHook> ResourceTracker for TracingTracker<T, H> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        Some(&mut self.hook)
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        self.inner.warning_sink()
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.inner.clock()
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
+}
@@ -7,7 +7,7 @@
 /// # Implemented Methods
 ///
 /// ## Encoding/Decoding
-/// - `decode([encoding[, errors]])` - Decode to string (UTF-8 only)
+/// - `decode([encoding[, errors]])` - Decode to string (UTF-8, ASCII or Latin-1)
 /// - `hex([sep[, bytes_per_sep]])` - Return hex string representation
 /// - `fromhex(string)` - Create bytes from hex string (classmethod)
 ///
@@ -70,13 +70,14 @@ use std::fmt::Write;
 use ahash::AHashSet;
 use smallvec::smallvec;
 
-use super::{MontyIter, PyTrait, Type, str::Str};
+use super::{MontyIter, PyTrait, Type, methods::py_methods, str::Str};
 use crate::{
     args::ArgValues,
+    codecs::{Codec, codec_arg},
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunResult, SimpleException},
     heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
-    intern::{Interns, StaticStrings, StringId},
+    intern::Interns,
     resource::{DepthGuard, ResourceError, ResourceTracker},
     types::List,
     value::{EitherStr, Value},
@@ -292,6 +293,46 @@ impl PyTrait for Bytes {
         Ok(self.0 == other.0)
     }
 
+    /// Extends the bytes in place; only used when nothing else can see the change (see
+    /// `VM::inplace_add`).
+    fn py_iadd(
+        &mut self,
+        other: Value,
+        heap: &mut Heap<impl ResourceTracker>,
+        self_id: Option<HeapId>,
+        interns: &Interns,
+    ) -> Result<bool, ResourceError> {
+        let rhs_len = match &other {
+            Value::Ref(other_id) if Some(*other_id) == self_id => Some(self.0.len()),
+            Value::Ref(other_id) => match heap.get(*other_id) {
+                HeapData::Bytes(rhs) => Some(rhs.as_slice().len()),
+                _ => None,
+            },
+            Value::InternBytes(bytes_id) => Some(interns.get_bytes(*bytes_id).len()),
+            _ => None,
+        };
+        let Some(rhs_len) = rhs_len else {
+            other.drop_with_heap(heap);
+            return Ok(false);
+        };
+        if let Err(err) = heap.charge_growth(rhs_len) {
+            other.drop_with_heap(heap);
+            return Err(err);
+        }
+        match &other {
+            Value::Ref(other_id) if Some(*other_id) == self_id => self.0.extend_from_within(..),
+            Value::Ref(other_id) => {
+                if let HeapData::Bytes(rhs) = heap.get(*other_id) {
+                    self.0.extend_from_slice(rhs.as_slice());
+                }
+            }
+            Value::InternBytes(bytes_id) => self.0.extend_from_slice(interns.get_bytes(*bytes_id)),
+            _ => {}
+        }
+        other.drop_with_heap(heap);
+        Ok(true)
+    }
+
     /// Bytes don't contain nested heap references.
     fn py_dec_ref_ids(&mut self, _stack: &mut Vec<HeapId>) {
         // No-op: bytes don't hold Value references
@@ -319,141 +360,62 @@ impl PyTrait for Bytes {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        let Some(method) = attr.static_string() else {
-            args.drop_with_heap(heap);
-            return Err(ExcType::attribute_error(Type::Bytes, attr.as_str(interns)));
-        };
-
-        call_bytes_method_impl(self.as_slice(), method, args, heap, interns)
+        call_bytes_method(self.as_slice(), attr, args, heap, interns)
     }
 }
 
-/// Calls a bytes method on a byte slice by method name.
-///
-/// This is the entry point for bytes method calls from the VM on interned bytes.
-/// Converts the `StringId` to `StaticStrings` and delegates to `call_bytes_method_impl`.
-pub fn call_bytes_method(
-    bytes: &[u8],
-    method_id: StringId,
-    args: ArgValues,
-    heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
-) -> RunResult<Value> {
-    let Some(method) = StaticStrings::from_string_id(method_id) else {
-        args.drop_with_heap(heap);
-        return Err(ExcType::attribute_error(Type::Bytes, interns.get_str(method_id)));
-    };
-    call_bytes_method_impl(bytes, method, args, heap, interns)
-}
-
-/// Calls a bytes method on a byte slice.
-///
-/// This is the unified implementation for bytes method calls, used by both
-/// heap-allocated `Bytes` (via `py_call_attr`) and interned bytes literals
-/// (`Value::InternBytes`).
-fn call_bytes_method_impl(
-    bytes: &[u8],
-    method: StaticStrings,
-    args: ArgValues,
-    heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
-) -> RunResult<Value> {
-    match method {
-        // Decode method
-        StaticStrings::Decode => bytes_decode(bytes, args, heap, interns),
-        // Simple transformations (no arguments)
-        StaticStrings::Lower => {
-            args.check_zero_args("bytes.lower", heap)?;
-            bytes_lower(bytes, heap)
-        }
-        StaticStrings::Upper => {
-            args.check_zero_args("bytes.upper", heap)?;
-            bytes_upper(bytes, heap)
-        }
-        StaticStrings::Capitalize => {
-            args.check_zero_args("bytes.capitalize", heap)?;
-            bytes_capitalize(bytes, heap)
-        }
-        StaticStrings::Title => {
-            args.check_zero_args("bytes.title", heap)?;
-            bytes_title(bytes, heap)
-        }
-        StaticStrings::Swapcase => {
-            args.check_zero_args("bytes.swapcase", heap)?;
-            bytes_swapcase(bytes, heap)
-        }
-        // Predicate methods (no arguments, return bool)
-        StaticStrings::Isalpha => {
-            args.check_zero_args("bytes.isalpha", heap)?;
-            Ok(Value::Bool(bytes_isalpha(bytes)))
-        }
-        StaticStrings::Isdigit => {
-            args.check_zero_args("bytes.isdigit", heap)?;
-            Ok(Value::Bool(bytes_isdigit(bytes)))
-        }
-        StaticStrings::Isalnum => {
-            args.check_zero_args("bytes.isalnum", heap)?;
-            Ok(Value::Bool(bytes_isalnum(bytes)))
-        }
-        StaticStrings::Isspace => {
-            args.check_zero_args("bytes.isspace", heap)?;
-            Ok(Value::Bool(bytes_isspace(bytes)))
-        }
-        StaticStrings::Islower => {
-            args.check_zero_args("bytes.islower", heap)?;
-            Ok(Value::Bool(bytes_islower(bytes)))
-        }
-        StaticStrings::Isupper => {
-            args.check_zero_args("bytes.isupper", heap)?;
-            Ok(Value::Bool(bytes_isupper(bytes)))
-        }
-        StaticStrings::Isascii => {
-            args.check_zero_args("bytes.isascii", heap)?;
-            Ok(Value::Bool(bytes.iter().all(|&b| b <= 127)))
-        }
-        StaticStrings::Istitle => {
-            args.check_zero_args("bytes.istitle", heap)?;
-            Ok(Value::Bool(bytes_istitle(bytes)))
-        }
+py_methods! {
+    /// Dispatches a method call on a bytes value.
+    ///
+    /// This is the unified implementation for bytes method calls, used by both
+    /// heap-allocated `Bytes` (via `py_call_attr`) and interned bytes literals
+    /// (`Value::InternBytes`).
+    pub fn call_bytes_method(&[u8], Type::Bytes, "bytes") {
+        Decode "decode" (args) => bytes_decode,
+        // Simple transformations
+        Lower "lower" (none) => bytes_lower,
+        Upper "upper" (none) => bytes_upper,
+        Capitalize "capitalize" (none) => bytes_capitalize,
+        Title "title" (none) => bytes_title,
+        Swapcase "swapcase" (none) => bytes_swapcase,
+        // Predicate methods
+        Isalpha "isalpha" (predicate) => bytes_isalpha,
+        Isdigit "isdigit" (predicate) => bytes_isdigit,
+        Isalnum "isalnum" (predicate) => bytes_isalnum,
+        Isspace "isspace" (predicate) => bytes_isspace,
+        Islower "islower" (predicate) => bytes_islower,
+        Isupper "isupper" (predicate) => bytes_isupper,
+        Isascii "isascii" (predicate) => bytes_isascii,
+        Istitle "istitle" (predicate) => bytes_istitle,
         // Search methods
-        StaticStrings::Count => bytes_count(bytes, args, heap, interns),
-        StaticStrings::Find => bytes_find(bytes, args, heap, interns),
-        StaticStrings::Rfind => bytes_rfind(bytes, args, heap, interns),
-        StaticStrings::Index => bytes_index(bytes, args, heap, interns),
-        StaticStrings::Rindex => bytes_rindex(bytes, args, heap, interns),
-        StaticStrings::Startswith => bytes_startswith(bytes, args, heap, interns),
-        StaticStrings::Endswith => bytes_endswith(bytes, args, heap, interns),
+        Count "count" (args) => bytes_count,
+        Find "find" (args) => bytes_find,
+        Rfind "rfind" (args) => bytes_rfind,
+        Index "index" (args) => bytes_index,
+        Rindex "rindex" (args) => bytes_rindex,
+        Startswith "startswith" (args) => bytes_startswith,
+        Endswith "endswith" (args) => bytes_endswith,
         // Strip/trim methods
-        StaticStrings::Strip => bytes_strip(bytes, args, heap, interns),
-        StaticStrings::Lstrip => bytes_lstrip(bytes, args, heap, interns),
-        StaticStrings::Rstrip => bytes_rstrip(bytes, args, heap, interns),
-        StaticStrings::Removeprefix => bytes_removeprefix(bytes, args, heap, interns),
-        StaticStrings::Removesuffix => bytes_removesuffix(bytes, args, heap, interns),
+        Strip "strip" (args) => bytes_strip,
+        Lstrip "lstrip" (args) => bytes_lstrip,
+        Rstrip "rstrip" (args) => bytes_rstrip,
+        Removeprefix "removeprefix" (args) => bytes_removeprefix,
+        Removesuffix "removesuffix" (args) => bytes_removesuffix,
         // Split methods
-        StaticStrings::Split => bytes_split(bytes, args, heap, interns),
-        StaticStrings::Rsplit => bytes_rsplit(bytes, args, heap, interns),
-        StaticStrings::Splitlines => bytes_splitlines(bytes, args, heap, interns),
-        StaticStrings::Partition => bytes_partition(bytes, args, heap, interns),
-        StaticStrings::Rpartition => bytes_rpartition(bytes, args, heap, interns),
+        Split "split" (args) => bytes_split,
+        Rsplit "rsplit" (args) => bytes_rsplit,
+        Splitlines "splitlines" (args) => bytes_splitlines,
+        Partition "partition" (args) => bytes_partition,
+        Rpartition "rpartition" (args) => bytes_rpartition,
         // Replace/padding methods
-        StaticStrings::Replace => bytes_replace(bytes, args, heap, interns),
-        StaticStrings::Center => bytes_center(bytes, args, heap, interns),
-        StaticStrings::Ljust => bytes_ljust(bytes, args, heap, interns),
-        StaticStrings::Rjust => bytes_rjust(bytes, args, heap, interns),
-        StaticStrings::Zfill => bytes_zfill(bytes, args, heap),
-        // Join method
-        StaticStrings::Join => {
-            let iterable = args.get_one_arg("bytes.join", heap)?;
-            bytes_join(bytes, iterable, heap, interns)
-        }
-        // Hex method
-        StaticStrings::Hex => bytes_hex(bytes, args, heap, interns),
-        // fromhex is a classmethod but also accessible on instances
-        StaticStrings::Fromhex => bytes_fromhex(args, heap, interns),
-        _ => {
-            args.drop_with_heap(heap);
-            Err(ExcType::attribute_error(Type::Bytes, method.into()))
-        }
+        Replace "replace" (args) => bytes_replace,
+        Center "center" (args) => bytes_center,
+        Ljust "ljust" (args) => bytes_ljust,
+        Rjust "rjust" (args) => bytes_rjust,
+        Zfill "zfill" (one) => bytes_zfill,
+        Join "join" (one) => bytes_join,
+        Hex "hex" (args) => bytes_hex,
+        Fromhex "fromhex" (args) => bytes_fromhex_method,
     }
 }
 
@@ -503,7 +465,8 @@ pub fn bytes_repr(bytes: &[u8]) -> String {
 
 /// Implements Python's `bytes.decode([encoding[, errors]])` method.
 ///
-/// Converts bytes to a string. Currently only supports UTF-8 encoding.
+/// Converts bytes to a string using the UTF-8, ASCII or Latin-1 codec.
+/// See `codecs` for the supported error handlers.
 fn bytes_decode(
     bytes: &[u8],
     args: ArgValues,
@@ -512,47 +475,20 @@ fn bytes_decode(
 ) -> RunResult<Value> {
     let (encoding, errors) = args.get_zero_one_two_args("bytes.decode", heap)?;
     defer_drop!(encoding, heap);
-    defer_drop!(errors, heap); // NB we don't use errors argument yet
+    defer_drop!(errors, heap);
 
-    // Check encoding (default UTF-8)
-    let encoding = if let Some(enc) = encoding {
-        get_encoding_str(enc, heap, interns)?.to_ascii_lowercase()
-    } else {
-        "utf-8".to_owned()
+    let codec = match encoding {
+        Some(enc) => Codec::lookup(&codec_arg("decode", "encoding", enc, heap, interns)?)?,
+        None => Codec::Utf8,
+    };
+    let errors = match errors {
+        Some(errors) => codec_arg("decode", "errors", errors, heap, interns)?,
+        None => "strict".to_owned(),
     };
 
-    // Only support UTF-8 family
-    if !matches!(encoding.as_str(), "utf-8" | "utf8" | "utf_8") {
-        return Err(ExcType::lookup_error_unknown_encoding(&encoding));
-    }
-
-    // Decode as UTF-8
-    match std::str::from_utf8(bytes) {
-        Ok(s) => {
-            let heap_id = heap.allocate(HeapData::Str(Str::from(s.to_owned())))?;
-            Ok(Value::Ref(heap_id))
-        }
-        Err(_) => Err(ExcType::unicode_decode_error_invalid_utf8()),
-    }
-}
-
-/// Helper function to extract encoding string from a value.
-fn get_encoding_str<'a>(
-    encoding: &Value,
-    heap: &'a Heap<impl ResourceTracker>,
-    interns: &'a Interns,
-) -> RunResult<&'a str> {
-    match encoding {
-        Value::InternString(id) => Ok(interns.get_str(*id)),
-        Value::Ref(id) => match heap.get(*id) {
-            HeapData::Str(s) => Ok(s.as_str()),
-            _ => Err(ExcType::type_error(
-                "decode() argument 'encoding' must be str, not bytes",
-            )),
-        },
-        // FIXME: should use proper encoding.py_type() here
-        _ => Err(ExcType::type_error("decode() argument 'encoding' must be str, not int")),
-    }
+    let s = codec.decode(bytes, &errors)?;
+    let heap_id = heap.allocate(HeapData::Str(Str::from(s)))?;
+    Ok(Value::Ref(heap_id))
 }
 
 /// Implements Python's `bytes.count(sub[, start[, end]])` method.
@@ -890,7 +826,7 @@ fn normalize_bytes_index(index: i64, len: usize) -> usize {
 /// Implements Python's `bytes.lower()` method.
 ///
 /// Returns a copy of the bytes with all ASCII uppercase characters converted to lowercase.
-fn bytes_lower(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn bytes_lower(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let result: Vec<u8> = bytes.iter().map(|&b| b.to_ascii_lowercase()).collect();
     allocate_bytes(result, heap)
 }
@@ -898,7 +834,7 @@ fn bytes_lower(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult
 /// Implements Python's `bytes.upper()` method.
 ///
 /// Returns a copy of the bytes with all ASCII lowercase characters converted to uppercase.
-fn bytes_upper(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn bytes_upper(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let result: Vec<u8> = bytes.iter().map(|&b| b.to_ascii_uppercase()).collect();
     allocate_bytes(result, heap)
 }
@@ -907,7 +843,7 @@ fn bytes_upper(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult
 ///
 /// Returns a copy of the bytes with the first byte capitalized (if ASCII) and
 /// the rest lowercased.
-fn bytes_capitalize(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn bytes_capitalize(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let mut result = Vec::with_capacity(bytes.len());
     if let Some((&first, rest)) = bytes.split_first() {
         result.push(first.to_ascii_uppercase());
@@ -922,7 +858,7 @@ fn bytes_capitalize(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunR
 ///
 /// Returns a titlecased version of the bytes where words start with an uppercase
 /// ASCII character and the remaining characters are lowercase.
-fn bytes_title(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn bytes_title(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let mut result = Vec::with_capacity(bytes.len());
     let mut prev_is_cased = false;
 
@@ -942,7 +878,7 @@ fn bytes_title(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult
 ///
 /// Returns a copy of the bytes with ASCII uppercase characters converted to
 /// lowercase and vice versa.
-fn bytes_swapcase(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn bytes_swapcase(bytes: &[u8], heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let result: Vec<u8> = bytes
         .iter()
         .map(|&b| {
@@ -969,6 +905,13 @@ fn bytes_isalpha(bytes: &[u8]) -> bool {
     !bytes.is_empty() && bytes.iter().all(|&b| b.is_ascii_alphabetic())
 }
 
+/// Implements Python's `bytes.isascii()` method.
+///
+/// Returns True if all bytes in the bytes are ASCII, including when there are none.
+fn bytes_isascii(bytes: &[u8]) -> bool {
+    bytes.is_ascii()
+}
+
 /// Implements Python's `bytes.isdigit()` method.
 ///
 /// Returns True if all bytes in the bytes are ASCII digits and there is at least one byte.
@@ -2055,8 +1998,12 @@ fn parse_bytes_justify_args(
 /// Implements Python's `bytes.zfill(width)` method.
 ///
 /// Returns a copy of the bytes left filled with ASCII '0' digits.
-fn bytes_zfill(bytes: &[u8], args: ArgValues, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
-    let width_value = args.get_one_arg("bytes.zfill", heap)?;
+fn bytes_zfill(
+    bytes: &[u8],
+    width_value: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    _interns: &Interns,
+) -> RunResult<Value> {
     defer_drop!(width_value, heap);
     let width_i64 = width_value.as_int(heap)?;
 
@@ -2343,6 +2290,17 @@ pub fn bytes_fromhex(args: ArgValues, heap: &mut Heap<impl ResourceTracker>, int
     allocate_bytes(result, heap)
 }
 
+/// Implements `bytes.fromhex` called on an instance, which ignores the instance like the
+/// classmethod it is.
+fn bytes_fromhex_method(
+    _bytes: &[u8],
+    args: ArgValues,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    bytes_fromhex(args, heap, interns)
+}
+
 /// Converts a hex character to its numeric value.
 fn hex_char_to_value(c: char) -> Option<u8> {
     match c {
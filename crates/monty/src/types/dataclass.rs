@@ -7,7 +7,7 @@ use crate::{
     args::ArgValues,
     defer_drop,
     exception_private::{ExcType, RunResult},
-    heap::{Heap, HeapId},
+    heap::{Heap, HeapId, HeapIdMap},
     intern::{Interns, StringId},
     io::PrintWriter,
     resource::{DepthGuard, ResourceError, ResourceTracker},
@@ -105,6 +105,11 @@ impl Dataclass {
         self.attrs.has_refs()
     }
 
+    /// Rewrites heap ids in the attrs after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        self.attrs.remap_heap_ids(map);
+    }
+
     /// Returns a reference to the attrs Dict.
     #[must_use]
     pub fn attrs(&self) -> &Dict {
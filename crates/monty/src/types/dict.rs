@@ -8,14 +8,14 @@ use ahash::AHashSet;
 use hashbrown::{HashTable, hash_table::Entry};
 use smallvec::smallvec;
 
-use super::{List, MontyIter, PyTrait, allocate_tuple};
+use super::{List, MontyIter, PyTrait, allocate_tuple, methods::py_methods};
 use crate::{
     args::{ArgValues, KwargsValues},
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunResult},
-    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
-    intern::{Interns, StaticStrings},
-    resource::{DepthGuard, ResourceError, ResourceTracker},
+    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId, HeapIdMap},
+    intern::Interns,
+    resource::{CollectionKind, DepthGuard, ResourceError, ResourceTracker},
     types::Type,
     value::{EitherStr, Value},
 };
@@ -107,6 +107,20 @@ impl Dict {
         self.contains_refs
     }
 
+    /// Rewrites heap ids in keys and values after heap compaction.
+    ///
+    /// Entry hashes are kept: a ref key's hash is cached on its heap entry, which moves
+    /// with it, so lookups keep finding the same hash.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        if !self.contains_refs {
+            return;
+        }
+        for entry in &mut self.entries {
+            map.remap_value(&mut entry.key);
+            map.remap_value(&mut entry.value);
+        }
+    }
+
     /// Creates a dict from a vector of (key, value) pairs.
     ///
     /// Assumes the caller is transferring ownership of all keys and values in the pairs.
@@ -152,6 +166,12 @@ impl Dict {
     /// This is an O(1) lookup that doesn't require mutable heap access.
     /// Only works for string keys - returns None if the key is not found.
     pub fn get_by_str(&self, key_str: &str, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> Option<&Value> {
+        self.index_of_str(key_str, heap, interns)
+            .map(|idx| &self.entries[idx].value)
+    }
+
+    /// Returns the position in insertion order of the string key `key_str`, if present.
+    pub fn index_of_str(&self, key_str: &str, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> Option<usize> {
         // Compute hash for the string key
         let mut hasher = DefaultHasher::new();
         key_str.hash(&mut hasher);
@@ -159,21 +179,24 @@ impl Dict {
 
         // Find entry with matching hash and key
         self.indices
-            .find(hash, |&idx| {
-                let entry_key = &self.entries[idx].key;
-                match entry_key {
-                    Value::InternString(id) => interns.get_str(*id) == key_str,
-                    Value::Ref(id) => {
-                        if let HeapData::Str(s) = heap.get(*id) {
-                            s.as_str() == key_str
-                        } else {
-                            false
-                        }
-                    }
-                    _ => false,
-                }
-            })
-            .map(|&idx| &self.entries[idx].value)
+            .find(hash, |&idx| key_is_str(&self.entries[idx].key, key_str, heap, interns))
+            .copied()
+    }
+
+    /// Returns the value at position `index` if its key is the string `key_str`.
+    ///
+    /// Lets a caller that remembered a key's position from `index_of_str` read it again
+    /// without hashing. Returns `None` once the key has moved, so the caller can fall
+    /// back to a full lookup.
+    pub fn get_by_str_at(
+        &self,
+        index: usize,
+        key_str: &str,
+        heap: &Heap<impl ResourceTracker>,
+        interns: &Interns,
+    ) -> Option<&Value> {
+        let entry = self.entries.get(index)?;
+        key_is_str(&entry.key, key_str, heap, interns).then_some(&entry.value)
     }
 
     /// Sets a key-value pair in the dict.
@@ -184,7 +207,8 @@ impl Dict {
     ///
     /// If the key already exists, replaces the old value and returns it (caller now
     /// owns the old value and is responsible for its refcount).
-    /// Returns Err if key is unhashable.
+    /// Returns Err if key is unhashable, or if adding a new key would exceed the tracker's
+    /// maximum dict size.
     pub fn set(
         &mut self,
         key: Value,
@@ -218,6 +242,14 @@ impl Dict {
             // Transfer ownership of the old value to caller (no clone needed)
             Ok(Some(old_entry.value))
         } else {
+            if let Err(err) = heap
+                .tracker()
+                .check_collection_len(CollectionKind::Dict, self.entries.len() + 1)
+            {
+                entry.key.drop_with_heap(heap);
+                entry.value.drop_with_heap(heap);
+                return Err(err.into());
+            }
             // Key doesn't exist, add new pair to indices and entries
             let index = self.entries.len();
             self.entries.push(entry);
@@ -397,6 +429,21 @@ impl Dict {
     }
 }
 
+/// Returns whether the dict key `key` is a string equal to `key_str`.
+fn key_is_str(key: &Value, key_str: &str, heap: &Heap<impl ResourceTracker>, interns: &Interns) -> bool {
+    match key {
+        Value::InternString(id) => interns.get_str(*id) == key_str,
+        Value::Ref(id) => {
+            if let HeapData::Str(s) = heap.get(*id) {
+                s.as_str() == key_str
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
 /// Iterator over borrowed (key, value) pairs in a dict.
 pub(crate) struct DictIter<'a>(std::slice::Iter<'a, DictEntry>);
 
@@ -579,92 +626,24 @@ impl PyTrait for Dict {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        let Some(method) = attr.static_string() else {
-            args.drop_with_heap(heap);
-            return Err(ExcType::attribute_error(Type::Dict, attr.as_str(interns)));
-        };
+        call_dict_method(self, attr, args, heap, interns)
+    }
+}
 
-        match method {
-            StaticStrings::Get => {
-                // dict.get() accepts 1 or 2 arguments
-                let (key, default) = args.get_one_two_args("get", heap)?;
-                defer_drop!(key, heap);
-                let default = default.unwrap_or(Value::None);
-                let mut default_guard = HeapGuard::new(default, heap);
-                let heap = default_guard.heap();
-                // Handle the lookup - may fail for unhashable keys
-                let value = match self.get(key, heap, interns)? {
-                    Some(v) => v.clone_with_heap(heap),
-                    None => default_guard.into_inner(),
-                };
-                Ok(value)
-            }
-            StaticStrings::Keys => {
-                args.check_zero_args("dict.keys", heap)?;
-                let keys = self.keys(heap);
-                let list_id = heap.allocate(HeapData::List(List::new(keys)))?;
-                Ok(Value::Ref(list_id))
-            }
-            StaticStrings::Values => {
-                args.check_zero_args("dict.values", heap)?;
-                let values = self.values(heap);
-                let list_id = heap.allocate(HeapData::List(List::new(values)))?;
-                Ok(Value::Ref(list_id))
-            }
-            StaticStrings::Items => {
-                args.check_zero_args("dict.items", heap)?;
-                // Return list of tuples
-                let tuples = self
-                    .items()
-                    .into_iter()
-                    .map(|(k, v)| allocate_tuple(smallvec![k.clone_with_heap(heap), v.clone_with_heap(heap)], heap))
-                    .collect::<Result<_, _>>()?;
-                let list_id = heap.allocate(HeapData::List(List::new(tuples)))?;
-                Ok(Value::Ref(list_id))
-            }
-            StaticStrings::Pop => {
-                // dict.pop() accepts 1 or 2 arguments (key, optional default)
-                let (key, default) = args.get_one_two_args("pop", heap)?;
-                defer_drop!(key, heap);
-                let mut default_guard = HeapGuard::new(default, heap);
-                let heap = default_guard.heap();
-                if let Some((old_key, value)) = self.pop(key, heap, interns)? {
-                    // Drop the old key - we don't need it
-                    old_key.drop_with_heap(heap);
-                    Ok(value)
-                } else {
-                    let (default, heap) = default_guard.into_parts();
-                    // No matching key - return default if provided, else KeyError
-                    if let Some(d) = default {
-                        Ok(d)
-                    } else {
-                        let err = ExcType::key_error(key, heap, interns);
-                        Err(err)
-                    }
-                }
-            }
-            StaticStrings::Clear => {
-                args.check_zero_args("dict.clear", heap)?;
-                dict_clear(self, heap);
-                Ok(Value::None)
-            }
-            StaticStrings::Copy => {
-                args.check_zero_args("dict.copy", heap)?;
-                dict_copy(self, heap, interns)
-            }
-            StaticStrings::Update => dict_update(self, args, heap, interns),
-            StaticStrings::Setdefault => dict_setdefault(self, args, heap, interns),
-            StaticStrings::Popitem => {
-                args.check_zero_args("dict.popitem", heap)?;
-                dict_popitem(self, heap)
-            }
-            // fromkeys is a classmethod but also accessible on instances
-            StaticStrings::Fromkeys => dict_fromkeys(args, heap, interns),
-            _ => {
-                args.drop_with_heap(heap);
-                Err(ExcType::attribute_error(Type::Dict, attr.as_str(interns)))
-            }
-        }
+py_methods! {
+    /// Dispatches a method call on a dict value.
+    fn call_dict_method(&mut Dict, Type::Dict, "dict") {
+        Get "get" (one_or_two) => dict_get,
+        Keys "keys" (none) => dict_keys,
+        Values "values" (none) => dict_values,
+        Items "items" (none) => dict_items,
+        Pop "pop" (one_or_two) => dict_pop,
+        Clear "clear" (none) => dict_clear,
+        Copy "copy" (none) => dict_copy,
+        Update "update" (args) => dict_update,
+        Setdefault "setdefault" (one_or_two) => dict_setdefault,
+        Popitem "popitem" (none) => dict_popitem,
+        Fromkeys "fromkeys" (args) => dict_fromkeys_method,
     }
 }
 
@@ -677,16 +656,100 @@ impl DropWithHeap for Dict {
     }
 }
 
+/// Implements Python's `dict.get(key[, default])` method.
+///
+/// Returns the value for `key`, or `default` (None if not given) if the key is missing.
+fn dict_get(
+    dict: &Dict,
+    key: Value,
+    default: Option<Value>,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(key, heap);
+    let default = default.unwrap_or(Value::None);
+    let mut default_guard = HeapGuard::new(default, heap);
+    let heap = default_guard.heap();
+    // Handle the lookup - may fail for unhashable keys
+    let value = match dict.get(key, heap, interns)? {
+        Some(v) => v.clone_with_heap(heap),
+        None => default_guard.into_inner(),
+    };
+    Ok(value)
+}
+
+/// Implements Python's `dict.keys()` method.
+///
+/// Returns the keys as a list.
+fn dict_keys(dict: &Dict, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    let keys = dict.keys(heap);
+    let list_id = heap.allocate(HeapData::List(List::new(keys)))?;
+    Ok(Value::Ref(list_id))
+}
+
+/// Implements Python's `dict.values()` method.
+///
+/// Returns the values as a list.
+fn dict_values(dict: &Dict, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    let values = dict.values(heap);
+    let list_id = heap.allocate(HeapData::List(List::new(values)))?;
+    Ok(Value::Ref(list_id))
+}
+
+/// Implements Python's `dict.items()` method.
+///
+/// Returns the `(key, value)` pairs as a list of tuples.
+fn dict_items(dict: &Dict, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    let tuples = dict
+        .items()
+        .into_iter()
+        .map(|(k, v)| allocate_tuple(smallvec![k.clone_with_heap(heap), v.clone_with_heap(heap)], heap))
+        .collect::<Result<_, _>>()?;
+    let list_id = heap.allocate(HeapData::List(List::new(tuples)))?;
+    Ok(Value::Ref(list_id))
+}
+
+/// Implements Python's `dict.pop(key[, default])` method.
+///
+/// Removes `key` and returns its value, or returns `default` if the key is missing.
+/// Raises KeyError if the key is missing and no default is given.
+fn dict_pop(
+    dict: &mut Dict,
+    key: Value,
+    default: Option<Value>,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(key, heap);
+    let mut default_guard = HeapGuard::new(default, heap);
+    let heap = default_guard.heap();
+    if let Some((old_key, value)) = dict.pop(key, heap, interns)? {
+        // Drop the old key - we don't need it
+        old_key.drop_with_heap(heap);
+        Ok(value)
+    } else {
+        let (default, heap) = default_guard.into_parts();
+        // No matching key - return default if provided, else KeyError
+        if let Some(d) = default {
+            Ok(d)
+        } else {
+            let err = ExcType::key_error(key, heap, interns);
+            Err(err)
+        }
+    }
+}
+
 /// Implements Python's `dict.clear()` method.
 ///
 /// Removes all items from the dict.
-fn dict_clear(dict: &mut Dict, heap: &mut Heap<impl ResourceTracker>) {
+fn dict_clear(dict: &mut Dict, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     for entry in dict.entries.drain(..) {
         entry.key.drop_with_heap(heap);
         entry.value.drop_with_heap(heap);
     }
     dict.indices.clear();
     // Note: contains_refs stays true even if all refs removed, per conservative GC strategy
+    Ok(Value::None)
 }
 
 /// Implements Python's `dict.copy()` method.
@@ -847,11 +910,11 @@ fn dict_update_from_kwargs(
 /// If not, insert key with a value of default (or None) and return default.
 fn dict_setdefault(
     dict: &mut Dict,
-    args: ArgValues,
+    key: Value,
+    default: Option<Value>,
     heap: &mut Heap<impl ResourceTracker>,
     interns: &Interns,
 ) -> RunResult<Value> {
-    let (key, default) = args.get_one_two_args("setdefault", heap)?;
     let default = default.unwrap_or(Value::None);
     let mut key_guard = HeapGuard::new(key, heap);
     let (key, heap) = key_guard.as_parts();
@@ -877,7 +940,7 @@ fn dict_setdefault(
 ///
 /// Removes and returns the last inserted key-value pair as a tuple.
 /// Raises KeyError if the dict is empty.
-fn dict_popitem(dict: &mut Dict, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn dict_popitem(dict: &mut Dict, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     if dict.is_empty() {
         return Err(ExcType::key_error_popitem_empty_dict());
     }
@@ -967,3 +1030,14 @@ pub fn dict_fromkeys(args: ArgValues, heap: &mut Heap<impl ResourceTracker>, int
     let heap_id = heap.allocate(HeapData::Dict(dict))?;
     Ok(Value::Ref(heap_id))
 }
+
+/// Implements `dict.fromkeys` called on an instance, which ignores the instance like the
+/// classmethod it is.
+fn dict_fromkeys_method(
+    _dict: &Dict,
+    args: ArgValues,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    dict_fromkeys(args, heap, interns)
+}
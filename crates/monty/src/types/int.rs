@@ -0,0 +1,224 @@
+//! Methods of Python's `int` type.
+//!
+//! Integers are stored as `Value::Int(i64)` (or `Value::Bool`) when they fit and as
+//! heap-allocated [`LongInt`](super::LongInt) otherwise, so every method here works on a
+//! `BigInt` and both representations share one implementation.
+//!
+//! # Implemented Methods
+//! - `bit_length()` - Number of bits needed to represent the absolute value
+//! - `bit_count()` - Number of ones in the binary representation of the absolute value
+//! - `to_bytes(length=1, byteorder='big', *, signed=False)` - Encode as bytes
+//! - `from_bytes(bytes, byteorder='big', *, signed=False)` - Decode from bytes (classmethod)
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{Signed, Zero};
+
+use crate::{
+    args::ArgValues,
+    defer_drop, defer_drop_mut,
+    exception_private::{ExcType, RunError, RunResult, SimpleException},
+    heap::{DropWithHeap, Heap, HeapData, HeapGuard},
+    intern::{Interns, StaticStrings},
+    resource::{ResourceTracker, check_repeat_size},
+    types::{Bytes, LongInt, PyTrait, Type},
+    value::{EitherStr, Value},
+};
+
+/// Calls an int method on an integer value.
+///
+/// Used for `Value::Int` and `Value::Bool` receivers from the VM and for heap-allocated
+/// `LongInt` receivers via `HeapData::py_call_attr`.
+pub fn call_int_method(
+    value: &BigInt,
+    attr: &EitherStr,
+    args: ArgValues,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    match attr.static_string() {
+        Some(StaticStrings::BitLength) => {
+            args.check_zero_args("int.bit_length", heap)?;
+            Ok(Value::Int(bits_to_int(value.bits())))
+        }
+        Some(StaticStrings::BitCount) => {
+            args.check_zero_args("int.bit_count", heap)?;
+            Ok(Value::Int(bits_to_int(value.magnitude().count_ones())))
+        }
+        Some(StaticStrings::ToBytes) => int_to_bytes(value, args, heap, interns),
+        // from_bytes is a classmethod but also accessible on instances
+        Some(StaticStrings::FromBytes) => int_from_bytes(args, heap, interns),
+        _ => {
+            args.drop_with_heap(heap);
+            Err(ExcType::attribute_error(Type::Int, attr.as_str(interns)))
+        }
+    }
+}
+
+/// Implements Python's `int.to_bytes(length=1, byteorder='big', *, signed=False)`.
+///
+/// Negative values are written in two's complement and need `signed=True`. Raises
+/// `OverflowError` if the value doesn't fit in `length` bytes.
+fn int_to_bytes(
+    value: &BigInt,
+    args: ArgValues,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    let (length, little_endian, signed) = parse_byteorder_args("to_bytes", "length", args, heap, interns)?;
+    let length = match length {
+        Some(length) => {
+            defer_drop!(length, heap);
+            length.as_int(heap)?
+        }
+        None => 1,
+    };
+    let Ok(length) = usize::try_from(length) else {
+        return Err(SimpleException::new_msg(ExcType::ValueError, "length argument must be non-negative").into());
+    };
+
+    if !signed && value.is_negative() {
+        return Err(SimpleException::new_msg(ExcType::OverflowError, "can't convert negative int to unsigned").into());
+    }
+    // zero needs no bytes at all, so `(0).to_bytes(0)` is valid
+    let minimal = if value.is_zero() {
+        Vec::new()
+    } else if signed {
+        value.to_signed_bytes_be()
+    } else {
+        value.to_bytes_be().1
+    };
+    if minimal.len() > length {
+        return Err(SimpleException::new_msg(ExcType::OverflowError, "int too big to convert").into());
+    }
+    check_repeat_size(length, 1, heap.tracker())?;
+
+    let fill = if value.is_negative() { 0xff } else { 0x00 };
+    let mut bytes = vec![fill; length - minimal.len()];
+    bytes.extend_from_slice(&minimal);
+    if little_endian {
+        bytes.reverse();
+    }
+    Ok(Value::Ref(heap.allocate(HeapData::Bytes(Bytes::new(bytes)))?))
+}
+
+/// Implements Python's `int.from_bytes(bytes, byteorder='big', *, signed=False)` classmethod.
+///
+/// With `signed=True` the bytes are read as two's complement.
+pub fn int_from_bytes(args: ArgValues, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
+    let (data, little_endian, signed) = parse_byteorder_args("from_bytes", "bytes", args, heap, interns)?;
+    let Some(data) = data else {
+        return Err(ExcType::type_error(
+            "from_bytes() missing required argument 'bytes' (pos 1)",
+        ));
+    };
+    defer_drop!(data, heap);
+
+    let bytes = match data {
+        Value::InternBytes(id) => interns.get_bytes(*id),
+        Value::Ref(id) => match heap.get(*id) {
+            HeapData::Bytes(b) => b.as_slice(),
+            _ => return Err(cannot_convert_to_bytes(data, heap)),
+        },
+        _ => return Err(cannot_convert_to_bytes(data, heap)),
+    };
+    let value = match (little_endian, signed) {
+        (false, false) => BigInt::from_bytes_be(Sign::Plus, bytes),
+        (true, false) => BigInt::from_bytes_le(Sign::Plus, bytes),
+        (false, true) => BigInt::from_signed_bytes_be(bytes),
+        (true, true) => BigInt::from_signed_bytes_le(bytes),
+    };
+    Ok(LongInt::new(value).into_value(heap)?)
+}
+
+/// Parses the `(first, byteorder='big', *, signed=False)` signature shared by
+/// `to_bytes()` and `from_bytes()`.
+///
+/// Returns the first argument if given, whether the byte order is little-endian, and
+/// the truthiness of `signed`.
+fn parse_byteorder_args(
+    method: &str,
+    first_name: &str,
+    args: ArgValues,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<(Option<Value>, bool, bool)> {
+    let (pos_iter, kwargs) = args.into_parts();
+    defer_drop_mut!(pos_iter, heap);
+    let kwargs_iter = kwargs.into_iter();
+    defer_drop_mut!(kwargs_iter, heap);
+
+    let first_value = pos_iter.next();
+    defer_drop_mut!(first_value, heap);
+    let byteorder_value = pos_iter.next();
+    defer_drop_mut!(byteorder_value, heap);
+    let signed_value: Option<Value> = None;
+    defer_drop_mut!(signed_value, heap);
+
+    if pos_iter.len() != 0 {
+        return Err(ExcType::type_error_at_most(method, 2, 2 + pos_iter.len()));
+    }
+
+    for (key, value) in kwargs_iter {
+        defer_drop!(key, heap);
+        let mut value_guard = HeapGuard::new(value, heap);
+
+        let Some(keyword_name) = key.as_either_str(value_guard.heap()) else {
+            return Err(ExcType::type_error("keywords must be strings"));
+        };
+
+        let key_str = keyword_name.as_str(interns);
+        let slot = if key_str == first_name {
+            &mut *first_value
+        } else if key_str == "byteorder" {
+            &mut *byteorder_value
+        } else if key_str == "signed" {
+            &mut *signed_value
+        } else {
+            return Err(ExcType::type_error(format!(
+                "'{key_str}' is an invalid keyword argument for {method}()"
+            )));
+        };
+        if let Some(previous_value) = slot.replace(value_guard.into_inner()) {
+            previous_value.drop_with_heap(heap);
+            return Err(ExcType::type_error(format!(
+                "argument for {method}() given by name ('{key_str}') and position"
+            )));
+        }
+    }
+
+    let little_endian = match byteorder_value {
+        None => false,
+        Some(value) => {
+            let Some(byteorder) = value.as_either_str(heap) else {
+                let t = value.py_type(heap);
+                return Err(ExcType::type_error(format!(
+                    "{method}() argument 'byteorder' must be str, not {t}"
+                )));
+            };
+            match byteorder.as_str(interns) {
+                "big" => false,
+                "little" => true,
+                _ => {
+                    return Err(SimpleException::new_msg(
+                        ExcType::ValueError,
+                        "byteorder must be either 'little' or 'big'",
+                    )
+                    .into());
+                }
+            }
+        }
+    };
+    let signed = signed_value.as_ref().is_some_and(|value| value.py_bool(heap, interns));
+
+    Ok((first_value.take(), little_endian, signed))
+}
+
+/// Creates the `TypeError` for a `from_bytes()` argument that isn't bytes.
+fn cannot_convert_to_bytes(value: &Value, heap: &Heap<impl ResourceTracker>) -> RunError {
+    ExcType::type_error(format!("cannot convert '{}' object to bytes", value.py_type(heap)))
+}
+
+/// Converts a bit count to a Python int.
+fn bits_to_int(bits: u64) -> i64 {
+    i64::try_from(bits).expect("bit count exceeds i64::MAX")
+}
@@ -26,15 +26,24 @@
 //! This allows `advance_on_heap()` to coordinate access without extracting
 //! the iterator from the heap (avoiding `std::mem::replace` overhead).
 //!
+//! ## Host Iterables
+//!
+//! Iterating over a host iterable (see `CompileOptions::host_iterables`) yields the items
+//! the host has handed over so far. When they run out, `advance_for_loop()` tells the VM to
+//! wait for the host to hand over more; every other consumer raises `TypeError`.
+//!
 //! ## Builtin Support
 //!
 //! The `iterator_next()` helper implements the `next()` builtin.
 
+use std::collections::VecDeque;
+
 use crate::{
     args::ArgValues,
-    exception_private::{ExcType, RunResult},
-    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
-    intern::{BytesId, Interns, StringId},
+    exception_private::{ExcType, RunError, RunResult, SimpleException},
+    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId, HeapIdMap},
+    intern::{BytesId, ExtFunctionId, Interns, StringId},
+    object::MontyObject,
     resource::ResourceTracker,
     types::{PyTrait, Range, str::allocate_char},
     value::Value,
@@ -124,6 +133,15 @@ impl MontyIter {
         self.value.py_dec_ref_ids(stack);
     }
 
+    /// Rewrites the iterated value's heap id, and the copy cached for container iteration,
+    /// after heap compaction.
+    pub fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        map.remap_value(&mut self.value);
+        if let IterValue::HeapRef { heap_id, .. } = &mut self.iter_value {
+            map.remap(heap_id);
+        }
+    }
+
     /// Returns whether this iterator holds a heap reference (`Value::Ref`).
     ///
     /// Used during allocation to determine if this container could create cycles.
@@ -140,6 +158,24 @@ impl MontyIter {
         &self.value
     }
 
+    /// Hands items from the host to an iterator over a host iterable, `exhausted` marking
+    /// that the host has no more.
+    ///
+    /// Returns `false`, leaving the iterator unchanged, if it isn't over a host iterable.
+    pub fn feed_host(&mut self, items: Vec<MontyObject>, exhausted: bool) -> bool {
+        let IterValue::Host {
+            ready,
+            exhausted: host_exhausted,
+            ..
+        } = &mut self.iter_value
+        else {
+            return false;
+        };
+        ready.extend(items);
+        *host_exhausted |= exhausted;
+        true
+    }
+
     /// Returns the current iterator state without mutation.
     ///
     /// This is used by the multi-phase approach in `advance_on_heap()` for complex
@@ -149,9 +185,10 @@ impl MontyIter {
     /// Returns `None` if the iterator is exhausted.
     fn iter_state(&self) -> Option<IterState> {
         match &self.iter_value {
-            // Range, InternBytes, and ASCII IterStr are handled by try_advance_simple() fast path
-            IterValue::Range { .. } | IterValue::InternBytes { .. } => {
-                unreachable!("Range and InternBytes use fast path, not iter_state")
+            // Range, InternBytes, and ASCII IterStr are handled by try_advance_simple() fast path,
+            // and Host by advance_for_loop() itself
+            IterValue::Range { .. } | IterValue::InternBytes { .. } | IterValue::Host { .. } => {
+                unreachable!("Range, InternBytes and Host use fast path, not iter_state")
             }
             IterValue::IterStr {
                 string,
@@ -259,7 +296,7 @@ impl MontyIter {
                     Some(Ok(Some(Value::Int(i64::from(bytes[i])))))
                 }
             }
-            IterValue::HeapRef { .. } => None,
+            IterValue::HeapRef { .. } | IterValue::Host { .. } => None,
         }
     }
 
@@ -338,6 +375,15 @@ impl MontyIter {
                 self.index += 1;
                 Ok(Some(clone_and_inc_ref(item, heap)))
             }
+            IterValue::Host {
+                ext_function_id,
+                ready,
+                exhausted,
+            } => match ready.pop_front() {
+                Some(item) => host_item_value(item, heap, interns).map(Some),
+                None if *exhausted => Ok(None),
+                None => Err(host_iterable_not_ready(*ext_function_id, interns)),
+            },
         }
     }
 
@@ -358,6 +404,8 @@ impl MontyIter {
                     list.len()
                 })
             }
+            // items are taken off the front of `ready`, so the index stays at zero
+            IterValue::Host { ready, .. } => ready.len(),
         };
         len.saturating_sub(self.index)
     }
@@ -394,8 +442,45 @@ impl<T: ResourceTracker> Iterator for HeapedMontyIter<'_, T> {
     }
 }
 
+/// The outcome of advancing an iterator for a `for` loop.
+#[derive(Debug)]
+pub(crate) enum ForLoopStep {
+    /// The next item.
+    Item(Value),
+    /// The iterator has no more items.
+    Exhausted,
+    /// The iterator is over a host iterable that has no items ready, so the loop has to wait
+    /// for the host to hand over more.
+    WaitOnHost(ExtFunctionId),
+}
+
+impl From<Option<Value>> for ForLoopStep {
+    fn from(next: Option<Value>) -> Self {
+        next.map_or(Self::Exhausted, Self::Item)
+    }
+}
+
 /// Advances an iterator stored on the heap and returns the next value.
 ///
+/// Raises `TypeError` when an iterator over a host iterable runs out of the items the host
+/// handed over, since only `for` loops can wait for more; see `advance_for_loop()`.
+///
+/// Returns `Ok(None)` when the iterator is exhausted.
+/// Returns `Err` for dict/set size changes or allocation failures.
+pub(crate) fn advance_on_heap(
+    heap: &mut Heap<impl ResourceTracker>,
+    iter_id: HeapId,
+    interns: &Interns,
+) -> RunResult<Option<Value>> {
+    match advance_for_loop(heap, iter_id, interns)? {
+        ForLoopStep::Item(value) => Ok(Some(value)),
+        ForLoopStep::Exhausted => Ok(None),
+        ForLoopStep::WaitOnHost(ext_function_id) => Err(host_iterable_not_ready(ext_function_id, interns)),
+    }
+}
+
+/// Advances an iterator stored on the heap for a `for` loop.
+///
 /// Uses a fast path for simple iterators (Range, InternBytes, ASCII IterStr) that don't need
 /// additional heap access - these are handled with a single mutable borrow.
 ///
@@ -407,13 +492,15 @@ impl<T: ResourceTracker> Iterator for HeapedMontyIter<'_, T> {
 /// This is more efficient than `std::mem::replace` with a placeholder because
 /// it avoids creating and moving placeholder objects on every iteration.
 ///
-/// Returns `Ok(None)` when the iterator is exhausted.
+/// Iterators over host iterables take the next item the host handed over, or return
+/// `ForLoopStep::WaitOnHost` if there is none and the host hasn't said there are no more.
+///
 /// Returns `Err` for dict/set size changes or allocation failures.
-pub(crate) fn advance_on_heap(
+pub(crate) fn advance_for_loop(
     heap: &mut Heap<impl ResourceTracker>,
     iter_id: HeapId,
     interns: &Interns,
-) -> RunResult<Option<Value>> {
+) -> RunResult<ForLoopStep> {
     // Fast path: Range and InternBytes don't need additional heap access,
     // so we can handle them with a single mutable borrow.
     {
@@ -421,7 +508,20 @@ pub(crate) fn advance_on_heap(
             panic!("advance_on_heap: expected Iterator on heap");
         };
         if let Some(result) = iter.try_advance_simple(interns) {
-            return result;
+            return result.map(Into::into);
+        }
+        if let IterValue::Host {
+            ext_function_id,
+            ready,
+            exhausted,
+        } = &mut iter.iter_value
+        {
+            let (ext_function_id, exhausted, item) = (*ext_function_id, *exhausted, ready.pop_front());
+            return match item {
+                Some(item) => host_item_value(item, heap, interns).map(ForLoopStep::Item),
+                None if exhausted => Ok(ForLoopStep::Exhausted),
+                None => Ok(ForLoopStep::WaitOnHost(ext_function_id)),
+            };
         }
     }
     // Mutable borrow ends here, allowing the multi-phase approach below
@@ -432,7 +532,7 @@ pub(crate) fn advance_on_heap(
         panic!("advance_on_heap: expected Iterator on heap");
     };
     let Some(state) = iter.iter_state() else {
-        return Ok(None); // Iterator exhausted
+        return Ok(ForLoopStep::Exhausted);
     };
 
     // Phase 2: Based on state, get the value and determine char_len for strings
@@ -449,7 +549,7 @@ pub(crate) fn advance_on_heap(
             let item = get_heap_item(heap, heap_id, index, expected_len)?;
             // Check for list exhaustion (list can shrink during iteration)
             let Some(item) = item else {
-                return Ok(None);
+                return Ok(ForLoopStep::Exhausted);
             };
             // Inc refcount after borrow ends
             if let Value::Ref(id) = &item {
@@ -465,7 +565,23 @@ pub(crate) fn advance_on_heap(
     };
     iter.advance(string_char_len);
 
-    Ok(Some(value))
+    Ok(ForLoopStep::Item(value))
+}
+
+/// Converts an item the host handed over for a host iterable into a value.
+fn host_item_value(item: MontyObject, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
+    item.to_value(heap, interns).map_err(|e| {
+        SimpleException::new(ExcType::RuntimeError, Some(format!("invalid host iterable item: {e}"))).into()
+    })
+}
+
+/// The error raised when something other than a `for` loop runs out of the items a host
+/// iterable's host handed over, since only loops can wait for more.
+fn host_iterable_not_ready(ext_function_id: ExtFunctionId, interns: &Interns) -> RunError {
+    let name = interns.get_external_function_name(ext_function_id);
+    ExcType::type_error(format!(
+        "host iterable '{name}' can only be consumed by a for loop or comprehension"
+    ))
 }
 
 /// Gets an item from a heap-allocated container at the given index.
@@ -662,6 +778,15 @@ enum IterValue {
         len: Option<usize>,
         checks_mutation: bool,
     },
+    /// Iterating over a host iterable, yields the items the host has handed over.
+    ///
+    /// - `ready`: items handed over and not yet yielded.
+    /// - `exhausted`: whether the host has said there are no more items.
+    Host {
+        ext_function_id: ExtFunctionId,
+        ready: VecDeque<MontyObject>,
+        exhausted: bool,
+    },
 }
 
 impl IterValue {
@@ -670,6 +795,11 @@ impl IterValue {
             Value::InternString(string_id) => Some(Self::from_str(interns.get_str(*string_id))),
             Value::InternBytes(bytes_id) => Some(Self::from_intern_bytes(*bytes_id, interns)),
             Value::Ref(heap_id) => Self::from_heap_data(*heap_id, heap),
+            Value::ExtFunction(ext_function_id) if interns.is_external_iterable(*ext_function_id) => Some(Self::Host {
+                ext_function_id: *ext_function_id,
+                ready: VecDeque::new(),
+                exhausted: false,
+            }),
             _ => None,
         }
     }
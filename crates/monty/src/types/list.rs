@@ -1,18 +1,18 @@
-use std::{cmp::Ordering, fmt::Write};
+use std::{cmp::Ordering, fmt::Write, mem::ManuallyDrop};
 
 use ahash::AHashSet;
 use smallvec::SmallVec;
 
-use super::{AttrCallResult, MontyIter, PyTrait};
+use super::{AttrCallResult, MontyIter, PyTrait, methods::py_methods};
 use crate::{
     args::ArgValues,
     builtins::Builtins,
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunError, RunResult},
-    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
+    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId, HeapIdMap},
     intern::{Interns, StaticStrings},
     io::PrintWriter,
-    resource::{DepthGuard, ResourceError, ResourceTracker},
+    resource::{CollectionKind, DepthGuard, ResourceError, ResourceTracker},
     types::Type,
     value::{EitherStr, Value},
 };
@@ -51,6 +51,12 @@ use crate::{
 /// This allows `collect_child_ids` and `py_dec_ref_ids` to skip iteration when the
 /// list contains only primitive values (ints, bools, None, etc.), significantly
 /// improving GC performance for lists of primitives.
+///
+/// # Slicing and Repetition
+/// Every list owns its items outright: `l[a:b]` and `l * n` build a new vector and take
+/// one reference per copied item. Lists never share a backing vector, since refcounts,
+/// GC marking, heap compaction and snapshots all treat each heap entry as the sole owner
+/// of its children, and every mutating method writes through `items` directly.
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub(crate) struct List {
     items: Vec<Value>,
@@ -93,6 +99,13 @@ impl List {
         &mut self.items
     }
 
+    /// Rewrites heap ids in the list's items after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        if self.contains_refs {
+            map.remap_values(&mut self.items);
+        }
+    }
+
     /// Returns the number of elements in the list.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -123,8 +136,24 @@ impl List {
     /// is NOT incremented here - the caller is responsible for ensuring the refcount
     /// was already incremented (e.g., via `clone_with_heap` or `evaluate_use`).
     ///
-    /// Returns `Value::None`, matching Python's behavior where `list.append()` returns None.
-    pub fn append(&mut self, heap: &mut Heap<impl ResourceTracker>, item: Value) {
+    /// Returns `Err(ResourceError::CollectionSize)` (after dropping `item`) if the list would
+    /// exceed the tracker's maximum list length.
+    pub fn append(&mut self, heap: &mut Heap<impl ResourceTracker>, item: Value) -> Result<(), ResourceError> {
+        if let Err(err) = heap
+            .tracker()
+            .check_collection_len(CollectionKind::List, self.items.len() + 1)
+        {
+            item.drop_with_heap(heap);
+            return Err(err);
+        }
+        self.push(heap, item);
+        Ok(())
+    }
+
+    /// Pushes an element without checking the list length limit.
+    ///
+    /// Callers that add several items at once check the final length up front, then push.
+    fn push(&mut self, heap: &mut Heap<impl ResourceTracker>, item: Value) {
         // Track if we're adding a reference and mark potential cycle
         if matches!(item, Value::Ref(_)) {
             self.contains_refs = true;
@@ -144,8 +173,21 @@ impl List {
     /// * `index` - The position to insert at (0-based). If index >= len(),
     ///   the item is appended to the end (matching Python semantics).
     ///
-    /// Returns `Value::None`, matching Python's behavior where `list.insert()` returns None.
-    pub fn insert(&mut self, heap: &mut Heap<impl ResourceTracker>, index: usize, item: Value) {
+    /// Returns `Err(ResourceError::CollectionSize)` (after dropping `item`) if the list would
+    /// exceed the tracker's maximum list length.
+    pub fn insert(
+        &mut self,
+        heap: &mut Heap<impl ResourceTracker>,
+        index: usize,
+        item: Value,
+    ) -> Result<(), ResourceError> {
+        if let Err(err) = heap
+            .tracker()
+            .check_collection_len(CollectionKind::List, self.items.len() + 1)
+        {
+            item.drop_with_heap(heap);
+            return Err(err);
+        }
         // Track if we're adding a reference and mark potential cycle
         if matches!(item, Value::Ref(_)) {
             self.contains_refs = true;
@@ -158,6 +200,7 @@ impl List {
         } else {
             self.items.insert(index, item);
         }
+        Ok(())
     }
 
     /// Creates a list from the `list()` constructor call.
@@ -377,6 +420,13 @@ impl PyTrait for List {
         let Value::Ref(other_id) = &other else { return Ok(false) };
 
         if Some(*other_id) == self_id {
+            if let Err(err) = heap
+                .tracker()
+                .check_collection_len(CollectionKind::List, self.items.len() * 2)
+            {
+                other.drop_with_heap(heap);
+                return Err(err);
+            }
             // Self-extend: clone our own items with proper refcounting
             let items = self
                 .items
@@ -395,6 +445,17 @@ impl PyTrait for List {
             if !heap.iadd_extend_list(*other_id, &mut self.items) {
                 return Ok(false);
             }
+            if let Err(err) = heap
+                .tracker()
+                .check_collection_len(CollectionKind::List, self.items.len())
+            {
+                // Undo the extend so the list is left unchanged
+                for item in self.items.drain(prev_len..) {
+                    item.drop_with_heap(heap);
+                }
+                other.drop_with_heap(heap);
+                return Err(err);
+            }
             // Check if we added any refs and mark potential cycle
             if self.contains_refs {
                 // Already had refs, but adding more may create cycles
@@ -422,13 +483,7 @@ impl PyTrait for List {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        let args_guard = HeapGuard::new(args, heap);
-        let Some(method) = attr.static_string() else {
-            return Err(ExcType::attribute_error(Type::List, attr.as_str(interns)));
-        };
-
-        let (args, heap) = args_guard.into_parts();
-        call_list_method(self, method, args, heap, interns)
+        call_list_method(self, attr, args, heap, interns)
     }
 
     /// Intercepts `sort` to call `do_list_sort` (which needs `PrintWriter` for key functions),
@@ -450,61 +505,44 @@ impl PyTrait for List {
     }
 }
 
-/// Dispatches a method call on a list value.
-///
-/// This is the unified entry point for list method calls.
-///
-/// # Arguments
-/// * `list` - The list to call the method on
-/// * `method` - The method to call (e.g., `StaticStrings::Append`)
-/// * `args` - The method arguments
-/// * `heap` - The heap for allocation and reference counting
-/// * `interns` - The interns table for resolving interned strings
-fn call_list_method(
+py_methods! {
+    /// Dispatches a method call on a list value.
+    ///
+    /// `list.sort` is intercepted by `py_call_attr_raw` before reaching this function,
+    /// because sort needs the `PrintWriter` for key functions.
+    fn call_list_method(&mut List, Type::List, "list") {
+        Append "append" (one) => list_append,
+        Insert "insert" (two) => list_insert,
+        Pop "pop" (zero_or_one) => list_pop,
+        Remove "remove" (one) => list_remove,
+        Clear "clear" (none) => list_clear,
+        Copy "copy" (none) => list_copy,
+        Extend "extend" (one) => list_extend,
+        Index "index" (args) => list_index,
+        Count "count" (one) => list_count,
+        Reverse "reverse" (none) => list_reverse,
+    }
+}
+
+/// Implements Python's `list.append(item)` method.
+fn list_append(
     list: &mut List,
-    method: StaticStrings,
-    args: ArgValues,
+    item: Value,
     heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
+    _interns: &Interns,
 ) -> RunResult<Value> {
-    match method {
-        StaticStrings::Append => {
-            let item = args.get_one_arg("list.append", heap)?;
-            list.append(heap, item);
-            Ok(Value::None)
-        }
-        StaticStrings::Insert => list_insert(list, args, heap),
-        StaticStrings::Pop => list_pop(list, args, heap),
-        StaticStrings::Remove => list_remove(list, args, heap, interns),
-        StaticStrings::Clear => {
-            args.check_zero_args("list.clear", heap)?;
-            list_clear(list, heap);
-            Ok(Value::None)
-        }
-        StaticStrings::Copy => {
-            args.check_zero_args("list.copy", heap)?;
-            Ok(list_copy(list, heap)?)
-        }
-        StaticStrings::Extend => list_extend(list, args, heap, interns),
-        StaticStrings::Index => list_index(list, args, heap, interns),
-        StaticStrings::Count => list_count(list, args, heap, interns),
-        StaticStrings::Reverse => {
-            args.check_zero_args("list.reverse", heap)?;
-            list.items.reverse();
-            Ok(Value::None)
-        }
-        // Note: list.sort is handled by call_list_attr_raw which intercepts it
-        // before reaching this function, because sort needs PrintWriter for key functions
-        _ => {
-            args.drop_with_heap(heap);
-            Err(ExcType::attribute_error(Type::List, method.into()))
-        }
-    }
+    list.append(heap, item)?;
+    Ok(Value::None)
 }
 
 /// Implements Python's `list.insert(index, item)` method.
-fn list_insert(list: &mut List, args: ArgValues, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
-    let (index_obj, item) = args.get_two_args("insert", heap)?;
+fn list_insert(
+    list: &mut List,
+    index_obj: Value,
+    item: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    _interns: &Interns,
+) -> RunResult<Value> {
     defer_drop!(index_obj, heap);
     let mut item_guard = HeapGuard::new(item, heap);
     let heap = item_guard.heap();
@@ -523,7 +561,7 @@ fn list_insert(list: &mut List, args: ArgValues, heap: &mut Heap<impl ResourceTr
         usize::try_from(index_i64).unwrap_or(len)
     };
     let (item, heap) = item_guard.into_parts();
-    list.insert(heap, index, item);
+    list.insert(heap, index, item)?;
     Ok(Value::None)
 }
 
@@ -531,9 +569,12 @@ fn list_insert(list: &mut List, args: ArgValues, heap: &mut Heap<impl ResourceTr
 ///
 /// Removes the item at the given index (default: -1) and returns it.
 /// Raises IndexError if the list is empty or the index is out of range.
-fn list_pop(list: &mut List, args: ArgValues, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
-    let index_arg = args.get_zero_one_arg("list.pop", heap)?;
-
+fn list_pop(
+    list: &mut List,
+    index_arg: Option<Value>,
+    heap: &mut Heap<impl ResourceTracker>,
+    _interns: &Interns,
+) -> RunResult<Value> {
     // Validate index type FIRST (if provided), matching Python's validation order.
     // Python raises TypeError for bad index type even on empty list.
     let index_i64 = if let Some(v) = index_arg {
@@ -569,11 +610,10 @@ fn list_pop(list: &mut List, args: ArgValues, heap: &mut Heap<impl ResourceTrack
 /// Removes the first occurrence of value. Raises ValueError if not found.
 fn list_remove(
     list: &mut List,
-    args: ArgValues,
+    value: Value,
     heap: &mut Heap<impl ResourceTracker>,
     interns: &Interns,
 ) -> RunResult<Value> {
-    let value = args.get_one_arg("list.remove", heap)?;
     defer_drop!(value, heap);
 
     // Find the first matching element
@@ -601,17 +641,18 @@ fn list_remove(
 /// Implements Python's `list.clear()` method.
 ///
 /// Removes all items from the list.
-fn list_clear(list: &mut List, heap: &mut Heap<impl ResourceTracker>) {
+fn list_clear(list: &mut List, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     for item in list.items.drain(..) {
         item.drop_with_heap(heap);
     }
     // Note: contains_refs stays true even if all refs removed, per conservative GC strategy
+    Ok(Value::None)
 }
 
 /// Implements Python's `list.copy()` method.
 ///
 /// Returns a shallow copy of the list.
-fn list_copy(list: &List, heap: &mut Heap<impl ResourceTracker>) -> Result<Value, ResourceError> {
+fn list_copy(list: &List, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let items: Vec<Value> = list.items.iter().map(|v| v.clone_with_heap(heap)).collect();
     let heap_id = heap.allocate(HeapData::List(List::new(items)))?;
     Ok(Value::Ref(heap_id))
@@ -622,16 +663,25 @@ fn list_copy(list: &List, heap: &mut Heap<impl ResourceTracker>) -> Result<Value
 /// Extends the list by appending all items from the iterable.
 fn list_extend(
     list: &mut List,
-    args: ArgValues,
+    iterable: Value,
     heap: &mut Heap<impl ResourceTracker>,
     interns: &Interns,
 ) -> RunResult<Value> {
-    let iterable = args.get_one_arg("list.extend", heap)?;
     let items: SmallVec<[_; 2]> = MontyIter::new(iterable, heap, interns)?.collect(heap, interns)?;
 
+    if let Err(err) = heap
+        .tracker()
+        .check_collection_len(CollectionKind::List, list.len() + items.len())
+    {
+        for item in items {
+            item.drop_with_heap(heap);
+        }
+        return Err(err.into());
+    }
+
     // Add each item to the list
     for item in items {
-        list.append(heap, item);
+        list.push(heap, item);
     }
 
     Ok(Value::None)
@@ -652,7 +702,7 @@ fn list_index(
 
     let len = list.items.len();
     let (value, start, end) = match pos_args.as_slice() {
-        [] => return Err(ExcType::type_error_at_least("list.index", 1, 0)),
+        [] => return Err(ExcType::type_error_at_least("index", 1, 0)),
         [value] => (value, 0, len),
         [value, start_arg] => {
             let start = normalize_list_index(start_arg.as_int(heap)?, len);
@@ -663,7 +713,7 @@ fn list_index(
             let end = normalize_list_index(end_arg.as_int(heap)?, len).max(start);
             (value, start, end)
         }
-        other => return Err(ExcType::type_error_at_most("list.index", 3, other.len())),
+        other => return Err(ExcType::type_error_at_most("index", 3, other.len())),
     };
 
     // Search for the value in the specified range
@@ -682,13 +732,7 @@ fn list_index(
 /// Implements Python's `list.count(value)` method.
 ///
 /// Returns the number of occurrences of value in the list.
-fn list_count(
-    list: &List,
-    args: ArgValues,
-    heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
-) -> RunResult<Value> {
-    let value = args.get_one_arg("list.count", heap)?;
+fn list_count(list: &List, value: Value, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
     defer_drop!(value, heap);
 
     let mut guard = DepthGuard::default();
@@ -704,6 +748,12 @@ fn list_count(
     Ok(Value::Int(count_i64))
 }
 
+/// Implements Python's `list.reverse()` method.
+fn list_reverse(list: &mut List, _heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    list.items.reverse();
+    Ok(Value::None)
+}
+
 /// Normalizes a Python-style list index to a valid index in range [0, len].
 fn normalize_list_index(index: i64, len: usize) -> usize {
     if index < 0 {
@@ -939,6 +989,10 @@ fn call_key_function(
 /// This helper function is used to implement `__repr__` for sequence types like
 /// lists and tuples. It writes items as comma-separated repr interns.
 ///
+/// Lists and tuples nested inside are written from a work stack rather than by recursion,
+/// so deeply nested ones can't overflow the call stack. Nesting deeper than the tracker's
+/// `max_data_depth` is written as `...`.
+///
 /// # Arguments
 /// * `start` - The opening character (e.g., '[' for lists, '(' for tuples)
 /// * `end` - The closing character (e.g., ']' for lists, ')' for tuples)
@@ -963,24 +1017,182 @@ pub(crate) fn repr_sequence_fmt(
     if !guard.increase() {
         return f.write_str("...");
     }
+    let result = repr_nested_sequences_fmt(start, end, items, f, heap, heap_ids, guard, interns);
+    guard.decrease();
+    result
+}
+
+/// A list or tuple being written by `repr_nested_sequences_fmt`.
+struct ReprFrame<'h> {
+    items: &'h [Value],
+    /// Index of the next item to write.
+    next: usize,
+    end: char,
+    /// Heap ID of the sequence, `None` for the outermost one, whose caller tracks it.
+    id: Option<HeapId>,
+}
 
+#[expect(clippy::too_many_arguments)]
+fn repr_nested_sequences_fmt<'h>(
+    start: char,
+    end: char,
+    items: &'h [Value],
+    f: &mut impl Write,
+    heap: &'h Heap<impl ResourceTracker>,
+    heap_ids: &mut AHashSet<HeapId>,
+    guard: &mut DepthGuard,
+    interns: &Interns,
+) -> std::fmt::Result {
+    // the outermost sequence already took its level from the guard
+    let max_depth = heap
+        .tracker()
+        .max_data_depth()
+        .min(guard.nesting_remaining().saturating_add(1));
     f.write_char(start)?;
-    let mut iter = items.iter();
-    if let Some(first) = iter.next() {
-        first.py_repr_fmt(f, heap, heap_ids, guard, interns)?;
-        for item in iter {
+    let mut stack = vec![ReprFrame {
+        items,
+        next: 0,
+        end,
+        id: None,
+    }];
+    while let Some(frame) = stack.last_mut() {
+        let Some(item) = frame.items.get(frame.next) else {
+            f.write_char(frame.end)?;
+            if let Some(id) = frame.id {
+                heap_ids.remove(&id);
+            }
+            stack.pop();
+            continue;
+        };
+        if frame.next > 0 {
             if heap.check_time().is_err() {
                 f.write_str(", ...[timeout]")?;
-                break;
+                frame.next = frame.items.len();
+                continue;
             }
             f.write_str(", ")?;
-            item.py_repr_fmt(f, heap, heap_ids, guard, interns)?;
         }
+        frame.next += 1;
+
+        // lists and tuples not already being written are descended into in place
+        if let Value::Ref(id) = item
+            && !heap_ids.contains(id)
+        {
+            let nested = match heap.get(*id) {
+                HeapData::List(list) => Some(('[', list.as_slice(), ']')),
+                HeapData::Tuple(tuple) => Some(('(', tuple.as_slice(), ')')),
+                _ => None,
+            };
+            if let Some((start, items, end)) = nested {
+                if stack.len() >= max_depth {
+                    f.write_str("...")?;
+                } else {
+                    heap_ids.insert(*id);
+                    f.write_char(start)?;
+                    stack.push(ReprFrame {
+                        items,
+                        next: 0,
+                        end,
+                        id: Some(*id),
+                    });
+                }
+                continue;
+            }
+        }
+        item.py_repr_fmt(f, heap, heap_ids, guard, interns)?;
     }
-    f.write_char(end)?;
+    Ok(())
+}
 
+/// Compares two lists or two tuples with `==`, or returns `None` if `left` and `right`
+/// aren't both lists or both tuples.
+///
+/// Lists and tuples nested inside are compared from a work stack rather than by recursion,
+/// so deeply nested ones can't overflow the call stack. Nesting deeper than the tracker's
+/// `max_data_depth` raises `RecursionError`, which is also what comparing two lists that
+/// contain themselves ends in, as in CPython.
+pub(crate) fn sequences_eq(
+    left: HeapId,
+    right: HeapId,
+    heap: &mut Heap<impl ResourceTracker>,
+    guard: &mut DepthGuard,
+    interns: &Interns,
+) -> Option<Result<bool, ResourceError>> {
+    let (left_items, right_items) = same_kind_items(heap.get(left), heap.get(right))?;
+    if left_items.len() != right_items.len() {
+        return Some(Ok(false));
+    }
+    if let Err(err) = guard.increase_err() {
+        return Some(Err(err));
+    }
+    let result = nested_sequences_eq(left, right, heap, guard, interns);
     guard.decrease();
-    Ok(())
+    Some(result)
+}
+
+fn nested_sequences_eq(
+    left: HeapId,
+    right: HeapId,
+    heap: &mut Heap<impl ResourceTracker>,
+    guard: &mut DepthGuard,
+    interns: &Interns,
+) -> Result<bool, ResourceError> {
+    // the outermost pair already took its level from the guard
+    let max_depth = heap
+        .tracker()
+        .max_data_depth()
+        .min(guard.nesting_remaining().saturating_add(1));
+    // pairs of sequences of equal length being compared, with the index of their next items
+    let mut stack = vec![(left, right, 0)];
+    while let Some((left, right, next)) = stack.last_mut() {
+        let (left, right, index) = (*left, *right, *next);
+        *next += 1;
+        // shallow copies let the items be compared while the heap is borrowed mutably; they
+        // don't own the references they copy, so they must never be dropped
+        let items = {
+            let (left_items, right_items) = same_kind_items(heap.get(left), heap.get(right))
+                .expect("sequences_eq: compared sequences are lists or tuples");
+            left_items.get(index).zip(right_items.get(index)).map(|(a, b)| {
+                (
+                    ManuallyDrop::new(a.copy_for_extend()),
+                    ManuallyDrop::new(b.copy_for_extend()),
+                )
+            })
+        };
+        let Some((a, b)) = items else {
+            stack.pop();
+            continue;
+        };
+        heap.check_time()?;
+
+        if let (Value::Ref(a_id), Value::Ref(b_id)) = (&*a, &*b)
+            && a_id != b_id
+            && let Some((a_items, b_items)) = same_kind_items(heap.get(*a_id), heap.get(*b_id))
+        {
+            if a_items.len() != b_items.len() {
+                return Ok(false);
+            }
+            if stack.len() >= max_depth {
+                return Err(ResourceError::Recursion {
+                    limit: max_depth,
+                    depth: stack.len() + 1,
+                });
+            }
+            stack.push((*a_id, *b_id, 0));
+        } else if !a.py_eq(&b, heap, guard, interns)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Returns the items of `left` and `right` if both are lists or both are tuples.
+fn same_kind_items<'h>(left: &'h HeapData, right: &'h HeapData) -> Option<(&'h [Value], &'h [Value])> {
+    match (left, right) {
+        (HeapData::List(left), HeapData::List(right)) => Some((left.as_slice(), right.as_slice())),
+        (HeapData::Tuple(left), HeapData::Tuple(right)) => Some((left.as_slice(), right.as_slice())),
+        _ => None,
+    }
 }
 
 /// Helper to extract items from a slice for list/tuple slicing.
@@ -0,0 +1,134 @@
+//! Generating the method dispatch of a type from a table of its methods.
+//!
+//! Hand-written dispatch has to check the argument count of every method, name the method
+//! the way CPython does in each arity error, drop the arguments and raise `AttributeError`
+//! for names the type doesn't have. [`py_methods!`] does all of that from one row per
+//! method, so a type's methods only implement their behavior.
+
+/// Generates the method dispatch function of a type from a table of its methods.
+///
+/// The header gives the receiver type, which is `&mut` for types with mutating methods, the
+/// `Type` named by `AttributeError` and the type name used in arity errors. Each row names
+/// the method's `StaticStrings` variant and Python name, how many positional arguments it
+/// takes and the function implementing it:
+///
+/// ```text
+/// py_methods! {
+///     /// Dispatches a method call on a list.
+///     fn call_list_method(&mut List, Type::List, "list") {
+///         Append "append" (one) => list_append,
+///         Pop "pop" (zero_or_one) => list_pop,
+///         Index "index" (args) => list_index,
+///     }
+/// }
+/// ```
+///
+/// The generated function takes `(receiver, attr, args, heap, interns)`, checks the
+/// argument count before calling the implementation and raises `AttributeError` for any
+/// other attribute. Implementations take the receiver, then the arguments the arity gives
+/// them, then `heap` and `interns`:
+/// - `none`: no arguments
+/// - `predicate`: no arguments; the implementation takes only the receiver and returns `bool`
+/// - `one`: `Value`
+/// - `two`: `Value, Value`
+/// - `zero_or_one`: `Option<Value>`
+/// - `one_or_two`: `Value, Option<Value>`
+/// - `zero_to_two`: `Option<Value>, Option<Value>`
+/// - `args`: the unchecked `ArgValues`, for methods with more arguments or keywords
+///
+/// Like CPython, errors name `none`, `predicate` and `one` methods with their type
+/// (`list.append() takes exactly one argument (0 given)`) and the others without (`pop
+/// expected at most 1 argument, got 2`).
+macro_rules! py_methods {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $dispatch:ident($receiver:ty, $py_type:expr, $type_name:literal) {
+            $($variant:ident $name:literal ($arity:ident) => $method:path,)*
+        }
+    ) => {
+        $(#[$meta])*
+        $vis fn $dispatch(
+            this: $receiver,
+            attr: &$crate::value::EitherStr,
+            args: $crate::args::ArgValues,
+            heap: &mut $crate::heap::Heap<impl $crate::resource::ResourceTracker>,
+            interns: &$crate::intern::Interns,
+        ) -> $crate::exception_private::RunResult<$crate::value::Value> {
+            match attr.static_string() {
+                $(
+                    Some($crate::intern::StaticStrings::$variant) => $crate::types::methods::py_methods!(
+                        @call $arity,
+                        $method,
+                        concat!($type_name, ".", $name),
+                        $name,
+                        (this, args, heap, interns)
+                    ),
+                )*
+                _ => {
+                    $crate::heap::DropWithHeap::drop_with_heap(args, heap);
+                    Err($crate::exception_private::ExcType::attribute_error(
+                        $py_type,
+                        attr.as_str(interns),
+                    ))
+                }
+            }
+        }
+    };
+    (
+        @call none, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        $args.check_zero_args($qualified, $heap)?;
+        $method($this, $heap, $interns)
+    }};
+    (
+        @call predicate, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        $args.check_zero_args($qualified, $heap)?;
+        Ok($crate::value::Value::Bool($method($this)))
+    }};
+    (
+        @call one, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        let arg = $args.get_one_arg($qualified, $heap)?;
+        $method($this, arg, $heap, $interns)
+    }};
+    (
+        @call two, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        let (first, second) = $args.get_two_args($name, $heap)?;
+        $method($this, first, second, $heap, $interns)
+    }};
+    (
+        @call zero_or_one, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        let arg = $args.get_zero_one_arg($name, $heap)?;
+        $method($this, arg, $heap, $interns)
+    }};
+    (
+        @call one_or_two, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        let (first, second) = $args.get_one_two_args($name, $heap)?;
+        $method($this, first, second, $heap, $interns)
+    }};
+    (
+        @call zero_to_two, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {{
+        let (first, second) = $args.get_zero_one_two_args($name, $heap)?;
+        $method($this, first, second, $heap, $interns)
+    }};
+    (
+        @call args, $method:path, $qualified:expr, $name:literal,
+        ($this:ident, $args:ident, $heap:ident, $interns:ident)
+    ) => {
+        $method($this, $args, $heap, $interns)
+    };
+}
+
+pub(crate) use py_methods;
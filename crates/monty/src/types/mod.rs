@@ -8,9 +8,11 @@
 pub mod bytes;
 pub mod dataclass;
 pub mod dict;
+pub mod int;
 pub mod iter;
 pub mod list;
 pub mod long_int;
+pub mod methods;
 pub mod module;
 pub mod namedtuple;
 pub mod path;
@@ -3,7 +3,7 @@
 use crate::{
     args::ArgValues,
     exception_private::{ExcType, RunResult},
-    heap::{Heap, HeapGuard, HeapId},
+    heap::{Heap, HeapGuard, HeapId, HeapIdMap},
     intern::{Interns, StringId},
     io::PrintWriter,
     resource::ResourceTracker,
@@ -66,6 +66,7 @@ impl Module {
         let key = Value::InternString(name.into());
         // Unwrap is safe because InternString keys are always hashable
         self.attrs.set(key, value, heap, interns).unwrap();
+        heap.bump_namespace_generation();
     }
 
     /// Looks up an attribute by name in the module's attribute dictionary.
@@ -88,11 +89,32 @@ impl Module {
             .map(Value::copy_for_extend)
     }
 
+    /// Looks up an attribute that an inline cache can hold on to.
+    ///
+    /// Returns `None` if the attribute doesn't exist, is a heap reference (the cache
+    /// holds no refcount), or is a `Property` whose getter must run on every access.
+    pub fn cacheable_attr(
+        &self,
+        attr_id: StringId,
+        heap: &Heap<impl ResourceTracker>,
+        interns: &Interns,
+    ) -> Option<Value> {
+        match self.attrs.get_by_str(interns.get_str(attr_id), heap, interns)? {
+            Value::Ref(_) | Value::Property(_) => None,
+            value => Some(value.clone_immediate()),
+        }
+    }
+
     /// Returns whether this module has any heap references in its attributes.
     pub fn has_refs(&self) -> bool {
         self.attrs.has_refs()
     }
 
+    /// Rewrites heap ids in the module's attributes after heap compaction.
+    pub fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        self.attrs.remap_heap_ids(map);
+    }
+
     /// Collects child HeapIds for reference counting.
     pub fn py_dec_ref_ids(&mut self, stack: &mut Vec<HeapId>) {
         self.attrs.py_dec_ref_ids(stack);
@@ -148,7 +170,7 @@ impl Module {
         match self.get_attr(&attr_key, args_guard.heap(), interns) {
             Some(Value::ModuleFunction(mf)) => {
                 let (args, heap) = args_guard.into_parts();
-                mf.call(heap, args)
+                mf.call(heap, interns, args)
             }
             Some(func) => {
                 // Found attribute but it's not callable
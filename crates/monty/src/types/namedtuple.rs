@@ -22,7 +22,7 @@ use ahash::AHashSet;
 use super::PyTrait;
 use crate::{
     exception_private::{ExcType, RunResult},
-    heap::{Heap, HeapId},
+    heap::{Heap, HeapId, HeapIdMap},
     intern::{Interns, StringId},
     resource::{DepthGuard, ResourceError, ResourceTracker},
     types::{AttrCallResult, Type},
@@ -118,6 +118,13 @@ impl NamedTuple {
         self.contains_refs
     }
 
+    /// Rewrites heap ids in the named tuple's items after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        if self.contains_refs {
+            map.remap_values(&mut self.items);
+        }
+    }
+
     /// Gets a field value by name (StringId).
     ///
     /// Compares field names by actual string content, not just variant type.
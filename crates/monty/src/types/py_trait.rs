@@ -7,7 +7,7 @@
 /// The trait is designed to work with `enum_dispatch` for efficient virtual
 /// dispatch on `HeapData` without boxing overhead.
 use std::borrow::Cow;
-use std::{cmp::Ordering, fmt::Write};
+use std::{cmp::Ordering, fmt::Write, time::Duration};
 
 use ahash::AHashSet;
 
@@ -70,6 +70,10 @@ pub enum AttrCallResult {
     /// Used by `asyncio.run()` to execute a coroutine without an explicit `await`.
     /// The VM will push the value onto the stack and execute `exec_get_awaitable`.
     AwaitValue(Value),
+    /// `time.sleep()` was called. VM should yield `FrameExit::Sleep` to host.
+    ///
+    /// The host decides when to resume; the VM never blocks.
+    Sleep(Duration),
 }
 
 /// Common operations for heap-allocated Python values.
@@ -3,13 +3,13 @@ use std::fmt::Write;
 use ahash::AHashSet;
 use hashbrown::HashTable;
 
-use super::{MontyIter, PyTrait};
+use super::{MontyIter, PyTrait, methods::py_methods};
 use crate::{
     args::ArgValues,
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunResult},
-    heap::{DropWithHeap, Heap, HeapData, HeapId},
-    intern::{Interns, StaticStrings},
+    heap::{DropWithHeap, Heap, HeapData, HeapId, HeapIdMap},
+    intern::Interns,
     resource::{DepthGuard, ResourceError, ResourceTracker},
     types::Type,
     value::{EitherStr, Value},
@@ -111,6 +111,11 @@ impl SetStorage {
         self.entries.iter().any(|e| matches!(e.value, Value::Ref(_)))
     }
 
+    /// Rewrites heap ids in the stored values after heap compaction.
+    fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        map.remap_values(self.entries.iter_mut().map(|e| &mut e.value));
+    }
+
     /// Adds an element to the set, transferring ownership.
     ///
     /// Returns `Ok(true)` if the element was added (not already present),
@@ -520,6 +525,11 @@ impl Set {
         self.0.has_refs()
     }
 
+    /// Rewrites heap ids in the stored values after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        self.0.remap_heap_ids(map);
+    }
+
     /// Adds an element to the set, transferring ownership.
     ///
     /// Returns `Ok(true)` if added, `Ok(false)` if already present.
@@ -665,88 +675,7 @@ impl PyTrait for Set {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        match attr.static_string() {
-            Some(StaticStrings::Add) => {
-                let value = args.get_one_arg("set.add", heap)?;
-                self.add(value, heap, interns)?;
-                Ok(Value::None)
-            }
-            Some(StaticStrings::Remove) => {
-                let value = args.get_one_arg("set.remove", heap)?;
-                defer_drop!(value, heap);
-                self.remove(value, heap, interns)?;
-                Ok(Value::None)
-            }
-            Some(StaticStrings::Discard) => {
-                let value = args.get_one_arg("set.discard", heap)?;
-                defer_drop!(value, heap);
-                self.discard(value, heap, interns)?;
-                Ok(Value::None)
-            }
-            Some(StaticStrings::Pop) => {
-                args.check_zero_args("set.pop", heap)?;
-                self.pop()
-            }
-            Some(StaticStrings::Clear) => {
-                args.check_zero_args("set.clear", heap)?;
-                self.clear(heap);
-                Ok(Value::None)
-            }
-            Some(StaticStrings::Copy) => {
-                args.check_zero_args("set.copy", heap)?;
-                let copy = self.copy(heap);
-                let heap_id = heap.allocate(HeapData::Set(copy))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Update) => {
-                let other = args.get_one_arg("set.update", heap)?;
-                self.update_from_value(other, heap, interns)?;
-                Ok(Value::None)
-            }
-            Some(StaticStrings::Union) => {
-                let other = args.get_one_arg("set.union", heap)?;
-                let result = self.union_from_value(other, heap, interns)?;
-                let heap_id = heap.allocate(HeapData::Set(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Intersection) => {
-                let other = args.get_one_arg("set.intersection", heap)?;
-                let result = self.intersection_from_value(other, heap, interns)?;
-                let heap_id = heap.allocate(HeapData::Set(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Difference) => {
-                let other = args.get_one_arg("set.difference", heap)?;
-                let result = self.difference_from_value(other, heap, interns)?;
-                let heap_id = heap.allocate(HeapData::Set(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::SymmetricDifference) => {
-                let other = args.get_one_arg("set.symmetric_difference", heap)?;
-                let result = self.symmetric_difference_from_value(other, heap, interns)?;
-                let heap_id = heap.allocate(HeapData::Set(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Issubset) => {
-                let other = args.get_one_arg("set.issubset", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.issubset_from_value(other, heap, interns)?))
-            }
-            Some(StaticStrings::Issuperset) => {
-                let other = args.get_one_arg("set.issuperset", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.issuperset_from_value(other, heap, interns)?))
-            }
-            Some(StaticStrings::Isdisjoint) => {
-                let other = args.get_one_arg("set.isdisjoint", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.isdisjoint_from_value(other, heap, interns)?))
-            }
-            _ => {
-                args.drop_with_heap(heap);
-                Err(ExcType::attribute_error(Type::Set, attr.as_str(interns)))
-            }
-        }
+        call_set_method(self, attr, args, heap, interns)
     }
 
     fn py_sub(
@@ -762,6 +691,160 @@ impl PyTrait for Set {
     }
 }
 
+py_methods! {
+    /// Dispatches a method call on a set value.
+    fn call_set_method(&mut Set, Type::Set, "set") {
+        Add "add" (one) => set_add,
+        Remove "remove" (one) => set_remove,
+        Discard "discard" (one) => set_discard,
+        Pop "pop" (none) => set_pop,
+        Clear "clear" (none) => set_clear,
+        Copy "copy" (none) => set_copy,
+        Update "update" (one) => set_update,
+        Union "union" (one) => set_union,
+        Intersection "intersection" (one) => set_intersection,
+        Difference "difference" (one) => set_difference,
+        SymmetricDifference "symmetric_difference" (one) => set_symmetric_difference,
+        Issubset "issubset" (one) => set_issubset,
+        Issuperset "issuperset" (one) => set_issuperset,
+        Isdisjoint "isdisjoint" (one) => set_isdisjoint,
+    }
+}
+
+/// Implements Python's `set.add(elem)` method.
+fn set_add(set: &mut Set, value: Value, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
+    set.add(value, heap, interns)?;
+    Ok(Value::None)
+}
+
+/// Implements Python's `set.remove(elem)` method.
+///
+/// Raises KeyError if the element is not in the set.
+fn set_remove(
+    set: &mut Set,
+    value: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(value, heap);
+    set.remove(value, heap, interns)?;
+    Ok(Value::None)
+}
+
+/// Implements Python's `set.discard(elem)` method.
+fn set_discard(
+    set: &mut Set,
+    value: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(value, heap);
+    set.discard(value, heap, interns)?;
+    Ok(Value::None)
+}
+
+/// Implements Python's `set.pop()` method.
+///
+/// Raises KeyError if the set is empty.
+fn set_pop(set: &mut Set, _heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    set.pop()
+}
+
+/// Implements Python's `set.clear()` method.
+fn set_clear(set: &mut Set, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    set.clear(heap);
+    Ok(Value::None)
+}
+
+/// Implements Python's `set.copy()` method.
+fn set_copy(set: &Set, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    let copy = set.copy(heap);
+    let heap_id = heap.allocate(HeapData::Set(copy))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `set.update(iterable)` method.
+fn set_update(
+    set: &mut Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    set.update_from_value(other, heap, interns)?;
+    Ok(Value::None)
+}
+
+/// Implements Python's `set.union(iterable)` method.
+fn set_union(set: &Set, other: Value, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
+    let result = set.union_from_value(other, heap, interns)?;
+    let heap_id = heap.allocate(HeapData::Set(result))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `set.intersection(iterable)` method.
+fn set_intersection(
+    set: &Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    let result = set.intersection_from_value(other, heap, interns)?;
+    let heap_id = heap.allocate(HeapData::Set(result))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `set.difference(iterable)` method.
+fn set_difference(
+    set: &Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    let result = set.difference_from_value(other, heap, interns)?;
+    let heap_id = heap.allocate(HeapData::Set(result))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `set.symmetric_difference(iterable)` method.
+fn set_symmetric_difference(
+    set: &Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    let result = set.symmetric_difference_from_value(other, heap, interns)?;
+    let heap_id = heap.allocate(HeapData::Set(result))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `set.issubset(iterable)` method.
+fn set_issubset(set: &Set, other: Value, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(set.issubset_from_value(other, heap, interns)?))
+}
+
+/// Implements Python's `set.issuperset(iterable)` method.
+fn set_issuperset(
+    set: &Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(set.issuperset_from_value(other, heap, interns)?))
+}
+
+/// Implements Python's `set.isdisjoint(iterable)` method.
+fn set_isdisjoint(
+    set: &Set,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(set.isdisjoint_from_value(other, heap, interns)?))
+}
+
 /// Helper methods for set operations with arbitrary iterables.
 impl Set {
     /// Updates this set with elements from an iterable value.
@@ -1028,6 +1111,11 @@ impl FrozenSet {
         self.0.has_refs()
     }
 
+    /// Rewrites heap ids in the stored values after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        self.0.remap_heap_ids(map);
+    }
+
     /// Returns a shallow copy of the frozenset.
     #[must_use]
     pub fn copy(&self, heap: &mut Heap<impl ResourceTracker>) -> Self {
@@ -1169,73 +1257,7 @@ impl PyTrait for FrozenSet {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        match attr.static_string() {
-            Some(StaticStrings::Copy) => {
-                args.check_zero_args("frozenset.copy", heap)?;
-                let copy = self.copy(heap);
-                let heap_id = heap.allocate(HeapData::FrozenSet(copy))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Union) => {
-                let other = args.get_one_arg("frozenset.union", heap)?;
-                let other_storage = Set::get_storage_from_value(other, heap, interns)?;
-                let result = self.union(&other_storage, heap, interns)?;
-                for entry in other_storage.entries {
-                    entry.value.drop_with_heap(heap);
-                }
-                let heap_id = heap.allocate(HeapData::FrozenSet(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Intersection) => {
-                let other = args.get_one_arg("frozenset.intersection", heap)?;
-                let other_storage = Set::get_storage_from_value(other, heap, interns)?;
-                let result = self.intersection(&other_storage, heap, interns)?;
-                for entry in other_storage.entries {
-                    entry.value.drop_with_heap(heap);
-                }
-                let heap_id = heap.allocate(HeapData::FrozenSet(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Difference) => {
-                let other = args.get_one_arg("frozenset.difference", heap)?;
-                let other_storage = Set::get_storage_from_value(other, heap, interns)?;
-                let result = self.difference(&other_storage, heap, interns)?;
-                for entry in other_storage.entries {
-                    entry.value.drop_with_heap(heap);
-                }
-                let heap_id = heap.allocate(HeapData::FrozenSet(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::SymmetricDifference) => {
-                let other = args.get_one_arg("frozenset.symmetric_difference", heap)?;
-                let other_storage = Set::get_storage_from_value(other, heap, interns)?;
-                let result = self.symmetric_difference(&other_storage, heap, interns)?;
-                for entry in other_storage.entries {
-                    entry.value.drop_with_heap(heap);
-                }
-                let heap_id = heap.allocate(HeapData::FrozenSet(result))?;
-                Ok(Value::Ref(heap_id))
-            }
-            Some(StaticStrings::Issubset) => {
-                let other = args.get_one_arg("frozenset.issubset", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.issubset_from_value(other, heap, interns)?))
-            }
-            Some(StaticStrings::Issuperset) => {
-                let other = args.get_one_arg("frozenset.issuperset", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.issuperset_from_value(other, heap, interns)?))
-            }
-            Some(StaticStrings::Isdisjoint) => {
-                let other = args.get_one_arg("frozenset.isdisjoint", heap)?;
-                defer_drop!(other, heap);
-                Ok(Value::Bool(self.isdisjoint_from_value(other, heap, interns)?))
-            }
-            _ => {
-                args.drop_with_heap(heap);
-                Err(ExcType::attribute_error(Type::FrozenSet, attr.as_str(interns)))
-            }
-        }
+        call_frozenset_method(self, attr, args, heap, interns)
     }
 
     fn py_sub(
@@ -1248,6 +1270,122 @@ impl PyTrait for FrozenSet {
     }
 }
 
+py_methods! {
+    /// Dispatches a method call on a frozenset value.
+    fn call_frozenset_method(&FrozenSet, Type::FrozenSet, "frozenset") {
+        Copy "copy" (none) => frozenset_copy,
+        Union "union" (one) => frozenset_union,
+        Intersection "intersection" (one) => frozenset_intersection,
+        Difference "difference" (one) => frozenset_difference,
+        SymmetricDifference "symmetric_difference" (one) => frozenset_symmetric_difference,
+        Issubset "issubset" (one) => frozenset_issubset,
+        Issuperset "issuperset" (one) => frozenset_issuperset,
+        Isdisjoint "isdisjoint" (one) => frozenset_isdisjoint,
+    }
+}
+
+/// Implements Python's `frozenset.copy()` method.
+fn frozenset_copy(
+    frozenset: &FrozenSet,
+    heap: &mut Heap<impl ResourceTracker>,
+    _interns: &Interns,
+) -> RunResult<Value> {
+    let copy = frozenset.copy(heap);
+    let heap_id = heap.allocate(HeapData::FrozenSet(copy))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Applies the binary set operation `op` to a frozenset and the elements of `other`,
+/// returning the result as a new frozenset.
+fn frozenset_binary_op<T: ResourceTracker>(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<T>,
+    interns: &Interns,
+    op: impl FnOnce(&FrozenSet, &SetStorage, &mut Heap<T>, &Interns) -> RunResult<FrozenSet>,
+) -> RunResult<Value> {
+    let other_storage = Set::get_storage_from_value(other, heap, interns)?;
+    let result = op(frozenset, &other_storage, heap, interns);
+    for entry in other_storage.entries {
+        entry.value.drop_with_heap(heap);
+    }
+    let heap_id = heap.allocate(HeapData::FrozenSet(result?))?;
+    Ok(Value::Ref(heap_id))
+}
+
+/// Implements Python's `frozenset.union(iterable)` method.
+fn frozenset_union(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    frozenset_binary_op(frozenset, other, heap, interns, FrozenSet::union)
+}
+
+/// Implements Python's `frozenset.intersection(iterable)` method.
+fn frozenset_intersection(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    frozenset_binary_op(frozenset, other, heap, interns, FrozenSet::intersection)
+}
+
+/// Implements Python's `frozenset.difference(iterable)` method.
+fn frozenset_difference(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    frozenset_binary_op(frozenset, other, heap, interns, FrozenSet::difference)
+}
+
+/// Implements Python's `frozenset.symmetric_difference(iterable)` method.
+fn frozenset_symmetric_difference(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    frozenset_binary_op(frozenset, other, heap, interns, FrozenSet::symmetric_difference)
+}
+
+/// Implements Python's `frozenset.issubset(iterable)` method.
+fn frozenset_issubset(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(frozenset.issubset_from_value(other, heap, interns)?))
+}
+
+/// Implements Python's `frozenset.issuperset(iterable)` method.
+fn frozenset_issuperset(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(frozenset.issuperset_from_value(other, heap, interns)?))
+}
+
+/// Implements Python's `frozenset.isdisjoint(iterable)` method.
+fn frozenset_isdisjoint(
+    frozenset: &FrozenSet,
+    other: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    interns: &Interns,
+) -> RunResult<Value> {
+    defer_drop!(other, heap);
+    Ok(Value::Bool(frozenset.isdisjoint_from_value(other, heap, interns)?))
+}
+
 /// Helper methods for frozenset operations with arbitrary iterables.
 impl FrozenSet {
     /// Checks if this frozenset is a subset of an iterable.
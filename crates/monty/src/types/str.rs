@@ -8,14 +8,15 @@ use std::{borrow::Cow, fmt};
 use ahash::AHashSet;
 use smallvec::smallvec;
 
-use super::{Bytes, MontyIter, PyTrait};
+use super::{Bytes, MontyIter, PyTrait, methods::py_methods};
 use crate::{
     args::ArgValues,
+    codecs::{Codec, codec_arg},
     defer_drop, defer_drop_mut,
     exception_private::{ExcType, RunResult},
-    heap::{DropWithHeap, Heap, HeapData, HeapGuard, HeapId},
+    heap::{DropWithHeap, Heap, HeapData, HeapId},
     intern::{Interns, StaticStrings, StringId},
-    resource::{DepthGuard, ResourceError, ResourceTracker},
+    resource::{CollectionKind, DepthGuard, ResourceError, ResourceTracker},
     types::Type,
     value::{EitherStr, Value},
 };
@@ -72,8 +73,7 @@ impl Str {
             .map_err(|()| ExcType::value_error_slice_step_zero())?;
 
         let result_str = get_str_slice(&self.0, start, stop, step);
-        let heap_id = heap.allocate(HeapData::Str(Self::from(result_str)))?;
-        Ok(Value::Ref(heap_id))
+        Ok(allocate_str(result_str, heap)?)
     }
 }
 
@@ -105,11 +105,23 @@ impl From<Str> for String {
 /// This avoids heap allocation for common cases like results from `strip()`,
 /// `split()`, string iteration, etc.
 pub fn allocate_string(s: String, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+    Ok(allocate_str(s, heap)?)
+}
+
+/// Same as [`allocate_string`], for callers whose only failure mode is a resource limit.
+///
+/// Used by concatenation, slicing and repetition, so loops that build strings up from
+/// `''` one character at a time don't allocate until the string is two bytes long.
+pub fn allocate_str(s: String, heap: &mut Heap<impl ResourceTracker>) -> Result<Value, ResourceError> {
     match s.len() {
-        0 => Ok(Value::InternString(StaticStrings::EmptyString.into())),
+        0 => {
+            heap.record_string_cache_hit();
+            Ok(Value::InternString(StaticStrings::EmptyString.into()))
+        }
         1 => {
             // Single byte means single ASCII character
             let byte = s.as_bytes()[0];
+            heap.record_string_cache_hit();
             Ok(Value::InternString(StringId::from_ascii(byte)))
         }
         _ => {
@@ -127,6 +139,7 @@ pub fn allocate_string(s: String, heap: &mut Heap<impl ResourceTracker>) -> RunR
 /// This is used by string iteration and `chr()` builtin.
 pub fn allocate_char(c: char, heap: &mut Heap<impl ResourceTracker>) -> Result<Value, ResourceError> {
     if c.is_ascii() {
+        heap.record_string_cache_hit();
         Ok(Value::InternString(StringId::from_ascii(c as u8)))
     } else {
         let heap_id = heap.allocate(HeapData::Str(Str::new(c.to_string())))?;
@@ -285,8 +298,7 @@ impl PyTrait for Str {
         _interns: &Interns,
     ) -> Result<Option<Value>, crate::resource::ResourceError> {
         let result = format!("{}{}", self.0, other.0);
-        let id = heap.allocate(HeapData::Str(result.into()))?;
-        Ok(Some(Value::Ref(id)))
+        Ok(Some(allocate_str(result, heap)?))
     }
 
     fn py_iadd(
@@ -298,6 +310,22 @@ impl PyTrait for Str {
     ) -> Result<bool, crate::resource::ResourceError> {
         match &other {
             Value::Ref(other_id) => {
+                let rhs_len = if Some(*other_id) == self_id {
+                    self.0.len()
+                } else if let HeapData::Str(rhs) = heap.get(*other_id) {
+                    rhs.as_str().len()
+                } else {
+                    other.drop_with_heap(heap);
+                    return Ok(false);
+                };
+                if let Err(err) = heap
+                    .tracker()
+                    .check_collection_len(CollectionKind::Str, self.0.len() + rhs_len)
+                    .and_then(|()| heap.charge_growth(rhs_len))
+                {
+                    other.drop_with_heap(heap);
+                    return Err(err);
+                }
                 if Some(*other_id) == self_id {
                     let rhs = self.0.clone();
                     self.0.push_str(&rhs);
@@ -311,7 +339,11 @@ impl PyTrait for Str {
                 Ok(true)
             }
             Value::InternString(string_id) => {
-                self.0.push_str(interns.get_str(*string_id));
+                let rhs = interns.get_str(*string_id);
+                heap.tracker()
+                    .check_collection_len(CollectionKind::Str, self.0.len() + rhs.len())?;
+                heap.charge_growth(rhs.len())?;
+                self.0.push_str(rhs);
                 Ok(true)
             }
             _ => Ok(false),
@@ -325,169 +357,78 @@ impl PyTrait for Str {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        let args_guard = HeapGuard::new(args, heap);
-        let Some(method) = attr.static_string() else {
-            return Err(ExcType::attribute_error(Type::Str, attr.as_str(interns)));
-        };
-
-        let (args, heap) = args_guard.into_parts();
-        call_str_method_impl(&self.0, method, args, heap, interns)
+        call_str_method(&self.0, attr, args, heap, interns)
     }
 }
 
-/// Dispatches a method call on a string value by method name.
-///
-/// This is the entry point for string method calls from the VM on interned strings.
-/// Converts the `StringId` to `StaticStrings` and delegates to `call_str_method_impl`.
-pub fn call_str_method(
-    s: &str,
-    method_id: StringId,
-    args: ArgValues,
-    heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
-) -> RunResult<Value> {
-    let args_guard = HeapGuard::new(args, heap);
-    let Some(method) = StaticStrings::from_string_id(method_id) else {
-        return Err(ExcType::attribute_error(Type::Str, interns.get_str(method_id)));
-    };
-    let (args, heap) = args_guard.into_parts();
-    call_str_method_impl(s, method, args, heap, interns)
-}
-
-/// Dispatches a method call on a string value.
-///
-/// This is the unified implementation for string method calls, used by both:
-/// - `Str::py_call_attr()` for heap-allocated strings
-/// - `call_str_method()` for interned string literals from the VM
-///
-/// # Not Yet Implemented
-///
-/// The following Python string methods are not yet implemented:
-///
-/// - `format()` - Requires implementing the format spec mini-language (PEP 3101),
-///   which is complex and involves parsing format specifications like `{:>10.2f}`.
-/// - `format_map(mapping)` - Similar to `format()` but takes a mapping; depends on
-///   `format()` implementation.
-/// - `maketrans()` / `translate()` - Character translation tables; moderate complexity,
-///   requires building and applying Unicode translation maps.
-/// - `expandtabs(tabsize=8)` - Tab expansion; simple but rarely used in practice.
-/// - `isprintable()` - Checks if all characters are printable; requires accurate Unicode
-///   category data for the "printable" property.
-fn call_str_method_impl(
-    s: &str,
-    method: StaticStrings,
-    args: ArgValues,
-    heap: &mut Heap<impl ResourceTracker>,
-    interns: &Interns,
-) -> RunResult<Value> {
-    match method {
-        // Simple transformations (no arguments)
-        StaticStrings::Lower => {
-            args.check_zero_args("str.lower", heap)?;
-            str_lower(s, heap)
-        }
-        StaticStrings::Upper => {
-            args.check_zero_args("str.upper", heap)?;
-            str_upper(s, heap)
-        }
-        StaticStrings::Capitalize => {
-            args.check_zero_args("str.capitalize", heap)?;
-            str_capitalize(s, heap)
-        }
-        StaticStrings::Title => {
-            args.check_zero_args("str.title", heap)?;
-            str_title(s, heap)
-        }
-        StaticStrings::Swapcase => {
-            args.check_zero_args("str.swapcase", heap)?;
-            str_swapcase(s, heap)
-        }
-        StaticStrings::Casefold => {
-            args.check_zero_args("str.casefold", heap)?;
-            str_casefold(s, heap)
-        }
-        // Predicate methods (no arguments, return bool)
-        StaticStrings::Isalpha => {
-            args.check_zero_args("str.isalpha", heap)?;
-            Ok(Value::Bool(str_isalpha(s)))
-        }
-        StaticStrings::Isdigit => {
-            args.check_zero_args("str.isdigit", heap)?;
-            Ok(Value::Bool(str_isdigit(s)))
-        }
-        StaticStrings::Isalnum => {
-            args.check_zero_args("str.isalnum", heap)?;
-            Ok(Value::Bool(str_isalnum(s)))
-        }
-        StaticStrings::Isnumeric => {
-            args.check_zero_args("str.isnumeric", heap)?;
-            Ok(Value::Bool(str_isnumeric(s)))
-        }
-        StaticStrings::Isspace => {
-            args.check_zero_args("str.isspace", heap)?;
-            Ok(Value::Bool(str_isspace(s)))
-        }
-        StaticStrings::Islower => {
-            args.check_zero_args("str.islower", heap)?;
-            Ok(Value::Bool(str_islower(s)))
-        }
-        StaticStrings::Isupper => {
-            args.check_zero_args("str.isupper", heap)?;
-            Ok(Value::Bool(str_isupper(s)))
-        }
-        StaticStrings::Isascii => {
-            args.check_zero_args("str.isascii", heap)?;
-            Ok(Value::Bool(s.is_ascii()))
-        }
-        StaticStrings::Isdecimal => {
-            args.check_zero_args("str.isdecimal", heap)?;
-            Ok(Value::Bool(str_isdecimal(s)))
-        }
+py_methods! {
+    /// Dispatches a method call on a string value.
+    ///
+    /// This is the unified implementation for string method calls, used by both:
+    /// - `Str::py_call_attr()` for heap-allocated strings
+    /// - the VM for interned string literals
+    ///
+    /// # Not Yet Implemented
+    ///
+    /// The following Python string methods are not yet implemented:
+    ///
+    /// - `format()` - Requires implementing the format spec mini-language (PEP 3101),
+    ///   which is complex and involves parsing format specifications like `{:>10.2f}`.
+    /// - `format_map(mapping)` - Similar to `format()` but takes a mapping; depends on
+    ///   `format()` implementation.
+    /// - `maketrans()` / `translate()` - Character translation tables; moderate complexity,
+    ///   requires building and applying Unicode translation maps.
+    /// - `expandtabs(tabsize=8)` - Tab expansion; simple but rarely used in practice.
+    /// - `isprintable()` - Checks if all characters are printable; requires accurate Unicode
+    ///   category data for the "printable" property.
+    pub fn call_str_method(&str, Type::Str, "str") {
+        // Simple transformations
+        Lower "lower" (none) => str_lower,
+        Upper "upper" (none) => str_upper,
+        Capitalize "capitalize" (none) => str_capitalize,
+        Title "title" (none) => str_title,
+        Swapcase "swapcase" (none) => str_swapcase,
+        Casefold "casefold" (none) => str_casefold,
+        // Predicate methods
+        Isalpha "isalpha" (predicate) => str_isalpha,
+        Isdigit "isdigit" (predicate) => str_isdigit,
+        Isalnum "isalnum" (predicate) => str_isalnum,
+        Isnumeric "isnumeric" (predicate) => str_isnumeric,
+        Isspace "isspace" (predicate) => str_isspace,
+        Islower "islower" (predicate) => str_islower,
+        Isupper "isupper" (predicate) => str_isupper,
+        Isascii "isascii" (predicate) => str::is_ascii,
+        Isdecimal "isdecimal" (predicate) => str_isdecimal,
+        Isidentifier "isidentifier" (predicate) => str_isidentifier,
+        Istitle "istitle" (predicate) => str_istitle,
         // Search methods
-        StaticStrings::Find => str_find(s, args, heap, interns),
-        StaticStrings::Rfind => str_rfind(s, args, heap, interns),
-        StaticStrings::Index => str_index(s, args, heap, interns),
-        StaticStrings::Rindex => str_rindex(s, args, heap, interns),
-        StaticStrings::Count => str_count(s, args, heap, interns),
-        StaticStrings::Startswith => str_startswith(s, args, heap, interns),
-        StaticStrings::Endswith => str_endswith(s, args, heap, interns),
+        Find "find" (args) => str_find,
+        Rfind "rfind" (args) => str_rfind,
+        Index "index" (args) => str_index,
+        Rindex "rindex" (args) => str_rindex,
+        Count "count" (args) => str_count,
+        Startswith "startswith" (args) => str_startswith,
+        Endswith "endswith" (args) => str_endswith,
         // Strip/trim methods
-        StaticStrings::Strip => str_strip(s, args, heap, interns),
-        StaticStrings::Lstrip => str_lstrip(s, args, heap, interns),
-        StaticStrings::Rstrip => str_rstrip(s, args, heap, interns),
-        StaticStrings::Removeprefix => str_removeprefix(s, args, heap, interns),
-        StaticStrings::Removesuffix => str_removesuffix(s, args, heap, interns),
+        Strip "strip" (args) => str_strip,
+        Lstrip "lstrip" (args) => str_lstrip,
+        Rstrip "rstrip" (args) => str_rstrip,
+        Removeprefix "removeprefix" (args) => str_removeprefix,
+        Removesuffix "removesuffix" (args) => str_removesuffix,
         // Split methods
-        StaticStrings::Split => str_split(s, args, heap, interns),
-        StaticStrings::Rsplit => str_rsplit(s, args, heap, interns),
-        StaticStrings::Splitlines => str_splitlines(s, args, heap, interns),
-        StaticStrings::Partition => str_partition(s, args, heap, interns),
-        StaticStrings::Rpartition => str_rpartition(s, args, heap, interns),
+        Split "split" (args) => str_split,
+        Rsplit "rsplit" (args) => str_rsplit,
+        Splitlines "splitlines" (args) => str_splitlines,
+        Partition "partition" (args) => str_partition,
+        Rpartition "rpartition" (args) => str_rpartition,
         // Replace/modify methods
-        StaticStrings::Replace => str_replace(s, args, heap, interns),
-        StaticStrings::Center => str_center(s, args, heap, interns),
-        StaticStrings::Ljust => str_ljust(s, args, heap, interns),
-        StaticStrings::Rjust => str_rjust(s, args, heap, interns),
-        StaticStrings::Zfill => str_zfill(s, args, heap),
-        // Additional methods
-        StaticStrings::Encode => str_encode(s, args, heap, interns),
-        StaticStrings::Isidentifier => {
-            args.check_zero_args("str.isidentifier", heap)?;
-            Ok(Value::Bool(str_isidentifier(s)))
-        }
-        StaticStrings::Istitle => {
-            args.check_zero_args("str.istitle", heap)?;
-            Ok(Value::Bool(str_istitle(s)))
-        }
-        // Existing method
-        StaticStrings::Join => {
-            let iterable = args.get_one_arg("str.join", heap)?;
-            str_join(s, iterable, heap, interns)
-        }
-        _ => {
-            args.drop_with_heap(heap);
-            Err(ExcType::attribute_error(Type::Str, method.into()))
-        }
+        Replace "replace" (args) => str_replace,
+        Center "center" (args) => str_center,
+        Ljust "ljust" (args) => str_ljust,
+        Rjust "rjust" (args) => str_rjust,
+        Zfill "zfill" (one) => str_zfill,
+        Encode "encode" (args) => str_encode,
+        Join "join" (one) => str_join,
     }
 }
 
@@ -605,19 +546,19 @@ impl fmt::Display for StringRepr<'_> {
 // =============================================================================
 
 /// Implements Python's `str.lower()` method.
-fn str_lower(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn str_lower(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     allocate_string(s.to_lowercase(), heap)
 }
 
 /// Implements Python's `str.upper()` method.
-fn str_upper(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn str_upper(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     allocate_string(s.to_uppercase(), heap)
 }
 
 /// Implements Python's `str.capitalize()` method.
 ///
 /// Returns a copy of the string with its first character capitalized and the rest lowercased.
-fn str_capitalize(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn str_capitalize(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let mut chars = s.chars();
     let result = match chars.next() {
         None => String::new(),
@@ -636,7 +577,7 @@ fn str_capitalize(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<V
 ///
 /// Returns a titlecased version of the string where words start with an uppercase
 /// character and the remaining characters are lowercase.
-fn str_title(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn str_title(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let mut result = String::with_capacity(s.len());
     let mut prev_is_cased = false;
 
@@ -655,7 +596,7 @@ fn str_title(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value>
 /// Implements Python's `str.swapcase()` method.
 ///
 /// Returns a copy of the string with uppercase characters converted to lowercase and vice versa.
-fn str_swapcase(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
+fn str_swapcase(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
     let mut result = String::with_capacity(s.len());
 
     for c in s.chars() {
@@ -674,10 +615,125 @@ fn str_swapcase(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Val
 /// Implements Python's `str.casefold()` method.
 ///
 /// Returns a casefolded copy of the string. Casefolding is similar to lowercasing
-/// but more aggressive because it is intended for caseless string matching.
-fn str_casefold(s: &str, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
-    // Rust's to_lowercase() is equivalent to Unicode casefolding for most purposes
-    allocate_string(s.to_lowercase(), heap)
+/// but more aggressive because it is intended for caseless string matching,
+/// e.g. `'ß'.casefold() == 'ss'` while `'ß'.lower() == 'ß'`.
+fn str_casefold(s: &str, heap: &mut Heap<impl ResourceTracker>, _interns: &Interns) -> RunResult<Value> {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        casefold_char(c, &mut result);
+    }
+    allocate_string(result, heap)
+}
+
+/// Appends the full Unicode case folding of `c` to `out`.
+///
+/// Each character is folded on its own, so unlike `str::to_lowercase` a final
+/// sigma folds to `σ`. Most characters fold to their lowercase form; this handles
+/// the entries of Unicode's `CaseFolding.txt` where the two differ.
+fn casefold_char(c: char, out: &mut String) {
+    let folded = match c {
+        '\u{b5}' => "\u{3bc}",
+        '\u{df}' | '\u{1e9e}' => "ss",
+        '\u{149}' => "\u{2bc}n",
+        '\u{17f}' => "s",
+        '\u{1f0}' => "j\u{30c}",
+        '\u{345}' | '\u{1fbe}' => "\u{3b9}",
+        '\u{390}' => "\u{3b9}\u{308}\u{301}",
+        '\u{3b0}' => "\u{3c5}\u{308}\u{301}",
+        '\u{3c2}' => "\u{3c3}",
+        '\u{3d0}' => "\u{3b2}",
+        '\u{3d1}' => "\u{3b8}",
+        '\u{3d5}' => "\u{3c6}",
+        '\u{3d6}' => "\u{3c0}",
+        '\u{3f0}' => "\u{3ba}",
+        '\u{3f1}' => "\u{3c1}",
+        '\u{3f5}' => "\u{3b5}",
+        '\u{587}' => "\u{565}\u{582}",
+        '\u{1c80}' => "\u{432}",
+        '\u{1c81}' => "\u{434}",
+        '\u{1c82}' => "\u{43e}",
+        '\u{1c83}' => "\u{441}",
+        '\u{1c84}' | '\u{1c85}' => "\u{442}",
+        '\u{1c86}' => "\u{44a}",
+        '\u{1c87}' => "\u{463}",
+        '\u{1c88}' => "\u{a64b}",
+        '\u{1e96}' => "h\u{331}",
+        '\u{1e97}' => "t\u{308}",
+        '\u{1e98}' => "w\u{30a}",
+        '\u{1e99}' => "y\u{30a}",
+        '\u{1e9a}' => "a\u{2be}",
+        '\u{1e9b}' => "\u{1e61}",
+        '\u{1f50}' => "\u{3c5}\u{313}",
+        '\u{1f52}' => "\u{3c5}\u{313}\u{300}",
+        '\u{1f54}' => "\u{3c5}\u{313}\u{301}",
+        '\u{1f56}' => "\u{3c5}\u{313}\u{342}",
+        '\u{1fb2}' => "\u{1f70}\u{3b9}",
+        '\u{1fb3}' | '\u{1fbc}' => "\u{3b1}\u{3b9}",
+        '\u{1fb4}' => "\u{3ac}\u{3b9}",
+        '\u{1fb6}' => "\u{3b1}\u{342}",
+        '\u{1fb7}' => "\u{3b1}\u{342}\u{3b9}",
+        '\u{1fc2}' => "\u{1f74}\u{3b9}",
+        '\u{1fc3}' | '\u{1fcc}' => "\u{3b7}\u{3b9}",
+        '\u{1fc4}' => "\u{3ae}\u{3b9}",
+        '\u{1fc6}' => "\u{3b7}\u{342}",
+        '\u{1fc7}' => "\u{3b7}\u{342}\u{3b9}",
+        '\u{1fd2}' => "\u{3b9}\u{308}\u{300}",
+        '\u{1fd3}' => "\u{3b9}\u{308}\u{301}",
+        '\u{1fd6}' => "\u{3b9}\u{342}",
+        '\u{1fd7}' => "\u{3b9}\u{308}\u{342}",
+        '\u{1fe2}' => "\u{3c5}\u{308}\u{300}",
+        '\u{1fe3}' => "\u{3c5}\u{308}\u{301}",
+        '\u{1fe4}' => "\u{3c1}\u{313}",
+        '\u{1fe6}' => "\u{3c5}\u{342}",
+        '\u{1fe7}' => "\u{3c5}\u{308}\u{342}",
+        '\u{1ff2}' => "\u{1f7c}\u{3b9}",
+        '\u{1ff3}' | '\u{1ffc}' => "\u{3c9}\u{3b9}",
+        '\u{1ff4}' => "\u{3ce}\u{3b9}",
+        '\u{1ff6}' => "\u{3c9}\u{342}",
+        '\u{1ff7}' => "\u{3c9}\u{342}\u{3b9}",
+        '\u{fb00}' => "ff",
+        '\u{fb01}' => "fi",
+        '\u{fb02}' => "fl",
+        '\u{fb03}' => "ffi",
+        '\u{fb04}' => "ffl",
+        '\u{fb05}' | '\u{fb06}' => "st",
+        '\u{fb13}' => "\u{574}\u{576}",
+        '\u{fb14}' => "\u{574}\u{565}",
+        '\u{fb15}' => "\u{574}\u{56b}",
+        '\u{fb16}' => "\u{57e}\u{576}",
+        '\u{fb17}' => "\u{574}\u{56d}",
+        // Greek with ypogegrammeni/prosgegrammeni: both cases fold to the
+        // lowercase letter followed by iota
+        '\u{1f80}'..='\u{1faf}' => {
+            let base = match c {
+                '\u{1f80}'..='\u{1f8f}' => 0x1f00,
+                '\u{1f90}'..='\u{1f9f}' => 0x1f20,
+                _ => 0x1f60,
+            };
+            let letter = base + (u32::from(c) & 0x7);
+            out.push(char::from_u32(letter).expect("Greek letter is a valid char"));
+            out.push('\u{3b9}');
+            return;
+        }
+        // Cherokee folds to its uppercase letters, which are the older assignments
+        '\u{13a0}'..='\u{13f5}' => {
+            out.push(c);
+            return;
+        }
+        '\u{13f8}'..='\u{13fd}' | '\u{ab70}'..='\u{abbf}' => {
+            let upper = match c {
+                '\u{13f8}'..='\u{13fd}' => u32::from(c) - 8,
+                _ => u32::from(c) - 0xab70 + 0x13a0,
+            };
+            out.push(char::from_u32(upper).expect("Cherokee letter is a valid char"));
+            return;
+        }
+        _ => {
+            out.extend(c.to_lowercase());
+            return;
+        }
+    };
+    out.push_str(folded);
 }
 
 // =============================================================================
@@ -1955,8 +2011,12 @@ fn parse_justify_args(
 ///
 /// Returns a copy of the string left filled with ASCII '0' digits to make a
 /// string of length width. A sign prefix is handled correctly.
-fn str_zfill(s: &str, args: ArgValues, heap: &mut Heap<impl ResourceTracker>) -> RunResult<Value> {
-    let width_value = args.get_one_arg("str.zfill", heap)?;
+fn str_zfill(
+    s: &str,
+    width_value: Value,
+    heap: &mut Heap<impl ResourceTracker>,
+    _interns: &Interns,
+) -> RunResult<Value> {
     defer_drop!(width_value, heap);
     let width_i64 = extract_int_arg(width_value, heap)?;
 
@@ -1998,24 +2058,11 @@ fn str_zfill(s: &str, args: ArgValues, heap: &mut Heap<impl ResourceTracker>) ->
 
 /// Implements Python's `str.encode(encoding='utf-8', errors='strict')` method.
 ///
-/// Returns an encoded version of the string as a bytes object. Only supports
-/// UTF-8 encoding (the native encoding for Rust strings).
+/// Returns an encoded version of the string as a bytes object, using the UTF-8,
+/// ASCII or Latin-1 codec. See `codecs` for the supported error handlers.
 fn str_encode(s: &str, args: ArgValues, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Value> {
     let (encoding, errors) = parse_encode_args(args, heap, interns)?;
-
-    // Only UTF-8 is supported - Rust strings are always valid UTF-8
-    let encoding_lower = encoding.to_ascii_lowercase();
-    if encoding_lower != "utf-8" && encoding_lower != "utf8" {
-        return Err(ExcType::lookup_error_unknown_encoding(&encoding));
-    }
-
-    // For UTF-8 encoding of a valid UTF-8 string, errors mode doesn't matter
-    // since there's nothing to handle - the string is already valid UTF-8
-    if errors != "strict" && errors != "ignore" && errors != "replace" && errors != "backslashreplace" {
-        return Err(ExcType::lookup_error_unknown_error_handler(&errors));
-    }
-
-    let bytes = s.as_bytes().to_vec();
+    let bytes = Codec::lookup(&encoding)?.encode(s, &errors)?;
     let heap_id = heap.allocate(HeapData::Bytes(Bytes::new(bytes)))?;
     Ok(Value::Ref(heap_id))
 }
@@ -2032,14 +2079,14 @@ fn parse_encode_args(
 
     let encoding = if let Some(v) = first {
         defer_drop!(v, heap);
-        extract_string_arg(v, heap, interns)?
+        codec_arg("encode", "encoding", v, heap, interns)?
     } else {
         "utf-8".to_owned()
     };
 
     let errors = if let Some(v) = second {
         defer_drop!(v, heap);
-        extract_string_arg(v, heap, interns)?
+        codec_arg("encode", "errors", v, heap, interns)?
     } else {
         "strict".to_owned()
     };
@@ -29,13 +29,14 @@ pub(crate) type TupleVec = SmallVec<[Value; TUPLE_INLINE_CAPACITY]>;
 use super::{
     MontyIter, PyTrait,
     list::{get_slice_items, repr_sequence_fmt},
+    methods::py_methods,
 };
 use crate::{
     args::ArgValues,
     defer_drop,
     exception_private::{ExcType, RunResult},
-    heap::{Heap, HeapData, HeapGuard, HeapId},
-    intern::{Interns, StaticStrings},
+    heap::{Heap, HeapData, HeapId, HeapIdMap},
+    intern::Interns,
     resource::{DepthGuard, ResourceError, ResourceTracker},
     types::Type,
     value::{EitherStr, Value},
@@ -55,6 +56,11 @@ use crate::{
 /// The `contains_refs` flag tracks whether the tuple contains any `Value::Ref` items.
 /// This allows `collect_child_ids` and `py_dec_ref_ids` to skip iteration when the
 /// tuple contains only primitive values (ints, bools, None, etc.).
+///
+/// # Slicing and Repetition
+/// A slice covering every item, or repetition by one, returns the tuple itself. Any other
+/// slice or repeat copies the selected items into a new tuple; tuples never share part of
+/// a backing vector, for the same ownership reasons as [`List`](super::List).
 #[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub(crate) struct Tuple {
     items: TupleVec,
@@ -95,6 +101,13 @@ impl Tuple {
         self.contains_refs
     }
 
+    /// Rewrites heap ids in the tuple's items after heap compaction.
+    pub(crate) fn remap_heap_ids(&mut self, map: &HeapIdMap) {
+        if self.contains_refs {
+            map.remap_values(self.items.iter_mut());
+        }
+    }
+
     /// Creates a tuple from the `tuple()` constructor call.
     ///
     /// - `tuple()` with no args returns an empty tuple (singleton)
@@ -258,18 +271,7 @@ impl PyTrait for Tuple {
         args: ArgValues,
         interns: &Interns,
     ) -> RunResult<Value> {
-        let args_guard = HeapGuard::new(args, heap);
-        match attr.static_string() {
-            Some(StaticStrings::Index) => {
-                let (args, heap) = args_guard.into_parts();
-                tuple_index(self, args, heap, interns)
-            }
-            Some(StaticStrings::Count) => {
-                let (args, heap) = args_guard.into_parts();
-                tuple_count(self, args, heap, interns)
-            }
-            _ => Err(ExcType::attribute_error(Type::Tuple, attr.as_str(interns))),
-        }
+        call_tuple_method(self, attr, args, heap, interns)
     }
 
     fn py_bool(&self, _heap: &Heap<impl ResourceTracker>, _interns: &Interns) -> bool {
@@ -288,6 +290,14 @@ impl PyTrait for Tuple {
     }
 }
 
+py_methods! {
+    /// Dispatches a method call on a tuple value.
+    fn call_tuple_method(&Tuple, Type::Tuple, "tuple") {
+        Index "index" (args) => tuple_index,
+        Count "count" (one) => tuple_count,
+    }
+}
+
 /// Implements Python's `tuple.index(value[, start[, end]])` method.
 ///
 /// Returns the index of the first occurrence of value.
@@ -303,7 +313,7 @@ fn tuple_index(
 
     let len = tuple.as_slice().len();
     let (value, start, end) = match pos_args.as_slice() {
-        [] => return Err(ExcType::type_error_at_least("tuple.index", 1, 0)),
+        [] => return Err(ExcType::type_error_at_least("index", 1, 0)),
         [value] => (value, 0, len),
         [value, start_arg] => {
             let start = normalize_tuple_index(start_arg.as_int(heap)?, len);
@@ -314,7 +324,7 @@ fn tuple_index(
             let end = normalize_tuple_index(end_arg.as_int(heap)?, len).max(start);
             (value, start, end)
         }
-        other => return Err(ExcType::type_error_at_most("tuple.index", 3, other.len())),
+        other => return Err(ExcType::type_error_at_most("index", 3, other.len())),
     };
 
     let mut guard = DepthGuard::default();
@@ -334,11 +344,10 @@ fn tuple_index(
 /// Returns the number of occurrences of value in the tuple.
 fn tuple_count(
     tuple: &Tuple,
-    args: ArgValues,
+    value: Value,
     heap: &mut Heap<impl ResourceTracker>,
     interns: &Interns,
 ) -> RunResult<Value> {
-    let value = args.get_one_arg("tuple.count", heap)?;
     defer_drop!(value, heap);
 
     let mut guard = DepthGuard::default();
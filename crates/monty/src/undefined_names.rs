@@ -0,0 +1,355 @@
+//! Static detection of names that are read before anything assigns them.
+//!
+//! `prepare` gives a name the `LocalUnassigned` scope when nothing binds it at the point
+//! it's first resolved: at module level that's a read before the name's first assignment
+//! in source order, and in a function it's a name that is neither local, captured, nor a
+//! global assigned before the function is defined. Running such a read raises `NameError`.
+//!
+//! A read inside a module-level loop may follow an assignment made by an earlier
+//! iteration, so those reads are only reported when the name is never assigned at module
+//! level.
+
+use ahash::AHashSet;
+
+use crate::{
+    args::ArgExprs,
+    expressions::{
+        Callable, Comprehension, Expr, ExprLoc, Identifier, NameScope, Node, PreparedFunctionDef, PreparedNode,
+        UnpackTarget,
+    },
+    fstring::{FStringPart, FormatSpec},
+    parse::Try,
+};
+
+/// Finds the reads in `nodes` that raise `NameError` when they run, in source order.
+pub(crate) fn find_undefined_names(nodes: &[PreparedNode]) -> Vec<Identifier> {
+    let mut finder = Finder::default();
+    finder.visit_block(nodes);
+    let Finder {
+        mut found,
+        in_loop,
+        module_assigned,
+        ..
+    } = finder;
+    found.extend(
+        in_loop
+            .into_iter()
+            .filter(|ident| !module_assigned.contains(&ident.namespace_id().index())),
+    );
+    found.sort_by_key(|ident| {
+        let start = ident.position.start();
+        (start.line, start.column)
+    });
+    found
+}
+
+#[derive(Default)]
+struct Finder {
+    /// Reads that are undefined wherever they run.
+    found: Vec<Identifier>,
+    /// Module-level reads inside a loop, kept until every module-level assignment is known.
+    in_loop: Vec<Identifier>,
+    /// Module namespace slots assigned anywhere at module level.
+    module_assigned: AHashSet<usize>,
+    /// Whether the walk is inside a function body.
+    in_function: bool,
+    /// Number of module-level loops enclosing the walk.
+    loop_depth: usize,
+}
+
+impl Finder {
+    fn read(&mut self, ident: &Identifier) {
+        match ident.scope {
+            NameScope::LocalUnassigned if !self.in_function && self.loop_depth > 0 => {
+                self.in_loop.push(*ident);
+            }
+            NameScope::LocalUnassigned => self.found.push(*ident),
+            NameScope::Local | NameScope::Global | NameScope::Cell => {}
+        }
+    }
+
+    fn assign(&mut self, ident: &Identifier) {
+        if !self.in_function {
+            self.module_assigned.insert(ident.namespace_id().index());
+        }
+    }
+
+    fn assign_target(&mut self, target: &UnpackTarget) {
+        match target {
+            UnpackTarget::Name(ident) | UnpackTarget::Starred(ident) => self.assign(ident),
+            UnpackTarget::Tuple { targets, .. } => {
+                for target in targets {
+                    self.assign_target(target);
+                }
+            }
+            UnpackTarget::Subscript { object, index, .. } => {
+                self.visit_expr(object);
+                self.visit_expr(index);
+            }
+            UnpackTarget::Attr { object, .. } => self.visit_expr(object),
+        }
+    }
+
+    fn visit_block(&mut self, nodes: &[PreparedNode]) {
+        for node in nodes {
+            self.visit_node(node);
+        }
+    }
+
+    /// Visits a loop body, where module-level reads may see an earlier iteration's assignments.
+    fn visit_loop(&mut self, nodes: &[PreparedNode]) {
+        self.loop_depth += 1;
+        self.visit_block(nodes);
+        self.loop_depth -= 1;
+    }
+
+    fn visit_node(&mut self, node: &PreparedNode) {
+        match node {
+            Node::Expr(expr) | Node::Return(expr) => self.visit_expr(expr),
+            Node::Raise(expr) => {
+                if let Some(expr) = expr {
+                    self.visit_expr(expr);
+                }
+            }
+            Node::RaiseFrom { exc, cause } => {
+                self.visit_expr(exc);
+                self.visit_expr(cause);
+            }
+            Node::Assert { test, msg } => {
+                self.visit_expr(test);
+                if let Some(msg) = msg {
+                    self.visit_expr(msg);
+                }
+            }
+            Node::Assign { target, object } => {
+                self.visit_expr(object);
+                self.assign(target);
+            }
+            Node::UnpackAssign { targets, object, .. } => {
+                self.visit_expr(object);
+                for target in targets {
+                    self.assign_target(target);
+                }
+            }
+            Node::OpAssign { target, object, .. } => {
+                self.read(target);
+                self.visit_expr(object);
+                self.assign(target);
+            }
+            Node::SubscriptAssign {
+                target, index, value, ..
+            } => {
+                self.read(target);
+                self.visit_expr(index);
+                self.visit_expr(value);
+            }
+            Node::AttrAssign { object, value, .. } => {
+                self.visit_expr(object);
+                self.visit_expr(value);
+            }
+            Node::For {
+                target,
+                iter,
+                body,
+                or_else,
+            } => {
+                self.visit_expr(iter);
+                self.assign_target(target);
+                self.visit_loop(body);
+                self.visit_block(or_else);
+            }
+            Node::While { test, body, or_else } => {
+                self.loop_depth += 1;
+                self.visit_expr(test);
+                self.loop_depth -= 1;
+                self.visit_loop(body);
+                self.visit_block(or_else);
+            }
+            Node::If { test, body, or_else } => {
+                self.visit_expr(test);
+                self.visit_block(body);
+                self.visit_block(or_else);
+            }
+            Node::FunctionDef(func_def) => {
+                self.visit_function(func_def);
+                self.assign(&func_def.name);
+            }
+            Node::Try(Try {
+                body,
+                handlers,
+                or_else,
+                finally,
+            }) => {
+                self.visit_block(body);
+                for handler in handlers {
+                    if let Some(exc_type) = &handler.exc_type {
+                        self.visit_expr(exc_type);
+                    }
+                    if let Some(name) = &handler.name {
+                        self.assign(name);
+                    }
+                    self.visit_block(&handler.body);
+                }
+                self.visit_block(or_else);
+                self.visit_block(finally);
+            }
+            Node::Import { binding, .. } => self.assign(binding),
+            Node::ImportFrom { names, .. } => {
+                for (_, binding) in names {
+                    self.assign(binding);
+                }
+            }
+            Node::Pass
+            | Node::ReturnNone
+            | Node::Break { .. }
+            | Node::Continue { .. }
+            | Node::Global { .. }
+            | Node::Nonlocal { .. } => {}
+        }
+    }
+
+    fn visit_function(&mut self, func_def: &PreparedFunctionDef) {
+        // defaults are evaluated in the enclosing scope when the function is defined
+        for default in &func_def.default_exprs {
+            self.visit_expr(default);
+        }
+        let outer_in_function = std::mem::replace(&mut self.in_function, true);
+        let outer_loop_depth = std::mem::take(&mut self.loop_depth);
+        self.visit_block(&func_def.body);
+        self.in_function = outer_in_function;
+        self.loop_depth = outer_loop_depth;
+    }
+
+    fn visit_exprs(&mut self, exprs: &[ExprLoc]) {
+        for expr in exprs {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_args(&mut self, args: &ArgExprs) {
+        match args {
+            ArgExprs::Empty => {}
+            ArgExprs::One(arg) => self.visit_expr(arg),
+            ArgExprs::Two(first, second) => {
+                self.visit_expr(first);
+                self.visit_expr(second);
+            }
+            ArgExprs::Args(args) => self.visit_exprs(args),
+            ArgExprs::Kwargs(kwargs) => {
+                for kwarg in kwargs {
+                    self.visit_expr(&kwarg.value);
+                }
+            }
+            ArgExprs::ArgsKargs {
+                args,
+                var_args,
+                kwargs,
+                var_kwargs,
+            } => {
+                self.visit_exprs(args.as_deref().unwrap_or_default());
+                if let Some(var_args) = var_args {
+                    self.visit_expr(var_args);
+                }
+                for kwarg in kwargs.as_deref().unwrap_or_default() {
+                    self.visit_expr(&kwarg.value);
+                }
+                if let Some(var_kwargs) = var_kwargs {
+                    self.visit_expr(var_kwargs);
+                }
+            }
+        }
+    }
+
+    fn visit_generators(&mut self, generators: &[Comprehension]) {
+        for generator in generators {
+            self.visit_expr(&generator.iter);
+            self.visit_exprs(&generator.ifs);
+        }
+    }
+
+    fn visit_fstring(&mut self, parts: &[FStringPart]) {
+        for part in parts {
+            if let FStringPart::Interpolation { expr, format_spec, .. } = part {
+                self.visit_expr(expr);
+                if let Some(FormatSpec::Dynamic(spec_parts)) = format_spec {
+                    self.visit_fstring(spec_parts);
+                }
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr_loc: &ExprLoc) {
+        match &expr_loc.expr {
+            Expr::Name(ident) => self.read(ident),
+            Expr::Call { callable, args } => {
+                if let Callable::Name(ident) = callable {
+                    self.read(ident);
+                }
+                self.visit_args(args);
+            }
+            Expr::AttrCall { object, args, .. } => {
+                self.visit_expr(object);
+                self.visit_args(args);
+            }
+            Expr::IndirectCall { callable, args } => {
+                self.visit_expr(callable);
+                self.visit_args(args);
+            }
+            Expr::AttrGet { object, .. }
+            | Expr::Not(object)
+            | Expr::UnaryMinus(object)
+            | Expr::UnaryPlus(object)
+            | Expr::UnaryInvert(object)
+            | Expr::Await(object) => self.visit_expr(object),
+            Expr::Op { left, right, .. } | Expr::CmpOp { left, right, .. } => {
+                self.visit_expr(left);
+                self.visit_expr(right);
+            }
+            Expr::ChainCmp { left, comparisons } => {
+                self.visit_expr(left);
+                for (_, operand) in comparisons {
+                    self.visit_expr(operand);
+                }
+            }
+            Expr::List(elements) | Expr::Tuple(elements) | Expr::Set(elements) => self.visit_exprs(elements),
+            Expr::Dict(pairs) => {
+                for (key, value) in pairs {
+                    self.visit_expr(key);
+                    self.visit_expr(value);
+                }
+            }
+            Expr::Subscript { object, index } => {
+                self.visit_expr(object);
+                self.visit_expr(index);
+            }
+            Expr::Slice { lower, upper, step } => {
+                for part in [lower, upper, step].into_iter().flatten() {
+                    self.visit_expr(part);
+                }
+            }
+            Expr::FString(parts) => self.visit_fstring(parts),
+            Expr::IfElse { test, body, orelse } => {
+                self.visit_expr(test);
+                self.visit_expr(body);
+                self.visit_expr(orelse);
+            }
+            Expr::ListComp { elt, generators }
+            | Expr::SetComp { elt, generators }
+            | Expr::Generator { elt, generators } => {
+                self.visit_expr(elt);
+                self.visit_generators(generators);
+            }
+            Expr::DictComp { key, value, generators } => {
+                self.visit_expr(key);
+                self.visit_expr(value);
+                self.visit_generators(generators);
+            }
+            Expr::Lambda { func_def } => self.visit_function(func_def),
+            Expr::Named { target, value } => {
+                self.visit_expr(value);
+                self.assign(target);
+            }
+            Expr::Literal(_) | Expr::Builtin(_) | Expr::LambdaRaw { .. } => {}
+        }
+    }
+}
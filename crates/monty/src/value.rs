@@ -5,7 +5,6 @@ use std::{
     fmt::{self, Write},
     hash::{Hash, Hasher},
     mem::discriminant,
-    str::FromStr,
 };
 
 use ahash::AHashSet;
@@ -17,18 +16,20 @@ use crate::{
     asyncio::CallId,
     builtins::Builtins,
     exception_private::{ExcType, RunError, RunResult, SimpleException},
+    fstring::float_repr,
     heap::{Heap, HeapData, HeapId},
     intern::{BytesId, ExtFunctionId, FunctionId, Interns, LongIntId, StaticStrings, StringId},
     modules::ModuleFunctions,
     resource::{
-        DepthGuard, ResourceError, ResourceTracker, check_div_size, check_lshift_size, check_pow_size,
+        CollectionKind, DepthGuard, ResourceError, ResourceTracker, check_div_size, check_lshift_size, check_pow_size,
         check_repeat_size,
     },
     types::{
         AttrCallResult, LongInt, Property, PyTrait, Str, Type,
         bytes::{bytes_repr_fmt, get_byte_at_index, get_bytes_slice},
+        list::sequences_eq,
         path,
-        str::{allocate_char, get_char_at_index, get_str_slice, string_repr_fmt},
+        str::{allocate_char, allocate_str, get_char_at_index, get_str_slice, string_repr_fmt},
     },
 };
 
@@ -238,6 +239,10 @@ impl PyTrait for Value {
                 if *id1 == *id2 {
                     return Ok(true);
                 }
+                // Lists and tuples are compared without recursing into nested ones
+                if let Some(equal) = sequences_eq(*id1, *id2, heap, guard, interns) {
+                    return equal;
+                }
                 // Need to use with_two for proper borrow management
                 heap.with_two(*id1, *id2, |heap, left, right| left.py_eq(right, heap, guard, interns))
             }
@@ -373,14 +378,7 @@ impl PyTrait for Value {
             Self::Bool(false) => f.write_str("False"),
             Self::Int(v) => write!(f, "{v}"),
             Self::InternLongInt(long_int_id) => write!(f, "{}", interns.get_long_int(*long_int_id)),
-            Self::Float(v) => {
-                let s = v.to_string();
-                if s.contains('.') {
-                    f.write_str(&s)
-                } else {
-                    write!(f, "{s}.0")
-                }
-            }
+            Self::Float(v) => f.write_str(&float_repr(*v)),
             Self::Builtin(b) => b.py_repr_fmt(f),
             Self::ModuleFunction(mf) => mf.py_repr_fmt(f, self.id()),
             Self::DefFunction(f_id) => interns.get_function(*f_id).py_repr_fmt(f, interns, self.id()),
@@ -485,13 +483,13 @@ impl PyTrait for Value {
             }
             (Self::InternString(s1), Self::InternString(s2)) => {
                 let concat = format!("{}{}", interns.get_str(*s1), interns.get_str(*s2));
-                Ok(Some(Self::Ref(heap.allocate(HeapData::Str(concat.into()))?)))
+                Ok(Some(allocate_str(concat, heap)?))
             }
             // for strings we need to account for the fact they might be either interned or not
             (Self::InternString(string_id), Self::Ref(id2)) => {
                 if let HeapData::Str(s2) = heap.get(*id2) {
                     let concat = format!("{}{}", interns.get_str(*string_id), s2.as_str());
-                    Ok(Some(Self::Ref(heap.allocate(HeapData::Str(concat.into()))?)))
+                    Ok(Some(allocate_str(concat, heap)?))
                 } else {
                     Ok(None)
                 }
@@ -499,7 +497,7 @@ impl PyTrait for Value {
             (Self::Ref(id1), Self::InternString(string_id)) => {
                 if let HeapData::Str(s1) = heap.get(*id1) {
                     let concat = format!("{}{}", s1.as_str(), interns.get_str(*string_id));
-                    Ok(Some(Self::Ref(heap.allocate(HeapData::Str(concat.into()))?)))
+                    Ok(Some(allocate_str(concat, heap)?))
                 } else {
                     Ok(None)
                 }
@@ -730,13 +728,13 @@ impl PyTrait for Value {
             }
             (Self::InternString(s1), Self::InternString(s2)) => {
                 let concat = format!("{}{}", interns.get_str(*s1), interns.get_str(*s2));
-                *self = Self::Ref(heap.allocate(HeapData::Str(concat.into()))?);
+                *self = allocate_str(concat, heap)?;
                 Ok(true)
             }
             (Self::InternString(string_id), Self::Ref(id2)) => {
                 let result = if let HeapData::Str(s2) = heap.get(*id2) {
                     let concat = format!("{}{}", interns.get_str(*string_id), s2.as_str());
-                    *self = Self::Ref(heap.allocate(HeapData::Str(concat.into()))?);
+                    *self = allocate_str(concat, heap)?;
                     true
                 } else {
                     false
@@ -746,12 +744,17 @@ impl PyTrait for Value {
                 Ok(result)
             }
             (Self::Ref(id1), Self::InternString(string_id)) => {
+                let suffix = interns.get_str(*string_id);
+                let HeapData::Str(s1) = heap.get(*id1) else {
+                    return Ok(false);
+                };
+                heap.tracker()
+                    .check_collection_len(CollectionKind::Str, s1.as_str().len() + suffix.len())?;
+                heap.charge_growth(suffix.len())?;
                 if let HeapData::Str(s1) = heap.get_mut(*id1) {
-                    s1.as_string_mut().push_str(interns.get_str(*string_id));
-                    Ok(true)
-                } else {
-                    Ok(false)
+                    s1.as_string_mut().push_str(suffix);
                 }
+                Ok(true)
             }
             // same for bytes
             (Self::InternBytes(b1), Self::InternBytes(b2)) => {
@@ -779,12 +782,15 @@ impl PyTrait for Value {
                 Ok(result)
             }
             (Self::Ref(id1), Self::InternBytes(bytes_id)) => {
+                if !matches!(heap.get(*id1), HeapData::Bytes(_)) {
+                    return Ok(false);
+                }
+                let suffix = interns.get_bytes(*bytes_id);
+                heap.charge_growth(suffix.len())?;
                 if let HeapData::Bytes(b1) = heap.get_mut(*id1) {
-                    b1.as_vec_mut().extend_from_slice(interns.get_bytes(*bytes_id));
-                    Ok(true)
-                } else {
-                    Ok(false)
+                    b1.as_vec_mut().extend_from_slice(suffix);
                 }
+                Ok(true)
             }
             (Self::Ref(id), Self::Ref(_)) => {
                 heap.with_entry_mut(*id, |heap, data| data.py_iadd(other, heap, Some(*id), interns))
@@ -851,8 +857,10 @@ impl PyTrait for Value {
                 let count = i64_to_repeat_count(*n)?;
                 let str_ref = interns.get_str(*s);
                 check_repeat_size(str_ref.len(), count, heap.tracker())?;
+                heap.tracker()
+                    .check_collection_len(CollectionKind::Str, str_ref.len().saturating_mul(count))?;
                 let result = str_ref.repeat(count);
-                Ok(Some(Self::Ref(heap.allocate(HeapData::Str(result.into()))?)))
+                Ok(Some(allocate_str(result, heap)?))
             }
 
             // Bytes repetition: b"ab" * 3 or 3 * b"ab"
@@ -870,8 +878,10 @@ impl PyTrait for Value {
                     let count = longint_to_repeat_count(li)?;
                     let str_ref = interns.get_str(*s);
                     check_repeat_size(str_ref.len(), count, heap.tracker())?;
+                    heap.tracker()
+                        .check_collection_len(CollectionKind::Str, str_ref.len().saturating_mul(count))?;
                     let result = str_ref.repeat(count);
-                    Ok(Some(Self::Ref(heap.allocate(HeapData::Str(result.into()))?)))
+                    Ok(Some(allocate_str(result, heap)?))
                 } else {
                     Ok(None)
                 }
@@ -1398,8 +1408,12 @@ impl PyTrait for Value {
     fn py_getitem(&self, key: &Self, heap: &mut Heap<impl ResourceTracker>, interns: &Interns) -> RunResult<Self> {
         match self {
             Self::Ref(id) => {
-                // Need to take entry out to allow mutable heap access
                 let id = *id;
+                if heap.is_whole_slice_of_immutable(id, key) {
+                    heap.inc_ref(id);
+                    return Ok(Self::Ref(id));
+                }
+                // Need to take entry out to allow mutable heap access
                 heap.with_entry_mut(id, |heap, data| data.py_getitem(key, heap, interns))
             }
             Self::InternString(string_id) => {
@@ -1413,8 +1427,7 @@ impl PyTrait for Value {
                         .indices(char_count)
                         .map_err(|()| ExcType::value_error_slice_step_zero())?;
                     let result_str = get_str_slice(s, start, stop, step);
-                    let heap_id = heap.allocate(HeapData::Str(Str::from(result_str)))?;
-                    return Ok(Self::Ref(heap_id));
+                    return Ok(allocate_str(result_str, heap)?);
                 }
 
                 // Handle interned string indexing, accepting Int and Bool
@@ -1742,6 +1755,13 @@ impl Value {
                     return Ok(AttrCallResult::Value(Self::Ref(str_id)));
                 }
             }
+            Self::Builtin(Builtins::ExcType(exc_type)) => {
+                if name_id == StaticStrings::DunderName {
+                    let name_str: &'static str = (*exc_type).into();
+                    let str_id = heap.allocate(HeapData::Str(Str::from(name_str)))?;
+                    return Ok(AttrCallResult::Value(Self::Ref(str_id)));
+                }
+            }
             _ => {}
         }
         let type_name = self.py_type(heap);
@@ -1750,7 +1770,7 @@ impl Value {
 
     /// Sets an attribute on this value.
     ///
-    /// Currently only Dataclass objects support attribute setting.
+    /// Currently only Dataclass and exception objects support attribute setting.
     /// Returns AttributeError for other types.
     ///
     /// Takes ownership of `value` and drops it on error.
@@ -1766,23 +1786,24 @@ impl Value {
 
         if let Self::Ref(heap_id) = self {
             let heap_id = *heap_id;
-            let is_dataclass = matches!(heap.get(heap_id), HeapData::Dataclass(_));
+            let has_attrs = matches!(heap.get(heap_id), HeapData::Dataclass(_) | HeapData::Exception(_));
 
-            if is_dataclass {
+            if has_attrs {
                 let name_value = Self::InternString(name_id);
                 heap.with_entry_mut(heap_id, |heap, data| {
-                    if let HeapData::Dataclass(dc) = data {
-                        match dc.set_attr(name_value, value, heap, interns) {
-                            Ok(old_value) => {
-                                if let Some(old) = old_value {
-                                    old.drop_with_heap(heap);
-                                }
-                                Ok(())
+                    let result = match data {
+                        HeapData::Dataclass(dc) => dc.set_attr(name_value, value, heap, interns),
+                        HeapData::Exception(exc) => exc.set_attr(name_value, value, heap, interns),
+                        _ => unreachable!("type changed during borrow"),
+                    };
+                    match result {
+                        Ok(old_value) => {
+                            if let Some(old) = old_value {
+                                old.drop_with_heap(heap);
                             }
-                            Err(e) => Err(e),
+                            Ok(())
                         }
-                    } else {
-                        unreachable!("type changed during borrow")
+                        Err(e) => Err(e),
                     }
                 })
             } else {
@@ -2089,9 +2110,9 @@ impl From<StaticStrings> for EitherStr {
 /// otherwise use Heap for user-defined field names.
 impl From<String> for EitherStr {
     fn from(s: String) -> Self {
-        match StaticStrings::from_str(&s) {
-            Ok(s) => s.into(),
-            Err(_) => Self::Heap(s),
+        match StaticStrings::from_static_str(&s) {
+            Some(s) => s.into(),
+            None => Self::Heap(s),
         }
     }
 }
@@ -2423,7 +2444,7 @@ fn longint_to_repeat_count(li: &LongInt) -> RunResult<usize> {
 ///
 /// Returns `Some(BigInt)` for Int, Bool, and LongInt values.
 /// Returns `None` for other types (Float, Str, etc.).
-fn extract_bigint(value: &Value, heap: &Heap<impl ResourceTracker>) -> Option<BigInt> {
+pub(crate) fn extract_bigint(value: &Value, heap: &Heap<impl ResourceTracker>) -> Option<BigInt> {
     match value {
         Value::Int(i) => Some(BigInt::from(*i)),
         Value::Bool(b) => Some(BigInt::from(i64::from(*b))),
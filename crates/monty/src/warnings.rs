@@ -0,0 +1,171 @@
+//! Warnings reported to the host instead of being dropped or raised as errors.
+//!
+//! The VM asks its [`ResourceTracker`] for a [`WarningSink`] through `warning_sink()`,
+//! which returns `None` by default, so runs nobody listens to never build warnings.
+//! Wrapping a tracker in [`WarningTracker`] attaches a sink.
+//!
+//! Compile-time warnings, such as an `assert` on a tuple that is always true, are found by
+//! `MontyRun::new` and kept with the compiled code: `MontyRun::warnings` returns them, and
+//! each run reports them to its sink before the first instruction executes. Runtime
+//! warnings, such as comparing `bytes` with `str`, are reported when they happen.
+
+use std::fmt;
+
+use crate::{
+    clock::Clock,
+    exception_public::CodeLoc,
+    resource::{CollectionKind, ResourceBudget, ResourceError, ResourceTracker},
+    timeline::SpanKind,
+    trace::TraceHook,
+};
+
+/// The kind of a [`MontyWarning`], named after the matching Python warning class.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::IntoStaticStr, serde::Serialize, serde::Deserialize,
+)]
+pub enum WarningCategory {
+    /// Dubious syntax found while compiling, e.g. `x is 1`.
+    SyntaxWarning,
+    /// Dubious behaviour found while running, e.g. a coroutine that is never awaited.
+    RuntimeWarning,
+    /// Implicit mixing of `str` and `bytes`, e.g. `b'a' == 'a'`.
+    BytesWarning,
+}
+
+/// A warning, located by the position of the code that caused it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MontyWarning {
+    /// The kind of warning.
+    pub category: WarningCategory,
+    /// Description of the problem.
+    pub message: String,
+    /// Script name passed to `MontyRun::new`.
+    pub filename: String,
+    /// Start of the code that caused the warning.
+    pub start: CodeLoc,
+}
+
+/// Formats the warning like Python's `warnings.formatwarning`, without the source line.
+impl fmt::Display for MontyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.filename, self.start.line, self.category, self.message
+        )
+    }
+}
+
+/// Receives the warnings of a run.
+///
+/// Called synchronously from the VM; warnings can't affect execution.
+pub trait WarningSink: fmt::Debug {
+    /// Called once for each warning.
+    fn warn(&mut self, warning: MontyWarning);
+}
+
+/// Collects warnings in order.
+impl WarningSink for Vec<MontyWarning> {
+    fn warn(&mut self, warning: MontyWarning) {
+        self.push(warning);
+    }
+}
+
+/// A resource tracker that wraps another tracker and reports warnings to a [`WarningSink`].
+///
+/// All limit checks are delegated to the inner tracker. The sink is not serialized: a
+/// deserialized tracker starts with `S::default()`; use `set_sink` to reattach the host's
+/// sink after loading a snapshot.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct WarningTracker<T: ResourceTracker, S: WarningSink> {
+    inner: T,
+    #[serde(skip)]
+    sink: S,
+}
+
+impl<T: ResourceTracker, S: WarningSink> WarningTracker<T, S> {
+    /// Creates a tracker wrapping `inner` and reporting warnings to `sink`.
+    #[must_use]
+    pub fn new(inner: T, sink: S) -> Self {
+        Self { inner, sink }
+    }
+
+    /// Returns a reference to the sink.
+    #[must_use]
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Returns a mutable reference to the sink.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Replaces the sink, e.g. after deserializing a snapshot.
+    pub fn set_sink(&mut self, sink: S) {
+        self.sink = sink;
+    }
+
+    /// Returns a mutable reference to the wrapped tracker.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ResourceTracker, S: WarningSink> ResourceTracker for WarningTracker<T, S> {
+    fn on_allocate(&mut self, get_size: impl FnOnce() -> usize) -> Result<(), ResourceError> {
+        self.inner.on_allocate(get_size)
+    }
+
+    fn on_free(&mut self, get_size: impl FnOnce() -> usize) {
+        self.inner.on_free(get_size);
+    }
+
+    fn on_grow(&mut self, additional: usize) -> Result<(), ResourceError> {
+        self.inner.on_grow(additional)
+    }
+
+    fn check_time(&self) -> Result<(), ResourceError> {
+        self.inner.check_time()
+    }
+
+    fn check_recursion_depth(&self, current_depth: usize) -> Result<(), ResourceError> {
+        self.inner.check_recursion_depth(current_depth)
+    }
+
+    fn check_large_result(&self, estimated_bytes: usize) -> Result<(), ResourceError> {
+        self.inner.check_large_result(estimated_bytes)
+    }
+
+    fn check_collection_len(&self, kind: CollectionKind, len: usize) -> Result<(), ResourceError> {
+        self.inner.check_collection_len(kind, len)
+    }
+
+    fn max_data_depth(&self) -> usize {
+        self.inner.max_data_depth()
+    }
+
+    fn on_span_enter(&mut self, kind: SpanKind, name: &str) {
+        self.inner.on_span_enter(kind, name);
+    }
+
+    fn on_span_exit(&mut self, kind: SpanKind) {
+        self.inner.on_span_exit(kind);
+    }
+
+    fn trace_hook(&mut self) -> Option<&mut dyn TraceHook> {
+        self.inner.trace_hook()
+    }
+
+    fn warning_sink(&mut self) -> Option<&mut dyn WarningSink> {
+        Some(&mut self.sink)
+    }
+
+    fn clock(&self) -> &dyn Clock {
+        self.inner.clock()
+    }
+
+    fn remaining_budget(&self) -> Option<ResourceBudget> {
+        self.inner.remaining_budget()
+    }
+}
@@ -0,0 +1,2 @@
+b'abc'.isalpha(1)
+# Raise=TypeError('bytes.isalpha() takes no arguments (1 given)')
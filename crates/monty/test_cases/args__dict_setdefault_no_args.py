@@ -0,0 +1,3 @@
+x = {}
+x.setdefault()
+# Raise=TypeError('setdefault expected at least 1 argument, got 0')
@@ -0,0 +1,3 @@
+x = [1, 2]
+x.clear(1)
+# Raise=TypeError('list.clear() takes no arguments (1 given)')
@@ -0,0 +1,3 @@
+x = [1, 2]
+x.index()
+# Raise=TypeError('index expected at least 1 argument, got 0')
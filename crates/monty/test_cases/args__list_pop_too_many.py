@@ -0,0 +1,3 @@
+x = [1]
+x.pop(0, 1)
+# Raise=TypeError('pop expected at most 1 argument, got 2')
@@ -0,0 +1,3 @@
+s = set()
+s.add()
+# Raise=TypeError('set.add() takes exactly one argument (0 given)')
@@ -0,0 +1,2 @@
+'abc'.upper(1)
+# Raise=TypeError('str.upper() takes no arguments (1 given)')
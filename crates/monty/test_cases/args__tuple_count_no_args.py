@@ -0,0 +1,3 @@
+x = (1, 2)
+x.count()
+# Raise=TypeError('tuple.count() takes exactly one argument (0 given)')
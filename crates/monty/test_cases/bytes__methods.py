@@ -375,13 +375,12 @@ assert b'hello'.decode('utf-8', 'strict') == 'hello', 'decode with strict errors
 assert b'hello'.decode('utf-8', 'ignore') == 'hello', 'decode with ignore errors'
 assert b'hello'.decode('utf-8', 'replace') == 'hello', 'decode with replace errors'
 
-# TODO: errors argument type validation - CPython raises TypeError for non-string errors
-# This is not implemented yet
-# try:
-#     b'hello'.decode('utf-8', 123)
-#     assert False, 'decode with non-string errors should error'
-# except TypeError as e:
-#     assert 'str' in str(e), f'decode errors type error should mention str, error: {e}'
+# errors argument type validation - CPython raises TypeError for non-string errors
+try:
+    b'hello'.decode('utf-8', 123)
+    assert False, 'decode with non-string errors should error'
+except TypeError as e:
+    assert 'str' in str(e), f'decode errors type error should mention str, error: {e}'
 
 # === Error message for unknown classmethod ===
 # Error message should say 'bytes' not 'type'
@@ -191,3 +191,14 @@ assert list(a + b for a, b in pairs) == [3, 7], 'generator with unpacking'
 assert ''.join(str(x) for x in range(5)) == '01234', 'list of strings join'
 a = '1', '2', '3'
 assert ''.join(a) == '123', 'tuple of strings join'
+
+# === Comprehensions over literal-bounded iterables (pre-sized at compile time) ===
+assert [x * 2 for x in range(4)] == [0, 2, 4, 6], 'list comp over range literal'
+assert [x for x in range(10, 0, -3)] == [10, 7, 4, 1], 'list comp over negative step range'
+assert [x for x in range(5, 5)] == [], 'list comp over empty range'
+assert {x % 3 for x in range(9)} == {0, 1, 2}, 'set comp over range literal'
+assert {k: k * k for k in (1, 2, 3)} == {1: 1, 2: 4, 3: 9}, 'dict comp over tuple literal'
+assert [x for x in [1, 2, 3, 4] if x > 2] == [3, 4], 'filtered list comp over list literal'
+big = [x for x in range(10000)]
+assert len(big) == 10000, 'list comp larger than pre-size cap'
+assert big[-1] == 9999, 'list comp larger than pre-size cap last item'
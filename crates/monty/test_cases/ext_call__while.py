@@ -0,0 +1,42 @@
+# call-external
+# === External calls in while loops ===
+
+# Ext call in the condition
+i = 0
+while add_ints(i, 1) < 4:
+    i += 1
+assert i == 3, 'ext call in while condition'
+
+# Ext call in the body, with break and else
+total = 0
+while True:
+    total = add_ints(total, 2)
+    if total >= 6:
+        break
+else:
+    total = -1
+assert total == 6, 'ext call in while True body'
+
+# Continue after an ext call
+i = 0
+odds = []
+while i < 6:
+    i = add_ints(i, 1)
+    if i % 2 == 0:
+        continue
+    odds.append(i)
+else:
+    odds.append('else')
+assert odds == [1, 3, 5, 'else'], 'ext call with continue and else'
+
+# Ext call in a finally inside the loop
+i = 0
+calls = 0
+while i < 3:
+    try:
+        i += 1
+        if i == 2:
+            continue
+    finally:
+        calls = add_ints(calls, 1)
+assert calls == 3, 'ext call in finally inside while'
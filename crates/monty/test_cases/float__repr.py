@@ -0,0 +1,47 @@
+# === Shortest round-trip digits ===
+assert repr(0.1) == '0.1', 'repr 0.1'
+assert repr(0.1 + 0.2) == '0.30000000000000004', 'repr 0.1 + 0.2'
+assert repr(1 / 3) == '0.3333333333333333', 'repr 1/3'
+assert repr(2 / 3) == '0.6666666666666666', 'repr 2/3'
+assert repr(3.141592653589793) == '3.141592653589793', 'repr pi'
+assert repr(1.5) == '1.5', 'repr 1.5'
+assert repr(-2.25) == '-2.25', 'repr negative'
+assert str(0.1) == '0.1', 'str matches repr'
+assert str(1 / 3) == '0.3333333333333333', 'str 1/3'
+
+# === Whole numbers keep .0 ===
+assert repr(1.0) == '1.0', 'repr 1.0'
+assert repr(100.0) == '100.0', 'repr 100.0'
+assert repr(0.0) == '0.0', 'repr zero'
+assert repr(-0.0) == '-0.0', 'repr negative zero'
+assert repr(1e15) == '1000000000000000.0', 'largest fixed-point power'
+assert repr(9999999999999998.0) == '9999999999999998.0', 'largest fixed-point value'
+
+# === Scientific notation ===
+assert repr(1e16) == '1e+16', 'repr 1e16'
+assert repr(1e22) == '1e+22', 'repr 1e22'
+assert repr(1.5e300) == '1.5e+300', 'repr huge'
+assert repr(-1.5e20) == '-1.5e+20', 'repr negative huge'
+assert repr(1.7976931348623157e308) == '1.7976931348623157e+308', 'repr max float'
+assert repr(0.0001) == '0.0001', 'smallest fixed-point power'
+assert repr(0.00012345) == '0.00012345', 'small fixed-point'
+assert repr(0.00001) == '1e-05', 'repr 1e-5'
+assert repr(1.25e-7) == '1.25e-07', 'repr small'
+assert repr(5e-324) == '5e-324', 'repr smallest subnormal'
+assert str(2.0**70) == '1.1805916207174113e+21', 'str large power'
+
+# === Special values ===
+assert repr(float('inf')) == 'inf', 'repr inf'
+assert repr(float('-inf')) == '-inf', 'repr -inf'
+assert repr(float('nan')) == 'nan', 'repr nan'
+
+# === Containers and f-strings ===
+assert repr([0.1, 1e16, -0.0]) == '[0.1, 1e+16, -0.0]', 'repr in list'
+assert f'{0.1}' == '0.1', 'f-string default'
+assert f'{1e16}' == '1e+16', 'f-string scientific'
+assert f'{1 / 3:>20}' == '  0.3333333333333333', 'f-string width uses repr digits'
+assert f'{2.5:<6}|' == '2.5   |', 'f-string left aligned'
+assert f'{2.5:+}' == '+2.5', 'f-string sign'
+assert f'{1.0:.3}' == '1.0', 'f-string precision keeps .0'
+assert f'{1 / 3:.3}' == '0.333', 'f-string precision'
+assert f'{12345.678:.3}' == '1.23e+04', 'f-string precision scientific'
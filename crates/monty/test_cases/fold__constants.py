@@ -0,0 +1,52 @@
+# === Arithmetic ===
+assert 60 * 60 * 24 == 86400, 'int multiplication chain'
+assert 7 // -2 == -4, 'floor division rounds towards negative infinity'
+assert -7 % 3 == 2, 'modulo takes the sign of the divisor'
+assert 2**10 == 1024, 'int power'
+assert 1 << 62 == 4611686018427387904, 'left shift within i64'
+assert 1 << 64 == 18446744073709551616, 'left shift past i64 promotes to long int'
+assert -1 >> 100 == -1, 'large right shift of a negative int'
+assert 0.5 + 0.25 == 0.75, 'float addition'
+assert -(-3) == 3, 'double negation'
+assert ~5 == -6, 'bitwise invert'
+assert 2**-1 == 0.5, 'negative exponent gives a float'
+
+# === Strings ===
+assert 'ab' + 'cd' == 'abcd', 'string concatenation'
+assert f'{"a"}{1 + 1}-{"b"}' == 'a2-b', 'constant f-string pieces'
+x = 3
+assert f'x={x}, y={2 * 3}' == 'x=3, y=6', 'mixed f-string'
+assert f'{"a"!r}' == "'a'", 'conversions are not folded away'
+assert f'{5:03}' == '005', 'format specs are not folded away'
+
+# === Boolean logic ===
+assert (True and 'yes') == 'yes', 'and with truthy constant'
+assert (0 and x) == 0, 'and with falsy constant'
+assert ('' or x) == 3, 'or with falsy constant'
+assert (not '') is True, 'not of empty string'
+assert ('a' if 1 else 'b') == 'a', 'conditional expression with constant test'
+
+# === Dead branches ===
+y = 1
+if False:
+    y = 2
+elif 0:
+    y = 3
+else:
+    y = 4
+assert y == 4, 'else branch of constant-false if chain'
+
+while False:
+    y = 5
+else:
+    y = 6
+assert y == 6, 'else of while False'
+
+
+def f():
+    if True:
+        return 'taken'
+    return 'not taken'
+
+
+assert f() == 'taken', 'constant-true if in a function body'
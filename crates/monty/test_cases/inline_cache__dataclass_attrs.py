@@ -0,0 +1,57 @@
+# call-external
+# Tests that repeated dataclass attribute loads from the same site stay correct
+# === Repeated loads from one site ===
+point = make_point()
+xs = [point.x for _ in range(3)]
+assert xs == [1, 1, 1], 'dataclass attribute loaded in a loop'
+
+
+def coords(p):
+    return (p.x, p.y)
+
+
+assert coords(point) == (1, 2), 'first call fills the caches'
+assert coords(make_point()) == (1, 2), 'another instance of the same type hits the caches'
+
+# === Values written after the cache is filled ===
+mut_point = make_mutable_point()
+seen = []
+for value in [10, 20, 30]:
+    mut_point.x = value
+    seen.append(mut_point.x)
+assert seen == [10, 20, 30], 'reassigned attribute is reread'
+
+mut_point.label = 'origin'
+labels = [mut_point.label for _ in range(2)]
+assert labels == ['origin', 'origin'], 'attribute added after construction'
+assert coords(mut_point) == (30, 2), 'original fields still found after adding one'
+
+# === One site, receivers with different layouts ===
+# The test fixtures share one type id, so these all key the same cache entry
+
+
+def name_of(obj):
+    return obj.name
+
+
+alice = make_user('Alice')
+assert name_of(alice) == 'Alice', 'first receiver fills the cache'
+assert name_of(make_user('Bob')) == 'Bob', 'same layout, different value'
+try:
+    name_of(point)
+    assert False, 'Point has no name'
+except AttributeError as e:
+    assert str(e) == "'Point' object has no attribute 'name'", 'lookup falls back for another layout'
+assert name_of(alice) == 'Alice', 'original receiver still works'
+
+
+def second_field(obj):
+    return obj.y
+
+
+assert second_field(point) == 2, 'y on Point'
+try:
+    second_field(alice)
+    assert False, 'User has no y'
+except AttributeError as e:
+    assert str(e) == "'User' object has no attribute 'y'", 'cached position holds a different key'
@@ -0,0 +1,65 @@
+# call-external
+# Tests that repeated module attribute lookups from the same call site stay correct
+import os
+import sys
+
+# === Repeated calls from one site ===
+found = []
+for name in ['VIRTUAL_HOME', 'NONEXISTENT', 'VIRTUAL_USER']:
+    found.append(os.getenv(name, 'missing'))
+assert found == ['/virtual/home', 'missing', 'testuser'], 'module function called in a loop'
+
+
+def home_dirs(n):
+    return [os.getenv('VIRTUAL_HOME') for _ in range(n)]
+
+
+assert home_dirs(3) == ['/virtual/home'] * 3, 'module function called in a comprehension'
+assert home_dirs(2) == ['/virtual/home'] * 2, 'second call reuses the function cache'
+
+# === Repeated loads from one site ===
+platform = sys.platform
+platforms = set()
+for _ in range(5):
+    platforms.add(sys.platform)
+assert platforms == {platform}, 'module attribute loaded in a loop'
+
+versions = [sys.version_info[0] for _ in range(3)]
+assert versions == [3, 3, 3], 'heap attributes are reloaded each time'
+
+sizes = [len(os.environ) for _ in range(2)]
+assert sizes == [3, 3], 'properties run their getter each time'
+
+# === One site, different receivers ===
+def platform_of(module):
+    return module.platform
+
+
+assert platform_of(sys) == platform, 'first receiver fills the cache'
+try:
+    platform_of(os)
+    assert False, 'a different module must not hit the cache'
+except AttributeError as e:
+    assert str(e) == "module 'os' has no attribute 'platform'", 'lookup falls back for another module'
+assert platform_of(sys) == platform, 'original receiver still works'
+
+
+def getenv_from(module):
+    return module.getenv('VIRTUAL_USER')
+
+
+assert getenv_from(os) == 'testuser', 'call site filled by os'
+try:
+    getenv_from(sys)
+    assert False, 'sys has no getenv'
+except AttributeError as e:
+    assert str(e) == "module 'sys' has no attribute 'getenv'", 'call falls back for another module'
+
+# === Re-importing ===
+def fresh_platform():
+    import sys
+
+    return sys.platform
+
+
+assert [fresh_platform() for _ in range(3)] == [platform] * 3, 'import inside a function on each call'
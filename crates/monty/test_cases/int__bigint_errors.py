@@ -170,10 +170,7 @@ try:
     pow(2, -1, 4)  # gcd(2, 4) != 1, no inverse exists
     assert False, 'pow(2, -1, 4) should raise ValueError'
 except ValueError as e:
-    # CPython: "base is not invertible for the given modulus"
-    # Monty: "pow() 2nd argument cannot be negative when 3rd argument specified"
-    # Accept either message since Monty doesn't support modular inverse yet
-    assert 'not invertible' in str(e) or 'cannot be negative' in str(e), f'pow non-invertible error: {e}'
+    assert str(e) == 'base is not invertible for the given modulus', f'pow non-invertible error: {e}'
 
 try:
     pow(2.0, 2, 5)
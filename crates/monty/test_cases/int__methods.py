@@ -0,0 +1,103 @@
+# === bit_length ===
+assert (0).bit_length() == 0, 'bit_length of zero'
+assert (1).bit_length() == 1, 'bit_length of one'
+assert (255).bit_length() == 8, 'bit_length of 255'
+assert (-256).bit_length() == 9, 'bit_length uses the absolute value'
+assert True.bit_length() == 1, 'bit_length of bool'
+assert (2**100).bit_length() == 101, 'bit_length of long int'
+assert (-(2**64)).bit_length() == 65, 'bit_length of negative long int'
+
+# === bit_count ===
+assert (0).bit_count() == 0, 'bit_count of zero'
+assert (0b1011).bit_count() == 3, 'bit_count'
+assert (-7).bit_count() == 3, 'bit_count uses the absolute value'
+assert (2**100 - 1).bit_count() == 100, 'bit_count of long int'
+
+# === to_bytes ===
+assert (1024).to_bytes(2, 'big') == b'\x04\x00', 'to_bytes big endian'
+assert (1024).to_bytes(2, 'little') == b'\x00\x04', 'to_bytes little endian'
+assert (1024).to_bytes(4, byteorder='big') == b'\x00\x00\x04\x00', 'to_bytes pads with zeros'
+assert (65).to_bytes() == b'A', 'to_bytes defaults'
+assert (0).to_bytes(0, 'big') == b'', 'zero fits in no bytes'
+assert (-1).to_bytes(2, 'big', signed=True) == b'\xff\xff', 'to_bytes negative'
+assert (-256).to_bytes(3, 'little', signed=True) == b'\x00\xff\xff', 'to_bytes negative little endian'
+assert (127).to_bytes(1, 'big', signed=True) == b'\x7f', 'to_bytes signed max'
+assert (2**64).to_bytes(9, 'big') == b'\x01' + b'\x00' * 8, 'to_bytes long int'
+assert (-(2**63)).to_bytes(8, 'big', signed=True) == b'\x80' + b'\x00' * 7, 'to_bytes min i64'
+assert (258).to_bytes(length=2, byteorder='little', signed=False) == b'\x02\x01', 'to_bytes all keywords'
+
+try:
+    (256).to_bytes(1, 'big')
+    assert False, 'to_bytes should overflow'
+except OverflowError as e:
+    assert str(e) == 'int too big to convert', f'to_bytes overflow message: {e}'
+
+try:
+    (128).to_bytes(1, 'big', signed=True)
+    assert False, 'signed to_bytes should overflow'
+except OverflowError as e:
+    assert str(e) == 'int too big to convert', f'signed overflow message: {e}'
+
+try:
+    (-1).to_bytes(1, 'big')
+    assert False, 'unsigned to_bytes of negative should fail'
+except OverflowError as e:
+    assert str(e) == "can't convert negative int to unsigned", f'negative unsigned message: {e}'
+
+try:
+    (1).to_bytes(1, 'middle')
+    assert False, 'bad byteorder should fail'
+except ValueError as e:
+    assert str(e) == "byteorder must be either 'little' or 'big'", f'byteorder message: {e}'
+
+try:
+    (1).to_bytes(-1, 'big')
+    assert False, 'negative length should fail'
+except ValueError as e:
+    assert str(e) == 'length argument must be non-negative', f'length message: {e}'
+
+# === from_bytes ===
+assert int.from_bytes(b'\x04\x00', 'big') == 1024, 'from_bytes big endian'
+assert int.from_bytes(b'\x00\x04', 'little') == 1024, 'from_bytes little endian'
+assert int.from_bytes(b'\x04\x00') == 1024, 'from_bytes default byteorder'
+assert int.from_bytes(b'', 'big') == 0, 'from_bytes empty'
+assert int.from_bytes(b'\xff\xff', 'big') == 65535, 'from_bytes unsigned'
+assert int.from_bytes(b'\xff\xff', 'big', signed=True) == -1, 'from_bytes signed'
+assert int.from_bytes(b'\x00\xff\xff', byteorder='little', signed=True) == -256, 'from_bytes signed little'
+assert int.from_bytes(b'\x01' + b'\x00' * 8, 'big') == 2**64, 'from_bytes long int'
+assert (5).from_bytes(b'\x07', 'big') == 7, 'from_bytes on instance'
+
+try:
+    int.from_bytes('abc', 'big')
+    assert False, 'from_bytes of str should fail'
+except TypeError as e:
+    assert str(e) == "cannot convert 'str' object to bytes", f'from_bytes type message: {e}'
+
+# === round trips ===
+for n in [0, 1, 255, 256, 65535, 2**32 + 5, 2**80 + 12345]:
+    length = (n.bit_length() + 7) // 8
+    for order in ['big', 'little']:
+        assert int.from_bytes(n.to_bytes(length, order), order) == n, f'unsigned round trip {n} {order}'
+for n in [-1, -128, 127, -(2**70), 2**70]:
+    data = n.to_bytes(10, 'little', signed=True)
+    assert int.from_bytes(data, 'little', signed=True) == n, f'signed round trip {n}'
+
+# === divmod ===
+assert divmod(17, 5) == (3, 2), 'divmod'
+assert divmod(-17, 5) == (-4, 3), 'divmod negative'
+assert divmod(2**100, 7) == (2**100 // 7, 2**100 % 7), 'divmod long int'
+
+# === pow with modulus ===
+assert pow(3, 4, 5) == 1, 'pow mod'
+assert pow(-3, 3, 7) == 1, 'pow mod negative base'
+assert pow(2, 3, -5) == -2, 'pow mod negative modulus'
+assert pow(2, 100, 2**61 - 1) == 2**100 % (2**61 - 1), 'pow mod large modulus'
+assert pow(2**100, 2**70, 10**9 + 7) == pow(2**100 % (10**9 + 7), 2**70, 10**9 + 7), 'pow mod long operands'
+assert pow(3, 10**30, 2**127 - 1) == pow(3, 10**30 % (2**127 - 2), 2**127 - 1), 'pow mod huge exponent'
+assert pow(True, 5, 3) == 1, 'pow mod bool base'
+assert pow(3, -1, 7) == 5, 'pow modular inverse'
+assert pow(3, -2, 7) == 4, 'pow inverse squared'
+assert pow(-3, -1, 7) == 2, 'pow inverse of negative base'
+assert pow(3, -1, -7) == -2, 'pow inverse negative modulus'
+assert pow(38, -1, 97) * 38 % 97 == 1, 'pow inverse property'
+assert pow(2**90 + 1, -1, 2**89 - 1) * (2**90 + 1) % (2**89 - 1) == 1, 'pow inverse long int'
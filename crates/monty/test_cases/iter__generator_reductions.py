@@ -0,0 +1,80 @@
+# === sum over a generator expression ===
+xs = [1, 2, 3, 4]
+assert sum(x * x for x in xs) == 30, 'sum of squares'
+assert sum(x for x in xs if x % 2 == 0) == 6, 'sum with condition'
+assert sum(x * y for x in range(3) for y in range(3)) == 9, 'sum over nested generators'
+assert sum(x for x in []) == 0, 'sum of empty generator'
+assert sum(x / 2 for x in xs) == 5.0, 'sum of floats'
+assert sum(a * b for a, b in [(1, 2), (3, 4)]) == 14, 'sum with unpacking'
+assert sum(x for x in range(10**5)) == 4999950000, 'sum over a long range'
+
+try:
+    sum([x] for x in xs)
+    assert False, 'summing lists should fail'
+except TypeError as e:
+    assert str(e) == "unsupported operand type(s) for +: 'int' and 'list'", 'sum type error'
+
+try:
+    sum(c for c in 'abc')
+    assert False, 'summing strings should fail'
+except TypeError as e:
+    assert str(e) == "unsupported operand type(s) for +: 'int' and 'str'", 'sum str error'
+
+# === any over a generator expression ===
+assert any(x > 3 for x in xs), 'any true'
+assert not any(x > 4 for x in xs), 'any false'
+assert not any(x for x in []), 'any of empty generator'
+assert any(x * y == 4 for x in range(3) for y in range(3)), 'any over nested generators'
+
+# === all over a generator expression ===
+assert all(x > 0 for x in xs), 'all true'
+assert not all(x > 1 for x in xs), 'all false'
+assert all(x for x in []), 'all of empty generator'
+assert not all(x + y < 3 for x in range(3) for y in range(3)), 'all over nested generators'
+
+# === any and all stop at the first deciding element ===
+seen = []
+
+
+def check(x):
+    seen.append(x)
+    return x
+
+
+assert any(check(x) > 1 for x in xs), 'any short-circuit result'
+assert seen == [1, 2], 'any stops after the first true element'
+
+seen = []
+assert not all(check(x) < 2 for x in xs), 'all short-circuit result'
+assert seen == [1, 2], 'all stops after the first false element'
+
+seen = []
+assert any(check(x) == 1 for x in range(3) for _ in range(5)), 'nested short-circuit result'
+assert seen == [0, 0, 0, 0, 0, 1], 'nested any stops with both loops open'
+
+
+# === Results can be used inside other expressions and loops ===
+def count_matches(rows, target):
+    total = 0
+    for row in rows:
+        if any(cell == target for cell in row):
+            total += sum(1 for cell in row if cell == target)
+    return total
+
+
+assert count_matches([[1, 2], [2, 2], [3]], 2) == 3, 'reductions inside a loop'
+assert [all(c.isdigit() for c in s) for s in ['12', 'a1']] == [True, False], 'all inside a comprehension'
+
+# === Loop variables don't leak ===
+y = 'outer'
+assert sum(y for y in range(3)) == 3, 'sum uses the comprehension variable'
+assert y == 'outer', 'comprehension variable does not leak'
+
+
+# === Shadowed builtins are called normally ===
+def shadowed():
+    sum = list
+    return sum(x for x in range(3))
+
+
+assert shadowed() == [0, 1, 2], 'shadowed sum gets the items'
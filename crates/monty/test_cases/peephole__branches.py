@@ -0,0 +1,89 @@
+# === Compare and branch ===
+def classify(a, b):
+    if a < b:
+        return 'lt'
+    elif a == b:
+        return 'eq'
+    return 'gt'
+
+
+assert classify(1, 2) == 'lt', 'less than branch'
+assert classify(2, 2) == 'eq', 'equality branch'
+assert classify(3, 2) == 'gt', 'fallthrough'
+
+nan = float('nan')
+assert classify(nan, 1.0) == 'gt', 'nan is neither less than nor equal'
+hits = []
+if not nan < 1.0:
+    hits.append('not lt')
+if not nan >= 1.0:
+    hits.append('not ge')
+assert hits == ['not lt', 'not ge'], 'negated ordering comparisons with nan'
+
+
+def find(items, target):
+    for item in items:
+        if item is None:
+            continue
+        if item in target:
+            return item
+    return None
+
+
+assert find([None, 'x', 'b'], 'abc') == 'b', 'is and in branches'
+assert find([None], 'abc') is None, 'no match'
+
+value = None
+assert value is None, 'assert with is'
+value = []
+assert value is not None, 'assert with is not'
+assert 'a' not in 'xyz', 'assert with not in'
+
+# === Loops ===
+i = 0
+total = 0
+while i < 10:
+    total += i
+    i = i + 1
+assert total == 45, 'while loop with store then load'
+
+count = 0
+for n in range(20):
+    if n % 2 == 0:
+        if n > 10:
+            count += 1
+        else:
+            count += 2
+    else:
+        count += 0
+assert count == 14, 'nested branches ending in jumps'
+
+# === Statements with no effect ===
+def documented():
+    'docstring is a constant expression statement'
+    ...
+    return 1
+
+
+assert documented() == 1, 'constant statements are dropped'
+
+# === Exceptions ===
+def guarded(x):
+    try:
+        y = x
+        if y == 0:
+            raise ValueError('zero')
+        return 10 // y
+    except ValueError:
+        return -1
+
+
+assert guarded(5) == 2, 'try body'
+assert guarded(0) == -1, 'handler'
+
+try:
+    if 1 in 5:
+        pass
+except TypeError as e:
+    msg = str(e)
+assert msg == "argument of type 'int' is not iterable", 'fused compare raises like the plain op'
@@ -147,3 +147,27 @@ assert 'hello'[-5::-1] == 'h', 'string exactly at first'
 # Tuple slicing with out-of-bounds negative start
 assert (0, 1, 2, 3, 4)[-10::-1] == (), 'tuple far negative start empty'
 assert (0, 1, 2, 3, 4)[-5::-1] == (0,), 'tuple exactly at first'
+
+# === Whole slices and repeat-by-one share immutable sequences ===
+t = tuple([1, [2], 3])
+assert t[:] is t, 'tuple whole slice is the same object'
+assert t[::1] is t, 'tuple whole slice with step 1 is the same object'
+assert t[0:3] is t, 'tuple explicit whole slice is the same object'
+assert t * 1 is t, 'tuple repeated once is the same object'
+assert t[1:] is not t, 'tuple partial slice is a new object'
+s = 'ab' * 3
+assert s[:] is s, 'str whole slice is the same object'
+assert s * 1 is s, 'str repeated once is the same object'
+u = 'h' + 'éllo'
+assert u[:] is u, 'non-ascii str whole slice is the same object'
+assert u[-100:] is u, 'non-ascii str whole slice from far negative start is the same object'
+assert u[1:] == 'éllo', 'non-ascii str partial slice'
+assert u[0:5] == u, 'non-ascii str slice to its length is equal'
+b = bytes([1, 2, 3])
+assert b[:] is b, 'bytes whole slice is the same object'
+lst6 = [1, [2]]
+assert lst6[:] is not lst6, 'list whole slice is always a copy'
+assert lst6 * 1 is not lst6, 'list repeated once is always a copy'
+rep = lst6 * 3
+assert rep == [1, [2], 1, [2], 1, [2]], 'list repeat'
+assert rep[1] is rep[3] is lst6[1], 'list repeat shares items'
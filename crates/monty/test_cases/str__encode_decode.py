@@ -0,0 +1,151 @@
+# === encode to UTF-8 ===
+assert 'café'.encode() == b'caf\xc3\xa9', 'encode non-ASCII as utf-8'
+assert '日本'.encode('utf-8') == b'\xe6\x97\xa5\xe6\x9c\xac', 'encode CJK as utf-8'
+assert '😀'.encode('UTF8') == b'\xf0\x9f\x98\x80', 'encode a four-byte character'
+assert 'abc'.encode('utf_8') == b'abc', 'utf_8 alias'
+assert 'é'.encode('u8') == b'\xc3\xa9', 'u8 alias'
+
+# === encode to ASCII and Latin-1 ===
+assert 'abc'.encode('ascii') == b'abc', 'encode ascii'
+assert 'café'.encode('latin-1') == b'caf\xe9', 'encode latin-1'
+assert 'café'.encode('ISO-8859-1') == b'caf\xe9', 'iso-8859-1 alias'
+assert 'café'.encode('latin1') == b'caf\xe9', 'latin1 alias'
+
+try:
+    'café'.encode('ascii')
+    assert False, 'encoding e-acute as ascii should fail'
+except UnicodeEncodeError as e:
+    assert str(e) == "'ascii' codec can't encode character '\\xe9' in position 3: ordinal not in range(128)", (
+        'ascii encode error message'
+    )
+
+try:
+    'a日本b'.encode('ascii')
+    assert False, 'encoding CJK as ascii should fail'
+except UnicodeEncodeError as e:
+    assert str(e) == "'ascii' codec can't encode characters in position 1-2: ordinal not in range(128)", (
+        'consecutive characters are reported together'
+    )
+
+try:
+    '€'.encode('latin-1')
+    assert False, 'encoding the euro sign as latin-1 should fail'
+except UnicodeEncodeError as e:
+    assert str(e) == "'latin-1' codec can't encode character '\\u20ac' in position 0: ordinal not in range(256)", (
+        'latin-1 encode error message'
+    )
+
+try:
+    'x😀'.encode('latin-1')
+    assert False, 'encoding an emoji as latin-1 should fail'
+except ValueError as e:
+    assert str(e) == "'latin-1' codec can't encode character '\\U0001f600' in position 1: ordinal not in range(256)", (
+        'UnicodeEncodeError is a ValueError'
+    )
+
+# === encode error handlers ===
+assert 'café'.encode('ascii', 'ignore') == b'caf', 'encode ignore'
+assert 'café'.encode('ascii', 'replace') == b'caf?', 'encode replace'
+assert 'a日本b'.encode('ascii', 'replace') == b'a??b', 'encode replace each character'
+assert 'é€😀'.encode('ascii', 'backslashreplace') == b'\\xe9\\u20ac\\U0001f600', 'encode backslashreplace'
+assert 'é€'.encode('ascii', 'xmlcharrefreplace') == b'&#233;&#8364;', 'encode xmlcharrefreplace'
+assert '€'.encode('latin-1', 'replace') == b'?', 'latin-1 replace'
+assert 'abc'.encode('ascii', 'bogus') == b'abc', 'error handler is only looked up on error'
+
+try:
+    'é'.encode('ascii', 'bogus')
+    assert False, 'unknown error handler should fail'
+except LookupError as e:
+    assert str(e) == "unknown error handler name 'bogus'", 'unknown error handler message'
+
+try:
+    'abc'.encode('utf-16-bogus')
+    assert False, 'unknown encoding should fail'
+except LookupError as e:
+    assert str(e) == 'unknown encoding: utf-16-bogus', 'unknown encoding message'
+
+try:
+    'abc'.encode(1)
+    assert False, 'non-str encoding should fail'
+except TypeError as e:
+    assert str(e) == "encode() argument 'encoding' must be str, not int", 'encoding type error'
+
+# === decode UTF-8 ===
+assert b'caf\xc3\xa9'.decode() == 'café', 'decode utf-8'
+assert b'\xf0\x9f\x98\x80'.decode('utf-8') == '😀', 'decode a four-byte character'
+assert len(b'\xe6\x97\xa5\xe6\x9c\xac'.decode()) == 2, 'decoded CJK has code point length'
+
+try:
+    b'ab\xffcd'.decode()
+    assert False, 'invalid start byte should fail'
+except UnicodeDecodeError as e:
+    assert str(e) == "'utf-8' codec can't decode byte 0xff in position 2: invalid start byte", 'invalid start byte'
+
+try:
+    b'\xc3(x'.decode()
+    assert False, 'invalid continuation byte should fail'
+except UnicodeDecodeError as e:
+    assert str(e) == "'utf-8' codec can't decode byte 0xc3 in position 0: invalid continuation byte", (
+        'invalid continuation byte'
+    )
+
+try:
+    b'\xe6\x97(x'.decode()
+    assert False, 'invalid continuation byte after two bytes should fail'
+except UnicodeDecodeError as e:
+    assert str(e) == "'utf-8' codec can't decode bytes in position 0-1: invalid continuation byte", (
+        'invalid continuation byte after a partial sequence'
+    )
+
+try:
+    b'ok\xe6\x97'.decode()
+    assert False, 'truncated sequence should fail'
+except UnicodeDecodeError as e:
+    assert str(e) == "'utf-8' codec can't decode bytes in position 2-3: unexpected end of data", (
+        'unexpected end of data'
+    )
+
+# === decode error handlers ===
+assert b'a\xffb'.decode('utf-8', 'replace') == 'a�b', 'decode replace'
+assert b'a\xffb'.decode('utf-8', 'ignore') == 'ab', 'decode ignore'
+assert b'a\xff\xfeb'.decode('utf-8', 'replace') == 'a��b', 'decode replace each invalid byte'
+assert b'\xe6\x97(x'.decode('utf-8', 'replace') == '�(x', 'decode replace a partial sequence once'
+assert b'ok\xe6\x97'.decode('utf-8', 'replace') == 'ok�', 'decode replace truncated data once'
+assert b'a\xff\xfe'.decode('utf-8', 'backslashreplace') == 'a\\xff\\xfe', 'decode backslashreplace'
+assert b'abc'.decode('utf-8', 'bogus') == 'abc', 'decode error handler is only looked up on error'
+
+try:
+    b'\xff'.decode('utf-8', 'bogus')
+    assert False, 'unknown decode error handler should fail'
+except LookupError as e:
+    assert str(e) == "unknown error handler name 'bogus'", 'unknown decode error handler message'
+
+try:
+    b'\xff'.decode('utf-8', 'xmlcharrefreplace')
+    assert False, 'xmlcharrefreplace cannot decode'
+except TypeError as e:
+    assert str(e) == "don't know how to handle UnicodeDecodeError in error callback", 'xmlcharrefreplace decode'
+
+try:
+    b'abc'.decode('utf-8', 1)
+    assert False, 'non-str errors should fail'
+except TypeError as e:
+    assert str(e) == "decode() argument 'errors' must be str, not int", 'errors type error'
+
+# === decode ASCII and Latin-1 ===
+assert b'abc'.decode('ascii') == 'abc', 'decode ascii'
+assert b'caf\xe9'.decode('latin-1') == 'café', 'decode latin-1'
+assert b'\x00\x7f\x80\xff'.decode('latin-1') == '\x00\x7f\x80\xff', 'latin-1 maps bytes to code points'
+assert b'a\xe9\xe8'.decode('ascii', 'replace') == 'a��', 'ascii decode replace'
+
+try:
+    b'caf\xe9'.decode('ascii')
+    assert False, 'decoding a high byte as ascii should fail'
+except UnicodeDecodeError as e:
+    assert str(e) == "'ascii' codec can't decode byte 0xe9 in position 3: ordinal not in range(128)", (
+        'ascii decode error message'
+    )
+
+# === round trips ===
+for text in ['', 'plain', 'café', '日本語', 'a😀b', 'Ωmega']:
+    assert text.encode().decode() == text, f'utf-8 round trip of {text!r}'
@@ -0,0 +1,81 @@
+# === Repeated += builds the string ===
+v = ''
+for i in range(200):
+    v += 'x'
+assert v == 'x' * 200, 'loop concat'
+assert len(v) == 200, 'loop concat length'
+
+# === Aliases keep their value ===
+a = ''.join(['ab', 'c'])
+b = a
+a += 'd'
+assert a == 'abcd', 'alias target'
+assert b == 'abc', 'alias untouched'
+
+items = [''.join(['x', 'y'])]
+s = items[0]
+s += 'z'
+assert items == ['xy'], 'list element untouched'
+assert s == 'xyz', 'concat of list element'
+
+
+def append(value):
+    value += '!'
+    return value
+
+
+original = ''.join(['hi', 'there'])
+assert append(original) == 'hithere!', 'function concat'
+assert original == 'hithere', 'argument untouched'
+
+# === Rebinding the target during the right-hand side ===
+s = ''.join(['a', 'b'])
+s += ((t := s), (s := 'c'))[1]
+assert s == 'abc', 'target rebound'
+assert t == 'ab', 'walrus alias untouched'
+
+# === Hashes follow the new contents ===
+k = ''.join(['ke', 'y'])
+lookup = {'key': 1, 'keys': 2}
+assert lookup[k] == 1, 'hash before concat'
+k += 's'
+assert lookup[k] == 2, 'hash after concat'
+assert hash(k) == hash('keys'), 'hash matches equal string'
+
+# === Subscript targets ===
+d = {'text': ''.join(['a', 'b'])}
+kept = d['text']
+d['text'] += 'c'
+assert d['text'] == 'abc', 'subscript target'
+assert kept == 'ab', 'subscript alias untouched'
+
+# === Bytes ===
+data = b''.join([b'a', b'b'])
+copy = data
+for i in range(3):
+    data += b'!'
+assert data == b'ab!!!', 'bytes concat'
+assert copy == b'ab', 'bytes alias untouched'
+
+tail = b''.join([b'?', b'?'])
+for i in range(3):
+    data += tail
+assert data == b'ab!!!??????', 'bytes concat of heap bytes'
+assert tail == b'??', 'bytes operand untouched'
+
+data += data
+assert data == b'ab!!!??????ab!!!??????', 'bytes concat with itself'
+
+
+
+# === Local target read again right after the concat ===
+def concat_then_read(empty, piece):
+    out = empty
+    for i in range(5):
+        out += piece
+        assert out == piece * (i + 1), 'concat then read'
+    return out
+
+
+assert concat_then_read('', 'y') == 'yyyyy', 'str concat then read'
+assert concat_then_read(b'', b'z') == b'zzzzz', 'bytes concat then read'
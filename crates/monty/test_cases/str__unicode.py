@@ -0,0 +1,58 @@
+# === len counts code points, not bytes ===
+assert len('café') == 4, 'len with a two-byte character'
+assert len('日本語') == 3, 'len of CJK text'
+assert len('😀') == 1, 'len of a four-byte character'
+assert len('é') == 2, 'combining marks are separate code points'
+s = 'naïve ' + '日本'
+assert len(s) == 8, 'len of a heap string'
+
+# === indexing ===
+assert 'café'[3] == 'é', 'index a non-ASCII character'
+assert 'café'[-1] == 'é', 'negative index of a non-ASCII character'
+assert '日本語'[1] == '本', 'index CJK text'
+assert 'a😀b'[2] == 'b', 'index past a four-byte character'
+assert s[6] == '日', 'index a heap string'
+try:
+    '日本語'[3]
+    assert False, 'index past the end should fail'
+except IndexError as e:
+    assert str(e) == 'string index out of range', 'index error message'
+
+# === slicing ===
+assert 'café au lait'[:4] == 'café', 'slice ending after a non-ASCII character'
+assert 'café au lait'[3:6] == 'é a', 'slice starting at a non-ASCII character'
+assert '日本語'[::-1] == '語本日', 'reversed CJK text'
+assert 'a😀b😀c'[::2] == 'abc', 'stepped slice skipping four-byte characters'
+assert 'a😀b😀c'[1::2] == '😀😀', 'stepped slice of four-byte characters'
+assert s[-2:] == '日本', 'negative slice of a heap string'
+
+# === iteration and search report code points ===
+assert list('añb') == ['a', 'ñ', 'b'], 'iterating yields code points'
+assert 'añb'.find('b') == 2, 'find returns a code point index'
+assert '日本語'.index('語') == 2, 'index returns a code point index'
+assert [c for c in '😀x'] == ['😀', 'x'], 'iterating a four-byte character'
+
+# === upper and lower ===
+assert 'straße'.upper() == 'STRASSE', 'upper expands sharp s'
+assert 'ÀÉÎÕÜ'.lower() == 'àéîõü', 'lower Latin-1 letters'
+assert 'àéîõü'.upper() == 'ÀÉÎÕÜ', 'upper Latin-1 letters'
+assert 'ΑΒΓ'.lower() == 'αβγ', 'lower Greek'
+assert 'ΟΔΟΣ'.lower() == 'οδος', 'lower uses final sigma at the end of a word'
+assert 'Привет'.upper() == 'ПРИВЕТ', 'upper Cyrillic'
+assert 'İ'.lower() == 'i̇', 'lower dotted capital I'
+assert len('İ'.lower()) == 2, 'lower dotted capital I adds a combining dot'
+assert 'ﬁ'.upper() == 'FI', 'upper expands the fi ligature'
+assert '日本'.upper() == '日本', 'uncased characters are unchanged'
+
+# === casefold ===
+assert 'ß'.casefold() == 'ss', 'casefold sharp s'
+assert 'ß'.lower() == 'ß', 'lower keeps sharp s'
+assert 'Straße'.casefold() == 'STRASSE'.casefold(), 'casefold for caseless matching'
+assert 'ΟΔΟΣ'.casefold() == 'οδοσ', 'casefold does not use final sigma'
+assert 'ς'.casefold() == 'σ', 'casefold final sigma'
+assert 'µ'.casefold() == 'μ', 'casefold micro sign'
+assert 'ﬃ'.casefold() == 'ffi', 'casefold ffi ligature'
+assert 'ᾈ'.casefold() == 'ἀι', 'casefold Greek with prosgegrammeni'
+assert 'ꭰ'.casefold() == 'Ꭰ', 'casefold Cherokee to its uppercase letters'
+assert 'Ꭰ'.casefold() == 'Ꭰ', 'casefold keeps uppercase Cherokee'
+assert 'ÀB'.casefold() == 'àb', 'casefold plain letters like lower'
@@ -0,0 +1,70 @@
+# Tests loops and expressions the optimizer turns into fused instructions
+# === Loops storing to a local ===
+total = 0
+for i in range(10):
+    total += i
+assert total == 45, 'module level loop'
+
+
+def squares(n):
+    out = []
+    for i in range(n):
+        out.append(i * i)
+    return out
+
+
+assert squares(4) == [0, 1, 4, 9], 'function level loop'
+
+
+def first_even(items):
+    for item in items:
+        if item % 2 == 0:
+            break
+    else:
+        item = None
+    return item
+
+
+assert first_even([1, 3, 4, 5]) == 4, 'break keeps the stored value'
+assert first_even([1, 3]) is None, 'else branch after exhaustion'
+assert first_even([]) is None, 'empty iterable never stores'
+
+pairs = []
+for a in 'ab':
+    for b in 'xy':
+        pairs.append(a + b)
+assert pairs == ['ax', 'ay', 'bx', 'by'], 'nested loops'
+
+# === Adjacent local loads ===
+def mix(a, b, c):
+    return a * b + b * c - c * a
+
+
+assert mix(2, 3, 4) == 10, 'pairs of parameters'
+
+
+def many(a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11, a12, a13, a14, a15, a16, a17):
+    return a15 + a16 + a17 - a0 + a1
+
+
+assert many(*range(18)) == 49, 'slots past the fused range'
+
+# === Errors inside fused instructions ===
+d = {0: 0}
+try:
+    for k in d:
+        d[k + 1] = k
+except RuntimeError as e:
+    msg = str(e)
+assert msg == 'dictionary changed size during iteration', 'iteration error'
+
+
+def unbound_second(a):
+    try:
+        return a + b
+    except NameError as e:
+        return type(e).__name__
+    b = 1
+
+
+assert unbound_second(1) == 'UnboundLocalError', 'second load of a pair raises'
@@ -0,0 +1,19 @@
+# Tests that an error in the second half of a fused load points at that load
+def add(a):
+    total = a + b
+    b = 1
+    return total
+
+
+add(1)
+"""
+TRACEBACK:
+Traceback (most recent call last):
+  File "superinstructions__pair_traceback.py", line 8, in <module>
+    add(1)
+    ~~~~~~
+  File "superinstructions__pair_traceback.py", line 3, in add
+    total = a + b
+                ~
+UnboundLocalError: cannot access local variable 'b' where it is not associated with a value
+"""
@@ -0,0 +1,52 @@
+import sys
+
+# === sys.exit raises SystemExit ===
+try:
+    sys.exit(3)
+    assert False, 'sys.exit should raise'
+except SystemExit as e:
+    assert str(e) == '3', f'int code: {e}'
+
+try:
+    sys.exit()
+except SystemExit as e:
+    assert str(e) == '', f'no code: {e!r}'
+
+try:
+    sys.exit('bye')
+except SystemExit as e:
+    assert str(e) == 'bye', f'str code: {e}'
+
+try:
+    raise SystemExit(2)
+except SystemExit as e:
+    assert str(e) == '2', f'SystemExit(2): {e}'
+
+# === SystemExit is not an Exception ===
+caught_by = None
+try:
+    try:
+        sys.exit(1)
+    except Exception:
+        caught_by = 'Exception'
+except BaseException:
+    caught_by = 'BaseException'
+assert caught_by == 'BaseException', 'SystemExit bypasses except Exception'
+
+# === finally blocks still run ===
+ran_finally = False
+try:
+    try:
+        sys.exit(0)
+    finally:
+        ran_finally = True
+except SystemExit:
+    pass
+assert ran_finally, 'finally runs on exit'
+
+# === argument errors ===
+try:
+    sys.exit(1, 2)
+    assert False, 'two arguments should raise'
+except TypeError as e:
+    assert str(e) == 'exit expected at most 1 argument, got 2', f'arg count: {e}'
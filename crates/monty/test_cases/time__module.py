@@ -0,0 +1,46 @@
+import time
+
+# === Clock readings ===
+now = time.time()
+assert isinstance(now, float), 'time() returns a float'
+assert now > 1_600_000_000, 'time() is seconds since the epoch'
+
+start = time.monotonic()
+end = time.monotonic()
+assert isinstance(start, float), 'monotonic() returns a float'
+assert end >= start, 'monotonic() never goes backwards'
+
+p1 = time.perf_counter()
+p2 = time.perf_counter()
+assert p2 >= p1, 'perf_counter() never goes backwards'
+
+# === Argument errors ===
+try:
+    time.time(1)
+    assert False, 'time() with an argument should raise'
+except TypeError as e:
+    assert str(e) == 'time.time() takes no arguments (1 given)', f'time() arg error: {e}'
+
+try:
+    time.sleep('1')
+    assert False, 'sleep(str) should raise'
+except TypeError as e:
+    assert str(e) == "'str' object cannot be interpreted as an integer", f'sleep type error: {e}'
+
+try:
+    time.sleep(-1)
+    assert False, 'negative sleep should raise'
+except ValueError as e:
+    assert str(e) == 'sleep length must be non-negative', f'negative sleep: {e}'
+
+try:
+    time.sleep(float('nan'))
+    assert False, 'nan sleep should raise'
+except ValueError as e:
+    assert str(e) == 'Invalid value NaN (not a number)', f'nan sleep: {e}'
+
+try:
+    time.sleep(float('inf'))
+    assert False, 'infinite sleep should raise'
+except OverflowError as e:
+    assert str(e) == 'timestamp out of range for platform time_t', f'inf sleep: {e}'
@@ -0,0 +1,121 @@
+# === Bound exception has its type and args ===
+try:
+    raise ValueError('bad value')
+except ValueError as e:
+    assert type(e) is ValueError, 'type of bound exception'
+    assert type(e).__name__ == 'ValueError', 'name of the type of bound exception'
+    assert isinstance(e, Exception), 'bound exception is an Exception'
+    assert e.args == ('bad value',), 'args of bound exception'
+    assert str(e) == 'bad value', 'str of bound exception'
+
+# === type(e) constructs a new exception ===
+try:
+    raise IndexError('first')
+except IndexError as e:
+    new = type(e)('second')
+assert isinstance(new, IndexError), 'type(e) builds an instance of the same type'
+assert new.args == ('second',), 'type(e) passes args through'
+
+# === Attributes can be set and read back ===
+try:
+    raise KeyError('k')
+except KeyError as e:
+    e.code = 42
+    e.details = [1, 2]
+    assert e.code == 42, 'int attribute'
+    assert e.details == [1, 2], 'list attribute'
+    e.code = 43
+    assert e.code == 43, 'attribute can be reassigned'
+    assert getattr(e, 'missing', None) is None, 'unset attribute falls back to default'
+
+# === Attributes survive a bare raise ===
+try:
+    try:
+        raise ValueError('inner')
+    except ValueError as e:
+        e.note = 'added'
+        raise
+except ValueError as outer:
+    assert outer.note == 'added', 'attribute survives bare raise'
+    assert outer.args == ('inner',), 'args survive bare raise'
+
+# === Raising an existing object binds that same object ===
+saved = ValueError('saved')
+saved.tag = 'x'
+try:
+    raise saved
+except ValueError as e:
+    assert e is saved, 'handler binds the raised object'
+    assert e.tag == 'x', 'attribute survives raise'
+
+# === Exception object outlives its name ===
+kept = None
+try:
+    raise TypeError('kept')
+except TypeError as e:
+    kept = e
+assert repr(kept) == "TypeError('kept')", 'exception outlives its name'
+try:
+    raise kept
+except TypeError as e:
+    assert e is kept, 're-raising a kept exception binds the same object'
+
+# === Name is unbound after the handler ===
+try:
+    raise ValueError('a')
+except ValueError as err:
+    pass
+try:
+    _ = err  # pyright: ignore
+    unbound = False
+except NameError:
+    unbound = True
+assert unbound, 'name unbound after handler'
+
+# === Name is unbound when the handler raises ===
+try:
+    try:
+        raise ValueError('a')
+    except ValueError as err2:
+        raise TypeError('b')
+except TypeError:
+    pass
+try:
+    _ = err2  # pyright: ignore
+    unbound = False
+except NameError:
+    unbound = True
+assert unbound, 'name unbound after handler raised'
+
+# === Name is unbound when the handler breaks out of a loop ===
+for _ in range(3):
+    try:
+        raise ValueError('loop')
+    except ValueError as err3:
+        break
+try:
+    _ = err3  # pyright: ignore
+    unbound = False
+except NameError:
+    unbound = True
+assert unbound, 'name unbound after break'
+
+# === Handler may delete the name itself ===
+try:
+    raise ValueError('x')
+except ValueError as e:
+    del e
+
+
+# === Name is unbound in functions too ===
+def catch_and_raise():
+    try:
+        raise ValueError('in function')
+    except ValueError as e:
+        raise KeyError('other')
+
+
+try:
+    catch_and_raise()
+except KeyError as e:
+    assert e.args == ('other',), 'exception from handler propagates'
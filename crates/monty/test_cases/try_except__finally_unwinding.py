@@ -0,0 +1,224 @@
+# === Return in finally during an exception runs finally once ===
+log = []
+
+
+def return_in_finally():
+    try:
+        raise ValueError('swallowed')
+    finally:
+        log.append('finally')
+        return 'finally'  # type: ignore
+
+
+assert return_in_finally() == 'finally', 'return in finally swallows the exception'
+assert log == ['finally'], f'finally should run once: {log}'
+
+# === Exception raised by finally during a return runs finally once ===
+log = []
+
+
+def raise_in_finally():
+    try:
+        return 'try'
+    finally:
+        log.append('finally')
+        raise KeyError('from finally')
+
+
+try:
+    raise_in_finally()
+except KeyError:
+    log.append('caught')
+assert log == ['finally', 'caught'], f'finally should run once: {log}'
+
+# === Return in else runs finally ===
+log = []
+
+
+def return_in_else():
+    try:
+        log.append('try')
+    except ValueError:
+        log.append('except')
+    else:
+        return 'else'
+    finally:
+        log.append('finally')
+
+
+assert return_in_else() == 'else', 'return in else'
+assert log == ['try', 'finally'], f'return in else should run finally: {log}'
+
+# === Break and continue in else run finally ===
+log = []
+for i in range(3):
+    try:
+        log.append(i)
+    except ValueError:
+        pass
+    else:
+        if i == 0:
+            continue
+        break
+    finally:
+        log.append('finally')
+assert log == [0, 'finally', 1, 'finally'], f'break/continue in else: {log}'
+
+# === Exception in else is not handled by the same try but runs finally ===
+log = []
+try:
+    try:
+        log.append('try')
+    except ValueError:
+        log.append('except')
+    else:
+        raise ValueError('from else')
+    finally:
+        log.append('finally')
+except ValueError as e:
+    log.append(str(e))
+assert log == ['try', 'finally', 'from else'], f'exception in else: {log}'
+
+# === Break in finally during an exception swallows the exception ===
+log = []
+for i in range(3):
+    try:
+        raise ValueError('swallowed')
+    finally:
+        log.append('finally')
+        break  # type: ignore
+log.append('after')
+assert log == ['finally', 'after'], f'break in finally: {log}'
+
+# === Continue in finally during an exception swallows the exception ===
+log = []
+for i in range(3):
+    try:
+        raise ValueError('swallowed')
+    finally:
+        log.append(i)
+        continue  # type: ignore
+assert log == [0, 1, 2], f'continue in finally: {log}'
+
+# === Break in a while loop inside try/finally ===
+log = []
+n = 0
+while True:
+    n += 1
+    try:
+        if n == 3:
+            break
+    finally:
+        log.append(n)
+assert log == [1, 2, 3], f'break in while through finally: {log}'
+
+# === Break in a loop inside an except handler keeps the handled exception ===
+try:
+    try:
+        raise ValueError('outer')
+    except ValueError:
+        for i in range(3):
+            try:
+                raise KeyError('inner')
+            except KeyError:
+                break
+        raise
+except ValueError as e:
+    assert str(e) == 'outer', f'bare raise should re-raise the outer exception: {e!r}'
+
+# === Return from an except handler discards the handled exception ===
+
+
+def return_from_handler():
+    try:
+        raise ValueError('handled')
+    except ValueError:
+        return 'handler'
+
+
+try:
+    assert return_from_handler() == 'handler', 'return from handler'
+    raise
+except RuntimeError as e:
+    assert str(e) == 'No active exception to reraise', f'handled exception leaked: {e!r}'
+
+# === Return from nested handlers through finally ===
+log = []
+
+
+def return_from_nested_handlers():
+    try:
+        raise ValueError('outer')
+    except ValueError:
+        try:
+            raise KeyError('inner')
+        except KeyError:
+            return 'nested'
+        finally:
+            log.append('inner-finally')
+    finally:
+        log.append('outer-finally')
+
+
+assert return_from_nested_handlers() == 'nested', 'return from nested handlers'
+assert log == ['inner-finally', 'outer-finally'], f'both finally blocks run: {log}'
+
+# === Exception handled inside finally during a return keeps the return value ===
+
+
+def handled_in_finally():
+    try:
+        return 'value'
+    finally:
+        try:
+            raise KeyError('inner')
+        except KeyError:
+            pass
+
+
+assert handled_in_finally() == 'value', 'return value survives exception handled in finally'
+
+# === Finally re-raises the original exception after handling another ===
+try:
+    try:
+        raise ValueError('original')
+    finally:
+        try:
+            raise KeyError('inner')
+        except KeyError:
+            pass
+except ValueError as e:
+    assert str(e) == 'original', f'finally should re-raise the original: {e!r}'
+
+# === Continue in finally discards a pending return ===
+log = []
+
+
+def continue_discards_return():
+    for i in range(3):
+        try:
+            if i < 2:
+                return 'try'
+        finally:
+            log.append(i)
+            if i == 0:
+                continue  # type: ignore
+    return 'loop'
+
+
+assert continue_discards_return() == 'try', 'continue in finally discards the pending return'
+assert log == [0, 1], f'continue in finally: {log}'
+
+# === Break in finally discards a pending return ===
+
+
+def break_discards_return():
+    for i in range(3):
+        try:
+            return 'try'
+        finally:
+            break  # type: ignore
+    return 'after loop'
+
+
+assert break_discards_return() == 'after loop', 'break in finally discards the pending return'
@@ -0,0 +1,19 @@
+def fail():
+    raise ValueError('boom')
+
+
+try:
+    fail()
+except ValueError as e:
+    e.note = 'seen'
+    raise
+"""
+TRACEBACK:
+Traceback (most recent call last):
+  File "try_except__reraise_traceback.py", line 6, in <module>
+    fail()
+    ~~~~~~
+  File "try_except__reraise_traceback.py", line 2, in fail
+    raise ValueError('boom')
+ValueError: boom
+"""
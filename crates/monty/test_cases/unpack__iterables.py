@@ -0,0 +1,66 @@
+# === Dicts unpack their keys ===
+a, b = {'x': 1, 'y': 2}
+assert (a, b) == ('x', 'y'), 'dict keys'
+
+first, *rest = {'p': 1, 'q': 2, 'r': 3}
+assert first == 'p', 'dict star first'
+assert rest == ['q', 'r'], 'dict star rest'
+
+# === Items of a dict ===
+(k1, v1), (k2, v2) = {'x': 1, 'y': 2}.items()
+assert (k1, v1, k2, v2) == ('x', 1, 'y', 2), 'dict items'
+
+# === Ranges ===
+a, b, c = range(3)
+assert (a, b, c) == (0, 1, 2), 'range'
+
+head, *middle, tail = range(6)
+assert head == 0, 'range star head'
+assert middle == [1, 2, 3, 4], 'range star middle'
+assert tail == 5, 'range star tail'
+
+*empty, only = range(1)
+assert empty == [], 'range star empty'
+assert only == 0, 'range star only'
+
+# === Sets ===
+(s,) = {42}
+assert s == 42, 'single item set'
+
+(f,) = frozenset([7])
+assert f == 7, 'single item frozenset'
+
+# === Bytes ===
+x, y = b'hi'
+assert (x, y) == (104, 105), 'bytes unpack to ints'
+
+# === Nested iterables ===
+(a, b), c = range(2), 'z'
+assert (a, b, c) == (0, 1, 'z'), 'nested range'
+
+# === Too many values from an iterable ===
+try:
+    a, b = range(5)
+    assert False, 'should have raised'
+except ValueError as e:
+    assert str(e) == 'too many values to unpack (expected 2)', str(e)
+
+# === Not enough values from an iterable ===
+try:
+    a, b, c = {'x': 1}
+    assert False, 'should have raised'
+except ValueError as e:
+    assert str(e) == 'not enough values to unpack (expected 3, got 1)', str(e)
+
+try:
+    a, *b, c = range(1)
+    assert False, 'should have raised'
+except ValueError as e:
+    assert str(e) == 'not enough values to unpack (expected at least 2, got 1)', str(e)
+
+# === Strings report no length when there are too many values ===
+try:
+    a, b = 'abc'
+    assert False, 'should have raised'
+except ValueError as e:
+    assert str(e) == 'too many values to unpack (expected 2)', str(e)
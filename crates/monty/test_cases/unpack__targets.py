@@ -0,0 +1,59 @@
+# === Swapping list items ===
+xs = [1, 2, 3, 4]
+xs[0], xs[3] = xs[3], xs[0]
+assert xs == [4, 2, 3, 1], 'swap list items'
+
+i, j = 1, 2
+xs[i], xs[j] = xs[j], xs[i]
+assert xs == [4, 3, 2, 1], 'swap list items by variable index'
+
+# === Dict item targets ===
+d = {}
+d['a'], d['b'] = 1, 2
+assert d == {'a': 1, 'b': 2}, 'dict item targets'
+
+# === Mixed name and item targets ===
+n, d['c'] = 'name', 'item'
+assert n == 'name', 'mixed targets name'
+assert d['c'] == 'item', 'mixed targets item'
+
+# === Targets are evaluated after earlier targets are stored ===
+ys = [0, 0, 0]
+k, ys[k] = 2, 'x'
+assert ys == [0, 0, 'x'], 'index uses the value just assigned'
+
+# === Nested and starred item targets ===
+grid = [[0, 0], [0, 0]]
+(grid[0][0], (grid[1][1], last)) = ('a', ('b', 'c'))
+assert grid == [['a', 0], [0, 'b']], 'nested item targets'
+assert last == 'c', 'nested name target'
+
+zs = [None, None]
+zs[0], *zs[1] = [1, 2, 3]
+assert zs == [1, [2, 3]], 'starred next to item target'
+
+# === List display targets ===
+ws = [1, 2]
+[ws[0], ws[1]] = ws[1], ws[0]
+assert ws == [2, 1], 'list display item targets'
+
+# === For loop item targets ===
+seen = {}
+for seen['last'] in range(3):
+    pass
+assert seen == {'last': 2}, 'for loop item target'
+
+pairs = [(0, 'a'), (1, 'b')]
+out = ['', '']
+for idx, out[idx] in pairs:
+    pass
+assert out == ['a', 'b'], 'for loop unpacks into item target'
+
+
+# === Item targets in functions ===
+def swap(items, a, b):
+    items[a], items[b] = items[b], items[a]
+    return items
+
+
+assert swap([1, 2, 3], 0, 2) == [3, 2, 1], 'swap in function'
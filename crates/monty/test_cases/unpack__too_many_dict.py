@@ -0,0 +1,9 @@
+a, b = {'x': 1, 'y': 2, 'z': 3}
+"""
+TRACEBACK:
+Traceback (most recent call last):
+  File "unpack__too_many_dict.py", line 1, in <module>
+    a, b = {'x': 1, 'y': 2, 'z': 3}
+    ~~~~
+ValueError: too many values to unpack (expected 2, got 3)
+"""
@@ -204,3 +204,47 @@ while i < 2:
         result.append('inner-else')
     i += 1
 assert result == [0, 0], 'break skips inner else only'
+
+# === while True with continue and break ===
+i = 0
+result = []
+while True:
+    i += 1
+    if i % 2 == 0:
+        continue
+    if i > 7:
+        break
+    result.append(i)
+else:
+    result.append('else')
+assert result == [1, 3, 5, 7], 'while True with continue and break'
+
+
+# === return from while True ===
+def first_square_over(limit):
+    n = 0
+    while True:
+        n += 1
+        if n * n > limit:
+            return n
+
+
+assert first_square_over(50) == 8, 'return from while True'
+
+# === while 1 ===
+i = 0
+while 1:
+    i += 1
+    if i == 4:
+        break
+assert i == 4, 'while 1'
+
+# === break through finally in while True ===
+result = []
+while True:
+    try:
+        result.append('body')
+        break
+    finally:
+        result.append('finally')
+assert result == ['body', 'finally'], 'break through finally in while True'
@@ -0,0 +1,107 @@
+//! Tests for restricting the builtins a script may use with `CompileOptions`.
+
+use monty::{CompileOptions, ExcType, MontyException, MontyObject, MontyRepl, MontyRun, NoLimitTracker, PrintWriter};
+
+fn compile(code: &str, options: CompileOptions) -> Result<MontyRun, MontyException> {
+    MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec![], options)
+}
+
+fn new_repl(code: &str, options: CompileOptions) -> Result<MontyRepl<NoLimitTracker>, MontyException> {
+    MontyRepl::new_with_options(
+        code.to_owned(),
+        "repl.py",
+        vec![],
+        vec![],
+        vec![],
+        NoLimitTracker,
+        &mut PrintWriter::Disabled,
+        options,
+    )
+    .map(|(repl, _)| repl)
+}
+
+#[test]
+fn allowed_builtins_can_be_used() {
+    let options = CompileOptions::new().allow_builtins(["len", "range"]);
+    let runner = compile("len(range(4))", options).unwrap();
+    assert_eq!(runner.run_no_limits(vec![]).unwrap(), MontyObject::Int(4));
+}
+
+#[test]
+fn builtins_outside_the_allowlist_are_name_errors_at_compile_time() {
+    let options = CompileOptions::new().allow_builtins(["len"]);
+    let code = "def f(x):\n    return len(x)\n\nf(sorted([2, 1]))";
+    let err = compile(code, options).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+    assert_eq!(err.message(), Some("name 'sorted' is not defined"));
+    let frame = &err.traceback()[0];
+    assert_eq!((frame.start.line, frame.start.column), (4, 3));
+}
+
+#[test]
+fn denied_builtins_are_rejected_even_inside_functions() {
+    let options = CompileOptions::new().deny_builtins(["print"]);
+    let err = compile("def log(msg):\n    print(msg)\n", options).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+    assert_eq!(err.message(), Some("name 'print' is not defined"));
+}
+
+#[test]
+fn denylist_wins_over_allowlist() {
+    let options = CompileOptions::new()
+        .allow_builtins(["len", "print"])
+        .deny_builtins(["print"]);
+    assert!(compile("len('ab')", options.clone()).is_ok());
+    assert!(compile("print('ab')", options).is_err());
+}
+
+#[test]
+fn script_definitions_shadow_disallowed_builtins() {
+    let options = CompileOptions::new().allow_builtins(Vec::<String>::new());
+    let runner = compile("def len(x):\n    return 7\n\nlen([])", options).unwrap();
+    assert_eq!(runner.run_no_limits(vec![]).unwrap(), MontyObject::Int(7));
+}
+
+#[test]
+fn names_that_are_not_builtins_are_rejected() {
+    let err = compile("1", CompileOptions::new().allow_builtins(["len", "lenn"])).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert_eq!(err.message(), Some("'lenn' is not a builtin"));
+
+    let err = compile("1", CompileOptions::new().deny_builtins(["prnt"])).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert_eq!(err.message(), Some("'prnt' is not a builtin"));
+
+    let err = new_repl("1", CompileOptions::new().deny_builtins(["prnt"])).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+}
+
+#[test]
+fn repl_snippets_are_restricted_like_the_initial_code() {
+    let options = CompileOptions::new().deny_builtins(["print"]);
+    assert!(new_repl("print('setup')", options.clone()).is_err());
+
+    let mut repl = new_repl("x = [3, 1, 2]", options).unwrap();
+    assert_eq!(
+        repl.feed("sorted(x)", &mut PrintWriter::Disabled).unwrap(),
+        MontyObject::List(vec![MontyObject::Int(1), MontyObject::Int(2), MontyObject::Int(3),])
+    );
+    let err = repl.feed("print(x)", &mut PrintWriter::Disabled).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+    assert_eq!(err.message(), Some("name 'print' is not defined"));
+}
+
+#[test]
+fn repl_options_survive_dump_and_load() {
+    let repl = new_repl("x = 1", CompileOptions::new().deny_builtins(["print"])).unwrap();
+    let mut repl = MontyRepl::<NoLimitTracker>::load(&repl.dump().unwrap()).unwrap();
+    let err = repl.feed("print(x)", &mut PrintWriter::Disabled).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+}
+
+#[test]
+fn repl_rejects_options_it_cannot_apply() {
+    let err = new_repl("1", CompileOptions::new().host_iterables(["rows"])).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert_eq!(err.message(), Some("MontyRepl doesn't support host iterables"));
+}
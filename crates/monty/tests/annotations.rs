@@ -0,0 +1,102 @@
+//! Tests for capturing function annotations and checking external calls against them.
+
+use monty::{
+    CompileOptions, ExcType, MontyException, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress, Snapshot,
+    ast::{self, Stmt},
+};
+
+const STUBS: &str = r"
+def fetch(url: str, *, timeout: float | None = None) -> dict: ...
+def count(items: list[str]) -> int: ...
+";
+
+fn start(code: &str) -> Result<RunProgress<NoLimitTracker>, MontyException> {
+    let options = CompileOptions::new().check_external_calls(STUBS);
+    let external_functions = vec!["fetch".to_owned(), "count".to_owned()];
+    let runner = MontyRun::new_with_options(code.to_owned(), "test.py", vec![], external_functions, options).unwrap();
+    runner.start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+}
+
+fn expect_call(progress: RunProgress<NoLimitTracker>) -> (String, Snapshot<NoLimitTracker>) {
+    let (name, _, _, _, _, state) = progress.into_function_call().expect("expected an external call");
+    (name, state)
+}
+
+#[test]
+fn annotations_are_exposed_on_the_ast() {
+    let module = ast::parse(
+        "def f(x: int, *, y: list[str] = []) -> str | None:\n    pass",
+        "test.py",
+    )
+    .unwrap();
+    let Stmt::FunctionDef(function) = &module.body[0] else {
+        panic!("expected a function definition");
+    };
+    assert_eq!(function.params.args[0].annotation.as_deref(), Some("int"));
+    assert_eq!(function.params.keyword_only[0].annotation.as_deref(), Some("list[str]"));
+    assert_eq!(function.returns.as_deref(), Some("str | None"));
+}
+
+#[test]
+fn matching_arguments_reach_the_host() {
+    let (name, _) = expect_call(start("fetch('https://example.com', timeout=1)").unwrap());
+    assert_eq!(name, "fetch");
+    let (name, _) = expect_call(start("count(['a'])").unwrap());
+    assert_eq!(name, "count");
+}
+
+#[test]
+fn mismatched_positional_argument_is_a_type_error() {
+    let err = start("fetch(b'https://example.com')").unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(err.message(), Some("fetch() argument 'url' must be str, not bytes"));
+}
+
+#[test]
+fn mismatched_keyword_argument_is_a_type_error() {
+    let err = start("fetch('https://example.com', timeout='soon')").unwrap_err();
+    assert_eq!(
+        err.message(),
+        Some("fetch() argument 'timeout' must be float | None, not str")
+    );
+}
+
+#[test]
+fn mismatched_return_value_raises_at_the_call() {
+    let code = r"
+try:
+    n = count(['a', 'b'])
+except TypeError as e:
+    n = str(e)
+n
+";
+    let (_, state) = expect_call(start(code).unwrap());
+    let progress = state
+        .run(MontyObject::String("two".to_owned()), &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(
+        progress.into_complete(),
+        Some(MontyObject::String(
+            "count() return value must be int, not str".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn matching_return_value_is_returned() {
+    let (_, state) = expect_call(start("count(['a', 'b'])").unwrap());
+    let progress = state.run(MontyObject::Int(2), &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(2)));
+}
+
+#[test]
+fn external_calls_are_unchecked_without_stubs() {
+    let runner = MontyRun::new("fetch(1)".to_owned(), "test.py", vec![], vec!["fetch".to_owned()]).unwrap();
+    let progress = runner
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    let (name, state) = expect_call(progress);
+    assert_eq!(name, "fetch");
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::None));
+}
@@ -0,0 +1,130 @@
+//! Tests for the public syntax tree returned by `monty::ast::parse`.
+
+use monty::{
+    ExcType,
+    ast::{self, Arg, BinOp, Constant, Expr, ExprKind, Stmt, Target, Visitor},
+};
+
+/// Collects the names of called functions and methods.
+#[derive(Default)]
+struct CallNames(Vec<String>);
+
+impl Visitor for CallNames {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Call { func, .. } = &expr.kind {
+            match &func.kind {
+                ExprKind::Name(name) => self.0.push(name.clone()),
+                ExprKind::Attribute { attr, .. } => self.0.push(format!(".{attr}")),
+                _ => {}
+            }
+        }
+        ast::walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn visitor_reaches_nested_expressions() {
+    let code = r"
+def total(items, scale=int('2')):
+    return sum([item.price() for item in items]) * scale
+
+for row in fetch():
+    if row:
+        print(f'{total(row):>{width()}}')
+";
+    let module = ast::parse(code, "test.py").unwrap();
+    let mut calls = CallNames::default();
+    module.visit(&mut calls);
+    assert_eq!(calls.0, ["int", "sum", ".price", "fetch", "print", "total", "width"]);
+}
+
+#[test]
+fn assignments_share_one_statement_kind() {
+    let code = r"
+a, *rest = values
+config['debug'] = True
+count += 1
+obj.name = 'x'
+";
+    let module = ast::parse(code, "test.py").unwrap();
+    let targets: Vec<&Target> = module
+        .body
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Assign { target, .. } => Some(target),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(targets.len(), 3);
+    assert!(matches!(&module.body[2], Stmt::AugAssign { target, op: BinOp::Add, .. } if target.id == "count"));
+
+    let Target::Tuple { targets: unpacked, .. } = targets[0] else {
+        panic!("expected tuple target, got {:?}", targets[0]);
+    };
+    assert!(matches!(&unpacked[0], Target::Name(name) if name.id == "a"));
+    assert!(matches!(&unpacked[1], Target::Starred(name) if name.id == "rest"));
+    assert!(matches!(targets[1], Target::Subscript { .. }));
+    assert!(matches!(targets[2], Target::Attribute { attr, .. } if attr == "name"));
+}
+
+#[test]
+fn unpacking_into_items_and_attributes() {
+    let module = ast::parse("items[key()], point.x = pair", "test.py").unwrap();
+    let Stmt::Assign {
+        target: Target::Tuple { targets, .. },
+        ..
+    } = &module.body[0]
+    else {
+        panic!("expected tuple assignment, got {:?}", module.body[0]);
+    };
+    assert!(matches!(&targets[0], Target::Subscript { .. }));
+    assert!(matches!(&targets[1], Target::Attribute { attr, .. } if attr == "x"));
+
+    let mut calls = CallNames::default();
+    module.visit(&mut calls);
+    assert_eq!(calls.0, ["key"]);
+}
+
+#[test]
+fn expressions_resolve_literals_and_positions() {
+    let module = ast::parse("x = f(1, 2.5, 'a' 'b', key=None) or 10**30", "test.py").unwrap();
+    let [Stmt::Assign { value, .. }] = module.body.as_slice() else {
+        panic!("expected a single assignment, got {:?}", module.body);
+    };
+    assert_eq!(value.span.start.line, 1);
+    assert_eq!(value.span.start.column, 5);
+
+    let ExprKind::BinOp { left, op, .. } = &value.kind else {
+        panic!("expected `or`, got {value:?}");
+    };
+    assert_eq!(*op, BinOp::Or);
+    let ExprKind::Call { args, .. } = &left.kind else {
+        panic!("expected a call, got {left:?}");
+    };
+    let constants: Vec<&Constant> = args
+        .iter()
+        .map(|arg| match arg {
+            Arg::Positional(expr) | Arg::Keyword { value: expr, .. } => match &expr.kind {
+                ExprKind::Constant(constant) => constant,
+                other => panic!("expected a constant, got {other:?}"),
+            },
+            other => panic!("unexpected argument {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        constants,
+        [
+            &Constant::Int(1),
+            &Constant::Float(2.5),
+            &Constant::Str("ab".to_owned()),
+            &Constant::None
+        ]
+    );
+    assert!(matches!(&args[3], Arg::Keyword { name, .. } if name.id == "key"));
+}
+
+#[test]
+fn parse_reports_syntax_errors() {
+    let err = ast::parse("def f(:\n    pass", "test.py").unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::SyntaxError);
+}
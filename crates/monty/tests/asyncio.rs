@@ -72,6 +72,18 @@ fn drive_to_resolve_futures<T: monty::ResourceTracker>(
             RunProgress::OsCall { function, .. } => {
                 panic!("unexpected OsCall: {function:?}");
             }
+            RunProgress::Paused(_) => {
+                panic!("unexpected Paused");
+            }
+            RunProgress::Breakpoint(_) => {
+                panic!("unexpected Breakpoint");
+            }
+            RunProgress::Emit { .. } => {
+                panic!("unexpected Emit");
+            }
+            RunProgress::Sleep { .. } => {
+                panic!("unexpected Sleep");
+            }
         }
     }
 }
@@ -774,6 +786,18 @@ fn drive_collecting_calls<T: monty::ResourceTracker>(
             RunProgress::OsCall { function, .. } => {
                 panic!("unexpected OsCall: {function:?}");
             }
+            RunProgress::Paused(_) => {
+                panic!("unexpected Paused");
+            }
+            RunProgress::Breakpoint(_) => {
+                panic!("unexpected Breakpoint");
+            }
+            RunProgress::Emit { .. } => {
+                panic!("unexpected Emit");
+            }
+            RunProgress::Sleep { .. } => {
+                panic!("unexpected Sleep");
+            }
         }
     }
 }
@@ -0,0 +1,181 @@
+//! Tests for batching external calls: queuing calls to batched functions and handing them
+//! to the host as one group.
+
+use monty::{
+    BatchedCall, ExcType, ExternalResult, FutureSnapshot, MontyException, MontyObject, MontyRun, NoLimitTracker,
+    PrintWriter, RunProgress,
+};
+
+fn start(code: &str) -> RunProgress<NoLimitTracker> {
+    let mut run = MontyRun::builder(code)
+        .external_functions(["fetch", "log"])
+        .batch_external_calls(["fetch"])
+        .build()
+        .unwrap();
+    run.start(vec![]).unwrap()
+}
+
+fn expect_futures(progress: RunProgress<NoLimitTracker>) -> FutureSnapshot<NoLimitTracker> {
+    progress
+        .into_resolve_futures()
+        .expect("expected the run to wait on futures")
+}
+
+/// Resolves every call in the batch with its first argument doubled.
+fn double_all(calls: &[BatchedCall]) -> Vec<(u32, ExternalResult)> {
+    calls
+        .iter()
+        .map(|call| {
+            let MontyObject::Int(n) = call.args[0] else {
+                panic!("expected an int argument, got {:?}", call.args[0]);
+            };
+            (call.call_id, ExternalResult::Return(MontyObject::Int(n * 2)))
+        })
+        .collect()
+}
+
+#[test]
+fn calls_in_a_loop_are_handed_over_together() {
+    let code = r"
+import asyncio
+pending = []
+for n in range(4):
+    pending.append(fetch(n, retries=n % 2))
+await asyncio.gather(*pending)
+";
+    let state = expect_futures(start(code));
+    let calls = state.batched_calls();
+    assert_eq!(calls.len(), 4);
+    for (n, call) in (0..4).zip(calls) {
+        assert_eq!(call.function_name, "fetch");
+        assert_eq!(call.args, vec![MontyObject::Int(n)]);
+        assert_eq!(
+            call.kwargs,
+            vec![(MontyObject::String("retries".to_owned()), MontyObject::Int(n % 2))]
+        );
+    }
+    let call_ids: Vec<u32> = calls.iter().map(|call| call.call_id).collect();
+    assert_eq!(state.pending_call_ids().len(), 4);
+    assert!(call_ids.iter().all(|id| state.pending_call_ids().contains(id)));
+
+    let results = double_all(state.batched_calls());
+    let result = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    let expected = MontyObject::List((0..4).map(|n| MontyObject::Int(n * 2)).collect());
+    assert_eq!(result.into_complete(), Some(expected));
+}
+
+#[test]
+fn results_are_scattered_back_by_call_id() {
+    let code = r"
+a = fetch(1)
+b = fetch(2)
+c = fetch(3)
+(await c, await a, await b)
+";
+    let state = expect_futures(start(code));
+    // resolving the batch in reverse order doesn't matter
+    let mut results = double_all(state.batched_calls());
+    results.reverse();
+    let result = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    let expected = MontyObject::Tuple(vec![MontyObject::Int(6), MontyObject::Int(2), MontyObject::Int(4)]);
+    assert_eq!(result.into_complete(), Some(expected));
+}
+
+#[test]
+fn each_call_is_handed_over_once() {
+    let code = r"
+a = fetch(1)
+b = fetch(2)
+(await a) + (await b)
+";
+    let state = expect_futures(start(code));
+    let mut results = double_all(state.batched_calls());
+    let second = results.pop().unwrap();
+
+    // with only the first call resolved, the run waits on the second without handing it over again
+    let state = expect_futures(state.resume(results, &mut PrintWriter::Disabled).unwrap());
+    assert!(state.batched_calls().is_empty());
+    assert_eq!(state.pending_call_ids(), [second.0]);
+
+    let result = state.resume(vec![second], &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(result.into_complete(), Some(MontyObject::Int(6)));
+}
+
+#[test]
+fn awaiting_each_call_in_turn_makes_batches_of_one() {
+    let code = r"
+total = 0
+for n in range(3):
+    total += await fetch(n)
+total
+";
+    let mut progress = start(code);
+    for n in 0..3 {
+        let state = expect_futures(progress);
+        assert_eq!(state.batched_calls().len(), 1);
+        assert_eq!(state.batched_calls()[0].args, vec![MontyObject::Int(n)]);
+        let results = double_all(state.batched_calls());
+        progress = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    }
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(6)));
+}
+
+#[test]
+fn unbatched_calls_still_pause_the_run() {
+    let code = r"
+a = fetch(1)
+log('queued')
+b = fetch(2)
+(await a) + (await b)
+";
+    let (name, args, _, _, _, state) = start(code).into_function_call().expect("expected a call to log");
+    assert_eq!(name, "log");
+    assert_eq!(args, vec![MontyObject::String("queued".to_owned())]);
+
+    // the batch holds the calls made before and after the unbatched one
+    let state = expect_futures(state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap());
+    let args: Vec<_> = state.batched_calls().iter().map(|call| call.args.clone()).collect();
+    assert_eq!(args, vec![vec![MontyObject::Int(1)], vec![MontyObject::Int(2)]]);
+
+    let results = double_all(state.batched_calls());
+    let result = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(result.into_complete(), Some(MontyObject::Int(6)));
+}
+
+#[test]
+fn failed_calls_raise_in_the_script() {
+    let code = r"
+a = fetch(1)
+b = fetch(2)
+(await a) + (await b)
+";
+    let state = expect_futures(start(code));
+    let calls = state.batched_calls();
+    let error = MontyException::new(ExcType::ValueError, Some("not found".to_owned()));
+    let results = vec![
+        (calls[0].call_id, ExternalResult::Error(error)),
+        (calls[1].call_id, ExternalResult::Return(MontyObject::Int(10))),
+    ];
+    let err = state.resume(results, &mut PrintWriter::Disabled).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert_eq!(err.message(), Some("not found"));
+}
+
+#[test]
+fn calls_never_awaited_are_never_handed_over() {
+    let result = start("fetch(1)\n42").into_complete();
+    assert_eq!(result, Some(MontyObject::Int(42)));
+}
+
+#[test]
+fn batches_survive_a_dump_and_load() {
+    let code = "import asyncio\nawait asyncio.gather(fetch(1), fetch(2))";
+    let bytes = start(code).dump().unwrap();
+    let state = expect_futures(RunProgress::load(&bytes).unwrap());
+    assert_eq!(state.batched_calls().len(), 2);
+
+    let results = double_all(state.batched_calls());
+    let result = state.resume(results, &mut PrintWriter::Disabled).unwrap();
+    let expected = MontyObject::List(vec![MontyObject::Int(2), MontyObject::Int(4)]);
+    assert_eq!(result.into_complete(), Some(expected));
+}
@@ -4,7 +4,10 @@
 //! - Caching parsed code to avoid re-parsing
 //! - Snapshotting execution state for external function calls
 
-use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+use monty::{
+    ExternalResult, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress, SNAPSHOT_FORMAT_VERSION, Snapshot,
+    SnapshotError,
+};
 
 // === MontyRun dump/load Tests ===
 
@@ -88,6 +91,113 @@ result
     assert_eq!(result, expected);
 }
 
+#[test]
+fn monty_run_dump_code_load_code() {
+    let code = "def double(x):\n    return x * 2\n\ndouble(n)";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec!["n".to_owned()], vec![]).unwrap();
+    let bytes = runner.dump_code().unwrap();
+    let loaded = MontyRun::load_code(&bytes).unwrap();
+
+    assert_eq!(loaded.code(), code);
+    for n in [1, 2, 3] {
+        assert_eq!(
+            loaded.run_no_limits(vec![MontyObject::Int(n)]).unwrap(),
+            MontyObject::Int(n * 2)
+        );
+    }
+}
+
+#[test]
+fn monty_run_load_code_rejects_foreign_bytes() {
+    let runner = MontyRun::new("1 + 2".to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    // `dump()` output has no header
+    assert!(matches!(
+        MontyRun::load_code(&runner.dump().unwrap()),
+        Err(SnapshotError::BadMagic)
+    ));
+
+    // Bytecode written by a different version
+    let mut stale = runner.dump_code().unwrap();
+    stale[VERSION_OFFSET] = b'x';
+    assert!(matches!(
+        MontyRun::load_code(&stale),
+        Err(SnapshotError::VersionMismatch { .. })
+    ));
+
+    let bytes = runner.dump_code().unwrap();
+    assert!(MontyRun::load_code(&bytes[..bytes.len() / 2]).is_err());
+}
+
+// === Snapshot header Tests ===
+
+/// Offset of the crate version string in the snapshot header: magic (4), format version (2), length (1).
+const VERSION_OFFSET: usize = 7;
+
+fn paused_progress_bytes() -> Vec<u8> {
+    let runner = MontyRun::new("ext(1) + 1".to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap();
+    let progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap();
+    progress.dump().unwrap()
+}
+
+#[test]
+fn run_progress_dump_has_header() {
+    let bytes = paused_progress_bytes();
+    assert_eq!(&bytes[..4], b"MNTR");
+    assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), SNAPSHOT_FORMAT_VERSION);
+    let version_len = usize::from(bytes[6]);
+    assert_eq!(
+        &bytes[VERSION_OFFSET..VERSION_OFFSET + version_len],
+        env!("CARGO_PKG_VERSION").as_bytes()
+    );
+}
+
+#[test]
+fn run_progress_load_rejects_other_version() {
+    let mut bytes = paused_progress_bytes();
+    bytes[VERSION_OFFSET] = b'x';
+    let err = RunProgress::<NoLimitTracker>::load(&bytes).unwrap_err();
+    let SnapshotError::VersionMismatch {
+        format_version,
+        crate_version,
+    } = &err
+    else {
+        panic!("expected VersionMismatch, got {err:?}");
+    };
+    assert_eq!(*format_version, SNAPSHOT_FORMAT_VERSION);
+    assert!(crate_version.starts_with('x'));
+    assert!(err.to_string().contains(env!("CARGO_PKG_VERSION")));
+
+    let mut bytes = paused_progress_bytes();
+    bytes[4] = bytes[4].wrapping_add(1);
+    assert!(matches!(
+        RunProgress::<NoLimitTracker>::load(&bytes),
+        Err(SnapshotError::VersionMismatch { .. })
+    ));
+}
+
+#[test]
+fn run_progress_load_rejects_corruption() {
+    let mut bytes = paused_progress_bytes();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    assert!(matches!(
+        RunProgress::<NoLimitTracker>::load(&bytes),
+        Err(SnapshotError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn run_progress_load_rejects_foreign_bytes() {
+    let runner = MontyRun::new("1".to_owned(), "test.py", vec![], vec![]).unwrap();
+    for bytes in [Vec::new(), runner.dump().unwrap(), runner.dump_code().unwrap()] {
+        assert!(matches!(
+            RunProgress::<NoLimitTracker>::load(&bytes),
+            Err(SnapshotError::BadMagic)
+        ));
+    }
+}
+
 #[test]
 fn monty_run_dump_load_multiple_runs() {
     // A loaded runner can be run multiple times
@@ -136,6 +246,32 @@ fn run_progress_dump_load_roundtrip() {
     assert_eq!(result.into_complete().unwrap(), MontyObject::Int(101)); // 100 + 1
 }
 
+#[test]
+fn snapshot_dump_load_roundtrip() {
+    let runner = MontyRun::new("ext(1) + 1".to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap();
+    let progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap();
+    let (.., state) = progress.into_function_call().expect("should be at function call");
+
+    let bytes = state.dump().unwrap();
+    assert_eq!(&bytes[..4], b"MNTS");
+    // a snapshot on its own is not a `RunProgress`, and the other way around
+    assert!(matches!(
+        RunProgress::<NoLimitTracker>::load(&bytes),
+        Err(SnapshotError::BadMagic)
+    ));
+    assert!(matches!(
+        Snapshot::<NoLimitTracker>::load(&paused_progress_bytes()),
+        Err(SnapshotError::BadMagic)
+    ));
+
+    let loaded = Snapshot::<NoLimitTracker>::load(&bytes).unwrap();
+    let result = loaded.run(MontyObject::Int(41), &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(result.into_complete().unwrap(), MontyObject::Int(42));
+    // the original snapshot was only borrowed for the dump
+    let result = state.run(MontyObject::Int(1), &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(result.into_complete().unwrap(), MontyObject::Int(2));
+}
+
 #[test]
 fn run_progress_dump_load_multiple_calls() {
     // Test multiple external calls with dump/load between each
@@ -181,3 +317,154 @@ fn run_progress_complete_roundtrip() {
 
     assert_eq!(loaded.into_complete().unwrap(), MontyObject::Int(3));
 }
+
+/// Dumps and loads `progress`, which must be paused at a call to `ext_fn`, and resumes it with `value`.
+fn reload_and_resume(progress: RunProgress<NoLimitTracker>, value: MontyObject) -> MontyObject {
+    let bytes = progress.dump().unwrap();
+    let loaded: RunProgress<NoLimitTracker> = RunProgress::load(&bytes).unwrap();
+    let (fn_name, _, _, _call_id, _, state) = loaded.into_function_call().expect("should be at function call");
+    assert_eq!(fn_name, "ext_fn");
+    let result = state.run(value, &mut PrintWriter::Disabled).unwrap();
+    result.into_complete().unwrap()
+}
+
+#[test]
+fn run_progress_resumes_finally_during_exception() {
+    // The exception being propagated is part of the snapshot and re-raised after finally
+    let code = r"
+log = []
+try:
+    try:
+        raise ValueError('boom')
+    finally:
+        log.append(ext_fn(1))
+except ValueError as e:
+    log.append(str(e))
+log
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext_fn".to_owned()]).unwrap();
+    let progress = runner
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(
+        reload_and_resume(progress, MontyObject::Int(10)),
+        MontyObject::List(vec![MontyObject::Int(10), MontyObject::String("boom".to_owned())])
+    );
+}
+
+#[test]
+fn run_progress_resumes_finally_during_return() {
+    // The pending return value is part of the snapshot and returned after finally
+    let code = r"
+def f():
+    for i in range(3):
+        try:
+            return i * 10
+        finally:
+            ext_fn(i)
+f()
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext_fn".to_owned()]).unwrap();
+    let progress = runner
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(reload_and_resume(progress, MontyObject::None), MontyObject::Int(0));
+}
+
+// === Heap compaction Tests ===
+
+#[test]
+fn compact_heap_shrinks_snapshot_and_resumes() {
+    let code = r"
+garbage = [f'item-{i}' for i in range(2000)]
+garbage = None
+
+def make_counter():
+    count = [0]
+    def inc():
+        count[0] += 1
+        return count[0]
+    return inc
+
+inc = make_counter()
+data = {'items': [f'live-{i}' for i in range(3)], 'inc': inc}
+inc()
+x = ext_fn(len(data['items']))
+(x, data['items'], data['inc']())
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext_fn".to_owned()]).unwrap();
+    let mut progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap();
+
+    let uncompacted = progress.dump().unwrap();
+    progress.compact_heap();
+    let compacted = progress.dump().unwrap();
+    assert!(
+        compacted.len() + 1000 < uncompacted.len(),
+        "expected compaction to drop freed slots: {} -> {}",
+        uncompacted.len(),
+        compacted.len()
+    );
+
+    let loaded: RunProgress<NoLimitTracker> = RunProgress::load(&compacted).unwrap();
+    let (_, args, _, _, _, state) = loaded.into_function_call().unwrap();
+    assert_eq!(args, vec![MontyObject::Int(3)]);
+    let result = state.run(MontyObject::Int(7), &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(
+        result.into_complete().unwrap(),
+        MontyObject::Tuple(vec![
+            MontyObject::Int(7),
+            MontyObject::List(vec![
+                MontyObject::String("live-0".to_owned()),
+                MontyObject::String("live-1".to_owned()),
+                MontyObject::String("live-2".to_owned()),
+            ]),
+            MontyObject::Int(2),
+        ])
+    );
+}
+
+#[test]
+fn compact_heap_preserves_pending_async_tasks() {
+    let code = r"
+import asyncio
+
+garbage = [f'item-{i}' for i in range(500)]
+garbage = None
+
+async def main():
+    a, b = await asyncio.gather(foo(), bar())
+    return [a, b]
+
+await main()
+";
+    let runner = MontyRun::new(
+        code.to_owned(),
+        "test.py",
+        vec![],
+        vec!["foo".to_owned(), "bar".to_owned()],
+    )
+    .unwrap();
+    let mut progress = runner.start(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap();
+    let mut call_ids = Vec::new();
+    let mut state = loop {
+        match progress {
+            RunProgress::FunctionCall { call_id, state, .. } => {
+                call_ids.push(call_id);
+                progress = state.run_pending(&mut PrintWriter::Stdout).unwrap();
+            }
+            RunProgress::ResolveFutures(state) => break state,
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    };
+
+    state.compact_heap();
+    let results = vec![
+        (call_ids[0], ExternalResult::Return(MontyObject::Int(1))),
+        (call_ids[1], ExternalResult::Return(MontyObject::Int(2))),
+    ];
+    let result = state.resume(results, &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(
+        result.into_complete().unwrap(),
+        MontyObject::List(vec![MontyObject::Int(1), MontyObject::Int(2)])
+    );
+}
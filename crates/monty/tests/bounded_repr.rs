@@ -0,0 +1,87 @@
+//! Tests for the depth- and length-bounded `repr()` used by `print()`, exception messages
+//! and `MontyObject::display_truncated`.
+
+use monty::{ExcType, MontyException, MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+
+fn printed(code: &str) -> String {
+    let run = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let mut writer = PrintWriter::Collect(String::new());
+    run.run(vec![], NoLimitTracker, &mut writer).unwrap();
+    writer.collected_output().unwrap().to_owned()
+}
+
+fn raised(code: &str) -> MontyException {
+    let run = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    run.run_no_limits(vec![]).unwrap_err()
+}
+
+#[test]
+fn printing_a_huge_list_stops_at_the_limit() {
+    let output = printed("print(list(range(300_000)))");
+    let line = output.strip_suffix("...\n").expect("output should be cut short");
+    assert_eq!(line.len() + 3, 1 << 20);
+    assert!(line.starts_with("[0, 1, 2, 3, "));
+}
+
+#[test]
+fn printing_a_huge_string_writes_all_of_it() {
+    let output = printed("print('x' * 2_000_000)");
+    assert_eq!(output.len(), 2_000_001);
+    assert!(output.ends_with("xxx\n"));
+}
+
+#[test]
+fn printing_small_values_is_unchanged() {
+    let output = printed("print([1, 'two', (3.0, None)], {'a': [1]}, 'text')");
+    assert_eq!(output, "[1, 'two', (3.0, None)] {'a': [1]} text\n");
+}
+
+#[test]
+fn key_errors_quote_huge_keys_briefly() {
+    let err = raised("d = {}\nd[tuple(range(100_000))]");
+    assert_eq!(err.exc_type(), ExcType::KeyError);
+    let message = err.message().unwrap();
+    assert_eq!(message.len(), 1_000);
+    assert!(message.starts_with("(0, 1, 2, "));
+    assert!(message.ends_with("..."));
+}
+
+#[test]
+fn key_errors_quote_deeply_nested_keys_briefly() {
+    let code = "key = 0\nfor _ in range(40):\n    key = (key, 0)\n{}[key]";
+    let err = raised(code);
+    assert_eq!(err.exc_type(), ExcType::KeyError);
+    let expected = format!("{}...{}", "(".repeat(16), ", 0)".repeat(16));
+    assert_eq!(err.message(), Some(expected.as_str()));
+}
+
+#[test]
+fn key_errors_quote_small_keys_in_full() {
+    let err = raised("{}[(1, 'a')]");
+    assert_eq!(err.message(), Some("(1, 'a')"));
+}
+
+#[test]
+fn display_truncated_cuts_long_output() {
+    let numbers = MontyObject::List((0..1_000).map(MontyObject::Int).collect());
+    assert_eq!(numbers.display_truncated(20), "[0, 1, 2, 3, 4, 5...");
+    let full = numbers.to_string();
+    assert_eq!(numbers.display_truncated(full.len()), full);
+    assert_eq!(numbers.display_truncated(full.len() - 1).len(), full.len() - 1);
+}
+
+#[test]
+fn display_truncated_counts_characters() {
+    let text = MontyObject::String("héllo wörld".to_owned());
+    assert_eq!(text.display_truncated(11), "héllo wörld");
+    assert_eq!(text.display_truncated(8), "héllo...");
+}
+
+#[test]
+fn display_truncated_handles_deep_nesting() {
+    let mut nested = MontyObject::Int(0);
+    for _ in 0..1_000 {
+        nested = MontyObject::List(vec![nested]);
+    }
+    assert_eq!(nested.display_truncated(8), "[[[[[...");
+}
@@ -0,0 +1,121 @@
+//! Tests for configuring runs with `MontyRun::builder`.
+
+use std::sync::{Arc, Mutex};
+
+use monty::{ExcType, MontyObject, MontyRun, PrintWriter, ResourceLimits, TraceHook, TracePosition};
+
+/// Records the lines traced in every run, shared between the hook's clones.
+#[derive(Debug, Default, Clone)]
+struct LineRecorder(Arc<Mutex<Vec<u16>>>);
+
+impl TraceHook for LineRecorder {
+    fn on_line(&mut self, position: &TracePosition<'_>) {
+        self.0.lock().unwrap().push(position.line);
+    }
+}
+
+#[test]
+fn defaults_run_without_limits_or_output() {
+    let mut run = MontyRun::builder("print('hidden')\n1 + 2").build().unwrap();
+    assert_eq!(run.run(vec![]), Ok(MontyObject::Int(3)));
+    assert_eq!(run.collected_output(), None);
+}
+
+#[test]
+fn script_name_appears_in_tracebacks() {
+    let mut run = MontyRun::builder("1 / 0").script_name("calc.py").build().unwrap();
+    let err = run.run(vec![]).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ZeroDivisionError);
+    assert!(err.to_string().contains("calc.py"), "{err}");
+}
+
+#[test]
+fn build_reports_compile_errors() {
+    let err = MontyRun::builder("1 +").build().unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::SyntaxError);
+
+    let err = MontyRun::builder("sum([1])")
+        .deny_builtins(["sum"])
+        .build()
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+    assert_eq!(err.message(), Some("name 'sum' is not defined"));
+}
+
+#[test]
+fn limits_apply_to_each_run() {
+    let mut run = MontyRun::builder("[[i] for i in range(n)]")
+        .inputs(["n"])
+        .limits(ResourceLimits::new().max_allocations(50))
+        .build()
+        .unwrap();
+    // each run gets a fresh tracker, so repeating a run within the limit keeps working
+    for _ in 0..3 {
+        assert!(run.run(vec![MontyObject::Int(10)]).is_ok());
+    }
+    let err = run.run(vec![MontyObject::Int(100)]).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::MemoryError);
+}
+
+#[test]
+fn print_output_collects_across_runs() {
+    let mut run = MontyRun::builder("print(prefix, n)")
+        .inputs(["n"])
+        .globals([("prefix", MontyObject::from("n ="))])
+        .print(PrintWriter::Collect(String::new()))
+        .build()
+        .unwrap();
+    run.run(vec![MontyObject::Int(1)]).unwrap();
+    run.run(vec![MontyObject::Int(2)]).unwrap();
+    assert_eq!(run.collected_output(), Some("n = 1\nn = 2\n"));
+}
+
+#[test]
+fn trace_hook_sees_every_run() {
+    let recorder = LineRecorder::default();
+    let mut run = MontyRun::builder("x = 1\ny = x + 1\ny")
+        .limits(ResourceLimits::new().max_allocations(10))
+        .trace(recorder.clone())
+        .build()
+        .unwrap();
+    assert_eq!(run.run(vec![]), Ok(MontyObject::Int(2)));
+    assert_eq!(run.run(vec![]), Ok(MontyObject::Int(2)));
+    assert_eq!(*recorder.0.lock().unwrap(), vec![1, 2, 3, 1, 2, 3]);
+}
+
+#[test]
+fn limits_keep_earlier_trace_and_determinism() {
+    let recorder = LineRecorder::default();
+    let mut run = MontyRun::builder("import time\nt = time.time()\nt")
+        .trace(recorder.clone())
+        .deterministic()
+        .limits(ResourceLimits::new().max_allocations(100))
+        .build()
+        .unwrap();
+    assert_eq!(run.run(vec![]), Ok(MontyObject::Float(0.0)));
+    assert_eq!(*recorder.0.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn start_pauses_at_external_calls() {
+    let mut run = MontyRun::builder("print('got', fetch(1))\n'done'")
+        .external_functions(["fetch"])
+        .check_external_calls("def fetch(key: int) -> str: ...")
+        .print(PrintWriter::Collect(String::new()))
+        .build()
+        .unwrap();
+    let progress = run.start(vec![]).unwrap();
+    let (name, args, _, _, _, state) = progress.into_function_call().unwrap();
+    assert_eq!(name, "fetch");
+    assert_eq!(args, vec![MontyObject::Int(1)]);
+    let progress = state.run(MontyObject::from("one"), run.print_mut()).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::from("done")));
+    assert_eq!(run.collected_output(), Some("got one\n"));
+}
+
+#[test]
+fn optimize_false_keeps_the_code_as_written() {
+    let optimized = MontyRun::builder("60 * 60").build().unwrap();
+    let unoptimized = MontyRun::builder("60 * 60").optimize(false).build().unwrap();
+    assert!(optimized.runner().disassemble().len() < unoptimized.runner().disassemble().len());
+}
@@ -9,7 +9,7 @@
 
 use std::fmt::Write;
 
-use monty::{ExcType, MontyRun};
+use monty::{ExcType, MontyObject, MontyRun};
 
 /// Generates Python code with N local variables in a function.
 ///
@@ -148,6 +148,31 @@ mod local_variable_limits {
         let result = run.run_no_limits(vec![]);
         assert!(result.is_ok(), "300 locals should run successfully");
     }
+
+    #[test]
+    fn except_name_past_u8_limit_is_unbound() {
+        // the `except ... as e` name lands past slot 255, so unbinding it needs DeleteLocalW
+        let mut code = String::from("def f():\n");
+        for i in 0..300 {
+            writeln!(code, "    v{i} = {i}").unwrap();
+        }
+        code.push_str(
+            "    try:
+        raise ValueError('boom')
+    except ValueError as e:
+        caught = str(e)
+    try:
+        e
+    except NameError:
+        return caught
+    return 'still bound'
+f()",
+        );
+        let run = MontyRun::new(code, "test.py", vec![], vec![]).expect("300+ locals with except should compile");
+
+        let result = run.run_no_limits(vec![]).expect("300+ locals with except should run");
+        assert_eq!(result, MontyObject::String("boom".to_owned()));
+    }
 }
 
 mod function_argument_limits {
@@ -0,0 +1,75 @@
+//! Tests for rejecting scripts before running them with `MontyRun::check`.
+
+use monty::{ExcType, MontyRun, Severity};
+
+/// Returns the line and message of each diagnostic `check` reports.
+fn check(code: &str) -> Vec<(u16, String)> {
+    MontyRun::check(code, "test.py", vec!["data".to_owned()], vec!["fetch".to_owned()])
+        .into_iter()
+        .map(|diagnostic| (diagnostic.start.line, diagnostic.message))
+        .collect()
+}
+
+#[test]
+fn defined_names_pass() {
+    let code = r"
+import sys
+
+def double(x):
+    return x * 2
+
+result = [double(n) for n in data]
+fetch(result, sys.platform)
+";
+    assert_eq!(check(code), []);
+}
+
+#[test]
+fn name_read_before_assignment_is_reported() {
+    let code = "total = price * 2\nprice = 3\ntotal + price";
+    let diagnostics = MontyRun::check(code, "test.py", vec![], vec![]);
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(diagnostic.exc_type, Some(ExcType::NameError));
+    assert_eq!(diagnostic.message, "name 'price' is not defined");
+    assert_eq!(&code[diagnostic.span.clone()], "price");
+}
+
+#[test]
+fn undefined_names_in_functions_are_reported() {
+    let code = r"
+def f(x):
+    return x + offset + missing()
+
+offset = 1
+";
+    assert_eq!(
+        check(code),
+        [
+            (3, "name 'offset' is not defined".to_owned()),
+            (3, "name 'missing' is not defined".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn loop_assignments_from_earlier_iterations_are_not_reported() {
+    let code = r"
+for n in data:
+    if n > 0:
+        print(previous)
+    previous = n
+    print(never_set)
+";
+    assert_eq!(check(code), [(6, "name 'never_set' is not defined".to_owned())]);
+}
+
+#[test]
+fn unsupported_features_are_reported() {
+    let code = "class A:\n    pass\n\nprint(undefined)\n";
+    let diagnostics = MontyRun::check(code, "test.py", vec![], vec![]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].exc_type, Some(ExcType::NotImplementedError));
+    assert_eq!(diagnostics[0].start.line, 1);
+}
@@ -0,0 +1,97 @@
+//! Tests for automatic periodic checkpointing with `Checkpointer`.
+
+use std::time::Duration;
+
+use monty::{CheckpointPolicy, Checkpointer, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+
+const LOOP_CODE: &str = r"
+total = 0
+for i in range(10000):
+    total += i
+total
+";
+
+fn runner(code: &str) -> MontyRun {
+    MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap()
+}
+
+#[test]
+fn checkpoints_every_n_opcodes() {
+    let mut dumps = Vec::new();
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new().every_opcodes(1000), |bytes: &[u8]| {
+        dumps.push(bytes.to_vec());
+    });
+    let progress = checkpointer
+        .start(runner(LOOP_CODE), vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(49_995_000)));
+    let checkpoints = checkpointer.checkpoints();
+    assert!(checkpoints > 3, "expected several checkpoints, got {checkpoints}");
+    drop(checkpointer);
+    assert_eq!(dumps.len(), checkpoints);
+}
+
+#[test]
+fn restored_checkpoint_finishes_with_same_result() {
+    let mut last = None;
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new().every_opcodes(500), |bytes: &[u8]| {
+        last = Some(bytes.to_vec());
+    });
+    checkpointer
+        .start(runner(LOOP_CODE), vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+    drop(checkpointer);
+
+    // pretend the host crashed after the last checkpoint
+    let restored: RunProgress<NoLimitTracker> = RunProgress::load(&last.unwrap()).unwrap();
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new().every_opcodes(500), |_: &[u8]| {});
+    let progress = checkpointer.run(restored, &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(49_995_000)));
+}
+
+#[test]
+fn external_calls_are_returned_to_the_host() {
+    let code = r"
+total = 0
+for i in range(1000):
+    total += i
+total = ext(total)
+for i in range(1000):
+    total += i
+total
+";
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new().every_opcodes(300), |_: &[u8]| {});
+    let progress = checkpointer
+        .start(runner(code), vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+    let (name, args, _, _, _, state) = progress.into_function_call().expect("expected FunctionCall");
+    assert_eq!(name, "ext");
+    assert_eq!(args, vec![MontyObject::Int(499_500)]);
+    let before_call = checkpointer.checkpoints();
+
+    let progress = state.run(MontyObject::Int(0), &mut PrintWriter::Stdout).unwrap();
+    let progress = checkpointer.run(progress, &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(499_500)));
+    assert!(checkpointer.checkpoints() > before_call);
+}
+
+#[test]
+fn checkpoints_by_time() {
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new().every(Duration::ZERO), |_: &[u8]| {});
+    let progress = checkpointer
+        .start(runner(LOOP_CODE), vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(49_995_000)));
+    assert!(checkpointer.checkpoints() > 0);
+}
+
+#[test]
+fn default_policy_takes_no_checkpoints() {
+    let mut checkpointer = Checkpointer::new(CheckpointPolicy::new(), |_: &[u8]| panic!("unexpected checkpoint"));
+    let progress = checkpointer
+        .start(runner(LOOP_CODE), vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(49_995_000)));
+    assert_eq!(checkpointer.checkpoints(), 0);
+}
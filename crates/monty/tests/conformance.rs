@@ -0,0 +1,19 @@
+//! Runs every snippet in `conformance/` under both Monty and CPython and fails if the
+//! value of the last expression, the exception raised or the printed output differ.
+//!
+//! Unlike `test_cases/`, the snippets carry no expectations: CPython is the reference.
+//! Only built with the `conformance` feature, see `make test-conformance`.
+
+use std::{error::Error, fs, path::Path};
+
+fn run_conformance(path: &Path) -> Result<(), Box<dyn Error>> {
+    let code = fs::read_to_string(path)?;
+    let comparison = monty::conformance::compare(&code);
+    if comparison.matches() {
+        Ok(())
+    } else {
+        Err(format!("{} differs from CPython:\n{comparison}", path.display()).into())
+    }
+}
+
+datatest_stable::harness!(run_conformance, "conformance", r"^.*\.py$");
@@ -0,0 +1,111 @@
+//! Tests for compile-time constant folding and `CompileOptions::optimize`.
+
+use monty::{CompileOptions, LimitedTracker, MontyObject, MontyRun, PrintWriter, ResourceLimits};
+
+const CODE: &str = r"
+x = 'ab' + 'cd'
+y = 60 * 60 * 24
+if False:
+    y = 0
+while False:
+    y = 1
+else:
+    y += 1
+(x, y, f'{x}-{1 + 1}', resources()['allocations'])
+";
+
+/// Runs `CODE` and returns the tuple it evaluates to.
+fn run_with(options: CompileOptions) -> Vec<MontyObject> {
+    let ex = MontyRun::new_with_options(CODE.to_owned(), "test.py", vec![], vec![], options).unwrap();
+    let tracker = LimitedTracker::new(ResourceLimits::new().max_allocations(1_000));
+    let result = ex.run(vec![], tracker, &mut PrintWriter::Stdout).unwrap();
+    let MontyObject::Tuple(items) = result else {
+        panic!("expected tuple, got {result:?}");
+    };
+    items
+}
+
+#[test]
+fn folding_preserves_results() {
+    let optimized = run_with(CompileOptions::new());
+    let unoptimized = run_with(CompileOptions::new().optimize(false));
+
+    let expected = [
+        MontyObject::String("abcd".to_owned()),
+        MontyObject::Int(86_401),
+        MontyObject::String("abcd-2".to_owned()),
+    ];
+    assert_eq!(optimized[..3], expected);
+    assert_eq!(unoptimized[..3], expected);
+}
+
+#[test]
+fn folded_string_concatenation_does_not_allocate() {
+    let optimized = run_with(CompileOptions::new());
+    let unoptimized = run_with(CompileOptions::new().optimize(false));
+
+    let (MontyObject::Int(optimized_left), MontyObject::Int(unoptimized_left)) = (&optimized[3], &unoptimized[3])
+    else {
+        panic!("expected remaining allocation counts");
+    };
+    // only `'ab' + 'cd'` differs: folded, it is an interned literal rather than a heap string
+    assert_eq!(optimized_left - unoptimized_left, 1);
+}
+
+#[test]
+fn folding_leaves_raising_expressions_to_the_runtime() {
+    let ex = MontyRun::new("1 // 0".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let err = ex.run_no_limits(vec![]).unwrap_err();
+    assert_eq!(err.exc_type(), monty::ExcType::ZeroDivisionError);
+
+    // overflowing i64 is left unfolded so the runtime can promote to a long int
+    let ex = MontyRun::new("2 ** 64".to_owned(), "test.py", vec![], vec![]).unwrap();
+    assert_eq!(
+        ex.run_no_limits(vec![]).unwrap(),
+        MontyObject::BigInt(18_446_744_073_709_551_616_u128.into())
+    );
+}
+
+/// Runs `code` and returns the traceback it raises with.
+fn traceback_with(code: &str, options: CompileOptions) -> Vec<monty::StackFrame> {
+    let ex = MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec![], options).unwrap();
+    ex.run_no_limits(vec![]).unwrap_err().traceback().to_vec()
+}
+
+/// A division spanning lines, which fails when `scale` is 0.
+const MULTI_LINE: &str = r"def total(items, scale):
+    acc = 0
+    for item in items:
+        acc = acc + (
+            item
+            / scale
+        )
+    return acc
+
+total([1, 2], 0)
+";
+
+/// Two loads the optimizer fuses, the second of which fails.
+const FUSED_LOADS: &str = r"def f(a):
+    if a > 5:
+        b = 2
+    return a + b
+
+f(1)
+";
+
+#[test]
+fn optimization_keeps_error_positions() {
+    for code in [MULTI_LINE, FUSED_LOADS] {
+        let optimized = traceback_with(code, CompileOptions::new());
+        let unoptimized = traceback_with(code, CompileOptions::new().optimize(false));
+        assert_eq!(optimized, unoptimized, "{code}");
+    }
+
+    let frames = traceback_with(MULTI_LINE, CompileOptions::new());
+    let division = frames.last().unwrap();
+    assert_eq!((division.start.line, division.end.line), (5, 6));
+    let frames = traceback_with(FUSED_LOADS, CompileOptions::new());
+    let load = frames.last().unwrap();
+    assert_eq!((load.start.line, load.start.column, load.end.column), (4, 16, 17));
+}
@@ -0,0 +1,54 @@
+//! Tests for line coverage reported by `MontyRun::run_coverage`.
+
+use monty::{CoverageReport, MontyException, MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+
+fn run_coverage(code: &str, x: i64) -> (Result<MontyObject, MontyException>, CoverageReport) {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec!["x".to_owned()], vec![]).unwrap();
+    runner.run_coverage(vec![MontyObject::Int(x)], NoLimitTracker, &mut PrintWriter::Stdout)
+}
+
+const CODE: &str = r"
+def sign(n):
+    if n < 0:
+        return 'negative'
+    return 'positive'
+
+# a comment
+result = sign(x)
+result
+";
+
+#[test]
+fn coverage_reports_branch_that_never_ran() {
+    let (result, report) = run_coverage(CODE, 5);
+    assert_eq!(result.unwrap(), MontyObject::String("positive".to_owned()));
+
+    assert_eq!(report.executable_lines(), [2, 3, 4, 5, 8, 9]);
+    assert_eq!(report.executed_lines(), [2, 3, 5, 8, 9]);
+    assert_eq!(report.missed_lines(), [4]);
+    assert!(!report.is_executable(7));
+    assert!(!report.is_executed(4));
+    assert_eq!(report.to_string(), "5/6 lines covered (83.3%), missed: 4");
+}
+
+#[test]
+fn coverage_bitmap_is_indexed_by_line() {
+    let (result, report) = run_coverage(CODE, -5);
+    assert_eq!(result.unwrap(), MontyObject::String("negative".to_owned()));
+
+    let bitmap = report.executed_bitmap();
+    assert_eq!(bitmap, [false, true, true, true, false, false, false, true, true]);
+    assert_eq!(report.missed_lines(), [5]);
+}
+
+#[test]
+fn coverage_is_returned_when_the_run_fails() {
+    let code = r"
+y = 1 // x
+z = y + 1
+";
+    let (result, report) = run_coverage(code, 0);
+    assert!(result.is_err());
+    assert_eq!(report.missed_lines(), [3]);
+    assert!(report.percent_covered() < 100.0);
+}
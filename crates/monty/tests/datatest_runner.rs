@@ -1518,6 +1518,15 @@ fn run_iter_loop(exec: MontyRun) -> Result<MontyObject, MontyException> {
                 let result = dispatch_os_call(function, &args, &kwargs);
                 progress = state.run(result, &mut PrintWriter::Stdout)?;
             }
+            RunProgress::Paused(state) => {
+                progress = state.run(&mut PrintWriter::Stdout)?;
+            }
+            RunProgress::Breakpoint(state) => {
+                progress = state.resume(&mut PrintWriter::Stdout)?;
+            }
+            RunProgress::Emit { state, .. } | RunProgress::Sleep { state, .. } => {
+                progress = state.run(MontyObject::None, &mut PrintWriter::Stdout)?;
+            }
         }
     }
 }
@@ -0,0 +1,108 @@
+//! Tests for line breakpoints started with `MontyRun::start_with_breakpoints`.
+
+use monty::{BreakpointSnapshot, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+
+const CODE: &str = r"
+def double(n):
+    doubled = n * 2
+    return doubled
+
+a = 1
+b = double(a)
+c = a + b
+c
+";
+
+fn start(code: &str, breakpoints: &[u16]) -> RunProgress<NoLimitTracker> {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    runner
+        .start_with_breakpoints(
+            vec![],
+            NoLimitTracker,
+            breakpoints.iter().copied(),
+            &mut PrintWriter::Stdout,
+        )
+        .unwrap()
+}
+
+fn breakpoint(progress: RunProgress<NoLimitTracker>) -> BreakpointSnapshot<NoLimitTracker> {
+    progress.into_breakpoint().expect("expected a breakpoint")
+}
+
+#[test]
+fn breakpoint_exposes_line_and_locals() {
+    let state = breakpoint(start(CODE, &[3]));
+    assert_eq!(state.line(), 3);
+    assert_eq!(state.function_name(), "double");
+    // `doubled` isn't assigned yet
+    assert_eq!(state.locals(), [("n".to_owned(), MontyObject::Int(1))]);
+    assert_eq!(state.breakpoints(), [3]);
+}
+
+#[test]
+fn resume_runs_to_next_breakpoint_then_completes() {
+    let state = breakpoint(start(CODE, &[6, 8]));
+    assert_eq!(state.line(), 6);
+    assert_eq!(state.function_name(), "<module>");
+    // `double` is defined, `a` isn't yet
+    let names: Vec<String> = state.locals().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["double"]);
+
+    let state = breakpoint(state.resume(&mut PrintWriter::Stdout).unwrap());
+    assert_eq!(state.line(), 8);
+    let locals = state.locals();
+    assert!(locals.contains(&("a".to_owned(), MontyObject::Int(1))));
+    assert!(locals.contains(&("b".to_owned(), MontyObject::Int(2))));
+
+    let progress = state.resume(&mut PrintWriter::Stdout).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(3)));
+}
+
+#[test]
+fn step_stops_at_every_new_line() {
+    let mut state = breakpoint(start(CODE, &[7]));
+    let mut lines = vec![state.line()];
+    loop {
+        match state.step(&mut PrintWriter::Stdout).unwrap() {
+            RunProgress::Breakpoint(next) => {
+                lines.push(next.line());
+                state = next;
+            }
+            RunProgress::Complete(value) => {
+                assert_eq!(value, MontyObject::Int(3));
+                break;
+            }
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    }
+    assert_eq!(lines, [7, 3, 4, 8, 9]);
+}
+
+#[test]
+fn call_returning_to_breakpoint_line_does_not_stop_again() {
+    let state = breakpoint(start(CODE, &[7]));
+    assert_eq!(state.line(), 7);
+    let progress = state.resume(&mut PrintWriter::Stdout).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(3)));
+}
+
+#[test]
+fn breakpoints_can_be_changed_and_survive_dump_and_load() {
+    let mut state = breakpoint(start(CODE, &[6]));
+    state.set_breakpoints([4]);
+
+    let bytes = RunProgress::Breakpoint(state).dump().unwrap();
+    let state = breakpoint(RunProgress::load(&bytes).unwrap());
+    assert_eq!(state.line(), 6);
+    assert_eq!(state.breakpoints(), [4]);
+
+    let state = breakpoint(state.resume(&mut PrintWriter::Stdout).unwrap());
+    assert_eq!(state.line(), 4);
+    assert_eq!(
+        state.locals(),
+        [
+            ("n".to_owned(), MontyObject::Int(1)),
+            ("doubled".to_owned(), MontyObject::Int(2))
+        ]
+    );
+}
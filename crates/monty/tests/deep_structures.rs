@@ -0,0 +1,99 @@
+//! Tests for `==`, `repr()` and freeing on deeply nested lists and tuples, which are walked
+//! without recursion and limited by `ResourceLimits::max_data_depth`.
+
+use monty::{ExcType, LimitedTracker, MontyException, MontyObject, MontyRun, PrintWriter, ResourceLimits};
+
+/// Builds `a`, `b` and `c` as lists nested `depth` deep, `c` differing from the others at
+/// the innermost level, followed by `code`.
+fn nested(depth: usize, code: &str) -> String {
+    format!("a = []\nb = []\nc = [1]\nfor _ in range({depth}):\n    a = [a]\n    b = [b]\n    c = [c]\n{code}")
+}
+
+fn run(code: &str) -> MontyObject {
+    let run = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    run.run_no_limits(vec![]).unwrap()
+}
+
+fn run_limited(code: &str, limits: ResourceLimits) -> Result<MontyObject, MontyException> {
+    let run = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    run.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Disabled)
+}
+
+#[test]
+fn comparing_deeply_nested_lists() {
+    let result = run(&nested(10_000, "(a == b, a == c, a != c)"));
+    let expected = MontyObject::Tuple(vec![
+        MontyObject::Bool(true),
+        MontyObject::Bool(false),
+        MontyObject::Bool(true),
+    ]);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn comparing_deeply_nested_tuples() {
+    let code = "a = ()\nb = ()\nfor i in range(10_000):\n    a = (i, a)\n    b = (i, b)\n(a == b, a == (0, b))";
+    let expected = MontyObject::Tuple(vec![MontyObject::Bool(true), MontyObject::Bool(false)]);
+    assert_eq!(run(code), expected);
+}
+
+#[test]
+fn repr_of_deeply_nested_lists() {
+    let result = run(&nested(10_000, "s = repr(a)\n(len(s), s[:3], s[-3:])"));
+    let expected = MontyObject::Tuple(vec![
+        MontyObject::Int(20_002),
+        MontyObject::from("[[["),
+        MontyObject::from("]]]"),
+    ]);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn freeing_deeply_nested_lists() {
+    let code = "a = []\nfor _ in range(100_000):\n    a = [a]\na = None\n'freed'";
+    assert_eq!(run(code), MontyObject::from("freed"));
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn comparing_past_the_data_depth_limit_raises_recursion_error() {
+    let limits = ResourceLimits::new().max_data_depth(100);
+    let err = run_limited(&nested(200, "a == b"), limits.clone()).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::RecursionError);
+    assert_eq!(err.message(), Some("maximum recursion depth exceeded"));
+
+    let result = run_limited(&nested(50, "a == b"), limits).unwrap();
+    assert_eq!(result, MontyObject::Bool(true));
+}
+
+#[test]
+fn repr_past_the_data_depth_limit_is_truncated() {
+    let limits = ResourceLimits::new().max_data_depth(100);
+    let result = run_limited(&nested(200, "s = repr(a)\n(s.count('['), '...' in s)"), limits).unwrap();
+    let expected = MontyObject::Tuple(vec![MontyObject::Int(100), MontyObject::Bool(true)]);
+    assert_eq!(result, expected);
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn comparing_lists_containing_themselves_raises_recursion_error() {
+    let code = "a = []\na.append(a)\nb = []\nb.append(b)\na == b";
+    let err = run_limited(code, ResourceLimits::new().max_data_depth(1_000)).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::RecursionError);
+}
+
+#[test]
+fn lists_containing_themselves_repr_as_in_cpython() {
+    let code = "a = [1]\na.append(a)\nb = (a, [a])\n(repr(a), repr(b))";
+    let expected = MontyObject::Tuple(vec![
+        MontyObject::from("[1, [...]]"),
+        MontyObject::from("([1, [...]], [[1, [...]]])"),
+    ]);
+    assert_eq!(run(code), expected);
+}
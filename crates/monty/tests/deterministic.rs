@@ -0,0 +1,107 @@
+//! Tests for deterministic runs: frozen time, stable compiled code and byte-identical snapshots.
+
+use std::time::Duration;
+
+use monty::{MontyObject, MontyRun, PrintWriter, ResourceLimits, RunProgress};
+
+/// Captures several variables in a closure, so compiling it assigns cell and free variable
+/// slots, then waits on three external calls at once, so the snapshot holds several
+/// pending calls.
+const CLOSURES_AND_GATHER: &str = r"
+import asyncio
+import time
+
+def make(alpha, beta, gamma):
+    delta = alpha + beta
+    epsilon = gamma * 2
+    zeta, eta, theta = 1, 2, 3
+    def inner():
+        return alpha + beta + gamma + delta + epsilon + zeta + eta + theta
+    return inner
+
+started = time.time()
+total = make(1, 2, 3)()
+
+async def main():
+    a, b, c = await asyncio.gather(fetch(1), fetch(2), fetch(3))
+    return a + b + c + total
+
+await main()
+";
+
+/// Runs `CLOSURES_AND_GATHER` deterministically until it waits on its external calls and
+/// returns the dumped snapshot.
+fn snapshot_waiting_on_gather() -> Vec<u8> {
+    let mut run = MontyRun::builder(CLOSURES_AND_GATHER)
+        .external_functions(["fetch"])
+        .limits(ResourceLimits::new().max_duration(Duration::from_secs(60)))
+        .deterministic()
+        .build()
+        .unwrap();
+    let mut progress = run.start(vec![]).unwrap();
+    while let RunProgress::FunctionCall { state, .. } = progress {
+        progress = state.run_pending(&mut PrintWriter::Disabled).unwrap();
+    }
+    assert!(matches!(progress, RunProgress::ResolveFutures(_)), "{progress:?}");
+    progress.dump().unwrap()
+}
+
+#[test]
+fn time_is_frozen() {
+    let mut run = MontyRun::builder("import time\n(time.time(), time.monotonic(), time.perf_counter())")
+        .deterministic()
+        .build()
+        .unwrap();
+    let frozen = MontyObject::Tuple(vec![
+        MontyObject::Float(0.0),
+        MontyObject::Float(0.0),
+        MontyObject::Float(0.0),
+    ]);
+    assert_eq!(run.run(vec![]), Ok(frozen.clone()));
+    assert_eq!(run.run(vec![]), Ok(frozen));
+}
+
+#[test]
+fn resources_hide_the_time_budget() {
+    let code = "budget = resources()\n(budget['time'], budget['allocations'] is None)";
+    let mut run = MontyRun::builder(code)
+        .limits(
+            ResourceLimits::new()
+                .max_duration(Duration::from_secs(60))
+                .max_allocations(1000),
+        )
+        .deterministic()
+        .build()
+        .unwrap();
+    assert_eq!(
+        run.run(vec![]),
+        Ok(MontyObject::Tuple(vec![MontyObject::None, MontyObject::Bool(false)]))
+    );
+}
+
+#[test]
+fn compiling_twice_gives_identical_code() {
+    let compile = || {
+        MontyRun::new(
+            CLOSURES_AND_GATHER.to_owned(),
+            "main.py",
+            vec![],
+            vec!["fetch".to_owned()],
+        )
+        .unwrap()
+        .dump_code()
+        .unwrap()
+    };
+    let first = compile();
+    for _ in 0..5 {
+        assert_eq!(compile(), first);
+    }
+}
+
+#[test]
+fn snapshots_are_byte_identical() {
+    let first = snapshot_waiting_on_gather();
+    for _ in 0..5 {
+        assert_eq!(snapshot_waiting_on_gather(), first);
+    }
+}
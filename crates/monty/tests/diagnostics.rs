@@ -0,0 +1,62 @@
+//! Tests for collecting every error in a script with `MontyRun::diagnostics`.
+
+use monty::{Diagnostic, ExcType, MontyRun, Severity};
+
+fn diagnostics(code: &str) -> Vec<Diagnostic> {
+    MontyRun::diagnostics(code, "test.py", vec![], vec![])
+}
+
+#[test]
+fn valid_code_has_no_diagnostics() {
+    assert_eq!(diagnostics("x = 1\nx + 1"), []);
+}
+
+#[test]
+fn unsupported_statements_are_all_reported() {
+    let code = r"
+class A:
+    pass
+
+def f():
+    yield 1
+    return 2
+
+x = 1j
+y = 2
+";
+    let found = diagnostics(code);
+    let lines: Vec<u16> = found.iter().map(|d| d.start.line).collect();
+    assert_eq!(lines, [2, 6, 9]);
+    for diagnostic in &found {
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.exc_type, Some(ExcType::NotImplementedError));
+    }
+    assert_eq!(&code[found[2].span.clone()], "1j");
+}
+
+#[test]
+fn compile_errors_are_reported_with_a_fix() {
+    let code = "x = 1\nbreak\n";
+    let found = diagnostics(code);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].exc_type, Some(ExcType::SyntaxError));
+    assert_eq!(found[0].message, "'break' outside loop");
+    let fix = found[0].fix.as_ref().unwrap();
+    assert_eq!(&code[fix.span.clone()], "break");
+    assert_eq!(fix.replacement, "");
+}
+
+#[test]
+fn unreachable_code_is_a_warning() {
+    let code = r"
+def f():
+    return 1
+    x = 2
+    x
+";
+    let found = diagnostics(code);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].severity, Severity::Warning);
+    assert_eq!(found[0].exc_type, None);
+    assert_eq!(&code[found[0].span.clone()], "x = 2\n    x");
+}
@@ -0,0 +1,119 @@
+//! Tests for the bytecode disassembler exposed by `MontyRun::disassemble`.
+
+use monty::{CodeDisassembly, CompileOptions, Instruction, MontyRun};
+
+fn disassembly(code: &str, optimize: bool) -> Vec<CodeDisassembly> {
+    let options = CompileOptions::new().optimize(optimize);
+    let runner = MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec![], options).unwrap();
+    runner.disassembly()
+}
+
+fn find<'a>(code: &'a CodeDisassembly, opname: &str) -> &'a Instruction {
+    code.instructions
+        .iter()
+        .find(|instr| instr.opname == opname)
+        .unwrap_or_else(|| panic!("no {opname} in {code}"))
+}
+
+#[test]
+fn lists_module_then_functions() {
+    let codes = disassembly("def f():\n    return 1\n\ndef g():\n    return 2\n\nf() + g()", true);
+    let names: Vec<&str> = codes.iter().map(|code| code.name.as_str()).collect();
+    assert_eq!(names, ["<module>", "f", "g"]);
+    assert_eq!(find(&codes[0], "MakeFunction").argrepr, "f");
+}
+
+#[test]
+fn resolves_constants_and_names() {
+    let codes = disassembly("x = 1000\ny = 'hi'\nx", true);
+    let module = &codes[0];
+
+    let load = find(module, "LoadConst");
+    assert_eq!(load.argrepr, "1000");
+    assert_eq!(load.line, Some(1));
+    let store = find(module, "StoreLocal");
+    assert_eq!(store.argrepr, "x");
+
+    let strings: Vec<&str> = module
+        .instructions
+        .iter()
+        .filter(|instr| instr.opname == "LoadConst")
+        .map(|instr| instr.argrepr.as_str())
+        .collect();
+    assert_eq!(strings, ["1000", "'hi'"]);
+}
+
+#[test]
+fn marks_jumps_and_lines() {
+    let codes = disassembly("i = 0\nwhile i < 3:\n    i += 1\ni", false);
+    let module = &codes[0];
+
+    let jumps: Vec<&Instruction> = module
+        .instructions
+        .iter()
+        .filter(|instr| instr.jump_target.is_some())
+        .collect();
+    assert!(!jumps.is_empty());
+    for jump in jumps {
+        let target = jump.jump_target.unwrap();
+        let landing = module.instructions.iter().find(|instr| instr.offset == target).unwrap();
+        assert!(landing.is_jump_target);
+    }
+    assert_eq!(find(module, "InplaceAdd").line, Some(3));
+}
+
+#[test]
+fn while_true_skips_the_condition_when_optimized() {
+    let code = "i = 0\nwhile True:\n    i += 1\n    if i == 3:\n        break\nelse:\n    i = -1\ni";
+    let has_load_true =
+        |codes: &[CodeDisassembly]| codes[0].instructions.iter().any(|instr| instr.opname == "LoadTrue");
+
+    assert!(!has_load_true(&disassembly(code, true)));
+    assert!(has_load_true(&disassembly(code, false)));
+}
+
+#[test]
+fn generator_reductions_build_no_list() {
+    let module = &disassembly("sum(x * x for x in range(3))\nany(x > 1 for x in range(3))", true)[0];
+    let builds_list = module
+        .instructions
+        .iter()
+        .any(|instr| instr.opname.starts_with("BuildList"));
+
+    assert!(!builds_list, "{module}");
+    find(module, "SumAdd");
+    assert!(
+        module
+            .instructions
+            .iter()
+            .all(|instr| instr.opname != "CallBuiltinFunction")
+    );
+}
+
+#[test]
+fn shows_superinstructions_when_optimized() {
+    let code = "def add(a, b):\n    return a + b\n\nadd(1, 2)";
+
+    let optimized = disassembly(code, true);
+    assert_eq!(find(&optimized[1], "LoadLocalPair").argrepr, "a, b");
+
+    let plain = disassembly(code, false);
+    assert!(
+        plain[1]
+            .instructions
+            .iter()
+            .all(|instr| instr.opname != "LoadLocalPair")
+    );
+    assert_eq!(find(&plain[1], "LoadLocal0").argrepr, "a");
+}
+
+#[test]
+fn text_listing() {
+    let runner = MontyRun::new("x = 1000\nx".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let text = runner.disassemble();
+    assert!(text.starts_with("Disassembly of <module>:\n"), "{text}");
+    let first = text.lines().nth(1).unwrap();
+    assert!(first.starts_with("   1"), "{text}");
+    assert!(first.contains("LoadConst"), "{text}");
+    assert!(first.ends_with("(1000)"), "{text}");
+}
@@ -0,0 +1,128 @@
+//! Tests for streaming values to the host with the `emit()` builtin.
+
+use monty::{ExcType, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+
+fn start(code: &str) -> RunProgress<NoLimitTracker> {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    runner.start(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap()
+}
+
+/// Drives a run to completion, collecting every emitted value.
+fn collect_emitted(mut progress: RunProgress<NoLimitTracker>) -> (Vec<MontyObject>, MontyObject) {
+    let mut emitted = Vec::new();
+    loop {
+        match progress {
+            RunProgress::Complete(value) => return (emitted, value),
+            RunProgress::Emit { value, state } => {
+                emitted.push(value);
+                progress = state.run(MontyObject::None, &mut PrintWriter::Stdout).unwrap();
+            }
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn emit_streams_values_in_order() {
+    let code = r"
+for i in range(3):
+    emit(i * 10)
+'done'
+";
+    let (emitted, result) = collect_emitted(start(code));
+    assert_eq!(
+        emitted,
+        vec![MontyObject::Int(0), MontyObject::Int(10), MontyObject::Int(20)]
+    );
+    assert_eq!(result, MontyObject::String("done".to_owned()));
+}
+
+#[test]
+fn emit_streams_heap_values() {
+    let code = r"
+for row in [[1, 2], [3, 4]]:
+    emit({'total': sum(row)})
+";
+    let (emitted, _) = collect_emitted(start(code));
+    assert_eq!(emitted.len(), 2);
+    assert_eq!(
+        emitted[1],
+        MontyObject::Dict(vec![(MontyObject::String("total".to_owned()), MontyObject::Int(7))].into())
+    );
+}
+
+#[test]
+fn emit_returns_host_value() {
+    let code = r"
+ack = emit('row')
+ack + 1
+";
+    let (value, state) = start(code).into_emit().expect("expected Emit");
+    assert_eq!(value, MontyObject::String("row".to_owned()));
+
+    let progress = state.run(MontyObject::Int(41), &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(42)));
+}
+
+#[test]
+fn emit_inside_function() {
+    let code = r"
+def produce(n):
+    for i in range(n):
+        emit(i)
+    return n
+
+produce(2)
+";
+    let (emitted, result) = collect_emitted(start(code));
+    assert_eq!(emitted, vec![MontyObject::Int(0), MontyObject::Int(1)]);
+    assert_eq!(result, MontyObject::Int(2));
+}
+
+#[test]
+fn emit_progress_dump_load_roundtrip() {
+    let progress = start("emit(1)\nemit(2)\n3");
+    let bytes = progress.dump().unwrap();
+    let loaded: RunProgress<NoLimitTracker> = RunProgress::load(&bytes).unwrap();
+
+    let (value, state) = loaded.into_emit().expect("expected Emit");
+    assert_eq!(value, MontyObject::Int(1));
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Stdout).unwrap();
+    let (emitted, result) = collect_emitted(progress);
+    assert_eq!(emitted, vec![MontyObject::Int(2)]);
+    assert_eq!(result, MontyObject::Int(3));
+}
+
+#[test]
+fn emit_not_supported_by_standard_execution() {
+    let runner = MontyRun::new("emit(1)".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let err = runner.run_no_limits(vec![]).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NotImplementedError);
+}
+
+#[test]
+fn emit_called_indirectly_raises() {
+    let code = r"
+try:
+    list(map(emit, [1]))
+except NotImplementedError as e:
+    result = str(e)
+result
+";
+    let progress = start(code);
+    assert_eq!(
+        progress.into_complete(),
+        Some(MontyObject::String(
+            "emit() can only be called directly from Python code".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn emit_requires_one_argument() {
+    let runner = MontyRun::new("emit()".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let err = runner
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+}
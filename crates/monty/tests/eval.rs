@@ -0,0 +1,74 @@
+//! Tests for single-expression evaluation with `eval_expr`.
+
+use monty::{EvalOptions, ExcType, MontyObject, NoLimitTracker, eval_expr, eval_expr_with_options};
+
+fn bindings() -> Vec<(String, MontyObject)> {
+    vec![
+        ("price".to_owned(), MontyObject::Float(2.5)),
+        ("qty".to_owned(), MontyObject::Int(4)),
+        (
+            "tags".to_owned(),
+            MontyObject::List(vec![MontyObject::String("a".to_owned())]),
+        ),
+    ]
+}
+
+#[test]
+fn eval_evaluates_expression_against_bindings() {
+    let result = eval_expr("price * qty if qty > 0 and 'a' in tags else 0", bindings()).unwrap();
+    assert_eq!(result, MontyObject::Float(10.0));
+
+    let result = eval_expr("f'{qty} x {price}'", bindings()).unwrap();
+    assert_eq!(result, MontyObject::String("4 x 2.5".to_owned()));
+
+    assert_eq!(eval_expr("None", bindings()).unwrap(), MontyObject::None);
+}
+
+#[test]
+fn eval_rejects_statements() {
+    for src in ["x = 1", "qty\nprice", "for i in tags: pass", "import os", ""] {
+        let err = eval_expr(src, bindings()).unwrap_err();
+        assert_eq!(err.exc_type(), ExcType::SyntaxError, "{src}");
+    }
+}
+
+#[test]
+fn eval_rejects_calls_and_comprehensions_by_default() {
+    for (src, message) in [
+        ("len(tags)", "function calls are not allowed in this expression"),
+        ("tags.count('a')", "function calls are not allowed in this expression"),
+        ("(lambda: 1)", "lambdas are not allowed in this expression"),
+        ("[t for t in tags]", "comprehensions are not allowed in this expression"),
+        ("qty + sum(tags)", "function calls are not allowed in this expression"),
+    ] {
+        let err = eval_expr(src, bindings()).unwrap_err();
+        assert_eq!(err.exc_type(), ExcType::SyntaxError, "{src}");
+        assert_eq!(err.message(), Some(message), "{src}");
+    }
+}
+
+#[test]
+fn eval_options_allow_calls_and_comprehensions() {
+    let options = EvalOptions::new().allow_calls(true).allow_comprehensions(true);
+    let result = eval_expr_with_options("len([t for t in tags if t])", bindings(), options, NoLimitTracker).unwrap();
+    assert_eq!(result, MontyObject::Int(1));
+}
+
+#[test]
+fn eval_reports_runtime_errors() {
+    let err = eval_expr("qty // 0", bindings()).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ZeroDivisionError);
+
+    let err = eval_expr("missing + 1", bindings()).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn eval_runs_under_default_limits() {
+    let err = eval_expr("'x' * 10 ** 9", bindings()).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::MemoryError);
+}
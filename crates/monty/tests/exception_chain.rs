@@ -0,0 +1,113 @@
+//! Tests for the structured exception details exposed to the host: args, chained
+//! causes and contexts, and traceback frames.
+
+use monty::{ExcType, MontyException, MontyObject, MontyRun};
+
+fn run_error(code: &str) -> MontyException {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    runner.run_no_limits(vec![]).unwrap_err()
+}
+
+#[test]
+fn raising_while_handling_sets_context() {
+    let err = run_error(
+        r"
+try:
+    1 / 0
+except ZeroDivisionError:
+    raise ValueError('bad')
+",
+    );
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert!(err.cause().is_none());
+    let context = err.context().unwrap();
+    assert_eq!(context.exc_type(), ExcType::ZeroDivisionError);
+    assert_eq!(context.traceback()[0].start.line, 3);
+}
+
+#[test]
+fn raise_from_sets_cause_and_hides_context() {
+    let err = run_error(
+        r"
+try:
+    {}['key']
+except KeyError as e:
+    raise ValueError('bad') from e
+",
+    );
+    let cause = err.cause().unwrap();
+    assert_eq!(cause.exc_type(), ExcType::KeyError);
+    assert!(err.context().is_none());
+}
+
+#[test]
+fn raise_from_none_suppresses_the_chain() {
+    let err = run_error(
+        r"
+try:
+    1 / 0
+except ZeroDivisionError:
+    raise ValueError from None
+",
+    );
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert!(err.cause().is_none());
+    assert!(err.context().is_none());
+}
+
+#[test]
+fn returning_from_a_handler_ends_the_handling() {
+    let err = run_error(
+        r"
+def swallow():
+    try:
+        1 / 0
+    except ZeroDivisionError:
+        return 'swallowed'
+    finally:
+        pass
+
+swallow()
+raise ValueError('bad')
+",
+    );
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert!(err.context().is_none());
+}
+
+#[test]
+fn raise_from_a_non_exception_is_a_type_error() {
+    let err = run_error("raise ValueError('bad') from 1");
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(err.message(), Some("exception causes must derive from BaseException"));
+}
+
+#[test]
+fn args_holds_the_message() {
+    let err = run_error("raise ValueError('bad')");
+    assert_eq!(
+        err.args(),
+        MontyObject::Tuple(vec![MontyObject::String("bad".to_owned())])
+    );
+    let err = run_error("raise ValueError");
+    assert_eq!(err.args(), MontyObject::Tuple(vec![]));
+}
+
+#[test]
+fn traceback_lists_frames_outermost_first() {
+    let err = run_error("def f():\n    raise ValueError('bad')\n\nf()\n");
+    let frames: Vec<(Option<&str>, &str, u16, u16)> = err
+        .traceback()
+        .iter()
+        .map(|frame| {
+            (
+                frame.frame_name.as_deref(),
+                frame.filename.as_str(),
+                frame.start.line,
+                frame.start.column,
+            )
+        })
+        .collect();
+    assert_eq!(frames[1..], [(Some("f"), "test.py", 2, 5)]);
+    assert_eq!(frames[0].2, 4);
+}
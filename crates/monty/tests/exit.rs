@@ -0,0 +1,107 @@
+//! Tests for surfacing `sys.exit()` to the host as `Exit::SystemExit`.
+
+use monty::{ExcType, Exit, MontyObject, MontyRun};
+
+fn run(code: &str) -> Result<Exit, monty::MontyException> {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    Exit::from_result(runner.run_no_limits(vec![]))
+}
+
+#[test]
+fn completed_run() {
+    let exit = run("1 + 2").unwrap();
+    assert_eq!(exit, Exit::Complete(MontyObject::Int(3)));
+    assert_eq!(exit.status(), 0);
+}
+
+#[test]
+fn exit_with_int_code() {
+    let exit = run("import sys\nsys.exit(3)\n'unreachable'").unwrap();
+    assert_eq!(exit, Exit::SystemExit(MontyObject::Int(3)));
+    assert_eq!(exit.status(), 3);
+}
+
+#[test]
+fn exit_without_code() {
+    let exit = run("import sys\nsys.exit()").unwrap();
+    assert_eq!(exit, Exit::SystemExit(MontyObject::None));
+    assert_eq!(exit.status(), 0);
+}
+
+#[test]
+fn exit_with_message() {
+    let exit = run("raise SystemExit('fatal: bad config')").unwrap();
+    assert_eq!(
+        exit,
+        Exit::SystemExit(MontyObject::String("fatal: bad config".to_owned()))
+    );
+    assert_eq!(exit.status(), 1);
+}
+
+#[test]
+fn exit_with_string_of_digits_keeps_it_a_string() {
+    let exit = run("import sys\nsys.exit('0')").unwrap();
+    assert_eq!(exit, Exit::SystemExit(MontyObject::String("0".to_owned())));
+    assert_eq!(exit.status(), 1);
+    assert_eq!(exit.message().as_deref(), Some("0"));
+}
+
+#[test]
+fn exit_codes_keep_their_type() {
+    let exit = run("import sys\nsys.exit([1, 'a'])").unwrap();
+    let code = MontyObject::List(vec![MontyObject::Int(1), MontyObject::String("a".to_owned())]);
+    assert_eq!(exit, Exit::SystemExit(code));
+    assert_eq!(exit.status(), 1);
+    assert_eq!(exit.message().as_deref(), Some("[1, 'a']"));
+
+    let exit = run("raise SystemExit(True)").unwrap();
+    assert_eq!(exit, Exit::SystemExit(MontyObject::Bool(true)));
+    assert_eq!(exit.status(), 1);
+    assert_eq!(exit.message(), None);
+}
+
+#[test]
+fn reraised_exit_keeps_its_code() {
+    let code = "import sys\ntry:\n    sys.exit(7)\nexcept SystemExit as e:\n    raise e";
+    let exit = run(code).unwrap();
+    assert_eq!(exit, Exit::SystemExit(MontyObject::Int(7)));
+    assert_eq!(exit.status(), 7);
+    assert_eq!(exit.message(), None);
+}
+
+#[test]
+fn exit_from_nested_function() {
+    let code = r"
+from sys import exit
+
+def check(n):
+    if n > 2:
+        exit(n)
+    return n
+
+[check(i) for i in range(5)]
+";
+    assert_eq!(run(code).unwrap(), Exit::SystemExit(MontyObject::Int(3)));
+}
+
+#[test]
+fn caught_exit_does_not_end_the_run() {
+    let code = r"
+import sys
+try:
+    sys.exit(4)
+except SystemExit as e:
+    result = 'caught ' + str(e)
+result
+";
+    assert_eq!(
+        run(code).unwrap(),
+        Exit::Complete(MontyObject::String("caught 4".to_owned()))
+    );
+}
+
+#[test]
+fn other_exceptions_are_still_errors() {
+    let err = run("1 / 0").unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ZeroDivisionError);
+}
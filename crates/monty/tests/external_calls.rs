@@ -0,0 +1,101 @@
+//! Tests for `MontyRun::external_calls`, the static listing of external function call sites.
+
+use monty::{CodeLoc, ExternalCallSite, MontyRun};
+
+fn runner(code: &str, externals: &[&str]) -> MontyRun {
+    let externals = externals.iter().map(|&name| name.to_owned()).collect();
+    MontyRun::new(code.to_owned(), "test.py", vec![], externals).unwrap()
+}
+
+#[test]
+fn lists_call_sites_with_positions_and_arg_counts() {
+    let code = "fetch('a')\nx = fetch('b', timeout=2)\n";
+    let run = runner(code, &["fetch"]);
+    let calls = run.external_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "fetch");
+    assert_eq!(
+        calls[0].call_sites,
+        vec![
+            ExternalCallSite {
+                start: CodeLoc { line: 1, column: 1 },
+                end: CodeLoc { line: 1, column: 11 },
+                arg_count: Some(1),
+            },
+            ExternalCallSite {
+                start: CodeLoc { line: 2, column: 5 },
+                end: CodeLoc { line: 2, column: 26 },
+                arg_count: Some(2),
+            },
+        ]
+    );
+    assert_eq!(calls[0].max_args(), Some(2));
+}
+
+#[test]
+fn unreferenced_externals_are_omitted() {
+    let run = runner("b()\na()", &["a", "unused", "b"]);
+    let names: Vec<&str> = run.external_calls().iter().map(|usage| usage.name.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn finds_calls_in_functions_lambdas_and_comprehensions() {
+    let code = r"
+def f(x, default=ext(1)):
+    return ext(x, 2, 3)
+
+g = lambda: ext()
+[ext(i) for i in range(3)]
+";
+    let run = runner(code, &["ext"]);
+    let calls = run.external_calls();
+    assert_eq!(calls.len(), 1);
+    let counts: Vec<Option<usize>> = calls[0].call_sites.iter().map(|site| site.arg_count).collect();
+    assert_eq!(counts, [Some(1), Some(3), Some(0), Some(1)]);
+    assert_eq!(calls[0].max_args(), Some(3));
+}
+
+#[test]
+fn local_names_shadowing_an_external_are_ignored() {
+    let code = r"
+def f(ext):
+    return ext(1)
+
+def g():
+    ext = len
+    return ext('abc')
+";
+    let run = runner(code, &["ext"]);
+    assert!(run.external_calls().is_empty());
+}
+
+#[test]
+fn unknown_arg_counts() {
+    let code = r"
+args = [1, 2]
+ext(*args)
+ext(**{'a': 1})
+list(map(ext, args))
+";
+    let run = runner(code, &["ext"]);
+    let calls = run.external_calls();
+    let counts: Vec<Option<usize>> = calls[0].call_sites.iter().map(|site| site.arg_count).collect();
+    assert_eq!(counts, [None, None, None]);
+    assert_eq!(calls[0].max_args(), None);
+}
+
+#[test]
+fn calls_in_dead_branches_are_reported() {
+    let run = runner("if False:\n    ext(1, 2)\n", &["ext"]);
+    let calls = run.external_calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].max_args(), Some(2));
+}
+
+#[test]
+fn external_calls_survive_dump_and_load() {
+    let run = runner("ext(1)", &["ext"]);
+    let loaded = MontyRun::load(&run.dump().unwrap()).unwrap();
+    assert_eq!(loaded.external_calls(), run.external_calls());
+}
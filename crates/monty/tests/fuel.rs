@@ -0,0 +1,133 @@
+//! Tests for fuel-limited stepped execution via `MontyRun::start_fuel` and `PausedSnapshot`.
+
+use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+
+const LOOP_CODE: &str = r"
+total = 0
+for i in range(1000):
+    total += i
+total
+";
+
+/// Drives a fuel-limited run to completion, returning the result and the number of pauses.
+fn run_in_slices(progress: RunProgress<NoLimitTracker>, fuel: u64) -> (MontyObject, usize) {
+    let mut progress = progress;
+    let mut pauses = 0;
+    loop {
+        match progress {
+            RunProgress::Complete(value) => return (value, pauses),
+            RunProgress::Paused(state) => {
+                pauses += 1;
+                progress = state.run_fuel(fuel, &mut PrintWriter::Stdout).unwrap();
+            }
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn fuel_pauses_and_resumes_to_same_result() {
+    let runner = MontyRun::new(LOOP_CODE.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let progress = runner
+        .start_fuel(vec![], NoLimitTracker, 100, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    let (value, pauses) = run_in_slices(progress, 100);
+    assert_eq!(value, MontyObject::Int(499_500));
+    assert!(pauses > 10, "expected many pauses with a small budget, got {pauses}");
+}
+
+#[test]
+fn fuel_pauses_inside_while_true() {
+    let code = "n = 0\nwhile True:\n    n += 1\n    if n == 500:\n        break\nn";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let progress = runner
+        .start_fuel(vec![], NoLimitTracker, 50, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    let (value, pauses) = run_in_slices(progress, 50);
+    assert_eq!(value, MontyObject::Int(500));
+    assert!(pauses > 10, "expected many pauses with a small budget, got {pauses}");
+}
+
+#[test]
+fn fuel_large_budget_completes_without_pausing() {
+    let runner = MontyRun::new("1 + 2".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let progress = runner
+        .start_fuel(vec![], NoLimitTracker, 1_000_000, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(3)));
+}
+
+#[test]
+fn fuel_zero_pauses_before_first_instruction() {
+    let runner = MontyRun::new("1 + 2".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let progress = runner
+        .start_fuel(vec![], NoLimitTracker, 0, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    let state = progress.into_paused().expect("expected Paused");
+    let result = state.run(&mut PrintWriter::Stdout).unwrap();
+    assert_eq!(result.into_complete(), Some(MontyObject::Int(3)));
+}
+
+#[test]
+fn fuel_paused_state_survives_serialization() {
+    let runner = MontyRun::new(LOOP_CODE.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let mut progress = runner
+        .start_fuel(vec![], NoLimitTracker, 50, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    loop {
+        let bytes = progress.dump().unwrap();
+        progress = RunProgress::load(&bytes).unwrap();
+        match progress {
+            RunProgress::Complete(value) => {
+                assert_eq!(value, MontyObject::Int(499_500));
+                break;
+            }
+            RunProgress::Paused(state) => {
+                progress = state.run_fuel(5_000, &mut PrintWriter::Stdout).unwrap();
+            }
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn fuel_carries_across_external_calls() {
+    let code = r"
+x = 0
+for i in range(200):
+    x += 1
+y = ext(x)
+for i in range(200):
+    y += 1
+y
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap();
+    let mut progress = runner
+        .start_fuel(vec![], NoLimitTracker, 300, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    let mut saw_call = false;
+    loop {
+        match progress {
+            RunProgress::Complete(value) => {
+                assert_eq!(value, MontyObject::Int(400));
+                break;
+            }
+            RunProgress::Paused(state) => {
+                progress = state.run_fuel(300, &mut PrintWriter::Stdout).unwrap();
+            }
+            RunProgress::FunctionCall { args, state, .. } => {
+                saw_call = true;
+                assert_eq!(args, vec![MontyObject::Int(200)]);
+                progress = state.run(MontyObject::Int(200), &mut PrintWriter::Stdout).unwrap();
+            }
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    }
+    assert!(saw_call, "external function should have been called");
+}
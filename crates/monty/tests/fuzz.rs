@@ -0,0 +1,87 @@
+//! Tests for the fuzzing entry points and the `Arbitrary` impls behind the `fuzzing` feature.
+#![cfg(feature = "fuzzing")]
+
+use std::time::Duration;
+
+use monty::{
+    MontyRun, NoLimitTracker, PrintWriter, ResourceLimits,
+    fuzz::{
+        FramedPayload, SnapshotTarget,
+        arbitrary::{Arbitrary, Unstructured},
+        fuzz_parse, fuzz_run_with_limits, fuzz_snapshot_load,
+    },
+};
+
+#[test]
+fn arbitrary_limits_are_bounded() {
+    for seed in 0..=u8::MAX {
+        let data: Vec<u8> = (0..64).map(|i| seed.wrapping_mul(31).wrapping_add(i)).collect();
+        let limits = ResourceLimits::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert!(limits.max_duration.is_some_and(|d| d <= Duration::from_millis(100)));
+        assert!(limits.max_memory.is_some_and(|m| m <= 4 * 1024 * 1024));
+        assert!(limits.max_recursion_depth.is_some_and(|r| r <= 200));
+    }
+}
+
+#[test]
+fn arbitrary_limits_from_empty_input() {
+    let limits = ResourceLimits::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert!(limits.max_duration.is_some());
+    assert!(limits.max_memory.is_some());
+}
+
+#[test]
+fn framed_payload_matches_dump() {
+    let runner = MontyRun::new("ext(1) + 1".to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap();
+    let bytes = runner
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap()
+        .dump()
+        .unwrap();
+    let header_len = 4 + 2 + 1 + usize::from(bytes[6]) + 4;
+    let framed = FramedPayload {
+        target: SnapshotTarget::RunProgress,
+        payload: bytes[header_len..].to_vec(),
+    };
+    assert_eq!(framed.to_bytes(), bytes);
+    fuzz_snapshot_load(&framed.to_bytes());
+}
+
+#[test]
+fn snapshot_load_ignores_garbage() {
+    let garbage: Vec<u8> = (0..200u8).map(|i| i.wrapping_mul(37)).collect();
+    fuzz_snapshot_load(&[]);
+    fuzz_snapshot_load(&garbage);
+    for target in [
+        SnapshotTarget::RunProgress,
+        SnapshotTarget::ReplProgress,
+        SnapshotTarget::Code,
+    ] {
+        for len in [0, 1, 7, garbage.len()] {
+            let framed = FramedPayload {
+                target,
+                payload: garbage[..len].to_vec(),
+            };
+            fuzz_snapshot_load(&framed.to_bytes());
+        }
+    }
+}
+
+#[test]
+fn parse_ignores_syntax_errors() {
+    fuzz_parse("def (");
+    fuzz_parse("x = 1\nx +");
+    fuzz_parse("print('ok')");
+}
+
+#[test]
+fn run_with_limits_stops_unbounded_code() {
+    let limits = ResourceLimits::new()
+        .max_duration(Duration::from_millis(50))
+        .max_memory(1024 * 1024);
+    fuzz_run_with_limits(
+        "items = []\nfor i in range(10 ** 9):\n    items.append(str(i))",
+        &limits,
+    );
+    fuzz_run_with_limits("def f():\n    return f()\nf()", &limits);
+}
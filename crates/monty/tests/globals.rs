@@ -0,0 +1,137 @@
+//! Tests for reading final module-level variables with `MontyRun::run_capture_globals`
+//! and seeding them with `MontyRun::with_globals`.
+
+use monty::{ExcType, MontyInstance, MontyObject, MontyRun, NoLimitTracker, Pool, PrintWriter};
+
+#[test]
+fn globals_hold_every_assigned_variable() {
+    let code = r"
+count = len(items)
+doubled = [i * 2 for i in items]
+del items
+never = None if count > 10 else 0
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec!["items".to_owned()], vec![]).unwrap();
+    let items = MontyObject::List(vec![MontyObject::Int(1), MontyObject::Int(2)]);
+    let (result, globals) = runner
+        .run_capture_globals(vec![items], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    assert_eq!(result, MontyObject::None);
+    assert_eq!(globals["count"], MontyObject::Int(2));
+    assert_eq!(
+        globals["doubled"],
+        MontyObject::List(vec![MontyObject::Int(2), MontyObject::Int(4)])
+    );
+    assert_eq!(globals["never"], MontyObject::Int(0));
+    // deleted names are gone
+    assert!(!globals.contains_key("items"));
+}
+
+#[test]
+fn globals_include_inputs_but_not_external_functions() {
+    let code = r"
+def helper():
+    return 1
+
+if x > 0:
+    y = x
+";
+    let runner = MontyRun::new(
+        code.to_owned(),
+        "test.py",
+        vec!["x".to_owned()],
+        vec!["fetch".to_owned()],
+    )
+    .unwrap();
+    let (_, globals) = runner
+        .run_capture_globals(vec![MontyObject::Int(-1)], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap();
+
+    assert_eq!(globals["x"], MontyObject::Int(-1));
+    assert!(globals.contains_key("helper"));
+    // never assigned on this path
+    assert!(!globals.contains_key("y"));
+    assert!(!globals.contains_key("fetch"));
+}
+
+#[test]
+fn failed_run_returns_the_exception() {
+    let runner = MontyRun::new("a = 1\nb = a // 0".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let err = runner
+        .run_capture_globals(vec![], NoLimitTracker, &mut PrintWriter::Stdout)
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ZeroDivisionError);
+}
+
+fn payload() -> MontyObject {
+    MontyObject::dict(vec![
+        (MontyObject::from("name"), MontyObject::from("widget")),
+        (MontyObject::from("qty"), MontyObject::Int(3)),
+    ])
+}
+
+#[test]
+fn injected_globals_are_visible_to_module_code_and_functions() {
+    let code = r"
+order = payload
+def total(price):
+    return order['qty'] * price + fee
+payload['name'] + ': ' + str(total(10))
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![])
+        .unwrap()
+        .with_globals([("payload", payload()), ("fee", MontyObject::Int(1))]);
+    assert_eq!(runner.run_no_limits(vec![]).unwrap(), MontyObject::from("widget: 31"));
+}
+
+#[test]
+fn injected_globals_apply_to_every_run() {
+    let code = "count += 1\ncount";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![])
+        .unwrap()
+        .with_globals([("count", MontyObject::Int(10))]);
+    let print = &mut PrintWriter::Disabled;
+
+    // assignments in one run don't leak into the next
+    assert_eq!(runner.run(vec![], NoLimitTracker, print), Ok(MontyObject::Int(11)));
+    assert_eq!(runner.run(vec![], NoLimitTracker, print), Ok(MontyObject::Int(11)));
+
+    let progress = runner.clone().start(vec![], NoLimitTracker, print).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(11)));
+
+    let mut pool = Pool::new(&runner);
+    assert_eq!(pool.run(vec![], NoLimitTracker, print), Ok(MontyObject::Int(11)));
+    assert_eq!(pool.run(vec![], NoLimitTracker, print), Ok(MontyObject::Int(11)));
+
+    assert!(MontyInstance::new(&runner, vec![], NoLimitTracker, print).is_ok());
+}
+
+#[test]
+fn injected_globals_are_replaced_and_filtered() {
+    let runner = MontyRun::new("[x, y]".to_owned(), "test.py", vec!["x".to_owned()], vec![])
+        .unwrap()
+        .with_globals([
+            ("x", MontyObject::from("ignored, x is an input")),
+            ("y", MontyObject::Int(1)),
+            ("unused", MontyObject::Int(2)),
+        ])
+        .with_globals([("y", MontyObject::Int(3))]);
+    let (result, globals) = runner
+        .run_capture_globals(vec![MontyObject::Int(0)], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(
+        result,
+        MontyObject::List(vec![MontyObject::Int(0), MontyObject::Int(3)])
+    );
+    assert!(!globals.contains_key("unused"));
+}
+
+#[test]
+fn missing_injected_global_is_a_name_error() {
+    let runner = MontyRun::new("payload".to_owned(), "test.py", vec![], vec![])
+        .unwrap()
+        .with_globals([("other", payload())]);
+    let err = runner.run_no_limits(vec![]).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+}
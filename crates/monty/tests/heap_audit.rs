@@ -0,0 +1,73 @@
+//! Tests for auditing the heap after a run with `MontyRun::run_audited`.
+#![cfg(feature = "heap-audit")]
+
+use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+
+fn audit(code: &str) -> (MontyObject, monty::HeapAudit) {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let (result, audit) = runner.run_audited(vec![], NoLimitTracker, &mut PrintWriter::Disabled);
+    (result.unwrap(), audit)
+}
+
+#[test]
+fn audit_counts_objects_reachable_from_globals() {
+    let code = r"
+names = ['a' * 3, 'b' * 3]
+index = {name: len(name) for name in names}
+pair = (names, index)
+len(index)
+";
+    let (result, audit) = audit(code);
+    assert_eq!(result, MontyObject::Int(2));
+    audit.assert_no_leaks();
+    assert_eq!(audit.objects_by_type["list"], 1);
+    assert_eq!(audit.objects_by_type["dict"], 1);
+    assert_eq!(audit.objects_by_type["tuple"], 1);
+    assert!(audit.unreachable.is_empty(), "{audit}");
+    assert_eq!(audit.refcount_total, audit.reference_total);
+}
+
+#[test]
+fn temporaries_are_freed_before_the_audit() {
+    let code = r"
+def build(n):
+    parts = [[i] * 2 for i in range(n)]
+    return sum([len(p) for p in parts])
+
+total = 0
+for n in range(5):
+    total += build(n)
+total
+";
+    let (result, audit) = audit(code);
+    assert_eq!(result, MontyObject::Int(20));
+    audit.assert_no_leaks();
+    assert!(
+        !audit.objects_by_type.contains_key("list"),
+        "{:?}",
+        audit.objects_by_type
+    );
+}
+
+#[test]
+fn uncollected_cycles_are_unreachable_but_not_leaks() {
+    let code = r"
+a = []
+a.append(a)
+a = None
+";
+    let (_, audit) = audit(code);
+    audit.assert_no_leaks();
+    assert_eq!(audit.unreachable.len(), 1);
+    assert_eq!(audit.unreachable[0].type_name, "list");
+    assert_eq!(audit.unreachable[0].refcount, 1);
+}
+
+#[test]
+fn failed_runs_are_audited_too() {
+    let runner = MontyRun::new("data = [1, 2]\ndata[5]".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let (result, audit) = runner.run_audited(vec![], NoLimitTracker, &mut PrintWriter::Disabled);
+    assert!(result.is_err());
+    audit.assert_no_leaks();
+    assert_eq!(audit.objects_by_type["list"], 1);
+}
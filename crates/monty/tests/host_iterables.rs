@@ -0,0 +1,186 @@
+//! Tests for host iterables: external functions scripts loop over, with the host handing
+//! over items as the loop needs them.
+
+use monty::{
+    ExcType, ExternalResult, HostIterable, MontyException, MontyObject, MontyRun, NoLimitTracker, PrintWriter,
+    RunProgress, Snapshot,
+};
+
+fn start(code: &str) -> RunProgress<NoLimitTracker> {
+    let mut run = MontyRun::builder(code)
+        .external_functions(["rows", "log"])
+        .host_iterables(["rows"])
+        .build()
+        .unwrap();
+    run.start(vec![]).unwrap()
+}
+
+fn start_err(code: &str) -> MontyException {
+    let mut run = MontyRun::builder(code)
+        .external_functions(["rows"])
+        .host_iterables(["rows"])
+        .build()
+        .unwrap();
+    run.start(vec![]).unwrap_err()
+}
+
+/// Returns the snapshot of a run waiting on the host for the items of `rows`.
+fn expect_rows(progress: RunProgress<NoLimitTracker>) -> Snapshot<NoLimitTracker> {
+    let (name, args, kwargs, _, _, state) = progress.into_function_call().expect("expected the run to wait on rows");
+    assert_eq!(name, "rows");
+    assert!(args.is_empty() && kwargs.is_empty());
+    assert!(state.is_host_iterable());
+    state
+}
+
+fn stop_iteration() -> ExternalResult {
+    ExternalResult::Error(MontyException::new(ExcType::StopIteration, None))
+}
+
+/// Runs `code` to completion, streaming `items` into `rows` `chunk_size` at a time, and
+/// returns the result and how many times the run waited on the host.
+fn stream(code: &str, items: Vec<MontyObject>, chunk_size: usize) -> (MontyObject, usize) {
+    let mut rows = HostIterable::new(items).chunk_size(chunk_size);
+    let mut progress = start(code);
+    let mut waits = 0;
+    while let RunProgress::FunctionCall { state, .. } = progress {
+        waits += 1;
+        progress = rows.resume(state, &mut PrintWriter::Disabled).unwrap();
+    }
+    (progress.into_complete().expect("expected the run to complete"), waits)
+}
+
+fn ints(range: std::ops::Range<i64>) -> Vec<MontyObject> {
+    range.map(MontyObject::Int).collect()
+}
+
+#[test]
+fn loops_ask_the_host_for_each_item() {
+    let code = "total = 0\nfor n in rows:\n    total += n\ntotal";
+    let mut progress = start(code);
+    for n in 1..=3 {
+        progress = expect_rows(progress)
+            .run(MontyObject::Int(n), &mut PrintWriter::Disabled)
+            .unwrap();
+    }
+    let result = expect_rows(progress).run(stop_iteration(), &mut PrintWriter::Disabled);
+    assert_eq!(result.unwrap().into_complete(), Some(MontyObject::Int(6)));
+}
+
+#[test]
+fn items_are_handed_over_in_chunks() {
+    let code = "total = 0\nfor n in rows:\n    total += n\ntotal";
+    let (result, waits) = stream(code, ints(0..1000), 100);
+    assert_eq!(result, MontyObject::Int(499_500));
+    // ten full chunks, then an empty one marking the end
+    assert_eq!(waits, 11);
+
+    let (result, waits) = stream(code, ints(0..950), 100);
+    assert_eq!(result, MontyObject::Int(451_725));
+    assert_eq!(waits, 10);
+}
+
+#[test]
+fn comprehensions_loop_over_host_iterables() {
+    let code = "[row['name'] for row in rows if row['age'] > 30]";
+    let people = [("ada", 36), ("bob", 25), ("cy", 41)]
+        .into_iter()
+        .map(|(name, age)| {
+            MontyObject::Dict(
+                vec![
+                    (MontyObject::from("name"), MontyObject::from(name)),
+                    (MontyObject::from("age"), MontyObject::Int(age)),
+                ]
+                .into(),
+            )
+        })
+        .collect();
+    let (result, _) = stream(code, people, 2);
+    assert_eq!(
+        result,
+        MontyObject::List(vec![MontyObject::from("ada"), MontyObject::from("cy")])
+    );
+}
+
+#[test]
+fn breaking_out_stops_asking_for_items() {
+    let code = "found = None\nfor n in rows:\n    if n > 2:\n        found = n\n        break\nfound";
+    let mut progress = start(code);
+    for n in 1..=3 {
+        progress = expect_rows(progress)
+            .run(MontyObject::Int(n), &mut PrintWriter::Disabled)
+            .unwrap();
+    }
+    assert_eq!(progress.into_complete(), Some(MontyObject::Int(3)));
+}
+
+#[test]
+fn host_errors_are_raised_by_the_loop() {
+    let code = r"
+seen = []
+try:
+    for n in rows:
+        seen.append(n)
+except ValueError as e:
+    seen.append(str(e))
+seen
+";
+    let state = expect_rows(start(code));
+    let state = expect_rows(state.run(MontyObject::Int(1), &mut PrintWriter::Disabled).unwrap());
+    let error = MontyException::new(ExcType::ValueError, Some("connection lost".to_owned()));
+    let result = state
+        .run(ExternalResult::Error(error), &mut PrintWriter::Disabled)
+        .unwrap();
+    let expected = MontyObject::List(vec![MontyObject::Int(1), MontyObject::from("connection lost")]);
+    assert_eq!(result.into_complete(), Some(expected));
+}
+
+#[test]
+fn only_loops_can_wait_on_the_host() {
+    let err = start_err("list(rows)");
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(
+        err.message(),
+        Some("host iterable 'rows' can only be consumed by a for loop or comprehension")
+    );
+
+    let err = start_err("rows()");
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(
+        err.message(),
+        Some("host iterable 'rows' is not callable, loop over it instead")
+    );
+}
+
+#[test]
+fn calls_are_not_host_iterables() {
+    let code = "for n in rows:\n    log(n)";
+    let state = expect_rows(start(code));
+    let (name, _, _, _, _, state) = state
+        .run(MontyObject::Int(1), &mut PrintWriter::Disabled)
+        .unwrap()
+        .into_function_call()
+        .expect("expected a call to log");
+    assert_eq!(name, "log");
+    assert!(!state.is_host_iterable());
+    let err = HostIterable::new(ints(0..3))
+        .resume(state, &mut PrintWriter::Disabled)
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::RuntimeError);
+}
+
+#[test]
+fn loops_survive_a_dump_and_load() {
+    let code = "total = 0\nfor n in rows:\n    total += n\ntotal";
+    let state = expect_rows(start(code));
+    let progress = state
+        .run_host_items(ints(1..4), false, &mut PrintWriter::Disabled)
+        .unwrap();
+    let bytes = progress.dump().unwrap();
+
+    let state = expect_rows(RunProgress::load(&bytes).unwrap());
+    let result = state
+        .run_host_items(ints(4..6), true, &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(result.into_complete(), Some(MontyObject::Int(15)));
+}
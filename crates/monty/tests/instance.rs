@@ -0,0 +1,142 @@
+//! Tests for calling script functions from the host with `MontyInstance` and `MontyRun::call`.
+
+use monty::{
+    ExcType, LimitedTracker, MontyInstance, MontyObject, MontyRun, NoLimitTracker, PrintWriter, ResourceLimits,
+};
+
+const HANDLER: &str = r"
+calls = []
+
+def handler(event, scale=1):
+    calls.append(event)
+    return {'event': event * scale, 'calls': len(calls)}
+
+def fail(message):
+    raise ValueError(message)
+";
+
+fn runner(code: &str) -> MontyRun {
+    MontyRun::new(code.to_owned(), "handler.py", vec![], vec![]).unwrap()
+}
+
+fn result(event: i64, calls: i64) -> MontyObject {
+    MontyObject::dict(vec![
+        (MontyObject::from("event"), MontyObject::Int(event)),
+        (MontyObject::from("calls"), MontyObject::Int(calls)),
+    ])
+}
+
+#[test]
+fn instance_keeps_globals_between_calls() {
+    let runner = runner(HANDLER);
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![], NoLimitTracker, print).unwrap();
+    for n in 1..=3 {
+        let value = instance.call("handler", vec![MontyObject::Int(n * 10)], print).unwrap();
+        assert_eq!(value, result(n * 10, n));
+    }
+}
+
+#[test]
+fn run_call_starts_from_fresh_globals() {
+    let runner = runner(HANDLER);
+    for n in 1..=3 {
+        let value = runner
+            .call(
+                "handler",
+                vec![],
+                vec![MontyObject::Int(n), MontyObject::Int(2)],
+                NoLimitTracker,
+                &mut PrintWriter::Disabled,
+            )
+            .unwrap();
+        assert_eq!(value, result(n * 2, 1));
+    }
+}
+
+#[test]
+fn exceptions_leave_the_instance_usable() {
+    let runner = runner(HANDLER);
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![], NoLimitTracker, print).unwrap();
+
+    let err = instance
+        .call("fail", vec![MontyObject::from("boom")], print)
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ValueError);
+    assert_eq!(err.message(), Some("boom"));
+
+    let err = instance.call("handler", vec![], print).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+
+    let value = instance.call("handler", vec![MontyObject::Int(5)], print).unwrap();
+    assert_eq!(value, result(5, 1));
+}
+
+#[test]
+fn unknown_function_is_a_name_error() {
+    let runner = runner(HANDLER);
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![], NoLimitTracker, print).unwrap();
+    let err = instance.call("missing", vec![], print).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+    assert_eq!(err.message(), Some("name 'missing' is not defined"));
+
+    let err = instance.call("calls", vec![], print).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+}
+
+#[test]
+fn module_errors_are_reported_by_new() {
+    let runner = runner("def handler():\n    return 1\n1 / 0");
+    let err = MontyInstance::new(&runner, vec![], NoLimitTracker, &mut PrintWriter::Disabled).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::ZeroDivisionError);
+}
+
+#[test]
+fn inputs_are_visible_to_functions() {
+    let runner = MontyRun::new(
+        "def greet(name):\n    return prefix + name".to_owned(),
+        "greet.py",
+        vec!["prefix".to_owned()],
+        vec![],
+    )
+    .unwrap();
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![MontyObject::from("hi ")], NoLimitTracker, print).unwrap();
+    let value = instance.call("greet", vec![MontyObject::from("monty")], print).unwrap();
+    assert_eq!(value, MontyObject::from("hi monty"));
+}
+
+#[test]
+fn closures_and_builtins_can_be_called() {
+    let code = r"
+def make_adder(n):
+    def add(x):
+        return x + n
+    return add
+
+add_two = make_adder(2)
+length = len
+";
+    let runner = runner(code);
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![], NoLimitTracker, print).unwrap();
+    assert_eq!(
+        instance.call("add_two", vec![MontyObject::Int(40)], print).unwrap(),
+        MontyObject::Int(42)
+    );
+    let list = MontyObject::List(vec![MontyObject::None; 3]);
+    assert_eq!(instance.call("length", vec![list], print).unwrap(), MontyObject::Int(3));
+}
+
+#[test]
+fn limits_apply_across_calls() {
+    let runner = runner("def grow(n):\n    return [[i] for i in range(n)]");
+    let tracker = LimitedTracker::new(ResourceLimits::new().max_allocations(150));
+    let print = &mut PrintWriter::Disabled;
+    let mut instance = MontyInstance::new(&runner, vec![], tracker, print).unwrap();
+    assert!(instance.call("grow", vec![MontyObject::Int(100)], print).is_ok());
+    let err = instance.call("grow", vec![MontyObject::Int(100)], print).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::MemoryError);
+}
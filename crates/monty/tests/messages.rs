@@ -0,0 +1,91 @@
+//! Tests for error codes and host-provided message catalogs.
+
+use monty::{ErrorCode, ExcType, Hint, MessageCatalog, MontyException, MontyRun};
+
+fn run_err(code: &str) -> MontyException {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    runner.run_no_limits(vec![]).unwrap_err()
+}
+
+#[test]
+fn raised_messages_have_codes() {
+    let cases = [
+        ("undefined_name", ErrorCode::NameNotDefined),
+        ("[].nope", ErrorCode::AttributeNotFound),
+        ("1 + 'a'", ErrorCode::UnsupportedOperand),
+        ("'a' + 1", ErrorCode::ConcatenateMismatch),
+        ("-'a'", ErrorCode::BadUnaryOperand),
+        ("1 / 0", ErrorCode::DivisionByZero),
+        ("[1][5]", ErrorCode::ListIndexOutOfRange),
+        ("hash([])", ErrorCode::Unhashable),
+        ("import nonexistent_module", ErrorCode::ModuleNotFound),
+    ];
+    for (code, expected) in cases {
+        assert_eq!(run_err(code).error_code(), Some(expected), "code: {code}");
+    }
+}
+
+#[test]
+fn messages_without_code() {
+    let err = run_err("raise ValueError('custom problem')");
+    assert_eq!(err.error_code(), None);
+
+    let catalog = MessageCatalog::new().message(ErrorCode::NameNotDefined, "unused");
+    assert_eq!(catalog.localize(&err), err);
+}
+
+#[test]
+fn catalog_rewrites_message_and_keeps_traceback() {
+    let catalog = MessageCatalog::new().message(
+        ErrorCode::AttributeNotFound,
+        "Objekt vom Typ '{0}' hat kein Attribut '{1}'",
+    );
+    let err = run_err("x = [1]\nx.missing");
+    let localized = catalog.localize(&err);
+
+    assert_eq!(localized.exc_type(), ExcType::AttributeError);
+    assert_eq!(
+        localized.message(),
+        Some("Objekt vom Typ 'list' hat kein Attribut 'missing'")
+    );
+    assert_eq!(localized.traceback(), err.traceback());
+    assert_eq!(err.message(), Some("'list' object has no attribute 'missing'"));
+}
+
+#[test]
+fn catalog_reorders_placeholders() {
+    let catalog = MessageCatalog::new().message(ErrorCode::UnsupportedOperand, "{1} {0} {2} is not supported");
+    let err = run_err("1 - 'a'");
+    assert_eq!(catalog.localize(&err).message(), Some("int - str is not supported"));
+}
+
+#[test]
+fn catalog_rewrites_hints_separately() {
+    let err = run_err("sys.platform");
+    assert_eq!(
+        err.message(),
+        Some("name 'sys' is not defined. Did you forget to import 'sys'?")
+    );
+
+    let classified = ErrorCode::classify(err.exc_type(), err.message().unwrap()).unwrap();
+    assert_eq!(classified.hint, Some((Hint::ForgotImport, vec!["sys".to_owned()])));
+
+    let message_only = MessageCatalog::new().message(ErrorCode::NameNotDefined, "unknown name {0}");
+    assert_eq!(
+        message_only.localize(&err).message(),
+        Some("unknown name sys. Did you forget to import 'sys'?")
+    );
+
+    let both = message_only.hint(Hint::ForgotImport, " (try `import {0}`)");
+    assert_eq!(
+        both.localize(&err).message(),
+        Some("unknown name sys (try `import sys`)")
+    );
+}
+
+#[test]
+fn codes_are_stable_strings() {
+    assert_eq!(ErrorCode::NameNotDefined.code(), "name-not-defined");
+    assert_eq!(ErrorCode::StrIndexOutOfRange.to_string(), "str-index-out-of-range");
+    assert_eq!(Hint::ForgotImport.code(), "forgot-import");
+}
@@ -0,0 +1,99 @@
+//! Tests for the container accessors on `MontyObject`.
+
+use monty::{ConversionErrorKind, MontyObject, MontyRun};
+
+fn run(code: &str) -> MontyObject {
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    ex.run_no_limits(vec![]).unwrap()
+}
+
+#[test]
+fn len_of_containers() {
+    assert_eq!(run("[1, 2, 3]").len(), Some(3));
+    assert_eq!(run("(1,)").len(), Some(1));
+    assert_eq!(run("{'a': 1, 'b': 2}").len(), Some(2));
+    assert_eq!(run("{1, 2, 2}").len(), Some(2));
+    assert_eq!(run("'héllo'").len(), Some(5));
+    assert_eq!(run("b'ab'").len(), Some(2));
+    assert_eq!(run("42").len(), None);
+    assert_eq!(run("[]").is_empty(), Some(true));
+    assert_eq!(run("None").is_empty(), None);
+}
+
+#[test]
+fn iter_over_sequences() {
+    let result = run("[i * 2 for i in range(3)]");
+    let items: Vec<&MontyObject> = result.iter().unwrap().collect();
+    assert_eq!(
+        items,
+        vec![&MontyObject::Int(0), &MontyObject::Int(2), &MontyObject::Int(4)]
+    );
+
+    let result = run("('a', 'b')");
+    assert_eq!(result.iter().unwrap().count(), 2);
+
+    let err = run("{'a': 1}").iter().unwrap_err();
+    assert_eq!(err.to_string(), "expected sequence, got dict");
+}
+
+#[test]
+fn get_item_by_index() {
+    let result = run("[10, 20, 30]");
+    assert_eq!(result.get_item(0), Some(&MontyObject::Int(10)));
+    assert_eq!(result.get_item(-1), Some(&MontyObject::Int(30)));
+    assert_eq!(result.get_item(3), None);
+    assert_eq!(result.get_item(-4), None);
+    assert_eq!(result.get_item("x"), None);
+}
+
+#[test]
+fn get_item_by_key() {
+    let result = run("{'name': 'monty', 1: [True], (1, 2): None}");
+    assert_eq!(result.get_item("name"), Some(&MontyObject::String("monty".to_owned())));
+    assert_eq!(
+        result.get_item(1).and_then(|list| list.get_item(0)),
+        Some(&MontyObject::Bool(true))
+    );
+    let key = MontyObject::Tuple(vec![MontyObject::Int(1), MontyObject::Int(2)]);
+    assert_eq!(result.get_item(key), Some(&MontyObject::None));
+    assert_eq!(result.get_item("missing"), None);
+}
+
+#[test]
+fn as_dict_yields_pairs_in_order() {
+    let result = run("{'b': 2, 'a': 1}");
+    let pairs: Vec<(String, i64)> = result
+        .as_dict()
+        .unwrap()
+        .map(|(key, value)| (key.extract().unwrap(), value.extract().unwrap()))
+        .collect();
+    assert_eq!(pairs, vec![("b".to_owned(), 2), ("a".to_owned(), 1)]);
+
+    let err = run("[1]").as_dict().err().unwrap();
+    assert_eq!(err.to_string(), "expected dict, got list");
+}
+
+#[test]
+fn extract_typed_values() {
+    assert_eq!(run("[1, 2, 3]").extract::<Vec<i64>>().unwrap(), vec![1, 2, 3]);
+    assert_eq!(
+        run("[['a'], ['b', 'c']]").extract::<Vec<Vec<String>>>().unwrap(),
+        vec![vec!["a".to_owned()], vec!["b".to_owned(), "c".to_owned()]]
+    );
+    assert_eq!(
+        run("[1, None]").extract::<Vec<Option<u8>>>().unwrap(),
+        vec![Some(1), None]
+    );
+    assert_eq!(run("None").extract::<Option<f64>>().unwrap(), None);
+    assert!(run("True").extract::<bool>().unwrap());
+}
+
+#[test]
+fn extract_reports_bad_items() {
+    let err = run("[1, 'two']").extract::<Vec<i64>>().unwrap_err();
+    assert_eq!(err.kind, ConversionErrorKind::WrongType);
+    assert_eq!(err.to_string(), "expected int, got str");
+
+    let err = run("[1, 300]").extract::<Vec<u8>>().unwrap_err();
+    assert_eq!(err.kind, ConversionErrorKind::Overflow);
+}
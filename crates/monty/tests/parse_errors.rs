@@ -99,6 +99,32 @@ fn invalid_fstring_format_spec_str_returns_syntax_error() {
     assert_eq!(get_exc_type(result), ExcType::SyntaxError);
 }
 
+#[test]
+fn multiple_starred_assignment_targets_return_syntax_error() {
+    for code in ["a, *b, *c = [1, 2, 3]", "[*a, *b] = [1, 2]", "x, (*a, *b) = 1, (2, 3)"] {
+        let result = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]);
+        assert_eq!(get_exc_type(result), ExcType::SyntaxError, "{code}");
+    }
+}
+
+#[test]
+fn lone_starred_assignment_target_returns_syntax_error() {
+    let result = MontyRun::new("*a = [1, 2]".to_owned(), "test.py", vec![], vec![]);
+    assert_eq!(get_exc_type(result), ExcType::SyntaxError);
+}
+
+#[test]
+fn subscript_comprehension_targets_return_not_implemented_error() {
+    let code = "d = {}\n[1 for d['k'] in range(3)]";
+    let result = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]);
+    let exc = result.expect_err("expected parse error");
+    assert_eq!(exc.exc_type(), ExcType::NotImplementedError);
+    assert!(
+        exc.message().is_some_and(|m| m.contains("comprehensions")),
+        "message should mention comprehensions, got: {exc}"
+    );
+}
+
 #[test]
 fn syntax_error_display_format() {
     let result = MontyRun::new("f'{1:10xyz}'".to_owned(), "test.py", vec![], vec![]);
@@ -0,0 +1,77 @@
+//! Tests for running one program repeatedly with a `Pool`.
+
+use monty::{ExcType, LimitedTracker, MontyObject, MontyRun, NoLimitTracker, Pool, PrintWriter, ResourceLimits};
+
+fn runner(code: &str) -> MontyRun {
+    MontyRun::new(code.to_owned(), "test.py", vec!["x".to_owned()], vec![]).unwrap()
+}
+
+#[test]
+fn batch_results_match_single_runs() {
+    let code = r"
+def fib(n):
+    if n < 2:
+        return n
+    return fib(n - 1) + fib(n - 2)
+
+{'n': x, 'fib': fib(x), 'squares': [i * i for i in range(x)]}
+";
+    let runner = runner(code);
+    let mut pool = Pool::new(&runner);
+    let inputs: Vec<_> = (0..10).map(|x| vec![MontyObject::Int(x)]).collect();
+    let results = pool.run_batch(inputs.clone(), || NoLimitTracker, &mut PrintWriter::Disabled);
+    assert_eq!(results.len(), 10);
+    for (inputs, result) in inputs.into_iter().zip(results) {
+        let expected = runner.run(inputs, NoLimitTracker, &mut PrintWriter::Disabled);
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn globals_do_not_leak_between_runs() {
+    let mut pool = Pool::new(&runner("if x:\n    leaked = 'from an earlier run'\nleaked"));
+    let first = pool.run(
+        vec![MontyObject::Bool(true)],
+        NoLimitTracker,
+        &mut PrintWriter::Disabled,
+    );
+    assert_eq!(first, Ok(MontyObject::String("from an earlier run".to_owned())));
+
+    let err = pool
+        .run(
+            vec![MontyObject::Bool(false)],
+            NoLimitTracker,
+            &mut PrintWriter::Disabled,
+        )
+        .unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NameError);
+}
+
+#[test]
+fn failed_runs_do_not_stop_the_batch() {
+    let mut pool = Pool::new(&runner("10 // x"));
+    let inputs = [2, 0, 5].map(|x| vec![MontyObject::Int(x)]);
+    let results = pool.run_batch(inputs, || NoLimitTracker, &mut PrintWriter::Disabled);
+    assert_eq!(results[0], Ok(MontyObject::Int(5)));
+    assert_eq!(results[1].as_ref().unwrap_err().exc_type(), ExcType::ZeroDivisionError);
+    assert_eq!(results[2], Ok(MontyObject::Int(2)));
+}
+
+#[test]
+fn each_run_gets_a_fresh_tracker() {
+    let mut pool = Pool::new(&runner("len([[i] for i in range(x)])"));
+    let new_tracker = || LimitedTracker::new(ResourceLimits::new().max_allocations(100));
+    let inputs = [60, 60, 60].map(|x| vec![MontyObject::Int(x)]);
+    let results = pool.run_batch(inputs, new_tracker, &mut PrintWriter::Disabled);
+    // the allocations of earlier runs don't count towards the limit of later ones
+    assert_eq!(results, vec![Ok(MontyObject::Int(60)); 3]);
+}
+
+#[test]
+fn output_is_collected_across_the_batch() {
+    let mut pool = Pool::new(&runner("print('run', x)"));
+    let mut print = PrintWriter::Collect(String::new());
+    let inputs = (1..=3).map(|x| vec![MontyObject::Int(x)]);
+    pool.run_batch(inputs, || NoLimitTracker, &mut print);
+    assert_eq!(print.collected_output(), Some("run 1\nrun 2\nrun 3\n"));
+}
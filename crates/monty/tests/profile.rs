@@ -0,0 +1,67 @@
+//! Tests for per-line profiling with `MontyRun::run_profiled`.
+
+use std::time::Duration;
+
+use monty::{ExcType, LimitedTracker, MontyObject, MontyRun, NoLimitTracker, PrintWriter, ResourceLimits};
+
+#[test]
+fn profile_counts_opcodes_per_line() {
+    let code = r"
+def square(n):
+    return n * n
+
+total = 0
+for i in range(10):
+    total += square(i)
+total
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let (result, report) = runner.run_profiled(vec![], NoLimitTracker, &mut PrintWriter::Stdout);
+    assert_eq!(result.unwrap(), MontyObject::Int(285));
+
+    let body = report.line("square", 3).expect("square body profiled");
+    let loop_body = report.line("<module>", 7).expect("loop body profiled");
+    // every call runs the same opcodes, so counts are exact multiples of the call count
+    assert_eq!(body.opcodes % 10, 0);
+    assert_eq!(loop_body.opcodes % 10, 0);
+    assert!(report.line("<module>", 5).unwrap().opcodes < loop_body.opcodes);
+    assert!(report.line("square", 4).is_none());
+
+    let total: u64 = report.lines().iter().map(|line| line.opcodes).sum();
+    assert_eq!(report.total_opcodes(), total);
+}
+
+#[test]
+fn profile_lines_are_sorted_slowest_first() {
+    let code = r"
+x = 0
+for i in range(2000):
+    x += i
+x
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let (result, report) = runner.run_profiled(vec![], NoLimitTracker, &mut PrintWriter::Stdout);
+    assert_eq!(result.unwrap(), MontyObject::Int(1_999_000));
+
+    let times: Vec<Duration> = report.lines().iter().map(|line| line.time).collect();
+    assert!(times.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert_eq!(report.total_time(), times.iter().sum());
+
+    let table = report.to_string();
+    assert!(table.starts_with("   time (us)"));
+    assert_eq!(table.lines().count(), report.lines().len() + 1);
+}
+
+#[test]
+fn profile_is_returned_when_the_run_fails() {
+    let code = r"
+i = 0
+while True:
+    i += 1
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let limits = ResourceLimits::new().max_duration(Duration::from_millis(50));
+    let (result, report) = runner.run_profiled(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout);
+    assert_eq!(result.unwrap_err().exc_type(), ExcType::TimeoutError);
+    assert!(report.line("<module>", 4).unwrap().opcodes > 0);
+}
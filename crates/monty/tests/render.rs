@@ -0,0 +1,45 @@
+//! Tests for CPython-style traceback rendering with `MontyException::render`.
+
+use monty::MontyRun;
+
+fn run_error(code: &str) -> String {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    runner.run_no_limits(vec![]).unwrap_err().render(code)
+}
+
+#[test]
+fn render_marks_expressions_and_omits_whole_line_markers() {
+    let code = "def f(x):\n    return x + None\n\nf(1)\n";
+    assert_eq!(
+        run_error(code),
+        "\
+Traceback (most recent call last):
+  File \"test.py\", line 4, in <module>
+    f(1)
+  File \"test.py\", line 2, in f
+    return x + None
+           ^^^^^^^^
+TypeError: unsupported operand type(s) for +: 'int' and 'NoneType'"
+    );
+}
+
+#[test]
+fn render_omits_markers_for_raise() {
+    let code = "if True:\n    raise ValueError('bad')\n";
+    assert_eq!(
+        run_error(code),
+        "\
+Traceback (most recent call last):
+  File \"test.py\", line 2, in <module>
+    raise ValueError('bad')
+ValueError: bad"
+    );
+}
+
+#[test]
+fn render_syntax_errors_omit_the_frame_name() {
+    let err = MontyRun::new("1 +".to_owned(), "test.py", vec![], vec![]).unwrap_err();
+    let rendered = err.render("1 +");
+    assert!(rendered.starts_with("Traceback (most recent call last):\n  File \"test.py\", line 1\n    1 +\n"));
+    assert!(rendered.ends_with(&err.summary()));
+}
@@ -4,8 +4,8 @@
 //! only the newly fed snippet each time.
 
 use monty::{
-    ExternalResult, MontyObject, MontyRepl, NoLimitTracker, PrintWriter, ReplContinuationMode, ReplProgress,
-    detect_repl_continuation_mode,
+    ExcType, ExternalResult, MontyObject, MontyRepl, NoLimitTracker, PrintWriter, ReplContinuationMode, ReplProgress,
+    SnapshotError, detect_repl_continuation_mode,
 };
 
 fn init_repl(code: &str, external_functions: Vec<String>) -> (MontyRepl<NoLimitTracker>, MontyObject) {
@@ -45,6 +45,25 @@ fn repl_persists_state_and_definitions() {
     assert_eq!(output, MontyObject::Int(42));
 }
 
+#[test]
+fn repl_globals_show_names_defined_by_earlier_snippets() {
+    let (mut repl, _) = init_repl("x = 1", vec!["fetch".to_owned()]);
+    repl.feed_no_print("items = [x, x + 1]").unwrap();
+    // `missing` gets a slot but is never bound
+    repl.feed_no_print("missing").unwrap_err();
+
+    let globals = repl.globals();
+    assert_eq!(globals.len(), 2);
+    assert_eq!(globals["x"], MontyObject::Int(1));
+    assert_eq!(
+        globals["items"],
+        MontyObject::List(vec![MontyObject::Int(1), MontyObject::Int(2)])
+    );
+    assert_eq!(repl.global("x"), Some(MontyObject::Int(1)));
+    assert_eq!(repl.global("missing"), None);
+    assert_eq!(repl.global("fetch"), None);
+}
+
 #[test]
 fn repl_function_redefinition_uses_latest_definition() {
     let (mut repl, init_output) = init_repl("", vec![]);
@@ -143,6 +162,24 @@ fn repl_dump_load_survives_between_snippets() {
     assert_eq!(output, MontyObject::Int(42));
 }
 
+#[test]
+fn repl_load_checks_the_header() {
+    let (repl, _) = init_repl("total = 1", vec![]);
+    let mut bytes = repl.dump().unwrap();
+    assert_eq!(&bytes[..4], b"MNTL");
+
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    assert!(matches!(
+        MontyRepl::<NoLimitTracker>::load(&bytes),
+        Err(SnapshotError::ChecksumMismatch)
+    ));
+    assert!(matches!(
+        MontyRepl::<NoLimitTracker>::load(b"not a session"),
+        Err(SnapshotError::BadMagic)
+    ));
+}
+
 #[test]
 fn repl_dump_load_preserves_heap_aliasing() {
     let (mut repl, _) = init_repl("a = []\nb = a", vec![]);
@@ -181,6 +218,20 @@ fn repl_start_external_call_resumes_to_updated_repl() {
     assert_eq!(repl.feed_no_print("x").unwrap(), MontyObject::Int(5));
 }
 
+#[test]
+fn repl_start_emit_resumes_to_updated_repl() {
+    let (repl, _) = init_repl("", vec![]);
+
+    let progress = repl.start("emit(7)\nx = 8\nx", &mut PrintWriter::Stdout).unwrap();
+    let (value, state) = progress.into_emit().expect("expected emit");
+    assert_eq!(value, MontyObject::Int(7));
+
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Stdout).unwrap();
+    let (mut repl, value) = progress.into_complete().expect("expected completion");
+    assert_eq!(value, MontyObject::Int(8));
+    assert_eq!(repl.feed_no_print("x").unwrap(), MontyObject::Int(8));
+}
+
 #[test]
 fn repl_progress_dump_load_roundtrip() {
     let (repl, _) = init_repl("", vec!["ext_fn".to_owned()]);
@@ -278,3 +329,66 @@ fn repl_dataclass_method_call_yields_function_call_with_method_flag() {
     // Verify REPL state is preserved after method call
     assert_eq!(repl.feed_no_print("1 + 1").unwrap(), MontyObject::Int(2));
 }
+
+#[test]
+fn repl_frozen_globals_reject_rebinding() {
+    let (mut repl, _) = init_repl("LIMIT = 10\ndef check(v):\n    return v < LIMIT", vec![]);
+    repl.freeze_globals();
+
+    let err = repl.feed_no_print("LIMIT = 0").unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(err.message(), Some("cannot assign to frozen global 'LIMIT'"));
+
+    let err = repl
+        .feed_no_print("def bump():\n    global LIMIT\n    LIMIT += 1\nbump()")
+        .unwrap_err();
+    assert_eq!(err.message(), Some("cannot assign to frozen global 'LIMIT'"));
+
+    let err = repl.feed_no_print("del check").unwrap_err();
+    assert_eq!(err.message(), Some("cannot assign to frozen global 'check'"));
+
+    // the error is catchable and the frozen state is unchanged
+    let output = repl
+        .feed_no_print("try:\n    LIMIT = 0\nexcept TypeError:\n    pass\ncheck(5)")
+        .unwrap();
+    assert_eq!(output, MontyObject::Bool(true));
+}
+
+#[test]
+fn repl_frozen_globals_allow_new_names() {
+    let (mut repl, _) = init_repl("base = 1", vec![]);
+    repl.freeze_globals();
+
+    repl.feed_no_print("extra = base + 1").unwrap();
+    repl.feed_no_print("extra = extra + 1").unwrap();
+    assert_eq!(repl.feed_no_print("extra").unwrap(), MontyObject::Int(3));
+}
+
+#[test]
+fn repl_frozen_globals_survive_dump_load() {
+    let (mut repl, _) = init_repl("x = 1", vec![]);
+    repl.freeze_globals();
+
+    let bytes = repl.dump().unwrap();
+    let mut loaded: MontyRepl<NoLimitTracker> = MontyRepl::load(&bytes).unwrap();
+    let err = loaded.feed_no_print("x = 2").unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::TypeError);
+    assert_eq!(loaded.feed_no_print("x").unwrap(), MontyObject::Int(1));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn repl_short_strings_come_from_cache() {
+    let (mut repl, _) = init_repl("v = ''", vec![]);
+    let before = repl.string_cache_hits();
+
+    // '' + 'x' and 'xy'[:1] both produce a single ASCII character, so neither allocates
+    repl.feed_no_print("v += 'x'").unwrap();
+    repl.feed_no_print("w = ('x' + 'y')[:1]").unwrap();
+    assert_eq!(repl.string_cache_hits() - before, 2);
+    assert_eq!(repl.feed_no_print("v == w == 'x'").unwrap(), MontyObject::Bool(true));
+
+    // longer results are still heap allocated
+    repl.feed_no_print("v += 'yz'").unwrap();
+    assert_eq!(repl.string_cache_hits() - before, 2);
+}
@@ -0,0 +1,210 @@
+//! Tests for recording external call results with `CallLog` and replaying them with `Replayer`.
+
+use monty::{
+    CallLog, CallLogEntry, ExcType, ExternalResult, MontyException, MontyObject, MontyRun, NoLimitTracker, PrintWriter,
+    RecordedCallKind, ReplayError, Replayer, RunProgress,
+};
+
+fn start(code: &str, inputs: Vec<MontyObject>) -> RunProgress<NoLimitTracker> {
+    let runner = MontyRun::new(
+        code.to_owned(),
+        "test.py",
+        vec!["x".to_owned()],
+        vec!["fetch".to_owned(), "fetch_async".to_owned()],
+    )
+    .unwrap();
+    runner.start(inputs, NoLimitTracker, &mut PrintWriter::Stdout).unwrap()
+}
+
+/// Drives a run like a host would, answering `fetch(n)` with `n * 10` (raising for negative
+/// `n`), `fetch_async` with a future resolved to `n * 100`, and `emit` with the count so far.
+/// Stops after `max_answers` answers, returning the progress reached.
+fn record(
+    mut progress: RunProgress<NoLimitTracker>,
+    log: &mut CallLog,
+    max_answers: usize,
+) -> Result<RunProgress<NoLimitTracker>, MontyException> {
+    let mut pending = Vec::new();
+    let mut emitted = 0;
+    for _ in 0..max_answers {
+        progress = match progress {
+            RunProgress::FunctionCall {
+                ref function_name,
+                ref args,
+                call_id,
+                ..
+            } => {
+                let MontyObject::Int(n) = args[0] else {
+                    panic!("expected int")
+                };
+                let result = if function_name == "fetch_async" {
+                    pending.push((call_id, n * 100));
+                    ExternalResult::Future
+                } else if n < 0 {
+                    MontyException::new(ExcType::ValueError, Some(format!("bad id {n}"))).into()
+                } else {
+                    MontyObject::Int(n * 10).into()
+                };
+                log.record(&progress, &result);
+                let (_, _, _, _, _, state) = progress.into_function_call().unwrap();
+                state.run(result, &mut PrintWriter::Stdout)?
+            }
+            RunProgress::Emit { .. } => {
+                emitted += 1;
+                let result = MontyObject::Int(emitted).into();
+                log.record(&progress, &result);
+                let (_, state) = progress.into_emit().unwrap();
+                state.run(result, &mut PrintWriter::Stdout)?
+            }
+            RunProgress::ResolveFutures(state) => {
+                let results: Vec<(u32, ExternalResult)> = pending
+                    .drain(..)
+                    .map(|(call_id, value)| (call_id, MontyObject::Int(value).into()))
+                    .collect();
+                log.record_resolved(&results);
+                state.resume(results, &mut PrintWriter::Stdout)?
+            }
+            other => return Ok(other),
+        };
+    }
+    Ok(progress)
+}
+
+const CODE: &str = r"
+a = fetch(x)
+b = emit(a)
+c = fetch(a + b)
+[a, b, c]
+";
+
+#[test]
+fn replay_reproduces_recorded_run() {
+    let mut log = CallLog::new();
+    let recorded = record(start(CODE, vec![MontyObject::Int(1)]), &mut log, usize::MAX).unwrap();
+    let expected = MontyObject::List(vec![MontyObject::Int(10), MontyObject::Int(1), MontyObject::Int(110)]);
+    assert_eq!(recorded.into_complete(), Some(expected.clone()));
+    assert_eq!(log.len(), 3);
+
+    let CallLogEntry::Call(emit) = &log.entries()[1] else {
+        panic!("expected a call entry");
+    };
+    assert_eq!(emit.kind, RecordedCallKind::Emit);
+    assert_eq!(emit.args, vec![MontyObject::Int(10)]);
+
+    let mut replayer = Replayer::new(log);
+    let replayed = replayer
+        .run(start(CODE, vec![MontyObject::Int(1)]), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(replayed.into_complete(), Some(expected));
+    assert!(replayer.is_finished());
+}
+
+#[test]
+fn replay_reproduces_exceptions() {
+    let code = r"
+try:
+    fetch(x)
+except ValueError as e:
+    result = str(e)
+result
+";
+    let mut log = CallLog::new();
+    record(start(code, vec![MontyObject::Int(-3)]), &mut log, usize::MAX).unwrap();
+
+    let replayed = Replayer::new(log)
+        .run(start(code, vec![MontyObject::Int(-3)]), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(
+        replayed.into_complete(),
+        Some(MontyObject::String("bad id -3".to_owned()))
+    );
+}
+
+#[test]
+fn replay_reproduces_async_resolution() {
+    let code = r"
+import asyncio
+
+async def main():
+    return await asyncio.gather(fetch_async(x), fetch_async(x + 1))
+
+await main()
+";
+    let mut log = CallLog::new();
+    let recorded = record(start(code, vec![MontyObject::Int(1)]), &mut log, usize::MAX).unwrap();
+    let expected = MontyObject::List(vec![MontyObject::Int(100), MontyObject::Int(200)]);
+    assert_eq!(recorded.into_complete(), Some(expected.clone()));
+    assert!(matches!(log.entries().last(), Some(CallLogEntry::Resolve(results)) if results.len() == 2));
+
+    let replayed = Replayer::new(log)
+        .run(start(code, vec![MontyObject::Int(1)]), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(replayed.into_complete(), Some(expected));
+}
+
+#[test]
+fn replay_stops_where_the_log_ends() {
+    // the host "crashed" after answering the first call
+    let mut log = CallLog::new();
+    record(start(CODE, vec![MontyObject::Int(1)]), &mut log, 1).unwrap();
+    assert_eq!(log.len(), 1);
+
+    let mut replayer = Replayer::new(log);
+    let progress = replayer
+        .run(start(CODE, vec![MontyObject::Int(1)]), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert!(replayer.is_finished());
+    assert_eq!(replayer.position(), 1);
+
+    // the run is at the call the host crashed on and can be continued live
+    let (value, state) = progress.into_emit().expect("expected Emit");
+    assert_eq!(value, MontyObject::Int(10));
+    let mut log = CallLog::new();
+    let progress = state.run(MontyObject::Int(5), &mut PrintWriter::Stdout).unwrap();
+    let result = record(progress, &mut log, usize::MAX).unwrap();
+    assert_eq!(
+        result.into_complete(),
+        Some(MontyObject::List(vec![
+            MontyObject::Int(10),
+            MontyObject::Int(5),
+            MontyObject::Int(150)
+        ]))
+    );
+}
+
+#[test]
+fn replay_detects_divergence() {
+    let mut log = CallLog::new();
+    record(start(CODE, vec![MontyObject::Int(1)]), &mut log, usize::MAX).unwrap();
+
+    let err = Replayer::new(log)
+        .run(start(CODE, vec![MontyObject::Int(2)]), &mut PrintWriter::Stdout)
+        .unwrap_err();
+    match err {
+        ReplayError::Diverged {
+            position,
+            expected,
+            found,
+        } => {
+            assert_eq!(position, 0);
+            assert_eq!(expected, "call fetch(1)");
+            assert_eq!(found, "call fetch(2)");
+        }
+        other @ ReplayError::Exception(_) => panic!("expected Diverged, got {other:?}"),
+    }
+}
+
+#[test]
+fn call_log_serde_roundtrip() {
+    let mut log = CallLog::new();
+    record(start(CODE, vec![MontyObject::Int(1)]), &mut log, usize::MAX).unwrap();
+
+    let json = serde_json::to_string(&log).unwrap();
+    let loaded: CallLog = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, log);
+
+    let replayed = Replayer::new(loaded)
+        .run(start(CODE, vec![MontyObject::Int(1)]), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert!(matches!(replayed, RunProgress::Complete(_)));
+}
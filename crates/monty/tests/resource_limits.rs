@@ -4,7 +4,9 @@
 /// allocation limits, time limits, and triggers garbage collection.
 use std::time::{Duration, Instant};
 
-use monty::{ExcType, LimitedTracker, MontyObject, MontyRun, PrintWriter, ResourceLimits};
+use monty::{
+    CancellableTracker, ExcType, LimitedTracker, MontyObject, MontyRun, NoLimitTracker, PrintWriter, ResourceLimits,
+};
 
 /// Test that GC properly collects dict cycles via the has_refs() check in allocate().
 ///
@@ -172,7 +174,7 @@ result
 
 #[test]
 fn time_limit_exceeded() {
-    // Create a long-running loop using for + range (while isn't implemented yet)
+    // Create a long-running loop using for + range
     // Use a very large range to ensure it runs long enough to hit the time limit
     let code = r"
 x = 0
@@ -196,6 +198,18 @@ x
     );
 }
 
+#[test]
+fn time_limit_stops_while_true() {
+    let code = "x = 0\nwhile True:\n    x += 1\nx";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let limits = ResourceLimits::new().max_duration(Duration::from_millis(50));
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .unwrap_err();
+    assert_eq!(exc.exc_type(), ExcType::TimeoutError);
+}
+
 #[test]
 fn time_limit_not_exceeded() {
     // Simple code that runs quickly
@@ -210,6 +224,101 @@ fn time_limit_not_exceeded() {
     assert!(result.is_ok(), "should not exceed time limit");
 }
 
+/// Test that an absolute deadline is enforced even without `max_duration`.
+#[test]
+fn deadline_exceeded() {
+    let code = r"
+x = 0
+for i in range(100000000):
+    x = x + 1
+x
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let limits = ResourceLimits::new().wall_deadline(Instant::now() + Duration::from_millis(50));
+    let result = ex.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout);
+
+    let exc = result.expect_err("should pass the deadline");
+    assert_eq!(exc.exc_type(), ExcType::TimeoutError);
+    assert!(
+        exc.message().is_some_and(|m| m.contains("time limit exceeded")),
+        "expected time limit error, got: {exc}"
+    );
+}
+
+/// Test that a deadline already in the past stops execution even with a generous `max_duration`.
+#[test]
+fn deadline_wins_over_max_duration() {
+    let code = r"
+x = 0
+for i in range(100000000):
+    x = x + 1
+x
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let limits = ResourceLimits::new()
+        .max_duration(Duration::from_secs(60))
+        .wall_deadline(Instant::now());
+    let start = Instant::now();
+    let result = ex.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout);
+
+    let exc = result.expect_err("should pass the deadline");
+    assert_eq!(exc.exc_type(), ExcType::TimeoutError);
+    assert!(
+        start.elapsed() < Duration::from_secs(5),
+        "deadline should stop execution promptly"
+    );
+}
+
+/// Test that cancelling from another thread stops a long-running script with `KeyboardInterrupt`.
+#[test]
+fn cancel_handle_interrupts_run() {
+    let code = r"
+x = 0
+for i in range(100000000):
+    try:
+        x = x + 1
+    except BaseException:
+        pass
+x
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let tracker = CancellableTracker::new(NoLimitTracker);
+    let handle = tracker.cancel_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+    });
+
+    let start = Instant::now();
+    let result = ex.run(vec![], tracker, &mut PrintWriter::Stdout);
+    canceller.join().unwrap();
+
+    // `except BaseException` must not swallow the cancellation
+    let exc = result.expect_err("run should be cancelled");
+    assert_eq!(exc.exc_type(), ExcType::KeyboardInterrupt);
+    assert_eq!(exc.message(), Some("execution cancelled"));
+    assert!(
+        start.elapsed() < Duration::from_secs(5),
+        "cancellation should be prompt"
+    );
+}
+
+/// Test that a handle cancelled before the run starts stops it immediately.
+#[test]
+fn cancel_handle_before_run() {
+    let ex = MontyRun::new("1 + 2".to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let tracker = CancellableTracker::new(LimitedTracker::new(ResourceLimits::new()));
+    tracker.cancel_handle().cancel();
+    let exc = ex
+        .run(vec![], tracker, &mut PrintWriter::Stdout)
+        .expect_err("run should be cancelled");
+    assert_eq!(exc.exc_type(), ExcType::KeyboardInterrupt);
+}
+
 /// Test that memory limits return an error.
 #[test]
 fn memory_limit_exceeded() {
@@ -237,6 +346,33 @@ result
     );
 }
 
+/// Test that `+=` growing a str or bytes in place is charged against the memory limit.
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn inplace_concat_memory_limit_exceeded() {
+    for code in [
+        "s = ''\nfor _ in range(200_000):\n    s += 'xxxxxxxxxx'\nlen(s)",
+        "s = 'x'\nfor _ in range(200_000):\n    s += str(len(s) % 10) * 10\nlen(s)",
+        "b = b''\nfor _ in range(200_000):\n    b += b'xxxxxxxxxx'\nlen(b)",
+    ] {
+        let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+        // The loops grow a single value to 2MB, well past the 1MB limit
+        let limits = ResourceLimits::new().max_memory(1024 * 1024);
+        let result = ex.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout);
+
+        let exc = result.expect_err("should exceed memory limit");
+        assert_eq!(exc.exc_type(), ExcType::MemoryError, "{code}");
+        assert!(
+            exc.message().is_some_and(|m| m.contains("memory limit exceeded")),
+            "expected memory limit error, got: {exc}"
+        );
+    }
+}
+
 #[test]
 fn combined_limits() {
     // Test multiple limits together
@@ -477,6 +613,38 @@ recurse(100)
     );
 }
 
+/// Test that a recursion depth limit error carries a traceback through every active frame.
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn recursion_depth_limit_traceback() {
+    let code = r"
+def recurse(n):
+    return recurse(n + 1)
+recurse(0)
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+
+    let limits = ResourceLimits::new().max_recursion_depth(Some(5));
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .unwrap_err();
+
+    assert_eq!(exc.exc_type(), ExcType::RecursionError);
+    let traceback = exc.traceback();
+    // `<module>` plus one frame for each of the 5 `recurse` calls allowed by the limit
+    assert_eq!(traceback.len(), 6, "unexpected traceback: {traceback:?}");
+    assert_eq!(traceback[0].frame_name, None);
+    assert!(
+        traceback[1..]
+            .iter()
+            .all(|frame| frame.frame_name.as_deref() == Some("recurse")),
+        "unexpected traceback: {traceback:?}"
+    );
+}
+
 #[test]
 fn recursion_depth_limit_not_exceeded() {
     let code = r"
@@ -562,6 +730,52 @@ fn pow_large_base_moderate_exp_rejected() {
     );
 }
 
+/// Test that `sum()` over a generator expression doesn't build the intermediate list,
+/// so it fits in a memory limit the equivalent list comprehension exceeds.
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn generator_sum_uses_flat_memory() {
+    let limits = ResourceLimits::new().max_memory(10_000);
+
+    let code = "sum(x * x for x in range(100_000))";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run(vec![], LimitedTracker::new(limits.clone()), &mut PrintWriter::Stdout);
+    assert_eq!(result.unwrap(), MontyObject::Int(333_328_333_350_000));
+
+    let code = "sum([x * x for x in range(100_000)])";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .unwrap_err();
+    assert_eq!(exc.exc_type(), ExcType::MemoryError);
+}
+
+/// Test that the room a comprehension reserves for its items counts against the memory
+/// limit, even when a filter leaves the result empty.
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn comprehension_presize_is_checked_against_memory_limit() {
+    let limits = ResourceLimits::new().max_memory(8_000);
+
+    let code = "[x for x in range(1_000) if x < 0]";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits.clone()), &mut PrintWriter::Stdout)
+        .unwrap_err();
+    assert_eq!(exc.exc_type(), ExcType::MemoryError);
+
+    let code = "[x for x in range(100) if x < 0]";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout);
+    assert_eq!(result.unwrap(), MontyObject::List(vec![]));
+}
+
 /// Test that the 4× safety multiplier for pow intermediate allocations catches
 /// cases where the final result fits but repeated-squaring intermediates don't.
 ///
@@ -1369,3 +1583,174 @@ repr(x)
 ";
     assert_repr_timeout(code, "set repr");
 }
+
+// === Per-collection size limits ===
+
+/// Runs `code` with the given limits and returns the error message, asserting it is a `MemoryError`.
+fn collection_limit_error(code: &str, limits: ResourceLimits) -> String {
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .expect_err("should exceed collection size limit");
+    assert_eq!(exc.exc_type(), ExcType::MemoryError);
+    exc.message().unwrap_or_default().to_owned()
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn max_str_len_rejects_repeat() {
+    let msg = collection_limit_error("'ab' * 1000", ResourceLimits::new().max_str_len(100));
+    assert_eq!(msg, "str length limit exceeded: 2000 > 100");
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn max_str_len_rejects_inplace_concat() {
+    let code = r"
+s = ''
+for i in range(100):
+    s += 'abc'
+";
+    let msg = collection_limit_error(code, ResourceLimits::new().max_str_len(50));
+    assert_eq!(msg, "str length limit exceeded: 51 > 50");
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn max_list_len_rejects_append() {
+    let code = r"
+x = []
+for i in range(100):
+    x.append(i)
+";
+    let msg = collection_limit_error(code, ResourceLimits::new().max_list_len(10));
+    assert_eq!(msg, "list length limit exceeded: 11 > 10");
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn max_list_len_rejects_comprehension_and_extend() {
+    let limits = || ResourceLimits::new().max_list_len(10);
+    collection_limit_error("[i for i in range(20)]", limits());
+    let msg = collection_limit_error("x = [1, 2]\nx.extend(range(20))", limits());
+    assert_eq!(msg, "list length limit exceeded: 22 > 10");
+}
+
+#[test]
+#[cfg_attr(
+    feature = "ref-count-panic",
+    ignore = "resource exhaustion doesn't guarantee heap state consistency"
+)]
+fn max_dict_entries_rejects_setitem() {
+    let code = r"
+d = {}
+for i in range(100):
+    d[i] = i
+";
+    let msg = collection_limit_error(code, ResourceLimits::new().max_dict_entries(5));
+    assert_eq!(msg, "dict length limit exceeded: 6 > 5");
+}
+
+#[test]
+fn collection_limits_allow_updates_within_limit() {
+    let code = r"
+d = {'a': 1}
+for i in range(100):
+    d['a'] = i
+x = [0] * 10
+s = 'x' * 10
+(d['a'], len(x), len(s))
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let limits = ResourceLimits::new()
+        .max_dict_entries(1)
+        .max_list_len(10)
+        .max_str_len(10);
+    let result = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(
+        result,
+        MontyObject::Tuple(vec![MontyObject::Int(99), MontyObject::Int(10), MontyObject::Int(10)])
+    );
+}
+
+// === resources() builtin ===
+
+#[test]
+fn resources_reports_remaining_budget() {
+    let code = r"
+before = resources()
+data = [str(i) for i in range(100)]
+after = resources()
+(
+    before['memory'] > after['memory'],
+    before['allocations'] > after['allocations'],
+    0 < after['time'] <= 10.0,
+    after['instructions'],
+)
+";
+    let ex = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let limits = ResourceLimits::new()
+        .max_memory(1_000_000)
+        .max_allocations(10_000)
+        .max_duration(Duration::from_secs(10));
+    let result = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .unwrap();
+    assert_eq!(
+        result,
+        MontyObject::Tuple(vec![
+            MontyObject::Bool(true),
+            MontyObject::Bool(true),
+            MontyObject::Bool(true),
+            MontyObject::None,
+        ])
+    );
+}
+
+#[test]
+fn resources_unlimited_values_are_none() {
+    let ex = MontyRun::new("resources()".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run(vec![], NoLimitTracker, &mut PrintWriter::Stdout).unwrap();
+    let MontyObject::Dict(pairs) = result else {
+        panic!("expected dict, got {result:?}");
+    };
+    let values: Vec<_> = pairs.into_iter().map(|(_, v)| v).collect();
+    assert_eq!(values, vec![MontyObject::None; 4]);
+}
+
+#[test]
+fn resources_reports_remaining_fuel() {
+    let ex = MontyRun::new("resources()['instructions']".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let progress = ex
+        .start_fuel(vec![], NoLimitTracker, 1_000, &mut PrintWriter::Stdout)
+        .unwrap();
+    let Some(MontyObject::Int(fuel)) = progress.into_complete() else {
+        panic!("expected int result");
+    };
+    assert!(fuel > 990 && fuel < 1_000, "unexpected remaining fuel {fuel}");
+}
+
+#[test]
+fn resources_hidden_raises_name_error() {
+    let ex = MontyRun::new("resources()".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let limits = ResourceLimits::new().hide_resources(true);
+    let exc = ex
+        .run(vec![], LimitedTracker::new(limits), &mut PrintWriter::Stdout)
+        .expect_err("resources() should be hidden");
+    assert_eq!(exc.exc_type(), ExcType::NameError);
+    assert_eq!(exc.message(), Some("name 'resources' is not defined"));
+}
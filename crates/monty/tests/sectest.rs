@@ -0,0 +1,80 @@
+//! Tests for the bundled security regression suite.
+//!
+//! These run every adversarial case against a typical sandbox configuration, and check
+//! that cases needing a limit are reported rather than run when the limit is missing.
+
+use std::time::Duration;
+
+use monty::{
+    ResourceLimits,
+    sectest::{self, Category, Outcome},
+};
+
+fn sandbox_limits() -> ResourceLimits {
+    ResourceLimits::new()
+        .max_memory(32 * 1024 * 1024)
+        .max_duration(Duration::from_millis(250))
+        .max_str_len(1_000_000)
+        .max_list_len(1_000_000)
+        .max_dict_entries(1_000_000)
+}
+
+#[test]
+fn suite_holds_with_sandbox_limits() {
+    let report = sectest::run_suite(&sandbox_limits());
+    assert!(report.passed(), "security suite failed:\n{report}");
+    assert_eq!(report.results.len(), sectest::cases().len());
+}
+
+#[test]
+fn every_category_has_cases() {
+    for category in [
+        Category::ResourceBomb,
+        Category::DeepNesting,
+        Category::SnapshotCorruption,
+        Category::RefcountAbuse,
+    ] {
+        assert!(
+            sectest::cases().iter().any(|case| case.category == category),
+            "no cases for {category}"
+        );
+    }
+}
+
+#[test]
+fn case_names_are_unique() {
+    let cases = sectest::cases();
+    for (i, case) in cases.iter().enumerate() {
+        assert!(
+            cases[i + 1..].iter().all(|other| other.name != case.name),
+            "duplicate case name {}",
+            case.name
+        );
+    }
+}
+
+#[test]
+fn inconsistent_snapshots_are_rejected_on_load() {
+    for name in ["snapshot_dangling_heap_id", "snapshot_missing_function"] {
+        let case = sectest::cases().iter().find(|case| case.name == name).unwrap();
+        match sectest::run_case(case, &sandbox_limits()) {
+            Outcome::Held(detail) => assert!(detail.starts_with("rejected: corrupt snapshot: "), "{name}: {detail}"),
+            outcome => panic!("{name}: {outcome}"),
+        }
+    }
+}
+
+#[test]
+fn missing_limits_are_reported_unprotected() {
+    let limits = ResourceLimits::default();
+    let infinite_loop = sectest::cases()
+        .iter()
+        .find(|case| case.name == "infinite_loop")
+        .unwrap();
+    assert_eq!(sectest::run_case(infinite_loop, &limits), Outcome::Unprotected);
+
+    let report = sectest::run_suite(&limits);
+    assert!(!report.passed());
+    assert!(report.failures().all(|result| result.outcome == Outcome::Unprotected));
+    assert!(report.failures().all(|result| !result.case.requires_any.is_empty()));
+}
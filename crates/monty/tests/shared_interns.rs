@@ -0,0 +1,116 @@
+//! Tests for sharing interned constants between compiled programs.
+
+use monty::{CompileOptions, MontyObject, MontyRun, SharedInterns};
+
+const FIRST: &str = "a = 'alpha literal'\nb = 'beta literal'\n(a, b)";
+const SECOND: &str = "a = 'alpha literal'\nc = 'gamma literal'\n(a, c)";
+
+fn compile(code: &str, shared: &SharedInterns) -> MontyRun {
+    let options = CompileOptions::new().share_interns(shared.clone());
+    MontyRun::new_with_options(code.to_owned(), "tenant.py", vec![], vec![], options).unwrap()
+}
+
+fn strings(items: &[&str]) -> MontyObject {
+    MontyObject::Tuple(items.iter().map(|&s| MontyObject::from(s)).collect())
+}
+
+#[test]
+fn programs_keep_one_copy_of_common_constants() {
+    let shared = SharedInterns::new();
+    assert!(shared.is_empty());
+
+    let first = compile(FIRST, &shared);
+    let after_first = shared.len();
+    assert!(after_first >= 2, "both literals should be in the table");
+
+    let again = compile(FIRST, &shared);
+    assert_eq!(shared.len(), after_first, "identical code adds nothing");
+
+    let second = compile(SECOND, &shared);
+    assert_eq!(shared.len(), after_first + 1, "only 'gamma literal' is new");
+
+    assert_eq!(
+        first.run_no_limits(vec![]).unwrap(),
+        strings(&["alpha literal", "beta literal"])
+    );
+    assert_eq!(
+        again.run_no_limits(vec![]).unwrap(),
+        strings(&["alpha literal", "beta literal"])
+    );
+    assert_eq!(
+        second.run_no_limits(vec![]).unwrap(),
+        strings(&["alpha literal", "gamma literal"])
+    );
+}
+
+#[test]
+fn bytes_and_long_integer_literals_are_shared() {
+    let code = "(b'payload bytes', 123456789012345678901234567890)";
+    let shared = SharedInterns::new();
+    let first = compile(code, &shared);
+    let after_first = shared.len();
+    let second = compile(code, &shared);
+    assert_eq!(shared.len(), after_first);
+
+    for runner in [first, second] {
+        let MontyObject::Tuple(items) = runner.run_no_limits(vec![]).unwrap() else {
+            panic!("expected a tuple");
+        };
+        assert_eq!(items[0], MontyObject::Bytes(b"payload bytes".to_vec()));
+        assert_eq!(items[1].to_string(), "123456789012345678901234567890");
+    }
+}
+
+#[test]
+fn pruning_drops_constants_of_dropped_programs() {
+    let shared = SharedInterns::new();
+    let first = compile(FIRST, &shared);
+    let after_first = shared.len();
+    let second = compile(SECOND, &shared);
+    assert_eq!(shared.len(), after_first + 1);
+
+    // constants stay in the table until it is pruned
+    drop(second);
+    assert_eq!(shared.len(), after_first + 1);
+    shared.prune();
+    assert_eq!(shared.len(), after_first);
+
+    drop(first);
+    shared.prune();
+    assert!(shared.is_empty());
+}
+
+#[test]
+fn loaded_code_shares_constants() {
+    let bytes = MontyRun::new(FIRST.to_owned(), "tenant.py", vec![], vec![])
+        .unwrap()
+        .dump_code()
+        .unwrap();
+    let shared = SharedInterns::new();
+    let compiled = compile(FIRST, &shared);
+    let after_compile = shared.len();
+
+    let loaded = MontyRun::load_code_shared(&bytes, &shared).unwrap();
+    assert_eq!(shared.len(), after_compile);
+    assert_eq!(
+        loaded.run_no_limits(vec![]).unwrap(),
+        compiled.run_no_limits(vec![]).unwrap()
+    );
+}
+
+#[test]
+fn tables_can_be_shared_across_threads() {
+    let shared = SharedInterns::new();
+    let results: Vec<MontyObject> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| compile(SECOND, &shared).run_no_limits(vec![]).unwrap()))
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    for result in results {
+        assert_eq!(result, strings(&["alpha literal", "gamma literal"]));
+    }
+    let after_threads = shared.len();
+    let _again = compile(SECOND, &shared);
+    assert_eq!(shared.len(), after_threads);
+}
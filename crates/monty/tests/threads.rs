@@ -0,0 +1,83 @@
+//! Tests for sharing one compiled program between runs on many threads.
+
+use std::{sync::Arc, thread};
+
+use monty::{CompiledProgram, MontyObject, MontyRun, NoLimitTracker, PrintWriter};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn compiled_programs_are_send_and_sync() {
+    assert_send_sync::<MontyRun>();
+    assert_send_sync::<CompiledProgram>();
+}
+
+#[test]
+fn clones_share_the_compiled_program() {
+    let runner = MontyRun::new("x + 1".to_owned(), "test.py", vec!["x".to_owned()], vec![]).unwrap();
+    let clone = runner.clone();
+    assert!(Arc::ptr_eq(runner.program(), clone.program()));
+
+    let from_program = MontyRun::from(Arc::clone(runner.program()));
+    let result = from_program
+        .run(vec![MontyObject::Int(1)], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    assert_eq!(result, MontyObject::Int(2));
+}
+
+#[test]
+fn one_program_runs_concurrently() {
+    let code = "items = [n * factor for n in range(100)]\nsum(items)";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec!["factor".to_owned()], vec![]).unwrap();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|factor| {
+                let runner = &runner;
+                scope.spawn(move || {
+                    runner
+                        .run(
+                            vec![MontyObject::Int(factor)],
+                            NoLimitTracker,
+                            &mut PrintWriter::Disabled,
+                        )
+                        .unwrap()
+                })
+            })
+            .collect();
+        for (factor, handle) in (0..8).zip(handles) {
+            assert_eq!(handle.join().unwrap(), MontyObject::Int(4950 * factor));
+        }
+    });
+}
+
+#[test]
+fn iterative_runs_on_other_threads() {
+    let runner = MontyRun::new(
+        "fetch(n) * 2".to_owned(),
+        "test.py",
+        vec!["n".to_owned()],
+        vec!["fetch".to_owned()],
+    )
+    .unwrap();
+
+    let handles: Vec<_> = (0..4)
+        .map(|n| {
+            let runner = runner.clone();
+            thread::spawn(move || {
+                let progress = runner
+                    .start(vec![MontyObject::Int(n)], NoLimitTracker, &mut PrintWriter::Disabled)
+                    .unwrap();
+                let (_, args, _, _, _, state) = progress.into_function_call().unwrap();
+                state
+                    .run(args.into_iter().next().unwrap(), &mut PrintWriter::Disabled)
+                    .unwrap()
+                    .into_complete()
+                    .unwrap()
+            })
+        })
+        .collect();
+    for (n, handle) in (0..4).zip(handles) {
+        assert_eq!(handle.join().unwrap(), MontyObject::Int(n * 2));
+    }
+}
@@ -0,0 +1,112 @@
+//! Tests for the `time` module: host clocks and `time.sleep()` suspension.
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use monty::{Clock, ClockTracker, ExcType, MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress};
+
+fn runner(code: &str) -> MontyRun {
+    MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap()
+}
+
+/// A virtual clock the host advances by hand, shared with the test through an `Rc`.
+#[derive(Debug, Default, Clone)]
+struct VirtualClock(Rc<Cell<f64>>);
+
+impl VirtualClock {
+    fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration.as_secs_f64());
+    }
+}
+
+impl Clock for VirtualClock {
+    fn time(&self) -> f64 {
+        1_000_000.0 + self.0.get()
+    }
+
+    fn monotonic(&self) -> f64 {
+        self.0.get()
+    }
+}
+
+#[test]
+fn sleep_yields_requested_duration() {
+    let code = r"
+import time
+time.sleep(1.5)
+time.sleep(2)
+'done'
+";
+    let progress = runner(code)
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    let (duration, state) = progress.into_sleep().expect("expected a sleep");
+    assert_eq!(duration, Duration::from_millis(1500));
+
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+    let (duration, state) = progress.into_sleep().expect("expected a second sleep");
+    assert_eq!(duration, Duration::from_secs(2));
+
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::String("done".to_owned())));
+}
+
+#[test]
+fn sleep_returns_none() {
+    let progress = runner("import time\ntime.sleep(0)")
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    let (duration, state) = progress.into_sleep().unwrap();
+    assert_eq!(duration, Duration::ZERO);
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(progress.into_complete(), Some(MontyObject::None));
+}
+
+#[test]
+fn host_clock_is_advanced_across_sleeps() {
+    let code = r"
+import time
+start = time.monotonic()
+wall = time.time()
+time.sleep(3)
+(time.monotonic() - start, time.time() - wall)
+";
+    let clock = VirtualClock::default();
+    let tracker = ClockTracker::new(NoLimitTracker, clock.clone());
+    let mut progress = runner(code).start(vec![], tracker, &mut PrintWriter::Disabled).unwrap();
+    let value = loop {
+        match progress {
+            RunProgress::Sleep { duration, state } => {
+                clock.advance(duration);
+                progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+            }
+            RunProgress::Complete(value) => break value,
+            other => panic!("unexpected progress: {other:?}"),
+        }
+    };
+    assert_eq!(
+        value,
+        MontyObject::Tuple(vec![MontyObject::Float(3.0), MontyObject::Float(3.0)])
+    );
+}
+
+#[test]
+fn sleep_survives_dump_and_load() {
+    let progress = runner("import time\ntime.sleep(0.25)\n'resumed'")
+        .start(vec![], NoLimitTracker, &mut PrintWriter::Disabled)
+        .unwrap();
+    let bytes = progress.dump().unwrap();
+    let loaded: RunProgress<NoLimitTracker> = RunProgress::load(&bytes).unwrap();
+    let (duration, state) = loaded.into_sleep().unwrap();
+    assert_eq!(duration, Duration::from_millis(250));
+    let progress = state.run(MontyObject::None, &mut PrintWriter::Disabled).unwrap();
+    assert_eq!(
+        progress.into_complete(),
+        Some(MontyObject::String("resumed".to_owned()))
+    );
+}
+
+#[test]
+fn sleep_is_not_implemented_with_standard_execution() {
+    let err = runner("import time\ntime.sleep(1)").run_no_limits(vec![]).unwrap_err();
+    assert_eq!(err.exc_type(), ExcType::NotImplementedError);
+}
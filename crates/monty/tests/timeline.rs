@@ -0,0 +1,97 @@
+//! Tests for the span timeline recorded by `TimelineTracker`.
+
+use monty::{MontyObject, MontyRun, NoLimitTracker, PrintWriter, RunProgress, SpanKind, TimelineTracker};
+
+#[test]
+fn timeline_records_calls_and_external_waits() {
+    let code = r"
+def inner(x):
+    return x + 1
+
+def outer(x):
+    return inner(x) * 2
+
+y = ext(outer(1))
+outer(y)
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec!["ext".to_owned()]).unwrap();
+    let tracker = TimelineTracker::new(NoLimitTracker);
+    let handle = tracker.timeline_handle();
+
+    let progress = runner.start(vec![], tracker, &mut PrintWriter::Stdout).unwrap();
+    let RunProgress::FunctionCall { args, state, .. } = progress else {
+        panic!("expected FunctionCall");
+    };
+    assert_eq!(args, vec![MontyObject::Int(4)]);
+    let result = state.run(MontyObject::Int(10), &mut PrintWriter::Stdout).unwrap();
+    assert_eq!(result.into_complete(), Some(MontyObject::Int(22)));
+
+    let timeline = handle.lock();
+    assert_eq!(timeline.open_count(), 0);
+    let spans: Vec<_> = timeline
+        .spans()
+        .iter()
+        .filter(|span| span.kind != SpanKind::GarbageCollection)
+        .collect();
+    let names: Vec<&str> = spans.iter().map(|span| span.name.as_str()).collect();
+    assert_eq!(names, ["inner", "outer", "ext", "inner", "outer"]);
+
+    assert_eq!(spans[2].kind, SpanKind::ExternalWait);
+    assert_eq!(spans[2].parent_id, None);
+    // each `inner` call is nested inside the `outer` call that made it
+    assert_eq!(spans[0].parent_id, Some(spans[1].id));
+    assert_eq!(spans[3].parent_id, Some(spans[4].id));
+    assert_eq!(spans[1].parent_id, None);
+}
+
+#[test]
+fn timeline_span_cap_drops_excess_spans() {
+    let code = r"
+def f():
+    return 1
+
+for _ in range(10):
+    f()
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let tracker = TimelineTracker::with_max_spans(NoLimitTracker, 3);
+    let handle = tracker.timeline_handle();
+
+    runner.run(vec![], tracker, &mut PrintWriter::Stdout).unwrap();
+
+    let timeline = handle.lock();
+    assert_eq!(timeline.spans().len(), 3);
+    assert_eq!(timeline.dropped(), 7);
+}
+
+#[test]
+fn timeline_closes_spans_unwound_by_exceptions() {
+    let code = r"
+def fail():
+    raise ValueError('boom')
+
+def call():
+    try:
+        fail()
+    except ValueError:
+        pass
+    return 1
+
+call()
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let tracker = TimelineTracker::new(NoLimitTracker);
+    let handle = tracker.timeline_handle();
+
+    runner.run(vec![], tracker, &mut PrintWriter::Stdout).unwrap();
+
+    let timeline = handle.lock();
+    assert_eq!(timeline.open_count(), 0);
+    let names: Vec<&str> = timeline
+        .spans()
+        .iter()
+        .filter(|span| span.kind == SpanKind::FunctionCall)
+        .map(|span| span.name.as_str())
+        .collect();
+    assert_eq!(names, ["fail", "call"]);
+}
@@ -0,0 +1,171 @@
+//! Tests for trace events reported to a `TraceHook` through `TracingTracker`.
+
+use std::sync::{Arc, Mutex};
+
+use monty::{
+    CompileOptions, ExcType, MontyObject, MontyRun, NoLimitTracker, PrintWriter, TraceHook, TracePosition,
+    TracingTracker,
+};
+
+/// Records every event as a string, shared with the test through an `Arc`.
+#[derive(Debug, Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn push(&self, event: String) {
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl TraceHook for Recorder {
+    fn on_line(&mut self, position: &TracePosition<'_>) {
+        self.push(format!("line {}:{}", position.function, position.line));
+    }
+
+    fn on_call(&mut self, position: &TracePosition<'_>) {
+        self.push(format!("call {} depth={}", position.function, position.depth));
+    }
+
+    fn on_return(&mut self, position: &TracePosition<'_>) {
+        self.push(format!("return {} depth={}", position.function, position.depth));
+    }
+
+    fn on_exception(&mut self, position: &TracePosition<'_>, exc_type: ExcType, message: Option<&str>) {
+        self.push(format!(
+            "exception {}:{} {exc_type}: {}",
+            position.function,
+            position.line,
+            message.unwrap_or_default()
+        ));
+    }
+}
+
+fn run_traced(code: &str) -> (MontyObject, Vec<String>) {
+    run_traced_with(code, CompileOptions::new())
+}
+
+fn run_traced_with(code: &str, options: CompileOptions) -> (MontyObject, Vec<String>) {
+    let runner = MontyRun::new_with_options(code.to_owned(), "test.py", vec![], vec![], options).unwrap();
+    let recorder = Recorder::default();
+    let tracker = TracingTracker::new(NoLimitTracker, recorder.clone());
+    let result = runner.run(vec![], tracker, &mut PrintWriter::Stdout).unwrap();
+    (result, recorder.events())
+}
+
+#[test]
+fn trace_reports_lines_calls_and_returns() {
+    let code = r"
+def add(a, b):
+    return a + b
+
+x = add(1, 2)
+x + 1
+";
+    let (result, events) = run_traced(code);
+    assert_eq!(result, MontyObject::Int(4));
+    assert_eq!(
+        events,
+        [
+            "line <module>:2",
+            "line <module>:5",
+            "call add depth=2",
+            "line add:3",
+            "return add depth=2",
+            "line <module>:6",
+        ]
+    );
+}
+
+#[test]
+fn trace_reports_loop_lines_on_every_iteration() {
+    let code = r"
+total = 0
+for i in range(3):
+    total += i
+total
+";
+    let (result, events) = run_traced(code);
+    assert_eq!(result, MontyObject::Int(3));
+    assert_eq!(events.iter().filter(|event| *event == "line <module>:4").count(), 3);
+    assert_eq!(events.first().map(String::as_str), Some("line <module>:2"));
+    assert_eq!(events.last().map(String::as_str), Some("line <module>:5"));
+}
+
+#[test]
+fn trace_reports_exceptions_and_unwound_frames() {
+    let code = r"
+def fail():
+    raise ValueError('boom')
+
+try:
+    fail()
+except ValueError:
+    caught = True
+caught
+";
+    let (result, events) = run_traced(code);
+    assert_eq!(result, MontyObject::Bool(true));
+    let raised = events
+        .iter()
+        .position(|event| event == "exception fail:3 ValueError: boom")
+        .expect("exception event");
+    assert_eq!(events[raised + 1], "return fail depth=2");
+    assert!(events[raised..].contains(&"line <module>:8".to_owned()));
+}
+
+#[test]
+fn trace_pairs_nested_calls_and_returns() {
+    let code = r"
+def inner(x):
+    return x + 1
+
+def outer(x):
+    return inner(x) * 2
+
+outer(1)
+";
+    let (result, events) = run_traced(code);
+    assert_eq!(result, MontyObject::Int(4));
+    let calls: Vec<&str> = events
+        .iter()
+        .map(String::as_str)
+        .filter(|event| event.starts_with("call") || event.starts_with("return"))
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            "call outer depth=2",
+            "call inner depth=3",
+            "return inner depth=3",
+            "return outer depth=2",
+        ]
+    );
+    // returning into `outer` mid-line doesn't report its line again
+    assert_eq!(events.iter().filter(|event| *event == "line outer:6").count(), 1);
+}
+
+#[test]
+fn trace_reports_the_same_lines_with_and_without_optimization() {
+    let code = r"
+def total(items, scale):
+    acc = 0
+    for item in items:
+        acc = acc + (
+            item
+            / scale
+        )
+    return acc
+
+total([1, 2], 2)
+";
+    let (result, events) = run_traced_with(code, CompileOptions::new());
+    let (_, unoptimized_events) = run_traced_with(code, CompileOptions::new().optimize(false));
+    assert_eq!(result, MontyObject::Float(1.5));
+    assert_eq!(events, unoptimized_events);
+    // each line of the multi-line expression is reported as it is reached
+    assert!(events.contains(&"line total:7".to_owned()));
+}
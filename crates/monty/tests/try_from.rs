@@ -1,4 +1,4 @@
-use monty::MontyRun;
+use monty::{ConversionErrorKind, MontyObject, MontyRun};
 
 /// Tests for successful TryFrom conversions from Python values to Rust types.
 ///
@@ -165,3 +165,91 @@ fn try_from_err_none_to_bool() {
     let err = TryInto::<bool>::try_into(&result).expect_err("conversion should fail");
     assert_eq!(err.to_string(), "expected bool, got NoneType");
 }
+
+/// Tests for checked integer conversions across the numeric tower.
+///
+/// Values that don't fit in the target Rust type must return an overflow error rather
+/// than being silently truncated.
+
+#[test]
+fn try_from_ok_int_to_u32() {
+    let ex = MontyRun::new("4_000_000_000".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let value: u32 = (&result).try_into().expect("conversion should succeed");
+    assert_eq!(value, 4_000_000_000);
+}
+
+#[test]
+fn try_from_err_int_overflow_u8() {
+    let ex = MontyRun::new("256".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let err = TryInto::<u8>::try_into(&result).expect_err("conversion should fail");
+    assert_eq!(err.kind, ConversionErrorKind::Overflow);
+    assert_eq!(err.to_string(), "int value out of range for u8");
+}
+
+#[test]
+fn try_from_err_negative_to_u64() {
+    let ex = MontyRun::new("-1".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let err = TryInto::<u64>::try_into(&result).expect_err("conversion should fail");
+    assert_eq!(err.to_string(), "int value out of range for u64");
+}
+
+#[test]
+fn try_from_err_bigint_to_i64() {
+    let ex = MontyRun::new("2 ** 100".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let err = TryInto::<i64>::try_into(&result).expect_err("conversion should fail");
+    assert_eq!(err.to_string(), "int value out of range for i64");
+}
+
+#[test]
+fn try_from_ok_bigint_to_u128() {
+    let ex = MontyRun::new("2 ** 100".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let value: u128 = (&result).try_into().expect("conversion should succeed");
+    assert_eq!(value, 1 << 100);
+}
+
+#[test]
+fn try_from_err_str_to_u16_is_wrong_type() {
+    let ex = MontyRun::new("'1'".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    let err = TryInto::<u16>::try_into(&result).expect_err("conversion should fail");
+    assert_eq!(err.kind, ConversionErrorKind::WrongType);
+    assert_eq!(err.to_string(), "expected int, got str");
+}
+
+#[test]
+fn from_wide_int_round_trips() {
+    assert_eq!(MontyObject::from(7_u64), MontyObject::Int(7));
+    let big = MontyObject::from(u64::MAX);
+    assert!(matches!(big, MontyObject::BigInt(_)));
+    assert_eq!(TryInto::<u64>::try_into(&big).unwrap(), u64::MAX);
+    assert_eq!(MontyObject::from(-3_i8), MontyObject::Int(-3));
+}
+
+#[test]
+#[expect(clippy::float_cmp)]
+fn as_f64_lossy_converts_ints() {
+    let ex = MontyRun::new("2 ** 100".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    assert_eq!(result.as_f64_lossy().unwrap(), 2f64.powi(100));
+
+    let ex = MontyRun::new("-(10 ** 400)".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    assert_eq!(result.as_f64_lossy().unwrap(), f64::NEG_INFINITY);
+
+    let err = MontyObject::None.as_f64_lossy().expect_err("conversion should fail");
+    assert_eq!(err.to_string(), "expected float, got NoneType");
+}
+
+#[test]
+fn to_bigint_accepts_int_and_bigint() {
+    assert_eq!(MontyObject::Int(5).to_bigint().unwrap().to_string(), "5");
+    let ex = MontyRun::new("2 ** 70".to_owned(), "test.py", vec![], vec![]).unwrap();
+    let result = ex.run_no_limits(vec![]).unwrap();
+    assert_eq!(result.to_bigint().unwrap().to_string(), "1180591620717411303424");
+    assert!(MontyObject::Float(1.0).to_bigint().is_err());
+}
@@ -0,0 +1,103 @@
+//! Tests for compile-time and runtime warnings reported to a `WarningSink`.
+
+use std::sync::{Arc, Mutex};
+
+use monty::{MontyRun, MontyWarning, NoLimitTracker, PrintWriter, WarningCategory, WarningSink, WarningTracker};
+
+/// Records every warning, shared with the test through an `Arc`.
+#[derive(Debug, Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<MontyWarning>>>);
+
+impl WarningSink for Recorder {
+    fn warn(&mut self, warning: MontyWarning) {
+        self.0.lock().unwrap().push(warning);
+    }
+}
+
+fn run_warnings(code: &str) -> Vec<String> {
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let recorder = Recorder::default();
+    let tracker = WarningTracker::new(NoLimitTracker, recorder.clone());
+    runner.run(vec![], tracker, &mut PrintWriter::Disabled).unwrap();
+    let warnings = recorder.0.lock().unwrap();
+    warnings.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn compile_time_warnings_are_found_by_new() {
+    let code = r"
+def f(x):
+    assert (x, 'x must be set')
+    return x is not 'a'
+    print(x)
+
+f(1)
+";
+    let runner = MontyRun::new(code.to_owned(), "test.py", vec![], vec![]).unwrap();
+    let warnings: Vec<(WarningCategory, u16, &str)> = runner
+        .warnings()
+        .iter()
+        .map(|warning| (warning.category, warning.start.line, warning.message.as_str()))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            (
+                WarningCategory::SyntaxWarning,
+                3,
+                "assertion is always true, perhaps remove parentheses?"
+            ),
+            (
+                WarningCategory::SyntaxWarning,
+                4,
+                "\"is not\" with 'str' literal. Did you mean \"!=\"?"
+            ),
+            (WarningCategory::SyntaxWarning, 5, "code is unreachable"),
+        ]
+    );
+}
+
+#[test]
+fn compile_time_warnings_are_reported_to_the_sink() {
+    assert_eq!(
+        run_warnings("x = 1\nx is 1"),
+        ["test.py:2: SyntaxWarning: \"is\" with 'int' literal. Did you mean \"==\"?"]
+    );
+}
+
+#[test]
+fn bytes_and_str_mixing_warns_at_runtime() {
+    let code = r"
+data = b'abc'
+if data == 'abc':
+    pass
+f'{data}'
+";
+    assert_eq!(
+        run_warnings(code),
+        [
+            "test.py:3: BytesWarning: Comparison between bytes and string",
+            "test.py:5: BytesWarning: str() on a bytes instance",
+        ]
+    );
+}
+
+#[test]
+fn discarded_coroutine_warns_at_runtime() {
+    let code = r"
+async def fetch():
+    return 1
+
+fetch()
+done = True
+";
+    assert_eq!(
+        run_warnings(code),
+        ["test.py:5: RuntimeWarning: coroutine 'fetch' was never awaited"]
+    );
+}
+
+#[test]
+fn code_without_problems_has_no_warnings() {
+    assert_eq!(run_warnings("x = b'a'\nx == b'a'\nf'{x!r}'"), Vec::<String>::new());
+}